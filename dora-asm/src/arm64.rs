@@ -178,7 +178,7 @@ impl AssemblerArm64 {
         self.buffer.bind_label(lbl);
     }
 
-    fn offset(&self, lbl: Label) -> Option<u32> {
+    pub fn offset(&self, lbl: Label) -> Option<u32> {
         self.buffer.offset(lbl)
     }
 
@@ -711,6 +711,14 @@ impl AssemblerArm64 {
         self.emit_u32(cls::csel(0, 1, 0, rm, cond, 0, rn, rd));
     }
 
+    pub fn dmb_ish(&mut self) {
+        self.emit_u32(cls::barrier(0b1011, 0b101));
+    }
+
+    pub fn dmb_ishld(&mut self) {
+        self.emit_u32(cls::barrier(0b1001, 0b101));
+    }
+
     pub fn eon_sh(&mut self, rd: Register, rn: Register, rm: Register, shift: Shift, imm6: u32) {
         self.emit_u32(cls::logical_shreg(1, 0b10, shift, 1, rm, imm6, rn, rd));
     }
@@ -887,6 +895,54 @@ impl AssemblerArm64 {
         ));
     }
 
+    pub fn fmax_d(&mut self, rd: NeonRegister, rn: NeonRegister, rm: NeonRegister) {
+        self.emit_u32(cls::fp_dataproc2(
+            0,
+            0,
+            FLOAT_TYPE_DOUBLE,
+            rm,
+            0b0100,
+            rn,
+            rd,
+        ));
+    }
+
+    pub fn fmax_s(&mut self, rd: NeonRegister, rn: NeonRegister, rm: NeonRegister) {
+        self.emit_u32(cls::fp_dataproc2(
+            0,
+            0,
+            FLOAT_TYPE_SINGLE,
+            rm,
+            0b0100,
+            rn,
+            rd,
+        ));
+    }
+
+    pub fn fmin_d(&mut self, rd: NeonRegister, rn: NeonRegister, rm: NeonRegister) {
+        self.emit_u32(cls::fp_dataproc2(
+            0,
+            0,
+            FLOAT_TYPE_DOUBLE,
+            rm,
+            0b0101,
+            rn,
+            rd,
+        ));
+    }
+
+    pub fn fmin_s(&mut self, rd: NeonRegister, rn: NeonRegister, rm: NeonRegister) {
+        self.emit_u32(cls::fp_dataproc2(
+            0,
+            0,
+            FLOAT_TYPE_SINGLE,
+            rm,
+            0b0101,
+            rn,
+            rd,
+        ));
+    }
+
     pub fn fmov_d(&mut self, rd: NeonRegister, rn: NeonRegister) {
         self.emit_u32(cls::fp_dataproc1(0, 0, FLOAT_TYPE_DOUBLE, 0b000000, rn, rd));
     }
@@ -2977,6 +3033,13 @@ mod cls {
             | rd.encoding()
     }
 
+    pub(super) fn barrier(crm: u32, op2: u32) -> u32 {
+        assert!(fits_u4(crm));
+        assert!(op2 < 8);
+
+        0xD503301F | crm << 8 | op2 << 5
+    }
+
     pub(super) fn system(imm: u32) -> u32 {
         assert!(fits_u7(imm));
 
@@ -3415,6 +3478,14 @@ mod tests {
         assert_emit!(0x1e614083; fneg_d(F3, F4));
     }
 
+    #[test]
+    fn test_fmin_fmax() {
+        assert_emit!(0x1e224820; fmax_s(F0, F1, F2)); // fmax s0, s1, s2
+        assert_emit!(0x1e624820; fmax_d(F0, F1, F2)); // fmax d0, d1, d2
+        assert_emit!(0x1e225820; fmin_s(F0, F1, F2)); // fmin s0, s1, s2
+        assert_emit!(0x1e625820; fmin_d(F0, F1, F2)); // fmin d0, d1, d2
+    }
+
     #[test]
     fn test_fsqrt() {
         assert_emit!(0x1e21c020; fsqrt_s(F0, F1)); // fsqrt s0, s1
@@ -3435,6 +3506,12 @@ mod tests {
         assert_emit!(0xd503201f; nop());
     }
 
+    #[test]
+    fn test_dmb() {
+        assert_emit!(0xd5033bbf; dmb_ish()); // dmb ish
+        assert_emit!(0xd50339bf; dmb_ishld()); // dmb ishld
+    }
+
     #[test]
     fn test_div() {
         assert_emit!(0x1ac20820; udiv_w(R0, R1, R2));
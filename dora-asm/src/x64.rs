@@ -95,7 +95,7 @@ impl AssemblerX64 {
         self.buffer.bind_label(lbl);
     }
 
-    fn offset(&self, lbl: Label) -> Option<u32> {
+    pub fn offset(&self, lbl: Label) -> Option<u32> {
         self.buffer.offset(lbl)
     }
 
@@ -130,6 +130,11 @@ impl AssemblerX64 {
         self.buffer.emit_u8(value);
     }
 
+    pub fn emit_u16(&mut self, value: u16) {
+        self.buffer.emit_u8(value as u8);
+        self.buffer.emit_u8((value >> 8) as u8);
+    }
+
     pub fn emit_u32(&mut self, value: u32) {
         self.buffer.emit_u32(value);
     }
@@ -188,6 +193,35 @@ impl AssemblerX64 {
         self.emit_u8(0x90);
     }
 
+    pub fn mfence(&mut self) {
+        self.emit_u8(0x0F);
+        self.emit_u8(0xAE);
+        self.emit_u8(0xF0);
+    }
+
+    pub fn prefetcht0_a(&mut self, address: Address) {
+        self.emit_prefetch(0b001, address);
+    }
+
+    pub fn prefetcht1_a(&mut self, address: Address) {
+        self.emit_prefetch(0b010, address);
+    }
+
+    pub fn prefetcht2_a(&mut self, address: Address) {
+        self.emit_prefetch(0b011, address);
+    }
+
+    pub fn prefetchnta_a(&mut self, address: Address) {
+        self.emit_prefetch(0b000, address);
+    }
+
+    fn emit_prefetch(&mut self, hint: u8, address: Address) {
+        self.emit_rex32_address_optional(address);
+        self.emit_u8(0x0F);
+        self.emit_u8(0x18);
+        self.emit_address(hint, address);
+    }
+
     pub fn setcc_r(&mut self, condition: Condition, dest: Register) {
         if dest.needs_rex() || dest.low_bits() > 3 {
             self.emit_rex(false, false, false, dest.needs_rex());
@@ -218,6 +252,12 @@ impl AssemblerX64 {
         self.emit_address(dest.low_bits(), src);
     }
 
+    pub fn leal(&mut self, dest: Register, src: Address) {
+        self.emit_rex32_modrm_address(dest, src);
+        self.emit_u8(0x8D);
+        self.emit_address(dest.low_bits(), src);
+    }
+
     pub fn movq_rr(&mut self, dest: Register, src: Register) {
         self.emit_rex64_modrm(src, dest);
         self.emit_u8(0x89);
@@ -257,6 +297,22 @@ impl AssemblerX64 {
         self.emit_u8(src.uint8());
     }
 
+    pub fn movw_ar(&mut self, dest: Address, src: Register) {
+        self.emit_u8(0x66);
+        self.emit_rex32_modrm_address(src, dest);
+        self.emit_u8(0x89);
+        self.emit_address(src.low_bits(), dest);
+    }
+
+    pub fn movw_ai(&mut self, dest: Address, src: Immediate) {
+        assert!(src.is_int16() || src.is_uint16());
+        self.emit_u8(0x66);
+        self.emit_rex32_address_optional(dest);
+        self.emit_u8(0xc7);
+        self.emit_address(0b000, dest);
+        self.emit_u16(src.uint16());
+    }
+
     pub fn movq_ar(&mut self, dest: Address, src: Register) {
         self.emit_rex64_modrm_address(src, dest);
         self.emit_u8(0x89);
@@ -318,6 +374,13 @@ impl AssemblerX64 {
         self.emit_address(dest.low_bits(), src);
     }
 
+    pub fn movzxw_ra(&mut self, dest: Register, src: Address) {
+        self.emit_rex32_modrm_address(dest, src);
+        self.emit_u8(0x0f);
+        self.emit_u8(0xb7);
+        self.emit_address(dest.low_bits(), src);
+    }
+
     pub fn movsxbl_rr(&mut self, dest: Register, src: Register) {
         self.emit_rex32_byte_optional(dest, src);
         self.emit_u8(0x0f);
@@ -406,6 +469,22 @@ impl AssemblerX64 {
         self.emit_modrm_registers(src, dest);
     }
 
+    pub fn addq_ai(&mut self, dest: Address, imm: Immediate) {
+        assert!(imm.is_int32());
+
+        if imm.is_int8() {
+            self.emit_rex64_address(dest);
+            self.emit_u8(0x83);
+            self.emit_address(0b000, dest);
+            self.emit_u8(imm.int8() as u8);
+        } else {
+            self.emit_rex64_address(dest);
+            self.emit_u8(0x81);
+            self.emit_address(0b000, dest);
+            self.emit_u32(imm.int32() as u32);
+        }
+    }
+
     pub fn addl_ri(&mut self, dest: Register, imm: Immediate) {
         self.emit_alu32_imm(dest, imm, 0b000, 0x05);
     }
@@ -617,6 +696,52 @@ impl AssemblerX64 {
         self.emit_modrm_sse_registers(dest, src);
     }
 
+    pub fn minss_rr(&mut self, dest: XmmRegister, src: XmmRegister) {
+        self.emit_u8(0xf3);
+        self.emit_rex_sse_modrm_optional(dest, src);
+        self.emit_u8(0x0f);
+        self.emit_u8(0x5d);
+        self.emit_modrm_sse_registers(dest, src);
+    }
+
+    pub fn minsd_rr(&mut self, dest: XmmRegister, src: XmmRegister) {
+        self.emit_u8(0xf2);
+        self.emit_rex_sse_modrm_optional(dest, src);
+        self.emit_u8(0x0f);
+        self.emit_u8(0x5d);
+        self.emit_modrm_sse_registers(dest, src);
+    }
+
+    pub fn maxss_rr(&mut self, dest: XmmRegister, src: XmmRegister) {
+        self.emit_u8(0xf3);
+        self.emit_rex_sse_modrm_optional(dest, src);
+        self.emit_u8(0x0f);
+        self.emit_u8(0x5f);
+        self.emit_modrm_sse_registers(dest, src);
+    }
+
+    pub fn maxsd_rr(&mut self, dest: XmmRegister, src: XmmRegister) {
+        self.emit_u8(0xf2);
+        self.emit_rex_sse_modrm_optional(dest, src);
+        self.emit_u8(0x0f);
+        self.emit_u8(0x5f);
+        self.emit_modrm_sse_registers(dest, src);
+    }
+
+    pub fn vfmadd213ss_rrr(&mut self, dest: XmmRegister, src1: XmmRegister, src2: XmmRegister) {
+        self.emit_vex3_rxbm(dest.needs_rex(), false, src2.needs_rex(), 0b00010);
+        self.emit_vex3_wvlp(false, src1, false, 0b01);
+        self.emit_u8(0xa9);
+        self.emit_modrm_sse_registers(dest, src2);
+    }
+
+    pub fn vfmadd213sd_rrr(&mut self, dest: XmmRegister, src1: XmmRegister, src2: XmmRegister) {
+        self.emit_vex3_rxbm(dest.needs_rex(), false, src2.needs_rex(), 0b00010);
+        self.emit_vex3_wvlp(true, src1, false, 0b01);
+        self.emit_u8(0xa9);
+        self.emit_modrm_sse_registers(dest, src2);
+    }
+
     pub fn pxor_rr(&mut self, dest: XmmRegister, src: XmmRegister) {
         self.emit_u8(0x66);
         self.emit_rex_sse_modrm_optional(dest, src);
@@ -679,6 +804,20 @@ impl AssemblerX64 {
         self.emit_address(src.low_bits(), dest);
     }
 
+    pub fn andps_rr(&mut self, dest: XmmRegister, src: XmmRegister) {
+        self.emit_rex_sse_modrm_optional(dest, src);
+        self.emit_u8(0x0f);
+        self.emit_u8(0x54);
+        self.emit_modrm_sse_registers(dest, src);
+    }
+
+    pub fn orps_rr(&mut self, dest: XmmRegister, src: XmmRegister) {
+        self.emit_rex_sse_modrm_optional(dest, src);
+        self.emit_u8(0x0f);
+        self.emit_u8(0x56);
+        self.emit_modrm_sse_registers(dest, src);
+    }
+
     pub fn andps_ra(&mut self, dest: XmmRegister, src: Address) {
         self.emit_rex_sse_address_optional(dest, src);
         self.emit_u8(0x0f);
@@ -803,6 +942,29 @@ impl AssemblerX64 {
         self.emit_modrm_registers(dest, src);
     }
 
+    pub fn imull_ri(&mut self, dest: Register, src: Register, imm: Immediate) {
+        self.emit_rex32_optional(dest, src);
+        self.emit_imul_imm(dest, src, imm);
+    }
+
+    pub fn imulq_ri(&mut self, dest: Register, src: Register, imm: Immediate) {
+        self.emit_rex64_modrm(dest, src);
+        self.emit_imul_imm(dest, src, imm);
+    }
+
+    fn emit_imul_imm(&mut self, dest: Register, src: Register, imm: Immediate) {
+        if imm.is_int8() {
+            self.emit_u8(0x6B);
+            self.emit_modrm_registers(dest, src);
+            self.emit_u8(imm.int8() as u8);
+        } else {
+            assert!(imm.is_int32());
+            self.emit_u8(0x69);
+            self.emit_modrm_registers(dest, src);
+            self.emit_u32(imm.int32() as u32);
+        }
+    }
+
     pub fn mulss_rr(&mut self, dest: XmmRegister, src: XmmRegister) {
         self.emit_u8(0xf3);
         self.emit_rex_sse_modrm_optional(dest, src);
@@ -862,6 +1024,28 @@ impl AssemblerX64 {
         self.emit_u8(0x99);
     }
 
+    pub fn rep_stosb(&mut self) {
+        self.emit_u8(0xF3);
+        self.emit_u8(0xAA);
+    }
+
+    pub fn rep_stosw(&mut self) {
+        self.emit_u8(0x66);
+        self.emit_u8(0xF3);
+        self.emit_u8(0xAB);
+    }
+
+    pub fn rep_stosl(&mut self) {
+        self.emit_u8(0xF3);
+        self.emit_u8(0xAB);
+    }
+
+    pub fn rep_stosq(&mut self) {
+        self.emit_u8(0xF3);
+        self.emit_rex64();
+        self.emit_u8(0xAB);
+    }
+
     pub fn negl(&mut self, reg: Register) {
         self.emit_rex32_rm_optional(reg);
         self.emit_u8(0xF7);
@@ -1316,6 +1500,21 @@ impl AssemblerX64 {
         }
     }
 
+    // The R, X and B bits are stored inverted, `map` selects the opcode map
+    // (1 = 0F, 2 = 0F38, 3 = 0F3A).
+    fn emit_vex3_rxbm(&mut self, r: bool, x: bool, b: bool, map: u8) {
+        assert!(map < 32);
+        self.emit_u8(0xc4);
+        self.emit_u8((!r as u8) << 7 | (!x as u8) << 6 | (!b as u8) << 5 | map);
+    }
+
+    // `vvvv` is stored inverted, `pp` selects the implied prefix
+    // (0 = none, 1 = 66, 2 = F3, 3 = F2).
+    fn emit_vex3_wvlp(&mut self, w: bool, vvvv: XmmRegister, l: bool, pp: u8) {
+        assert!(pp < 4);
+        self.emit_u8((w as u8) << 7 | (!vvvv.value() & 0xf) << 3 | (l as u8) << 2 | pp);
+    }
+
     fn emit_rex32_rm_optional(&mut self, reg: Register) {
         if reg.needs_rex() {
             self.emit_rex(false, false, false, true);
@@ -1520,6 +1719,11 @@ impl Immediate {
         -limit <= self.0 && self.0 < limit
     }
 
+    pub fn is_int16(&self) -> bool {
+        let limit = 1i64 << 15;
+        -limit <= self.0 && self.0 < limit
+    }
+
     pub fn is_int32(&self) -> bool {
         let limit = 1i64 << 31;
         -limit <= self.0 && self.0 < limit
@@ -1529,6 +1733,10 @@ impl Immediate {
         0 <= self.0 && self.0 < 256
     }
 
+    pub fn is_uint16(&self) -> bool {
+        0 <= self.0 && self.0 < (1i64 << 16)
+    }
+
     pub fn is_uint32(&self) -> bool {
         let limit = 1i64 << 32;
         0 <= self.0 && self.0 < limit
@@ -1538,6 +1746,10 @@ impl Immediate {
         self.0 as u8
     }
 
+    pub fn uint16(&self) -> u16 {
+        self.0 as u16
+    }
+
     pub fn int8(&self) -> i8 {
         self.0 as i8
     }
@@ -1816,6 +2028,22 @@ mod tests {
         assert_emit!(0x90; nop);
     }
 
+    #[test]
+    fn test_mfence() {
+        assert_emit!(0x0f, 0xae, 0xf0; mfence);
+    }
+
+    #[test]
+    fn test_prefetch() {
+        assert_emit!(0x0f, 0x18, 0x08; prefetcht0_a(Address::offset(RAX, 0)));
+        assert_emit!(0x0f, 0x18, 0x10; prefetcht1_a(Address::offset(RAX, 0)));
+        assert_emit!(0x0f, 0x18, 0x18; prefetcht2_a(Address::offset(RAX, 0)));
+        assert_emit!(0x0f, 0x18, 0x00; prefetchnta_a(Address::offset(RAX, 0)));
+
+        assert_emit!(0x0f, 0x18, 0x48, 0x40; prefetcht0_a(Address::offset(RAX, 64)));
+        assert_emit!(0x41, 0x0f, 0x18, 0x08; prefetcht0_a(Address::offset(R8, 0)));
+    }
+
     #[test]
     fn test_emit_movq_rr() {
         assert_emit!(0x49, 0x89, 0xc7; movq_rr(R15, RAX));
@@ -1851,6 +2079,14 @@ mod tests {
         assert_emit!(0x48, 0x99; cqo);
     }
 
+    #[test]
+    fn test_rep_stos() {
+        assert_emit!(0xf3, 0xaa; rep_stosb);
+        assert_emit!(0x66, 0xf3, 0xab; rep_stosw);
+        assert_emit!(0xf3, 0xab; rep_stosl);
+        assert_emit!(0xf3, 0x48, 0xab; rep_stosq);
+    }
+
     #[test]
     fn test_setcc_r() {
         assert_emit!(0x0f, 0x94, 0xc0; setcc_r(Condition::Equal, RAX));
@@ -1955,6 +2191,19 @@ mod tests {
         assert_emit!(0x49, 0x0f, 0xaf, 0xcf; imulq_rr(RCX, R15));
     }
 
+    #[test]
+    fn test_imull_ri() {
+        assert_emit!(0x6b, 0xc3, 0x03; imull_ri(RAX, RBX, Immediate(3)));
+        assert_emit!(0x41, 0x69, 0xcf, 0xe8, 0x03, 0, 0; imull_ri(RCX, R15, Immediate(1000)));
+    }
+
+    #[test]
+    fn test_imulq_ri() {
+        assert_emit!(0x48, 0x6b, 0xc3, 0x03; imulq_ri(RAX, RBX, Immediate(3)));
+        assert_emit!(0x49, 0x69, 0xcf, 0xe8, 0x03, 0, 0; imulq_ri(RCX, R15, Immediate(1000)));
+        assert_emit!(0x4c, 0x6b, 0xc0, 0xff; imulq_ri(R8, RAX, Immediate(-1)));
+    }
+
     #[test]
     fn test_idivl_r() {
         assert_emit!(0xf7, 0xf8; idivl_r(RAX));
@@ -2207,6 +2456,15 @@ mod tests {
         assert_emit!(0x48, 0x8d, 0x40, 1; lea(RAX, Address::offset(RAX, 1)));
         assert_emit!(0x49, 0x8d, 0x00; lea(RAX, Address::offset(R8, 0)));
         assert_emit!(0x4c, 0x8d, 0x00; lea(R8, Address::offset(RAX, 0)));
+        assert_emit!(0x48, 0x8d, 0x04, 0x40; lea(RAX, Address::array(RAX, RAX, ScaleFactor::Two, 0)));
+    }
+
+    #[test]
+    fn test_leal() {
+        assert_emit!(0x8d, 0x00; leal(RAX, Address::offset(RAX, 0)));
+        assert_emit!(0x41, 0x8d, 0x00; leal(RAX, Address::offset(R8, 0)));
+        assert_emit!(0x44, 0x8d, 0x00; leal(R8, Address::offset(RAX, 0)));
+        assert_emit!(0x8d, 0x0c, 0x9b; leal(RCX, Address::array(RBX, RBX, ScaleFactor::Four, 0)));
     }
 
     #[test]
@@ -2223,6 +2481,27 @@ mod tests {
         assert_emit!(0x41, 0x0f, 0xb6, 0x00; movzxb_ra(RAX, Address::offset(R8, 0)));
     }
 
+    #[test]
+    fn test_movzxw_ra() {
+        assert_emit!(0x0f, 0xb7, 0x00; movzxw_ra(RAX, Address::offset(RAX, 0)));
+        assert_emit!(0x44, 0x0f, 0xb7, 0x00; movzxw_ra(R8, Address::offset(RAX, 0)));
+        assert_emit!(0x41, 0x0f, 0xb7, 0x00; movzxw_ra(RAX, Address::offset(R8, 0)));
+    }
+
+    #[test]
+    fn test_movw_ar() {
+        assert_emit!(0x66, 0x89, 0x04, 0x24; movw_ar(Address::offset(RSP, 0), RAX));
+        assert_emit!(0x66, 0x44, 0x89, 0x04, 0x24; movw_ar(Address::offset(RSP, 0), R8));
+        assert_emit!(0x66, 0x41, 0x89, 0x00; movw_ar(Address::offset(R8, 0), RAX));
+    }
+
+    #[test]
+    fn test_movw_ai() {
+        assert_emit!(0x66, 0xc7, 0x00, 1, 0; movw_ai(Address::offset(RAX, 0), Immediate(1)));
+        assert_emit!(0x66, 0xc7, 0x00, 0xff, 0xff; movw_ai(Address::offset(RAX, 0), Immediate(65535)));
+        assert_emit!(0x66, 0x41, 0xc7, 0x00, 0x00, 0x80; movw_ai(Address::offset(R8, 0), Immediate(-32768)));
+    }
+
     #[test]
     fn test_movsxlq_rr() {
         assert_emit!(0x4c, 0x63, 0xf8; movsxlq_rr(R15, RAX));
@@ -2322,6 +2601,13 @@ mod tests {
         assert_emit!(0x41, 0x81, 0x38, 0x80, 0, 0, 0; cmpl_ai(Address::offset(R8, 0), Immediate(128)));
     }
 
+    #[test]
+    fn test_addq_ai() {
+        assert_emit!(0x48, 0x83, 0x00, 0x01; addq_ai(Address::offset(RAX, 0), Immediate(1)));
+        assert_emit!(0x48, 0x81, 0x00, 0x80, 0, 0, 0; addq_ai(Address::offset(RAX, 0), Immediate(128)));
+        assert_emit!(0x49, 0x83, 0x00, 0x01; addq_ai(Address::offset(R8, 0), Immediate(1)));
+    }
+
     #[test]
     fn test_cmpq_ai() {
         assert_emit!(0x48, 0x83, 0x38, 0x7f; cmpq_ai(Address::offset(RAX, 0), Immediate(127)));
@@ -2487,6 +2773,47 @@ mod tests {
         assert_emit!(0xf2, 0x41, 0x0f, 0x51, 0xe0; sqrtsd_rr(XMM4, XMM8));
     }
 
+    #[test]
+    fn test_minss_rr() {
+        assert_emit!(0xf3, 0x0f, 0x5d, 0xc1; minss_rr(XMM0, XMM1));
+        assert_emit!(0xf3, 0x41, 0x0f, 0x5d, 0xdf; minss_rr(XMM3, XMM15));
+        assert_emit!(0xf3, 0x44, 0x0f, 0x5d, 0xc4; minss_rr(XMM8, XMM4));
+    }
+
+    #[test]
+    fn test_minsd_rr() {
+        assert_emit!(0xf2, 0x0f, 0x5d, 0xc1; minsd_rr(XMM0, XMM1));
+        assert_emit!(0xf2, 0x41, 0x0f, 0x5d, 0xdf; minsd_rr(XMM3, XMM15));
+        assert_emit!(0xf2, 0x44, 0x0f, 0x5d, 0xc4; minsd_rr(XMM8, XMM4));
+    }
+
+    #[test]
+    fn test_maxss_rr() {
+        assert_emit!(0xf3, 0x0f, 0x5f, 0xc1; maxss_rr(XMM0, XMM1));
+        assert_emit!(0xf3, 0x41, 0x0f, 0x5f, 0xdf; maxss_rr(XMM3, XMM15));
+        assert_emit!(0xf3, 0x44, 0x0f, 0x5f, 0xc4; maxss_rr(XMM8, XMM4));
+    }
+
+    #[test]
+    fn test_maxsd_rr() {
+        assert_emit!(0xf2, 0x0f, 0x5f, 0xc1; maxsd_rr(XMM0, XMM1));
+        assert_emit!(0xf2, 0x41, 0x0f, 0x5f, 0xdf; maxsd_rr(XMM3, XMM15));
+        assert_emit!(0xf2, 0x44, 0x0f, 0x5f, 0xc4; maxsd_rr(XMM8, XMM4));
+    }
+
+    #[test]
+    fn test_vfmadd213ss_rrr() {
+        assert_emit!(0xc4, 0xe2, 0x71, 0xa9, 0xc2; vfmadd213ss_rrr(XMM0, XMM1, XMM2));
+        assert_emit!(0xc4, 0x42, 0x31, 0xa9, 0xc2; vfmadd213ss_rrr(XMM8, XMM9, XMM10));
+    }
+
+    #[test]
+    fn test_vfmadd213sd_rrr() {
+        assert_emit!(0xc4, 0xe2, 0xf1, 0xa9, 0xc2; vfmadd213sd_rrr(XMM0, XMM1, XMM2));
+        assert_emit!(0xc4, 0x42, 0xb1, 0xa9, 0xc2; vfmadd213sd_rrr(XMM8, XMM9, XMM10));
+        assert_emit!(0xc4, 0xc2, 0xc1, 0xa9, 0xdf; vfmadd213sd_rrr(XMM3, XMM7, XMM15));
+    }
+
     #[test]
     fn test_tzcnt() {
         assert_emit!(0xF3, 0x48, 0x0F, 0xBC, 0xF8; tzcntq_rr(RDI, RAX));
@@ -2744,6 +3071,18 @@ mod tests {
         assert_emit!(0x41, 0x0f, 0x57, 0xf8; xorps_rr(XMM7, XMM8));
     }
 
+    #[test]
+    fn test_andps_rr() {
+        assert_emit!(0x0f, 0x54, 0xc1; andps_rr(XMM0, XMM1));
+        assert_emit!(0x41, 0x0f, 0x54, 0xf8; andps_rr(XMM7, XMM8));
+    }
+
+    #[test]
+    fn test_orps_rr() {
+        assert_emit!(0x0f, 0x56, 0xc1; orps_rr(XMM0, XMM1));
+        assert_emit!(0x41, 0x0f, 0x56, 0xf8; orps_rr(XMM7, XMM8));
+    }
+
     #[test]
     fn test_andps_ra() {
         assert_emit!(0x0f, 0x54, 0x05, 0x04, 0x00, 0x00, 0x00; andps_ra(XMM0, Address::rip(4)));
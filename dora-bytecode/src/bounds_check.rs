@@ -0,0 +1,207 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::liveness::{collect_instructions, InstructionInfo};
+use crate::{BytecodeFunction, BytecodeInstruction, BytecodeOffset, BytecodeReader, Register};
+
+// Array accesses that are known to be in bounds. This recognizes loops of
+// the form
+//
+//   LoopStart
+//   ArrayLength len, arr
+//   TestLt cond, idx, len
+//   JumpIfFalse cond, <loop exit>
+//
+// where `arr` isn't written within the loop and `idx` is only ever set to
+// non-negative constants or incremented by them (Add traps on overflow). LoadArray and StoreArray on `arr` with `idx` are in bounds
+// as long as `idx` wasn't written since the loop condition was checked
+// and no nested loop was entered.
+pub struct BoundsCheckElimination {
+    in_bounds: HashSet<BytecodeOffset>,
+}
+
+impl BoundsCheckElimination {
+    pub fn compute(bc: &BytecodeFunction) -> BoundsCheckElimination {
+        let infos = collect_instructions(bc);
+        let instructions: Vec<BytecodeInstruction> = BytecodeReader::new(bc.code()).collect();
+        assert_eq!(infos.len(), instructions.len());
+
+        let mut defs: HashMap<Register, Vec<usize>> = HashMap::new();
+
+        for (idx, info) in infos.iter().enumerate() {
+            for &reg in &info.defs {
+                defs.entry(reg).or_default().push(idx);
+            }
+        }
+
+        let jump_targets = infos
+            .iter()
+            .flat_map(|info| info.targets.iter().copied())
+            .chain(
+                bc.exception_handlers()
+                    .iter()
+                    .map(|handler| handler.handler),
+            )
+            .collect();
+
+        let analysis = Analysis {
+            bc,
+            infos: &infos,
+            instructions: &instructions,
+            defs: &defs,
+            jump_targets,
+        };
+
+        let mut in_bounds = HashSet::new();
+
+        for (start, inst) in instructions.iter().enumerate() {
+            if let BytecodeInstruction::LoopStart = inst {
+                analysis.analyze_loop(start, &mut in_bounds);
+            }
+        }
+
+        BoundsCheckElimination { in_bounds }
+    }
+
+    // returns true if the LoadArray or StoreArray at the given offset
+    // doesn't need a bounds check
+    pub fn is_in_bounds(&self, offset: BytecodeOffset) -> bool {
+        self.in_bounds.contains(&offset)
+    }
+}
+
+struct Analysis<'a> {
+    bc: &'a BytecodeFunction,
+    infos: &'a [InstructionInfo],
+    instructions: &'a [BytecodeInstruction],
+    defs: &'a HashMap<Register, Vec<usize>>,
+    jump_targets: HashSet<BytecodeOffset>,
+}
+
+impl<'a> Analysis<'a> {
+    fn analyze_loop(&self, start: usize, in_bounds: &mut HashSet<BytecodeOffset>) {
+        let loop_offset = self.infos[start].offset;
+
+        // the last back edge ends the loop
+        let end = match (start..self.instructions.len()).rev().find(|&idx| {
+            matches!(self.instructions[idx], BytecodeInstruction::JumpLoop { .. })
+                && self.infos[idx].targets.contains(&loop_offset)
+        }) {
+            Some(end) => end,
+            None => return,
+        };
+
+        if end < start + 4 {
+            return;
+        }
+
+        let (len, arr) = match self.instructions[start + 1] {
+            BytecodeInstruction::ArrayLength { dest, arr } => (dest, arr),
+            _ => return,
+        };
+
+        let (cond, index) = match self.instructions[start + 2] {
+            BytecodeInstruction::TestLt { dest, lhs, rhs } if rhs == len => (dest, lhs),
+            _ => return,
+        };
+
+        match self.instructions[start + 3] {
+            BytecodeInstruction::JumpIfFalse { opnd, .. }
+            | BytecodeInstruction::JumpIfFalseConst { opnd, .. }
+                if opnd == cond => {}
+            _ => return,
+        }
+
+        let loop_end = self.infos[end].offset;
+
+        if !self.infos[start + 3]
+            .targets
+            .iter()
+            .all(|&target| target > loop_end)
+        {
+            return;
+        }
+
+        let inside = |idx: usize| start <= idx && idx <= end;
+
+        let arr_invariant = self
+            .defs
+            .get(&arr)
+            .is_none_or(|defs| defs.iter().all(|&idx| !inside(idx)));
+
+        if !arr_invariant || !self.is_non_negative_index(index) {
+            return;
+        }
+
+        for idx in start + 4..end {
+            match self.instructions[idx] {
+                BytecodeInstruction::LoopStart => break,
+
+                BytecodeInstruction::LoadArray {
+                    arr: access_arr,
+                    idx: access_idx,
+                    ..
+                }
+                | BytecodeInstruction::StoreArray {
+                    arr: access_arr,
+                    idx: access_idx,
+                    ..
+                } if access_arr == arr && access_idx == index => {
+                    in_bounds.insert(self.infos[idx].offset);
+                }
+
+                _ => {}
+            }
+
+            if self.infos[idx].defs.contains(&index) {
+                break;
+            }
+        }
+    }
+
+    // The index is never negative: all its writes are either non-negative
+    // constants or increments by non-negative constants.
+    fn is_non_negative_index(&self, index: Register) -> bool {
+        if index.0 < self.bc.arguments() as usize
+            || self
+                .bc
+                .exception_handlers()
+                .iter()
+                .any(|handler| handler.register == index)
+        {
+            return false;
+        }
+
+        let defs = match self.defs.get(&index) {
+            Some(defs) => defs,
+            None => return false,
+        };
+
+        defs.iter().all(|&idx| match self.instructions[idx] {
+            BytecodeInstruction::Add { dest, lhs, rhs } if dest == index && lhs == index => {
+                // the constant right before needs to be the only way
+                // to reach the increment
+                idx > 0
+                    && !self.jump_targets.contains(&self.infos[idx].offset)
+                    && self.is_non_negative_const(idx - 1, rhs)
+            }
+            _ => self.is_non_negative_const(idx, index),
+        })
+    }
+
+    fn is_non_negative_const(&self, idx: usize, reg: Register) -> bool {
+        match self.instructions[idx] {
+            BytecodeInstruction::ConstInt64 {
+                dest,
+                idx: const_idx,
+            } => {
+                dest == reg
+                    && self
+                        .bc
+                        .const_pool(const_idx)
+                        .to_int64()
+                        .is_some_and(|value| value >= 0)
+            }
+            _ => false,
+        }
+    }
+}
@@ -1,10 +1,19 @@
 use std::collections::{HashMap, HashSet};
 
 use crate::{
-    BytecodeFunction, BytecodeType, BytecodeTypeArray, BytecodeWriter, ClassId, ConstPoolEntry,
-    ConstPoolIdx, EnumId, FunctionId, GlobalId, Label, Location, Register, StructId, TraitId,
+    BytecodeFunction, BytecodeInstruction, BytecodeReader, BytecodeType, BytecodeTypeArray,
+    BytecodeWriter, ClassId, ConstPoolEntry, ConstPoolIdx, EnumId, FunctionId, GlobalId, Label,
+    Location, Register, StructId, TraitId, WriterCheckpoint,
 };
 
+// Position in the emitted bytecode together with the register allocation
+// state. Code generation can return to it to discard speculatively emitted
+// code.
+pub struct BytecodeCheckpoint {
+    writer: WriterCheckpoint,
+    registers: Registers,
+}
+
 pub struct BytecodeBuilder {
     writer: BytecodeWriter,
     registers: Registers,
@@ -30,6 +39,40 @@ impl BytecodeBuilder {
         self.writer.bind_label(lbl)
     }
 
+    pub fn checkpoint(&self) -> BytecodeCheckpoint {
+        BytecodeCheckpoint {
+            writer: self.writer.checkpoint(),
+            registers: self.registers.clone(),
+        }
+    }
+
+    pub fn rollback(&mut self, checkpoint: BytecodeCheckpoint) {
+        self.writer.rollback(checkpoint.writer);
+        self.registers = checkpoint.registers;
+    }
+
+    // Decodes the instructions emitted since the checkpoint.
+    pub fn instructions_since(&self, checkpoint: &BytecodeCheckpoint) -> Vec<BytecodeInstruction> {
+        let code = &self.writer.code()[checkpoint.writer.offset()..];
+        BytecodeReader::new(code).collect()
+    }
+
+    pub fn const_pool(&self, idx: ConstPoolIdx) -> &ConstPoolEntry {
+        self.writer.const_pool(idx)
+    }
+
+    pub fn add_exception_handler(
+        &mut self,
+        start: Label,
+        end: Label,
+        handler: Label,
+        exception_type: BytecodeType,
+        register: Register,
+    ) {
+        self.writer
+            .add_exception_handler(start, end, handler, exception_type, register);
+    }
+
     pub fn set_arguments(&mut self, arguments: u32) {
         self.writer.set_arguments(arguments)
     }
@@ -180,6 +223,11 @@ impl BytecodeBuilder {
         self.writer.emit_add(dest, lhs, rhs);
     }
 
+    pub fn emit_add_saturating(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        assert!(self.def(dest) && self.used(lhs) && self.used(rhs));
+        self.writer.emit_add_saturating(dest, lhs, rhs);
+    }
+
     pub fn emit_and(&mut self, dest: Register, lhs: Register, rhs: Register) {
         assert!(self.def(dest) && self.used(lhs) && self.used(rhs));
         self.writer.emit_and(dest, lhs, rhs);
@@ -299,6 +347,11 @@ impl BytecodeBuilder {
         self.writer.emit_jump_loop(lbl);
     }
 
+    pub fn emit_switch(&mut self, opnd: Register, low: i64, targets: Vec<Label>) {
+        assert!(self.used(opnd));
+        self.writer.emit_switch(opnd, low, targets);
+    }
+
     pub fn emit_loop_start(&mut self) {
         self.writer.emit_loop_start();
     }
@@ -345,6 +398,11 @@ impl BytecodeBuilder {
         self.writer.emit_sub(dest, lhs, rhs);
     }
 
+    pub fn emit_sub_saturating(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        assert!(self.def(dest) && self.used(lhs) && self.used(rhs));
+        self.writer.emit_sub_saturating(dest, lhs, rhs);
+    }
+
     pub fn emit_mov(&mut self, dest: Register, src: Register) {
         assert!(self.def(dest) && self.used(src));
         self.writer.emit_mov(dest, src);
@@ -384,6 +442,35 @@ impl BytecodeBuilder {
         self.writer.emit_load_trait_object_value(dest, object);
     }
 
+    pub fn emit_test_trait_object(&mut self, dest: Register, idx: ConstPoolIdx, src: Register) {
+        assert!(self.def(dest) && self.used(src));
+        self.writer.emit_test_trait_object(dest, idx, src);
+    }
+
+    pub fn emit_cast_trait_object(
+        &mut self,
+        dest: Register,
+        idx: ConstPoolIdx,
+        src: Register,
+        location: Location,
+    ) {
+        assert!(self.def(dest) && self.used(src));
+        self.writer.set_location(location);
+        self.writer.emit_cast_trait_object(dest, idx, src);
+    }
+
+    pub fn emit_checked_cast(&mut self, dest: Register, src: Register, location: Location) {
+        assert!(self.def(dest) && self.used(src));
+        self.writer.set_location(location);
+        self.writer.emit_checked_cast(dest, src);
+    }
+
+    pub fn emit_throw(&mut self, src: Register, location: Location) {
+        assert!(self.used(src));
+        self.writer.set_location(location);
+        self.writer.emit_throw(src);
+    }
+
     pub fn emit_ret(&mut self, src: Register) {
         assert!(self.used(src));
         self.writer.emit_ret(src);
@@ -562,6 +649,18 @@ impl BytecodeBuilder {
         self.writer.emit_store_array(src, array, index);
     }
 
+    pub fn emit_array_fill(
+        &mut self,
+        src: Register,
+        array: Register,
+        length: Register,
+        location: Location,
+    ) {
+        assert!(self.used(src) && self.used(array) && self.used(length));
+        self.writer.set_location(location);
+        self.writer.emit_array_fill(src, array, length);
+    }
+
     pub fn emit_load_array(
         &mut self,
         dest: Register,
@@ -624,6 +723,7 @@ impl BytecodeBuilder {
     }
 }
 
+#[derive(Clone)]
 struct Registers {
     all: Vec<BytecodeType>,
     scopes: Vec<RegisterScope>,
@@ -723,6 +823,7 @@ impl Registers {
     }
 }
 
+#[derive(Clone)]
 struct RegisterScope(HashSet<Register>);
 
 impl RegisterScope {
@@ -20,7 +20,8 @@ impl BytecodeOffset {
     }
 }
 
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(IntoPrimitive, TryFromPrimitive, Copy, Clone, Debug, PartialEq)]
+#[repr(u8)]
 pub enum BytecodeTypeKind {
     Unit,
     Bool,
@@ -38,6 +39,9 @@ pub enum BytecodeTypeKind {
     Class,
     Trait,
     Lambda,
+    UInt16,
+    UInt32,
+    UInt64,
 }
 
 #[derive(IntoPrimitive, TryFromPrimitive, Copy, Clone, PartialEq, Eq)]
@@ -46,7 +50,9 @@ pub enum BytecodeOpcode {
     Wide,
 
     Add,
+    AddSaturating,
     Sub,
+    SubSaturating,
     Neg,
     Mul,
     Div,
@@ -103,6 +109,7 @@ pub enum BytecodeOpcode {
     JumpIfFalseConst,
     JumpIfTrue,
     JumpIfTrueConst,
+    Switch,
 
     InvokeDirect,
     InvokeVirtual,
@@ -124,8 +131,15 @@ pub enum BytecodeOpcode {
 
     LoadArray,
     StoreArray,
+    ArrayFill,
 
     LoadTraitObjectValue,
+    TestTraitObject,
+    CastTraitObject,
+
+    CheckedCast,
+
+    Throw,
 
     Ret,
 }
@@ -152,6 +166,17 @@ impl BytecodeOpcode {
         }
     }
 
+    pub fn is_conditional_jump(self) -> bool {
+        match self {
+            BytecodeOpcode::JumpIfFalse
+            | BytecodeOpcode::JumpIfFalseConst
+            | BytecodeOpcode::JumpIfTrue
+            | BytecodeOpcode::JumpIfTrueConst
+            | BytecodeOpcode::Switch => true,
+            _ => false,
+        }
+    }
+
     pub fn is_new_enum(self) -> bool {
         match self {
             BytecodeOpcode::NewEnum => true,
@@ -216,6 +241,7 @@ impl BytecodeOpcode {
             | BytecodeOpcode::ConstTrue
             | BytecodeOpcode::ConstFalse
             | BytecodeOpcode::Ret
+            | BytecodeOpcode::Throw
             | BytecodeOpcode::JumpConst
             | BytecodeOpcode::Jump
             | BytecodeOpcode::JumpLoop => opcode_size(width) + 1 * operand_size(width),
@@ -232,6 +258,7 @@ impl BytecodeOpcode {
             | BytecodeOpcode::ConstFloat64
             | BytecodeOpcode::ConstString
             | BytecodeOpcode::ArrayLength
+            | BytecodeOpcode::CheckedCast
             | BytecodeOpcode::NewObject
             | BytecodeOpcode::NewTuple
             | BytecodeOpcode::NewEnum
@@ -244,10 +271,13 @@ impl BytecodeOpcode {
             | BytecodeOpcode::JumpIfTrueConst
             | BytecodeOpcode::JumpIfTrue
             | BytecodeOpcode::JumpIfFalseConst
-            | BytecodeOpcode::JumpIfFalse => opcode_size(width) + 2 * operand_size(width),
+            | BytecodeOpcode::JumpIfFalse
+            | BytecodeOpcode::Switch => opcode_size(width) + 2 * operand_size(width),
 
             BytecodeOpcode::Add
+            | BytecodeOpcode::AddSaturating
             | BytecodeOpcode::Sub
+            | BytecodeOpcode::SubSaturating
             | BytecodeOpcode::Mul
             | BytecodeOpcode::Div
             | BytecodeOpcode::Mod
@@ -269,8 +299,11 @@ impl BytecodeOpcode {
             | BytecodeOpcode::TestLe
             | BytecodeOpcode::LoadArray
             | BytecodeOpcode::StoreArray
+            | BytecodeOpcode::ArrayFill
             | BytecodeOpcode::NewArray
-            | BytecodeOpcode::NewTraitObject => opcode_size(width) + 3 * operand_size(width),
+            | BytecodeOpcode::NewTraitObject
+            | BytecodeOpcode::TestTraitObject
+            | BytecodeOpcode::CastTraitObject => opcode_size(width) + 3 * operand_size(width),
 
             BytecodeOpcode::LoadTupleElement | BytecodeOpcode::LoadEnumElement => {
                 opcode_size(width) + 4 * operand_size(width)
@@ -301,10 +334,14 @@ impl BytecodeOpcode {
             | BytecodeOpcode::NewTuple
             | BytecodeOpcode::NewStruct
             | BytecodeOpcode::NewTraitObject
+            | BytecodeOpcode::CastTraitObject
             | BytecodeOpcode::NewLambda
             | BytecodeOpcode::ArrayLength
             | BytecodeOpcode::LoadArray
             | BytecodeOpcode::StoreArray
+            | BytecodeOpcode::ArrayFill
+            | BytecodeOpcode::CheckedCast
+            | BytecodeOpcode::Throw
             | BytecodeOpcode::LoadEnumElement
             | BytecodeOpcode::LoadEnumVariant
             | BytecodeOpcode::LoadGlobal
@@ -323,12 +360,24 @@ pub enum BytecodeInstruction {
         rhs: Register,
     },
 
+    AddSaturating {
+        dest: Register,
+        lhs: Register,
+        rhs: Register,
+    },
+
     Sub {
         dest: Register,
         lhs: Register,
         rhs: Register,
     },
 
+    SubSaturating {
+        dest: Register,
+        lhs: Register,
+        rhs: Register,
+    },
+
     Neg {
         dest: Register,
         src: Register,
@@ -541,6 +590,10 @@ pub enum BytecodeInstruction {
         opnd: Register,
         idx: ConstPoolIdx,
     },
+    Switch {
+        opnd: Register,
+        idx: ConstPoolIdx,
+    },
 
     InvokeDirect {
         dest: Register,
@@ -622,11 +675,35 @@ pub enum BytecodeInstruction {
         arr: Register,
         idx: Register,
     },
+    ArrayFill {
+        src: Register,
+        arr: Register,
+        length: Register,
+    },
 
     LoadTraitObjectValue {
         dest: Register,
         object: Register,
     },
+    TestTraitObject {
+        dest: Register,
+        idx: ConstPoolIdx,
+        src: Register,
+    },
+    CastTraitObject {
+        dest: Register,
+        idx: ConstPoolIdx,
+        src: Register,
+    },
+
+    CheckedCast {
+        dest: Register,
+        src: Register,
+    },
+
+    Throw {
+        opnd: Register,
+    },
 
     Ret {
         opnd: Register,
@@ -655,7 +732,7 @@ impl OperandWidth {
     }
 }
 
-#[derive(Copy, Clone, PartialEq, Debug, Eq, Hash)]
+#[derive(Copy, Clone, PartialEq, Debug, Eq, Hash, Decode, Encode)]
 pub struct Register(pub usize);
 
 impl Register {
@@ -712,6 +789,18 @@ impl std::fmt::Display for Location {
     }
 }
 
+// Objects thrown while executing an instruction in [start, end) whose class
+// matches `exception_type` continue at `handler`, the thrown object is
+// stored in `register`. Inner handlers come before outer ones.
+#[derive(Clone, Debug, Decode, Encode)]
+pub struct BytecodeExceptionHandler {
+    pub start: BytecodeOffset,
+    pub end: BytecodeOffset,
+    pub handler: BytecodeOffset,
+    pub exception_type: BytecodeType,
+    pub register: Register,
+}
+
 #[derive(Clone, Debug, Decode, Encode)]
 pub struct BytecodeFunction {
     code: Vec<u8>,
@@ -719,6 +808,7 @@ pub struct BytecodeFunction {
     const_pool: Vec<ConstPoolEntry>,
     arguments: u32,
     locations: Vec<(BytecodeOffset, Location)>,
+    exception_handlers: Vec<BytecodeExceptionHandler>,
 }
 
 impl BytecodeFunction {
@@ -728,6 +818,7 @@ impl BytecodeFunction {
         registers: Vec<BytecodeType>,
         arguments: u32,
         locations: Vec<(BytecodeOffset, Location)>,
+        exception_handlers: Vec<BytecodeExceptionHandler>,
     ) -> BytecodeFunction {
         BytecodeFunction {
             code,
@@ -735,6 +826,7 @@ impl BytecodeFunction {
             registers,
             arguments,
             locations,
+            exception_handlers,
         }
     }
     pub fn code(&self) -> &[u8] {
@@ -749,6 +841,26 @@ impl BytecodeFunction {
         &self.locations
     }
 
+    // First instruction of every run of consecutive instructions on the
+    // same source line.
+    pub fn line_starts(&self) -> Vec<(BytecodeOffset, u32)> {
+        let mut result = Vec::new();
+        let mut last_line = None;
+
+        for &(offset, location) in &self.locations {
+            if last_line != Some(location.line()) {
+                result.push((offset, location.line()));
+                last_line = Some(location.line());
+            }
+        }
+
+        result
+    }
+
+    pub fn exception_handlers(&self) -> &[BytecodeExceptionHandler] {
+        &self.exception_handlers
+    }
+
     pub fn register_type(&self, register: Register) -> BytecodeType {
         self.registers
             .get(register.0)
@@ -784,7 +896,7 @@ impl BytecodeFunction {
     }
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, IntoPrimitive)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, IntoPrimitive, TryFromPrimitive)]
 #[repr(u8)]
 pub enum ConstPoolOpcode {
     String,
@@ -807,6 +919,7 @@ pub enum ConstPoolOpcode {
     TupleElement,
     Tuple,
     Lambda,
+    JumpTable,
 }
 
 #[derive(Clone, Debug, PartialEq, Decode, Encode)]
@@ -830,6 +943,7 @@ pub enum ConstPoolEntry {
     TupleElement(BytecodeType, u32),
     Tuple(BytecodeTypeArray),
     Lambda(BytecodeTypeArray, BytecodeType),
+    JumpTable(i64, Vec<u32>),
 }
 
 impl ConstPoolEntry {
@@ -875,6 +989,13 @@ impl ConstPoolEntry {
         }
     }
 
+    pub fn to_jump_table(&self) -> Option<(i64, &[u32])> {
+        match self {
+            ConstPoolEntry::JumpTable(low, ref targets) => Some((*low, targets)),
+            _ => None,
+        }
+    }
+
     pub fn is_fct(&self) -> bool {
         match self {
             ConstPoolEntry::Fct(_, _) => true,
@@ -0,0 +1,81 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::liveness::collect_instructions;
+use crate::{BytecodeFunction, BytecodeInstruction, BytecodeReader, ConstPoolIdx, Register};
+
+// Registers holding objects that never escape the function. Such a register
+// is written exactly once by NewObject or NewObjectInitialized and is only
+// used as the object of LoadField and StoreField. Any other use (storing it
+// into a field or global, returning it, moving it into another register or
+// passing it to a call) lets the object escape. Since no other register can
+// refer to the object, a backend may reuse the same storage each time the
+// allocation is executed.
+pub struct EscapeAnalysis {
+    non_escaping: HashMap<Register, ConstPoolIdx>,
+}
+
+impl EscapeAnalysis {
+    pub fn compute(bc: &BytecodeFunction) -> EscapeAnalysis {
+        let instructions = collect_instructions(bc);
+        let reader = BytecodeReader::new(bc.code());
+
+        let mut allocations: HashMap<Register, ConstPoolIdx> = HashMap::new();
+        let mut defs: HashMap<Register, usize> = HashMap::new();
+        let mut escaping: HashSet<Register> = HashSet::new();
+
+        // arguments and exception registers are written outside of the code
+        for idx in 0..bc.arguments() as usize {
+            escaping.insert(Register(idx));
+        }
+
+        for handler in bc.exception_handlers() {
+            escaping.insert(handler.register);
+        }
+
+        for (info, inst) in instructions.iter().zip(reader) {
+            for &reg in &info.defs {
+                *defs.entry(reg).or_insert(0) += 1;
+            }
+
+            let object_use = match inst {
+                BytecodeInstruction::NewObject { dest, cls }
+                | BytecodeInstruction::NewObjectInitialized { dest, cls } => {
+                    allocations.insert(dest, cls);
+                    None
+                }
+
+                BytecodeInstruction::LoadField { obj, .. } => Some(obj),
+
+                BytecodeInstruction::StoreField { src, obj, .. } => {
+                    escaping.insert(src);
+                    Some(obj)
+                }
+
+                _ => None,
+            };
+
+            for &reg in &info.uses {
+                if Some(reg) != object_use {
+                    escaping.insert(reg);
+                }
+            }
+        }
+
+        let non_escaping = allocations
+            .into_iter()
+            .filter(|(reg, _)| !escaping.contains(reg) && defs.get(reg) == Some(&1))
+            .collect();
+
+        EscapeAnalysis { non_escaping }
+    }
+
+    // returns true if the object allocated into the register doesn't escape
+    pub fn is_non_escaping(&self, reg: Register) -> bool {
+        self.non_escaping.contains_key(&reg)
+    }
+
+    // non-escaping registers with the class allocated into them
+    pub fn non_escaping(&self) -> &HashMap<Register, ConstPoolIdx> {
+        &self.non_escaping
+    }
+}
@@ -1,21 +1,33 @@
+pub mod bounds_check;
 pub mod builder;
 pub mod data;
+pub mod escape;
+pub mod liveness;
 pub mod program;
 pub mod reader;
+pub mod serializer;
 pub mod ty;
+pub mod verifier;
 pub mod writer;
 
 #[cfg(test)]
 mod tests;
 
+pub use bounds_check::BoundsCheckElimination;
 pub use builder::*;
 pub use data::*;
+pub use escape::EscapeAnalysis;
+pub use liveness::Liveness;
 pub use program::{
     ClassData, ClassField, ClassId, EnumData, EnumId, EnumVariant, FunctionData, FunctionId,
     FunctionKind, GlobalData, GlobalId, ImplData, ImplId, Intrinsic, ModuleData, ModuleId,
     NativeFunction, PackageData, PackageId, Program, SourceFileData, SourceFileId, StructData,
-    StructField, StructId, TraitData, TraitId, TypeParamBound, TypeParamData,
+    StructField, StructId, TraitData, TraitId, TypeParamBound, TypeParamData, TRAP_NAMES,
 };
 pub use reader::*;
+pub use serializer::{
+    decode_module, encode_module, BytecodeModule, ModuleError, MODULE_MAGIC, MODULE_VERSION,
+};
 pub use ty::{BytecodeType, BytecodeTypeArray};
+pub use verifier::{verify, VerifyError};
 pub use writer::*;
@@ -0,0 +1,621 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    read, BytecodeFunction, BytecodeOffset, BytecodeVisitor, ConstPoolIdx, GlobalId, Register,
+};
+
+// Registers live before and after each instruction of a function. A register
+// is live if its current value might still be read on some path starting at
+// that point. Arguments pushed with PushRegister are also read by the
+// instruction consuming them (e.g. an invoke) and stay live until then.
+// Instructions in a try range have the exception handler as additional
+// successor, the handler's register is written when entering the handler.
+pub struct Liveness {
+    live_in: HashMap<BytecodeOffset, HashSet<Register>>,
+    live_out: HashMap<BytecodeOffset, HashSet<Register>>,
+}
+
+impl Liveness {
+    pub fn compute(bc: &BytecodeFunction) -> Liveness {
+        let instructions = collect_instructions(bc);
+        let index: HashMap<BytecodeOffset, usize> = instructions
+            .iter()
+            .enumerate()
+            .map(|(idx, inst)| (inst.offset, idx))
+            .collect();
+
+        let successors: Vec<Vec<usize>> = instructions
+            .iter()
+            .enumerate()
+            .map(|(idx, inst)| {
+                let mut successors: Vec<usize> = inst
+                    .targets
+                    .iter()
+                    .filter_map(|target| index.get(target))
+                    .copied()
+                    .collect();
+
+                if inst.falls_through && idx + 1 < instructions.len() {
+                    successors.push(idx + 1);
+                }
+
+                successors
+            })
+            .collect();
+
+        // exception handlers with the register written when entering them
+        let handlers: Vec<Vec<(usize, Register)>> = instructions
+            .iter()
+            .map(|inst| {
+                bc.exception_handlers()
+                    .iter()
+                    .filter(|handler| handler.start <= inst.offset && inst.offset < handler.end)
+                    .filter_map(|handler| {
+                        index
+                            .get(&handler.handler)
+                            .map(|&target| (target, handler.register))
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let mut live_in: Vec<HashSet<Register>> = vec![HashSet::new(); instructions.len()];
+        let mut live_out: Vec<HashSet<Register>> = vec![HashSet::new(); instructions.len()];
+        let mut changed = true;
+
+        while changed {
+            changed = false;
+
+            for idx in (0..instructions.len()).rev() {
+                let inst = &instructions[idx];
+                let mut out = HashSet::new();
+
+                for &succ in &successors[idx] {
+                    out.extend(live_in[succ].iter().copied());
+                }
+
+                let mut live = out.clone();
+
+                for reg in &inst.defs {
+                    live.remove(reg);
+                }
+
+                // a throwing instruction doesn't write its destination, so
+                // registers live at the handler stay live across the defs
+                for &(handler, written) in &handlers[idx] {
+                    let handler_live = live_in[handler].iter().filter(|&&reg| reg != written);
+                    out.extend(handler_live.clone().copied());
+                    live.extend(handler_live.copied());
+                }
+
+                live.extend(inst.uses.iter().copied());
+
+                if live != live_in[idx] || out != live_out[idx] {
+                    live_in[idx] = live;
+                    live_out[idx] = out;
+                    changed = true;
+                }
+            }
+        }
+
+        let offsets = instructions.iter().map(|inst| inst.offset);
+
+        Liveness {
+            live_in: offsets.clone().zip(live_in).collect(),
+            live_out: offsets.zip(live_out).collect(),
+        }
+    }
+
+    // registers live right before the instruction at the given offset
+    pub fn live_in(&self, offset: BytecodeOffset) -> &HashSet<Register> {
+        self.live_in.get(&offset).expect("no instruction at offset")
+    }
+
+    // registers live right after the instruction at the given offset
+    pub fn live_out(&self, offset: BytecodeOffset) -> &HashSet<Register> {
+        self.live_out
+            .get(&offset)
+            .expect("no instruction at offset")
+    }
+
+    // returns true if the instruction reads the register and its
+    // value isn't needed anymore afterwards
+    pub fn is_last_use(&self, offset: BytecodeOffset, reg: Register) -> bool {
+        self.live_in(offset).contains(&reg) && !self.live_out(offset).contains(&reg)
+    }
+}
+
+pub(crate) struct InstructionInfo {
+    pub(crate) offset: BytecodeOffset,
+    pub(crate) uses: Vec<Register>,
+    pub(crate) defs: Vec<Register>,
+    pub(crate) targets: Vec<BytecodeOffset>,
+    falls_through: bool,
+}
+
+// registers read and written by each instruction of the function, in order
+pub(crate) fn collect_instructions(bc: &BytecodeFunction) -> Vec<InstructionInfo> {
+    let mut collector = InstructionCollector {
+        bc,
+        instructions: Vec::new(),
+        pushed: Vec::new(),
+    };
+
+    read(bc.code(), &mut collector);
+
+    collector.instructions
+}
+
+struct InstructionCollector<'a> {
+    bc: &'a BytecodeFunction,
+    instructions: Vec<InstructionInfo>,
+    pushed: Vec<Register>,
+}
+
+impl<'a> InstructionCollector<'a> {
+    fn current(&mut self) -> &mut InstructionInfo {
+        self.instructions.last_mut().expect("no instruction")
+    }
+
+    fn use_register(&mut self, reg: Register) {
+        self.current().uses.push(reg);
+    }
+
+    fn define(&mut self, reg: Register) {
+        self.current().defs.push(reg);
+    }
+
+    fn use_arguments(&mut self) {
+        let pushed = std::mem::take(&mut self.pushed);
+        self.current().uses.extend(pushed);
+    }
+
+    fn no_fallthrough(&mut self) {
+        self.current().falls_through = false;
+    }
+
+    fn jump(&mut self, distance: i64) {
+        let inst = self.current();
+        let target = inst.offset.to_u32() as i64 + distance;
+        inst.targets.push(BytecodeOffset(target as u32));
+    }
+
+    fn const_jump(&mut self, idx: ConstPoolIdx) {
+        let distance = self.bc.const_pool(idx).to_int32().expect("int32 expected");
+        self.jump(distance as i64);
+    }
+
+    fn jump_table(&mut self, idx: ConstPoolIdx) {
+        let (_low, targets) = self
+            .bc
+            .const_pool(idx)
+            .to_jump_table()
+            .expect("jump table expected");
+
+        for &distance in targets {
+            self.jump(distance as i64);
+        }
+    }
+}
+
+impl<'a> BytecodeVisitor for InstructionCollector<'a> {
+    fn visit_instruction(&mut self, offset: BytecodeOffset) {
+        self.instructions.push(InstructionInfo {
+            offset,
+            uses: Vec::new(),
+            defs: Vec::new(),
+            targets: Vec::new(),
+            falls_through: true,
+        });
+    }
+
+    fn visit_add(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.use_register(lhs);
+        self.use_register(rhs);
+        self.define(dest);
+    }
+
+    fn visit_add_saturating(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.use_register(lhs);
+        self.use_register(rhs);
+        self.define(dest);
+    }
+
+    fn visit_sub(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.use_register(lhs);
+        self.use_register(rhs);
+        self.define(dest);
+    }
+
+    fn visit_sub_saturating(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.use_register(lhs);
+        self.use_register(rhs);
+        self.define(dest);
+    }
+
+    fn visit_neg(&mut self, dest: Register, src: Register) {
+        self.use_register(src);
+        self.define(dest);
+    }
+
+    fn visit_mul(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.use_register(lhs);
+        self.use_register(rhs);
+        self.define(dest);
+    }
+
+    fn visit_div(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.use_register(lhs);
+        self.use_register(rhs);
+        self.define(dest);
+    }
+
+    fn visit_mod(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.use_register(lhs);
+        self.use_register(rhs);
+        self.define(dest);
+    }
+
+    fn visit_and(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.use_register(lhs);
+        self.use_register(rhs);
+        self.define(dest);
+    }
+
+    fn visit_or(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.use_register(lhs);
+        self.use_register(rhs);
+        self.define(dest);
+    }
+
+    fn visit_xor(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.use_register(lhs);
+        self.use_register(rhs);
+        self.define(dest);
+    }
+
+    fn visit_not(&mut self, dest: Register, src: Register) {
+        self.use_register(src);
+        self.define(dest);
+    }
+
+    fn visit_shl(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.use_register(lhs);
+        self.use_register(rhs);
+        self.define(dest);
+    }
+
+    fn visit_shr(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.use_register(lhs);
+        self.use_register(rhs);
+        self.define(dest);
+    }
+
+    fn visit_sar(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.use_register(lhs);
+        self.use_register(rhs);
+        self.define(dest);
+    }
+
+    fn visit_mov(&mut self, dest: Register, src: Register) {
+        self.use_register(src);
+        self.define(dest);
+    }
+
+    fn visit_load_tuple_element(&mut self, dest: Register, src: Register, _idx: ConstPoolIdx) {
+        self.use_register(src);
+        self.define(dest);
+    }
+
+    fn visit_load_enum_element(&mut self, dest: Register, src: Register, _idx: ConstPoolIdx) {
+        self.use_register(src);
+        self.define(dest);
+    }
+
+    fn visit_load_enum_variant(&mut self, dest: Register, src: Register, _idx: ConstPoolIdx) {
+        self.use_register(src);
+        self.define(dest);
+    }
+
+    fn visit_load_struct_field(&mut self, dest: Register, obj: Register, _field: ConstPoolIdx) {
+        self.use_register(obj);
+        self.define(dest);
+    }
+
+    fn visit_load_field(&mut self, dest: Register, obj: Register, _field: ConstPoolIdx) {
+        self.use_register(obj);
+        self.define(dest);
+    }
+
+    fn visit_store_field(&mut self, src: Register, obj: Register, _field: ConstPoolIdx) {
+        self.use_register(src);
+        self.use_register(obj);
+    }
+
+    fn visit_load_global(&mut self, dest: Register, _global_id: GlobalId) {
+        self.define(dest);
+    }
+
+    fn visit_store_global(&mut self, src: Register, _global_id: GlobalId) {
+        self.use_register(src);
+    }
+
+    fn visit_push_register(&mut self, src: Register) {
+        self.use_register(src);
+        self.pushed.push(src);
+    }
+
+    fn visit_const_true(&mut self, dest: Register) {
+        self.define(dest);
+    }
+
+    fn visit_const_false(&mut self, dest: Register) {
+        self.define(dest);
+    }
+
+    fn visit_const_zero_uint8(&mut self, dest: Register) {
+        self.define(dest);
+    }
+
+    fn visit_const_zero_char(&mut self, dest: Register) {
+        self.define(dest);
+    }
+
+    fn visit_const_zero_int32(&mut self, dest: Register) {
+        self.define(dest);
+    }
+
+    fn visit_const_zero_int64(&mut self, dest: Register) {
+        self.define(dest);
+    }
+
+    fn visit_const_zero_float32(&mut self, dest: Register) {
+        self.define(dest);
+    }
+
+    fn visit_const_zero_float64(&mut self, dest: Register) {
+        self.define(dest);
+    }
+
+    fn visit_const_char(&mut self, dest: Register, _value: ConstPoolIdx) {
+        self.define(dest);
+    }
+
+    fn visit_const_uint8(&mut self, dest: Register, _value: u8) {
+        self.define(dest);
+    }
+
+    fn visit_const_int32(&mut self, dest: Register, _value: ConstPoolIdx) {
+        self.define(dest);
+    }
+
+    fn visit_const_int64(&mut self, dest: Register, _value: ConstPoolIdx) {
+        self.define(dest);
+    }
+
+    fn visit_const_float32(&mut self, dest: Register, _value: ConstPoolIdx) {
+        self.define(dest);
+    }
+
+    fn visit_const_float64(&mut self, dest: Register, _value: ConstPoolIdx) {
+        self.define(dest);
+    }
+
+    fn visit_const_string(&mut self, dest: Register, _value: ConstPoolIdx) {
+        self.define(dest);
+    }
+
+    fn visit_test_identity(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.use_register(lhs);
+        self.use_register(rhs);
+        self.define(dest);
+    }
+
+    fn visit_test_eq(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.use_register(lhs);
+        self.use_register(rhs);
+        self.define(dest);
+    }
+
+    fn visit_test_ne(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.use_register(lhs);
+        self.use_register(rhs);
+        self.define(dest);
+    }
+
+    fn visit_test_gt(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.use_register(lhs);
+        self.use_register(rhs);
+        self.define(dest);
+    }
+
+    fn visit_test_ge(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.use_register(lhs);
+        self.use_register(rhs);
+        self.define(dest);
+    }
+
+    fn visit_test_lt(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.use_register(lhs);
+        self.use_register(rhs);
+        self.define(dest);
+    }
+
+    fn visit_test_le(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.use_register(lhs);
+        self.use_register(rhs);
+        self.define(dest);
+    }
+
+    fn visit_jump_if_false(&mut self, opnd: Register, offset: u32) {
+        self.use_register(opnd);
+        self.jump(offset as i64);
+    }
+
+    fn visit_jump_if_false_const(&mut self, opnd: Register, idx: ConstPoolIdx) {
+        self.use_register(opnd);
+        self.const_jump(idx);
+    }
+
+    fn visit_jump_if_true(&mut self, opnd: Register, offset: u32) {
+        self.use_register(opnd);
+        self.jump(offset as i64);
+    }
+
+    fn visit_jump_if_true_const(&mut self, opnd: Register, idx: ConstPoolIdx) {
+        self.use_register(opnd);
+        self.const_jump(idx);
+    }
+
+    fn visit_switch(&mut self, opnd: Register, idx: ConstPoolIdx) {
+        self.use_register(opnd);
+        self.jump_table(idx);
+    }
+
+    fn visit_jump_loop(&mut self, offset: u32) {
+        self.jump(-(offset as i64));
+        self.no_fallthrough();
+    }
+
+    fn visit_loop_start(&mut self) {}
+
+    fn visit_jump(&mut self, offset: u32) {
+        self.jump(offset as i64);
+        self.no_fallthrough();
+    }
+
+    fn visit_jump_const(&mut self, idx: ConstPoolIdx) {
+        self.const_jump(idx);
+        self.no_fallthrough();
+    }
+
+    fn visit_invoke_direct(&mut self, dest: Register, _fct: ConstPoolIdx) {
+        self.use_arguments();
+        self.define(dest);
+    }
+
+    fn visit_invoke_virtual(&mut self, dest: Register, _fct: ConstPoolIdx) {
+        self.use_arguments();
+        self.define(dest);
+    }
+
+    fn visit_invoke_static(&mut self, dest: Register, _fct: ConstPoolIdx) {
+        self.use_arguments();
+        self.define(dest);
+    }
+
+    fn visit_invoke_lambda(&mut self, dest: Register, _idx: ConstPoolIdx) {
+        self.use_arguments();
+        self.define(dest);
+    }
+
+    fn visit_invoke_generic_static_void(&mut self, _fct: ConstPoolIdx) {
+        self.use_arguments();
+    }
+
+    fn visit_invoke_generic_static(&mut self, dest: Register, _fct: ConstPoolIdx) {
+        self.use_arguments();
+        self.define(dest);
+    }
+
+    fn visit_invoke_generic_direct_void(&mut self, _fct: ConstPoolIdx) {
+        self.use_arguments();
+    }
+
+    fn visit_invoke_generic_direct(&mut self, dest: Register, _fct: ConstPoolIdx) {
+        self.use_arguments();
+        self.define(dest);
+    }
+
+    fn visit_new_object(&mut self, dest: Register, _cls: ConstPoolIdx) {
+        self.define(dest);
+    }
+
+    fn visit_new_object_initialized(&mut self, dest: Register, _cls: ConstPoolIdx) {
+        self.use_arguments();
+        self.define(dest);
+    }
+
+    fn visit_new_array(&mut self, dest: Register, _cls: ConstPoolIdx, length: Register) {
+        self.use_register(length);
+        self.define(dest);
+    }
+
+    fn visit_new_tuple(&mut self, dest: Register, _idx: ConstPoolIdx) {
+        self.use_arguments();
+        self.define(dest);
+    }
+
+    fn visit_new_enum(&mut self, dest: Register, _idx: ConstPoolIdx) {
+        self.use_arguments();
+        self.define(dest);
+    }
+
+    fn visit_new_struct(&mut self, dest: Register, _idx: ConstPoolIdx) {
+        self.use_arguments();
+        self.define(dest);
+    }
+
+    fn visit_new_trait_object(&mut self, dest: Register, _idx: ConstPoolIdx, src: Register) {
+        self.use_register(src);
+        self.define(dest);
+    }
+
+    fn visit_new_lambda(&mut self, dest: Register, _idx: ConstPoolIdx) {
+        self.use_arguments();
+        self.define(dest);
+    }
+
+    fn visit_array_length(&mut self, dest: Register, arr: Register) {
+        self.use_register(arr);
+        self.define(dest);
+    }
+
+    fn visit_load_array(&mut self, dest: Register, arr: Register, idx: Register) {
+        self.use_register(arr);
+        self.use_register(idx);
+        self.define(dest);
+    }
+
+    fn visit_store_array(&mut self, src: Register, arr: Register, idx: Register) {
+        self.use_register(src);
+        self.use_register(arr);
+        self.use_register(idx);
+    }
+
+    fn visit_array_fill(&mut self, src: Register, arr: Register, length: Register) {
+        self.use_register(src);
+        self.use_register(arr);
+        self.use_register(length);
+    }
+
+    fn visit_load_trait_object_value(&mut self, dest: Register, object: Register) {
+        self.use_register(object);
+        self.define(dest);
+    }
+
+    fn visit_test_trait_object(&mut self, dest: Register, _idx: ConstPoolIdx, src: Register) {
+        self.use_register(src);
+        self.define(dest);
+    }
+
+    fn visit_cast_trait_object(&mut self, dest: Register, _idx: ConstPoolIdx, src: Register) {
+        self.use_register(src);
+        self.define(dest);
+    }
+
+    fn visit_checked_cast(&mut self, dest: Register, src: Register) {
+        self.use_register(src);
+        self.define(dest);
+    }
+
+    fn visit_throw(&mut self, opnd: Register) {
+        self.use_register(opnd);
+        self.no_fallthrough();
+    }
+
+    fn visit_ret(&mut self, opnd: Register) {
+        self.use_register(opnd);
+        self.no_fallthrough();
+    }
+}
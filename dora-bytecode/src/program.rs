@@ -39,11 +39,26 @@ pub struct FunctionData {
     pub intrinsic: Option<Intrinsic>,
     pub vtable_index: Option<u32>,
     pub is_test: bool,
+    pub expected_trap: Option<String>,
     pub is_optimize_immediately: bool,
     pub is_variadic: bool,
     pub bytecode: Option<BytecodeFunction>,
 }
 
+// Names of all traps, the argument of `@Test.expectTrap` needs to be one of them.
+pub const TRAP_NAMES: &[&str] = &[
+    "DIV0",
+    "ASSERT",
+    "INDEX_OUT_OF_BOUNDS",
+    "NIL",
+    "CAST",
+    "OOM",
+    "STACK_OVERFLOW",
+    "ILLEGAL",
+    "OVERFLOW",
+    "UNCAUGHT_EXCEPTION",
+];
+
 #[derive(Debug, Decode, Encode)]
 pub enum FunctionKind {
     Impl(ImplId),
@@ -215,12 +230,15 @@ pub enum InternalClass {
     String,
     Thread,
     StacktraceElement,
+    WeakRef,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Decode, Encode)]
 pub enum InternalFunction {
     StacktraceRetrieve,
+    ThrowableNew,
     BootsCompile,
+    Finalize,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Decode, Encode)]
@@ -235,7 +253,12 @@ pub enum NativeFunction {
     Argv,
     ForceCollect,
     Timestamp,
+    HashSeed,
+    HasHashSeed,
     ForceMinorCollect,
+    GcStat,
+    GcSetYoungSize,
+    RegisterFinalizer,
     Sleep,
     UInt8ToString,
     CharToString,
@@ -253,6 +276,7 @@ pub enum NativeFunction {
     StringPlus,
     Float32ToString,
     Float64ToString,
+    Float64ToStringFixed,
     StringFromBytesPart,
     StringFromStringPart,
     RetrieveStacktrace,
@@ -276,12 +300,23 @@ pub enum NativeFunction {
     SocketBind,
     SocketAccept,
     StringClone,
+    StringCopyBytes,
+    MathSin,
+    MathCos,
+    MathTan,
+    MathExp,
+    MathLn,
+    MathPow,
+    RandomOsSeed,
+    MonotonicNanos,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Decode, Encode)]
 pub enum Intrinsic {
     ArrayNewOfSize,
     ArrayWithValues,
+    ArrayFill,
+    ArrayCopy,
     ArrayLen,
     ArrayGet,
     ArraySet,
@@ -324,15 +359,25 @@ pub enum Intrinsic {
     EnumEq,
     EnumNe,
 
+    ObjectEq,
+    ObjectNe,
+    EnumOrdinal,
+    EnumValues,
+
     Int32Eq,
     Int32Cmp,
 
     Int32Add,
     Int32AddUnchecked,
+    Int32AddSaturating,
+    Int32AddOverflows,
     Int32Sub,
     Int32SubUnchecked,
+    Int32SubSaturating,
+    Int32SubOverflows,
     Int32Mul,
     Int32MulUnchecked,
+    Int32MulOverflows,
     Int32Div,
     Int32Mod,
 
@@ -370,10 +415,15 @@ pub enum Intrinsic {
 
     Int64Add,
     Int64AddUnchecked,
+    Int64AddSaturating,
+    Int64AddOverflows,
     Int64Sub,
     Int64SubUnchecked,
+    Int64SubSaturating,
+    Int64SubOverflows,
     Int64Mul,
     Int64MulUnchecked,
+    Int64MulOverflows,
     Int64Div,
     Int64Mod,
 
@@ -411,6 +461,7 @@ pub enum Intrinsic {
     Float32Sub,
     Float32Mul,
     Float32Div,
+    Float32Mod,
 
     Float32Plus,
     Float32Neg,
@@ -424,6 +475,9 @@ pub enum Intrinsic {
 
     Float32Sqrt,
 
+    Float32Min,
+    Float32Max,
+
     Float64ToInt32,
     Float64ToInt64,
     DemoteFloat64ToFloat32,
@@ -436,6 +490,7 @@ pub enum Intrinsic {
     Float64Sub,
     Float64Mul,
     Float64Div,
+    Float64Mod,
 
     Float64Plus,
     Float64Neg,
@@ -449,6 +504,9 @@ pub enum Intrinsic {
 
     Float64Sqrt,
 
+    Float64Min,
+    Float64Max,
+
     OptionGetOrPanic,
     OptionIsNone,
     OptionIsSome,
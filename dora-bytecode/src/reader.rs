@@ -33,7 +33,7 @@ impl<'a> BytecodeReader<'a> {
         reader.read_opcode()
     }
 
-    fn offset(&self) -> usize {
+    pub fn offset(&self) -> usize {
         self.offset
     }
 
@@ -50,12 +50,24 @@ impl<'a> BytecodeReader<'a> {
                 let rhs = self.read_register();
                 BytecodeInstruction::Add { dest, lhs, rhs }
             }
+            BytecodeOpcode::AddSaturating => {
+                let dest = self.read_register();
+                let lhs = self.read_register();
+                let rhs = self.read_register();
+                BytecodeInstruction::AddSaturating { dest, lhs, rhs }
+            }
             BytecodeOpcode::Sub => {
                 let dest = self.read_register();
                 let lhs = self.read_register();
                 let rhs = self.read_register();
                 BytecodeInstruction::Sub { dest, lhs, rhs }
             }
+            BytecodeOpcode::SubSaturating => {
+                let dest = self.read_register();
+                let lhs = self.read_register();
+                let rhs = self.read_register();
+                BytecodeInstruction::SubSaturating { dest, lhs, rhs }
+            }
             BytecodeOpcode::Neg => {
                 let dest = self.read_register();
                 let src = self.read_register();
@@ -304,6 +316,11 @@ impl<'a> BytecodeReader<'a> {
                 let idx = self.read_const_pool_idx();
                 BytecodeInstruction::JumpIfTrueConst { opnd, idx }
             }
+            BytecodeOpcode::Switch => {
+                let opnd = self.read_register();
+                let idx = self.read_const_pool_idx();
+                BytecodeInstruction::Switch { opnd, idx }
+            }
             BytecodeOpcode::Jump => {
                 let offset = self.read_offset();
                 BytecodeInstruction::Jump { offset }
@@ -412,11 +429,41 @@ impl<'a> BytecodeReader<'a> {
                 BytecodeInstruction::StoreArray { src, arr, idx }
             }
 
+            BytecodeOpcode::ArrayFill => {
+                let src = self.read_register();
+                let arr = self.read_register();
+                let length = self.read_register();
+                BytecodeInstruction::ArrayFill { src, arr, length }
+            }
+
             BytecodeOpcode::LoadTraitObjectValue => {
                 let dest = self.read_register();
                 let object = self.read_register();
                 BytecodeInstruction::LoadTraitObjectValue { dest, object }
             }
+            BytecodeOpcode::TestTraitObject => {
+                let dest = self.read_register();
+                let idx = self.read_const_pool_idx();
+                let src = self.read_register();
+                BytecodeInstruction::TestTraitObject { dest, idx, src }
+            }
+            BytecodeOpcode::CastTraitObject => {
+                let dest = self.read_register();
+                let idx = self.read_const_pool_idx();
+                let src = self.read_register();
+                BytecodeInstruction::CastTraitObject { dest, idx, src }
+            }
+
+            BytecodeOpcode::CheckedCast => {
+                let dest = self.read_register();
+                let src = self.read_register();
+                BytecodeInstruction::CheckedCast { dest, src }
+            }
+
+            BytecodeOpcode::Throw => {
+                let opnd = self.read_register();
+                BytecodeInstruction::Throw { opnd }
+            }
 
             BytecodeOpcode::Ret => {
                 let opnd = self.read_register();
@@ -524,9 +571,15 @@ where
             BytecodeInstruction::Add { dest, lhs, rhs } => {
                 self.visitor.visit_add(dest, lhs, rhs);
             }
+            BytecodeInstruction::AddSaturating { dest, lhs, rhs } => {
+                self.visitor.visit_add_saturating(dest, lhs, rhs);
+            }
             BytecodeInstruction::Sub { dest, lhs, rhs } => {
                 self.visitor.visit_sub(dest, lhs, rhs);
             }
+            BytecodeInstruction::SubSaturating { dest, lhs, rhs } => {
+                self.visitor.visit_sub_saturating(dest, lhs, rhs);
+            }
             BytecodeInstruction::Neg { dest, src } => {
                 self.visitor.visit_neg(dest, src);
             }
@@ -675,6 +728,9 @@ where
             BytecodeInstruction::JumpIfTrueConst { opnd, idx } => {
                 self.visitor.visit_jump_if_true_const(opnd, idx);
             }
+            BytecodeInstruction::Switch { opnd, idx } => {
+                self.visitor.visit_switch(opnd, idx);
+            }
             BytecodeInstruction::Jump { offset } => {
                 self.visitor.visit_jump(offset);
             }
@@ -743,9 +799,27 @@ where
                 self.visitor.visit_store_array(src, arr, idx);
             }
 
+            BytecodeInstruction::ArrayFill { src, arr, length } => {
+                self.visitor.visit_array_fill(src, arr, length);
+            }
+
             BytecodeInstruction::LoadTraitObjectValue { dest, object } => {
                 self.visitor.visit_load_trait_object_value(dest, object);
             }
+            BytecodeInstruction::TestTraitObject { dest, idx, src } => {
+                self.visitor.visit_test_trait_object(dest, idx, src);
+            }
+            BytecodeInstruction::CastTraitObject { dest, idx, src } => {
+                self.visitor.visit_cast_trait_object(dest, idx, src);
+            }
+
+            BytecodeInstruction::CheckedCast { dest, src } => {
+                self.visitor.visit_checked_cast(dest, src);
+            }
+
+            BytecodeInstruction::Throw { opnd } => {
+                self.visitor.visit_throw(opnd);
+            }
 
             BytecodeInstruction::Ret { opnd } => {
                 self.visitor.visit_ret(opnd);
@@ -761,10 +835,18 @@ pub trait BytecodeVisitor {
         unimplemented!();
     }
 
+    fn visit_add_saturating(&mut self, _dest: Register, _lhs: Register, _rhs: Register) {
+        unimplemented!();
+    }
+
     fn visit_sub(&mut self, _dest: Register, _lhs: Register, _rhs: Register) {
         unimplemented!();
     }
 
+    fn visit_sub_saturating(&mut self, _dest: Register, _lhs: Register, _rhs: Register) {
+        unimplemented!();
+    }
+
     fn visit_neg(&mut self, _dest: Register, _src: Register) {
         unimplemented!();
     }
@@ -927,6 +1009,9 @@ pub trait BytecodeVisitor {
     fn visit_jump_if_true_const(&mut self, _opnd: Register, _idx: ConstPoolIdx) {
         unimplemented!();
     }
+    fn visit_switch(&mut self, _opnd: Register, _idx: ConstPoolIdx) {
+        unimplemented!();
+    }
     fn visit_jump_loop(&mut self, _offset: u32) {
         unimplemented!();
     }
@@ -1007,10 +1092,30 @@ pub trait BytecodeVisitor {
         unimplemented!();
     }
 
+    fn visit_array_fill(&mut self, _src: Register, _arr: Register, _length: Register) {
+        unimplemented!();
+    }
+
     fn visit_load_trait_object_value(&mut self, _dest: Register, _object: Register) {
         unimplemented!();
     }
 
+    fn visit_test_trait_object(&mut self, _dest: Register, _idx: ConstPoolIdx, _src: Register) {
+        unimplemented!();
+    }
+
+    fn visit_cast_trait_object(&mut self, _dest: Register, _idx: ConstPoolIdx, _src: Register) {
+        unimplemented!();
+    }
+
+    fn visit_checked_cast(&mut self, _dest: Register, _src: Register) {
+        unimplemented!();
+    }
+
+    fn visit_throw(&mut self, _opnd: Register) {
+        unimplemented!();
+    }
+
     fn visit_ret(&mut self, _opnd: Register) {
         unimplemented!();
     }
@@ -0,0 +1,576 @@
+use std::convert::TryFrom;
+
+use crate::{
+    BytecodeExceptionHandler, BytecodeFunction, BytecodeOffset, BytecodeType, BytecodeTypeArray,
+    BytecodeTypeKind, ClassId, ConstPoolEntry, ConstPoolOpcode, EnumId, FunctionId, Location,
+    Program, Register, StructId, TraitId,
+};
+
+// Layout of a serialized module:
+//   magic, format version, source hash,
+//   program metadata (bincode, without bytecode),
+//   bytecode of all functions (stable format below).
+// The version needs to be bumped whenever the encoding changes.
+pub const MODULE_MAGIC: [u8; 4] = *b"DBCM";
+pub const MODULE_VERSION: u32 = 5;
+
+pub struct BytecodeModule {
+    pub source_hash: u64,
+    pub program: Program,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ModuleError {
+    InvalidHeader,
+    UnsupportedVersion(u32),
+    Corrupted,
+}
+
+pub fn encode_module(program: &mut Program, source_hash: u64) -> Vec<u8> {
+    let mut buffer = ByteBuffer::new();
+    buffer.emit_bytes(&MODULE_MAGIC);
+    buffer.emit_u32(MODULE_VERSION);
+    buffer.emit_u64(source_hash);
+
+    // bytecode is encoded separately, remove it temporarily from the metadata
+    let bytecode: Vec<Option<BytecodeFunction>> = program
+        .functions
+        .iter_mut()
+        .map(|fct| fct.bytecode.take())
+        .collect();
+
+    let config = bincode::config::standard();
+    let metadata = bincode::encode_to_vec(&*program, config).expect("serialization failed");
+
+    for (fct, bytecode) in program.functions.iter_mut().zip(bytecode) {
+        fct.bytecode = bytecode;
+    }
+
+    buffer.emit_u32(metadata.len() as u32);
+    buffer.emit_bytes(&metadata);
+
+    buffer.emit_u32(program.functions.len() as u32);
+
+    for fct in &program.functions {
+        if let Some(ref bytecode) = fct.bytecode {
+            buffer.emit_u8(1);
+            encode_bytecode_function(bytecode, &mut buffer);
+        } else {
+            buffer.emit_u8(0);
+        }
+    }
+
+    buffer.data
+}
+
+pub fn decode_module(data: &[u8]) -> Result<BytecodeModule, ModuleError> {
+    let mut reader = ByteReader::new(data);
+
+    if reader.read_bytes(MODULE_MAGIC.len()) != Ok(&MODULE_MAGIC[..]) {
+        return Err(ModuleError::InvalidHeader);
+    }
+
+    let version = reader.read_u32()?;
+
+    if version != MODULE_VERSION {
+        return Err(ModuleError::UnsupportedVersion(version));
+    }
+
+    let source_hash = reader.read_u64()?;
+
+    let metadata_len = reader.read_u32()? as usize;
+    let metadata = reader.read_bytes(metadata_len)?;
+
+    let config = bincode::config::standard();
+    let (mut program, decoded_len): (Program, usize) =
+        bincode::decode_from_slice(metadata, config).map_err(|_| ModuleError::Corrupted)?;
+
+    if decoded_len != metadata_len {
+        return Err(ModuleError::Corrupted);
+    }
+
+    let functions = reader.read_u32()? as usize;
+
+    if functions != program.functions.len() {
+        return Err(ModuleError::Corrupted);
+    }
+
+    for fct in &mut program.functions {
+        fct.bytecode = match reader.read_u8()? {
+            0 => None,
+            1 => Some(decode_bytecode_function(&mut reader)?),
+            _ => return Err(ModuleError::Corrupted),
+        };
+    }
+
+    if !reader.is_empty() {
+        return Err(ModuleError::Corrupted);
+    }
+
+    Ok(BytecodeModule {
+        source_hash,
+        program,
+    })
+}
+
+fn encode_bytecode_function(fct: &BytecodeFunction, buffer: &mut ByteBuffer) {
+    buffer.emit_u32(fct.code().len() as u32);
+    buffer.emit_bytes(fct.code());
+
+    buffer.emit_u32(fct.registers().len() as u32);
+
+    for ty in fct.registers() {
+        encode_bytecode_type(ty, buffer);
+    }
+
+    buffer.emit_u32(fct.const_pool_entries().len() as u32);
+
+    for entry in fct.const_pool_entries() {
+        encode_const_pool_entry(entry, buffer);
+    }
+
+    buffer.emit_u32(fct.arguments());
+
+    buffer.emit_u32(fct.locations().len() as u32);
+
+    for (offset, location) in fct.locations() {
+        buffer.emit_u32(offset.to_u32());
+        buffer.emit_u32(location.line());
+        buffer.emit_u32(location.column());
+    }
+
+    buffer.emit_u32(fct.exception_handlers().len() as u32);
+
+    for handler in fct.exception_handlers() {
+        buffer.emit_u32(handler.start.to_u32());
+        buffer.emit_u32(handler.end.to_u32());
+        buffer.emit_u32(handler.handler.to_u32());
+        encode_bytecode_type(&handler.exception_type, buffer);
+        buffer.emit_u32(handler.register.to_usize() as u32);
+    }
+}
+
+fn decode_bytecode_function(reader: &mut ByteReader) -> Result<BytecodeFunction, ModuleError> {
+    let code_len = reader.read_u32()? as usize;
+    let code = reader.read_bytes(code_len)?.to_vec();
+
+    let registers_len = reader.read_u32()?;
+    let mut registers = Vec::new();
+
+    for _ in 0..registers_len {
+        registers.push(decode_bytecode_type(reader)?);
+    }
+
+    let const_pool_len = reader.read_u32()?;
+    let mut const_pool = Vec::new();
+
+    for _ in 0..const_pool_len {
+        const_pool.push(decode_const_pool_entry(reader)?);
+    }
+
+    let arguments = reader.read_u32()?;
+
+    let locations_len = reader.read_u32()?;
+    let mut locations = Vec::new();
+
+    for _ in 0..locations_len {
+        let offset = BytecodeOffset(reader.read_u32()?);
+        let line = reader.read_u32()?;
+        let column = reader.read_u32()?;
+        locations.push((offset, Location::new(line, column)));
+    }
+
+    let exception_handlers_len = reader.read_u32()?;
+    let mut exception_handlers = Vec::new();
+
+    for _ in 0..exception_handlers_len {
+        let start = BytecodeOffset(reader.read_u32()?);
+        let end = BytecodeOffset(reader.read_u32()?);
+        let handler = BytecodeOffset(reader.read_u32()?);
+        let exception_type = decode_bytecode_type(reader)?;
+        let register = Register(reader.read_u32()? as usize);
+        exception_handlers.push(BytecodeExceptionHandler {
+            start,
+            end,
+            handler,
+            exception_type,
+            register,
+        });
+    }
+
+    Ok(BytecodeFunction::new(
+        code,
+        const_pool,
+        registers,
+        arguments,
+        locations,
+        exception_handlers,
+    ))
+}
+
+fn encode_bytecode_type_array(types: &BytecodeTypeArray, buffer: &mut ByteBuffer) {
+    buffer.emit_u32(types.len() as u32);
+
+    for ty in types.iter() {
+        encode_bytecode_type(&ty, buffer);
+    }
+}
+
+fn decode_bytecode_type_array(reader: &mut ByteReader) -> Result<BytecodeTypeArray, ModuleError> {
+    let len = reader.read_u32()?;
+    let mut types = Vec::new();
+
+    for _ in 0..len {
+        types.push(decode_bytecode_type(reader)?);
+    }
+
+    Ok(BytecodeTypeArray::new(types))
+}
+
+fn encode_bytecode_type(ty: &BytecodeType, buffer: &mut ByteBuffer) {
+    match ty {
+        BytecodeType::This => unreachable!(),
+        _ => buffer.emit_u8(ty.kind().into()),
+    }
+
+    match ty {
+        BytecodeType::Tuple(subtypes) => {
+            encode_bytecode_type_array(subtypes, buffer);
+        }
+        BytecodeType::TypeParam(id) => {
+            buffer.emit_u32(*id);
+        }
+        BytecodeType::Enum(enum_id, type_params) => {
+            buffer.emit_u32(enum_id.0);
+            encode_bytecode_type_array(type_params, buffer);
+        }
+        BytecodeType::Struct(struct_id, type_params) => {
+            buffer.emit_u32(struct_id.0);
+            encode_bytecode_type_array(type_params, buffer);
+        }
+        BytecodeType::Class(cls_id, type_params) => {
+            buffer.emit_u32(cls_id.0);
+            encode_bytecode_type_array(type_params, buffer);
+        }
+        BytecodeType::Trait(trait_id, type_params) => {
+            buffer.emit_u32(trait_id.0);
+            encode_bytecode_type_array(type_params, buffer);
+        }
+        BytecodeType::Lambda(params, return_type) => {
+            encode_bytecode_type_array(params, buffer);
+            encode_bytecode_type(return_type, buffer);
+        }
+        _ => {}
+    }
+}
+
+fn decode_bytecode_type(reader: &mut ByteReader) -> Result<BytecodeType, ModuleError> {
+    let kind = BytecodeTypeKind::try_from(reader.read_u8()?).map_err(|_| ModuleError::Corrupted)?;
+
+    let ty = match kind {
+        BytecodeTypeKind::Unit => BytecodeType::Unit,
+        BytecodeTypeKind::Bool => BytecodeType::Bool,
+        BytecodeTypeKind::UInt8 => BytecodeType::UInt8,
+        BytecodeTypeKind::UInt16 => BytecodeType::UInt16,
+        BytecodeTypeKind::UInt32 => BytecodeType::UInt32,
+        BytecodeTypeKind::UInt64 => BytecodeType::UInt64,
+        BytecodeTypeKind::Char => BytecodeType::Char,
+        BytecodeTypeKind::Int32 => BytecodeType::Int32,
+        BytecodeTypeKind::Int64 => BytecodeType::Int64,
+        BytecodeTypeKind::Float32 => BytecodeType::Float32,
+        BytecodeTypeKind::Float64 => BytecodeType::Float64,
+        BytecodeTypeKind::Ptr => BytecodeType::Ptr,
+        BytecodeTypeKind::Tuple => BytecodeType::Tuple(decode_bytecode_type_array(reader)?),
+        BytecodeTypeKind::TypeParam => BytecodeType::TypeParam(reader.read_u32()?),
+        BytecodeTypeKind::Enum => {
+            let enum_id = EnumId(reader.read_u32()?);
+            BytecodeType::Enum(enum_id, decode_bytecode_type_array(reader)?)
+        }
+        BytecodeTypeKind::Struct => {
+            let struct_id = StructId(reader.read_u32()?);
+            BytecodeType::Struct(struct_id, decode_bytecode_type_array(reader)?)
+        }
+        BytecodeTypeKind::Class => {
+            let cls_id = ClassId(reader.read_u32()?);
+            BytecodeType::Class(cls_id, decode_bytecode_type_array(reader)?)
+        }
+        BytecodeTypeKind::Trait => {
+            let trait_id = TraitId(reader.read_u32()?);
+            BytecodeType::Trait(trait_id, decode_bytecode_type_array(reader)?)
+        }
+        BytecodeTypeKind::Lambda => {
+            let params = decode_bytecode_type_array(reader)?;
+            let return_type = decode_bytecode_type(reader)?;
+            BytecodeType::Lambda(params, Box::new(return_type))
+        }
+    };
+
+    Ok(ty)
+}
+
+fn encode_const_pool_entry(entry: &ConstPoolEntry, buffer: &mut ByteBuffer) {
+    match entry {
+        ConstPoolEntry::String(value) => {
+            buffer.emit_u8(ConstPoolOpcode::String.into());
+            buffer.emit_u32(value.len() as u32);
+            buffer.emit_bytes(value.as_bytes());
+        }
+        ConstPoolEntry::Float32(value) => {
+            buffer.emit_u8(ConstPoolOpcode::Float32.into());
+            buffer.emit_u32(value.to_bits());
+        }
+        ConstPoolEntry::Float64(value) => {
+            buffer.emit_u8(ConstPoolOpcode::Float64.into());
+            buffer.emit_u64(value.to_bits());
+        }
+        ConstPoolEntry::Int32(value) => {
+            buffer.emit_u8(ConstPoolOpcode::Int32.into());
+            buffer.emit_u32(*value as u32);
+        }
+        ConstPoolEntry::Int64(value) => {
+            buffer.emit_u8(ConstPoolOpcode::Int64.into());
+            buffer.emit_u64(*value as u64);
+        }
+        ConstPoolEntry::Char(value) => {
+            buffer.emit_u8(ConstPoolOpcode::Char.into());
+            buffer.emit_u32(*value as u32);
+        }
+        ConstPoolEntry::Class(cls_id, type_params) => {
+            buffer.emit_u8(ConstPoolOpcode::Class.into());
+            buffer.emit_u32(cls_id.0);
+            encode_bytecode_type_array(type_params, buffer);
+        }
+        ConstPoolEntry::Field(cls_id, type_params, field_idx) => {
+            buffer.emit_u8(ConstPoolOpcode::Field.into());
+            buffer.emit_u32(cls_id.0);
+            encode_bytecode_type_array(type_params, buffer);
+            buffer.emit_u32(*field_idx);
+        }
+        ConstPoolEntry::Fct(fct_id, type_params) => {
+            buffer.emit_u8(ConstPoolOpcode::Fct.into());
+            buffer.emit_u32(fct_id.0);
+            encode_bytecode_type_array(type_params, buffer);
+        }
+        ConstPoolEntry::Generic(tp_id, fct_id, type_params) => {
+            buffer.emit_u8(ConstPoolOpcode::Generic.into());
+            buffer.emit_u32(*tp_id);
+            buffer.emit_u32(fct_id.0);
+            encode_bytecode_type_array(type_params, buffer);
+        }
+        ConstPoolEntry::Enum(enum_id, type_params) => {
+            buffer.emit_u8(ConstPoolOpcode::Enum.into());
+            buffer.emit_u32(enum_id.0);
+            encode_bytecode_type_array(type_params, buffer);
+        }
+        ConstPoolEntry::EnumVariant(enum_id, type_params, variant_idx) => {
+            buffer.emit_u8(ConstPoolOpcode::EnumVariant.into());
+            buffer.emit_u32(enum_id.0);
+            encode_bytecode_type_array(type_params, buffer);
+            buffer.emit_u32(*variant_idx);
+        }
+        ConstPoolEntry::EnumElement(enum_id, type_params, variant_idx, element_idx) => {
+            buffer.emit_u8(ConstPoolOpcode::EnumElement.into());
+            buffer.emit_u32(enum_id.0);
+            encode_bytecode_type_array(type_params, buffer);
+            buffer.emit_u32(*variant_idx);
+            buffer.emit_u32(*element_idx);
+        }
+        ConstPoolEntry::Struct(struct_id, type_params) => {
+            buffer.emit_u8(ConstPoolOpcode::Struct.into());
+            buffer.emit_u32(struct_id.0);
+            encode_bytecode_type_array(type_params, buffer);
+        }
+        ConstPoolEntry::StructField(struct_id, type_params, field_idx) => {
+            buffer.emit_u8(ConstPoolOpcode::StructField.into());
+            buffer.emit_u32(struct_id.0);
+            encode_bytecode_type_array(type_params, buffer);
+            buffer.emit_u32(*field_idx);
+        }
+        ConstPoolEntry::Trait(trait_id, type_params, object_ty) => {
+            buffer.emit_u8(ConstPoolOpcode::Trait.into());
+            buffer.emit_u32(trait_id.0);
+            encode_bytecode_type_array(type_params, buffer);
+            encode_bytecode_type(object_ty, buffer);
+        }
+        ConstPoolEntry::TupleElement(tuple_ty, element_idx) => {
+            buffer.emit_u8(ConstPoolOpcode::TupleElement.into());
+            encode_bytecode_type(tuple_ty, buffer);
+            buffer.emit_u32(*element_idx);
+        }
+        ConstPoolEntry::Tuple(subtypes) => {
+            buffer.emit_u8(ConstPoolOpcode::Tuple.into());
+            encode_bytecode_type_array(subtypes, buffer);
+        }
+        ConstPoolEntry::Lambda(params, return_type) => {
+            buffer.emit_u8(ConstPoolOpcode::Lambda.into());
+            encode_bytecode_type_array(params, buffer);
+            encode_bytecode_type(return_type, buffer);
+        }
+        ConstPoolEntry::JumpTable(low, targets) => {
+            buffer.emit_u8(ConstPoolOpcode::JumpTable.into());
+            buffer.emit_u64(*low as u64);
+            buffer.emit_u32(targets.len() as u32);
+
+            for &target in targets {
+                buffer.emit_u32(target);
+            }
+        }
+    }
+}
+
+fn decode_const_pool_entry(reader: &mut ByteReader) -> Result<ConstPoolEntry, ModuleError> {
+    let opcode =
+        ConstPoolOpcode::try_from(reader.read_u8()?).map_err(|_| ModuleError::Corrupted)?;
+
+    let entry = match opcode {
+        ConstPoolOpcode::String => {
+            let len = reader.read_u32()? as usize;
+            let bytes = reader.read_bytes(len)?.to_vec();
+            let value = String::from_utf8(bytes).map_err(|_| ModuleError::Corrupted)?;
+            ConstPoolEntry::String(value)
+        }
+        ConstPoolOpcode::Float32 => ConstPoolEntry::Float32(f32::from_bits(reader.read_u32()?)),
+        ConstPoolOpcode::Float64 => ConstPoolEntry::Float64(f64::from_bits(reader.read_u64()?)),
+        ConstPoolOpcode::Int32 => ConstPoolEntry::Int32(reader.read_u32()? as i32),
+        ConstPoolOpcode::Int64 => ConstPoolEntry::Int64(reader.read_u64()? as i64),
+        ConstPoolOpcode::Char => {
+            let value = char::from_u32(reader.read_u32()?).ok_or(ModuleError::Corrupted)?;
+            ConstPoolEntry::Char(value)
+        }
+        ConstPoolOpcode::Class => {
+            let cls_id = ClassId(reader.read_u32()?);
+            ConstPoolEntry::Class(cls_id, decode_bytecode_type_array(reader)?)
+        }
+        ConstPoolOpcode::Field => {
+            let cls_id = ClassId(reader.read_u32()?);
+            let type_params = decode_bytecode_type_array(reader)?;
+            ConstPoolEntry::Field(cls_id, type_params, reader.read_u32()?)
+        }
+        ConstPoolOpcode::Fct => {
+            let fct_id = FunctionId(reader.read_u32()?);
+            ConstPoolEntry::Fct(fct_id, decode_bytecode_type_array(reader)?)
+        }
+        ConstPoolOpcode::Generic => {
+            let tp_id = reader.read_u32()?;
+            let fct_id = FunctionId(reader.read_u32()?);
+            ConstPoolEntry::Generic(tp_id, fct_id, decode_bytecode_type_array(reader)?)
+        }
+        ConstPoolOpcode::Enum => {
+            let enum_id = EnumId(reader.read_u32()?);
+            ConstPoolEntry::Enum(enum_id, decode_bytecode_type_array(reader)?)
+        }
+        ConstPoolOpcode::EnumVariant => {
+            let enum_id = EnumId(reader.read_u32()?);
+            let type_params = decode_bytecode_type_array(reader)?;
+            ConstPoolEntry::EnumVariant(enum_id, type_params, reader.read_u32()?)
+        }
+        ConstPoolOpcode::EnumElement => {
+            let enum_id = EnumId(reader.read_u32()?);
+            let type_params = decode_bytecode_type_array(reader)?;
+            let variant_idx = reader.read_u32()?;
+            let element_idx = reader.read_u32()?;
+            ConstPoolEntry::EnumElement(enum_id, type_params, variant_idx, element_idx)
+        }
+        ConstPoolOpcode::Struct => {
+            let struct_id = StructId(reader.read_u32()?);
+            ConstPoolEntry::Struct(struct_id, decode_bytecode_type_array(reader)?)
+        }
+        ConstPoolOpcode::StructField => {
+            let struct_id = StructId(reader.read_u32()?);
+            let type_params = decode_bytecode_type_array(reader)?;
+            ConstPoolEntry::StructField(struct_id, type_params, reader.read_u32()?)
+        }
+        ConstPoolOpcode::Trait => {
+            let trait_id = TraitId(reader.read_u32()?);
+            let type_params = decode_bytecode_type_array(reader)?;
+            let object_ty = decode_bytecode_type(reader)?;
+            ConstPoolEntry::Trait(trait_id, type_params, object_ty)
+        }
+        ConstPoolOpcode::TupleElement => {
+            let tuple_ty = decode_bytecode_type(reader)?;
+            ConstPoolEntry::TupleElement(tuple_ty, reader.read_u32()?)
+        }
+        ConstPoolOpcode::Tuple => ConstPoolEntry::Tuple(decode_bytecode_type_array(reader)?),
+        ConstPoolOpcode::Lambda => {
+            let params = decode_bytecode_type_array(reader)?;
+            let return_type = decode_bytecode_type(reader)?;
+            ConstPoolEntry::Lambda(params, return_type)
+        }
+        ConstPoolOpcode::JumpTable => {
+            let low = reader.read_u64()? as i64;
+            let len = reader.read_u32()?;
+            let mut targets = Vec::new();
+
+            for _ in 0..len {
+                targets.push(reader.read_u32()?);
+            }
+
+            ConstPoolEntry::JumpTable(low, targets)
+        }
+        ConstPoolOpcode::FieldFixed => return Err(ModuleError::Corrupted),
+    };
+
+    Ok(entry)
+}
+
+struct ByteBuffer {
+    data: Vec<u8>,
+}
+
+impl ByteBuffer {
+    fn new() -> ByteBuffer {
+        ByteBuffer { data: Vec::new() }
+    }
+
+    fn emit_u8(&mut self, value: u8) {
+        self.data.push(value);
+    }
+
+    fn emit_u32(&mut self, value: u32) {
+        self.data.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn emit_u64(&mut self, value: u64) {
+        self.data.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn emit_bytes(&mut self, bytes: &[u8]) {
+        self.data.extend_from_slice(bytes);
+    }
+}
+
+struct ByteReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    fn new(data: &'a [u8]) -> ByteReader<'a> {
+        ByteReader { data, pos: 0 }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.pos == self.data.len()
+    }
+
+    fn read_bytes(&mut self, len: usize) -> Result<&'a [u8], ModuleError> {
+        let end = self.pos.checked_add(len).ok_or(ModuleError::Corrupted)?;
+        let bytes = self.data.get(self.pos..end).ok_or(ModuleError::Corrupted)?;
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn read_u8(&mut self) -> Result<u8, ModuleError> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    fn read_u32(&mut self) -> Result<u32, ModuleError> {
+        let bytes = self.read_bytes(4)?;
+        Ok(u32::from_le_bytes(bytes.try_into().expect("wrong size")))
+    }
+
+    fn read_u64(&mut self) -> Result<u64, ModuleError> {
+        let bytes = self.read_bytes(8)?;
+        Ok(u64::from_le_bytes(bytes.try_into().expect("wrong size")))
+    }
+}
@@ -1,4 +1,10 @@
-use crate::{read, BytecodeOpcode, BytecodeVisitor, BytecodeWriter, ConstPoolIdx, Register};
+use crate::{
+    decode_module, encode_module, read, verify, BoundsCheckElimination, BytecodeFunction,
+    BytecodeOffset, BytecodeOpcode, BytecodeType, BytecodeTypeArray, BytecodeVisitor,
+    BytecodeWriter, ClassId, ConstPoolEntry, ConstPoolIdx, ConstPoolOpcode, EnumId, EscapeAnalysis,
+    FunctionData, FunctionId, FunctionKind, Liveness, Location, ModuleError, ModuleId, PackageId,
+    Program, Register, SourceFileId, StructId, TraitId, TypeParamData, VerifyError,
+};
 
 #[test]
 fn test_ret() {
@@ -282,3 +288,601 @@ fn test_cond_jump_wide() {
     read(fct.code(), &mut visitor);
     assert!(visitor.found);
 }
+
+#[test]
+fn test_switch() {
+    struct TestVisitor {
+        found: bool,
+    }
+    impl BytecodeVisitor for TestVisitor {
+        fn visit_switch(&mut self, opnd: Register, idx: ConstPoolIdx) {
+            assert_eq!(opnd, Register(3));
+            assert_eq!(idx, ConstPoolIdx(0));
+            self.found = true;
+        }
+        fn visit_ret(&mut self, _opnd: Register) {}
+    }
+    let mut writer = BytecodeWriter::new();
+    let first = writer.create_label();
+    let second = writer.create_label();
+    writer.emit_switch(Register(3), -1, vec![first, second, first]);
+    writer.bind_label(first);
+    writer.emit_ret(Register(0));
+    writer.bind_label(second);
+    writer.emit_ret(Register(1));
+    let fct = writer.generate();
+    assert_eq!(
+        fct.code(),
+        &[
+            BytecodeOpcode::Switch.into(),
+            3,
+            0,
+            BytecodeOpcode::Ret.into(),
+            0,
+            BytecodeOpcode::Ret.into(),
+            1
+        ]
+    );
+    assert_eq!(
+        fct.const_pool(ConstPoolIdx(0)),
+        &ConstPoolEntry::JumpTable(-1, vec![3, 5, 3])
+    );
+    let mut visitor = TestVisitor { found: false };
+    read(fct.code(), &mut visitor);
+    assert!(visitor.found);
+}
+
+#[test]
+fn test_rollback() {
+    let mut writer = BytecodeWriter::new();
+    writer.emit_const_int32(Register(0), 1);
+    let checkpoint = writer.checkpoint();
+    let lbl = writer.create_label();
+    writer.emit_const_int64(Register(1), 2);
+    writer.emit_jump_if_false(Register(2), lbl);
+    writer.bind_label(lbl);
+    writer.rollback(checkpoint);
+    writer.emit_ret(Register(0));
+    let fct = writer.generate();
+    assert_eq!(
+        fct.code(),
+        &[
+            BytecodeOpcode::ConstInt32.into(),
+            0,
+            0,
+            BytecodeOpcode::Ret.into(),
+            0
+        ]
+    );
+    assert_eq!(fct.const_pool_entries(), &[ConstPoolEntry::Int32(1)]);
+}
+
+#[test]
+fn test_saturating() {
+    struct TestVisitor {
+        found: usize,
+    }
+    impl BytecodeVisitor for TestVisitor {
+        fn visit_add_saturating(&mut self, dest: Register, lhs: Register, rhs: Register) {
+            assert_eq!((dest, lhs, rhs), (Register(2), Register(0), Register(1)));
+            self.found += 1;
+        }
+        fn visit_sub_saturating(&mut self, dest: Register, lhs: Register, rhs: Register) {
+            assert_eq!((dest, lhs, rhs), (Register(3), Register(2), Register(1)));
+            self.found += 1;
+        }
+    }
+    let mut writer = BytecodeWriter::new();
+    writer.emit_add_saturating(Register(2), Register(0), Register(1));
+    writer.emit_sub_saturating(Register(3), Register(2), Register(1));
+    let fct = writer.generate();
+    assert_eq!(
+        fct.code(),
+        &[
+            BytecodeOpcode::AddSaturating.into(),
+            2,
+            0,
+            1,
+            BytecodeOpcode::SubSaturating.into(),
+            3,
+            2,
+            1
+        ]
+    );
+    let mut visitor = TestVisitor { found: 0 };
+    read(fct.code(), &mut visitor);
+    assert_eq!(2, visitor.found);
+}
+
+#[test]
+fn test_module_roundtrip() {
+    let mut writer = BytecodeWriter::new();
+    let ty_params = BytecodeTypeArray::new(vec![BytecodeType::Int32, BytecodeType::TypeParam(0)]);
+    let tuple_ty = BytecodeType::Tuple(BytecodeTypeArray::new(vec![
+        BytecodeType::Bool,
+        BytecodeType::Float64,
+    ]));
+    let lambda_ty = BytecodeType::Lambda(
+        BytecodeTypeArray::new(vec![BytecodeType::Char]),
+        Box::new(BytecodeType::UInt8),
+    );
+
+    let entries = vec![
+        ConstPoolEntry::String("hello".into()),
+        ConstPoolEntry::Float32(1.5),
+        ConstPoolEntry::Float64(-2.25),
+        ConstPoolEntry::Int32(-17),
+        ConstPoolEntry::Int64(i64::MIN),
+        ConstPoolEntry::Char('ä'),
+        ConstPoolEntry::Class(ClassId(1), ty_params.clone()),
+        ConstPoolEntry::Field(ClassId(2), ty_params.clone(), 3),
+        ConstPoolEntry::Fct(FunctionId(4), BytecodeTypeArray::empty()),
+        ConstPoolEntry::Generic(0, FunctionId(5), ty_params.clone()),
+        ConstPoolEntry::Enum(EnumId(6), ty_params.clone()),
+        ConstPoolEntry::EnumVariant(EnumId(7), ty_params.clone(), 1),
+        ConstPoolEntry::EnumElement(EnumId(8), ty_params.clone(), 1, 2),
+        ConstPoolEntry::Struct(StructId(9), ty_params.clone()),
+        ConstPoolEntry::StructField(StructId(10), ty_params.clone(), 4),
+        ConstPoolEntry::Trait(
+            TraitId(11),
+            BytecodeTypeArray::empty(),
+            BytecodeType::Class(ClassId(1), BytecodeTypeArray::empty()),
+        ),
+        ConstPoolEntry::TupleElement(tuple_ty.clone(), 1),
+        ConstPoolEntry::Tuple(BytecodeTypeArray::new(vec![
+            tuple_ty.clone(),
+            lambda_ty.clone(),
+        ])),
+        ConstPoolEntry::Lambda(
+            BytecodeTypeArray::new(vec![BytecodeType::Int64]),
+            BytecodeType::Unit,
+        ),
+        ConstPoolEntry::JumpTable(-1, vec![0, 4, 8]),
+    ];
+
+    for entry in entries {
+        writer.add_const(entry);
+    }
+
+    let r0 = writer.add_register(tuple_ty);
+    let r1 = writer.add_register(lambda_ty);
+    let r2 = writer.add_register(BytecodeType::Int32);
+    writer.set_arguments(2);
+    writer.emit_mov(r0, r1);
+    writer.set_location(Location::new(3, 7));
+    writer.emit_div(r2, r2, r2);
+    writer.set_location(Location::new(4, 1));
+    writer.emit_div(r2, r2, r2);
+    writer.emit_ret(r0);
+
+    let mut program = test_program(writer.generate());
+    let encoded = encode_module(&mut program, 0x1234);
+
+    let mut module = decode_module(&encoded).expect("decoding failed");
+    assert_eq!(module.source_hash, 0x1234);
+
+    {
+        let original = program.functions[0].bytecode.as_ref().unwrap();
+        let decoded = module.program.functions[0].bytecode.as_ref().unwrap();
+        assert_eq!(original.code(), decoded.code());
+        assert_eq!(original.registers(), decoded.registers());
+        assert_eq!(original.const_pool_entries(), decoded.const_pool_entries());
+        assert_eq!(original.arguments(), decoded.arguments());
+        assert_eq!(original.locations(), decoded.locations());
+    }
+
+    let reencoded = encode_module(&mut module.program, module.source_hash);
+    assert_eq!(encoded, reencoded);
+}
+
+#[test]
+fn test_module_invalid() {
+    let mut program = test_program(BytecodeWriter::new().generate());
+    let encoded = encode_module(&mut program, 1);
+
+    assert_eq!(
+        decode_module(&encoded[1..]).err(),
+        Some(ModuleError::InvalidHeader)
+    );
+
+    let mut wrong_version = encoded.clone();
+    wrong_version[4] = 0xFF;
+    assert_eq!(
+        decode_module(&wrong_version).err(),
+        Some(ModuleError::UnsupportedVersion(0xFF))
+    );
+
+    assert_eq!(
+        decode_module(&encoded[..encoded.len() - 1]).err(),
+        Some(ModuleError::Corrupted)
+    );
+}
+
+fn test_program(bytecode: BytecodeFunction) -> Program {
+    let fct = FunctionData {
+        name: "f".into(),
+        file_id: SourceFileId(0),
+        loc: Location::new(1, 1),
+        kind: FunctionKind::Function,
+        package_id: PackageId(0),
+        module_id: ModuleId(0),
+        type_params: TypeParamData {
+            names: Vec::new(),
+            bounds: Vec::new(),
+        },
+        source_file_id: None,
+        params: Vec::new(),
+        return_type: BytecodeType::Unit,
+        native: None,
+        internal: None,
+        intrinsic: None,
+        vtable_index: None,
+        is_test: false,
+        expected_trap: None,
+        is_optimize_immediately: false,
+        is_variadic: false,
+        bytecode: Some(bytecode),
+    };
+
+    Program {
+        packages: Vec::new(),
+        modules: Vec::new(),
+        functions: vec![fct],
+        globals: Vec::new(),
+        classes: Vec::new(),
+        structs: Vec::new(),
+        enums: Vec::new(),
+        traits: Vec::new(),
+        impls: Vec::new(),
+        source_files: Vec::new(),
+        stdlib_package_id: PackageId(0),
+        program_package_id: PackageId(0),
+        boots_package_id: None,
+        main_fct_id: None,
+    }
+}
+
+fn verify_code(code: Vec<u8>, const_pool: Vec<ConstPoolEntry>) -> Result<(), VerifyError> {
+    let fct = BytecodeFunction::new(
+        code,
+        const_pool,
+        vec![BytecodeType::Int32, BytecodeType::Int32],
+        0,
+        Vec::new(),
+        Vec::new(),
+    );
+    verify(&fct)
+}
+
+#[test]
+fn test_verify_valid() {
+    let mut writer = BytecodeWriter::new();
+    let r0 = writer.add_register(BytecodeType::Int32);
+    let r1 = writer.add_register(BytecodeType::Bool);
+    let lbl_start = writer.define_label();
+    writer.emit_loop_start();
+    let lbl_end = writer.create_label();
+    writer.emit_const_int32(r0, 12);
+    writer.emit_jump_if_false(r1, lbl_end);
+    writer.emit_jump_loop(lbl_start);
+    writer.bind_label(lbl_end);
+    writer.emit_ret(r0);
+    let fct = writer.generate();
+    assert_eq!(verify(&fct), Ok(()));
+}
+
+#[test]
+fn test_verify_invalid_register() {
+    let mov = BytecodeOpcode::Mov.into();
+    let ret = BytecodeOpcode::Ret.into();
+    assert_eq!(
+        verify_code(vec![mov, 0, 1, ret, 2], Vec::new()),
+        Err(VerifyError::InvalidRegister {
+            offset: BytecodeOffset(3),
+            register: Register(2),
+        })
+    );
+}
+
+#[test]
+fn test_verify_invalid_const_pool_entry() {
+    let const_int32 = BytecodeOpcode::ConstInt32.into();
+    let ret = BytecodeOpcode::Ret.into();
+    assert_eq!(
+        verify_code(
+            vec![const_int32, 0, 0, ret, 0],
+            vec![ConstPoolEntry::String("foo".into())]
+        ),
+        Err(VerifyError::UnexpectedConstPoolEntry {
+            offset: BytecodeOffset(0),
+            idx: ConstPoolIdx(0),
+            expected: ConstPoolOpcode::Int32,
+        })
+    );
+    assert_eq!(
+        verify_code(
+            vec![const_int32, 0, 1, ret, 0],
+            vec![ConstPoolEntry::Int32(1)]
+        ),
+        Err(VerifyError::InvalidConstPoolIdx {
+            offset: BytecodeOffset(0),
+            idx: ConstPoolIdx(1),
+        })
+    );
+}
+
+#[test]
+fn test_verify_invalid_jump() {
+    let jump = BytecodeOpcode::Jump.into();
+    let mov = BytecodeOpcode::Mov.into();
+    let ret = BytecodeOpcode::Ret.into();
+    assert_eq!(
+        verify_code(vec![jump, 2, mov, 0, 1, ret, 0], Vec::new()),
+        Ok(())
+    );
+    assert_eq!(
+        verify_code(vec![jump, 3, mov, 0, 1, ret, 0], Vec::new()),
+        Err(VerifyError::InvalidJumpTarget {
+            offset: BytecodeOffset(0),
+            target: 3,
+        })
+    );
+    assert_eq!(
+        verify_code(vec![jump, 7, mov, 0, 1, ret, 0], Vec::new()),
+        Err(VerifyError::InvalidJumpTarget {
+            offset: BytecodeOffset(0),
+            target: 7,
+        })
+    );
+
+    let jump_loop = BytecodeOpcode::JumpLoop.into();
+    assert_eq!(
+        verify_code(vec![mov, 0, 1, jump_loop, 4], Vec::new()),
+        Err(VerifyError::InvalidJumpTarget {
+            offset: BytecodeOffset(3),
+            target: -1,
+        })
+    );
+}
+
+#[test]
+fn test_exception_handler() {
+    let mut writer = BytecodeWriter::new();
+    let r0 = writer.add_register(BytecodeType::Ptr);
+    let start = writer.define_label();
+    writer.set_location(Location::new(1, 1));
+    writer.emit_throw(r0);
+    let end = writer.define_label();
+    writer.emit_ret(r0);
+    let handler = writer.define_label();
+    writer.emit_ret(r0);
+    writer.add_exception_handler(start, end, handler, BytecodeType::Ptr, r0);
+    let fct = writer.generate();
+
+    assert_eq!(
+        fct.code(),
+        &[
+            BytecodeOpcode::Throw.into(),
+            0,
+            BytecodeOpcode::Ret.into(),
+            0,
+            BytecodeOpcode::Ret.into(),
+            0
+        ]
+    );
+
+    let handlers = fct.exception_handlers();
+    assert_eq!(1, handlers.len());
+    assert_eq!(BytecodeOffset(0), handlers[0].start);
+    assert_eq!(BytecodeOffset(2), handlers[0].end);
+    assert_eq!(BytecodeOffset(4), handlers[0].handler);
+    assert_eq!(r0, handlers[0].register);
+    assert_eq!(verify(&fct), Ok(()));
+}
+
+#[test]
+fn test_verify_invalid_exception_handler() {
+    let mut writer = BytecodeWriter::new();
+    let r0 = writer.add_register(BytecodeType::Ptr);
+    let start = writer.define_label();
+    writer.set_location(Location::new(1, 1));
+    writer.emit_throw(r0);
+    let end = writer.define_label();
+    writer.emit_ret(r0);
+    writer.add_exception_handler(start, end, end, BytecodeType::Ptr, Register(1));
+    let fct = writer.generate();
+
+    assert_eq!(
+        verify(&fct),
+        Err(VerifyError::InvalidExceptionHandler { index: 0 })
+    );
+}
+
+#[test]
+fn test_liveness_last_use() {
+    let mut writer = BytecodeWriter::new();
+    let r0 = writer.add_register(BytecodeType::Int32);
+    let r1 = writer.add_register(BytecodeType::Int32);
+    let r2 = writer.add_register(BytecodeType::Int32);
+    writer.set_arguments(1);
+    writer.emit_const_int32(r1, 1);
+    writer.set_location(Location::new(1, 1));
+    writer.emit_add(r2, r0, r1);
+    writer.emit_ret(r2);
+    let fct = writer.generate();
+
+    let liveness = Liveness::compute(&fct);
+    let add = BytecodeOffset(3);
+    assert_eq!(liveness.live_in(BytecodeOffset(0)), &[r0].into());
+    assert_eq!(liveness.live_in(add), &[r0, r1].into());
+    assert_eq!(liveness.live_out(add), &[r2].into());
+    assert!(liveness.is_last_use(add, r0));
+    assert!(liveness.is_last_use(add, r1));
+    assert!(!liveness.is_last_use(add, r2));
+    assert!(liveness.live_out(BytecodeOffset(7)).is_empty());
+}
+
+#[test]
+fn test_liveness_loop() {
+    let mut writer = BytecodeWriter::new();
+    let r0 = writer.add_register(BytecodeType::Bool);
+    let r1 = writer.add_register(BytecodeType::Int32);
+    let r2 = writer.add_register(BytecodeType::Int32);
+    writer.set_arguments(3);
+    let start = writer.define_label();
+    writer.emit_loop_start();
+    let end = writer.create_label();
+    writer.emit_jump_if_false(r0, end);
+    writer.set_location(Location::new(1, 1));
+    writer.emit_add(r1, r1, r2);
+    writer.emit_jump_loop(start);
+    writer.bind_label(end);
+    writer.emit_ret(r1);
+    let fct = writer.generate();
+
+    let liveness = Liveness::compute(&fct);
+    // values used in the loop stay live across the back edge
+    assert_eq!(liveness.live_out(BytecodeOffset(4)), &[r0, r1, r2].into());
+    assert_eq!(liveness.live_out(BytecodeOffset(8)), &[r0, r1, r2].into());
+    assert!(!liveness.is_last_use(BytecodeOffset(4), r2));
+    assert_eq!(liveness.live_in(BytecodeOffset(10)), &[r1].into());
+}
+
+#[test]
+fn test_liveness_exception_handler() {
+    let mut writer = BytecodeWriter::new();
+    let r0 = writer.add_register(BytecodeType::Int32);
+    let r1 = writer.add_register(BytecodeType::Int32);
+    let r2 = writer.add_register(BytecodeType::Ptr);
+    writer.set_arguments(1);
+    let start = writer.define_label();
+    writer.emit_const_int32(r1, 1);
+    writer.set_location(Location::new(1, 1));
+    writer.emit_div(r0, r1, r1);
+    let end = writer.define_label();
+    writer.emit_ret(r0);
+    let handler = writer.define_label();
+    writer.emit_ret(r0);
+    writer.add_exception_handler(start, end, handler, BytecodeType::Ptr, r2);
+    let fct = writer.generate();
+
+    let liveness = Liveness::compute(&fct);
+    let div = BytecodeOffset(3);
+    // r1 is defined in the try block and dead at the handler
+    assert_eq!(liveness.live_in(BytecodeOffset(9)), &[r0].into());
+    assert!(liveness.is_last_use(div, r1));
+    // the handler reads the old value of r0 if the division throws
+    assert_eq!(liveness.live_in(div), &[r0, r1].into());
+    assert_eq!(liveness.live_in(BytecodeOffset(0)), &[r0].into());
+}
+
+#[test]
+fn test_escape_analysis() {
+    let mut writer = BytecodeWriter::new();
+    let r0 = writer.add_register(BytecodeType::Int32);
+    let r1 = writer.add_register(BytecodeType::Ptr);
+    let r2 = writer.add_register(BytecodeType::Ptr);
+    let r3 = writer.add_register(BytecodeType::Ptr);
+    writer.set_arguments(1);
+    let cls = writer.add_const(ConstPoolEntry::Class(
+        ClassId(0),
+        BytecodeTypeArray::empty(),
+    ));
+    let field = writer.add_const(ConstPoolEntry::Field(
+        ClassId(0),
+        BytecodeTypeArray::empty(),
+        0,
+    ));
+    // r1 is only accessed through its fields
+    writer.set_location(Location::new(1, 1));
+    writer.emit_new_object(r1, cls);
+    writer.set_location(Location::new(1, 2));
+    writer.emit_store_field(r0, r1, field);
+    writer.set_location(Location::new(1, 3));
+    writer.emit_load_field(r0, r1, field);
+    // r2 is stored into a field of r1
+    writer.set_location(Location::new(1, 4));
+    writer.emit_new_object(r2, cls);
+    writer.set_location(Location::new(1, 5));
+    writer.emit_store_field(r2, r1, field);
+    // r3 is returned
+    writer.set_location(Location::new(1, 6));
+    writer.emit_new_object(r3, cls);
+    writer.emit_ret(r3);
+    let fct = writer.generate();
+
+    let escape = EscapeAnalysis::compute(&fct);
+    assert!(escape.is_non_escaping(r1));
+    assert!(!escape.is_non_escaping(r2));
+    assert!(!escape.is_non_escaping(r3));
+    assert_eq!(escape.non_escaping(), &[(r1, cls)].into());
+}
+
+fn array_loop(start: i64) -> BytecodeFunction {
+    let mut writer = BytecodeWriter::new();
+    let r0 = writer.add_register(BytecodeType::Ptr);
+    let r1 = writer.add_register(BytecodeType::Int64);
+    let r2 = writer.add_register(BytecodeType::Int64);
+    let r3 = writer.add_register(BytecodeType::Bool);
+    let r4 = writer.add_register(BytecodeType::Int64);
+    let r5 = writer.add_register(BytecodeType::Int64);
+    writer.set_arguments(1);
+    writer.emit_const_int64(r1, start);
+    let loop_start = writer.define_label();
+    writer.emit_loop_start();
+    writer.set_location(Location::new(1, 1));
+    writer.emit_array_length(r2, r0);
+    writer.emit_test_lt(r3, r1, r2);
+    let loop_end = writer.create_label();
+    writer.emit_jump_if_false(r3, loop_end);
+    writer.set_location(Location::new(1, 2));
+    writer.emit_load_array(r5, r0, r1);
+    writer.emit_const_int64(r4, 1);
+    writer.set_location(Location::new(1, 3));
+    writer.emit_add(r1, r1, r4);
+    writer.set_location(Location::new(1, 4));
+    writer.emit_load_array(r5, r0, r1);
+    writer.emit_jump_loop(loop_start);
+    writer.bind_label(loop_end);
+    writer.emit_ret(r5);
+    writer.generate()
+}
+
+#[test]
+fn test_bounds_check_elimination() {
+    let fct = array_loop(0);
+    let bounds_checks = BoundsCheckElimination::compute(&fct);
+    // checked by the loop condition
+    assert!(bounds_checks.is_in_bounds(BytecodeOffset(14)));
+    // index was incremented after the loop condition
+    assert!(!bounds_checks.is_in_bounds(BytecodeOffset(25)));
+}
+
+#[test]
+fn test_bounds_check_elimination_negative_start() {
+    let fct = array_loop(-1);
+    let bounds_checks = BoundsCheckElimination::compute(&fct);
+    assert!(!bounds_checks.is_in_bounds(BytecodeOffset(14)));
+    assert!(!bounds_checks.is_in_bounds(BytecodeOffset(25)));
+}
+
+#[test]
+fn test_line_starts() {
+    let mut writer = BytecodeWriter::new();
+    let r0 = writer.add_register(BytecodeType::Int32);
+    let r1 = writer.add_register(BytecodeType::Int32);
+    writer.set_arguments(2);
+    writer.set_location(Location::new(1, 5));
+    writer.emit_add(r0, r0, r1);
+    writer.set_location(Location::new(1, 9));
+    writer.emit_add(r0, r0, r1);
+    writer.set_location(Location::new(2, 5));
+    writer.emit_sub(r0, r0, r1);
+    writer.emit_ret(r0);
+    let fct = writer.generate();
+
+    assert_eq!(
+        fct.line_starts(),
+        vec![(BytecodeOffset(0), 1), (BytecodeOffset(8), 2)]
+    );
+}
@@ -9,6 +9,9 @@ pub enum BytecodeType {
     Unit,
     Bool,
     UInt8,
+    UInt16,
+    UInt32,
+    UInt64,
     Char,
     Int32,
     Int64,
@@ -31,6 +34,9 @@ impl BytecodeType {
             BytecodeType::Unit => BytecodeTypeKind::Unit,
             BytecodeType::Bool => BytecodeTypeKind::Bool,
             BytecodeType::UInt8 => BytecodeTypeKind::UInt8,
+            BytecodeType::UInt16 => BytecodeTypeKind::UInt16,
+            BytecodeType::UInt32 => BytecodeTypeKind::UInt32,
+            BytecodeType::UInt64 => BytecodeTypeKind::UInt64,
             BytecodeType::Char => BytecodeTypeKind::Char,
             BytecodeType::Int32 => BytecodeTypeKind::Int32,
             BytecodeType::Int64 => BytecodeTypeKind::Int64,
@@ -123,6 +129,9 @@ impl BytecodeType {
             BytecodeType::Unit
             | BytecodeType::Bool
             | BytecodeType::UInt8
+            | BytecodeType::UInt16
+            | BytecodeType::UInt32
+            | BytecodeType::UInt64
             | BytecodeType::Char
             | BytecodeType::Int32
             | BytecodeType::Int64
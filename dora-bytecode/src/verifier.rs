@@ -0,0 +1,612 @@
+use std::collections::HashSet;
+
+use crate::{
+    read, BytecodeFunction, BytecodeOffset, BytecodeVisitor, ConstPoolEntry, ConstPoolIdx,
+    ConstPoolOpcode, GlobalId, Register,
+};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum VerifyError {
+    InvalidRegister {
+        offset: BytecodeOffset,
+        register: Register,
+    },
+    InvalidConstPoolIdx {
+        offset: BytecodeOffset,
+        idx: ConstPoolIdx,
+    },
+    UnexpectedConstPoolEntry {
+        offset: BytecodeOffset,
+        idx: ConstPoolIdx,
+        expected: ConstPoolOpcode,
+    },
+    InvalidJumpTarget {
+        offset: BytecodeOffset,
+        target: i64,
+    },
+    InvalidExceptionHandler {
+        index: usize,
+    },
+}
+
+// Checks that all operands of all instructions are valid: registers and
+// const pool indices need to be in range, const pool entries need to have
+// the kind expected by the instruction and jumps need to land on the start
+// of an instruction. The same holds for the offsets of exception handlers.
+// Returns the first error found.
+pub fn verify(bc: &BytecodeFunction) -> Result<(), VerifyError> {
+    let mut verifier = BytecodeVerifier {
+        bc,
+        offset: BytecodeOffset(0),
+        instruction_starts: HashSet::new(),
+        jumps: Vec::new(),
+        error: None,
+    };
+
+    read(bc.code(), &mut verifier);
+
+    // jumps can only be checked after all instruction starts are known
+    for (offset, target) in std::mem::take(&mut verifier.jumps) {
+        let valid = target >= 0
+            && target <= u32::MAX as i64
+            && verifier
+                .instruction_starts
+                .contains(&BytecodeOffset(target as u32));
+
+        if !valid {
+            verifier.fail(VerifyError::InvalidJumpTarget { offset, target });
+        }
+    }
+
+    for (index, handler) in bc.exception_handlers().iter().enumerate() {
+        let is_boundary = |offset: BytecodeOffset| {
+            offset.to_usize() == bc.code().len() || verifier.instruction_starts.contains(&offset)
+        };
+
+        let valid = handler.start.to_usize() <= handler.end.to_usize()
+            && is_boundary(handler.start)
+            && is_boundary(handler.end)
+            && verifier.instruction_starts.contains(&handler.handler)
+            && handler.register.to_usize() < bc.registers().len();
+
+        if !valid {
+            verifier.fail(VerifyError::InvalidExceptionHandler { index });
+        }
+    }
+
+    match verifier.error {
+        Some(error) => Err(error),
+        None => Ok(()),
+    }
+}
+
+struct BytecodeVerifier<'a> {
+    bc: &'a BytecodeFunction,
+    offset: BytecodeOffset,
+    instruction_starts: HashSet<BytecodeOffset>,
+    jumps: Vec<(BytecodeOffset, i64)>,
+    error: Option<VerifyError>,
+}
+
+impl<'a> BytecodeVerifier<'a> {
+    fn fail(&mut self, error: VerifyError) {
+        if self.error.is_none() {
+            self.error = Some(error);
+        }
+    }
+
+    fn check_register(&mut self, register: Register) {
+        if register.to_usize() >= self.bc.registers().len() {
+            self.fail(VerifyError::InvalidRegister {
+                offset: self.offset,
+                register,
+            });
+        }
+    }
+
+    fn check_const_pool_entry(&mut self, idx: ConstPoolIdx, expected: ConstPoolOpcode) {
+        let entry = match self.bc.const_pool_entries().get(idx.0 as usize) {
+            Some(entry) => entry,
+            None => {
+                self.fail(VerifyError::InvalidConstPoolIdx {
+                    offset: self.offset,
+                    idx,
+                });
+                return;
+            }
+        };
+
+        if const_pool_opcode(entry) != expected {
+            self.fail(VerifyError::UnexpectedConstPoolEntry {
+                offset: self.offset,
+                idx,
+                expected,
+            });
+        }
+    }
+
+    fn check_jump_target(&mut self, distance: i64) {
+        let target = self.offset.to_u32() as i64 + distance;
+        self.jumps.push((self.offset, target));
+    }
+
+    fn check_const_jump_target(&mut self, idx: ConstPoolIdx) {
+        let distance = self
+            .bc
+            .const_pool_entries()
+            .get(idx.0 as usize)
+            .and_then(|entry| entry.to_int32());
+
+        if let Some(distance) = distance {
+            self.check_jump_target(distance as i64);
+        }
+    }
+
+    fn check_jump_table(&mut self, idx: ConstPoolIdx) {
+        let targets = match self.bc.const_pool_entries().get(idx.0 as usize) {
+            Some(ConstPoolEntry::JumpTable(_, targets)) => targets,
+            _ => return,
+        };
+
+        for &distance in targets {
+            self.check_jump_target(distance as i64);
+        }
+    }
+}
+
+fn const_pool_opcode(entry: &ConstPoolEntry) -> ConstPoolOpcode {
+    match entry {
+        ConstPoolEntry::String(..) => ConstPoolOpcode::String,
+        ConstPoolEntry::Float32(..) => ConstPoolOpcode::Float32,
+        ConstPoolEntry::Float64(..) => ConstPoolOpcode::Float64,
+        ConstPoolEntry::Int32(..) => ConstPoolOpcode::Int32,
+        ConstPoolEntry::Int64(..) => ConstPoolOpcode::Int64,
+        ConstPoolEntry::Char(..) => ConstPoolOpcode::Char,
+        ConstPoolEntry::Class(..) => ConstPoolOpcode::Class,
+        ConstPoolEntry::Field(..) => ConstPoolOpcode::Field,
+        ConstPoolEntry::Fct(..) => ConstPoolOpcode::Fct,
+        ConstPoolEntry::Generic(..) => ConstPoolOpcode::Generic,
+        ConstPoolEntry::Enum(..) => ConstPoolOpcode::Enum,
+        ConstPoolEntry::EnumVariant(..) => ConstPoolOpcode::EnumVariant,
+        ConstPoolEntry::EnumElement(..) => ConstPoolOpcode::EnumElement,
+        ConstPoolEntry::Struct(..) => ConstPoolOpcode::Struct,
+        ConstPoolEntry::StructField(..) => ConstPoolOpcode::StructField,
+        ConstPoolEntry::Trait(..) => ConstPoolOpcode::Trait,
+        ConstPoolEntry::TupleElement(..) => ConstPoolOpcode::TupleElement,
+        ConstPoolEntry::Tuple(..) => ConstPoolOpcode::Tuple,
+        ConstPoolEntry::Lambda(..) => ConstPoolOpcode::Lambda,
+        ConstPoolEntry::JumpTable(..) => ConstPoolOpcode::JumpTable,
+    }
+}
+
+impl<'a> BytecodeVisitor for BytecodeVerifier<'a> {
+    fn visit_instruction(&mut self, offset: BytecodeOffset) {
+        self.offset = offset;
+        self.instruction_starts.insert(offset);
+    }
+
+    fn visit_add(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.check_register(dest);
+        self.check_register(lhs);
+        self.check_register(rhs);
+    }
+
+    fn visit_add_saturating(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.check_register(dest);
+        self.check_register(lhs);
+        self.check_register(rhs);
+    }
+
+    fn visit_sub(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.check_register(dest);
+        self.check_register(lhs);
+        self.check_register(rhs);
+    }
+
+    fn visit_sub_saturating(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.check_register(dest);
+        self.check_register(lhs);
+        self.check_register(rhs);
+    }
+
+    fn visit_neg(&mut self, dest: Register, src: Register) {
+        self.check_register(dest);
+        self.check_register(src);
+    }
+
+    fn visit_mul(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.check_register(dest);
+        self.check_register(lhs);
+        self.check_register(rhs);
+    }
+
+    fn visit_div(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.check_register(dest);
+        self.check_register(lhs);
+        self.check_register(rhs);
+    }
+
+    fn visit_mod(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.check_register(dest);
+        self.check_register(lhs);
+        self.check_register(rhs);
+    }
+
+    fn visit_and(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.check_register(dest);
+        self.check_register(lhs);
+        self.check_register(rhs);
+    }
+
+    fn visit_or(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.check_register(dest);
+        self.check_register(lhs);
+        self.check_register(rhs);
+    }
+
+    fn visit_xor(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.check_register(dest);
+        self.check_register(lhs);
+        self.check_register(rhs);
+    }
+
+    fn visit_not(&mut self, dest: Register, src: Register) {
+        self.check_register(dest);
+        self.check_register(src);
+    }
+
+    fn visit_shl(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.check_register(dest);
+        self.check_register(lhs);
+        self.check_register(rhs);
+    }
+
+    fn visit_shr(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.check_register(dest);
+        self.check_register(lhs);
+        self.check_register(rhs);
+    }
+
+    fn visit_sar(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.check_register(dest);
+        self.check_register(lhs);
+        self.check_register(rhs);
+    }
+
+    fn visit_mov(&mut self, dest: Register, src: Register) {
+        self.check_register(dest);
+        self.check_register(src);
+    }
+
+    fn visit_load_tuple_element(&mut self, dest: Register, src: Register, idx: ConstPoolIdx) {
+        self.check_register(dest);
+        self.check_register(src);
+        self.check_const_pool_entry(idx, ConstPoolOpcode::TupleElement);
+    }
+
+    fn visit_load_enum_element(&mut self, dest: Register, src: Register, idx: ConstPoolIdx) {
+        self.check_register(dest);
+        self.check_register(src);
+        self.check_const_pool_entry(idx, ConstPoolOpcode::EnumElement);
+    }
+
+    fn visit_load_enum_variant(&mut self, dest: Register, src: Register, idx: ConstPoolIdx) {
+        self.check_register(dest);
+        self.check_register(src);
+        self.check_const_pool_entry(idx, ConstPoolOpcode::Enum);
+    }
+
+    fn visit_load_struct_field(&mut self, dest: Register, obj: Register, field: ConstPoolIdx) {
+        self.check_register(dest);
+        self.check_register(obj);
+        self.check_const_pool_entry(field, ConstPoolOpcode::StructField);
+    }
+
+    fn visit_load_field(&mut self, dest: Register, obj: Register, field: ConstPoolIdx) {
+        self.check_register(dest);
+        self.check_register(obj);
+        self.check_const_pool_entry(field, ConstPoolOpcode::Field);
+    }
+
+    fn visit_store_field(&mut self, src: Register, obj: Register, field: ConstPoolIdx) {
+        self.check_register(src);
+        self.check_register(obj);
+        self.check_const_pool_entry(field, ConstPoolOpcode::Field);
+    }
+
+    fn visit_load_global(&mut self, dest: Register, _global_id: GlobalId) {
+        self.check_register(dest);
+    }
+
+    fn visit_store_global(&mut self, src: Register, _global_id: GlobalId) {
+        self.check_register(src);
+    }
+
+    fn visit_push_register(&mut self, src: Register) {
+        self.check_register(src);
+    }
+
+    fn visit_const_true(&mut self, dest: Register) {
+        self.check_register(dest);
+    }
+
+    fn visit_const_false(&mut self, dest: Register) {
+        self.check_register(dest);
+    }
+
+    fn visit_const_zero_uint8(&mut self, dest: Register) {
+        self.check_register(dest);
+    }
+
+    fn visit_const_zero_char(&mut self, dest: Register) {
+        self.check_register(dest);
+    }
+
+    fn visit_const_zero_int32(&mut self, dest: Register) {
+        self.check_register(dest);
+    }
+
+    fn visit_const_zero_int64(&mut self, dest: Register) {
+        self.check_register(dest);
+    }
+
+    fn visit_const_zero_float32(&mut self, dest: Register) {
+        self.check_register(dest);
+    }
+
+    fn visit_const_zero_float64(&mut self, dest: Register) {
+        self.check_register(dest);
+    }
+
+    fn visit_const_char(&mut self, dest: Register, value: ConstPoolIdx) {
+        self.check_register(dest);
+        self.check_const_pool_entry(value, ConstPoolOpcode::Char);
+    }
+
+    fn visit_const_uint8(&mut self, dest: Register, _value: u8) {
+        self.check_register(dest);
+    }
+
+    fn visit_const_int32(&mut self, dest: Register, value: ConstPoolIdx) {
+        self.check_register(dest);
+        self.check_const_pool_entry(value, ConstPoolOpcode::Int32);
+    }
+
+    fn visit_const_int64(&mut self, dest: Register, value: ConstPoolIdx) {
+        self.check_register(dest);
+        self.check_const_pool_entry(value, ConstPoolOpcode::Int64);
+    }
+
+    fn visit_const_float32(&mut self, dest: Register, value: ConstPoolIdx) {
+        self.check_register(dest);
+        self.check_const_pool_entry(value, ConstPoolOpcode::Float32);
+    }
+
+    fn visit_const_float64(&mut self, dest: Register, value: ConstPoolIdx) {
+        self.check_register(dest);
+        self.check_const_pool_entry(value, ConstPoolOpcode::Float64);
+    }
+
+    fn visit_const_string(&mut self, dest: Register, value: ConstPoolIdx) {
+        self.check_register(dest);
+        self.check_const_pool_entry(value, ConstPoolOpcode::String);
+    }
+
+    fn visit_test_identity(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.check_register(dest);
+        self.check_register(lhs);
+        self.check_register(rhs);
+    }
+
+    fn visit_test_eq(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.check_register(dest);
+        self.check_register(lhs);
+        self.check_register(rhs);
+    }
+
+    fn visit_test_ne(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.check_register(dest);
+        self.check_register(lhs);
+        self.check_register(rhs);
+    }
+
+    fn visit_test_gt(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.check_register(dest);
+        self.check_register(lhs);
+        self.check_register(rhs);
+    }
+
+    fn visit_test_ge(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.check_register(dest);
+        self.check_register(lhs);
+        self.check_register(rhs);
+    }
+
+    fn visit_test_lt(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.check_register(dest);
+        self.check_register(lhs);
+        self.check_register(rhs);
+    }
+
+    fn visit_test_le(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.check_register(dest);
+        self.check_register(lhs);
+        self.check_register(rhs);
+    }
+
+    fn visit_jump_if_false(&mut self, opnd: Register, offset: u32) {
+        self.check_register(opnd);
+        self.check_jump_target(offset as i64);
+    }
+
+    fn visit_jump_if_false_const(&mut self, opnd: Register, idx: ConstPoolIdx) {
+        self.check_register(opnd);
+        self.check_const_pool_entry(idx, ConstPoolOpcode::Int32);
+        self.check_const_jump_target(idx);
+    }
+
+    fn visit_jump_if_true(&mut self, opnd: Register, offset: u32) {
+        self.check_register(opnd);
+        self.check_jump_target(offset as i64);
+    }
+
+    fn visit_jump_if_true_const(&mut self, opnd: Register, idx: ConstPoolIdx) {
+        self.check_register(opnd);
+        self.check_const_pool_entry(idx, ConstPoolOpcode::Int32);
+        self.check_const_jump_target(idx);
+    }
+
+    fn visit_switch(&mut self, opnd: Register, idx: ConstPoolIdx) {
+        self.check_register(opnd);
+        self.check_const_pool_entry(idx, ConstPoolOpcode::JumpTable);
+        self.check_jump_table(idx);
+    }
+
+    fn visit_jump_loop(&mut self, offset: u32) {
+        self.check_jump_target(-(offset as i64));
+    }
+
+    fn visit_loop_start(&mut self) {}
+
+    fn visit_jump(&mut self, offset: u32) {
+        self.check_jump_target(offset as i64);
+    }
+
+    fn visit_jump_const(&mut self, idx: ConstPoolIdx) {
+        self.check_const_pool_entry(idx, ConstPoolOpcode::Int32);
+        self.check_const_jump_target(idx);
+    }
+
+    fn visit_invoke_direct(&mut self, dest: Register, fct: ConstPoolIdx) {
+        self.check_register(dest);
+        self.check_const_pool_entry(fct, ConstPoolOpcode::Fct);
+    }
+
+    fn visit_invoke_virtual(&mut self, dest: Register, fct: ConstPoolIdx) {
+        self.check_register(dest);
+        self.check_const_pool_entry(fct, ConstPoolOpcode::Fct);
+    }
+
+    fn visit_invoke_static(&mut self, dest: Register, fct: ConstPoolIdx) {
+        self.check_register(dest);
+        self.check_const_pool_entry(fct, ConstPoolOpcode::Fct);
+    }
+
+    fn visit_invoke_lambda(&mut self, dest: Register, idx: ConstPoolIdx) {
+        self.check_register(dest);
+        self.check_const_pool_entry(idx, ConstPoolOpcode::Lambda);
+    }
+
+    fn visit_invoke_generic_static_void(&mut self, fct: ConstPoolIdx) {
+        self.check_const_pool_entry(fct, ConstPoolOpcode::Generic);
+    }
+
+    fn visit_invoke_generic_static(&mut self, dest: Register, fct: ConstPoolIdx) {
+        self.check_register(dest);
+        self.check_const_pool_entry(fct, ConstPoolOpcode::Generic);
+    }
+
+    fn visit_invoke_generic_direct_void(&mut self, fct: ConstPoolIdx) {
+        self.check_const_pool_entry(fct, ConstPoolOpcode::Generic);
+    }
+
+    fn visit_invoke_generic_direct(&mut self, dest: Register, fct: ConstPoolIdx) {
+        self.check_register(dest);
+        self.check_const_pool_entry(fct, ConstPoolOpcode::Generic);
+    }
+
+    fn visit_new_object(&mut self, dest: Register, cls: ConstPoolIdx) {
+        self.check_register(dest);
+        self.check_const_pool_entry(cls, ConstPoolOpcode::Class);
+    }
+
+    fn visit_new_object_initialized(&mut self, dest: Register, cls: ConstPoolIdx) {
+        self.check_register(dest);
+        self.check_const_pool_entry(cls, ConstPoolOpcode::Class);
+    }
+
+    fn visit_new_array(&mut self, dest: Register, cls: ConstPoolIdx, length: Register) {
+        self.check_register(dest);
+        self.check_const_pool_entry(cls, ConstPoolOpcode::Class);
+        self.check_register(length);
+    }
+
+    fn visit_new_tuple(&mut self, dest: Register, idx: ConstPoolIdx) {
+        self.check_register(dest);
+        self.check_const_pool_entry(idx, ConstPoolOpcode::Tuple);
+    }
+
+    fn visit_new_enum(&mut self, dest: Register, idx: ConstPoolIdx) {
+        self.check_register(dest);
+        self.check_const_pool_entry(idx, ConstPoolOpcode::EnumVariant);
+    }
+
+    fn visit_new_struct(&mut self, dest: Register, idx: ConstPoolIdx) {
+        self.check_register(dest);
+        self.check_const_pool_entry(idx, ConstPoolOpcode::Struct);
+    }
+
+    fn visit_new_trait_object(&mut self, dest: Register, idx: ConstPoolIdx, src: Register) {
+        self.check_register(dest);
+        self.check_const_pool_entry(idx, ConstPoolOpcode::Trait);
+        self.check_register(src);
+    }
+
+    fn visit_new_lambda(&mut self, dest: Register, idx: ConstPoolIdx) {
+        self.check_register(dest);
+        self.check_const_pool_entry(idx, ConstPoolOpcode::Fct);
+    }
+
+    fn visit_array_length(&mut self, dest: Register, arr: Register) {
+        self.check_register(dest);
+        self.check_register(arr);
+    }
+
+    fn visit_load_array(&mut self, dest: Register, arr: Register, idx: Register) {
+        self.check_register(dest);
+        self.check_register(arr);
+        self.check_register(idx);
+    }
+
+    fn visit_store_array(&mut self, src: Register, arr: Register, idx: Register) {
+        self.check_register(src);
+        self.check_register(arr);
+        self.check_register(idx);
+    }
+
+    fn visit_array_fill(&mut self, src: Register, arr: Register, length: Register) {
+        self.check_register(src);
+        self.check_register(arr);
+        self.check_register(length);
+    }
+
+    fn visit_load_trait_object_value(&mut self, dest: Register, object: Register) {
+        self.check_register(dest);
+        self.check_register(object);
+    }
+
+    fn visit_test_trait_object(&mut self, dest: Register, idx: ConstPoolIdx, src: Register) {
+        self.check_register(dest);
+        self.check_const_pool_entry(idx, ConstPoolOpcode::Trait);
+        self.check_register(src);
+    }
+
+    fn visit_cast_trait_object(&mut self, dest: Register, idx: ConstPoolIdx, src: Register) {
+        self.check_register(dest);
+        self.check_const_pool_entry(idx, ConstPoolOpcode::Trait);
+        self.check_register(src);
+    }
+
+    fn visit_checked_cast(&mut self, dest: Register, src: Register) {
+        self.check_register(dest);
+        self.check_register(src);
+    }
+
+    fn visit_throw(&mut self, opnd: Register) {
+        self.check_register(opnd);
+    }
+
+    fn visit_ret(&mut self, opnd: Register) {
+        self.check_register(opnd);
+    }
+}
@@ -1,13 +1,33 @@
 use std::mem;
 
 use crate::{
-    BytecodeFunction, BytecodeOffset, BytecodeOpcode, BytecodeType, ConstPoolEntry, ConstPoolIdx,
-    GlobalId, Location, Register,
+    BytecodeExceptionHandler, BytecodeFunction, BytecodeOffset, BytecodeOpcode, BytecodeType,
+    ConstPoolEntry, ConstPoolIdx, GlobalId, Location, Register,
 };
 
 #[derive(Copy, Clone, PartialEq, Debug, Eq, Hash)]
 pub struct Label(pub usize);
 
+// Position in the emitted bytecode that the writer can be reset to.
+pub struct WriterCheckpoint {
+    code: usize,
+    label_offsets: usize,
+    unresolved_jump_offsets: usize,
+    unresolved_jump_consts: usize,
+    unresolved_jump_tables: usize,
+    unresolved_exception_handlers: usize,
+    registers: usize,
+    const_pool: usize,
+    line_number_table: usize,
+    current_location: Option<Location>,
+}
+
+impl WriterCheckpoint {
+    pub fn offset(&self) -> usize {
+        self.code
+    }
+}
+
 pub struct BytecodeWriter {
     code: Vec<u8>,
     arguments: u32,
@@ -15,6 +35,8 @@ pub struct BytecodeWriter {
     label_offsets: Vec<Option<BytecodeOffset>>,
     unresolved_jump_offsets: Vec<(BytecodeOffset, BytecodeOffset, Label)>,
     unresolved_jump_consts: Vec<(BytecodeOffset, ConstPoolIdx, Label)>,
+    unresolved_jump_tables: Vec<(BytecodeOffset, ConstPoolIdx, Vec<Label>)>,
+    unresolved_exception_handlers: Vec<(Label, Label, Label, BytecodeType, Register)>,
 
     registers: Vec<BytecodeType>,
     const_pool: Vec<ConstPoolEntry>,
@@ -35,6 +57,8 @@ impl BytecodeWriter {
             label_offsets: Vec::new(),
             unresolved_jump_offsets: Vec::new(),
             unresolved_jump_consts: Vec::new(),
+            unresolved_jump_tables: Vec::new(),
+            unresolved_exception_handlers: Vec::new(),
 
             registers: Vec::new(),
             const_pool: Vec::new(),
@@ -78,6 +102,66 @@ impl BytecodeWriter {
         BytecodeOffset(self.code.len() as u32)
     }
 
+    pub fn checkpoint(&self) -> WriterCheckpoint {
+        WriterCheckpoint {
+            code: self.code.len(),
+            label_offsets: self.label_offsets.len(),
+            unresolved_jump_offsets: self.unresolved_jump_offsets.len(),
+            unresolved_jump_consts: self.unresolved_jump_consts.len(),
+            unresolved_jump_tables: self.unresolved_jump_tables.len(),
+            unresolved_exception_handlers: self.unresolved_exception_handlers.len(),
+            registers: self.registers.len(),
+            const_pool: self.const_pool.len(),
+            line_number_table: self.line_number_table.len(),
+            current_location: self.current_location,
+        }
+    }
+
+    // Drops everything emitted since the checkpoint. Labels created before the
+    // checkpoint must not have been bound in the meantime.
+    pub fn rollback(&mut self, checkpoint: WriterCheckpoint) {
+        assert!(self.code.len() >= checkpoint.code);
+        assert!(self.label_offsets[..checkpoint.label_offsets]
+            .iter()
+            .all(|offset| offset.map_or(true, |offset| offset.to_usize() <= checkpoint.code)));
+
+        self.code.truncate(checkpoint.code);
+        self.label_offsets.truncate(checkpoint.label_offsets);
+        self.unresolved_jump_offsets
+            .truncate(checkpoint.unresolved_jump_offsets);
+        self.unresolved_jump_consts
+            .truncate(checkpoint.unresolved_jump_consts);
+        self.unresolved_jump_tables
+            .truncate(checkpoint.unresolved_jump_tables);
+        self.unresolved_exception_handlers
+            .truncate(checkpoint.unresolved_exception_handlers);
+        self.registers.truncate(checkpoint.registers);
+        self.const_pool.truncate(checkpoint.const_pool);
+        self.line_number_table
+            .truncate(checkpoint.line_number_table);
+        self.current_location = checkpoint.current_location;
+    }
+
+    pub fn code(&self) -> &[u8] {
+        &self.code
+    }
+
+    pub fn const_pool(&self, idx: ConstPoolIdx) -> &ConstPoolEntry {
+        &self.const_pool[idx.0 as usize]
+    }
+
+    pub fn add_exception_handler(
+        &mut self,
+        start: Label,
+        end: Label,
+        handler: Label,
+        exception_type: BytecodeType,
+        register: Register,
+    ) {
+        self.unresolved_exception_handlers
+            .push((start, end, handler, exception_type, register));
+    }
+
     pub fn set_arguments(&mut self, arguments: u32) {
         self.arguments = arguments;
     }
@@ -94,6 +178,10 @@ impl BytecodeWriter {
         self.emit_reg3(BytecodeOpcode::Add, dest, lhs, rhs);
     }
 
+    pub fn emit_add_saturating(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.emit_reg3(BytecodeOpcode::AddSaturating, dest, lhs, rhs);
+    }
+
     pub fn emit_and(&mut self, dest: Register, lhs: Register, rhs: Register) {
         self.emit_reg3(BytecodeOpcode::And, dest, lhs, rhs);
     }
@@ -211,6 +299,15 @@ impl BytecodeWriter {
         self.emit_jmp_forward(BytecodeOpcode::Jump, BytecodeOpcode::JumpConst, None, lbl);
     }
 
+    pub fn emit_switch(&mut self, opnd: Register, low: i64, targets: Vec<Label>) {
+        assert!(targets.iter().all(|&lbl| self.lookup_label(lbl).is_none()));
+        let start = self.offset();
+        let idx = self.add_const(ConstPoolEntry::JumpTable(low, Vec::new()));
+        let values = [opnd.to_usize() as u32, idx.0];
+        self.emit_values(BytecodeOpcode::Switch, &values);
+        self.unresolved_jump_tables.push((start, idx, targets));
+    }
+
     pub fn emit_mod(&mut self, dest: Register, lhs: Register, rhs: Register) {
         self.emit_reg3(BytecodeOpcode::Mod, dest, lhs, rhs);
     }
@@ -239,6 +336,10 @@ impl BytecodeWriter {
         self.emit_reg3(BytecodeOpcode::Sub, dest, lhs, rhs);
     }
 
+    pub fn emit_sub_saturating(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.emit_reg3(BytecodeOpcode::SubSaturating, dest, lhs, rhs);
+    }
+
     pub fn emit_mov(&mut self, dest: Register, src: Register) {
         self.emit_reg2(BytecodeOpcode::Mov, dest, src);
     }
@@ -259,6 +360,24 @@ impl BytecodeWriter {
         self.emit_reg2(BytecodeOpcode::LoadTraitObjectValue, dest, object);
     }
 
+    pub fn emit_test_trait_object(&mut self, dest: Register, idx: ConstPoolIdx, src: Register) {
+        let values = [dest.to_usize() as u32, idx.0, src.to_usize() as u32];
+        self.emit_values(BytecodeOpcode::TestTraitObject, &values);
+    }
+
+    pub fn emit_cast_trait_object(&mut self, dest: Register, idx: ConstPoolIdx, src: Register) {
+        let values = [dest.to_usize() as u32, idx.0, src.to_usize() as u32];
+        self.emit_values(BytecodeOpcode::CastTraitObject, &values);
+    }
+
+    pub fn emit_checked_cast(&mut self, dest: Register, src: Register) {
+        self.emit_reg2(BytecodeOpcode::CheckedCast, dest, src);
+    }
+
+    pub fn emit_throw(&mut self, src: Register) {
+        self.emit_reg1(BytecodeOpcode::Throw, src);
+    }
+
     pub fn emit_ret(&mut self, src: Register) {
         self.emit_reg1(BytecodeOpcode::Ret, src);
     }
@@ -365,12 +484,17 @@ impl BytecodeWriter {
         self.emit_reg3(BytecodeOpcode::StoreArray, src, array, idx);
     }
 
+    pub fn emit_array_fill(&mut self, src: Register, array: Register, length: Register) {
+        self.emit_reg3(BytecodeOpcode::ArrayFill, src, array, length);
+    }
+
     pub fn emit_load_array(&mut self, dest: Register, array: Register, idx: Register) {
         self.emit_reg3(BytecodeOpcode::LoadArray, dest, array, idx);
     }
 
     pub fn generate(mut self) -> BytecodeFunction {
         self.resolve_forward_jumps();
+        let exception_handlers = self.resolve_exception_handlers();
 
         BytecodeFunction::new(
             self.code,
@@ -378,6 +502,7 @@ impl BytecodeWriter {
             self.registers,
             self.arguments,
             self.line_number_table,
+            exception_handlers,
         )
     }
 
@@ -385,6 +510,7 @@ impl BytecodeWriter {
         self.resolve_forward_jumps();
 
         assert!(self.registers.is_empty());
+        let exception_handlers = self.resolve_exception_handlers();
 
         BytecodeFunction::new(
             self.code,
@@ -392,9 +518,32 @@ impl BytecodeWriter {
             registers,
             self.arguments,
             self.line_number_table,
+            exception_handlers,
         )
     }
 
+    fn resolve_exception_handlers(&mut self) -> Vec<BytecodeExceptionHandler> {
+        let unresolved = mem::take(&mut self.unresolved_exception_handlers);
+
+        unresolved
+            .into_iter()
+            .map(|(start, end, handler, exception_type, register)| {
+                let start = self.lookup_label(start).expect("label not bound");
+                let end = self.lookup_label(end).expect("label not bound");
+                let handler = self.lookup_label(handler).expect("label not bound");
+                assert!(start.to_usize() <= end.to_usize());
+
+                BytecodeExceptionHandler {
+                    start,
+                    end,
+                    handler,
+                    exception_type,
+                    register,
+                }
+            })
+            .collect()
+    }
+
     fn resolve_forward_jumps(&mut self) {
         let unresolved_jumps = mem::replace(&mut self.unresolved_jump_offsets, Vec::new());
 
@@ -433,6 +582,26 @@ impl BytecodeWriter {
                 self.patch_const(const_idx, ConstPoolEntry::Int32(distance as i32));
             }
         }
+
+        let unresolved_tables = mem::take(&mut self.unresolved_jump_tables);
+
+        for (start, const_idx, labels) in unresolved_tables {
+            let low = match self.const_pool[const_idx.0 as usize] {
+                ConstPoolEntry::JumpTable(low, _) => low,
+                _ => unreachable!(),
+            };
+
+            let distances = labels
+                .into_iter()
+                .map(|label| {
+                    let target = self.lookup_label(label).expect("label not bound");
+                    assert!(start.to_usize() < target.to_usize());
+                    (target.to_usize() - start.to_usize()) as u32
+                })
+                .collect();
+
+            self.patch_const(const_idx, ConstPoolEntry::JumpTable(low, distances));
+        }
     }
 
     fn emit_reg3(&mut self, inst: BytecodeOpcode, r1: Register, r2: Register, r3: Register) {
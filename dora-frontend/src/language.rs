@@ -11,6 +11,8 @@ pub use readty::{read_type, read_type_unchecked, AllowSelf, TypeParamContext};
 pub(crate) mod access;
 mod clsdefck;
 mod constdefck;
+mod constfold;
+mod desugar;
 mod dumper;
 mod enumck;
 pub mod error;
@@ -108,6 +110,9 @@ pub fn check(sa: &mut SemAnalysis) -> bool {
     fctbodyck::check(sa);
     return_on_error!(sa);
 
+    // fold constant expressions in function bodies
+    constfold::fold(sa);
+
     true
 }
 
@@ -126,7 +131,8 @@ pub fn generate_bytecode(sa: &SemAnalysis) {
         let bc = {
             let fct = fct.read();
 
-            if !fct.has_body() {
+            // Default methods of traits are compiled per impl, see implck.
+            if !fct.has_body() || fct.in_trait() {
                 continue;
             }
 
@@ -142,8 +148,41 @@ pub fn emit_bytecode(sa: &SemAnalysis, filter: &str) {
     for fct in sa.fcts.iter() {
         let fct = fct.read();
 
-        if fct_pattern_match(sa, &*fct, filter) {
-            dumper::dump(sa, Some(&*fct), fct.bytecode.as_ref().unwrap());
+        if let Some(ref bytecode) = fct.bytecode {
+            if fct_pattern_match(sa, &*fct, filter) {
+                dumper::dump(sa, Some(&*fct), bytecode);
+            }
+        }
+    }
+}
+
+pub fn emit_bytecode_json(sa: &SemAnalysis, filter: &str) {
+    let mut dumps = Vec::new();
+
+    for fct in sa.fcts.iter() {
+        let fct = fct.read();
+
+        if let Some(ref bytecode) = fct.bytecode {
+            if fct_pattern_match(sa, &*fct, filter) {
+                dumps.push(dumper::dump_json(sa, Some(&fct), bytecode));
+            }
+        }
+    }
+
+    let dumps = serde_json::Value::Array(dumps);
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&dumps).expect("serialization failed")
+    );
+}
+
+pub fn explain_desugar(sa: &SemAnalysis, filter: &str) {
+    for fct in sa.fcts.iter() {
+        let fct = fct.read();
+
+        if fct.has_body() && !fct.in_trait() && fct_pattern_match(sa, &*fct, filter) {
+            let steps = generator::trace_desugar(sa, &*fct, fct.analysis());
+            print!("{}", desugar::explain(sa, &*fct, &steps));
         }
     }
 }
@@ -326,6 +365,22 @@ pub mod tests {
         });
     }
 
+    pub fn warnings(code: &'static str, vec: &[((u32, u32), ErrorMessage)]) {
+        test::check(code, |vm| {
+            let diag = vm.diag.lock();
+            let warnings = diag.warnings();
+
+            println!("warnings = {:?}", warnings);
+            assert!(!diag.has_errors(), "program should not have errors.");
+            assert_eq!(vec.len(), warnings.len());
+
+            for (ind, warning) in warnings.iter().enumerate() {
+                assert_eq!(Some(vec[ind].0), compute_pos(code, warning));
+                assert_eq!(vec[ind].1, warning.msg);
+            }
+        });
+    }
+
     fn compute_pos(code: &str, error: &ErrorDescriptor) -> Option<(u32, u32)> {
         if let Some(span) = error.span {
             let line_starts = compute_line_starts(code);
@@ -4,6 +4,7 @@ use crate::language::sem_analysis::{
     StructDefinitionId, TraitDefinitionId, Visibility,
 };
 use crate::language::sym::Sym;
+use dora_parser::ast::VisibilityRestriction;
 
 pub fn sym_accessible_from(sa: &SemAnalysis, sym: Sym, module_id: ModuleDefinitionId) -> bool {
     match sym {
@@ -59,11 +60,7 @@ pub fn class_field_accessible_from(
     accessible_from(
         sa,
         cls.module_id,
-        if cls.visibility.is_public() && field.visibility.is_public() {
-            Visibility::Public
-        } else {
-            Visibility::Module
-        },
+        Visibility::member(cls.visibility, field.visibility),
         module_id,
     )
 }
@@ -135,11 +132,7 @@ pub fn struct_field_accessible_from(
     accessible_from(
         sa,
         struct_.module_id,
-        if struct_.visibility.is_public() && field.visibility.is_public() {
-            Visibility::Public
-        } else {
-            Visibility::Module
-        },
+        Visibility::member(struct_.visibility, field.visibility),
         module_id,
     )
 }
@@ -203,6 +196,10 @@ fn accessible_from(
         return true;
     }
 
+    if !restriction_allows(sa, target_module_id, element_visibility, user_module_id) {
+        return false;
+    }
+
     // Find the common parent of both modules.
     let common_parent_id = common_parent(sa, target_module_id, user_module_id);
 
@@ -229,17 +226,17 @@ fn accessible_from(
         // the user module has access to everything along that path including the
         // common parent modules direct children.
         if common_parent_depth + 1 == target_module.depth {
-            element_visibility.is_public()
+            element_visibility.is_exported()
         } else {
             let start_depth = common_parent_depth + 2;
             for &ns_id in &target_module.parents[start_depth..] {
-                let ns = &sa.modules[ns_id].read();
-                if !ns.visibility.is_public() {
+                if !module_exported_to(sa, ns_id, user_module_id) {
                     return false;
                 }
             }
 
-            target_module.visibility.is_public() && element_visibility.is_public()
+            module_exported_to(sa, target_module_id, user_module_id)
+                && element_visibility.is_exported()
         }
     } else {
         // No common parent: means we try to access another package
@@ -256,6 +253,53 @@ fn accessible_from(
     }
 }
 
+fn module_exported_to(
+    sa: &SemAnalysis,
+    module_id: ModuleDefinitionId,
+    user_module_id: ModuleDefinitionId,
+) -> bool {
+    let module = sa.modules[module_id].read();
+
+    match module.parent_module_id {
+        Some(parent_id) => {
+            module.visibility.is_exported()
+                && restriction_allows(sa, parent_id, module.visibility, user_module_id)
+        }
+        None => module.visibility.is_public(),
+    }
+}
+
+// `pub(mod)` elements are only visible inside the module containing the
+// element's module, `pub(super)` elements one level further up.
+fn restriction_allows(
+    sa: &SemAnalysis,
+    element_module_id: ModuleDefinitionId,
+    element_visibility: Visibility,
+    user_module_id: ModuleDefinitionId,
+) -> bool {
+    let restriction = match element_visibility {
+        Visibility::Restricted(restriction) => restriction,
+        Visibility::Public | Visibility::Module => return true,
+    };
+
+    let levels = match restriction {
+        VisibilityRestriction::Module => 1,
+        VisibilityRestriction::Super => 2,
+    };
+
+    let element_module = sa.modules[element_module_id].read();
+
+    let scope_id = if element_module.depth >= levels {
+        element_module.parents[element_module.depth - levels]
+    } else if let Some(&root_id) = element_module.parents.first() {
+        root_id
+    } else {
+        element_module_id
+    };
+
+    module_contains(sa, scope_id, user_module_id)
+}
+
 fn common_parent(
     sa: &SemAnalysis,
     lhs_id: ModuleDefinitionId,
@@ -0,0 +1,206 @@
+use std::sync::Arc;
+
+use dora_bytecode::Intrinsic;
+use dora_parser::ast::visit::{self, Visitor};
+use dora_parser::ast::*;
+
+use crate::language::sem_analysis::{AnalysisData, ConstValue, NodeMap, SemAnalysis};
+use crate::language::ty::SourceType;
+
+pub fn fold(sa: &SemAnalysis) {
+    for fct in sa.fcts.iter() {
+        let map_consts = {
+            let fct = fct.read();
+
+            let analysis = match fct.analysis {
+                Some(ref analysis) => analysis,
+                None => continue,
+            };
+
+            let mut folder = ConstFolder {
+                sa,
+                analysis,
+                map_consts: NodeMap::new(),
+            };
+
+            visit::walk_fct(&mut folder, &fct.ast);
+            folder.map_consts
+        };
+
+        fct.write().analysis.as_mut().unwrap().map_consts = map_consts;
+    }
+}
+
+// Evaluates operators on Int32, Int64 and Bool constants. Operations that
+// would trap at runtime (overflow, division by zero) are never folded.
+struct ConstFolder<'a> {
+    sa: &'a SemAnalysis,
+    analysis: &'a AnalysisData,
+    map_consts: NodeMap<ConstValue>,
+}
+
+impl<'a> ConstFolder<'a> {
+    fn eval(&self, expr: &Expr) -> Option<ConstValue> {
+        match expr {
+            Expr::LitInt(lit) => self.eval_lit_int(lit, false),
+            Expr::LitBool(lit) => Some(ConstValue::Bool(lit.value)),
+            Expr::Paren(paren) => self.eval(&paren.expr),
+            Expr::Un(un) => self.eval_un(un),
+            Expr::Bin(bin) => self.eval_bin(bin),
+            _ => None,
+        }
+    }
+
+    fn eval_lit_int(&self, lit: &ExprLitIntType, neg: bool) -> Option<ConstValue> {
+        let value = if neg {
+            (lit.value as i64).wrapping_neg()
+        } else {
+            lit.value as i64
+        };
+
+        match self.analysis.ty(lit.id) {
+            SourceType::Int32 => Some(ConstValue::Int(value as i32 as i64)),
+            SourceType::Int64 => Some(ConstValue::Int(value)),
+            _ => None,
+        }
+    }
+
+    fn eval_un(&self, un: &ExprUnType) -> Option<ConstValue> {
+        if un.op == UnOp::Neg && un.opnd.is_lit_int() {
+            return self.eval_lit_int(un.opnd.to_lit_int().unwrap(), true);
+        }
+
+        let intrinsic = self.intrinsic(un.id)?;
+        let opnd = self.eval(&un.opnd)?;
+
+        match intrinsic {
+            Intrinsic::BoolNot => Some(ConstValue::Bool(!opnd.to_bool())),
+            Intrinsic::Int32Plus | Intrinsic::Int64Plus => Some(opnd),
+            Intrinsic::Int32Neg => (opnd.to_int() as i32).checked_neg().map(from_int32),
+            Intrinsic::Int64Neg => opnd.to_int().checked_neg().map(ConstValue::Int),
+            Intrinsic::Int32Not => Some(from_int32(!(opnd.to_int() as i32))),
+            Intrinsic::Int64Not => Some(ConstValue::Int(!opnd.to_int())),
+            _ => None,
+        }
+    }
+
+    fn eval_bin(&self, bin: &ExprBinType) -> Option<ConstValue> {
+        match bin.op {
+            // The right operand is not evaluated when the left one already
+            // determines the result.
+            BinOp::And => match self.eval(&bin.lhs)?.to_bool() {
+                false => Some(ConstValue::Bool(false)),
+                true => self.eval(&bin.rhs),
+            },
+            BinOp::Or => match self.eval(&bin.lhs)?.to_bool() {
+                true => Some(ConstValue::Bool(true)),
+                false => self.eval(&bin.rhs),
+            },
+            BinOp::Assign | BinOp::Cmp(CmpOp::Is) | BinOp::Cmp(CmpOp::IsNot) => None,
+            _ if bin.op.is_any_assign() => None,
+            _ => {
+                let intrinsic = self.intrinsic(bin.id)?;
+                let lhs = self.eval(&bin.lhs)?;
+                let rhs = self.eval(&bin.rhs)?;
+
+                match intrinsic {
+                    Intrinsic::BoolEq => cmp(bin.op, lhs.to_bool(), rhs.to_bool()),
+                    Intrinsic::Int32Eq | Intrinsic::Int32Cmp => {
+                        cmp(bin.op, lhs.to_int() as i32, rhs.to_int() as i32)
+                    }
+                    Intrinsic::Int64Eq | Intrinsic::Int64Cmp => {
+                        cmp(bin.op, lhs.to_int(), rhs.to_int())
+                    }
+                    _ => eval_int_bin(intrinsic, lhs.to_int(), rhs.to_int()),
+                }
+            }
+        }
+    }
+
+    fn intrinsic(&self, id: NodeId) -> Option<Intrinsic> {
+        let call_type = self.analysis.map_calls.get(id)?;
+
+        if let Some(intrinsic) = call_type.to_intrinsic() {
+            return Some(intrinsic);
+        }
+
+        let fct = self.sa.fcts.idx(call_type.fct_id()?);
+        let intrinsic = fct.read().intrinsic;
+        intrinsic
+    }
+}
+
+impl<'a> Visitor for ConstFolder<'a> {
+    fn visit_fct(&mut self, _f: &Arc<Function>) {
+        // Nested functions are folded on their own.
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        match expr {
+            // Lambdas are folded on their own.
+            Expr::Lambda(_) => {}
+
+            Expr::Un(un) if un.op == UnOp::Neg && un.opnd.is_lit_int() => {}
+
+            Expr::Un(_) | Expr::Bin(_) => {
+                if let Some(value) = self.eval(expr) {
+                    self.map_consts.insert(expr.id(), value);
+                } else {
+                    visit::walk_expr(self, expr);
+                }
+            }
+
+            _ => visit::walk_expr(self, expr),
+        }
+    }
+}
+
+fn from_int32(value: i32) -> ConstValue {
+    ConstValue::Int(value as i64)
+}
+
+pub fn cmp<T: PartialOrd>(op: BinOp, lhs: T, rhs: T) -> Option<ConstValue> {
+    let result = match op {
+        BinOp::Cmp(CmpOp::Eq) => lhs == rhs,
+        BinOp::Cmp(CmpOp::Ne) => lhs != rhs,
+        BinOp::Cmp(CmpOp::Lt) => lhs < rhs,
+        BinOp::Cmp(CmpOp::Le) => lhs <= rhs,
+        BinOp::Cmp(CmpOp::Gt) => lhs > rhs,
+        BinOp::Cmp(CmpOp::Ge) => lhs >= rhs,
+        _ => return None,
+    };
+
+    Some(ConstValue::Bool(result))
+}
+
+pub fn eval_int_bin(intrinsic: Intrinsic, lhs: i64, rhs: i64) -> Option<ConstValue> {
+    let (l32, r32) = (lhs as i32, rhs as i32);
+
+    match intrinsic {
+        Intrinsic::Int32Add => l32.checked_add(r32).map(from_int32),
+        Intrinsic::Int32Sub => l32.checked_sub(r32).map(from_int32),
+        Intrinsic::Int32Mul => l32.checked_mul(r32).map(from_int32),
+        Intrinsic::Int32Div => l32.checked_div(r32).map(from_int32),
+        Intrinsic::Int32Mod => l32.checked_rem(r32).map(from_int32),
+        Intrinsic::Int32Or => Some(from_int32(l32 | r32)),
+        Intrinsic::Int32And => Some(from_int32(l32 & r32)),
+        Intrinsic::Int32Xor => Some(from_int32(l32 ^ r32)),
+        Intrinsic::Int32Shl => Some(from_int32(l32.wrapping_shl(r32 as u32))),
+        Intrinsic::Int32Sar => Some(from_int32(l32.wrapping_shr(r32 as u32))),
+        Intrinsic::Int32Shr => Some(from_int32((l32 as u32).wrapping_shr(r32 as u32) as i32)),
+
+        Intrinsic::Int64Add => lhs.checked_add(rhs).map(ConstValue::Int),
+        Intrinsic::Int64Sub => lhs.checked_sub(rhs).map(ConstValue::Int),
+        Intrinsic::Int64Mul => lhs.checked_mul(rhs).map(ConstValue::Int),
+        Intrinsic::Int64Div => lhs.checked_div(rhs).map(ConstValue::Int),
+        Intrinsic::Int64Mod => lhs.checked_rem(rhs).map(ConstValue::Int),
+        Intrinsic::Int64Or => Some(ConstValue::Int(lhs | rhs)),
+        Intrinsic::Int64And => Some(ConstValue::Int(lhs & rhs)),
+        Intrinsic::Int64Xor => Some(ConstValue::Int(lhs ^ rhs)),
+        Intrinsic::Int64Shl => Some(ConstValue::Int(lhs.wrapping_shl(rhs as u32))),
+        Intrinsic::Int64Sar => Some(ConstValue::Int(lhs.wrapping_shr(rhs as u32))),
+        Intrinsic::Int64Shr => Some(ConstValue::Int((lhs as u64).wrapping_shr(rhs as u32) as i64)),
+
+        _ => None,
+    }
+}
@@ -0,0 +1,149 @@
+use std::fmt::Write;
+
+use dora_parser::Span;
+
+use crate::language::sem_analysis::{FctDefinition, SemAnalysis, SourceFileId};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DesugarKind {
+    For,
+    Operator,
+    Template,
+}
+
+impl DesugarKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            DesugarKind::For => "for",
+            DesugarKind::Operator => "operator",
+            DesugarKind::Template => "template",
+        }
+    }
+}
+
+// A construct the bytecode generator lowers into simpler operations. The
+// lowered form is printed as pseudo-source, `$`-prefixed names are temporaries
+// introduced by the generator.
+#[derive(Clone, Debug)]
+pub struct DesugarStep {
+    pub kind: DesugarKind,
+    pub span: Span,
+    pub lowered: String,
+}
+
+pub fn source_snippet(sa: &SemAnalysis, file_id: SourceFileId, span: Span) -> &str {
+    let content = &sa.source_file(file_id).content;
+    &content[span.start() as usize..span.end() as usize]
+}
+
+pub fn explain(sa: &SemAnalysis, fct: &FctDefinition, steps: &[DesugarStep]) -> String {
+    let mut output = String::new();
+    writeln!(output, "desugar {}:", fct.display_name(sa)).unwrap();
+
+    if steps.is_empty() {
+        writeln!(output, "  nothing to desugar").unwrap();
+    }
+
+    for step in steps {
+        let (line, column) = sa.compute_line_column(fct.file_id, step.span);
+        let original = source_snippet(sa, fct.file_id, step.span);
+
+        writeln!(output, "  {} @ {}:{}", step.kind.name(), line, column).unwrap();
+        // The original indentation of the source is meaningless here.
+        write_indented(
+            &mut output,
+            "before: ",
+            original.lines().map(str::trim_start),
+        );
+        write_indented(&mut output, "after:  ", step.lowered.lines());
+    }
+
+    output
+}
+
+fn write_indented<'a>(output: &mut String, label: &str, lines: impl Iterator<Item = &'a str>) {
+    let indent = " ".repeat(label.len());
+
+    for (idx, line) in lines.enumerate() {
+        let prefix = if idx == 0 { label } else { &indent };
+        writeln!(output, "    {}{}", prefix, line).unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::language::generator::trace_desugar;
+    use crate::language::tests::ok_with_test;
+
+    use super::explain;
+
+    fn explain_fct(code: &'static str, name: &str) -> String {
+        ok_with_test(code, |sa| {
+            let fct_id = sa.fct_by_name(name).expect("function not found");
+            let fct = sa.fcts.idx(fct_id);
+            let fct = fct.read();
+            let steps = trace_desugar(sa, &*fct, fct.analysis());
+            explain(sa, &*fct, &steps)
+        })
+    }
+
+    #[test]
+    fn explain_for_loop() {
+        let output = explain_fct(
+            "fn f(x: Array[Int32]) {
+                for i in x { i; }
+            }",
+            "f",
+        );
+
+        assert_eq!(
+            output,
+            "desugar f:
+  for @ 2:17
+    before: for i in x { i; }
+    after:  let $iter = x.makeIterator();
+            while let Some(i) = $iter.next() { i; }
+"
+        );
+    }
+
+    #[test]
+    fn explain_operator_and_template() {
+        let output = explain_fct(
+            "class Foo(value: Int64)
+            impl Foo {
+                fn plus(other: Foo): Foo { Foo(self.value + other.value) }
+            }
+            fn f(a: Foo, b: Foo): String {
+                let c = a + b;
+                \"value ${c.value}\"
+            }",
+            "f",
+        );
+
+        assert_eq!(
+            output,
+            "desugar f:
+  operator @ 6:25
+    before: a + b
+    after:  a.plus(b)
+  template @ 7:17
+    before: \"value ${c.value}\"
+    after:  {
+                let $buf = StringBuffer::empty();
+                $buf.append(\"value \");
+                $buf.append(c.value.toString());
+                $buf.toString()
+            }
+"
+        );
+    }
+
+    #[test]
+    fn explain_nothing() {
+        assert_eq!(
+            explain_fct("fn f(a: Int64): Int64 { a + 1 }", "f"),
+            "desugar f:\n  nothing to desugar\n"
+        );
+    }
+}
@@ -1,4 +1,6 @@
-use std::io;
+use std::fmt;
+
+use serde_json::{json, Value};
 
 use crate::language::generator::{ty_array_from_bty, ty_from_bty};
 use crate::language::sem_analysis::{
@@ -11,17 +13,13 @@ use dora_bytecode::{
 };
 
 pub fn dump(vm: &SemAnalysis, fct: Option<&FctDefinition>, bc: &BytecodeFunction) {
-    let mut stdout = io::stdout();
     if let Some(fct) = fct {
         println!("{}", fct.display_name(vm));
     }
-    let mut visitor = BytecodeDumper {
-        bc,
-        pos: BytecodeOffset(0),
-        w: &mut stdout,
-        sa: vm,
-    };
-    read(bc.code(), &mut visitor);
+
+    for inst in dump_instructions(vm, bc) {
+        println!("{}", inst);
+    }
 
     let align = "   ";
 
@@ -36,186 +34,21 @@ pub fn dump(vm: &SemAnalysis, fct: Option<&FctDefinition>, bc: &BytecodeFunction
     println!("  Constants:");
 
     for (idx, entry) in bc.const_pool_entries().iter().enumerate() {
-        match entry {
-            ConstPoolEntry::String(ref value) => {
-                println!("{}{} => String \"{}\"", align, idx, value)
-            }
-            ConstPoolEntry::Int32(ref value) => println!("{}{} => Int32 {}", align, idx, value),
-            ConstPoolEntry::Int64(ref value) => println!("{}{} => Int64 {}", align, idx, value),
-            ConstPoolEntry::Float32(ref value) => println!("{}{} => Float32 {}", align, idx, value),
-            ConstPoolEntry::Float64(ref value) => println!("{}{} => Float64 {}", align, idx, value),
-            ConstPoolEntry::Char(ref value) => println!("{}{} => Char {}", align, idx, value),
-            ConstPoolEntry::Class(cls_id, type_params) => {
-                let cls = vm.classes.idx(ClassDefinitionId(cls_id.0 as usize));
-                let cls = cls.read();
-                let type_params = ty_array_from_bty(type_params);
-                println!(
-                    "{}{} => Class {}",
-                    align,
-                    idx,
-                    cls.name_with_params(vm, &type_params)
-                )
-            }
-            ConstPoolEntry::Struct(struct_id, type_params) => {
-                let struct_ = vm.structs.idx(StructDefinitionId(struct_id.0));
-                let struct_ = struct_.read();
-                let type_params = ty_array_from_bty(type_params);
-                println!(
-                    "{}{} => Struct {}",
-                    align,
-                    idx,
-                    struct_.name_with_params(vm, &type_params)
-                )
-            }
-            ConstPoolEntry::StructField(struct_id, type_params, field_idx) => {
-                let struct_ = vm.structs.idx(StructDefinitionId(struct_id.0));
-                let struct_ = struct_.read();
-                let type_params = ty_array_from_bty(type_params);
-                let field = &struct_.fields[*field_idx as usize];
-                let fname = vm.interner.str(field.name);
-                println!(
-                    "{}{} => StructField {}.{}",
-                    align,
-                    idx,
-                    struct_.name_with_params(vm, &type_params),
-                    fname
-                )
-            }
-            ConstPoolEntry::Enum(enum_id, type_params) => {
-                let enum_ = &vm.enums[EnumDefinitionId(enum_id.0)];
-                let enum_ = enum_.read();
-                let type_params = ty_array_from_bty(type_params);
-                println!(
-                    "{}{} => Enum {}",
-                    align,
-                    idx,
-                    enum_.name_with_params(vm, &type_params)
-                )
-            }
-            ConstPoolEntry::EnumVariant(cls_id, type_params, variant_idx) => {
-                let enum_ = &vm.enums[EnumDefinitionId(cls_id.0)];
-                let enum_ = enum_.read();
-                let variant = &enum_.variants[*variant_idx as usize];
-                let variant_name = vm.interner.str(variant.name);
-                let type_params = ty_array_from_bty(type_params);
-                println!(
-                    "{}{} => EnumVariant {}::{}",
-                    align,
-                    idx,
-                    enum_.name_with_params(vm, &type_params),
-                    variant_name,
-                )
-            }
-            ConstPoolEntry::EnumElement(enum_id, type_params, variant_idx, element_idx) => {
-                let enum_ = &vm.enums[EnumDefinitionId(enum_id.0)];
-                let enum_ = enum_.read();
-                let type_params = ty_array_from_bty(type_params);
-                let variant = &enum_.variants[*variant_idx as usize];
-                let variant_name = vm.interner.str(variant.name);
-                println!(
-                    "{}{} => EnumVariantElement {}::{}::{}",
-                    align,
-                    idx,
-                    enum_.name_with_params(vm, &type_params),
-                    variant_name,
-                    element_idx,
-                )
-            }
-            ConstPoolEntry::Field(cls_id, type_params, field_id) => {
-                let cls = vm.classes.idx(ClassDefinitionId(cls_id.0 as usize));
-                let cls = cls.read();
-                let type_params = ty_array_from_bty(type_params);
-                let field = &cls.fields[*field_id as usize];
-                let fname = vm.interner.str(field.name);
-                println!(
-                    "{}{} => Field {}.{}",
-                    align,
-                    idx,
-                    cls.name_with_params(vm, &type_params),
-                    fname,
-                )
-            }
-            ConstPoolEntry::Fct(fct_id, type_params) => {
-                let fct = vm.fcts.idx(FctDefinitionId(fct_id.0 as usize));
-                let fct = fct.read();
-                let type_params = ty_array_from_bty(type_params);
-
-                if type_params.len() > 0 {
-                    let type_params = type_params
-                        .iter()
-                        .map(|n| n.name(vm))
-                        .collect::<Vec<_>>()
-                        .join(", ");
-                    println!(
-                        "{}{} => Fct {} with [{}]",
-                        align,
-                        idx,
-                        fct.display_name(vm),
-                        type_params
-                    );
-                } else {
-                    println!("{}{} => Fct {}", align, idx, fct.display_name(vm));
-                }
-            }
-            ConstPoolEntry::Generic(id, fct_id, type_params) => {
-                let fct = vm.fcts.idx(FctDefinitionId(fct_id.0 as usize));
-                let fct = fct.read();
-                let type_params = ty_array_from_bty(type_params);
+        println!("{}{} => {}", align, idx, const_pool_entry_name(vm, entry));
+    }
 
-                if type_params.len() > 0 {
-                    let type_params = type_params
-                        .iter()
-                        .map(|n| n.name(vm))
-                        .collect::<Vec<_>>()
-                        .join(", ");
-                    println!(
-                        "{}{} => TypeParam({}) Method {} with [{}]",
-                        align,
-                        idx,
-                        id,
-                        fct.display_name(vm),
-                        type_params
-                    );
-                } else {
-                    println!(
-                        "{}{} => TypeParam({}) Method {}",
-                        align,
-                        idx,
-                        id,
-                        fct.display_name(vm)
-                    );
-                }
-            }
-            ConstPoolEntry::Trait(trait_id, type_params, object_ty) => {
-                let trait_id = TraitDefinitionId(trait_id.0);
-                let trait_ = vm.traits.idx(trait_id);
-                let trait_ = trait_.read();
-                let type_params = ty_array_from_bty(type_params);
-                let object_ty = ty_from_bty(object_ty.clone());
-                println!(
-                    "{}{} => Trait {} from {}",
-                    align,
-                    idx,
-                    trait_.name_with_params(vm, &type_params),
-                    object_ty.name(vm),
-                )
-            }
-            ConstPoolEntry::TupleElement(_tuple_id, _idx) => {
-                println!("{}{} => TupleElement {}.{}", align, idx, "subtypes", idx)
-            }
-            ConstPoolEntry::Tuple(ref subtypes) => {
-                let source_type_array = ty_array_from_bty(subtypes);
-                let tuple_name = source_type_array.tuple_name(vm);
-                println!("{}{} => Tuple {}", align, idx, tuple_name)
-            }
-            ConstPoolEntry::Lambda(ref params, ref return_type) => {
-                let params = ty_array_from_bty(params);
-                let return_type = ty_from_bty(return_type.clone());
-                let params = params.tuple_name(vm);
-                let return_type = return_type.name(vm);
-                println!("{}{} => Lambda {}: {}", align, idx, params, return_type)
-            }
-        }
+    println!();
+    println!("  Exception Handlers:");
+    for handler in bc.exception_handlers() {
+        println!(
+            "{}{}-{} => {} ({}, {:?})",
+            align,
+            handler.start.to_u32(),
+            handler.end.to_u32(),
+            handler.handler.to_u32(),
+            handler.register,
+            handler.exception_type,
+        );
     }
 
     println!();
@@ -226,50 +59,396 @@ pub fn dump(vm: &SemAnalysis, fct: Option<&FctDefinition>, bc: &BytecodeFunction
     println!();
 }
 
-struct BytecodeDumper<'a> {
-    bc: &'a BytecodeFunction,
-    pos: BytecodeOffset,
-    w: &'a mut dyn io::Write,
-    sa: &'a SemAnalysis,
+// Same information as `dump` but as JSON for consumption by tools.
+pub fn dump_json(vm: &SemAnalysis, fct: Option<&FctDefinition>, bc: &BytecodeFunction) -> Value {
+    let instructions = dump_instructions(vm, bc)
+        .iter()
+        .map(|inst| inst.to_json())
+        .collect::<Vec<_>>();
+
+    let registers = bc
+        .registers()
+        .iter()
+        .enumerate()
+        .map(|(idx, ty)| json!({ "index": idx, "type": format!("{:?}", ty) }))
+        .collect::<Vec<_>>();
+
+    let constants = bc
+        .const_pool_entries()
+        .iter()
+        .enumerate()
+        .map(|(idx, entry)| {
+            json!({
+                "index": idx,
+                "kind": const_pool_entry_kind(entry),
+                "value": const_pool_entry_name(vm, entry),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let locations = bc
+        .locations()
+        .iter()
+        .map(|(offset, location)| {
+            json!({
+                "offset": offset.to_u32(),
+                "line": location.line(),
+                "column": location.column(),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let exception_handlers = bc
+        .exception_handlers()
+        .iter()
+        .map(|handler| {
+            json!({
+                "start": handler.start.to_u32(),
+                "end": handler.end.to_u32(),
+                "handler": handler.handler.to_u32(),
+                "register": handler.register.0,
+                "type": format!("{:?}", handler.exception_type),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    json!({
+        "name": fct.map(|fct| fct.display_name(vm)),
+        "instructions": instructions,
+        "registers": registers,
+        "constants": constants,
+        "exception_handlers": exception_handlers,
+        "locations": locations,
+    })
 }
 
-impl<'a> BytecodeDumper<'a> {
-    fn emit_inst(&mut self, name: &str) {
-        self.emit_start(name);
-        writeln!(self.w, "").expect("write! failed");
+fn dump_instructions(sa: &SemAnalysis, bc: &BytecodeFunction) -> Vec<Instruction> {
+    let mut visitor = BytecodeDumper {
+        bc,
+        pos: BytecodeOffset(0),
+        instructions: Vec::new(),
+        sa,
+    };
+    read(bc.code(), &mut visitor);
+    visitor.instructions
+}
+
+fn const_pool_entry_kind(entry: &ConstPoolEntry) -> &'static str {
+    match entry {
+        ConstPoolEntry::String(..) => "String",
+        ConstPoolEntry::Float32(..) => "Float32",
+        ConstPoolEntry::Float64(..) => "Float64",
+        ConstPoolEntry::Int32(..) => "Int32",
+        ConstPoolEntry::Int64(..) => "Int64",
+        ConstPoolEntry::Char(..) => "Char",
+        ConstPoolEntry::Class(..) => "Class",
+        ConstPoolEntry::Field(..) => "Field",
+        ConstPoolEntry::Fct(..) => "Fct",
+        ConstPoolEntry::Generic(..) => "Generic",
+        ConstPoolEntry::Enum(..) => "Enum",
+        ConstPoolEntry::EnumVariant(..) => "EnumVariant",
+        ConstPoolEntry::EnumElement(..) => "EnumElement",
+        ConstPoolEntry::Struct(..) => "Struct",
+        ConstPoolEntry::StructField(..) => "StructField",
+        ConstPoolEntry::Trait(..) => "Trait",
+        ConstPoolEntry::TupleElement(..) => "TupleElement",
+        ConstPoolEntry::Tuple(..) => "Tuple",
+        ConstPoolEntry::Lambda(..) => "Lambda",
+        ConstPoolEntry::JumpTable(..) => "JumpTable",
+    }
+}
+
+fn const_pool_entry_name(vm: &SemAnalysis, entry: &ConstPoolEntry) -> String {
+    match entry {
+        ConstPoolEntry::String(ref value) => format!("String \"{}\"", value),
+        ConstPoolEntry::Int32(ref value) => format!("Int32 {}", value),
+        ConstPoolEntry::Int64(ref value) => format!("Int64 {}", value),
+        ConstPoolEntry::Float32(ref value) => format!("Float32 {}", value),
+        ConstPoolEntry::Float64(ref value) => format!("Float64 {}", value),
+        ConstPoolEntry::Char(ref value) => format!("Char {}", value),
+        ConstPoolEntry::Class(cls_id, type_params) => {
+            let cls = vm.classes.idx(ClassDefinitionId(cls_id.0 as usize));
+            let cls = cls.read();
+            let type_params = ty_array_from_bty(type_params);
+            format!("Class {}", cls.name_with_params(vm, &type_params))
+        }
+        ConstPoolEntry::Struct(struct_id, type_params) => {
+            let struct_ = vm.structs.idx(StructDefinitionId(struct_id.0));
+            let struct_ = struct_.read();
+            let type_params = ty_array_from_bty(type_params);
+            format!("Struct {}", struct_.name_with_params(vm, &type_params))
+        }
+        ConstPoolEntry::StructField(struct_id, type_params, field_idx) => {
+            let struct_ = vm.structs.idx(StructDefinitionId(struct_id.0));
+            let struct_ = struct_.read();
+            let type_params = ty_array_from_bty(type_params);
+            let field = &struct_.fields[*field_idx as usize];
+            let fname = vm.interner.str(field.name);
+            format!(
+                "StructField {}.{}",
+                struct_.name_with_params(vm, &type_params),
+                fname
+            )
+        }
+        ConstPoolEntry::Enum(enum_id, type_params) => {
+            let enum_ = &vm.enums[EnumDefinitionId(enum_id.0)];
+            let enum_ = enum_.read();
+            let type_params = ty_array_from_bty(type_params);
+            format!("Enum {}", enum_.name_with_params(vm, &type_params))
+        }
+        ConstPoolEntry::EnumVariant(cls_id, type_params, variant_idx) => {
+            let enum_ = &vm.enums[EnumDefinitionId(cls_id.0)];
+            let enum_ = enum_.read();
+            let variant = &enum_.variants[*variant_idx as usize];
+            let variant_name = vm.interner.str(variant.name);
+            let type_params = ty_array_from_bty(type_params);
+            format!(
+                "EnumVariant {}::{}",
+                enum_.name_with_params(vm, &type_params),
+                variant_name,
+            )
+        }
+        ConstPoolEntry::EnumElement(enum_id, type_params, variant_idx, element_idx) => {
+            let enum_ = &vm.enums[EnumDefinitionId(enum_id.0)];
+            let enum_ = enum_.read();
+            let type_params = ty_array_from_bty(type_params);
+            let variant = &enum_.variants[*variant_idx as usize];
+            let variant_name = vm.interner.str(variant.name);
+            format!(
+                "EnumVariantElement {}::{}::{}",
+                enum_.name_with_params(vm, &type_params),
+                variant_name,
+                element_idx,
+            )
+        }
+        ConstPoolEntry::Field(cls_id, type_params, field_id) => {
+            let cls = vm.classes.idx(ClassDefinitionId(cls_id.0 as usize));
+            let cls = cls.read();
+            let type_params = ty_array_from_bty(type_params);
+            let field = &cls.fields[*field_id as usize];
+            let fname = vm.interner.str(field.name);
+            format!("Field {}.{}", cls.name_with_params(vm, &type_params), fname)
+        }
+        ConstPoolEntry::Fct(fct_id, type_params) => {
+            let fct = vm.fcts.idx(FctDefinitionId(fct_id.0 as usize));
+            let fct = fct.read();
+            let type_params = ty_array_from_bty(type_params);
+
+            if type_params.len() > 0 {
+                let type_params = type_params
+                    .iter()
+                    .map(|n| n.name(vm))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!("Fct {} with [{}]", fct.display_name(vm), type_params)
+            } else {
+                format!("Fct {}", fct.display_name(vm))
+            }
+        }
+        ConstPoolEntry::Generic(id, fct_id, type_params) => {
+            let fct = vm.fcts.idx(FctDefinitionId(fct_id.0 as usize));
+            let fct = fct.read();
+            let type_params = ty_array_from_bty(type_params);
+
+            if type_params.len() > 0 {
+                let type_params = type_params
+                    .iter()
+                    .map(|n| n.name(vm))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!(
+                    "TypeParam({}) Method {} with [{}]",
+                    id,
+                    fct.display_name(vm),
+                    type_params
+                )
+            } else {
+                format!("TypeParam({}) Method {}", id, fct.display_name(vm))
+            }
+        }
+        ConstPoolEntry::Trait(trait_id, type_params, object_ty) => {
+            let trait_id = TraitDefinitionId(trait_id.0);
+            let trait_ = vm.traits.idx(trait_id);
+            let trait_ = trait_.read();
+            let type_params = ty_array_from_bty(type_params);
+            let object_ty = ty_from_bty(object_ty.clone());
+            format!(
+                "Trait {} from {}",
+                trait_.name_with_params(vm, &type_params),
+                object_ty.name(vm),
+            )
+        }
+        ConstPoolEntry::TupleElement(tuple_ty, subtype_idx) => {
+            let tuple_ty = ty_from_bty(tuple_ty.clone());
+            format!("TupleElement {}.{}", tuple_ty.name(vm), subtype_idx)
+        }
+        ConstPoolEntry::Tuple(ref subtypes) => {
+            let source_type_array = ty_array_from_bty(subtypes);
+            format!("Tuple {}", source_type_array.tuple_name(vm))
+        }
+        ConstPoolEntry::Lambda(ref params, ref return_type) => {
+            let params = ty_array_from_bty(params);
+            let return_type = ty_from_bty(return_type.clone());
+            format!("Lambda {}: {}", params.tuple_name(vm), return_type.name(vm))
+        }
+        ConstPoolEntry::JumpTable(low, ref targets) => {
+            format!("JumpTable {} {:?}", low, targets)
+        }
+    }
+}
+
+enum Operand {
+    Register(Register),
+    ConstPoolIdx(ConstPoolIdx),
+    GlobalId(GlobalId),
+    Value(i64),
+}
+
+impl Operand {
+    fn to_json(&self) -> Value {
+        match self {
+            Operand::Register(reg) => json!({ "kind": "register", "value": reg.0 }),
+            Operand::ConstPoolIdx(idx) => json!({ "kind": "const", "value": idx.0 }),
+            Operand::GlobalId(id) => json!({ "kind": "global", "value": id.0 }),
+            Operand::Value(value) => json!({ "kind": "value", "value": value }),
+        }
     }
+}
 
-    fn emit_reg3(&mut self, name: &str, r1: Register, r2: Register, r3: Register) {
-        self.emit_start(name);
-        writeln!(self.w, " {}, {}, {}", r1, r2, r3).expect("write! failed");
+impl fmt::Display for Operand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Operand::Register(reg) => write!(f, "{}", reg),
+            Operand::ConstPoolIdx(idx) => write!(f, "ConstPoolIdx({})", idx.0),
+            Operand::GlobalId(id) => write!(f, "GlobalId({})", id.0),
+            Operand::Value(value) => write!(f, "{}", value),
+        }
     }
+}
+
+struct Instruction {
+    offset: BytecodeOffset,
+    name: &'static str,
+    operands: Vec<Operand>,
+    comment: Option<String>,
+}
+
+impl Instruction {
+    fn to_json(&self) -> Value {
+        let operands = self
+            .operands
+            .iter()
+            .map(|operand| operand.to_json())
+            .collect::<Vec<_>>();
+
+        json!({
+            "offset": self.offset.to_u32(),
+            "opcode": self.name,
+            "operands": operands,
+            "comment": self.comment,
+        })
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:3}: {}", self.offset.to_usize(), self.name)?;
+
+        for (idx, operand) in self.operands.iter().enumerate() {
+            let separator = if idx == 0 { " " } else { ", " };
+            write!(f, "{}{}", separator, operand)?;
+        }
+
+        if let Some(ref comment) = self.comment {
+            write!(f, " # {}", comment)?;
+        }
 
-    fn emit_reg2(&mut self, name: &str, r1: Register, r2: Register) {
-        self.emit_start(name);
-        writeln!(self.w, " {}, {}", r1, r2).expect("write! failed");
+        Ok(())
     }
+}
 
-    fn emit_tuple_load(&mut self, name: &str, r1: Register, r2: Register, idx: ConstPoolIdx) {
-        self.emit_start(name);
+struct BytecodeDumper<'a> {
+    bc: &'a BytecodeFunction,
+    pos: BytecodeOffset,
+    instructions: Vec<Instruction>,
+    sa: &'a SemAnalysis,
+}
+
+impl<'a> BytecodeDumper<'a> {
+    fn emit(&mut self, name: &'static str, operands: Vec<Operand>, comment: Option<String>) {
+        self.instructions.push(Instruction {
+            offset: self.pos,
+            name,
+            operands,
+            comment,
+        });
+    }
+
+    fn emit_inst(&mut self, name: &'static str) {
+        self.emit(name, Vec::new(), None);
+    }
+
+    fn emit_reg3(&mut self, name: &'static str, r1: Register, r2: Register, r3: Register) {
+        let operands = vec![
+            Operand::Register(r1),
+            Operand::Register(r2),
+            Operand::Register(r3),
+        ];
+        self.emit(name, operands, None);
+    }
+
+    fn emit_reg2(&mut self, name: &'static str, r1: Register, r2: Register) {
+        self.emit(
+            name,
+            vec![Operand::Register(r1), Operand::Register(r2)],
+            None,
+        );
+    }
+
+    fn emit_reg1(&mut self, name: &'static str, r1: Register) {
+        self.emit(name, vec![Operand::Register(r1)], None);
+    }
+
+    fn emit_reg_const(
+        &mut self,
+        name: &'static str,
+        r1: Register,
+        idx: ConstPoolIdx,
+        comment: String,
+    ) {
+        let operands = vec![Operand::Register(r1), Operand::ConstPoolIdx(idx)];
+        self.emit(name, operands, Some(comment));
+    }
+
+    fn emit_tuple_load(
+        &mut self,
+        name: &'static str,
+        r1: Register,
+        r2: Register,
+        idx: ConstPoolIdx,
+    ) {
         let (tuple_ty, subtype_idx) = match self.bc.const_pool(idx) {
             ConstPoolEntry::TupleElement(tuple_ty, subtype_idx) => {
                 (ty_from_bty(tuple_ty.clone()), *subtype_idx)
             }
             _ => unreachable!(),
         };
-        writeln!(
-            self.w,
-            " {}, {}, {}, {}",
-            r1,
-            r2,
-            tuple_ty.name(self.sa),
-            subtype_idx
-        )
-        .expect("write! failed");
-    }
-
-    fn emit_enum_load(&mut self, name: &str, r1: Register, r2: Register, idx: ConstPoolIdx) {
-        self.emit_start(name);
+        let operands = vec![
+            Operand::Register(r1),
+            Operand::Register(r2),
+            Operand::ConstPoolIdx(idx),
+        ];
+        let comment = format!("{}.{}", tuple_ty.name(self.sa), subtype_idx);
+        self.emit(name, operands, Some(comment));
+    }
+
+    fn emit_enum_load(
+        &mut self,
+        name: &'static str,
+        r1: Register,
+        r2: Register,
+        idx: ConstPoolIdx,
+    ) {
         let (enum_id, type_params, variant_idx, element_idx) = match self.bc.const_pool(idx) {
             ConstPoolEntry::EnumElement(enum_id, type_params, variant_idx, element_idx) => (
                 *enum_id,
@@ -286,16 +465,23 @@ impl<'a> BytecodeDumper<'a> {
             .sa
             .interner
             .str(enum_.variants[variant_idx as usize].name);
-        writeln!(
-            self.w,
-            " {}, {}, ConstPoolIdx({}), {} # {}::{}.{}",
-            r1, r2, idx.0, element_idx, enum_name, variant_name, element_idx
-        )
-        .expect("write! failed");
-    }
-
-    fn emit_enum_variant(&mut self, name: &str, r1: Register, r2: Register, idx: ConstPoolIdx) {
-        self.emit_start(name);
+        let operands = vec![
+            Operand::Register(r1),
+            Operand::Register(r2),
+            Operand::ConstPoolIdx(idx),
+            Operand::Value(element_idx as i64),
+        ];
+        let comment = format!("{}::{}.{}", enum_name, variant_name, element_idx);
+        self.emit(name, operands, Some(comment));
+    }
+
+    fn emit_enum_variant(
+        &mut self,
+        name: &'static str,
+        r1: Register,
+        r2: Register,
+        idx: ConstPoolIdx,
+    ) {
         let (enum_id, type_params) = match self.bc.const_pool(idx) {
             ConstPoolEntry::Enum(enum_id, type_params) => {
                 (*enum_id, ty_array_from_bty(type_params))
@@ -305,57 +491,62 @@ impl<'a> BytecodeDumper<'a> {
         let enum_ = &self.sa.enums[EnumDefinitionId(enum_id.0)];
         let enum_ = enum_.read();
         let enum_name = enum_.name_with_params(self.sa, &type_params);
-        writeln!(
-            self.w,
-            " {}, {}, ConstPoolIdx({}) # {}",
-            r1, r2, idx.0, enum_name,
-        )
-        .expect("write! failed");
-    }
-
-    fn emit_reg1(&mut self, name: &str, r1: Register) {
-        self.emit_start(name);
-        writeln!(self.w, " {}", r1).expect("write! failed");
+        let operands = vec![
+            Operand::Register(r1),
+            Operand::Register(r2),
+            Operand::ConstPoolIdx(idx),
+        ];
+        self.emit(name, operands, Some(enum_name));
     }
 
-    fn emit_cond_jump(&mut self, name: &str, opnd: Register, offset: i32) {
-        self.emit_start(name);
+    fn emit_cond_jump(&mut self, name: &'static str, opnd: Register, offset: i32) {
         let bc_target = self.pos.to_u32() as i32 + offset;
-        writeln!(self.w, " {}, {} # target {}", opnd, offset, bc_target).expect("write! failed");
+        let operands = vec![Operand::Register(opnd), Operand::Value(offset as i64)];
+        self.emit(name, operands, Some(format!("target {}", bc_target)));
     }
 
-    fn emit_cond_jump_const(&mut self, name: &str, opnd: Register, idx: ConstPoolIdx) {
-        self.emit_start(name);
+    fn emit_cond_jump_const(&mut self, name: &'static str, opnd: Register, idx: ConstPoolIdx) {
         let offset = self.bc.const_pool(idx).to_int32().expect("int expected");
         let bc_target = self.pos.to_u32() as i32 + offset;
-        writeln!(
-            self.w,
-            " {}, ConstPooldId({}) # offset {}, target {}",
-            opnd, idx.0, offset, bc_target
-        )
-        .expect("write! failed");
+        let comment = format!("offset {}, target {}", offset, bc_target);
+        self.emit_reg_const(name, opnd, idx, comment);
     }
 
-    fn emit_jump(&mut self, name: &str, offset: i32) {
-        self.emit_start(name);
+    fn emit_jump(&mut self, name: &'static str, offset: i32) {
         let bc_target = self.pos.to_u32() as i32 + offset;
-        writeln!(self.w, " {} # target {}", offset, bc_target).expect("write! failed");
+        let operands = vec![Operand::Value(offset as i64)];
+        self.emit(name, operands, Some(format!("target {}", bc_target)));
     }
 
-    fn emit_jump_const(&mut self, name: &str, idx: ConstPoolIdx) {
-        self.emit_start(name);
+    fn emit_jump_const(&mut self, name: &'static str, idx: ConstPoolIdx) {
         let offset = self.bc.const_pool(idx).to_int32().expect("int expected");
         let bc_target = self.pos.to_u32() as i32 + offset;
-        writeln!(
-            self.w,
-            " ConstPoolId({}) # offset {}, target {}",
-            idx.0, offset, bc_target
-        )
-        .expect("write! failed");
+        let comment = format!("offset {}, target {}", offset, bc_target);
+        self.emit(name, vec![Operand::ConstPoolIdx(idx)], Some(comment));
     }
 
-    fn emit_field(&mut self, name: &str, r1: Register, r2: Register, field_idx: ConstPoolIdx) {
-        self.emit_start(name);
+    fn emit_switch(&mut self, name: &'static str, opnd: Register, idx: ConstPoolIdx) {
+        let (low, targets) = self
+            .bc
+            .const_pool(idx)
+            .to_jump_table()
+            .expect("jump table expected");
+        let bc_targets = targets
+            .iter()
+            .map(|&offset| (self.pos.to_u32() + offset).to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let comment = format!("low {}, targets {}", low, bc_targets);
+        self.emit_reg_const(name, opnd, idx, comment);
+    }
+
+    fn emit_field(
+        &mut self,
+        name: &'static str,
+        r1: Register,
+        r2: Register,
+        field_idx: ConstPoolIdx,
+    ) {
         let (cname, fname) = match self.bc.const_pool(field_idx) {
             ConstPoolEntry::Field(cls_id, type_params, field_id) => {
                 let cls = self.sa.classes.idx(ClassDefinitionId(cls_id.0 as usize));
@@ -382,26 +573,25 @@ impl<'a> BytecodeDumper<'a> {
             _ => unreachable!(),
         };
 
-        writeln!(
-            self.w,
-            " {}, {}, ConstPoolIdx({}) # {}.{}",
-            r1, r2, field_idx.0, cname, fname,
-        )
-        .expect("write! failed");
+        let operands = vec![
+            Operand::Register(r1),
+            Operand::Register(r2),
+            Operand::ConstPoolIdx(field_idx),
+        ];
+        self.emit(name, operands, Some(format!("{}.{}", cname, fname)));
     }
 
-    fn emit_global(&mut self, name: &str, r1: Register, gid: GlobalId) {
-        self.emit_start(name);
+    fn emit_global(&mut self, name: &'static str, r1: Register, gid: GlobalId) {
         let global_var = self.sa.globals.idx(GlobalDefinitionId(gid.0));
         let global_var = global_var.read();
-        let name = self.sa.interner.str(global_var.name);
-        writeln!(self.w, " {}, GlobalId({}) # {}", r1, gid.0, name).expect("write! failed");
+        let global_name = self.sa.interner.str(global_var.name).to_string();
+        let operands = vec![Operand::Register(r1), Operand::GlobalId(gid)];
+        self.emit(name, operands, Some(global_name));
     }
 
-    fn emit_fct(&mut self, name: &str, r1: Register, fid: ConstPoolIdx) {
-        self.emit_start(name);
+    fn emit_fct(&mut self, name: &'static str, r1: Register, fid: ConstPoolIdx) {
         let fname = self.get_fct_name(fid);
-        writeln!(self.w, " {}, ConstPoolIdx({}) # {}", r1, fid.0, fname).expect("write! failed");
+        self.emit_reg_const(name, r1, fid, fname);
     }
 
     fn get_fct_name(&mut self, idx: ConstPoolIdx) -> String {
@@ -418,8 +608,7 @@ impl<'a> BytecodeDumper<'a> {
         fct.display_name(self.sa)
     }
 
-    fn emit_new_lambda(&mut self, name: &str, r1: Register, idx: ConstPoolIdx) {
-        self.emit_start(name);
+    fn emit_new_lambda(&mut self, name: &'static str, r1: Register, idx: ConstPoolIdx) {
         let (fct_id, _type_params) = match self.bc.const_pool(idx) {
             ConstPoolEntry::Fct(fct_id, type_params) => (*fct_id, type_params.clone()),
             _ => unreachable!(),
@@ -427,11 +616,10 @@ impl<'a> BytecodeDumper<'a> {
         let fct = self.sa.fcts.idx(FctDefinitionId(fct_id.0 as usize));
         let fct = fct.read();
         let fname = fct.display_name(self.sa);
-        writeln!(self.w, " {}, ConstPoolIdx({}) # {}", r1, idx.0, fname).expect("write! failed");
+        self.emit_reg_const(name, r1, idx, fname);
     }
 
-    fn emit_new_object(&mut self, name: &str, r1: Register, idx: ConstPoolIdx) {
-        self.emit_start(name);
+    fn emit_new_object(&mut self, name: &'static str, r1: Register, idx: ConstPoolIdx) {
         let (cls_id, type_params) = match self.bc.const_pool(idx) {
             ConstPoolEntry::Class(cls_id, type_params) => (*cls_id, ty_array_from_bty(type_params)),
             _ => unreachable!(),
@@ -439,11 +627,16 @@ impl<'a> BytecodeDumper<'a> {
         let cls = self.sa.classes.idx(ClassDefinitionId(cls_id.0 as usize));
         let cls = cls.read();
         let cname = cls.name_with_params(self.sa, &type_params);
-        writeln!(self.w, " {}, ConstPoolIdx({}) # {}", r1, idx.0, cname).expect("write! failed");
+        self.emit_reg_const(name, r1, idx, cname);
     }
 
-    fn emit_new_trait_object(&mut self, name: &str, r1: Register, idx: ConstPoolIdx, r2: Register) {
-        self.emit_start(name);
+    fn emit_new_trait_object(
+        &mut self,
+        name: &'static str,
+        r1: Register,
+        idx: ConstPoolIdx,
+        r2: Register,
+    ) {
         let (trait_id, type_params, actual_ty) = match self.bc.const_pool(idx) {
             ConstPoolEntry::Trait(trait_id, type_params, ty) => (
                 *trait_id,
@@ -455,20 +648,22 @@ impl<'a> BytecodeDumper<'a> {
         let trait_ = self.sa.traits.idx(TraitDefinitionId(trait_id.0));
         let trait_ = trait_.read();
         let trait_name = trait_.name_with_params(self.sa, &type_params);
-        writeln!(
-            self.w,
-            " {}, ConstPoolIdx({}), {} # {} wrapping {}",
-            r1,
-            idx.0,
-            r2,
-            trait_name,
-            actual_ty.name(self.sa),
-        )
-        .expect("write! failed");
-    }
-
-    fn emit_new_array(&mut self, name: &str, r1: Register, idx: ConstPoolIdx, length: Register) {
-        self.emit_start(name);
+        let operands = vec![
+            Operand::Register(r1),
+            Operand::ConstPoolIdx(idx),
+            Operand::Register(r2),
+        ];
+        let comment = format!("{} wrapping {}", trait_name, actual_ty.name(self.sa));
+        self.emit(name, operands, Some(comment));
+    }
+
+    fn emit_new_array(
+        &mut self,
+        name: &'static str,
+        r1: Register,
+        idx: ConstPoolIdx,
+        length: Register,
+    ) {
         let (cls_id, type_params) = match self.bc.const_pool(idx) {
             ConstPoolEntry::Class(cls_id, type_params) => (*cls_id, ty_array_from_bty(type_params)),
             _ => unreachable!(),
@@ -476,26 +671,24 @@ impl<'a> BytecodeDumper<'a> {
         let cls = self.sa.classes.idx(ClassDefinitionId(cls_id.0 as usize));
         let cls = cls.read();
         let cname = cls.name_with_params(self.sa, &type_params);
-        writeln!(
-            self.w,
-            " {}, ConstPoolIdx({}), {} # {}",
-            r1, idx.0, length, cname,
-        )
-        .expect("write! failed");
+        let operands = vec![
+            Operand::Register(r1),
+            Operand::ConstPoolIdx(idx),
+            Operand::Register(length),
+        ];
+        self.emit(name, operands, Some(cname));
     }
 
-    fn emit_new_tuple(&mut self, name: &str, r1: Register, idx: ConstPoolIdx) {
-        self.emit_start(name);
+    fn emit_new_tuple(&mut self, name: &'static str, r1: Register, idx: ConstPoolIdx) {
         let source_type_array = match self.bc.const_pool(idx) {
             ConstPoolEntry::Tuple(ref subtypes) => ty_array_from_bty(subtypes),
             _ => unreachable!(),
         };
         let tuple_name = source_type_array.tuple_name(self.sa);
-        writeln!(self.w, " {}, {}", r1, tuple_name).expect("write! failed");
+        self.emit_reg_const(name, r1, idx, tuple_name);
     }
 
-    fn emit_new_enum(&mut self, name: &str, r1: Register, idx: ConstPoolIdx) {
-        self.emit_start(name);
+    fn emit_new_enum(&mut self, name: &'static str, r1: Register, idx: ConstPoolIdx) {
         let (enum_id, type_params, variant_idx) = match self.bc.const_pool(idx) {
             ConstPoolEntry::EnumVariant(enum_id, type_params, variant_idx) => {
                 (*enum_id, ty_array_from_bty(type_params), *variant_idx)
@@ -509,16 +702,10 @@ impl<'a> BytecodeDumper<'a> {
             .sa
             .interner
             .str(enum_.variants[variant_idx as usize].name);
-        writeln!(
-            self.w,
-            " {}, ConstPoolIdx({}) # {}::{}",
-            r1, idx.0, enum_name, variant_name,
-        )
-        .expect("write! failed");
+        self.emit_reg_const(name, r1, idx, format!("{}::{}", enum_name, variant_name));
     }
 
-    fn emit_new_struct(&mut self, name: &str, r1: Register, idx: ConstPoolIdx) {
-        self.emit_start(name);
+    fn emit_new_struct(&mut self, name: &'static str, r1: Register, idx: ConstPoolIdx) {
         let (struct_id, type_params) = match self.bc.const_pool(idx) {
             ConstPoolEntry::Struct(struct_id, type_params) => (*struct_id, type_params),
             _ => unreachable!(),
@@ -527,12 +714,7 @@ impl<'a> BytecodeDumper<'a> {
         let struct_ = struct_.read();
         let type_params = ty_array_from_bty(type_params);
         let struct_name = struct_.name_with_params(self.sa, &type_params);
-        writeln!(self.w, " {}, ConstPoolIdx({}) # {}", r1, idx.0, struct_name,)
-            .expect("write! failed");
-    }
-
-    fn emit_start(&mut self, name: &str) {
-        write!(self.w, "{:3}: {}", self.pos.to_usize(), name).expect("write! failed");
+        self.emit_reg_const(name, r1, idx, struct_name);
     }
 }
 
@@ -545,10 +727,18 @@ impl<'a> BytecodeVisitor for BytecodeDumper<'a> {
         self.emit_reg3("Add", dest, lhs, rhs);
     }
 
+    fn visit_add_saturating(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.emit_reg3("AddSaturating", dest, lhs, rhs);
+    }
+
     fn visit_sub(&mut self, dest: Register, lhs: Register, rhs: Register) {
         self.emit_reg3("Sub", dest, lhs, rhs);
     }
 
+    fn visit_sub_saturating(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.emit_reg3("SubSaturating", dest, lhs, rhs);
+    }
+
     fn visit_neg(&mut self, dest: Register, src: Register) {
         self.emit_reg2("NegInt32", dest, src);
     }
@@ -656,56 +846,45 @@ impl<'a> BytecodeVisitor for BytecodeDumper<'a> {
         self.emit_reg1("ConstZeroFloat64", dest);
     }
     fn visit_const_char(&mut self, dest: Register, idx: ConstPoolIdx) {
-        self.emit_start("ConstChar");
         let value = self.bc.const_pool(idx).to_char().expect("char expected");
-        writeln!(
-            self.w,
-            " {}, ConstPoolIdx({}) # '{}' 0x{:x}",
-            dest, idx.0, value, value as u32
-        )
-        .expect("write! failed");
+        let comment = format!("'{}' 0x{:x}", value, value as u32);
+        self.emit_reg_const("ConstChar", dest, idx, comment);
     }
     fn visit_const_uint8(&mut self, dest: Register, value: u8) {
-        self.emit_start("ConstUInt8");
-        writeln!(self.w, " {}, {}", dest, value).expect("write! failed");
+        let operands = vec![Operand::Register(dest), Operand::Value(value as i64)];
+        self.emit("ConstUInt8", operands, None);
     }
     fn visit_const_int32(&mut self, dest: Register, idx: ConstPoolIdx) {
-        self.emit_start("ConstInt32");
         let value = self.bc.const_pool(idx).to_int32().expect("int32 expected");
-        writeln!(self.w, " {}, ConstPoolIdx({}) # {}", dest, idx.0, value).expect("write! failed");
+        self.emit_reg_const("ConstInt32", dest, idx, value.to_string());
     }
     fn visit_const_int64(&mut self, dest: Register, idx: ConstPoolIdx) {
-        self.emit_start("ConstInt64");
         let value = self.bc.const_pool(idx).to_int64().expect("int64 expected");
-        writeln!(self.w, " {}, ConstPoolIdx({}) # {}", dest, idx.0, value).expect("write! failed");
+        self.emit_reg_const("ConstInt64", dest, idx, value.to_string());
     }
     fn visit_const_float32(&mut self, dest: Register, idx: ConstPoolIdx) {
-        self.emit_start("ConstFloat32");
         let value = self
             .bc
             .const_pool(idx)
             .to_float32()
             .expect("float32 expected");
-        writeln!(self.w, " {}, ConstPoolIdx({}) # {}", dest, idx.0, value).expect("write! failed");
+        self.emit_reg_const("ConstFloat32", dest, idx, value.to_string());
     }
     fn visit_const_float64(&mut self, dest: Register, idx: ConstPoolIdx) {
-        self.emit_start("ConstFloat64");
         let value = self
             .bc
             .const_pool(idx)
             .to_float64()
             .expect("float64 expected");
-        writeln!(self.w, " {}, ConstPoolIdx({}) # {}", dest, idx.0, value).expect("write! failed");
+        self.emit_reg_const("ConstFloat64", dest, idx, value.to_string());
     }
     fn visit_const_string(&mut self, dest: Register, idx: ConstPoolIdx) {
-        self.emit_start("ConstString");
         let value = self
             .bc
             .const_pool(idx)
             .to_string()
             .expect("string expected");
-        writeln!(self.w, " {}, ConstPoolIdx({}) # \"{}\"", dest, idx.0, value)
-            .expect("write! failed");
+        self.emit_reg_const("ConstString", dest, idx, format!("\"{}\"", value));
     }
 
     fn visit_test_eq(&mut self, dest: Register, lhs: Register, rhs: Register) {
@@ -742,6 +921,9 @@ impl<'a> BytecodeVisitor for BytecodeDumper<'a> {
     fn visit_jump_if_true_const(&mut self, opnd: Register, idx: ConstPoolIdx) {
         self.emit_cond_jump_const("JumpIfTrueConst", opnd, idx);
     }
+    fn visit_switch(&mut self, opnd: Register, idx: ConstPoolIdx) {
+        self.emit_switch("Switch", opnd, idx);
+    }
     fn visit_jump_loop(&mut self, offset: u32) {
         self.emit_jump("JumpLoop", -(offset as i32));
     }
@@ -812,6 +994,10 @@ impl<'a> BytecodeVisitor for BytecodeDumper<'a> {
         self.emit_reg3("StoreArray", src, arr, idx);
     }
 
+    fn visit_array_fill(&mut self, src: Register, arr: Register, length: Register) {
+        self.emit_reg3("ArrayFill", src, arr, length);
+    }
+
     fn visit_array_length(&mut self, dest: Register, arr: Register) {
         self.emit_reg2("ArrayLength", dest, arr);
     }
@@ -820,7 +1006,177 @@ impl<'a> BytecodeVisitor for BytecodeDumper<'a> {
         self.emit_reg2("LoadTraitObjectValue", dest, object);
     }
 
+    fn visit_test_trait_object(&mut self, dest: Register, idx: ConstPoolIdx, src: Register) {
+        self.emit_new_trait_object("TestTraitObject", dest, idx, src);
+    }
+
+    fn visit_cast_trait_object(&mut self, dest: Register, idx: ConstPoolIdx, src: Register) {
+        self.emit_new_trait_object("CastTraitObject", dest, idx, src);
+    }
+
+    fn visit_checked_cast(&mut self, dest: Register, src: Register) {
+        self.emit_reg2("CheckedCast", dest, src);
+    }
+
+    fn visit_throw(&mut self, opnd: Register) {
+        self.emit_reg1("Throw", opnd);
+    }
+
     fn visit_ret(&mut self, opnd: Register) {
         self.emit_reg1("Ret", opnd);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{dump_instructions, dump_json};
+    use crate::language::generator::generate_fct;
+    use crate::language::test;
+    use serde_json::{json, Value};
+
+    fn dump_fct(code: &'static str) -> String {
+        test::check_valid(code, |sa| {
+            let fct_id = sa.fct_by_name("f").expect("no function `f`.");
+            let bc = generate_fct(sa, fct_id);
+            dump_instructions(sa, &bc)
+                .iter()
+                .map(|inst| format!("{}\n", inst))
+                .collect()
+        })
+    }
+
+    fn dump_fct_json(code: &'static str) -> Value {
+        test::check_valid(code, |sa| {
+            let fct_id = sa.fct_by_name("f").expect("no function `f`.");
+            let bc = generate_fct(sa, fct_id);
+            dump_json(sa, None, &bc)
+        })
+    }
+
+    #[test]
+    fn dump_add_saturating() {
+        assert_eq!(
+            "  0: AddSaturating r2, r0, r1\n  4: Ret r2\n",
+            dump_fct("fn f(a: Int32, b: Int32): Int32 { a.saturatingAdd(b) }")
+        );
+        assert_eq!(
+            "  0: AddSaturating r2, r0, r1\n  4: Ret r2\n",
+            dump_fct("fn f(a: Int64, b: Int64): Int64 { a.saturatingAdd(b) }")
+        );
+    }
+
+    #[test]
+    fn dump_sub_saturating() {
+        assert_eq!(
+            "  0: SubSaturating r2, r0, r1\n  4: Ret r2\n",
+            dump_fct("fn f(a: Int32, b: Int32): Int32 { a.saturatingSub(b) }")
+        );
+        assert_eq!(
+            "  0: SubSaturating r2, r0, r1\n  4: Ret r2\n",
+            dump_fct("fn f(a: Int64, b: Int64): Int64 { a.saturatingSub(b) }")
+        );
+    }
+
+    #[test]
+    fn dump_array_fill() {
+        assert_eq!(
+            "  0: ConstInt32 r2, ConstPoolIdx(1) # 7\n  3: NewArray r1, ConstPoolIdx(0), r0 # Array[Int32]\n  7: ArrayFill r2, r1, r0\n 11: Ret r1\n",
+            dump_fct("fn f(a: Int64): Array[Int32] { Array[Int32]::fill(a, 7i32) }")
+        );
+    }
+
+    #[test]
+    fn dump_checked_cast() {
+        assert_eq!(
+            "  0: CheckedCast r1, r0\n  3: Ret r1\n",
+            dump_fct("fn f(a: Int64): Int32 { a as Int32 }")
+        );
+        assert_eq!(
+            "  0: CheckedCast r1, r0\n  3: Ret r1\n",
+            dump_fct("fn f(a: Int32): UInt8 { a as UInt8 }")
+        );
+        assert_eq!(
+            "  0: CheckedCast r1, r0\n  3: Ret r1\n",
+            dump_fct("fn f(a: Float64): Int32 { a as Int32 }")
+        );
+    }
+
+    #[test]
+    fn dump_throw() {
+        assert_eq!(
+            "  0: Throw r0\n  2: Ret r1\n",
+            dump_fct("class Foo fn f(a: Foo) { throw a; }")
+        );
+    }
+
+    #[test]
+    fn dump_json_fct() {
+        let dump =
+            dump_fct_json("fn f(a: Int64): String { if a > 10 { \"big\" } else { \"small\" } }");
+
+        let instructions = dump["instructions"].as_array().expect("array expected");
+        let opcodes = instructions
+            .iter()
+            .map(|inst| inst["opcode"].as_str().expect("string expected"))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            opcodes,
+            vec![
+                "ConstInt64",
+                "TestGt",
+                "JumpIfFalse",
+                "ConstString",
+                "Jump",
+                "ConstString",
+                "Ret"
+            ]
+        );
+        assert_eq!(
+            instructions[2],
+            json!({
+                "offset": 7,
+                "opcode": "JumpIfFalse",
+                "operands": [
+                    { "kind": "register", "value": 2 },
+                    { "kind": "value", "value": 8 },
+                ],
+                "comment": "target 15",
+            })
+        );
+
+        let constants = dump["constants"].as_array().expect("array expected");
+        let kinds = constants
+            .iter()
+            .map(|entry| entry["kind"].as_str().expect("string expected"))
+            .collect::<Vec<_>>();
+        assert_eq!(kinds, vec!["Int64", "Int32", "String", "Int32", "String"]);
+        assert_eq!(
+            constants[2],
+            json!({ "index": 2, "kind": "String", "value": "String \"big\"" })
+        );
+
+        assert_eq!(dump["registers"][0], json!({ "index": 0, "type": "Int64" }));
+        assert_eq!(dump["exception_handlers"], json!([]));
+    }
+
+    #[test]
+    fn dump_json_exception_handlers() {
+        let dump = dump_fct_json(
+            "class Foo
+            fn g() {}
+            fn f(): Int32 { try { g(); 1i32 } catch e: Foo { 2i32 } }",
+        );
+
+        let handlers = dump["exception_handlers"]
+            .as_array()
+            .expect("array expected");
+        assert_eq!(handlers.len(), 1);
+
+        let handler = &handlers[0];
+        assert_eq!(handler["start"], json!(0));
+        assert_eq!(handler["end"], json!(6));
+        assert_eq!(handler["handler"], json!(8));
+        assert_eq!(handler["register"], json!(2));
+        assert!(handler["type"].as_str().unwrap().starts_with("Class("));
+    }
+}
@@ -1,29 +1,97 @@
 use crate::language::error::msg::{ErrorDescriptor, ErrorMessage};
 use crate::language::sem_analysis::{SemAnalysis, SourceFileId};
 
-use dora_parser::Span;
+use dora_parser::{ast, Span};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Lint {
+    UnusedVariable,
+    UnreachablePattern,
+    IrrefutableLetPattern,
+}
+
+impl Lint {
+    pub fn from_name(name: &str) -> Option<Lint> {
+        match name {
+            "unused_variable" => Some(Lint::UnusedVariable),
+            "unreachable_pattern" => Some(Lint::UnreachablePattern),
+            "irrefutable_let_pattern" => Some(Lint::IrrefutableLetPattern),
+            _ => None,
+        }
+    }
+}
+
+struct Suppression {
+    file: SourceFileId,
+    span: Span,
+    lint: Lint,
+}
 
 pub struct Diagnostic {
     errors: Vec<ErrorDescriptor>,
+    warnings: Vec<ErrorDescriptor>,
+    suppressions: Vec<Suppression>,
 }
 
 impl Diagnostic {
     pub fn new() -> Diagnostic {
-        Diagnostic { errors: Vec::new() }
+        Diagnostic {
+            errors: Vec::new(),
+            warnings: Vec::new(),
+            suppressions: Vec::new(),
+        }
     }
 
     pub fn errors(&self) -> &[ErrorDescriptor] {
         &self.errors
     }
 
+    pub fn warnings(&self) -> &[ErrorDescriptor] {
+        &self.warnings
+    }
+
+    // Bodies of default methods in traits are checked once for every impl,
+    // identical messages are therefore only reported once.
     pub fn report(&mut self, file: SourceFileId, span: Span, msg: ErrorMessage) {
-        self.errors.push(ErrorDescriptor::new(file, span, msg));
+        push_unique(&mut self.errors, ErrorDescriptor::new(file, span, msg));
     }
 
     pub fn report_without_location(&mut self, msg: ErrorMessage) {
         self.errors.push(ErrorDescriptor::new_without_location(msg));
     }
 
+    pub fn warn(&mut self, file: SourceFileId, span: Span, lint: Lint, msg: ErrorMessage) {
+        let suppressed = self.suppressions.iter().any(|suppression| {
+            suppression.file == file
+                && suppression.lint == lint
+                && suppression.span.start() <= span.start()
+                && span.start() < suppression.span.end()
+        });
+
+        if !suppressed {
+            push_unique(&mut self.warnings, ErrorDescriptor::new(file, span, msg));
+        }
+    }
+
+    // Registers the `// dora:allow(...)` comments of a file, unknown lint
+    // names are reported as warnings themselves.
+    pub fn add_suppressions(&mut self, file: SourceFileId, suppressions: &[ast::Suppression]) {
+        for suppression in suppressions {
+            for (name, span) in &suppression.lints {
+                if let Some(lint) = Lint::from_name(name) {
+                    self.suppressions.push(Suppression {
+                        file,
+                        span: suppression.span,
+                        lint,
+                    });
+                } else {
+                    let msg = ErrorMessage::UnknownLint(name.clone());
+                    self.warnings.push(ErrorDescriptor::new(file, *span, msg));
+                }
+            }
+        }
+    }
+
     pub fn has_errors(&self) -> bool {
         !self.errors.is_empty()
     }
@@ -33,4 +101,16 @@ impl Diagnostic {
             eprintln!("{}", &err.message(sa));
         }
     }
+
+    pub fn dump_warnings(&self, sa: &SemAnalysis) {
+        for warning in &self.warnings {
+            eprintln!("{}", &warning.warning_message(sa));
+        }
+    }
+}
+
+fn push_unique(descriptors: &mut Vec<ErrorDescriptor>, descriptor: ErrorDescriptor) {
+    if !descriptors.contains(&descriptor) {
+        descriptors.push(descriptor);
+    }
 }
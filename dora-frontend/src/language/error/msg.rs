@@ -26,6 +26,7 @@ pub enum ErrorMessage {
     IncompatibleWithNil(String),
     IdentifierExists(String),
     ShadowFunction(String),
+    NestedFctTypeParams,
     ShadowParam(String),
     ShadowClass(String),
     ShadowClassConstructor(String),
@@ -35,6 +36,7 @@ pub enum ErrorMessage {
     ShadowField(String),
     ShadowGlobal(String),
     ShadowConst(String),
+    MultipleCandidatesForAssociatedConst(String),
     ShadowModule(String),
     ShadowEnum(String),
     ShadowEnumVariant(String),
@@ -48,8 +50,15 @@ pub enum ErrorMessage {
     MatchPatternWrongNumberOfParams(usize, usize),
     EnumExpected,
     EnumVariantExpected,
-    MatchUncoveredVariant,
+    EnumValuesWithPayload(String),
+    MatchUncoveredVariant(Vec<String>),
+    MatchGuardType(String),
+    MatchPatternUnsupported,
+    MatchPatternTypeIncompatible(String, String),
+    MatchMissingWildcard(String),
+    MatchRangeEmpty,
     MatchUnreachablePattern,
+    IrrefutableLetPattern,
     VarNeedsTypeInfo(String),
     ParamTypesIncompatible(String, Vec<String>, Vec<String>),
     LambdaParamTypesIncompatible(Vec<String>, Vec<String>),
@@ -61,8 +70,13 @@ pub enum ErrorMessage {
     AssignField(String, String, String, String),
     UnOpType(String, String),
     BinOpType(String, String, String),
+    BinOpTraitNotImplemented(String, String, String),
+    IndexTraitNotImplemented(String, String),
     ConstValueExpected,
+    ConstCycle(String),
+    ConstOverflow,
     OutsideLoop,
+    UnknownLabel(String),
     NoReturnValue,
     MainNotFound,
     WrongMainDefinition,
@@ -71,6 +85,8 @@ pub enum ErrorMessage {
     SuperUnavailable,
     SuperNeedsMethodCall,
     TraitExpected(String),
+    TraitObjectExpected(String),
+    ConcreteTypeExpected(String),
     NoSuperModule,
     LetMissingInitialization,
     LetReassigned,
@@ -129,6 +145,8 @@ pub enum ErrorMessage {
     MethodNotInTrait(String, String, Vec<String>),
     StaticMethodMissingFromTrait(String, String, Vec<String>),
     MethodMissingFromTrait(String, String, Vec<String>),
+    ConstNotInTrait(String, String),
+    ConstMissingFromTrait(String, String),
     WrongNumberTypeParams(usize, usize),
     UnconstrainedTypeParam(String),
     ClassExpected,
@@ -144,6 +162,7 @@ pub enum ErrorMessage {
     MissingAbstractOverride(String, String),
     ModifierNotAllowedForStaticMethod(String),
     InvalidTestAnnotationUsage,
+    UnknownTrap(String),
     GlobalInitializerNotSupported,
     TypeNotUsableInForIn(String),
     UnknownStructField(String, String),
@@ -153,7 +172,11 @@ pub enum ErrorMessage {
     InvalidUseOfTypeParams,
     NameOfStaticMethodExpected,
     IfBranchTypesIncompatible(String, String),
+    IfWithoutElse(String),
     MatchBranchTypesIncompatible(String, String),
+    TryCatchTypesIncompatible(String, String),
+    ThrowExpectsClass(String),
+    CatchExpectsClass(String),
     VarAlreadyInPattern,
     NameExpected,
     IndexExpected,
@@ -167,6 +190,8 @@ pub enum ErrorMessage {
     MissingFileArgument,
     PackageAlreadyExists(String),
     UnknownPackage(String),
+    UnusedVariable(String),
+    UnknownLint(String),
 }
 
 impl ErrorMessage {
@@ -239,6 +264,9 @@ impl ErrorMessage {
             ErrorMessage::ShadowFunction(ref name) => {
                 format!("can not shadow function `{}`.", name)
             }
+            ErrorMessage::NestedFctTypeParams => {
+                "nested functions can not have type params.".into()
+            }
             ErrorMessage::ShadowParam(ref name) => format!("can not shadow param `{}`.", name),
             ErrorMessage::ShadowClass(ref name) => format!("can not shadow class `{}`.", name),
             ErrorMessage::ShadowClassConstructor(ref name) => {
@@ -257,6 +285,9 @@ impl ErrorMessage {
             }
             ErrorMessage::ShadowModule(ref name) => format!("can not shadow mod `{}`.", name),
             ErrorMessage::ShadowConst(ref name) => format!("can not shadow const `{}`.", name),
+            ErrorMessage::MultipleCandidatesForAssociatedConst(ref name) => {
+                format!("multiple candidates for associated const `{}`.", name)
+            }
             ErrorMessage::ShadowEnum(ref name) => format!("can not shadow enum `{}`.", name),
             ErrorMessage::ShadowEnumVariant(ref name) => {
                 format!("can not shadow enum variant `{}`.", name)
@@ -296,8 +327,33 @@ impl ErrorMessage {
             ErrorMessage::VarAlreadyInPattern => "var is already used in pattern.".into(),
             ErrorMessage::EnumExpected => format!("enum expected."),
             ErrorMessage::EnumVariantExpected => format!("enum variant expected."),
-            ErrorMessage::MatchUncoveredVariant => "not all variants are covered.".into(),
-            ErrorMessage::MatchUnreachablePattern => "not all variants are covered.".into(),
+            ErrorMessage::EnumValuesWithPayload(ref name) => {
+                format!(
+                    "enum `{}` has variants with payload, values() not available.",
+                    name
+                )
+            }
+            ErrorMessage::MatchUncoveredVariant(ref variants) => {
+                let variants = variants.join(", ");
+                format!("non-exhaustive match, missing variants: {}.", variants)
+            }
+            ErrorMessage::MatchGuardType(ref ty) => {
+                format!("match guard expects type `bool` but got `{}`.", ty)
+            }
+            ErrorMessage::MatchPatternUnsupported => {
+                "pattern is not supported in match yet.".into()
+            }
+            ErrorMessage::MatchPatternTypeIncompatible(ref expected, ref got) => {
+                format!("pattern expects type `{}` but got `{}`.", expected, got)
+            }
+            ErrorMessage::MatchMissingWildcard(ref ty) => {
+                format!("match over `{}` needs a wildcard pattern `_`.", ty)
+            }
+            ErrorMessage::MatchRangeEmpty => "range pattern is empty.".into(),
+            ErrorMessage::MatchUnreachablePattern => "unreachable pattern.".into(),
+            ErrorMessage::IrrefutableLetPattern => {
+                "irrefutable `let` pattern, the condition always matches.".into()
+            }
             ErrorMessage::VarNeedsTypeInfo(ref name) => format!(
                 "variable `{}` needs either type declaration or expression.",
                 name
@@ -345,8 +401,23 @@ impl ErrorMessage {
                 "binary operator `{}` can not handle expression of type `{} {} {}`",
                 op, lhs, op, rhs
             ),
+            ErrorMessage::BinOpTraitNotImplemented(ref op, ref ty, ref trait_name) => format!(
+                "binary operator `{}` requires type `{}` to implement trait `{}`.",
+                op, ty, trait_name
+            ),
+            ErrorMessage::IndexTraitNotImplemented(ref ty, ref trait_name) => format!(
+                "type `{}` can not be indexed, it does not implement trait `{}`.",
+                ty, trait_name
+            ),
             ErrorMessage::ConstValueExpected => "constant value expected".into(),
+            ErrorMessage::ConstCycle(ref name) => {
+                format!("const `{}` depends on its own value.", name)
+            }
+            ErrorMessage::ConstOverflow => {
+                "constant expression overflows or divides by zero.".into()
+            }
             ErrorMessage::OutsideLoop => "statement only allowed inside loops".into(),
+            ErrorMessage::UnknownLabel(ref name) => format!("unknown loop label `'{}`.", name),
             ErrorMessage::NoReturnValue => {
                 "function does not return a value in all code paths".into()
             }
@@ -363,6 +434,12 @@ impl ErrorMessage {
             ErrorMessage::TraitExpected(ref name) => {
                 format!("`{}` is not a trait.", name)
             }
+            ErrorMessage::TraitObjectExpected(ref name) => {
+                format!("`{}` is not a trait object.", name)
+            }
+            ErrorMessage::ConcreteTypeExpected(ref name) => {
+                format!("`{}` is a trait but concrete type expected.", name)
+            }
             ErrorMessage::NoSuperModule => "no super module.".into(),
             ErrorMessage::NotAccessible(ref name) => format!("`{}` is not accessible.", name),
             ErrorMessage::StructConstructorNotAccessible(ref name) => {
@@ -514,6 +591,18 @@ impl ErrorMessage {
                     trait_name, mtd_name, args
                 )
             }
+            ErrorMessage::ConstNotInTrait(ref trait_name, ref const_name) => {
+                format!(
+                    "trait `{}` does not define const `{}`.",
+                    trait_name, const_name
+                )
+            }
+            ErrorMessage::ConstMissingFromTrait(ref trait_name, ref const_name) => {
+                format!(
+                    "trait `{}` defines const `{}` but is missing in `impl`.",
+                    trait_name, const_name
+                )
+            }
             ErrorMessage::WrongNumberTypeParams(exp, actual) => {
                 format!("expected {} type parameters but got {}.", exp, actual)
             }
@@ -544,6 +633,7 @@ impl ErrorMessage {
                 format!("modifier `{}` not allowed for static method.", modifier)
             }
             ErrorMessage::InvalidTestAnnotationUsage => "invalid usage of @Test annotation.".into(),
+            ErrorMessage::UnknownTrap(ref name) => format!("unknown trap `{}`.", name),
             ErrorMessage::GlobalInitializerNotSupported => {
                 "global variables do no support initial assignment for now.".into()
             }
@@ -572,6 +662,20 @@ impl ErrorMessage {
                 "if-branches have incompatible types `{}` and `{}`.",
                 then_block, else_block
             ),
+            ErrorMessage::IfWithoutElse(ref then_block) => format!(
+                "`if` without `else` used as value of type `{}`, add an `else` branch.",
+                then_block
+            ),
+            ErrorMessage::TryCatchTypesIncompatible(ref try_block, ref catch_block) => format!(
+                "try and catch block have incompatible types `{}` and `{}`.",
+                try_block, catch_block
+            ),
+            ErrorMessage::ThrowExpectsClass(ref ty) => {
+                format!("`throw` expects class object but got `{}`.", ty)
+            }
+            ErrorMessage::CatchExpectsClass(ref ty) => {
+                format!("`catch` expects class type but got `{}`.", ty)
+            }
             ErrorMessage::MatchBranchTypesIncompatible(ref expected_ty, ref value_ty) => format!(
                 "match arms have incompatible types `{}` and `{}`.",
                 expected_ty, value_ty
@@ -600,11 +704,13 @@ impl ErrorMessage {
             ErrorMessage::UnknownPackage(ref name) => {
                 format!("no package with name `{}` was found.", name)
             }
+            ErrorMessage::UnusedVariable(ref name) => format!("unused variable `{}`.", name),
+            ErrorMessage::UnknownLint(ref name) => format!("unknown lint `{}`.", name),
         }
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct ErrorDescriptor {
     pub file: Option<SourceFileId>,
     pub span: Option<Span>,
@@ -629,6 +735,14 @@ impl ErrorDescriptor {
     }
 
     pub fn message(&self, sa: &SemAnalysis) -> String {
+        self.message_with_kind(sa, "error")
+    }
+
+    pub fn warning_message(&self, sa: &SemAnalysis) -> String {
+        self.message_with_kind(sa, "warning")
+    }
+
+    fn message_with_kind(&self, sa: &SemAnalysis, kind: &str) -> String {
         if let Some(file) = self.file {
             let file = sa.source_file(file);
 
@@ -636,7 +750,8 @@ impl ErrorDescriptor {
             let (line, column) = compute_line_column(&file.line_starts, span.start());
 
             format!(
-                "error in {:?} at {}:{}: {}",
+                "{} in {:?} at {}:{}: {}",
+                kind,
                 file.path,
                 line,
                 column,
@@ -644,7 +759,7 @@ impl ErrorDescriptor {
             )
         } else {
             assert!(self.span.is_none());
-            format!("error: {}", self.msg.message())
+            format!("{}: {}", kind, self.msg.message())
         }
     }
 }
@@ -79,6 +79,9 @@ impl<'x> ExtensionCheck<'x> {
 
                 SourceType::Bool
                 | SourceType::UInt8
+                | SourceType::UInt16
+                | SourceType::UInt32
+                | SourceType::UInt64
                 | SourceType::Char
                 | SourceType::Int32
                 | SourceType::Int64
@@ -155,6 +158,9 @@ impl<'x> ExtensionCheck<'x> {
             SourceType::Enum(enum_id, _) => self.check_in_enum(&fct.ast, enum_id),
             SourceType::Bool
             | SourceType::UInt8
+            | SourceType::UInt16
+            | SourceType::UInt32
+            | SourceType::UInt64
             | SourceType::Char
             | SourceType::Int32
             | SourceType::Int64
@@ -279,10 +285,14 @@ fn discover_type_params(sa: &SemAnalysis, ty: SourceType, used_type_params: &mut
     match ty {
         SourceType::Error
         | SourceType::Unit
+        | SourceType::Never
         | SourceType::This
         | SourceType::Any
         | SourceType::Bool
         | SourceType::UInt8
+        | SourceType::UInt16
+        | SourceType::UInt32
+        | SourceType::UInt64
         | SourceType::Char
         | SourceType::Int32
         | SourceType::Int64
@@ -1,5 +1,4 @@
 use crate::language::fctbodyck::body::{TypeCheck, VarManager};
-use crate::language::fctbodyck::constck::ConstCheck;
 use crate::language::sem_analysis::{AnalysisData, FctDefinitionId, SemAnalysis};
 use crate::language::sym::ModuleSymTable;
 
@@ -17,20 +16,7 @@ pub fn check(sa: &mut SemAnalysis) {
         idx += 1;
     }
 
-    for const_ in sa.consts.iter() {
-        let mut const_ = const_.write();
-
-        let (_, value) = {
-            let mut constck = ConstCheck {
-                sa,
-                const_: &*const_,
-            };
-
-            constck.check_expr(&const_.expr)
-        };
-
-        const_.value = value;
-    }
+    constck::check(sa);
 }
 
 fn check_function(sa: &mut SemAnalysis, id: FctDefinitionId) {
@@ -43,8 +29,8 @@ fn check_function(sa: &mut SemAnalysis, id: FctDefinitionId) {
             return;
         }
 
-        if fct.is_lambda() {
-            // Lambdas will be type-checked by their parent.
+        if fct.is_lambda() || fct.is_nested() {
+            // Lambdas and nested functions will be type-checked by their parent.
             return;
         }
 
@@ -60,7 +46,7 @@ fn check_function(sa: &mut SemAnalysis, id: FctDefinitionId) {
             file_id: fct.file_id,
             analysis: &mut analysis,
             symtable: &mut symtable,
-            in_loop: false,
+            loop_labels: Vec::new(),
             self_available: false,
             vars: &mut vars,
             contains_lambda: false,
@@ -9,15 +9,17 @@ use crate::language::access::{
     method_accessible_from, module_accessible_from, struct_accessible_from,
     struct_field_accessible_from,
 };
+use crate::language::error::diag::Lint;
 use crate::language::error::msg::ErrorMessage;
 use crate::language::fctbodyck::lookup::MethodLookup;
 use crate::language::sem_analysis::{
     create_tuple, find_field_in_class, find_methods_in_class, find_methods_in_enum,
-    find_methods_in_struct, implements_trait, AnalysisData, CallType, ClassDefinition,
-    ClassDefinitionId, ContextIdx, EnumDefinitionId, EnumVariant, FctDefinition, FctDefinitionId,
-    FctParent, Field, FieldId, ForTypeInfo, IdentType, ModuleDefinitionId, NestedVarId,
-    PackageDefinitionId, SemAnalysis, SourceFileId, StructDefinition, StructDefinitionId,
-    TypeParamDefinition, TypeParamId, Var, VarAccess, VarId, VarLocation, Visibility,
+    find_methods_in_struct, impl_matches, implements_trait, AnalysisData, CallType,
+    ClassDefinition, ClassDefinitionId, ContextIdx, EnumDefinitionId, EnumVariant, FctDefinition,
+    FctDefinitionId, FctParent, Field, FieldId, ForTypeInfo, IdentType, ModuleDefinitionId,
+    NestedVarId, PackageDefinitionId, SemAnalysis, SourceFileId, StructDefinition,
+    StructDefinitionId, TraitDefinitionId, TypeParamDefinition, TypeParamId, Var, VarAccess, VarId,
+    VarLocation, Visibility,
 };
 use crate::language::specialize::replace_type_param;
 use crate::language::sym::{ModuleSymTable, Sym};
@@ -42,7 +44,7 @@ pub struct TypeCheck<'a> {
     pub file_id: SourceFileId,
     pub analysis: &'a mut AnalysisData,
     pub symtable: &'a mut ModuleSymTable,
-    pub in_loop: bool,
+    pub loop_labels: Vec<Option<Name>>,
     pub self_available: bool,
     pub vars: &'a mut VarManager,
     pub contains_lambda: bool,
@@ -62,6 +64,8 @@ impl<'a> TypeCheck<'a> {
         let block = ast.block.as_ref().expect("missing block");
         let mut returns = false;
 
+        self.check_nested_fcts(&block.stmts);
+
         for stmt in &block.stmts {
             self.visit_stmt(stmt);
 
@@ -96,6 +100,8 @@ impl<'a> TypeCheck<'a> {
             self.setup_context_class();
         }
 
+        self.report_unused_vars();
+
         // Store var definitions for all local and context vars defined in this function.
         self.analysis.vars = self.vars.leave_function();
 
@@ -103,6 +109,28 @@ impl<'a> TypeCheck<'a> {
             Some(self.outer_context_access_in_function || self.outer_context_access_from_lambda);
     }
 
+    fn report_unused_vars(&mut self) {
+        // Lints are only reported for the program, not for its dependencies.
+        if self.fct.package_id != self.sa.program_package_id() {
+            return;
+        }
+
+        for (name, span) in self.vars.unused_vars() {
+            let name = self.sa.interner.str(name).to_string();
+
+            if name.starts_with('_') {
+                continue;
+            }
+
+            self.sa.diag.lock().warn(
+                self.file_id,
+                span,
+                Lint::UnusedVariable,
+                ErrorMessage::UnusedVariable(name),
+            );
+        }
+    }
+
     fn needs_context(&self) -> bool {
         // As soon as this function has context variables,
         // it definitely needs a Context object.
@@ -273,7 +301,7 @@ impl<'a> TypeCheck<'a> {
         let expr_type = s
             .expr
             .as_ref()
-            .map(|expr| self.check_expr(&expr, defined_type.clone()))
+            .map(|expr| self.check_value_expr(&expr, defined_type.clone()))
             .unwrap_or(SourceType::Any);
 
         let defined_type = if s.data_type.is_some() {
@@ -283,11 +311,7 @@ impl<'a> TypeCheck<'a> {
         };
 
         if !defined_type.is_error() && !defined_type.is_defined_type(self.sa) {
-            let tyname = self
-                .sa
-                .interner
-                .str(s.pattern.to_name().unwrap())
-                .to_string();
+            let tyname = self.let_pattern_name(&s.pattern);
             self.sa.diag.lock().report(
                 self.file_id,
                 s.span,
@@ -305,11 +329,7 @@ impl<'a> TypeCheck<'a> {
                 && !defined_type.is_error()
                 && !defined_type.allows(self.sa, expr_type.clone())
             {
-                let name = self
-                    .sa
-                    .interner
-                    .str(s.pattern.to_name().unwrap())
-                    .to_string();
+                let name = self.let_pattern_name(&s.pattern);
                 let defined_type = defined_type.name_fct(self.sa, self.fct);
                 let expr_type = expr_type.name_fct(self.sa, self.fct);
                 let msg = ErrorMessage::AssignType(name, defined_type, expr_type);
@@ -326,6 +346,21 @@ impl<'a> TypeCheck<'a> {
         }
     }
 
+    fn let_pattern_name(&self, pattern: &ast::LetPattern) -> String {
+        match pattern {
+            ast::LetPattern::Ident(ref ident) => self.sa.interner.str(ident.name).to_string(),
+            ast::LetPattern::Underscore(_) => "_".into(),
+            ast::LetPattern::Tuple(ref tuple) => {
+                let parts = tuple
+                    .parts
+                    .iter()
+                    .map(|part| self.let_pattern_name(part))
+                    .collect::<Vec<_>>();
+                format!("({})", parts.join(", "))
+            }
+        }
+    }
+
     fn read_type(&mut self, t: &ast::Type) -> SourceType {
         read_type(
             self.sa,
@@ -342,6 +377,7 @@ impl<'a> TypeCheck<'a> {
         match pattern {
             ast::LetPattern::Ident(ref ident) => {
                 let var_id = self.vars.add_var(ident.name, ty, ident.mutable);
+                self.vars.declared_at(var_id, ident.span);
 
                 self.add_local(var_id, ident.span);
                 self.analysis
@@ -425,12 +461,12 @@ impl<'a> TypeCheck<'a> {
             self.check_stmt_let_pattern(&stmt.pattern, ret_type);
             // store fct ids for code generation
             self.analysis.map_fors.insert(stmt.id, for_type_info);
-            self.check_loop_body(&stmt.block);
+            self.check_loop_body(&stmt.block, stmt.label);
             self.symtable.pop_level();
             return;
         }
 
-        if let Some((make_iterator, iterator_type)) =
+        if let Some((make_iterator, make_iterator_type_params, iterator_type)) =
             self.type_supports_make_iterator(object_type.clone())
         {
             if let Some((mut for_type_info, ret_type)) =
@@ -443,9 +479,10 @@ impl<'a> TypeCheck<'a> {
 
                 // store fct ids for code generation
                 for_type_info.make_iterator = Some(make_iterator);
+                for_type_info.make_iterator_type_params = make_iterator_type_params;
                 self.analysis.map_fors.insert(stmt.id, for_type_info);
 
-                self.check_loop_body(&stmt.block);
+                self.check_loop_body(&stmt.block, stmt.label);
                 self.symtable.pop_level();
                 return;
             }
@@ -461,23 +498,26 @@ impl<'a> TypeCheck<'a> {
         // set invalid error type
         self.symtable.push_level();
         self.check_stmt_let_pattern(&stmt.pattern, SourceType::Error);
-        self.check_loop_body(&stmt.block);
+        self.check_loop_body(&stmt.block, stmt.label);
         self.symtable.pop_level();
     }
 
-    fn check_loop_body(&mut self, stmt: &ast::Stmt) {
-        let old_in_loop = self.in_loop;
-        self.in_loop = true;
+    fn check_loop_body(&mut self, stmt: &ast::Stmt, label: Option<Name>) {
+        self.loop_labels.push(label);
         self.visit_stmt(&stmt);
-        self.in_loop = old_in_loop;
+        self.loop_labels.pop().expect("missing loop");
     }
 
     fn type_supports_make_iterator(
         &mut self,
         object_type: SourceType,
-    ) -> Option<(FctDefinitionId, SourceType)> {
+    ) -> Option<(FctDefinitionId, SourceTypeArray, SourceType)> {
         let make_iterator_name = self.sa.interner.intern("makeIterator");
 
+        if let SourceType::TypeParam(id) = object_type {
+            return self.find_type_param_method(id, make_iterator_name);
+        }
+
         let mut lookup = MethodLookup::new(self.sa, self.fct)
             .no_error_reporting()
             .method(object_type)
@@ -489,7 +529,11 @@ impl<'a> TypeCheck<'a> {
             let make_iterator_id = lookup.found_fct_id().unwrap();
             let make_iterator_ret = lookup.found_ret().unwrap();
 
-            Some((make_iterator_id, make_iterator_ret))
+            Some((
+                make_iterator_id,
+                SourceTypeArray::empty(),
+                make_iterator_ret,
+            ))
         } else {
             None
         }
@@ -501,18 +545,27 @@ impl<'a> TypeCheck<'a> {
     ) -> Option<(ForTypeInfo, SourceType)> {
         let next_name = self.sa.interner.intern("next");
 
-        let mut next = MethodLookup::new(self.sa, self.fct)
-            .no_error_reporting()
-            .method(object_type.clone())
-            .name(next_name)
-            .type_param_defs(&self.fct.type_params)
-            .args(&[]);
-
-        if !next.find() {
-            return None;
-        }
+        let (next_id, next_type_params, next_result_type) =
+            if let SourceType::TypeParam(id) = object_type {
+                self.find_type_param_method(id, next_name)?
+            } else {
+                let mut next = MethodLookup::new(self.sa, self.fct)
+                    .no_error_reporting()
+                    .method(object_type.clone())
+                    .name(next_name)
+                    .type_param_defs(&self.fct.type_params)
+                    .args(&[]);
+
+                if !next.find() {
+                    return None;
+                }
 
-        let next_result_type = next.found_ret().unwrap();
+                (
+                    next.found_fct_id().expect("fct_id missing"),
+                    SourceTypeArray::empty(),
+                    next.found_ret().unwrap(),
+                )
+            };
 
         let value_type = if let SourceType::Enum(enum_id, type_params) = next_result_type.clone() {
             if enum_id == self.sa.known.enums.option() {
@@ -528,7 +581,9 @@ impl<'a> TypeCheck<'a> {
         Some((
             ForTypeInfo {
                 make_iterator: None,
-                next: next.found_fct_id().expect("fct_id missing"),
+                make_iterator_type_params: SourceTypeArray::empty(),
+                next: next_id,
+                next_type_params,
                 iterator_type: object_type,
                 next_type: next_result_type,
                 value_type: value_type.clone(),
@@ -537,16 +592,115 @@ impl<'a> TypeCheck<'a> {
         ))
     }
 
+    // Looks up a method without arguments in the trait bounds of a type
+    // parameter, the method needs to be unique across all bounds. Returns
+    // the method, the type arguments of the bound and the return type
+    // specialized for the bound.
+    fn find_type_param_method(
+        &self,
+        id: TypeParamId,
+        name: Name,
+    ) -> Option<(FctDefinitionId, SourceTypeArray, SourceType)> {
+        let mut found_fcts = Vec::new();
+
+        for trait_ty in self.fct.type_params.bounds_for_type_param(id) {
+            let trait_id = trait_ty.trait_id().expect("trait expected");
+            let trait_ = self.sa.traits[trait_id].read();
+
+            if let Some(fid) = trait_.find_method_with_replace(
+                self.sa,
+                false,
+                name,
+                None,
+                &trait_ty.type_params(),
+                &[],
+            ) {
+                found_fcts.push((fid, trait_ty.type_params()));
+            }
+        }
+
+        if found_fcts.len() == 1 {
+            let (fct_id, trait_type_params) = found_fcts.pop().unwrap();
+            let ret = replace_type_param(
+                self.sa,
+                self.sa.fcts.idx(fct_id).read().return_type.clone(),
+                &trait_type_params,
+                Some(SourceType::TypeParam(id)),
+            );
+            Some((fct_id, trait_type_params, ret))
+        } else {
+            None
+        }
+    }
+
     fn check_stmt_while(&mut self, stmt: &ast::StmtWhileType) {
         let expr_type = self.check_expr(&stmt.cond, SourceType::Any);
 
+        if let Some(ref pattern) = stmt.pattern {
+            // Variables bound by the pattern are only visible in the body.
+            self.symtable.push_level();
+            self.check_cond_pattern(pattern, expr_type, stmt.cond.span());
+            self.check_loop_body(&stmt.block, stmt.label);
+            self.symtable.pop_level();
+            return;
+        }
+
         if !expr_type.is_error() && !expr_type.is_bool() {
             let expr_type = expr_type.name_fct(self.sa, self.fct);
             let msg = ErrorMessage::WhileCondType(expr_type);
             self.sa.diag.lock().report(self.file_id, stmt.span, msg);
         }
 
-        self.check_loop_body(&stmt.block);
+        self.check_loop_body(&stmt.block, stmt.label);
+    }
+
+    // Checks the pattern of `if let` and `while let` against the type of the
+    // scrutinee and binds its variables in the current scope level.
+    fn check_cond_pattern(&mut self, pattern: &MatchPattern, expr_type: SourceType, span: Span) {
+        if expr_type.is_error() {
+            return;
+        }
+
+        let enum_id = match expr_type.enum_id() {
+            Some(enum_id) => enum_id,
+            None => {
+                let msg = ErrorMessage::EnumExpected;
+                self.sa.diag.lock().report(self.file_id, span, msg);
+                return;
+            }
+        };
+
+        let variants = self.sa.enums[enum_id].read().variants.len();
+        let mut used_variants = FixedBitSet::with_capacity(variants);
+
+        self.check_expr_match_pattern(
+            Some(enum_id),
+            expr_type.type_params(),
+            pattern.span,
+            pattern,
+            &mut used_variants,
+            false,
+        );
+
+        if used_variants.count_ones(..) == variants && !has_nested_pattern(pattern) {
+            let msg = ErrorMessage::IrrefutableLetPattern;
+            self.sa
+                .diag
+                .lock()
+                .warn(self.file_id, pattern.span, Lint::IrrefutableLetPattern, msg);
+        }
+    }
+
+    fn check_stmt_do_while(&mut self, stmt: &ast::StmtDoWhileType) {
+        self.check_loop_body(&stmt.block, stmt.label);
+
+        let expr_type = self.check_expr(&stmt.cond, SourceType::Any);
+
+        if !expr_type.is_error() && !expr_type.is_bool() {
+            let expr_type = expr_type.name_fct(self.sa, self.fct);
+            let msg = ErrorMessage::WhileCondType(expr_type);
+            self.sa.diag.lock().report(self.file_id, stmt.span, msg);
+        }
     }
 
     fn check_stmt_return(&mut self, s: &ast::StmtReturnType) {
@@ -555,12 +709,22 @@ impl<'a> TypeCheck<'a> {
         let expr_type = s
             .expr
             .as_ref()
-            .map(|expr| self.check_expr(&expr, expected_ty))
+            .map(|expr| self.check_value_expr(&expr, expected_ty))
             .unwrap_or(SourceType::Unit);
 
         self.check_fct_return_type(s.span, expr_type);
     }
 
+    fn check_stmt_throw(&mut self, s: &ast::StmtThrowType) {
+        let expr_type = self.check_expr(&s.expr, SourceType::Any);
+
+        if !expr_type.is_error() && !expr_type.is_cls() {
+            let expr_type = expr_type.name_fct(self.sa, self.fct);
+            let msg = ErrorMessage::ThrowExpectsClass(expr_type);
+            self.sa.diag.lock().report(self.file_id, s.span, msg);
+        }
+    }
+
     fn check_fct_return_type(&mut self, span: Span, expr_type: SourceType) {
         let fct_type = self.fct.return_type.clone();
 
@@ -568,7 +732,12 @@ impl<'a> TypeCheck<'a> {
             let fct_type = fct_type.name_fct(self.sa, self.fct);
             let expr_type = expr_type.name_fct(self.sa, self.fct);
 
-            let msg = ErrorMessage::ReturnType(fct_type, expr_type);
+            let msg = if self.fct.is_associated_const {
+                let name = self.sa.interner.str(self.fct.name).to_string();
+                ErrorMessage::AssignType(name, fct_type, expr_type)
+            } else {
+                ErrorMessage::ReturnType(fct_type, expr_type)
+            };
 
             self.sa.diag.lock().report(self.file_id, span, msg);
         }
@@ -580,6 +749,7 @@ impl<'a> TypeCheck<'a> {
         _expected_ty: SourceType,
     ) -> SourceType {
         self.symtable.push_level();
+        self.check_nested_fcts(&block.stmts);
 
         for stmt in &block.stmts {
             self.visit_stmt(stmt);
@@ -597,10 +767,100 @@ impl<'a> TypeCheck<'a> {
         ty
     }
 
+    // Nested functions are visible in the whole block, so all of them are
+    // declared before any body is checked.
+    fn check_nested_fcts(&mut self, stmts: &[Box<ast::Stmt>]) {
+        let mut nested_fcts = Vec::new();
+
+        for stmt in stmts {
+            if let Some(node) = stmt.to_fct() {
+                let fct_id = self.declare_nested_fct(node);
+
+                if let Some(Sym::Fct(_)) = self.symtable.insert(node.name, Sym::Fct(fct_id)) {
+                    let name = self.sa.interner.str(node.name).to_string();
+                    let msg = ErrorMessage::ShadowFunction(name);
+                    self.sa.diag.lock().report(self.file_id, node.span, msg);
+                }
+
+                nested_fcts.push((fct_id, node));
+            }
+        }
+
+        for (fct_id, node) in nested_fcts {
+            let fct = self.sa.fcts.idx(fct_id);
+            let mut analysis = AnalysisData::new();
+
+            {
+                let fct = fct.read();
+                let mut symtable = self.symtable.for_nested_function();
+                let mut vars = VarManager::new();
+
+                let mut typeck = TypeCheck {
+                    sa: self.sa,
+                    fct: &*fct,
+                    package_id: self.package_id,
+                    module_id: self.module_id,
+                    file_id: self.file_id,
+                    analysis: &mut analysis,
+                    symtable: &mut symtable,
+                    loop_labels: Vec::new(),
+                    self_available: false,
+                    vars: &mut vars,
+                    contains_lambda: false,
+                    outer_context_access_in_function: false,
+                    outer_context_access_from_lambda: false,
+                };
+
+                typeck.check(node);
+            }
+
+            fct.write().analysis = Some(analysis);
+        }
+    }
+
+    fn declare_nested_fct(&mut self, node: &Arc<ast::Function>) -> FctDefinitionId {
+        if node.type_params.is_some() {
+            let msg = ErrorMessage::NestedFctTypeParams;
+            self.sa.diag.lock().report(self.file_id, node.span, msg);
+        }
+
+        let mut fct = FctDefinition::new(
+            self.package_id,
+            self.module_id,
+            self.file_id,
+            node,
+            FctParent::None,
+        );
+
+        // Nested functions share the type params of the enclosing function.
+        fct.type_params = self.fct.type_params.clone();
+
+        for param in &node.params {
+            if fct.is_variadic {
+                let msg = ErrorMessage::VariadicParameterNeedsToBeLast;
+                self.sa.diag.lock().report(self.file_id, param.span, msg);
+            }
+
+            fct.param_types.push(self.read_type(&param.data_type));
+
+            if param.variadic {
+                fct.is_variadic = true;
+            }
+        }
+
+        fct.return_type = match node.return_type {
+            Some(ref ret) => self.read_type(ret),
+            None => SourceType::Unit,
+        };
+        fct.initialized = true;
+
+        self.sa.add_fct(fct)
+    }
+
     fn check_expr_tuple(
         &mut self,
         tuple: &ast::ExprTupleType,
-        _expected_ty: SourceType,
+        expected_ty: SourceType,
     ) -> SourceType {
         let mut subtypes = Vec::new();
 
@@ -609,8 +869,26 @@ impl<'a> TypeCheck<'a> {
             return SourceType::Unit;
         }
 
-        for value in &tuple.values {
-            let subtype = self.check_expr(value, SourceType::Any);
+        // Element types of an expected tuple guide the inference of the
+        // values, e.g. for integer literals.
+        let expected_subtypes = if expected_ty.is_tuple() {
+            let expected_subtypes = expected_ty.tuple_subtypes();
+
+            if expected_subtypes.len() == tuple.values.len() {
+                Some(expected_subtypes)
+            } else {
+                None
+            }
+        } else {
+            None
+        };
+
+        for (idx, value) in tuple.values.iter().enumerate() {
+            let expected_subtype = expected_subtypes
+                .as_ref()
+                .map(|subtypes| subtypes[idx].clone())
+                .unwrap_or(SourceType::Any);
+            let subtype = self.check_expr(value, expected_subtype);
             subtypes.push(subtype);
         }
 
@@ -631,12 +909,41 @@ impl<'a> TypeCheck<'a> {
         ty
     }
 
+    fn check_expr_range(
+        &mut self,
+        range: &ast::ExprRangeType,
+        _expected_ty: SourceType,
+    ) -> SourceType {
+        self.sa
+            .diag
+            .lock()
+            .report(self.fct.file_id, range.span, ErrorMessage::Unimplemented);
+        self.analysis.set_ty(range.id, SourceType::Error);
+
+        SourceType::Error
+    }
+
+    fn check_expr_try(&mut self, node: &ast::ExprTryType, _expected_ty: SourceType) -> SourceType {
+        self.sa
+            .diag
+            .lock()
+            .report(self.fct.file_id, node.span, ErrorMessage::Unimplemented);
+        self.analysis.set_ty(node.id, SourceType::Error);
+
+        SourceType::Error
+    }
+
     fn check_expr_match(
         &mut self,
         node: &ast::ExprMatchType,
         expected_ty: SourceType,
     ) -> SourceType {
         let expr_type = self.check_expr(&node.expr, SourceType::Any);
+
+        if self.is_match_lit_type(&expr_type) {
+            return self.check_expr_match_lit(node, expr_type, expected_ty);
+        }
+
         let mut result_type = SourceType::Error;
 
         if !expr_type.is_enum() {
@@ -657,44 +964,232 @@ impl<'a> TypeCheck<'a> {
         };
 
         let mut used_variants = FixedBitSet::with_capacity(enum_variants);
+        let mut has_wildcard = false;
 
         for case in &node.cases {
             self.symtable.push_level();
 
             debug_assert_eq!(case.patterns.len(), 1);
             let pattern = case.patterns.first().expect("no pattern");
-            self.check_expr_match_pattern(
-                expr_enum_id,
-                expr_type_params.clone(),
-                case,
-                pattern,
-                &mut used_variants,
-            );
 
-            let case_ty = self.check_expr(&case.value, expected_ty.clone());
-
-            if result_type.is_error() {
-                result_type = case_ty;
-            } else if case_ty.is_error() {
-                // ignore this case
-            } else if !result_type.allows(self.sa, case_ty.clone()) {
-                let result_type_name = result_type.name_fct(self.sa, self.fct);
-                let case_ty_name = case_ty.name_fct(self.sa, self.fct);
-                let msg =
-                    ErrorMessage::MatchBranchTypesIncompatible(result_type_name, case_ty_name);
-                self.sa
-                    .diag
-                    .lock()
-                    .report(self.file_id, case.value.span(), msg);
+            if case.guard.is_some() || has_nested_pattern(pattern) {
+                // A guarded case or one with nested patterns never covers
+                // its variants.
+                let mut guarded_variants = used_variants.clone();
+                self.check_expr_match_pattern(
+                    expr_enum_id,
+                    expr_type_params.clone(),
+                    case.span,
+                    pattern,
+                    &mut guarded_variants,
+                    has_wildcard,
+                );
+
+                if let Some(ref guard) = case.guard {
+                    self.check_expr_match_guard(guard);
+                }
+            } else {
+                self.check_expr_match_pattern(
+                    expr_enum_id,
+                    expr_type_params.clone(),
+                    case.span,
+                    pattern,
+                    &mut used_variants,
+                    has_wildcard,
+                );
+
+                has_wildcard |= matches!(pattern.data, ast::MatchPatternData::Underscore);
             }
 
+            self.check_expr_match_case_value(case, expected_ty.clone(), &mut result_type);
+
             self.symtable.pop_level();
         }
 
         used_variants.toggle_range(..);
 
         if used_variants.count_ones(..) != 0 {
-            let msg = ErrorMessage::MatchUncoveredVariant;
+            let enum_ = self.sa.enums[expr_enum_id.expect("enum expected")].read();
+            let missing = used_variants
+                .ones()
+                .map(|idx| self.sa.interner.str(enum_.variants[idx].name).to_string())
+                .collect();
+            let msg = ErrorMessage::MatchUncoveredVariant(missing);
+            self.sa.diag.lock().report(self.file_id, node.span, msg);
+        }
+
+        self.analysis.set_ty(node.id, result_type.clone());
+
+        result_type
+    }
+
+    // Arms after a wildcard are only dead code, while an arm repeating an
+    // already matched variant is most likely a mistake.
+    fn report_unreachable_pattern(&mut self, span: Span, after_wildcard: bool) {
+        let msg = ErrorMessage::MatchUnreachablePattern;
+
+        if after_wildcard {
+            self.sa
+                .diag
+                .lock()
+                .warn(self.file_id, span, Lint::UnreachablePattern, msg);
+        } else {
+            self.sa.diag.lock().report(self.file_id, span, msg);
+        }
+    }
+
+    fn check_expr_match_guard(&mut self, guard: &ast::Expr) {
+        let guard_ty = self.check_expr(guard, SourceType::Bool);
+
+        if !guard_ty.is_bool() && !guard_ty.is_error() {
+            let guard_ty = guard_ty.name_fct(self.sa, self.fct);
+            let msg = ErrorMessage::MatchGuardType(guard_ty);
+            self.sa.diag.lock().report(self.file_id, guard.span(), msg);
+        }
+    }
+
+    fn check_expr_match_case_value(
+        &mut self,
+        case: &MatchCaseType,
+        expected_ty: SourceType,
+        result_type: &mut SourceType,
+    ) {
+        let case_ty = self.check_expr(&case.value, expected_ty);
+
+        if result_type.is_error() {
+            *result_type = case_ty;
+        } else if case_ty.is_error() {
+            // ignore this case
+        } else if !result_type.allows(self.sa, case_ty.clone()) {
+            let result_type_name = result_type.name_fct(self.sa, self.fct);
+            let case_ty_name = case_ty.name_fct(self.sa, self.fct);
+            let msg = ErrorMessage::MatchBranchTypesIncompatible(result_type_name, case_ty_name);
+            self.sa
+                .diag
+                .lock()
+                .report(self.file_id, case.value.span(), msg);
+        }
+    }
+
+    fn is_match_lit_type(&self, ty: &SourceType) -> bool {
+        match ty {
+            SourceType::UInt8 | SourceType::Char | SourceType::Int32 | SourceType::Int64 => true,
+            _ => ty.is_cls_id(self.sa.known.classes.string()),
+        }
+    }
+
+    fn check_expr_match_lit(
+        &mut self,
+        node: &ast::ExprMatchType,
+        expr_type: SourceType,
+        expected_ty: SourceType,
+    ) -> SourceType {
+        let mut result_type = SourceType::Error;
+        let mut has_wildcard = false;
+        let mut used_ranges: Vec<(i64, i64)> = Vec::new();
+        let mut used_strings: HashSet<String> = HashSet::new();
+
+        for case in &node.cases {
+            self.symtable.push_level();
+
+            debug_assert_eq!(case.patterns.len(), 1);
+            let pattern = case.patterns.first().expect("no pattern");
+            let guarded = case.guard.is_some();
+
+            let covered = match pattern.data {
+                ast::MatchPatternData::Underscore => {
+                    let covered = has_wildcard;
+                    has_wildcard |= !guarded;
+                    covered
+                }
+
+                ast::MatchPatternData::Lit(ref expr) => {
+                    match self.check_match_pattern_lit(pattern, expr, expr_type.clone()) {
+                        Some(MatchLitValue::Int(value)) => {
+                            let covered = has_wildcard
+                                || used_ranges
+                                    .iter()
+                                    .any(|&(start, end)| start <= value && value <= end);
+
+                            if !guarded {
+                                used_ranges.push((value, value));
+                            }
+
+                            covered
+                        }
+
+                        Some(MatchLitValue::Str(value)) => {
+                            let covered = has_wildcard || used_strings.contains(&value);
+
+                            if !guarded {
+                                used_strings.insert(value);
+                            }
+
+                            covered
+                        }
+
+                        None => false,
+                    }
+                }
+
+                ast::MatchPatternData::Range(ref range) => {
+                    let start =
+                        self.check_match_pattern_lit(pattern, &range.start, expr_type.clone());
+                    let end = self.check_match_pattern_lit(pattern, &range.end, expr_type.clone());
+
+                    match (start, end) {
+                        (Some(MatchLitValue::Int(start)), Some(MatchLitValue::Int(end))) => {
+                            if start > end {
+                                let msg = ErrorMessage::MatchRangeEmpty;
+                                self.sa.diag.lock().report(self.file_id, pattern.span, msg);
+                            }
+
+                            let covered = has_wildcard
+                                || used_ranges.iter().any(|&(used_start, used_end)| {
+                                    used_start <= start && end <= used_end
+                                });
+
+                            if !guarded {
+                                used_ranges.push((start, end));
+                            }
+
+                            covered
+                        }
+
+                        (Some(MatchLitValue::Str(_)), _) | (_, Some(MatchLitValue::Str(_))) => {
+                            let msg = ErrorMessage::MatchPatternUnsupported;
+                            self.sa.diag.lock().report(self.file_id, pattern.span, msg);
+                            false
+                        }
+
+                        _ => false,
+                    }
+                }
+
+                ast::MatchPatternData::Ident(_) | ast::MatchPatternData::Tuple(_) => {
+                    let msg = ErrorMessage::MatchPatternUnsupported;
+                    self.sa.diag.lock().report(self.file_id, pattern.span, msg);
+                    false
+                }
+            };
+
+            if covered {
+                let msg = ErrorMessage::MatchUnreachablePattern;
+                self.sa.diag.lock().report(self.file_id, case.span, msg);
+            }
+
+            if let Some(ref guard) = case.guard {
+                self.check_expr_match_guard(guard);
+            }
+
+            self.check_expr_match_case_value(case, expected_ty.clone(), &mut result_type);
+
+            self.symtable.pop_level();
+        }
+
+        if !has_wildcard {
+            let expr_type = expr_type.name_fct(self.sa, self.fct);
+            let msg = ErrorMessage::MatchMissingWildcard(expr_type);
             self.sa.diag.lock().report(self.file_id, node.span, msg);
         }
 
@@ -703,13 +1198,88 @@ impl<'a> TypeCheck<'a> {
         result_type
     }
 
+    fn check_match_pattern_lit(
+        &mut self,
+        pattern: &MatchPattern,
+        expr: &ast::Expr,
+        expr_type: SourceType,
+    ) -> Option<MatchLitValue> {
+        let value = match expr {
+            ast::Expr::LitInt(ref lit) => Some(MatchLitValue::Int(lit.value as i64)),
+            ast::Expr::Un(ref un) if un.op == ast::UnOp::Neg && un.opnd.is_lit_int() => {
+                let lit = un.opnd.to_lit_int().unwrap();
+                Some(MatchLitValue::Int((lit.value as i64).wrapping_neg()))
+            }
+            ast::Expr::LitChar(ref lit) => Some(MatchLitValue::Int(lit.value as i64)),
+            ast::Expr::LitStr(ref lit) => Some(MatchLitValue::Str(lit.value.clone())),
+            _ => None,
+        };
+
+        let ty = self.check_expr(expr, expr_type.clone());
+
+        if ty.is_error() {
+            return None;
+        }
+
+        let value = if let Some(value) = value {
+            value
+        } else {
+            let msg = ErrorMessage::MatchPatternUnsupported;
+            self.sa.diag.lock().report(self.file_id, expr.span(), msg);
+            return None;
+        };
+
+        if ty != expr_type {
+            let expr_type = expr_type.name_fct(self.sa, self.fct);
+            let ty = ty.name_fct(self.sa, self.fct);
+            let msg = ErrorMessage::MatchPatternTypeIncompatible(expr_type, ty);
+            self.sa.diag.lock().report(self.file_id, expr.span(), msg);
+            return None;
+        }
+
+        match value {
+            MatchLitValue::Int(value) => {
+                let value = match ty {
+                    SourceType::UInt8 => value as u8 as i64,
+                    SourceType::Int32 => value as i32 as i64,
+                    _ => value,
+                };
+
+                Some(MatchLitValue::Int(value))
+            }
+
+            MatchLitValue::Str(_) => {
+                // String patterns are compared through `String::equals`.
+                let name = self.sa.interner.intern("equals");
+
+                if let Some(descriptor) = lookup_method(
+                    self.sa,
+                    ty.clone(),
+                    &self.fct.type_params,
+                    false,
+                    name,
+                    std::slice::from_ref(&ty),
+                    &SourceTypeArray::empty(),
+                ) {
+                    let call_type = CallType::Method(ty, descriptor.fct_id, descriptor.type_params);
+                    self.analysis
+                        .map_calls
+                        .insert_or_replace(pattern.id, Arc::new(call_type));
+                }
+
+                Some(value)
+            }
+        }
+    }
+
     fn check_expr_match_pattern(
         &mut self,
         expr_enum_id: Option<EnumDefinitionId>,
         expr_type_params: SourceTypeArray,
-        case: &MatchCaseType,
+        case_span: Span,
         pattern: &MatchPattern,
         used_variants: &mut FixedBitSet,
+        after_wildcard: bool,
     ) {
         match pattern.data {
             ast::MatchPatternData::Underscore => {
@@ -717,8 +1287,7 @@ impl<'a> TypeCheck<'a> {
                 negated_used_variants.toggle_range(..);
 
                 if negated_used_variants.count_ones(..) == 0 {
-                    let msg = ErrorMessage::MatchUnreachablePattern;
-                    self.sa.diag.lock().report(self.file_id, case.span, msg);
+                    self.report_unreachable_pattern(case_span, after_wildcard);
                 }
 
                 used_variants.insert_range(..);
@@ -733,8 +1302,7 @@ impl<'a> TypeCheck<'a> {
                     Ok(Sym::EnumVariant(enum_id, variant_idx)) => {
                         if Some(enum_id) == expr_enum_id {
                             if used_variants.contains(variant_idx as usize) {
-                                let msg = ErrorMessage::MatchUnreachablePattern;
-                                self.sa.diag.lock().report(self.file_id, case.span, msg);
+                                self.report_unreachable_pattern(case_span, after_wildcard);
                             }
 
                             used_variants.insert(variant_idx as usize);
@@ -747,97 +1315,170 @@ impl<'a> TypeCheck<'a> {
                                 ),
                             );
 
-                            let enum_ = self.sa.enums.idx(enum_id);
-                            let enum_ = enum_.read();
-                            let variant = &enum_.variants[variant_idx as usize];
+                            self.check_expr_match_pattern_params(
+                                enum_id,
+                                variant_idx,
+                                &expr_type_params,
+                                ident,
+                                case_span,
+                                &mut used_idents,
+                            );
+                        } else {
+                            let msg = ErrorMessage::EnumVariantExpected;
+                            self.sa
+                                .diag
+                                .lock()
+                                .report(self.file_id, ident.path.span, msg);
+                        }
+                    }
 
-                            let given_params = if let Some(ref params) = ident.params {
-                                params.len()
-                            } else {
-                                0
-                            };
+                    Ok(_) => {
+                        let msg = ErrorMessage::EnumVariantExpected;
+                        self.sa
+                            .diag
+                            .lock()
+                            .report(self.file_id, ident.path.span, msg);
+                    }
 
-                            if given_params == 0 && ident.params.is_some() {
-                                let msg = ErrorMessage::MatchPatternNoParens;
-                                self.sa.diag.lock().report(self.file_id, case.span, msg);
-                            }
+                    Err(()) => {}
+                }
+            }
+
+            ast::MatchPatternData::Tuple(_)
+            | ast::MatchPatternData::Lit(_)
+            | ast::MatchPatternData::Range(_) => {
+                let msg = ErrorMessage::MatchPatternUnsupported;
+                self.sa.diag.lock().report(self.file_id, pattern.span, msg);
+            }
+        }
+    }
 
-                            let expected_params = variant.types.len();
+    fn check_expr_match_pattern_params(
+        &mut self,
+        enum_id: EnumDefinitionId,
+        variant_idx: u32,
+        type_params: &SourceTypeArray,
+        ident: &ast::MatchPatternIdent,
+        span: Span,
+        used_idents: &mut HashSet<Name>,
+    ) {
+        let variant_types = {
+            let enum_ = self.sa.enums.idx(enum_id);
+            let enum_ = enum_.read();
+            enum_.variants[variant_idx as usize].types.clone()
+        };
+
+        let given_params = if let Some(ref params) = ident.params {
+            params.len()
+        } else {
+            0
+        };
+
+        if given_params == 0 && ident.params.is_some() {
+            let msg = ErrorMessage::MatchPatternNoParens;
+            self.sa.diag.lock().report(self.file_id, span, msg);
+        }
+
+        let expected_params = variant_types.len();
+
+        if given_params != expected_params {
+            let msg = ErrorMessage::MatchPatternWrongNumberOfParams(given_params, expected_params);
+            self.sa.diag.lock().report(self.file_id, span, msg);
+        }
+
+        if let Some(ref params) = ident.params {
+            for (idx, param) in params.iter().enumerate() {
+                let ty = if idx < variant_types.len() {
+                    variant_types[idx].clone()
+                } else {
+                    SourceType::Error
+                };
+
+                let ty = replace_type_param(self.sa, ty, type_params, None);
+
+                if let Some(ref pattern) = param.pattern {
+                    self.check_expr_match_nested_pattern(ty, pattern, used_idents);
+                } else if let Some(name) = param.name {
+                    if used_idents.insert(name) == false {
+                        let msg = ErrorMessage::VarAlreadyInPattern;
+                        self.sa.diag.lock().report(self.file_id, param.span, msg);
+                    }
+
+                    let var_id = self.vars.add_var(name, ty, param.mutable);
+                    self.add_local(var_id, param.span);
+                    self.analysis
+                        .map_vars
+                        .insert(param.id, self.vars.local_var_id(var_id));
+                }
+            }
+        }
+    }
 
-                            if given_params != expected_params {
-                                let msg = ErrorMessage::MatchPatternWrongNumberOfParams(
-                                    given_params,
-                                    expected_params,
-                                );
-                                self.sa.diag.lock().report(self.file_id, case.span, msg);
-                            }
+    // Checks a variant pattern nested in the payload of another variant,
+    // `ty` is the type of the payload field.
+    fn check_expr_match_nested_pattern(
+        &mut self,
+        ty: SourceType,
+        pattern: &MatchPattern,
+        used_idents: &mut HashSet<Name>,
+    ) {
+        let ident = match pattern.data {
+            ast::MatchPatternData::Ident(ref ident) => ident,
+            _ => unreachable!(),
+        };
 
-                            if let Some(ref params) = ident.params {
-                                for (idx, param) in params.iter().enumerate() {
-                                    if let Some(name) = param.name {
-                                        let ty = if idx < variant.types.len() {
-                                            variant.types[idx].clone()
-                                        } else {
-                                            SourceType::Error
-                                        };
-
-                                        let ty = replace_type_param(
-                                            self.sa,
-                                            ty,
-                                            &expr_type_params,
-                                            None,
-                                        );
-
-                                        if used_idents.insert(name) == false {
-                                            let msg = ErrorMessage::VarAlreadyInPattern;
-                                            self.sa.diag.lock().report(
-                                                self.file_id,
-                                                param.span,
-                                                msg,
-                                            );
-                                        }
-
-                                        let var_id = self.vars.add_var(name, ty, param.mutable);
-                                        self.add_local(var_id, param.span);
-                                        self.analysis
-                                            .map_vars
-                                            .insert(param.id, self.vars.local_var_id(var_id));
-                                    }
-                                }
-                            }
-                        } else {
-                            let msg = ErrorMessage::EnumVariantExpected;
-                            self.sa
-                                .diag
-                                .lock()
-                                .report(self.file_id, ident.path.span, msg);
-                        }
-                    }
+        match self.read_path(&ident.path) {
+            Ok(Sym::EnumVariant(enum_id, variant_idx)) if ty.enum_id() == Some(enum_id) => {
+                let type_params = ty.type_params();
+                self.analysis.set_ty(pattern.id, ty);
+                self.analysis.map_idents.insert(
+                    pattern.id,
+                    IdentType::EnumValue(enum_id, type_params.clone(), variant_idx),
+                );
 
-                    Ok(_) => {
-                        let msg = ErrorMessage::EnumVariantExpected;
-                        self.sa
-                            .diag
-                            .lock()
-                            .report(self.file_id, ident.path.span, msg);
-                    }
+                self.check_expr_match_pattern_params(
+                    enum_id,
+                    variant_idx,
+                    &type_params,
+                    ident,
+                    pattern.span,
+                    used_idents,
+                );
+            }
 
-                    Err(()) => {}
+            Ok(_) => {
+                if !ty.is_error() {
+                    let msg = ErrorMessage::EnumVariantExpected;
+                    self.sa
+                        .diag
+                        .lock()
+                        .report(self.file_id, ident.path.span, msg);
                 }
             }
+
+            Err(()) => {}
         }
     }
 
     fn check_expr_if(&mut self, expr: &ast::ExprIfType, expected_ty: SourceType) -> SourceType {
         let expr_type = self.check_expr(&expr.cond, SourceType::Any);
 
-        if !expr_type.is_bool() && !expr_type.is_error() {
-            let expr_type = expr_type.name_fct(self.sa, self.fct);
-            let msg = ErrorMessage::IfCondType(expr_type);
-            self.sa.diag.lock().report(self.file_id, expr.span, msg);
-        }
+        let then_type = if let Some(ref pattern) = expr.pattern {
+            // Variables bound by the pattern are only visible in the then-branch.
+            self.symtable.push_level();
+            self.check_cond_pattern(pattern, expr_type, expr.cond.span());
+            let then_type = self.check_expr(&expr.then_block, expected_ty.clone());
+            self.symtable.pop_level();
+            then_type
+        } else {
+            if !expr_type.is_bool() && !expr_type.is_error() {
+                let expr_type = expr_type.name_fct(self.sa, self.fct);
+                let msg = ErrorMessage::IfCondType(expr_type);
+                self.sa.diag.lock().report(self.file_id, expr.span, msg);
+            }
 
-        let then_type = self.check_expr(&expr.then_block, expected_ty.clone());
+            self.check_expr(&expr.then_block, expected_ty.clone())
+        };
 
         let merged_type = if let Some(ref else_block) = expr.else_block {
             let else_type = self.check_expr(else_block, expected_ty);
@@ -848,16 +1489,16 @@ impl<'a> TypeCheck<'a> {
                 then_type
             } else if then_type.is_error() {
                 else_type
-            } else if else_type.is_error() {
+            } else if else_type.is_error() || then_type.allows(self.sa, else_type.clone()) {
                 then_type
-            } else if !then_type.allows(self.sa, else_type.clone()) {
+            } else if else_type.allows(self.sa, then_type.clone()) {
+                else_type
+            } else {
                 let then_type_name = then_type.name_fct(self.sa, self.fct);
                 let else_type_name = else_type.name_fct(self.sa, self.fct);
                 let msg = ErrorMessage::IfBranchTypesIncompatible(then_type_name, else_type_name);
                 self.sa.diag.lock().report(self.file_id, expr.span, msg);
                 then_type
-            } else {
-                then_type
             }
         } else {
             SourceType::Unit
@@ -868,11 +1509,87 @@ impl<'a> TypeCheck<'a> {
         merged_type
     }
 
+    // Like `check_expr` but for expressions whose value is used. An `if` without
+    // `else` only has a value when its condition holds.
+    fn check_value_expr(&mut self, expr: &ast::Expr, expected_ty: SourceType) -> SourceType {
+        let ty = self.check_expr(expr, expected_ty);
+
+        if let Some(expr_if) = expr.to_if() {
+            if expr_if.else_block.is_none() && !expr_always_returns(&expr_if.then_block) {
+                let then_type = self.analysis.ty(expr_if.then_block.id());
+
+                if !then_type.is_unit() && !then_type.is_error() {
+                    let then_type = then_type.name_fct(self.sa, self.fct);
+                    let msg = ErrorMessage::IfWithoutElse(then_type);
+                    self.sa.diag.lock().report(self.file_id, expr_if.span, msg);
+                    return SourceType::Error;
+                }
+            }
+        }
+
+        ty
+    }
+
+    fn check_expr_try_catch(
+        &mut self,
+        expr: &ast::ExprTryCatchType,
+        expected_ty: SourceType,
+    ) -> SourceType {
+        let try_type = self.check_expr(&expr.try_block, expected_ty.clone());
+
+        self.symtable.push_level();
+
+        let catch_type = self.read_type(&expr.catch_type);
+
+        if !catch_type.is_error() && !catch_type.is_cls() {
+            let catch_type = catch_type.name_fct(self.sa, self.fct);
+            let msg = ErrorMessage::CatchExpectsClass(catch_type);
+            self.sa
+                .diag
+                .lock()
+                .report(self.file_id, expr.catch_type.span(), msg);
+        }
+
+        let var_id = self.vars.add_var(expr.catch_name, catch_type, false);
+        self.vars.declared_at(var_id, expr.span);
+        self.add_local(var_id, expr.span);
+        self.analysis
+            .map_vars
+            .insert(expr.id, self.vars.local_var_id(var_id));
+
+        let catch_block_type = self.check_expr(&expr.catch_block, expected_ty);
+
+        self.symtable.pop_level();
+
+        let merged_type = if expr_always_returns(&expr.try_block) {
+            catch_block_type
+        } else if expr_always_returns(&expr.catch_block) {
+            try_type
+        } else if try_type.is_error() {
+            catch_block_type
+        } else if catch_block_type.is_error() {
+            try_type
+        } else if !try_type.allows(self.sa, catch_block_type.clone()) {
+            let try_type_name = try_type.name_fct(self.sa, self.fct);
+            let catch_type_name = catch_block_type.name_fct(self.sa, self.fct);
+            let msg = ErrorMessage::TryCatchTypesIncompatible(try_type_name, catch_type_name);
+            self.sa.diag.lock().report(self.file_id, expr.span, msg);
+            try_type
+        } else {
+            try_type
+        };
+
+        self.analysis.set_ty(expr.id, merged_type.clone());
+
+        merged_type
+    }
+
     fn check_expr_ident(&mut self, e: &ast::ExprIdentType, expected_ty: SourceType) -> SourceType {
         let sym = self.symtable.get(e.name);
 
         match sym {
             Some(Sym::Var(var_id)) => {
+                self.vars.mark_used(var_id);
                 let ty = self.vars.get_var(var_id).ty.clone();
                 self.analysis.set_ty(e.id, ty.clone());
 
@@ -942,6 +1659,8 @@ impl<'a> TypeCheck<'a> {
     fn check_expr_assign(&mut self, e: &ast::ExprBinType) {
         if e.lhs.is_call() {
             self.check_expr_assign_call(e);
+        } else if e.lhs.is_index() {
+            self.check_expr_assign_index(e);
         } else if e.lhs.is_dot() {
             self.check_expr_assign_field(e);
         } else if e.lhs.is_ident() {
@@ -1019,7 +1738,7 @@ impl<'a> TypeCheck<'a> {
             }
         };
 
-        let rhs_type = self.check_expr(&e.rhs, lhs_type.clone());
+        let rhs_type = self.check_value_expr(&e.rhs, lhs_type.clone());
 
         if !lhs_type.is_error()
             && !rhs_type.is_error()
@@ -1067,6 +1786,32 @@ impl<'a> TypeCheck<'a> {
         }
     }
 
+    fn check_expr_assign_index(&mut self, e: &ast::ExprBinType) {
+        let index = e.lhs.to_index().unwrap();
+        let object_type = self.check_expr(&index.object, SourceType::Any);
+        let index_type = self.check_expr(&index.index, SourceType::Any);
+        let value_type = self.check_expr(&e.rhs, SourceType::Any);
+
+        if object_type.is_error() || index_type.is_error() || value_type.is_error() {
+            return;
+        }
+
+        let trait_id = self.sa.known.traits.index_mut();
+
+        if let Some(descriptor) = self.find_index_method(
+            e.span,
+            object_type.clone(),
+            trait_id,
+            "set",
+            &[index_type, value_type],
+        ) {
+            let call_type = CallType::Expr(object_type, descriptor.fct_id, descriptor.type_params);
+            self.analysis
+                .map_calls
+                .insert_or_replace(e.id, Arc::new(call_type));
+        }
+    }
+
     fn check_expr_assign_field(&mut self, e: &ast::ExprBinType) {
         let field_expr = e.lhs.to_dot().unwrap();
 
@@ -1255,10 +2000,22 @@ impl<'a> TypeCheck<'a> {
                 self.check_expr_bin_bool(e, e.op, lhs_type, rhs_type)
             }
             ast::BinOp::Cmp(cmp) => self.check_expr_bin_cmp(e, cmp, lhs_type, rhs_type),
-            ast::BinOp::Add => self.check_expr_bin_method(e, e.op, "plus", lhs_type, rhs_type),
-            ast::BinOp::Sub => self.check_expr_bin_method(e, e.op, "minus", lhs_type, rhs_type),
-            ast::BinOp::Mul => self.check_expr_bin_method(e, e.op, "times", lhs_type, rhs_type),
-            ast::BinOp::Div => self.check_expr_bin_method(e, e.op, "div", lhs_type, rhs_type),
+            ast::BinOp::Add => {
+                let trait_id = self.sa.known.traits.add();
+                self.check_expr_bin_trait(e, e.op, trait_id, "plus", lhs_type, rhs_type)
+            }
+            ast::BinOp::Sub => {
+                let trait_id = self.sa.known.traits.sub();
+                self.check_expr_bin_trait(e, e.op, trait_id, "minus", lhs_type, rhs_type)
+            }
+            ast::BinOp::Mul => {
+                let trait_id = self.sa.known.traits.mul();
+                self.check_expr_bin_trait(e, e.op, trait_id, "times", lhs_type, rhs_type)
+            }
+            ast::BinOp::Div => {
+                let trait_id = self.sa.known.traits.div();
+                self.check_expr_bin_trait(e, e.op, trait_id, "div", lhs_type, rhs_type)
+            }
             ast::BinOp::Mod => self.check_expr_bin_method(e, e.op, "modulo", lhs_type, rhs_type),
             ast::BinOp::BitOr => {
                 self.check_expr_bin_method(e, e.op, "bitwiseOr", lhs_type, rhs_type)
@@ -1295,6 +2052,112 @@ impl<'a> TypeCheck<'a> {
         SourceType::Bool
     }
 
+    // Arithmetic operators on primitives lower to intrinsics. For all other
+    // types the operator resolves to the method of the corresponding trait
+    // (`Add::plus`, `Sub::minus`, ...), type parameters need the trait as bound.
+    fn check_expr_bin_trait(
+        &mut self,
+        e: &ast::ExprBinType,
+        op: ast::BinOp,
+        trait_id: TraitDefinitionId,
+        name: &str,
+        lhs_type: SourceType,
+        rhs_type: SourceType,
+    ) -> SourceType {
+        if lhs_type.is_primitive() {
+            return self.check_expr_bin_method(e, op, name, lhs_type, rhs_type);
+        }
+
+        let trait_ty = SourceType::new_trait(trait_id);
+
+        if let SourceType::TypeParam(id) = lhs_type {
+            if !self.fct.type_params.implements_trait(id, trait_ty) {
+                return self.report_bin_op_trait_missing(e, op, trait_id, lhs_type);
+            }
+
+            let trait_ = self.sa.traits[trait_id].read();
+            let name = self.sa.interner.intern(name);
+
+            let fct_id = match trait_.find_method_with_replace(
+                self.sa,
+                false,
+                name,
+                Some(lhs_type.clone()),
+                &SourceTypeArray::empty(),
+                std::slice::from_ref(&rhs_type),
+            ) {
+                Some(fct_id) => fct_id,
+                None => {
+                    let lhs_type = lhs_type.name_fct(self.sa, self.fct);
+                    let rhs_type = rhs_type.name_fct(self.sa, self.fct);
+                    let msg = ErrorMessage::BinOpType(op.as_str().into(), lhs_type, rhs_type);
+                    self.sa.diag.lock().report(self.file_id, e.span, msg);
+                    self.analysis.set_ty(e.id, SourceType::Error);
+                    return SourceType::Error;
+                }
+            };
+
+            let call_type = CallType::GenericMethod(id, trait_id, fct_id);
+            self.analysis
+                .map_calls
+                .insert_or_replace(e.id, Arc::new(call_type));
+
+            self.analysis.set_ty(e.id, lhs_type.clone());
+            return lhs_type;
+        }
+
+        // Classes and structs may still provide the operator method directly
+        // without implementing the trait.
+        let implements = match lhs_type {
+            SourceType::Class(..) | SourceType::Struct(..) | SourceType::Enum(..) => {
+                implements_trait(self.sa, lhs_type.clone(), &self.fct.type_params, trait_ty)
+            }
+            _ => false,
+        };
+
+        if !implements && !self.has_bin_method(name, lhs_type.clone(), rhs_type.clone()) {
+            return self.report_bin_op_trait_missing(e, op, trait_id, lhs_type);
+        }
+
+        self.check_expr_bin_method(e, op, name, lhs_type, rhs_type)
+    }
+
+    fn has_bin_method(&self, name: &str, lhs_type: SourceType, rhs_type: SourceType) -> bool {
+        let name = self.sa.interner.intern(name);
+
+        lookup_method(
+            self.sa,
+            lhs_type,
+            &self.fct.type_params,
+            false,
+            name,
+            &[rhs_type],
+            &SourceTypeArray::empty(),
+        )
+        .is_some()
+    }
+
+    fn report_bin_op_trait_missing(
+        &mut self,
+        e: &ast::ExprBinType,
+        op: ast::BinOp,
+        trait_id: TraitDefinitionId,
+        lhs_type: SourceType,
+    ) -> SourceType {
+        let ty = lhs_type.name_fct(self.sa, self.fct);
+        let trait_name = self
+            .sa
+            .interner
+            .str(self.sa.traits[trait_id].read().name)
+            .to_string();
+        let msg = ErrorMessage::BinOpTraitNotImplemented(op.as_str().into(), ty, trait_name);
+        self.sa.diag.lock().report(self.file_id, e.span, msg);
+
+        self.analysis.set_ty(e.id, SourceType::Error);
+
+        SourceType::Error
+    }
+
     fn check_expr_bin_method(
         &mut self,
         e: &ast::ExprBinType,
@@ -1365,6 +2228,8 @@ impl<'a> TypeCheck<'a> {
             ast::CmpOp::Eq | ast::CmpOp::Ne => {
                 if is_simple_enum(self.sa, lhs_type.clone()) {
                     self.check_expr_cmp_enum(e, cmp, lhs_type, rhs_type)
+                } else if self.is_identity_cmp(lhs_type.clone(), rhs_type.clone()) {
+                    self.check_expr_cmp_identity(e, cmp, lhs_type, rhs_type)
                 } else {
                     self.check_expr_bin_method(e, e.op, "equals", lhs_type, rhs_type);
                 }
@@ -1380,6 +2245,55 @@ impl<'a> TypeCheck<'a> {
         SourceType::Bool
     }
 
+    // `==` on classes and trait objects calls `equals` when the type
+    // provides it and falls back to identity like `===` otherwise.
+    fn is_identity_cmp(&self, lhs_type: SourceType, rhs_type: SourceType) -> bool {
+        if !lhs_type.is_cls() && !lhs_type.is_trait() {
+            return false;
+        }
+
+        let name = self.sa.interner.intern("equals");
+
+        lookup_method(
+            self.sa,
+            lhs_type,
+            &self.fct.type_params,
+            false,
+            name,
+            &[rhs_type],
+            &SourceTypeArray::empty(),
+        )
+        .is_none()
+    }
+
+    fn check_expr_cmp_identity(
+        &mut self,
+        e: &ast::ExprBinType,
+        op: ast::CmpOp,
+        lhs_type: SourceType,
+        rhs_type: SourceType,
+    ) {
+        if lhs_type.allows(self.sa, rhs_type.clone()) || rhs_type.allows(self.sa, lhs_type.clone())
+        {
+            let intrinsic = match op {
+                ast::CmpOp::Eq => Intrinsic::ObjectEq,
+                ast::CmpOp::Ne => Intrinsic::ObjectNe,
+                _ => unreachable!(),
+            };
+            let call_type = CallType::Intrinsic(intrinsic);
+            self.analysis
+                .map_calls
+                .insert_or_replace(e.id, Arc::new(call_type));
+        } else {
+            let op = e.op.as_str().into();
+            let lhs_type = lhs_type.name_fct(self.sa, self.fct);
+            let rhs_type = rhs_type.name_fct(self.sa, self.fct);
+            let msg = ErrorMessage::BinOpType(op, lhs_type, rhs_type);
+
+            self.sa.diag.lock().report(self.file_id, e.span, msg);
+        }
+    }
+
     fn check_expr_cmp_enum(
         &mut self,
         e: &ast::ExprBinType,
@@ -1637,7 +2551,9 @@ impl<'a> TypeCheck<'a> {
             let trait_ = self.sa.traits[trait_id].read();
 
             if let Some(fct_id) = trait_.find_method(self.sa, name, true) {
-                fcts.push((trait_id, fct_id));
+                if !self.sa.fcts.idx(fct_id).read().is_associated_const {
+                    fcts.push((trait_id, fct_id));
+                }
             }
         }
 
@@ -1732,14 +2648,132 @@ impl<'a> TypeCheck<'a> {
                 .map_calls
                 .insert_or_replace(e.id, Arc::new(call_type));
 
-            self.analysis.set_ty(e.id, descriptor.return_type.clone());
+            self.analysis.set_ty(e.id, descriptor.return_type.clone());
+
+            descriptor.return_type
+        } else {
+            self.analysis.set_ty(e.id, SourceType::Error);
+
+            SourceType::Error
+        }
+    }
+
+    fn check_expr_index(&mut self, e: &ast::ExprIndexType, _expected_ty: SourceType) -> SourceType {
+        let object_type = self.check_expr(&e.object, SourceType::Any);
+        let index_type = self.check_expr(&e.index, SourceType::Any);
+
+        if object_type.is_error() || index_type.is_error() {
+            self.analysis.set_ty(e.id, SourceType::Error);
+            return SourceType::Error;
+        }
+
+        let trait_id = self.sa.known.traits.index();
+
+        if let Some(descriptor) =
+            self.find_index_method(e.span, object_type.clone(), trait_id, "get", &[index_type])
+        {
+            let call_type = CallType::Expr(object_type, descriptor.fct_id, descriptor.type_params);
+            self.analysis
+                .map_calls
+                .insert_or_replace(e.id, Arc::new(call_type));
+
+            self.analysis.set_ty(e.id, descriptor.return_type.clone());
+
+            descriptor.return_type
+        } else {
+            self.analysis.set_ty(e.id, SourceType::Error);
+
+            SourceType::Error
+        }
+    }
+
+    // Arrays are indexed through their built-in `get`/`set` methods, all
+    // other types need an impl of `Index` or `IndexMut`.
+    fn find_index_method(
+        &mut self,
+        span: Span,
+        object_type: SourceType,
+        trait_id: TraitDefinitionId,
+        name: &str,
+        args: &[SourceType],
+    ) -> Option<MethodDescriptor> {
+        let name = self.sa.interner.intern(name);
+
+        if object_type.cls_id() == Some(self.sa.known.classes.array()) {
+            return self.find_method(
+                span,
+                object_type,
+                false,
+                name,
+                args,
+                &SourceTypeArray::empty(),
+            );
+        }
+
+        let mut found_impl = false;
+
+        for impl_ in self.sa.impls.iter() {
+            let impl_ = impl_.read();
+
+            if impl_.trait_id() != trait_id {
+                continue;
+            }
+
+            let bindings = match impl_matches(
+                self.sa,
+                object_type.clone(),
+                &self.fct.type_params,
+                impl_.id(),
+            ) {
+                Some(bindings) => bindings,
+                None => continue,
+            };
+
+            found_impl = true;
 
-            descriptor.return_type
-        } else {
-            self.analysis.set_ty(e.id, SourceType::Error);
+            let fct_id = *impl_.instance_names.get(&name).expect("missing method");
+            let method = self.sa.fcts.idx(fct_id);
+            let method = method.read();
 
-            SourceType::Error
+            if args_compatible_fct(self.sa, &method, args, &bindings, None) {
+                let return_type =
+                    replace_type_param(self.sa, method.return_type.clone(), &bindings, None);
+
+                return Some(MethodDescriptor {
+                    fct_id,
+                    type_params: bindings,
+                    return_type,
+                });
+            }
         }
+
+        let type_name = object_type.name_fct(self.sa, self.fct);
+
+        let msg = if found_impl {
+            let name = self.sa.interner.str(name).to_string();
+            let param_names = args
+                .iter()
+                .map(|a| a.name_fct(self.sa, self.fct))
+                .collect::<Vec<String>>();
+            ErrorMessage::UnknownMethod(type_name, name, param_names)
+        } else {
+            let trait_ = self.sa.traits[trait_id].read();
+            let trait_name = self.sa.interner.str(trait_.name).to_string();
+            ErrorMessage::IndexTraitNotImplemented(type_name, trait_name)
+        };
+
+        self.sa.diag.lock().report(self.file_id, span, msg);
+
+        None
+    }
+
+    fn identity_type_params(&self) -> SourceTypeArray {
+        let type_params_count = self.fct.type_params.len();
+        let type_params = (0..type_params_count)
+            .into_iter()
+            .map(|idx| SourceType::TypeParam(TypeParamId(idx)))
+            .collect::<Vec<SourceType>>();
+        SourceTypeArray::with(type_params)
     }
 
     fn check_expr_call_expr_lambda(
@@ -1751,12 +2785,7 @@ impl<'a> TypeCheck<'a> {
         let (params, return_type) = expr_type.to_lambda().expect("lambda expected");
 
         // Type params are mapped to themselves.
-        let type_params_count = self.fct.type_params.len();
-        let type_params = (0..type_params_count)
-            .into_iter()
-            .map(|idx| SourceType::TypeParam(TypeParamId(idx)))
-            .collect::<Vec<SourceType>>();
-        let type_params = SourceTypeArray::with(type_params);
+        let type_params = self.identity_type_params();
 
         if !args_compatible(
             self.sa,
@@ -1802,6 +2831,14 @@ impl<'a> TypeCheck<'a> {
             self.sa.diag.lock().report(self.file_id, e.span, msg);
         }
 
+        let type_params = if type_params.is_empty() && self.sa.fcts.idx(fct_id).read().is_nested() {
+            // Nested functions are instantiated with the type params of the
+            // enclosing function.
+            self.identity_type_params()
+        } else {
+            type_params
+        };
+
         let mut lookup = MethodLookup::new(self.sa, self.fct)
             .span(e.span)
             .callee(fct_id)
@@ -1878,6 +2915,10 @@ impl<'a> TypeCheck<'a> {
             return self.check_expr_call_generic(e, id, method_name, arg_types);
         }
 
+        if object_type.is_self() && self.fct.in_trait() {
+            return self.check_expr_call_self(e, method_name, arg_types);
+        }
+
         if object_type.is_error() {
             self.analysis.set_ty(e.id, SourceType::Error);
 
@@ -1921,6 +2962,14 @@ impl<'a> TypeCheck<'a> {
 
             return_type
         } else if lookup.found_fct_id().is_none() {
+            if object_type.is_enum()
+                && self.sa.interner.str(method_name).as_str() == "ordinal"
+                && fct_type_params.is_empty()
+                && arg_types.is_empty()
+            {
+                return self.check_expr_call_enum_ordinal(e);
+            }
+
             // No method with this name found, so this might actually be a field
             self.check_expr_call_field(e, object_type, method_name, fct_type_params, arg_types)
         } else {
@@ -1941,6 +2990,68 @@ impl<'a> TypeCheck<'a> {
         }
     }
 
+    fn check_expr_call_enum_ordinal(&mut self, e: &ast::ExprCallType) -> SourceType {
+        let call_type = CallType::Intrinsic(Intrinsic::EnumOrdinal);
+        self.analysis
+            .map_calls
+            .insert_or_replace(e.id, Arc::new(call_type));
+        self.analysis.set_ty(e.id, SourceType::Int32);
+
+        SourceType::Int32
+    }
+
+    fn is_enum_values_call(
+        &mut self,
+        object_type: SourceType,
+        method_name: Name,
+        fct_type_params: &SourceTypeArray,
+        arg_types: &[SourceType],
+    ) -> bool {
+        if self.sa.interner.str(method_name).as_str() != "values"
+            || !fct_type_params.is_empty()
+            || !arg_types.is_empty()
+        {
+            return false;
+        }
+
+        // A static method `values` defined by the user takes precedence.
+        let mut lookup = MethodLookup::new(self.sa, self.fct)
+            .no_error_reporting()
+            .static_method(object_type)
+            .name(method_name)
+            .type_param_defs(&self.fct.type_params)
+            .args(arg_types);
+
+        lookup.find();
+        lookup.found_fct_id().is_none()
+    }
+
+    fn check_expr_call_enum_values(
+        &mut self,
+        e: &ast::ExprCallType,
+        object_type: SourceType,
+    ) -> SourceType {
+        let enum_id = object_type.enum_id().expect("enum expected");
+        let enum_ = self.sa.enums.idx(enum_id);
+        let enum_ = enum_.read();
+
+        if !enum_.simple_enumeration {
+            let msg = ErrorMessage::EnumValuesWithPayload(enum_.name(self.sa));
+            self.sa.diag.lock().report(self.file_id, e.span, msg);
+
+            self.analysis.set_ty(e.id, SourceType::Error);
+            return SourceType::Error;
+        }
+
+        let call_type = CallType::Intrinsic(Intrinsic::EnumValues);
+        self.analysis.map_calls.insert(e.id, Arc::new(call_type));
+
+        let ty = self.sa.known.array_ty(object_type);
+        self.analysis.set_ty(e.id, ty.clone());
+
+        ty
+    }
+
     fn check_expr_call_field(
         &mut self,
         e: &ast::ExprCallType,
@@ -2201,6 +3312,55 @@ impl<'a> TypeCheck<'a> {
         )
     }
 
+    // Default method bodies in traits may call the other methods of the
+    // trait on `self`.
+    fn check_expr_call_self(
+        &mut self,
+        e: &ast::ExprCallType,
+        name: Name,
+        args: &[SourceType],
+    ) -> SourceType {
+        let trait_id = self.fct.trait_id();
+        let trait_ = self.sa.traits[trait_id].read();
+
+        if let Some(fct_id) = trait_.find_method_with_replace(
+            self.sa,
+            false,
+            name,
+            Some(SourceType::This),
+            &SourceTypeArray::empty(),
+            args,
+        ) {
+            let fct = self.sa.fcts.idx(fct_id);
+            let fct = fct.read();
+            let return_type = fct.return_type.clone();
+
+            let trait_type_params = (0..self.fct.container_type_params)
+                .map(|idx| SourceType::TypeParam(TypeParamId(idx)))
+                .collect::<Vec<SourceType>>();
+            let trait_ty = SourceType::Trait(trait_id, SourceTypeArray::with(trait_type_params));
+            let call_type = CallType::TraitObjectMethod(trait_ty, fct_id);
+            self.analysis
+                .map_calls
+                .insert_or_replace(e.id, Arc::new(call_type));
+            self.analysis.set_ty(e.id, return_type.clone());
+
+            return_type
+        } else {
+            let name = self.sa.interner.str(name).to_string();
+            let param_names = args
+                .iter()
+                .map(|a| a.name_fct(self.sa, self.fct))
+                .collect::<Vec<String>>();
+            let msg = ErrorMessage::UnknownMethod("Self".into(), name, param_names);
+            self.sa.diag.lock().report(self.file_id, e.span, msg);
+
+            self.analysis.set_ty(e.id, SourceType::Error);
+
+            SourceType::Error
+        }
+    }
+
     fn check_expr_call_generic_type_param(
         &mut self,
         e: &ast::ExprCallType,
@@ -2215,7 +3375,14 @@ impl<'a> TypeCheck<'a> {
             let trait_id = trait_ty.trait_id().expect("trait expected");
             let trait_ = self.sa.traits[trait_id].read();
 
-            if let Some(fid) = trait_.find_method_with_replace(self.sa, false, name, None, args) {
+            if let Some(fid) = trait_.find_method_with_replace(
+                self.sa,
+                false,
+                name,
+                None,
+                &trait_ty.type_params(),
+                args,
+            ) {
                 found_fcts.push(fid);
             }
         }
@@ -2388,6 +3555,15 @@ impl<'a> TypeCheck<'a> {
                     ) {
                         let object_ty = SourceType::Enum(enum_id, container_type_params);
 
+                        if self.is_enum_values_call(
+                            object_ty.clone(),
+                            method_name,
+                            &type_params,
+                            arg_types,
+                        ) {
+                            return self.check_expr_call_enum_values(e, object_ty);
+                        }
+
                         self.check_expr_call_static_method(
                             e,
                             object_ty,
@@ -2487,6 +3663,10 @@ impl<'a> TypeCheck<'a> {
                 self.check_expr_path_module(e, expected_ty, module_id, element_name)
             }
 
+            Some(Sym::TypeParam(id)) if type_params.is_empty() => {
+                self.check_expr_path_generic_const(e, id, element_name)
+            }
+
             _ => {
                 let msg = ErrorMessage::InvalidLeftSideOfSeparator;
                 self.sa.diag.lock().report(self.file_id, e.lhs.span(), msg);
@@ -2497,6 +3677,59 @@ impl<'a> TypeCheck<'a> {
         }
     }
 
+    fn check_expr_path_generic_const(
+        &mut self,
+        e: &ast::ExprPathType,
+        tp_id: TypeParamId,
+        name: Name,
+    ) -> SourceType {
+        let mut consts = Vec::new();
+
+        for trait_ty in self.fct.type_params.bounds_for_type_param(tp_id) {
+            let trait_id = trait_ty.trait_id().expect("trait expected");
+            let trait_ = self.sa.traits[trait_id].read();
+
+            if let Some(fct_id) = trait_.find_method(self.sa, name, true) {
+                if self.sa.fcts.idx(fct_id).read().is_associated_const {
+                    consts.push((trait_id, fct_id));
+                }
+            }
+        }
+
+        if consts.is_empty() {
+            let msg = ErrorMessage::InvalidLeftSideOfSeparator;
+            self.sa.diag.lock().report(self.file_id, e.lhs.span(), msg);
+
+            self.analysis.set_ty(e.id, SourceType::Error);
+            return SourceType::Error;
+        }
+
+        if consts.len() > 1 {
+            let name = self.sa.interner.str(name).to_string();
+            let msg = ErrorMessage::MultipleCandidatesForAssociatedConst(name);
+            self.sa.diag.lock().report(self.file_id, e.span, msg);
+
+            self.analysis.set_ty(e.id, SourceType::Error);
+            return SourceType::Error;
+        }
+
+        let (trait_id, fct_id) = consts[0];
+        let call_type = CallType::GenericStaticMethod(tp_id, trait_id, fct_id);
+        self.analysis.map_calls.insert(e.id, Arc::new(call_type));
+
+        let fct = self.sa.fcts.idx(fct_id);
+        let return_type = replace_type_param(
+            self.sa,
+            fct.read().return_type.clone(),
+            &SourceTypeArray::empty(),
+            Some(SourceType::TypeParam(tp_id)),
+        );
+
+        self.analysis.set_ty(e.id, return_type.clone());
+
+        return_type
+    }
+
     fn read_path_expr(&mut self, expr: &ast::Expr) -> Result<Option<Sym>, ()> {
         if let Some(expr_path) = expr.to_path() {
             let sym = self.read_path_expr(&expr_path.lhs)?;
@@ -3102,7 +4335,7 @@ impl<'a> TypeCheck<'a> {
                     file_id: self.fct.file_id,
                     analysis: &mut analysis,
                     symtable: &mut self.symtable,
-                    in_loop: false,
+                    loop_labels: Vec::new(),
                     self_available: self.self_available.clone(),
                     vars: self.vars,
                     contains_lambda: false,
@@ -3132,7 +4365,28 @@ impl<'a> TypeCheck<'a> {
         let check_type = self.read_type(&e.data_type);
         self.analysis.set_ty(e.data_type.id(), check_type.clone());
 
-        if check_type.is_trait() {
+        if object_type.is_trait() && (e.is || !check_type.is_trait()) {
+            return self.check_expr_conv_trait_object(e, object_type, check_type);
+        }
+
+        if !e.is && is_checked_cast(&object_type, &check_type) {
+            self.analysis.set_ty(e.id, check_type.clone());
+            return check_type;
+        }
+
+        if e.is {
+            if !object_type.is_error() {
+                let name = object_type.name_fct(self.sa, self.fct);
+                self.sa.diag.lock().report(
+                    self.file_id,
+                    e.span,
+                    ErrorMessage::TraitObjectExpected(name),
+                );
+            }
+
+            self.analysis.set_ty(e.id, SourceType::Bool);
+            SourceType::Bool
+        } else if check_type.is_trait() {
             let implements = implements_trait(
                 self.sa,
                 object_type.clone(),
@@ -3167,6 +4421,53 @@ impl<'a> TypeCheck<'a> {
         }
     }
 
+    // Checks `object is Type` and `object as Type` where object is a trait
+    // object and Type is the concrete type wrapped by it.
+    fn check_expr_conv_trait_object(
+        &mut self,
+        e: &ast::ExprConvType,
+        object_type: SourceType,
+        check_type: SourceType,
+    ) -> SourceType {
+        if check_type.is_trait() {
+            let name = check_type.name_fct(self.sa, self.fct);
+            self.sa.diag.lock().report(
+                self.file_id,
+                e.span,
+                ErrorMessage::ConcreteTypeExpected(name),
+            );
+        } else if !check_type.is_error() {
+            let implements = implements_trait(
+                self.sa,
+                check_type.clone(),
+                &self.fct.type_params,
+                object_type.clone(),
+            );
+
+            if !implements {
+                let check_type = check_type.name_fct(self.sa, self.fct);
+                let object_type = object_type.name_fct(self.sa, self.fct);
+
+                self.sa.diag.lock().report(
+                    self.file_id,
+                    e.span,
+                    ErrorMessage::TypeNotImplementingTrait(check_type, object_type),
+                );
+            }
+        }
+
+        let ty = if e.is {
+            SourceType::Bool
+        } else if check_type.is_trait() {
+            SourceType::Error
+        } else {
+            check_type
+        };
+
+        self.analysis.set_ty(e.id, ty.clone());
+        ty
+    }
+
     fn check_expr_lit_int(
         &mut self,
         e: &ast::ExprLitIntType,
@@ -3287,6 +4588,7 @@ impl<'a> TypeCheck<'a> {
             ast::Expr::Bin(ref expr) => self.check_expr_bin(expr, expected_ty),
             ast::Expr::Call(ref expr) => self.check_expr_call(expr, expected_ty),
             ast::Expr::TypeParam(ref expr) => self.check_expr_type_param(expr, expected_ty),
+            ast::Expr::Index(ref expr) => self.check_expr_index(expr, expected_ty),
             ast::Expr::Path(ref expr) => self.check_expr_path(expr, expected_ty),
             ast::Expr::Dot(ref expr) => self.check_expr_dot(expr, expected_ty),
             ast::Expr::This(ref expr) => self.check_expr_this(expr, expected_ty),
@@ -3297,15 +4599,33 @@ impl<'a> TypeCheck<'a> {
             ast::Expr::Tuple(ref expr) => self.check_expr_tuple(expr, expected_ty),
             ast::Expr::Paren(ref expr) => self.check_expr_paren(expr, expected_ty),
             ast::Expr::Match(ref expr) => self.check_expr_match(expr, expected_ty),
+            ast::Expr::Range(ref expr) => self.check_expr_range(expr, expected_ty),
+            ast::Expr::Try(ref expr) => self.check_expr_try(expr, expected_ty),
+            ast::Expr::TryCatch(ref expr) => self.check_expr_try_catch(expr, expected_ty),
         }
     }
 
     fn check_stmt_break_and_continue(&mut self, stmt: &ast::Stmt) {
-        if !self.in_loop {
+        let label = match stmt {
+            ast::Stmt::Break(ref stmt) => stmt.label,
+            ast::Stmt::Continue(ref stmt) => stmt.label,
+            _ => unreachable!(),
+        };
+
+        if self.loop_labels.is_empty() {
             self.sa
                 .diag
                 .lock()
                 .report(self.fct.file_id, stmt.span(), ErrorMessage::OutsideLoop);
+        } else if let Some(label) = label {
+            if !self.loop_labels.contains(&Some(label)) {
+                let name = self.sa.interner.str(label).to_string();
+                self.sa.diag.lock().report(
+                    self.fct.file_id,
+                    stmt.span(),
+                    ErrorMessage::UnknownLabel(name),
+                );
+            }
         }
     }
 }
@@ -3319,8 +4639,20 @@ impl<'a> Visitor for TypeCheck<'a> {
         match *s {
             ast::Stmt::Let(ref stmt) => self.check_stmt_let(stmt),
             ast::Stmt::While(ref stmt) => self.check_stmt_while(stmt),
+            ast::Stmt::DoWhile(ref stmt) => self.check_stmt_do_while(stmt),
+            // nested functions were already checked when entering the block
+            ast::Stmt::Fct(_) => {}
+            ast::Stmt::Loop(ref stmt) => {
+                self.check_loop_body(&stmt.block, stmt.label);
+
+                if always_returns(s) {
+                    self.analysis.set_ty(s.id(), SourceType::Never);
+                    return;
+                }
+            }
             ast::Stmt::For(ref stmt) => self.check_stmt_for(stmt),
             ast::Stmt::Return(ref stmt) => self.check_stmt_return(stmt),
+            ast::Stmt::Throw(ref stmt) => self.check_stmt_throw(stmt),
 
             // for the rest of the statements, no special handling is necessary
             ast::Stmt::Break(_) | ast::Stmt::Continue(_) => {
@@ -3412,8 +4744,12 @@ fn arg_allows(
     match def {
         SourceType::Error | SourceType::Any => unreachable!(),
         SourceType::Unit
+        | SourceType::Never
         | SourceType::Bool
         | SourceType::UInt8
+        | SourceType::UInt16
+        | SourceType::UInt32
+        | SourceType::UInt64
         | SourceType::Char
         | SourceType::Struct(_, _)
         | SourceType::Int32
@@ -3503,17 +4839,30 @@ pub fn check_lit_int(
 ) -> (SourceType, i64) {
     let ty = determine_type_literal_int(e, expected_type);
 
+    // The unsigned types wider than UInt8 exist in the type system but
+    // can't be generated yet.
+    if let SourceType::UInt16 | SourceType::UInt32 | SourceType::UInt64 = ty {
+        sa.diag
+            .lock()
+            .report(file, e.span, ErrorMessage::Unimplemented);
+    }
+
     let ty_name = ty.name(sa);
     let value = e.value;
 
     if e.base == IntBase::Dec {
         let max = match ty {
             SourceType::UInt8 => 256,
-            SourceType::Int32 => 1u64 << 31,
-            SourceType::Int64 => 1u64 << 63,
+            SourceType::UInt16 => 1u128 << 16,
+            SourceType::UInt32 => 1u128 << 32,
+            SourceType::UInt64 => 1u128 << 64,
+            SourceType::Int32 => 1u128 << 31,
+            SourceType::Int64 => 1u128 << 63,
             _ => unreachable!(),
         };
 
+        let value = value as u128;
+
         if (negate && value > max) || (!negate && value >= max) {
             sa.diag
                 .lock()
@@ -3531,7 +4880,10 @@ pub fn check_lit_int(
         assert!(!negate);
 
         let max = match ty {
-            SourceType::UInt8 => 256 as u64,
+            SourceType::UInt8 => u8::MAX as u64,
+            SourceType::UInt16 => u16::MAX as u64,
+            SourceType::UInt32 => u32::MAX as u64,
+            SourceType::UInt64 => u64::MAX,
             SourceType::Int32 => u32::max_value() as u64,
             SourceType::Int64 => u64::max_value() as u64,
             _ => unreachable!(),
@@ -3550,6 +4902,9 @@ pub fn check_lit_int(
 fn determine_suffix_type_int_literal(e: &ast::ExprLitIntType) -> Option<SourceType> {
     match e.suffix {
         IntSuffix::UInt8 => Some(SourceType::UInt8),
+        IntSuffix::UInt16 => Some(SourceType::UInt16),
+        IntSuffix::UInt32 => Some(SourceType::UInt32),
+        IntSuffix::UInt64 => Some(SourceType::UInt64),
         IntSuffix::Int32 => Some(SourceType::Int32),
         IntSuffix::Int64 => Some(SourceType::Int64),
         IntSuffix::None => None,
@@ -3564,6 +4919,9 @@ pub fn determine_type_literal_int(
 
     let default_type = match expected_type {
         SourceType::UInt8 => SourceType::UInt8,
+        SourceType::UInt16 => SourceType::UInt16,
+        SourceType::UInt32 => SourceType::UInt32,
+        SourceType::UInt64 => SourceType::UInt64,
         SourceType::Int32 => SourceType::Int32,
         SourceType::Int64 => SourceType::Int64,
         _ => SourceType::Int64,
@@ -3651,6 +5009,22 @@ fn lookup_method(
     None
 }
 
+// narrowing casts between primitive types, these trap when the value
+// doesn't fit into the target type.
+pub fn is_checked_cast(object_type: &SourceType, check_type: &SourceType) -> bool {
+    matches!(
+        (object_type, check_type),
+        (SourceType::Int64, SourceType::Int32)
+            | (SourceType::Int32, SourceType::UInt8)
+            | (SourceType::Float64, SourceType::Int32)
+    )
+}
+
+enum MatchLitValue {
+    Int(i64),
+    Str(String),
+}
+
 fn is_simple_enum(sa: &SemAnalysis, ty: SourceType) -> bool {
     match ty {
         SourceType::Enum(enum_id, _) => {
@@ -3662,6 +5036,16 @@ fn is_simple_enum(sa: &SemAnalysis, ty: SourceType) -> bool {
     }
 }
 
+fn has_nested_pattern(pattern: &MatchPattern) -> bool {
+    match pattern.data {
+        ast::MatchPatternData::Ident(ref ident) => ident
+            .params
+            .as_ref()
+            .is_some_and(|params| params.iter().any(|p| p.pattern.is_some())),
+        _ => false,
+    }
+}
+
 struct VarAccessPerFunction {
     level: usize,
     start_idx: usize,
@@ -3749,6 +5133,8 @@ impl VarManager {
             ty,
             mutable,
             location: VarLocation::Stack,
+            declaration: None,
+            used: false,
         };
 
         self.vars.push(var);
@@ -3756,6 +5142,23 @@ impl VarManager {
         id
     }
 
+    // Only variables with a declaration span are checked for being unused.
+    fn declared_at(&mut self, var_id: NestedVarId, span: Span) {
+        self.vars[var_id.0].declaration = Some(span);
+    }
+
+    fn mark_used(&mut self, var_id: NestedVarId) {
+        self.vars[var_id.0].used = true;
+    }
+
+    fn unused_vars(&self) -> Vec<(Name, Span)> {
+        self.vars[self.current_function().start_idx..]
+            .iter()
+            .filter(|var| !var.used)
+            .filter_map(|var| var.declaration.map(|span| (var.name, span)))
+            .collect()
+    }
+
     fn get_var(&self, idx: NestedVarId) -> &VarDefinition {
         &self.vars[idx.0]
     }
@@ -3791,4 +5194,6 @@ pub struct VarDefinition {
     pub ty: SourceType,
     pub mutable: bool,
     pub location: VarLocation,
+    pub declaration: Option<Span>,
+    pub used: bool,
 }
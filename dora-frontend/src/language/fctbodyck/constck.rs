@@ -1,51 +1,148 @@
+use std::collections::HashMap;
+
+use dora_bytecode::Intrinsic;
+
+use crate::language::access::const_accessible_from;
+use crate::language::constfold::{cmp, eval_int_bin};
 use crate::language::error::msg::ErrorMessage;
 use crate::language::fctbodyck::body::{
     check_lit_float, check_lit_int, determine_type_literal_int,
 };
-use crate::language::sem_analysis::{ConstDefinition, ConstValue, SemAnalysis};
+use crate::language::sem_analysis::{
+    ConstDefinitionId, ConstValue, ModuleDefinitionId, SemAnalysis, SourceFileId,
+};
+use crate::language::sym::{ModuleSymTable, Sym};
 use crate::language::ty::SourceType;
 
 use dora_parser::ast::*;
+use dora_parser::Span;
+
+pub fn check(sa: &SemAnalysis) {
+    let mut evaluator = ConstEvaluator {
+        sa,
+        states: HashMap::new(),
+    };
+
+    for const_ in sa.consts.iter() {
+        let const_id = const_.read().id();
+        evaluator.evaluate(const_id, None);
+    }
+}
+
+enum ConstState {
+    InProgress,
+    Done(SourceType, ConstValue),
+}
+
+// Consts are evaluated on first use, so a const can refer to consts
+// declared after it. Reaching a const that is still in progress means
+// the consts form a cycle.
+struct ConstEvaluator<'a> {
+    sa: &'a SemAnalysis,
+    states: HashMap<ConstDefinitionId, ConstState>,
+}
+
+impl<'a> ConstEvaluator<'a> {
+    fn evaluate(
+        &mut self,
+        const_id: ConstDefinitionId,
+        reference: Option<(SourceFileId, Span)>,
+    ) -> (SourceType, ConstValue) {
+        match self.states.get(&const_id) {
+            Some(ConstState::Done(ty, value)) => return (ty.clone(), value.clone()),
+            Some(ConstState::InProgress) => {
+                let (file_id, span) = reference.expect("reference missing");
+                let name = self.sa.consts.idx(const_id).read().name(self.sa);
+                let msg = ErrorMessage::ConstCycle(name);
+                self.sa.diag.lock().report(file_id, span, msg);
+                return (SourceType::Error, ConstValue::None);
+            }
+            None => {}
+        }
+
+        self.states.insert(const_id, ConstState::InProgress);
+
+        let const_ = self.sa.consts.idx(const_id);
+
+        let (file_id, module_id, name, const_ty, expr) = {
+            let const_ = const_.read();
+            (
+                const_.file_id,
+                const_.module_id,
+                const_.name,
+                const_.ty.clone(),
+                const_.expr.clone(),
+            )
+        };
+
+        let sa = self.sa;
+        let mut constck = ConstCheck {
+            sa,
+            evaluator: self,
+            file_id,
+            module_id,
+            symtable: ModuleSymTable::new(sa, module_id),
+        };
+
+        let (ty, value) = constck.check_expr(&expr, const_ty.clone());
+
+        let ty = if ty.is_error() {
+            ty
+        } else if !const_ty.allows(self.sa, ty.clone()) {
+            let name = self.sa.interner.str(name).to_string();
+            let const_ty = const_ty.name(self.sa);
+            let ty = ty.name(self.sa);
+            let msg = ErrorMessage::AssignType(name, const_ty, ty);
+            self.sa.diag.lock().report(file_id, expr.span(), msg);
+            SourceType::Error
+        } else {
+            const_ty
+        };
+
+        const_.write().value = value.clone();
+        self.states
+            .insert(const_id, ConstState::Done(ty.clone(), value.clone()));
 
-pub struct ConstCheck<'a> {
-    pub sa: &'a SemAnalysis,
-    pub const_: &'a ConstDefinition,
+        (ty, value)
+    }
 }
 
-impl<'a> ConstCheck<'a> {
-    pub fn check_expr(&mut self, expr: &Expr) -> (SourceType, ConstValue) {
-        let expected_type = self.const_.ty.clone();
+struct ConstCheck<'a, 'b> {
+    sa: &'a SemAnalysis,
+    evaluator: &'b mut ConstEvaluator<'a>,
+    file_id: SourceFileId,
+    module_id: ModuleDefinitionId,
+    symtable: ModuleSymTable,
+}
 
-        let (ty, lit) = match expr {
-            &Expr::LitChar(ref expr) => (SourceType::Char, ConstValue::Char(expr.value)),
-            &Expr::LitInt(ref expr) => {
-                let (ty, value) =
-                    check_lit_int(self.sa, self.const_.file_id, expr, false, expected_type);
+impl<'a, 'b> ConstCheck<'a, 'b> {
+    fn check_expr(&mut self, expr: &Expr, expected_type: SourceType) -> (SourceType, ConstValue) {
+        match expr {
+            Expr::LitChar(expr) => (SourceType::Char, ConstValue::Char(expr.value)),
+            Expr::LitInt(expr) => {
+                let (ty, value) = check_lit_int(self.sa, self.file_id, expr, false, expected_type);
 
                 (ty, ConstValue::Int(value))
             }
-            &Expr::LitFloat(ref expr) => {
-                let (ty, val) = check_lit_float(self.sa, self.const_.file_id, expr, false);
+            Expr::LitFloat(expr) => {
+                let (ty, val) = check_lit_float(self.sa, self.file_id, expr, false);
                 (ty, ConstValue::Float(val))
             }
-            &Expr::LitBool(ref expr) => (SourceType::Bool, ConstValue::Bool(expr.value)),
+            Expr::LitBool(expr) => (SourceType::Bool, ConstValue::Bool(expr.value)),
 
-            &Expr::Un(ref expr) if expr.op == UnOp::Neg && expr.opnd.is_lit_int() => {
+            Expr::Un(expr) if expr.op == UnOp::Neg && expr.opnd.is_lit_int() => {
                 let lit_int = expr.opnd.to_lit_int().unwrap();
                 let ty = determine_type_literal_int(lit_int, expected_type.clone());
 
                 if ty == SourceType::UInt8 {
                     let ty = SourceType::UInt8.name(self.sa);
                     let msg = ErrorMessage::UnOpType(expr.op.as_str().into(), ty);
-                    self.sa
-                        .diag
-                        .lock()
-                        .report(self.const_.file_id, expr.span, msg);
+                    self.sa.diag.lock().report(self.file_id, expr.span, msg);
                 }
 
                 let (ty, value) = check_lit_int(
                     self.sa,
-                    self.const_.file_id,
+                    self.file_id,
                     expr.opnd.to_lit_int().unwrap(),
                     true,
                     expected_type,
@@ -54,37 +151,220 @@ impl<'a> ConstCheck<'a> {
                 (ty, ConstValue::Int(value))
             }
 
-            &Expr::Un(ref expr) if expr.op == UnOp::Neg && expr.opnd.is_lit_float() => {
+            Expr::Un(expr) if expr.op == UnOp::Neg && expr.opnd.is_lit_float() => {
                 let (ty, val) = check_lit_float(
                     self.sa,
-                    self.const_.file_id,
+                    self.file_id,
                     expr.opnd.to_lit_float().unwrap(),
                     true,
                 );
                 (ty, ConstValue::Float(val))
             }
 
+            Expr::Paren(expr) => self.check_expr(&expr.expr, expected_type),
+            Expr::Ident(_) | Expr::Path(_) => self.check_expr_const_ref(expr),
+            Expr::Un(expr) => self.check_expr_un(expr, expected_type),
+            Expr::Bin(expr) => self.check_expr_bin(expr, expected_type),
+
+            _ => self.not_constant(expr.span()),
+        }
+    }
+
+    fn check_expr_const_ref(&mut self, expr: &Expr) -> (SourceType, ConstValue) {
+        let const_id = match self.read_path(expr) {
+            Some(Sym::Const(const_id)) => const_id,
+            _ => return self.not_constant(expr.span()),
+        };
+
+        if !const_accessible_from(self.sa, const_id, self.module_id) {
+            let name = self.sa.consts.idx(const_id).read().name(self.sa);
+            let msg = ErrorMessage::NotAccessible(name);
+            self.sa.diag.lock().report(self.file_id, expr.span(), msg);
+        }
+
+        self.evaluator
+            .evaluate(const_id, Some((self.file_id, expr.span())))
+    }
+
+    fn read_path(&self, expr: &Expr) -> Option<Sym> {
+        if let Some(ident) = expr.to_ident() {
+            return self.symtable.get(ident.name);
+        }
+
+        let path = expr.to_path()?;
+        let name = path.rhs.to_ident()?.name;
+
+        match self.read_path(&path.lhs)? {
+            Sym::Module(module_id) => {
+                let module = self.sa.modules[module_id].read();
+                let symtable = module.table.read();
+                symtable.get(name)
+            }
+            _ => None,
+        }
+    }
+
+    fn check_expr_un(
+        &mut self,
+        expr: &ExprUnType,
+        expected_type: SourceType,
+    ) -> (SourceType, ConstValue) {
+        let (ty, value) = self.check_expr(&expr.opnd, expected_type);
+
+        let result = match (expr.op, &ty) {
+            (_, SourceType::Error) => return (SourceType::Error, ConstValue::None),
+            (UnOp::Plus, SourceType::Int32)
+            | (UnOp::Plus, SourceType::Int64)
+            | (UnOp::Plus, SourceType::Float32)
+            | (UnOp::Plus, SourceType::Float64) => Some(value),
+            (UnOp::Neg, SourceType::Int32) => (value.to_int() as i32)
+                .checked_neg()
+                .map(|value| ConstValue::Int(value as i64)),
+            (UnOp::Neg, SourceType::Int64) => value.to_int().checked_neg().map(ConstValue::Int),
+            (UnOp::Neg, SourceType::Float32) | (UnOp::Neg, SourceType::Float64) => {
+                Some(ConstValue::Float(-value.to_float()))
+            }
+            (UnOp::Not, SourceType::Bool) => Some(ConstValue::Bool(!value.to_bool())),
+            (UnOp::Not, SourceType::Int32) | (UnOp::Not, SourceType::Int64) => {
+                Some(ConstValue::Int(!value.to_int()))
+            }
             _ => {
-                let msg = ErrorMessage::ConstValueExpected;
-                self.sa
-                    .diag
-                    .lock()
-                    .report(self.const_.file_id, expr.span(), msg);
+                let ty = ty.name(self.sa);
+                let msg = ErrorMessage::UnOpType(expr.op.as_str().into(), ty);
+                self.sa.diag.lock().report(self.file_id, expr.span, msg);
                 return (SourceType::Error, ConstValue::None);
             }
         };
 
-        if !self.const_.ty.allows(self.sa, ty.clone()) {
-            let name = self.sa.interner.str(self.const_.name).to_string();
-            let const_ty = self.const_.ty.name(self.sa);
-            let ty = ty.name(self.sa);
-            let msg = ErrorMessage::AssignType(name, const_ty, ty);
-            self.sa
-                .diag
-                .lock()
-                .report(self.const_.file_id, expr.span(), msg);
+        match result {
+            Some(value) => (ty, value),
+            None => self.overflow(expr.span),
+        }
+    }
+
+    fn check_expr_bin(
+        &mut self,
+        expr: &ExprBinType,
+        expected_type: SourceType,
+    ) -> (SourceType, ConstValue) {
+        let (lhs_expected, rhs_expected) = match expr.op {
+            BinOp::And | BinOp::Or => (SourceType::Bool, SourceType::Bool),
+            BinOp::ShiftL | BinOp::ArithShiftR | BinOp::LogicalShiftR => {
+                (expected_type, SourceType::Int32)
+            }
+            BinOp::Cmp(CmpOp::Is) | BinOp::Cmp(CmpOp::IsNot) | BinOp::Assign => {
+                return self.not_constant(expr.span);
+            }
+            BinOp::Cmp(_) => (SourceType::Any, SourceType::Any),
+            _ => (expected_type.clone(), expected_type),
+        };
+
+        let (lhs_ty, lhs) = self.check_expr(&expr.lhs, lhs_expected);
+
+        // Comparisons type unsuffixed literals on the right like the left side.
+        let rhs_expected = if rhs_expected == SourceType::Any {
+            lhs_ty.clone()
+        } else {
+            rhs_expected
+        };
+
+        let (rhs_ty, rhs) = self.check_expr(&expr.rhs, rhs_expected);
+
+        if lhs_ty.is_error() || rhs_ty.is_error() {
+            return (SourceType::Error, ConstValue::None);
+        }
+
+        let types_match = match expr.op {
+            BinOp::And | BinOp::Or => lhs_ty.is_bool() && rhs_ty.is_bool(),
+            BinOp::ShiftL | BinOp::ArithShiftR | BinOp::LogicalShiftR => {
+                rhs_ty == SourceType::Int32
+            }
+            _ => lhs_ty == rhs_ty,
+        };
+
+        if !types_match {
+            let lhs_ty = lhs_ty.name(self.sa);
+            let rhs_ty = rhs_ty.name(self.sa);
+            let msg = ErrorMessage::BinOpType(expr.op.as_str().into(), lhs_ty, rhs_ty);
+            self.sa.diag.lock().report(self.file_id, expr.span, msg);
+            return (SourceType::Error, ConstValue::None);
+        }
+
+        match expr.op {
+            BinOp::And => (
+                SourceType::Bool,
+                ConstValue::Bool(lhs.to_bool() && rhs.to_bool()),
+            ),
+            BinOp::Or => (
+                SourceType::Bool,
+                ConstValue::Bool(lhs.to_bool() || rhs.to_bool()),
+            ),
+            BinOp::Cmp(op) => {
+                let result = match lhs_ty {
+                    SourceType::Bool => cmp(BinOp::Cmp(op), lhs.to_bool(), rhs.to_bool()),
+                    SourceType::Char => cmp(BinOp::Cmp(op), lhs.to_char(), rhs.to_char()),
+                    SourceType::UInt8 | SourceType::Int32 | SourceType::Int64 => {
+                        cmp(BinOp::Cmp(op), lhs.to_int(), rhs.to_int())
+                    }
+                    _ => None,
+                };
+
+                match result {
+                    Some(value) => (SourceType::Bool, value),
+                    None => self.not_constant(expr.span),
+                }
+            }
+            _ => match int_intrinsic(expr.op, &lhs_ty) {
+                Some(intrinsic) => match eval_int_bin(intrinsic, lhs.to_int(), rhs.to_int()) {
+                    Some(value) => (lhs_ty, value),
+                    None => self.overflow(expr.span),
+                },
+                None => self.not_constant(expr.span),
+            },
         }
+    }
+
+    fn not_constant(&self, span: Span) -> (SourceType, ConstValue) {
+        let msg = ErrorMessage::ConstValueExpected;
+        self.sa.diag.lock().report(self.file_id, span, msg);
+        (SourceType::Error, ConstValue::None)
+    }
 
-        (ty, lit)
+    fn overflow(&self, span: Span) -> (SourceType, ConstValue) {
+        let msg = ErrorMessage::ConstOverflow;
+        self.sa.diag.lock().report(self.file_id, span, msg);
+        (SourceType::Error, ConstValue::None)
     }
 }
+
+fn int_intrinsic(op: BinOp, ty: &SourceType) -> Option<Intrinsic> {
+    let intrinsic = match (ty, op) {
+        (SourceType::Int32, BinOp::Add) => Intrinsic::Int32Add,
+        (SourceType::Int32, BinOp::Sub) => Intrinsic::Int32Sub,
+        (SourceType::Int32, BinOp::Mul) => Intrinsic::Int32Mul,
+        (SourceType::Int32, BinOp::Div) => Intrinsic::Int32Div,
+        (SourceType::Int32, BinOp::Mod) => Intrinsic::Int32Mod,
+        (SourceType::Int32, BinOp::BitOr) => Intrinsic::Int32Or,
+        (SourceType::Int32, BinOp::BitAnd) => Intrinsic::Int32And,
+        (SourceType::Int32, BinOp::BitXor) => Intrinsic::Int32Xor,
+        (SourceType::Int32, BinOp::ShiftL) => Intrinsic::Int32Shl,
+        (SourceType::Int32, BinOp::ArithShiftR) => Intrinsic::Int32Sar,
+        (SourceType::Int32, BinOp::LogicalShiftR) => Intrinsic::Int32Shr,
+
+        (SourceType::Int64, BinOp::Add) => Intrinsic::Int64Add,
+        (SourceType::Int64, BinOp::Sub) => Intrinsic::Int64Sub,
+        (SourceType::Int64, BinOp::Mul) => Intrinsic::Int64Mul,
+        (SourceType::Int64, BinOp::Div) => Intrinsic::Int64Div,
+        (SourceType::Int64, BinOp::Mod) => Intrinsic::Int64Mod,
+        (SourceType::Int64, BinOp::BitOr) => Intrinsic::Int64Or,
+        (SourceType::Int64, BinOp::BitAnd) => Intrinsic::Int64And,
+        (SourceType::Int64, BinOp::BitXor) => Intrinsic::Int64Xor,
+        (SourceType::Int64, BinOp::ShiftL) => Intrinsic::Int64Shl,
+        (SourceType::Int64, BinOp::ArithShiftR) => Intrinsic::Int64Sar,
+        (SourceType::Int64, BinOp::LogicalShiftR) => Intrinsic::Int64Shr,
+
+        _ => return None,
+    };
+
+    Some(intrinsic)
+}
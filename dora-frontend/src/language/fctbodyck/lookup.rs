@@ -15,7 +15,7 @@ use dora_parser::Span;
 enum LookupKind {
     Method(SourceType),
     Static(SourceType),
-    Trait(TraitDefinitionId),
+    Trait(TraitDefinitionId, SourceTypeArray),
     Callee(FctDefinitionId),
 }
 
@@ -70,8 +70,8 @@ impl<'a> MethodLookup<'a> {
     }
 
     pub fn method(mut self, obj: SourceType) -> MethodLookup<'a> {
-        self.kind = if let SourceType::Trait(trait_id, _) = obj {
-            Some(LookupKind::Trait(trait_id))
+        self.kind = if let SourceType::Trait(trait_id, type_params) = obj {
+            Some(LookupKind::Trait(trait_id, type_params))
         } else {
             Some(LookupKind::Method(obj))
         };
@@ -126,7 +126,7 @@ impl<'a> MethodLookup<'a> {
                 self.find_method(obj.clone(), name, false)
             }
 
-            LookupKind::Trait(trait_id) => {
+            LookupKind::Trait(trait_id, _) => {
                 let name = self.name.expect("name not set");
                 self.find_method_in_trait(trait_id, name, false)
             }
@@ -162,7 +162,7 @@ impl<'a> MethodLookup<'a> {
                     }
                 }
 
-                LookupKind::Trait(trait_id) => {
+                LookupKind::Trait(trait_id, _) => {
                     let trait_ = &self.sa.traits[trait_id];
                     let trait_ = trait_.read();
                     let type_name = self.sa.interner.str(trait_.name).to_string();
@@ -188,7 +188,8 @@ impl<'a> MethodLookup<'a> {
             LookupKind::Method(_) | LookupKind::Static(_) => {
                 self.found_container_type_params.clone().unwrap()
             }
-            _ => SourceTypeArray::empty(),
+            LookupKind::Trait(_, ref trait_type_params) => trait_type_params.clone(),
+            LookupKind::Callee(_) => SourceTypeArray::empty(),
         };
 
         let fct_tps: SourceTypeArray = if let Some(fct_tps) = self.fct_tps {
@@ -1,6 +1,7 @@
 use crate::language::error::msg::ErrorMessage;
-use crate::language::sem_analysis::ConstValue;
+use crate::language::sem_analysis::{CallType, ConstValue, FctParent, VarId};
 use crate::language::tests::*;
+use crate::language::ty::{SourceType, SourceTypeArray};
 use dora_parser::Span;
 
 #[test]
@@ -262,6 +263,200 @@ fn type_if() {
     );
 }
 
+#[test]
+fn type_if_value() {
+    ok("fn f(c: Bool): Int32 { let x = if c { 1i32 } else { 2i32 }; x }");
+    ok("fn f(c: Bool): Int32 { if c { 1i32 } else { 2i32 } }");
+    ok("fn f(c: Bool): Int32 { let mut x = 0i32; x = if c { 1i32 } else { 2i32 }; x }");
+    ok("fn f(c: Bool): Int32 { return if c { 1i32 } else { 2i32 }; }");
+    ok("fn f(c: Bool): Int32 { let x = if c { return 1i32; } else { 2i32 }; x }");
+    ok("fn f(c: Bool) { let x = if c { g(); }; } fn g() {}");
+    ok("fn f(c: Bool) { if c { 1i32 }; }");
+
+    ok_with_test(
+        "fn f(c: Bool): Int32 { if c { 1i32 } else { 2i32 } }",
+        |sa| {
+            let fct_id = sa.fct_by_name("f").unwrap();
+            let fct = sa.fcts.idx(fct_id);
+            let fct = fct.read();
+            let expr = fct.ast.block().expr.as_ref().unwrap();
+            assert_eq!(SourceType::Int32, fct.analysis().ty(expr.id()));
+        },
+    );
+
+    err(
+        "fn f(c: Bool) { let x = if c { 1i32 } else { \"foo\" }; }",
+        (1, 25),
+        ErrorMessage::IfBranchTypesIncompatible("Int32".into(), "String".into()),
+    );
+    err(
+        "fn f(c: Bool) { let x = if c { 1i32 }; }",
+        (1, 25),
+        ErrorMessage::IfWithoutElse("Int32".into()),
+    );
+    err(
+        "fn f(c: Bool): Int32 { return if c { 1i32 }; }",
+        (1, 31),
+        ErrorMessage::IfWithoutElse("Int32".into()),
+    );
+    err(
+        "fn f(c: Bool) { let mut x = 0i32; x = if c { 1i32 }; }",
+        (1, 39),
+        ErrorMessage::IfWithoutElse("Int32".into()),
+    );
+}
+
+#[test]
+fn type_labeled_break_and_continue() {
+    ok("fn f() { 'outer: while true { while true { break 'outer; } } }");
+    ok("fn f(x: Array[Int32]) { 'outer: for i in x { while true { continue 'outer; } } }");
+    err(
+        "fn f() { 'outer: while true { break 'inner; } }",
+        (1, 31),
+        ErrorMessage::UnknownLabel("inner".into()),
+    );
+    err(
+        "fn f() { while true { continue 'outer; } }",
+        (1, 23),
+        ErrorMessage::UnknownLabel("outer".into()),
+    );
+    err(
+        "fn f() { 'outer: while true { let x = || { break 'outer; }; } }",
+        (1, 44),
+        ErrorMessage::OutsideLoop,
+    );
+}
+
+#[test]
+fn type_loop() {
+    ok("fn f() { loop { break; } }");
+    ok("fn f(): Int32 { loop { } }");
+    ok("fn f(): Int32 { 'outer: loop { loop { break; } } }");
+    err(
+        "fn f(): Int32 { loop { break; } }",
+        (1, 15),
+        ErrorMessage::ReturnType("Int32".into(), "()".into()),
+    );
+    err(
+        "fn f(): Int32 { 'outer: loop { loop { break 'outer; } } }",
+        (1, 15),
+        ErrorMessage::ReturnType("Int32".into(), "()".into()),
+    );
+
+    ok_with_test("fn f() { loop { } }", |sa| {
+        let fct_id = sa.fct_by_name("f").unwrap();
+        let fct = sa.fcts.idx(fct_id);
+        let fct = fct.read();
+        let stmt = &fct.ast.block().stmts[0];
+        assert!(stmt.is_loop());
+        assert_eq!(SourceType::Never, fct.analysis().ty(stmt.id()));
+    });
+
+    ok_with_test("fn f() { loop { break; } }", |sa| {
+        let fct_id = sa.fct_by_name("f").unwrap();
+        let fct = sa.fcts.idx(fct_id);
+        let fct = fct.read();
+        let stmt = &fct.ast.block().stmts[0];
+        assert_eq!(SourceType::Unit, fct.analysis().ty(stmt.id()));
+    });
+}
+
+#[test]
+fn type_do_while() {
+    ok("fn f() { do { } while true; }");
+    ok("fn f() { let mut i = 0i32; do { i = i + 1i32; } while i < 10i32; }");
+    ok("fn f() { do { break; } while false; }");
+    ok("fn f() { do { continue; } while false; }");
+    err(
+        "fn f() { do { } while 2i32; }",
+        (1, 10),
+        ErrorMessage::WhileCondType("Int32".into()),
+    );
+    err(
+        "fn f() { do { let x = true; } while x; }",
+        (1, 37),
+        ErrorMessage::UnknownIdentifier("x".into()),
+    );
+    err(
+        "fn f(): Int32 { do { return 1i32; } while true; }",
+        (1, 15),
+        ErrorMessage::ReturnType("Int32".into(), "()".into()),
+    );
+}
+
+#[test]
+fn type_nested_fct() {
+    ok("fn f(): Int32 { fn g(a: Int32): Int32 { a + 1i32 } g(1i32) }");
+    ok("fn f(): Int32 { let x = g(1i32); fn g(a: Int32): Int32 { a } x }");
+    ok(
+        "fn f(): Int64 { fn even(n: Int64): Bool { if n == 0 { true } else { odd(n - 1) } }
+        fn odd(n: Int64): Bool { if n == 0 { false } else { even(n - 1) } }
+        if even(10) { 1 } else { 0 } }",
+    );
+    ok("fn f(): Int32 { fn g(): Int32 { 1i32 } { fn g(): Int32 { 2i32 } g() } }");
+    ok("fn f(): Int32 { fn g(): Int32 { 1i32 } { fn h(): Int32 { g() } h() } }");
+    ok("fn f[T](x: T): T { fn g(y: T): T { y } g(x) }");
+
+    err(
+        "fn f() { { fn g() {} } g(); }",
+        (1, 24),
+        ErrorMessage::UnknownIdentifier("g".into()),
+    );
+    err(
+        "fn f(): Int32 { let x = 1i32; fn g(): Int32 { x } g() }",
+        (1, 47),
+        ErrorMessage::UnknownIdentifier("x".into()),
+    );
+    err(
+        "fn f() { let g = 1i32; fn g() {} }",
+        (1, 14),
+        ErrorMessage::ShadowFunction("g".into()),
+    );
+    err(
+        "fn f() { fn g() {} fn g() {} }",
+        (1, 20),
+        ErrorMessage::ShadowFunction("g".into()),
+    );
+    err(
+        "fn f() { fn g[T]() {} }",
+        (1, 10),
+        ErrorMessage::NestedFctTypeParams,
+    );
+    err(
+        "fn f() { fn g(): Int32 { true } }",
+        (1, 24),
+        ErrorMessage::ReturnType("Int32".into(), "Bool".into()),
+    );
+}
+
+#[test]
+fn type_throw() {
+    ok("class Foo fn f() { throw Foo(); }");
+    ok("class Foo fn f(): Int32 { throw Foo(); }");
+    err(
+        "fn f() { throw 1i32; }",
+        (1, 10),
+        ErrorMessage::ThrowExpectsClass("Int32".into()),
+    );
+}
+
+#[test]
+fn type_try_catch() {
+    ok("class Foo fn f(): Int32 { try { 1i32 } catch e: Foo { 2i32 } }");
+    ok("class Foo fn f(): Foo { try { Foo() } catch e: Foo { e } }");
+    ok("class Foo fn f(): Int32 { try { 1i32 } catch e: Foo { throw e; } }");
+    err(
+        "fn f() { try { } catch e: Int32 { } }",
+        (1, 27),
+        ErrorMessage::CatchExpectsClass("Int32".into()),
+    );
+    err(
+        "class Foo fn f() { try { 1i32 } catch e: Foo { \"a\" }; }",
+        (1, 20),
+        ErrorMessage::TryCatchTypesIncompatible("Int32".into(), "String".into()),
+    );
+}
+
 #[test]
 fn type_return_unit() {
     ok("fn f() { return; }");
@@ -332,6 +527,50 @@ fn type_let() {
     ok("fn f(value: (Int32, (Int32, Int32))): Int32 { let (a, (b, c)) = value; a+b+c }");
 }
 
+#[test]
+fn type_let_tuple_inference() {
+    ok_with_test(
+        "fn f() { let (a, (b, c)) = (1i32, (true, \"x\")); let (d, _) = (a, b); }",
+        |sa| {
+            let fct_id = sa.fct_by_name("f").unwrap();
+            let fct = sa.fcts.idx(fct_id);
+            let fct = fct.read();
+            let vars = &fct.analysis().vars;
+            let types = (0..4)
+                .map(|idx| vars.get_var(VarId(idx)).ty.clone())
+                .collect::<Vec<_>>();
+            let string = SourceType::Class(sa.known.classes.string(), SourceTypeArray::empty());
+            assert_eq!(
+                types,
+                vec![
+                    SourceType::Int32,
+                    SourceType::Bool,
+                    string,
+                    SourceType::Int32
+                ]
+            );
+        },
+    );
+
+    ok("fn f(): Int32 { let (a, b): (Int32, Bool) = (1, true); a }");
+    ok("fn f(): UInt8 { let (a, (b, c)): (Int32, (Int64, UInt8)) = (1, (2, 3)); c }");
+
+    err(
+        "fn f() { let (a, b): (Int32, Bool) = (1, 2); }",
+        (1, 10),
+        ErrorMessage::AssignType(
+            "(a, b)".into(),
+            "(Int32, Bool)".into(),
+            "(Int32, Int64)".into(),
+        ),
+    );
+    err(
+        "fn f() { let (a, (b, c)) = (1, (2, 3, 4)); }",
+        (1, 18),
+        ErrorMessage::LetPatternExpectedTupleWithLength("(Int64, Int64, Int64)".into(), 3, 2),
+    );
+}
+
 #[test]
 fn type_assign_lvalue() {
     err("fn f() { 1 = 3; }", (1, 10), ErrorMessage::LvalueExpected);
@@ -395,12 +634,12 @@ fn type_bin_op() {
     err(
         "fn f(a: String) { a-a; }",
         (1, 19),
-        ErrorMessage::BinOpType("-".into(), "String".into(), "String".into()),
+        ErrorMessage::BinOpTraitNotImplemented("-".into(), "String".into(), "Sub".into()),
     );
     err(
         "fn f(a: String) { a*a; }",
         (1, 19),
-        ErrorMessage::BinOpType("*".into(), "String".into(), "String".into()),
+        ErrorMessage::BinOpTraitNotImplemented("*".into(), "String".into(), "Mul".into()),
     );
     err(
         "fn f(a: String) { a%a; }",
@@ -750,6 +989,28 @@ fn test_literal_bin_int_overflow() {
     ok("fn f() { let x: Int32 = 0b11111111_11111111_11111111_11111111i32; }");
 }
 
+#[test]
+fn test_literal_uint8_overflow() {
+    err(
+        "fn f() { let x = 256u8; }",
+        (1, 18),
+        ErrorMessage::NumberOverflow("UInt8".into()),
+    );
+    ok("fn f() { let x = 255u8; }");
+    err(
+        "fn f() { let x = 0x100u8; }",
+        (1, 18),
+        ErrorMessage::NumberOverflow("UInt8".into()),
+    );
+    ok("fn f() { let x = 0xFFu8; }");
+    err(
+        "fn f() { let x = 0b1_0000_0000u8; }",
+        (1, 18),
+        ErrorMessage::NumberOverflow("UInt8".into()),
+    );
+    ok("fn f() { let x = 0b1111_1111u8; }");
+}
+
 #[test]
 fn test_literal_int64_overflow() {
     err(
@@ -988,6 +1249,105 @@ fn test_const_values() {
     );
 }
 
+#[test]
+fn test_const_expressions() {
+    ok_with_test(
+        "  const max: Int32 = 1 << 10;
+                        const mask: Int64 = (1 << 40) - 1;
+                        const half: Int32 = max / 2 + -3;
+                        const big: Bool = max > 1000 && !(half == 0);
+                        const inverted: Int32 = !0;",
+        |sa| {
+            let value = |name: &'static str| {
+                let id = sa.const_by_name(name);
+                let const_ = sa.consts.idx(id);
+                let value = const_.read().value.clone();
+                value
+            };
+
+            assert_eq!(ConstValue::Int(1024), value("max"));
+            assert_eq!(ConstValue::Int((1 << 40) - 1), value("mask"));
+            assert_eq!(ConstValue::Int(509), value("half"));
+            assert_eq!(ConstValue::Bool(true), value("big"));
+            assert_eq!(ConstValue::Int(-1), value("inverted"));
+        },
+    );
+
+    err(
+        "const x: Int32 = 2147483647 + 1;",
+        (1, 18),
+        ErrorMessage::ConstOverflow,
+    );
+
+    err(
+        "const x: Int32 = 1 / 0;",
+        (1, 18),
+        ErrorMessage::ConstOverflow,
+    );
+
+    err(
+        "fn f(): Int32 { 1i32 } const x: Int32 = f();",
+        (1, 41),
+        ErrorMessage::ConstValueExpected,
+    );
+
+    err(
+        "const x: Int32 = 1i32 + 2i64;",
+        (1, 18),
+        ErrorMessage::BinOpType("+".into(), "Int32".into(), "Int64".into()),
+    );
+}
+
+#[test]
+fn test_const_referencing_const() {
+    ok_with_test(
+        "  const size: Int32 = count * 4;
+                        const count: Int32 = base + 1;
+                        const base: Int32 = 7;
+                        mod foo { pub const limit: Int64 = 100; }
+                        const scaled: Int64 = foo::limit * 3;",
+        |sa| {
+            let value = |name: &'static str| {
+                let id = sa.const_by_name(name);
+                let const_ = sa.consts.idx(id);
+                let value = const_.read().value.clone();
+                value
+            };
+
+            assert_eq!(ConstValue::Int(32), value("size"));
+            assert_eq!(ConstValue::Int(8), value("count"));
+            assert_eq!(ConstValue::Int(300), value("scaled"));
+        },
+    );
+
+    err(
+        "const a: Int32 = 1; const b: Int64 = a;",
+        (1, 38),
+        ErrorMessage::AssignType("b".into(), "Int64".into(), "Int32".into()),
+    );
+
+    err(
+        "mod foo { const limit: Int64 = 100; } const x: Int64 = foo::limit;",
+        (1, 56),
+        ErrorMessage::NotAccessible("foo::limit".into()),
+    );
+}
+
+#[test]
+fn test_const_cycle() {
+    err(
+        "const a: Int32 = b + 1; const b: Int32 = a;",
+        (1, 42),
+        ErrorMessage::ConstCycle("a".into()),
+    );
+
+    err(
+        "const a: Int32 = a * 2;",
+        (1, 18),
+        ErrorMessage::ConstCycle("a".into()),
+    );
+}
+
 #[test]
 fn test_assignment_to_const() {
     err(
@@ -1040,78 +1400,299 @@ fn test_operator_on_generic_type() {
     err(
         "fn f[T](a: T, b: T) { a + b; }",
         (1, 23),
-        ErrorMessage::BinOpType("+".into(), "T".into(), "T".into()),
+        ErrorMessage::BinOpTraitNotImplemented("+".into(), "T".into(), "Add".into()),
     );
-}
-
-#[test]
-fn test_find_class_method_precedence() {
-    // finding class method should have precedence over
-    // trait methods
-    ok("class A
-            impl A { fn foo() {} }
-            trait Foo { fn foo(); }
-            impl Foo for A { fn foo() {} }
-            fn test(a: A) { a.foo(); }");
-
+    ok("fn f[T: std::Add](a: T, b: T): T { a + b }");
     err(
-        "class A
-            impl A { fn foo() {} }
-            trait Foo { fn foo(a: Int32); }
-            impl Foo for A { fn foo(a: Int32) {} }
-            fn test(a: A) { a.foo(1i32); }",
-        (5, 29),
-        ErrorMessage::ParamTypesIncompatible("foo".into(), Vec::new(), vec!["Int32".into()]),
+        "fn f[T: std::Add](a: T, b: Int32) { a + b; }",
+        (1, 37),
+        ErrorMessage::BinOpType("+".into(), "T".into(), "Int32".into()),
     );
-
-    ok("class A
-            impl A { @static fn foo() {} }
-            trait Foo { fn foo(a: Int32); }
-            impl Foo for A { fn foo(a:  Int32) {} }
-            fn test(a: A) { a.foo(1i32); }");
 }
 
 #[test]
-fn test_global_get() {
-    ok("let mut x: Int32 = 0i32; fn foo(): Int32 { return x; }");
-}
+fn test_operator_trait() {
+    ok_with_test(
+        "
+        struct Vec2(x: Int32, y: Int32)
+        impl std::Add for Vec2 {
+            fn plus(other: Vec2): Vec2 { Vec2(self.x + other.x, self.y + other.y) }
+        }
+        fn f(a: Vec2, b: Vec2): Vec2 { a + b }
+    ",
+        |sa| {
+            let fct_id = sa.fct_by_name("f").expect("missing f");
+            let fct = sa.fcts.idx(fct_id);
+            let fct = fct.read();
+            let analysis = fct.analysis();
+            let plus_calls = analysis
+                .map_calls
+                .iter()
+                .filter(|(_, call_type)| match call_type.as_ref() {
+                    CallType::Method(_, fct_id, _) => {
+                        let callee = sa.fcts.idx(*fct_id);
+                        let callee = callee.read();
+                        matches!(callee.parent, FctParent::Impl(_))
+                            && sa.interner.str(callee.name).as_str() == "plus"
+                    }
+                    _ => false,
+                })
+                .count();
+            assert_eq!(plus_calls, 1);
+        },
+    );
 
-#[test]
-fn test_global_set() {
-    ok("let mut x: Int32 = 0i32; fn foo(a: Int32) { x = a; }");
     err(
-        "let x: Int32 = 0i32; fn foo(a: Int32) { x = a; }",
-        (1, 41),
-        ErrorMessage::LetReassigned,
+        "
+        struct Vec2(x: Int32, y: Int32)
+        fn f(a: Vec2, b: Vec2) { a - b; }
+    ",
+        (3, 34),
+        ErrorMessage::BinOpTraitNotImplemented("-".into(), "Vec2".into(), "Sub".into()),
     );
-}
-
-#[test]
-fn lambda_assignment() {
-    ok("fn f() { let x = || {}; }");
-    ok("fn f() { let x = ||: Int32 { return 2i32; }; }");
-    ok("fn f() { let x: (): () = || {}; }");
-    ok("fn f() { let x: (): () = ||: () {}; }");
-    ok("fn f() { let x: (): Int32 = ||: Int32 { return 2i32; }; }");
     err(
-        "fn f() { let x: (): Int32 = || {}; }",
-        (1, 10),
-        ErrorMessage::AssignType("x".into(), "() -> Int32".into(), "() -> ()".into()),
+        "
+        class Foo
+        impl std::Mul for Foo { fn times(other: Foo): Foo { self } }
+        fn f(a: Foo) { a / a; }
+    ",
+        (4, 24),
+        ErrorMessage::BinOpTraitNotImplemented("/".into(), "Foo".into(), "Div".into()),
     );
+    ok("
+        class Foo
+        impl std::Mul for Foo { fn times(other: Foo): Foo { self } }
+        fn f(a: Foo): Foo { a * a }
+    ");
 }
 
 #[test]
-fn method_call_with_multiple_matching_traits() {
-    err(
-        "class A
-            trait X { fn f(); }
-            trait Y { fn f(); }
+fn test_index_trait() {
+    ok_with_test(
+        "
+        class Grid(cells: Array[Int32])
+        impl std::Index[Int64, Int32] for Grid {
+            fn get(index: Int64): Int32 { self.cells(index) }
+        }
+        impl std::IndexMut[Int64, Int32] for Grid {
+            fn set(index: Int64, value: Int32) { self.cells(index) = value; }
+        }
+        fn f(g: Grid): Int32 { g[1] = 2i32; g[0] += 1i32; g[0] }
+    ",
+        |sa| {
+            let fct_id = sa.fct_by_name("f").expect("missing f");
+            let fct = sa.fcts.idx(fct_id);
+            let fct = fct.read();
+            let mut names = fct
+                .analysis()
+                .map_calls
+                .iter()
+                .filter_map(|(_, call_type)| match call_type.as_ref() {
+                    CallType::Expr(_, fct_id, _) => {
+                        let callee = sa.fcts.idx(*fct_id);
+                        let callee = callee.read();
+                        assert!(matches!(callee.parent, FctParent::Impl(_)));
+                        Some(sa.interner.str(callee.name).to_string())
+                    }
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            names.sort();
+            assert_eq!(names, vec!["get", "get", "set", "set"]);
+        },
+    );
 
-            impl X for A { fn f() {} }
-            impl Y for A { fn f() {} }
+    ok("fn f(a: Array[Int32]): Int32 { a[0] = 1i32; a[1] *= 2i32; a[0] }");
 
-            fn g(a: A) { a.f(); }",
-        (8, 26),
+    err(
+        "
+        class Foo
+        fn f(a: Foo): Int32 { a[0] }
+    ",
+        (3, 31),
+        ErrorMessage::IndexTraitNotImplemented("Foo".into(), "Index".into()),
+    );
+    err(
+        "
+        class Foo
+        impl std::Index[Int64, Int32] for Foo { fn get(index: Int64): Int32 { 0i32 } }
+        fn f(a: Foo) { a[0] = 1i32; }
+    ",
+        (4, 24),
+        ErrorMessage::IndexTraitNotImplemented("Foo".into(), "IndexMut".into()),
+    );
+    err(
+        "
+        class Foo
+        impl std::Index[Int64, Int32] for Foo { fn get(index: Int64): Int32 { 0i32 } }
+        fn f(a: Foo): Int32 { a[true] }
+    ",
+        (4, 31),
+        ErrorMessage::UnknownMethod("Foo".into(), "get".into(), vec!["Bool".into()]),
+    );
+}
+
+#[test]
+fn test_associated_const() {
+    ok_with_test(
+        "
+        trait Bounded { const MAX: Int32; }
+        class Percent
+        impl Bounded for Percent { const MAX: Int32 = 100i32; }
+        struct Byte(value: UInt8)
+        impl Bounded for Byte { const MAX: Int32 = 255i32; }
+        fn f[T: Bounded](): Int32 { T::MAX }
+    ",
+        |sa| {
+            let fct_id = sa.fct_by_name("f").expect("missing f");
+            let fct = sa.fcts.idx(fct_id);
+            let fct = fct.read();
+            let trait_const_id = fct
+                .analysis()
+                .map_calls
+                .iter()
+                .find_map(|(_, call_type)| match call_type.as_ref() {
+                    CallType::GenericStaticMethod(_, _, fct_id) => Some(*fct_id),
+                    _ => None,
+                })
+                .expect("missing T::MAX");
+            assert!(sa.fcts.idx(trait_const_id).read().is_associated_const);
+
+            let mut values = sa
+                .impls
+                .iter()
+                .filter_map(|impl_| {
+                    let impl_ = impl_.read();
+                    let const_id = *impl_.impl_for.get(&trait_const_id)?;
+                    let const_ = sa.fcts.idx(const_id);
+                    let const_ = const_.read();
+                    let value = const_.ast.block().expr.as_ref()?.to_lit_int()?.value;
+                    Some((impl_.extended_ty.name(sa), value))
+                })
+                .collect::<Vec<_>>();
+            values.sort();
+            assert_eq!(
+                values,
+                vec![("Byte".to_string(), 255), ("Percent".to_string(), 100)]
+            );
+        },
+    );
+
+    ok("
+        trait Bounded { const MIN: Int32 = 0i32; const MAX: Int32; }
+        class Percent
+        impl Bounded for Percent { const MAX: Int32 = 100i32; }
+        fn f[T: Bounded](): Int32 { T::MAX - T::MIN }
+        fn g(): Int32 { f[Percent]() }
+    ");
+
+    err(
+        "
+        trait Bounded { const MAX: Int32; }
+        fn f[T: Bounded](): Int32 { T::MIN }
+    ",
+        (3, 37),
+        ErrorMessage::InvalidLeftSideOfSeparator,
+    );
+
+    err(
+        "
+        trait Bounded { const MAX: Int32; }
+        fn f[T: Bounded](): Int32 { T::MAX() }
+    ",
+        (3, 37),
+        ErrorMessage::UnknownStaticMethodWithTypeParam,
+    );
+
+    err(
+        "
+        trait A { const MAX: Int32; }
+        trait B { const MAX: Int32; }
+        fn f[T: A + B](): Int32 { T::MAX }
+    ",
+        (4, 35),
+        ErrorMessage::MultipleCandidatesForAssociatedConst("MAX".into()),
+    );
+
+    err(
+        "
+        trait Bounded { const MAX: Int32; }
+        class Percent
+        impl Bounded for Percent { const MAX: Int32 = \"100\"; }
+    ",
+        (4, 55),
+        ErrorMessage::AssignType("MAX".into(), "Int32".into(), "String".into()),
+    );
+}
+
+#[test]
+fn test_find_class_method_precedence() {
+    // finding class method should have precedence over
+    // trait methods
+    ok("class A
+            impl A { fn foo() {} }
+            trait Foo { fn foo(); }
+            impl Foo for A { fn foo() {} }
+            fn test(a: A) { a.foo(); }");
+
+    err(
+        "class A
+            impl A { fn foo() {} }
+            trait Foo { fn foo(a: Int32); }
+            impl Foo for A { fn foo(a: Int32) {} }
+            fn test(a: A) { a.foo(1i32); }",
+        (5, 29),
+        ErrorMessage::ParamTypesIncompatible("foo".into(), Vec::new(), vec!["Int32".into()]),
+    );
+
+    ok("class A
+            impl A { @static fn foo() {} }
+            trait Foo { fn foo(a: Int32); }
+            impl Foo for A { fn foo(a:  Int32) {} }
+            fn test(a: A) { a.foo(1i32); }");
+}
+
+#[test]
+fn test_global_get() {
+    ok("let mut x: Int32 = 0i32; fn foo(): Int32 { return x; }");
+}
+
+#[test]
+fn test_global_set() {
+    ok("let mut x: Int32 = 0i32; fn foo(a: Int32) { x = a; }");
+    err(
+        "let x: Int32 = 0i32; fn foo(a: Int32) { x = a; }",
+        (1, 41),
+        ErrorMessage::LetReassigned,
+    );
+}
+
+#[test]
+fn lambda_assignment() {
+    ok("fn f() { let x = || {}; }");
+    ok("fn f() { let x = ||: Int32 { return 2i32; }; }");
+    ok("fn f() { let x: (): () = || {}; }");
+    ok("fn f() { let x: (): () = ||: () {}; }");
+    ok("fn f() { let x: (): Int32 = ||: Int32 { return 2i32; }; }");
+    err(
+        "fn f() { let x: (): Int32 = || {}; }",
+        (1, 10),
+        ErrorMessage::AssignType("x".into(), "() -> Int32".into(), "() -> ()".into()),
+    );
+}
+
+#[test]
+fn method_call_with_multiple_matching_traits() {
+    err(
+        "class A
+            trait X { fn f(); }
+            trait Y { fn f(); }
+
+            impl X for A { fn f() {} }
+            impl Y for A { fn f() {} }
+
+            fn g(a: A) { a.f(); }",
+        (8, 26),
         ErrorMessage::MultipleCandidatesForMethod("A".into(), "f".into(), Vec::new()),
     );
 }
@@ -1147,6 +1728,28 @@ fn test_generic_argument_with_trait_bound() {
     );
 }
 
+#[test]
+fn test_generic_argument_with_where_bound() {
+    ok("
+        trait Shape { fn area(): Int32; }
+        class Square(side: Int32)
+        impl Shape for Square { fn area(): Int32 { self.side * self.side } }
+        fn total[T](a: T, b: T): Int32 where T: Shape { a.area() + b.area() }
+        fn g(): Int32 { total[Square](Square(1i32), Square(2i32)) }
+    ");
+
+    err(
+        "
+        trait Shape { fn area(): Int32; }
+        class Circle
+        fn total[T](a: T, b: T): Int32 where T: Shape { a.area() + b.area() }
+        fn g(c: Circle): Int32 { total[Circle](c, c) }
+    ",
+        (5, 34),
+        ErrorMessage::TypeNotImplementingTrait("Circle".into(), "Shape".into()),
+    );
+}
+
 #[test]
 fn test_for_supports_make_iterator() {
     err(
@@ -1174,6 +1777,79 @@ fn test_for_supports_make_iterator() {
             fn f(): Int32 { for i in Foo() { return i; } return 0i32; }");
 }
 
+#[test]
+fn test_for_user_defined_iterator() {
+    ok("
+        class Range(start: Int32, end: Int32)
+        impl Range { fn makeIterator(): RangeIter { RangeIter(self.start, self.end, 0i64) } }
+        class RangeIter(cur: Int32, end: Int32, idx: Int64)
+        impl RangeIter {
+            fn next(): Option[(Int64, Int32)] { None[(Int64, Int32)] }
+        }
+        fn f(): Int32 {
+            for (idx, value) in Range(0i32, 10i32) { return idx.toInt32() + value; }
+            0i32
+        }");
+
+    err(
+        "
+        class Range(start: Int32, end: Int32)
+        fn f() { for i in Range(0i32, 10i32) {} }",
+        (3, 27),
+        ErrorMessage::TypeNotUsableInForIn("Range".into()),
+    );
+}
+
+#[test]
+fn test_for_type_param_iterator() {
+    ok("
+        trait Counter { fn next(): Option[Int32]; }
+        fn f[T: Counter](it: T): Int32 { for i in it { return i; } 0i32 }");
+
+    ok("
+        class Iter
+        impl Iter { fn next(): Option[Int32] { None[Int32] } }
+        trait Source { fn makeIterator(): Iter; }
+        fn f[T: Source](source: T): Int32 { for i in source { return i; } 0i32 }");
+
+    err(
+        "
+        trait Counter { fn count(): Int32; }
+        fn f[T: Counter](it: T) { for i in it {} }",
+        (3, 44),
+        ErrorMessage::TypeNotUsableInForIn("T".into()),
+    );
+}
+
+#[test]
+fn test_for_generic_trait_bound_iterator() {
+    ok("
+        trait Gen[X] { fn next(): Option[X]; }
+        fn f[T: Gen[Int32]](it: T): Int32 {
+            let mut sum = 0i32;
+            for i in it { sum = sum + i; }
+            sum
+        }");
+
+    ok("
+        trait Gen[X] { fn next(): Option[X]; }
+        trait Source[I] { fn makeIterator(): I; }
+        class Iter
+        impl Gen[String] for Iter { fn next(): Option[String] { None[String] } }
+        fn f[T: Source[Iter]](source: T): String {
+            for s in source { return s; }
+            \"\"
+        }");
+
+    err(
+        "
+        trait Gen[X] { fn next(): Option[X]; }
+        fn f[T: Gen[Int32]](it: T) { for i in it { let s: String = i; } }",
+        (3, 52),
+        ErrorMessage::AssignType("s".into(), "String".into(), "Int32".into()),
+    );
+}
+
 #[test]
 fn test_ctor_with_type_param() {
     err(
@@ -1427,6 +2103,30 @@ fn test_template() {
     ok("fn f[T: std::Stringable](x: T): String { return \"${x}\"; }");
 }
 
+#[test]
+fn test_template_user_type() {
+    ok("
+        class Foo
+        impl std::Stringable for Foo {
+            fn toString(): String { \"Foo\" }
+        }
+        fn f(x: Foo, y: Int32): String { \"${x}: ${y}\" }
+    ");
+
+    // A `toString` method alone is not enough, the type needs to implement Stringable.
+    err(
+        "
+            class Foo
+            impl Foo {
+                fn toString(): String { \"Foo\" }
+            }
+            fn f(x: Foo): String { \"x = ${x}\" }
+        ",
+        (6, 43),
+        ErrorMessage::ExpectedStringable("Foo".into()),
+    );
+}
+
 #[test]
 fn test_trait_object_as_argument() {
     ok("trait Foo { fn bar(): Int32; }
@@ -1762,6 +2462,27 @@ fn test_enum_with_static_method() {
     );
 }
 
+#[test]
+fn test_enum_values_and_ordinal() {
+    ok("enum A { V1, V2 } fn f(): Array[A] { A::values() }");
+    ok("enum A { V1, V2 } fn f(a: A): Int32 { a.ordinal() }");
+    ok("enum A { V1(Int32), V2 } fn f(a: A): Int32 { a.ordinal() }");
+
+    ok("
+        enum A { V1(Int32), V2 }
+        impl A {
+            @static fn values(): Int32 { 1 }
+        }
+        fn f(): Int32 { A::values() }
+        ");
+
+    err(
+        "enum A { V1(Int32), V2 } fn f(): Array[A] { A::values() }",
+        (1, 45),
+        ErrorMessage::EnumValuesWithPayload("A".into()),
+    );
+}
+
 #[test]
 fn test_enum() {
     ok("enum A { V1, V2 }");
@@ -1858,135 +2579,515 @@ fn test_enum_match_with_parens() {
     );
 }
 
-#[test]
-fn test_enum_match_wrong_number_params() {
-    err(
+#[test]
+fn test_enum_match_wrong_number_params() {
+    err(
+        "
+        enum A { V1(Int32), V2 }
+        fn f(x: A): Int32 {
+            match x {
+                A::V1 => 0i32,
+                A::V2 => 1i32
+            }
+        }
+    ",
+        (5, 17),
+        ErrorMessage::MatchPatternWrongNumberOfParams(0, 1),
+    );
+
+    err(
+        "
+        enum A { V1(Int32, Float32, Bool), V2 }
+        fn f(x: A): Int32 {
+            match x {
+                A::V1(a, b, c, d) => 0i32,
+                A::V2 => 1i32
+            }
+        }
+    ",
+        (5, 17),
+        ErrorMessage::MatchPatternWrongNumberOfParams(4, 3),
+    );
+}
+
+#[test]
+fn test_enum_match_params() {
+    ok("
+        enum A { V1(Int32, Int32, Int32), V2 }
+        fn f(x: A): Int32 {
+            match x {
+                A::V1(a, _, c) => a + c,
+                A::V2 => 1i32
+            }
+        }
+    ");
+
+    err(
+        "
+        enum A { V1(Int32, Int32, Int32), V2 }
+        fn f(x: A): Int32 {
+            match x {
+                A::V1(a, _, c) => a + c,
+                A::V2 => a
+            }
+        }
+    ",
+        (6, 26),
+        ErrorMessage::UnknownIdentifier("a".into()),
+    );
+
+    err(
+        "
+        enum A { V1(Int32, Int32), V2 }
+        fn f(x: A): Int32 {
+            match x {
+                A::V1(a, a) => a + a,
+                A::V2 => 1i32
+            }
+        }
+    ",
+        (5, 26),
+        ErrorMessage::VarAlreadyInPattern,
+    );
+}
+
+#[test]
+fn test_enum_match_missing_variants() {
+    err(
+        "
+        enum A { V1(Int32, Int32, Int32), V2, V3 }
+        fn f(x: A): Int32 {
+            match x {
+                A::V1(a, _, c) => a + c,
+                A::V2 => 1i32,
+            }
+        }
+    ",
+        (4, 13),
+        ErrorMessage::MatchUncoveredVariant(vec!["V3".into()]),
+    );
+
+    err(
+        "
+        enum A { V1(Int32, Int32, Int32), V2, V3 }
+        fn f(x: A): Int32 {
+            match x {
+                A::V1(a, _, c) => a + c,
+                A::V2 => 1i32,
+                A::V3 => 2i32,
+                A::V2 => 4i32,
+            }
+        }
+    ",
+        (8, 17),
+        ErrorMessage::MatchUnreachablePattern,
+    );
+}
+
+#[test]
+fn test_enum_match_guard() {
+    ok("
+        enum A { V1(Int32), V2 }
+        fn f(x: A): Int32 {
+            match x {
+                A::V1(a) if a > 0i32 => a,
+                A::V1(_) => 0i32,
+                A::V2 => 1i32,
+            }
+        }
+    ");
+
+    err(
+        "
+        enum A { V1(Int32), V2 }
+        fn f(x: A): Int32 {
+            match x {
+                A::V1(a) if a > 0i32 => a,
+                A::V2 => 1i32,
+            }
+        }
+    ",
+        (4, 13),
+        ErrorMessage::MatchUncoveredVariant(vec!["V1".into()]),
+    );
+
+    err(
+        "
+        enum A { V1(Int32), V2 }
+        fn f(x: A): Int32 {
+            match x {
+                A::V1(a) if a => a,
+                _ => 1i32,
+            }
+        }
+    ",
+        (5, 29),
+        ErrorMessage::MatchGuardType("Int32".into()),
+    );
+}
+
+#[test]
+fn test_enum_match_nested_pattern() {
+    ok("
+        enum A { V1(Int32), V2 }
+        enum B { W1(A, Int32), W2 }
+        fn f(x: B): Int32 {
+            match x {
+                B::W1(A::V1(a), b) => a + b,
+                B::W1(A::V2, b) => b,
+                B::W1(_, _) => 0i32,
+                B::W2 => 1i32,
+            }
+        }
+    ");
+
+    err(
+        "
+        enum A { V1(Int32), V2 }
+        enum B { W1(A), W2 }
+        fn f(x: B): Int32 {
+            match x {
+                B::W1(A::V1(a)) => a,
+                B::W2 => 1i32,
+            }
+        }
+    ",
+        (5, 13),
+        ErrorMessage::MatchUncoveredVariant(vec!["W1".into()]),
+    );
+
+    err(
+        "
+        enum A { V1(Int32), V2 }
+        enum B { W1(Int32), W2 }
+        fn f(x: B): Int32 {
+            match x {
+                B::W1(A::V1(_)) => 0i32,
+                _ => 1i32,
+            }
+        }
+    ",
+        (6, 23),
+        ErrorMessage::EnumVariantExpected,
+    );
+
+    err(
+        "
+        enum A { V1(Int32), V2 }
+        enum B { W1(A, Int32), W2 }
+        fn f(x: B): Int32 {
+            match x {
+                B::W1(A::V1(a), a) => a,
+                _ => 1i32,
+            }
+        }
+    ",
+        (6, 33),
+        ErrorMessage::VarAlreadyInPattern,
+    );
+}
+
+#[test]
+fn test_enum_match_unsupported_pattern() {
+    err(
+        "
+        enum A { V1, V2 }
+        fn f(x: A): Int32 {
+            match x {
+                1 => 0i32,
+                _ => 1i32,
+            }
+        }
+    ",
+        (5, 17),
+        ErrorMessage::MatchPatternUnsupported,
+    );
+}
+
+#[test]
+fn test_match_lit() {
+    ok("fn f(x: Int64): Int64 { match x { 0 => 1, 1..=3 => 2, _ => 3 } }");
+    ok("fn f(x: Char): Int64 { match x { 'a'..='z' => 1, '_' => 2, _ => 3 } }");
+    ok("fn f(x: String): Int64 { match x { \"a\" => 1, \"b\" => 2, _ => 3 } }");
+    ok("fn f(x: UInt8): Int64 { match x { 1 => 1, _ if x > 2u8 => 2, _ => 3 } }");
+
+    err(
+        "fn f(x: Int64): Int64 { match x { 0 => 1, 1 => 2 } }",
+        (1, 25),
+        ErrorMessage::MatchMissingWildcard("Int64".into()),
+    );
+
+    err(
+        "fn f(x: Int64): Int64 { match x { 0 => 1, _ if x > 1 => 2 } }",
+        (1, 25),
+        ErrorMessage::MatchMissingWildcard("Int64".into()),
+    );
+
+    err(
+        "fn f(x: Int64): Int64 { match x { 1..=5 => 1, 3 => 2, _ => 3 } }",
+        (1, 47),
+        ErrorMessage::MatchUnreachablePattern,
+    );
+
+    err(
+        "fn f(x: Int64): Int64 { match x { _ => 1, 3 => 2 } }",
+        (1, 43),
+        ErrorMessage::MatchUnreachablePattern,
+    );
+
+    err(
+        "fn f(x: Int64): Int64 { match x { 'a' => 1, _ => 2 } }",
+        (1, 35),
+        ErrorMessage::MatchPatternTypeIncompatible("Int64".into(), "Char".into()),
+    );
+
+    err(
+        "fn f(x: Int64): Int64 { match x { 5..=1 => 1, _ => 2 } }",
+        (1, 35),
+        ErrorMessage::MatchRangeEmpty,
+    );
+
+    err(
+        "fn f(x: String): Int64 { match x { \"a\"..=\"b\" => 1, _ => 2 } }",
+        (1, 36),
+        ErrorMessage::MatchPatternUnsupported,
+    );
+}
+
+#[test]
+fn test_enum_match_underscore() {
+    ok("
+        enum A { V1, V2, V3 }
+        fn f(x: A): Bool {
+            match x {
+                A::V1 => true,
+                _ => false,
+            }
+        }
+    ");
+
+    warnings(
+        "
+        enum A { V1, V2, V3 }
+        fn f(x: A): Bool {
+            match x {
+                _ => false,
+                A::V1 => true,
+            }
+        }
+    ",
+        &[((6, 17), ErrorMessage::MatchUnreachablePattern)],
+    );
+
+    warnings(
+        "
+        enum A { V1, V2, V3 }
+        fn f(x: A): Bool {
+            match x {
+                A::V1 => true,
+                _ => false,
+                _ => true,
+            }
+        }
+    ",
+        &[((7, 17), ErrorMessage::MatchUnreachablePattern)],
+    );
+
+    warnings(
+        "
+        enum A { V1, V2 }
+        fn f(x: A): Bool {
+            // dora:allow(unreachable_pattern)
+            match x {
+                _ => false,
+                A::V1 => true,
+            }
+        }
+    ",
+        &[],
+    );
+}
+
+#[test]
+fn test_if_let() {
+    ok("
+        fn f(x: Option[Int32]): Int32 {
+            if let Some(y) = x { y } else { 0i32 }
+        }
+    ");
+
+    ok("
+        enum A { V1(Int32, Bool), V2 }
+        fn f(x: A): Int32 {
+            if let A::V1(a, _) = x { return a; }
+            0i32
+        }
+    ");
+
+    err(
+        "
+        fn f(x: Option[Int32]): Int32 {
+            if let Some(y) = x { y } else { 0i32 };
+            y
+        }
+    ",
+        (4, 13),
+        ErrorMessage::UnknownIdentifier("y".into()),
+    );
+
+    err(
+        "
+        fn f(x: Option[Int32]): Int32 {
+            if let Some(y) = x { y } else { y }
+        }
+    ",
+        (3, 45),
+        ErrorMessage::UnknownIdentifier("y".into()),
+    );
+
+    err(
+        "
+        fn f(x: Int32) {
+            if let Some(y) = x {}
+        }
+    ",
+        (3, 30),
+        ErrorMessage::EnumExpected,
+    );
+
+    err(
+        "
+        enum A { V1(Int32), V2 }
+        fn f(x: A) {
+            if let A::V1(a, b) = x {}
+        }
+    ",
+        (4, 20),
+        ErrorMessage::MatchPatternWrongNumberOfParams(2, 1),
+    );
+}
+
+#[test]
+fn test_if_let_irrefutable() {
+    warnings(
+        "
+        fn f(x: Option[Int32]) {
+            if let _ = x {}
+        }
+    ",
+        &[((3, 20), ErrorMessage::IrrefutableLetPattern)],
+    );
+
+    warnings(
         "
-        enum A { V1(Int32), V2 }
+        enum A { V1(Int32) }
         fn f(x: A): Int32 {
-            match x {
-                A::V1 => 0i32,
-                A::V2 => 1i32
-            }
+            if let A::V1(a) = x { a } else { 0i32 }
         }
     ",
-        (5, 17),
-        ErrorMessage::MatchPatternWrongNumberOfParams(0, 1),
+        &[((4, 20), ErrorMessage::IrrefutableLetPattern)],
     );
 
-    err(
+    warnings(
         "
-        enum A { V1(Int32, Float32, Bool), V2 }
+        enum A { V1(Option[Int32]) }
         fn f(x: A): Int32 {
-            match x {
-                A::V1(a, b, c, d) => 0i32,
-                A::V2 => 1i32
-            }
+            if let A::V1(Option::Some(a)) = x { a } else { 0i32 }
         }
     ",
-        (5, 17),
-        ErrorMessage::MatchPatternWrongNumberOfParams(4, 3),
+        &[],
     );
 }
 
 #[test]
-fn test_enum_match_params() {
+fn test_while_let() {
     ok("
-        enum A { V1(Int32, Int32, Int32), V2 }
-        fn f(x: A): Int32 {
-            match x {
-                A::V1(a, _, c) => a + c,
-                A::V2 => 1i32
+        fn f(mut x: Option[Int32]): Int32 {
+            let mut sum = 0i32;
+            while let Some(y) = x {
+                sum = sum + y;
+                x = None[Int32];
             }
+            sum
         }
     ");
 
     err(
         "
-        enum A { V1(Int32, Int32, Int32), V2 }
-        fn f(x: A): Int32 {
-            match x {
-                A::V1(a, _, c) => a + c,
-                A::V2 => a
-            }
+        fn f(mut x: Option[Int32]): Int32 {
+            while let Some(y) = x { x = None[Int32]; }
+            y
         }
     ",
-        (6, 26),
-        ErrorMessage::UnknownIdentifier("a".into()),
+        (4, 13),
+        ErrorMessage::UnknownIdentifier("y".into()),
     );
 
     err(
         "
-        enum A { V1(Int32, Int32), V2 }
-        fn f(x: A): Int32 {
-            match x {
-                A::V1(a, a) => a + a,
-                A::V2 => 1i32
-            }
+        fn f(x: Bool) {
+            while let Some(y) = x {}
         }
     ",
-        (5, 26),
-        ErrorMessage::VarAlreadyInPattern,
+        (3, 33),
+        ErrorMessage::EnumExpected,
     );
 }
 
 #[test]
-fn test_enum_match_missing_variants() {
-    err(
-        "
-        enum A { V1(Int32, Int32, Int32), V2, V3 }
+fn test_enum_match_exhaustive() {
+    ok("
+        enum A { V1(Int32), V2, V3 }
         fn f(x: A): Int32 {
             match x {
-                A::V1(a, _, c) => a + c,
+                A::V1(a) => a,
                 A::V2 => 1i32,
+                A::V3 => 2i32,
             }
         }
-    ",
-        (4, 13),
-        ErrorMessage::MatchUncoveredVariant,
-    );
+    ");
 
     err(
         "
-        enum A { V1(Int32, Int32, Int32), V2, V3 }
+        enum A { V1(Int32), V2, V3, V4 }
         fn f(x: A): Int32 {
             match x {
-                A::V1(a, _, c) => a + c,
                 A::V2 => 1i32,
-                A::V3 => 2i32,
-                A::V2 => 4i32,
+                A::V4 => 2i32,
             }
         }
     ",
-        (8, 17),
-        ErrorMessage::MatchUnreachablePattern,
+        (4, 13),
+        ErrorMessage::MatchUncoveredVariant(vec!["V1".into(), "V3".into()]),
     );
-}
 
-#[test]
-fn test_enum_match_underscore() {
     ok("
-        enum A { V1, V2, V3 }
-        fn f(x: A): Bool {
+        enum A { V1(Int32), V2, V3 }
+        fn f(x: A): Int32 {
             match x {
-                A::V1 => true,
-                _ => false,
+                A::V2 => 1i32,
+                _ => 2i32,
             }
         }
     ");
+}
+
+#[test]
+fn test_object_equals() {
+    ok("class A fn f(x: A, y: A): Bool { x == y }");
+    ok("class A fn f(x: A, y: A): Bool { x != y }");
+    ok("trait T {} class A impl T for A {} fn f(x: T, y: T): Bool { x == y }");
+    ok("class A impl A { fn equals(rhs: A): Bool { true } } fn f(x: A, y: A): Bool { x == y }");
 
     err(
-        "
-        enum A { V1, V2, V3 }
-        fn f(x: A): Bool {
-            match x {
-                _ => false,
-                A::V1 => true,
-            }
-        }
-    ",
-        (6, 17),
-        ErrorMessage::MatchUnreachablePattern,
+        "class A class B fn f(x: A, y: B): Bool { x == y }",
+        (1, 42),
+        ErrorMessage::BinOpType("==".into(), "A".into(), "B".into()),
+    );
+    err(
+        "struct A fn f(x: A, y: A): Bool { x == y }",
+        (1, 35),
+        ErrorMessage::BinOpType("==".into(), "A".into(), "A".into()),
     );
 }
 
@@ -2443,11 +3544,11 @@ fn extension_with_fct_type_param() {
         class MyClass[T]
         class Foo
         impl MyClass[Foo] {
-            fn do[T](another: MyClass[T]) {}
+            fn apply[T](another: MyClass[T]) {}
         }
         fn f() {
-            MyClass[Foo]().do[Int32](MyClass[Int32]());
-            MyClass[Foo]().do[Float32](MyClass[Float32]());
+            MyClass[Foo]().apply[Int32](MyClass[Int32]());
+            MyClass[Foo]().apply[Float32](MyClass[Float32]());
         }
     ");
 }
@@ -2859,6 +3960,75 @@ fn mod_fct_call() {
     );
 }
 
+#[test]
+fn mod_restricted_visibility() {
+    ok("
+        mod foo {
+            fn f() { bar::g(); }
+            pub mod bar { pub(mod) fn g() {} }
+            pub mod baz { use super::bar::g; fn h() { g(); } }
+        }
+    ");
+
+    err(
+        "
+        fn f() { foo::bar::g(); }
+        mod foo {
+            pub mod bar { pub(mod) fn g() {} }
+        }
+    ",
+        (2, 18),
+        ErrorMessage::NotAccessible("foo::bar::g".into()),
+    );
+
+    ok("
+        fn f() { foo::bar::g(); }
+        mod foo {
+            pub mod bar { pub(super) fn g() {} }
+        }
+    ");
+
+    err(
+        "
+        fn f() { foo::bar::baz::g(); }
+        mod foo {
+            pub mod bar { pub mod baz { pub(super) fn g() {} } }
+        }
+    ",
+        (2, 18),
+        ErrorMessage::NotAccessible("foo::bar::baz::g".into()),
+    );
+
+    err(
+        "
+        fn f() { foo::bar::baz::g(); }
+        mod foo {
+            pub mod bar { pub(mod) mod baz { pub fn g() {} } }
+        }
+    ",
+        (2, 18),
+        ErrorMessage::NotAccessible("foo::bar::baz::g".into()),
+    );
+
+    err(
+        "
+        fn f(x: foo::bar::Foo) { let a = x.a; }
+        mod foo {
+            pub mod bar { pub class Foo(pub(mod) a: Int32) }
+        }
+    ",
+        (2, 43),
+        ErrorMessage::NotAccessible("a".into()),
+    );
+
+    ok("
+        mod foo {
+            fn f(x: bar::Foo) { let a = x.a; }
+            pub mod bar { pub class Foo(pub(mod) a: Int32) }
+        }
+    ");
+}
+
 #[test]
 fn mod_ctor_call() {
     ok("
@@ -3573,6 +4743,82 @@ fn trait_object_cast() {
     );
 }
 
+#[test]
+fn checked_cast() {
+    ok("fn f(x: Int64): Int32 { x as Int32 }");
+    ok("fn f(x: Int32): UInt8 { x as UInt8 }");
+    ok("fn f(x: Float64): Int32 { x as Int32 }");
+
+    err(
+        "fn f(x: Int32): Int64 { x as Int64 }",
+        (1, 25),
+        ErrorMessage::TraitExpected("Int64".into()),
+    );
+
+    err(
+        "fn f(x: Float32): Int32 { x as Int32 }",
+        (1, 27),
+        ErrorMessage::TraitExpected("Int32".into()),
+    );
+}
+
+#[test]
+fn trait_object_downcast() {
+    ok("
+        trait Foo { fn bar(): Int32; }
+        class Bar
+        impl Foo for Bar {
+            fn bar(): Int32 { 1i32 }
+        }
+        fn f(x: Foo): Bar { x as Bar }
+        fn g(x: Foo): Bool { x is Bar }
+    ");
+
+    ok("
+        trait Foo { fn bar(): Int32; }
+        fn f[T: Foo](x: Foo): T { x as T }
+        fn g[T: Foo](x: Foo): Bool { x is T }
+    ");
+
+    err(
+        "
+        trait Foo { fn bar(): Int32; }
+        fn f[T](x: Foo): T { x as T }
+    ",
+        (3, 30),
+        ErrorMessage::TypeNotImplementingTrait("T".into(), "Foo".into()),
+    );
+
+    err(
+        "
+        trait Foo { fn bar(): Int32; }
+        class Bar
+        fn f(x: Foo): Bar { x as Bar }
+    ",
+        (4, 29),
+        ErrorMessage::TypeNotImplementingTrait("Bar".into(), "Foo".into()),
+    );
+
+    err(
+        "
+        trait Foo { fn bar(): Int32; }
+        trait Baz { fn bar(): Int32; }
+        fn f(x: Foo): Bool { x is Baz }
+    ",
+        (4, 30),
+        ErrorMessage::ConcreteTypeExpected("Baz".into()),
+    );
+
+    err(
+        "
+        class Bar
+        fn f(x: Bar): Bool { x is Bar }
+    ",
+        (3, 30),
+        ErrorMessage::TraitObjectExpected("Bar".into()),
+    );
+}
+
 #[test]
 fn infer_enum_type() {
     ok("fn f(): Option[Int32] {
@@ -3724,3 +4970,126 @@ fn self_unavailable_in_lambda() {
         ErrorMessage::ThisUnavailable,
     );
 }
+
+#[test]
+fn range_expr_unimplemented() {
+    err(
+        "fn f(a: Int64, b: Int64) { let x = a..b; }",
+        (1, 36),
+        ErrorMessage::Unimplemented,
+    );
+    err(
+        "fn f() { let x = ..; }",
+        (1, 18),
+        ErrorMessage::Unimplemented,
+    );
+}
+
+#[test]
+fn unsigned_int_types() {
+    ok("fn f(x: UInt16, y: UInt32, z: UInt64): UInt64 { z }");
+    ok("fn f(x: UInt32): Int32 { UInt32::bits() }");
+    err(
+        "fn f(x: UInt32) { let y: UInt64 = x; }",
+        (1, 19),
+        ErrorMessage::AssignType("y".into(), "UInt64".into(), "UInt32".into()),
+    );
+    err(
+        "fn f() { let x = 1u32; }",
+        (1, 18),
+        ErrorMessage::Unimplemented,
+    );
+    err(
+        "fn f() { let x: UInt16 = 1; }",
+        (1, 26),
+        ErrorMessage::Unimplemented,
+    );
+}
+
+#[test]
+fn try_expr_unimplemented() {
+    err(
+        "fn f(a: Option[Int64]) { let x = a?; }",
+        (1, 34),
+        ErrorMessage::Unimplemented,
+    );
+}
+
+#[test]
+fn unused_variable() {
+    warnings(
+        "fn f() { let x = 1; let y = 2; y; }",
+        &[((1, 14), ErrorMessage::UnusedVariable("x".into()))],
+    );
+    warnings("fn f() { let _x = 1; let (a, _) = (1, 2); a; }", &[]);
+    warnings(
+        "fn f() { let x = 1; let g = || { let y = x; }; g(); }",
+        &[((1, 38), ErrorMessage::UnusedVariable("y".into()))],
+    );
+}
+
+#[test]
+fn unused_variable_allow_comment() {
+    warnings(
+        "fn f() {
+            // dora:allow(unused_variable)
+            let x = 1;
+            let y = 2;
+        }",
+        &[((4, 17), ErrorMessage::UnusedVariable("y".into()))],
+    );
+    warnings(
+        "// dora:allow(unused_variable)
+        fn f() { let x = 1; let y = 2; }
+        fn g() { let z = 1; }",
+        &[((3, 22), ErrorMessage::UnusedVariable("z".into()))],
+    );
+    warnings(
+        "fn f() {
+            // dora:allow(unused_variable, unknown_lint)
+            let x = 1;
+        }",
+        &[((2, 44), ErrorMessage::UnknownLint("unknown_lint".into()))],
+    );
+}
+
+#[test]
+fn variadic_parameter_packs_surplus_arguments() {
+    ok("
+        class Foo
+        impl Foo {
+            fn f(x: Int32...): Int64 { x.size() }
+        }
+        fn g(foo: Foo) {
+            foo.f();
+            foo.f(1i32);
+            foo.f(1i32, 2i32, 3i32);
+        }
+    ");
+    ok("
+        fn f[T](x: T...): Int64 { x.size() }
+        fn g() {
+            f[Int32]();
+            f[Int32](1i32);
+            f[Int32](1i32, 2i32);
+        }
+    ");
+    ok("
+        fn f(x: Int32...): Array[Int32] { x }
+        fn g(): Int32 { f(1i32, 2i32)(0i64) }
+    ");
+    err(
+        "
+        fn f(x: Int32, y: Int32...) {}
+        fn g() {
+            f(1i32, 2i32, true);
+        }
+    ",
+        (4, 13),
+        ErrorMessage::ParamTypesIncompatible(
+            "f".into(),
+            vec!["Int32".into(), "Int32".into()],
+            vec!["Int32".into(), "Int32".into(), "Bool".into()],
+        ),
+    );
+}
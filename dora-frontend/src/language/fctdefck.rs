@@ -1,5 +1,7 @@
 use std::collections::HashSet;
 
+use dora_bytecode::TRAP_NAMES;
+
 use crate::language::error::msg::ErrorMessage;
 use crate::language::sem_analysis::{
     FctDefinition, FctDefinitionId, FctParent, SemAnalysis, TypeParamId,
@@ -191,6 +193,15 @@ fn check_test(sa: &SemAnalysis, fct: &FctDefinition) {
         let msg = ErrorMessage::InvalidTestAnnotationUsage;
         sa.diag.lock().report(fct.file_id, fct.span, msg);
     }
+
+    if let Some(expected_trap) = fct.expected_trap {
+        let name = sa.interner.str(expected_trap);
+
+        if !TRAP_NAMES.contains(&name.as_str()) {
+            let msg = ErrorMessage::UnknownTrap(name.to_string());
+            sa.diag.lock().report(fct.file_id, fct.span, msg);
+        }
+    }
 }
 
 fn check_against_methods(sa: &SemAnalysis, fct: &FctDefinition, methods: &[FctDefinitionId]) {
@@ -217,6 +228,17 @@ mod tests {
     use crate::language::error::msg::ErrorMessage;
     use crate::language::tests::*;
 
+    #[test]
+    fn test_expect_trap() {
+        ok("@Test.expectTrap(INDEX_OUT_OF_BOUNDS) fn f() {}");
+        ok("@Test.expectTrap(DIV0) fn f() {}");
+        err(
+            "@Test.expectTrap(OUT_OF_BOUNDS) fn f() {}",
+            (1, 33),
+            ErrorMessage::UnknownTrap("OUT_OF_BOUNDS".into()),
+        );
+    }
+
     #[test]
     fn self_param() {
         err(
@@ -1,32 +1,54 @@
 use std::collections::HashMap;
 use std::convert::TryInto;
 
+use dora_parser::interner::Name;
 use dora_parser::{ast, Span};
 
+use crate::language::desugar::{self, DesugarKind, DesugarStep};
+use crate::language::fctbodyck::body::is_checked_cast;
 use crate::language::sem_analysis::{
     emit_as_bytecode_operation, find_impl, AnalysisData, CallType, ClassDefinitionId,
-    ConstDefinitionId, ContextIdx, EnumDefinitionId, FctDefinition, FctDefinitionId, FieldId,
-    GlobalDefinitionId, IdentType, SemAnalysis, StructDefinitionId, TraitDefinitionId, TypeParamId,
-    VarId,
+    ConstDefinitionId, ConstValue, ContextIdx, EnumDefinitionId, FctDefinition, FctDefinitionId,
+    FieldId, GlobalDefinitionId, IdentType, SemAnalysis, StructDefinitionId, TraitDefinitionId,
+    TypeParamId, VarId,
 };
 use crate::language::specialize::specialize_type;
 use crate::language::ty::{SourceType, SourceTypeArray};
 use crate::language::{expr_always_returns, expr_block_always_returns};
 use dora_bytecode::{
-    BytecodeBuilder, BytecodeFunction, BytecodeType, BytecodeTypeArray, ClassId, ConstPoolIdx,
-    EnumId, FunctionId, GlobalId, Intrinsic, Label, Location, Register, StructId, TraitId,
+    BytecodeBuilder, BytecodeFunction, BytecodeInstruction, BytecodeType, BytecodeTypeArray,
+    ClassId, ConstPoolIdx, EnumId, FunctionId, GlobalId, Intrinsic, Label, Location, Register,
+    StructId, TraitId,
 };
 
 use super::sem_analysis::VarLocation;
 
 pub struct LoopLabels {
+    label: Option<Name>,
     cond: Label,
     end: Label,
+    // The condition of `do`-`while` loops comes after the body, so `continue`
+    // needs a forward jump.
+    cond_after_body: bool,
 }
 
 impl LoopLabels {
-    fn new(cond: Label, end: Label) -> LoopLabels {
-        LoopLabels { cond, end }
+    fn new(label: Option<Name>, cond: Label, end: Label) -> LoopLabels {
+        LoopLabels {
+            label,
+            cond,
+            end,
+            cond_after_body: false,
+        }
+    }
+
+    fn post_condition(label: Option<Name>, cond: Label, end: Label) -> LoopLabels {
+        LoopLabels {
+            label,
+            cond,
+            end,
+            cond_after_body: true,
+        }
     }
 }
 
@@ -39,6 +61,26 @@ pub fn generate_fct(sa: &SemAnalysis, id: FctDefinitionId) -> BytecodeFunction {
 }
 
 pub fn generate(sa: &SemAnalysis, fct: &FctDefinition, src: &AnalysisData) -> BytecodeFunction {
+    generate_with_trace(sa, fct, src, None)
+}
+
+// Generates the function once more only to record its desugaring steps.
+pub fn trace_desugar(
+    sa: &SemAnalysis,
+    fct: &FctDefinition,
+    src: &AnalysisData,
+) -> Vec<DesugarStep> {
+    let mut steps = Vec::new();
+    generate_with_trace(sa, fct, src, Some(&mut steps));
+    steps
+}
+
+fn generate_with_trace<'a>(
+    sa: &'a SemAnalysis,
+    fct: &'a FctDefinition,
+    src: &'a AnalysisData,
+    desugar_steps: Option<&'a mut Vec<DesugarStep>>,
+) -> BytecodeFunction {
     let ast_bytecode_generator = AstBytecodeGen {
         sa,
         fct,
@@ -49,12 +91,27 @@ pub fn generate(sa: &SemAnalysis, fct: &FctDefinition, src: &AnalysisData) -> By
         var_registers: HashMap::new(),
         unit_register: None,
         context_register: None,
+        desugar_steps,
     };
     ast_bytecode_generator.generate(&fct.ast)
 }
 
 const SELF_VAR_ID: VarId = VarId(0);
 
+// Integer matches and `if x == 0 {..} else if x == 1 {..}` chains with at
+// least this many cases are lowered to a jump table, as long as the table is
+// at most twice as large as the number of values covered by the cases.
+const MATCH_JUMP_TABLE_MIN_CASES: usize = 4;
+const MATCH_JUMP_TABLE_MAX_SIZE: i64 = 1024;
+
+struct IfChainJumpTable<'e> {
+    scrutinee: Register,
+    arms: Vec<&'e ast::Expr>,
+    else_block: Option<&'e ast::Expr>,
+    low: i64,
+    table: Vec<Option<usize>>,
+}
+
 struct AstBytecodeGen<'a> {
     sa: &'a SemAnalysis,
     fct: &'a FctDefinition,
@@ -65,6 +122,7 @@ struct AstBytecodeGen<'a> {
     var_registers: HashMap<VarId, Register>,
     context_register: Option<Register>,
     unit_register: Option<Register>,
+    desugar_steps: Option<&'a mut Vec<DesugarStep>>,
 }
 
 impl<'a> AstBytecodeGen<'a> {
@@ -72,6 +130,25 @@ impl<'a> AstBytecodeGen<'a> {
         self.sa.compute_loc(self.fct.file_id, span)
     }
 
+    fn snippet(&self, span: Span) -> &'a str {
+        desugar::source_snippet(self.sa, self.fct.file_id, span)
+    }
+
+    fn trace_desugar<F>(&mut self, kind: DesugarKind, span: Span, lowered: F)
+    where
+        F: FnOnce(&Self) -> String,
+    {
+        if let Some(steps) = self.desugar_steps.take() {
+            let lowered = lowered(self);
+            steps.push(DesugarStep {
+                kind,
+                span,
+                lowered,
+            });
+            self.desugar_steps = Some(steps);
+        }
+    }
+
     fn generate(mut self, ast: &ast::Function) -> BytecodeFunction {
         self.push_scope();
 
@@ -222,16 +299,39 @@ impl<'a> AstBytecodeGen<'a> {
     fn visit_stmt(&mut self, stmt: &ast::Stmt) {
         match *stmt {
             ast::Stmt::Return(ref ret) => self.visit_stmt_return(ret),
+            ast::Stmt::Throw(ref stmt) => self.visit_stmt_throw(stmt),
             ast::Stmt::Break(ref stmt) => self.visit_stmt_break(stmt),
             ast::Stmt::Continue(ref stmt) => self.visit_stmt_continue(stmt),
             ast::Stmt::Expr(ref expr) => self.visit_stmt_expr(expr),
             ast::Stmt::Let(ref stmt) => self.visit_stmt_let(stmt),
             ast::Stmt::While(ref stmt) => self.visit_stmt_while(stmt),
+            ast::Stmt::Loop(ref stmt) => self.visit_stmt_loop(stmt),
+            ast::Stmt::DoWhile(ref stmt) => self.visit_stmt_do_while(stmt),
+            // Nested functions are generated like all other functions.
+            ast::Stmt::Fct(_) => {}
             ast::Stmt::For(ref stmt) => self.visit_stmt_for(stmt),
         }
     }
 
     fn visit_stmt_for(&mut self, stmt: &ast::StmtForType) {
+        self.trace_desugar(DesugarKind::For, stmt.span, |gen| {
+            let for_type_info = gen.analysis.map_fors.get(stmt.id).unwrap();
+            let expr = gen.snippet(stmt.expr.span());
+
+            let iterator = if for_type_info.make_iterator.is_some() {
+                format!("{}.makeIterator()", expr)
+            } else {
+                expr.to_string()
+            };
+
+            format!(
+                "let $iter = {};\nwhile let Some({}) = $iter.next() {}",
+                iterator,
+                gen.snippet(stmt.pattern.span()),
+                gen.snippet(stmt.block.span()),
+            )
+        });
+
         self.visit_stmt_for_iterator(stmt);
     }
 
@@ -367,11 +467,26 @@ impl<'a> AstBytecodeGen<'a> {
             // Emit: <iterator> = <obj>.makeIterator();
             let iterator_reg = self.alloc_var(BytecodeType::Ptr);
             self.builder.emit_push_register(object_reg);
-            let fct_idx = self
-                .builder
-                .add_const_fct_types(FunctionId(make_iterator.0 as u32), object_type_params);
-            self.builder
-                .emit_invoke_direct(iterator_reg, fct_idx, self.loc(stmt.expr.span()));
+
+            if let SourceType::TypeParam(id) = object_type {
+                let fct_idx = self.builder.add_const_generic(
+                    id.0 as u32,
+                    FunctionId(make_iterator.0 as u32),
+                    bty_array_from_ty(&for_type_info.make_iterator_type_params),
+                );
+                self.builder.emit_invoke_generic_direct(
+                    iterator_reg,
+                    fct_idx,
+                    self.loc(stmt.expr.span()),
+                );
+            } else {
+                let fct_idx = self
+                    .builder
+                    .add_const_fct_types(FunctionId(make_iterator.0 as u32), object_type_params);
+                self.builder
+                    .emit_invoke_direct(iterator_reg, fct_idx, self.loc(stmt.expr.span()));
+            }
+
             iterator_reg
         } else {
             // Object is already the iterator - just use it
@@ -395,18 +510,32 @@ impl<'a> AstBytecodeGen<'a> {
         let next_result_ty = register_bty_from_ty(for_type_info.next_type.clone());
         let next_result_reg = self.alloc_temp(next_result_ty);
 
-        let fct_idx = self.builder.add_const_fct_types(
-            FunctionId(for_type_info.next.0 as u32),
-            iterator_type_params,
-        );
-
         self.builder.emit_push_register(iterator_reg);
-        self.emit_invoke_direct(
-            for_type_info.next_type.clone(),
-            next_result_reg,
-            fct_idx,
-            self.loc(stmt.expr.span()),
-        );
+
+        if let SourceType::TypeParam(id) = iterator_type {
+            let fct_idx = self.builder.add_const_generic(
+                id.0 as u32,
+                FunctionId(for_type_info.next.0 as u32),
+                bty_array_from_ty(&for_type_info.next_type_params),
+            );
+            self.emit_invoke_generic_direct(
+                for_type_info.next_type.clone(),
+                next_result_reg,
+                fct_idx,
+                self.loc(stmt.expr.span()),
+            );
+        } else {
+            let fct_idx = self.builder.add_const_fct_types(
+                FunctionId(for_type_info.next.0 as u32),
+                iterator_type_params,
+            );
+            self.emit_invoke_direct(
+                for_type_info.next_type.clone(),
+                next_result_reg,
+                fct_idx,
+                self.loc(stmt.expr.span()),
+            );
+        }
 
         // Emit: if <next-result>.isNone() then goto lbl_end
         let cond_reg = self.alloc_temp(BytecodeType::Bool);
@@ -443,7 +572,8 @@ impl<'a> AstBytecodeGen<'a> {
             );
         }
 
-        self.loops.push(LoopLabels::new(lbl_cond, lbl_end));
+        self.loops
+            .push(LoopLabels::new(stmt.label, lbl_cond, lbl_end));
         self.visit_stmt(&stmt.block);
         self.loops.pop().unwrap();
 
@@ -553,15 +683,53 @@ impl<'a> AstBytecodeGen<'a> {
         let end_lbl = self.builder.create_label();
         self.builder.emit_loop_start();
         let cond_reg = self.visit_expr(&stmt.cond, DataDest::Alloc);
-        self.builder.emit_jump_if_false(cond_reg, end_lbl);
+        if let Some(ref pattern) = stmt.pattern {
+            self.push_scope();
+            self.emit_cond_pattern(pattern, &stmt.cond, cond_reg, end_lbl);
+        } else {
+            self.builder.emit_jump_if_false(cond_reg, end_lbl);
+        }
         self.free_if_temp(cond_reg);
-        self.loops.push(LoopLabels::new(cond_lbl, end_lbl));
+        self.loops
+            .push(LoopLabels::new(stmt.label, cond_lbl, end_lbl));
         self.visit_stmt(&stmt.block);
         self.loops.pop().unwrap();
+        if stmt.pattern.is_some() {
+            self.pop_scope();
+        }
         self.builder.emit_jump_loop(cond_lbl);
         self.builder.bind_label(end_lbl);
     }
 
+    fn visit_stmt_loop(&mut self, stmt: &ast::StmtLoopType) {
+        let start_lbl = self.builder.define_label();
+        let end_lbl = self.builder.create_label();
+        self.builder.emit_loop_start();
+        self.loops
+            .push(LoopLabels::new(stmt.label, start_lbl, end_lbl));
+        self.visit_stmt(&stmt.block);
+        self.loops.pop().unwrap();
+        self.builder.emit_jump_loop(start_lbl);
+        self.builder.bind_label(end_lbl);
+    }
+
+    fn visit_stmt_do_while(&mut self, stmt: &ast::StmtDoWhileType) {
+        let start_lbl = self.builder.define_label();
+        let cond_lbl = self.builder.create_label();
+        let end_lbl = self.builder.create_label();
+        self.builder.emit_loop_start();
+        self.loops
+            .push(LoopLabels::post_condition(stmt.label, cond_lbl, end_lbl));
+        self.visit_stmt(&stmt.block);
+        self.loops.pop().unwrap();
+        self.builder.bind_label(cond_lbl);
+        let cond_reg = self.visit_expr(&stmt.cond, DataDest::Alloc);
+        self.builder.emit_jump_if_false(cond_reg, end_lbl);
+        self.free_if_temp(cond_reg);
+        self.builder.emit_jump_loop(start_lbl);
+        self.builder.bind_label(end_lbl);
+    }
+
     fn visit_stmt_expr(&mut self, stmt: &ast::StmtExprType) {
         let reg = self.visit_expr(&stmt.expr, DataDest::Effect);
         self.free_if_temp(reg);
@@ -578,6 +746,12 @@ impl<'a> AstBytecodeGen<'a> {
         }
     }
 
+    fn visit_stmt_throw(&mut self, stmt: &ast::StmtThrowType) {
+        let exception_reg = self.visit_expr(&stmt.expr, DataDest::Alloc);
+        self.builder.emit_throw(exception_reg, self.loc(stmt.span));
+        self.free_if_temp(exception_reg);
+    }
+
     fn emit_ret_value(&mut self, result_reg: Register) {
         let ret_ty = self.fct.return_type.clone();
 
@@ -590,25 +764,55 @@ impl<'a> AstBytecodeGen<'a> {
         self.builder.emit_ret(result_reg);
     }
 
-    fn visit_stmt_break(&mut self, _stmt: &ast::StmtBreakType) {
-        let end = self.loops.last().unwrap().end;
+    fn visit_stmt_break(&mut self, stmt: &ast::StmtBreakType) {
+        let end = self.find_loop(stmt.label).end;
         self.builder.emit_jump(end);
     }
 
-    fn visit_stmt_continue(&mut self, _stmt: &ast::StmtContinueType) {
-        let cond = self.loops.last().unwrap().cond;
-        self.builder.emit_jump_loop(cond);
+    fn visit_stmt_continue(&mut self, stmt: &ast::StmtContinueType) {
+        let loop_labels = self.find_loop(stmt.label);
+        let cond = loop_labels.cond;
+
+        if loop_labels.cond_after_body {
+            self.builder.emit_jump(cond);
+        } else {
+            self.builder.emit_jump_loop(cond);
+        }
+    }
+
+    // Without a label this is the innermost loop, otherwise the innermost loop
+    // with the given label.
+    fn find_loop(&self, label: Option<Name>) -> &LoopLabels {
+        match label {
+            Some(label) => self
+                .loops
+                .iter()
+                .rev()
+                .find(|l| l.label == Some(label))
+                .expect("unknown loop label"),
+            None => self.loops.last().expect("not inside loop"),
+        }
     }
 
     fn visit_expr(&mut self, expr: &ast::Expr, dest: DataDest) -> Register {
+        if let Some(value) = self.analysis.map_consts.get(expr.id()).cloned() {
+            if dest.is_effect() {
+                return Register::invalid();
+            }
+
+            return self.emit_const_value(self.ty(expr.id()), &value, dest);
+        }
+
         match *expr {
             ast::Expr::Un(ref un) => self.visit_expr_un(un, dest),
             ast::Expr::Bin(ref bin) => self.visit_expr_bin(bin, dest),
             ast::Expr::Dot(ref field) => self.visit_expr_dot(field, dest),
             ast::Expr::Block(ref block) => self.visit_expr_block(block, dest),
             ast::Expr::If(ref expr) => self.visit_expr_if(expr, dest),
+            ast::Expr::TryCatch(ref expr) => self.visit_expr_try_catch(expr, dest),
             ast::Expr::Template(ref template) => self.visit_expr_template(template, dest),
             ast::Expr::TypeParam(ref expr) => self.visit_expr_type_param(expr, dest),
+            ast::Expr::Index(ref expr) => self.visit_expr_index(expr, dest),
             ast::Expr::Path(ref path) => self.visit_expr_path(path, dest),
             ast::Expr::LitChar(ref lit) => self.visit_expr_lit_char(lit, dest),
             ast::Expr::LitInt(ref lit) => self.visit_expr_lit_int(lit, dest, false),
@@ -623,6 +827,7 @@ impl<'a> AstBytecodeGen<'a> {
             ast::Expr::Paren(ref paren) => self.visit_expr(&paren.expr, dest),
             ast::Expr::Match(ref expr) => self.visit_expr_match(expr, dest),
             ast::Expr::Lambda(ref node) => self.visit_expr_lambda(node, dest),
+            ast::Expr::Range(_) | ast::Expr::Try(_) => unreachable!(),
         }
     }
 
@@ -631,6 +836,43 @@ impl<'a> AstBytecodeGen<'a> {
         self.free_if_temp(reg);
     }
 
+    fn visit_expr_index(&mut self, expr: &ast::ExprIndexType, dest: DataDest) -> Register {
+        if let Some(info) = self.get_intrinsic(expr.id) {
+            return self.emit_intrinsic_bin(
+                &expr.object,
+                &expr.index,
+                info,
+                None,
+                self.loc(expr.span),
+                dest,
+            );
+        }
+
+        let call_type = self.analysis.map_calls.get(expr.id).unwrap();
+        let callee_id = self.determine_callee(call_type);
+
+        let callee = self.sa.fcts.idx(callee_id);
+        let callee = callee.read();
+
+        let callee_idx = self.specialize_call(&callee, call_type);
+        let return_type = self.specialize_type_for_call(call_type, callee.return_type.clone());
+        let return_type_bc = register_bty_from_ty(return_type.clone());
+
+        let obj_reg = self.visit_expr(&expr.object, DataDest::Alloc);
+        let idx_reg = self.visit_expr(&expr.index, DataDest::Alloc);
+
+        let dest = self.ensure_register(dest, return_type_bc);
+
+        self.builder.emit_push_register(obj_reg);
+        self.builder.emit_push_register(idx_reg);
+        self.emit_invoke_direct(return_type, dest, callee_idx, self.loc(expr.span));
+
+        self.free_if_temp(obj_reg);
+        self.free_if_temp(idx_reg);
+
+        dest
+    }
+
     fn visit_expr_type_param(&mut self, expr: &ast::ExprTypeParamType, dest: DataDest) -> Register {
         let ident_type = self.analysis.map_idents.get(expr.id).cloned().unwrap();
 
@@ -644,6 +886,29 @@ impl<'a> AstBytecodeGen<'a> {
     }
 
     fn visit_expr_template(&mut self, expr: &ast::ExprTemplateType, dest: DataDest) -> Register {
+        self.trace_desugar(DesugarKind::Template, expr.span, |gen| {
+            let mut lowered = String::from("{\n    let $buf = StringBuffer::empty();\n");
+
+            for part in &expr.parts {
+                if is_empty_lit_str(part) {
+                    continue;
+                }
+
+                let part = if let Some(lit_str) = part.to_lit_str() {
+                    format!("{:?}", lit_str.value)
+                } else if gen.ty(part.id()).cls_id() == Some(gen.sa.known.classes.string()) {
+                    gen.snippet(part.span()).to_string()
+                } else {
+                    format!("{}.toString()", gen.snippet(part.span()))
+                };
+
+                lowered.push_str(&format!("    $buf.append({});\n", part));
+            }
+
+            lowered.push_str("    $buf.toString()\n}");
+            lowered
+        });
+
         let buffer_register = self.ensure_register(dest, BytecodeType::Ptr);
 
         // build StringBuffer::empty() call
@@ -655,6 +920,11 @@ impl<'a> AstBytecodeGen<'a> {
         let part_register = self.alloc_temp(BytecodeType::Ptr);
 
         for part in &expr.parts {
+            // The parser emits empty literals around interpolated expressions.
+            if is_empty_lit_str(part) {
+                continue;
+            }
+
             if let Some(ref lit_str) = part.to_lit_str() {
                 let value = lit_str.value.clone();
                 self.builder.emit_const_string(part_register, value);
@@ -746,6 +1016,10 @@ impl<'a> AstBytecodeGen<'a> {
     }
 
     fn visit_expr_path(&mut self, expr: &ast::ExprPathType, dest: DataDest) -> Register {
+        if let Some(call_type) = self.analysis.map_calls.get(expr.id).cloned() {
+            return self.visit_expr_path_associated_const(expr, &call_type, dest);
+        }
+
         let ident_type = self.analysis.map_idents.get(expr.id).cloned().unwrap();
 
         match ident_type {
@@ -761,6 +1035,33 @@ impl<'a> AstBytecodeGen<'a> {
         }
     }
 
+    fn visit_expr_path_associated_const(
+        &mut self,
+        expr: &ast::ExprPathType,
+        call_type: &CallType,
+        dest: DataDest,
+    ) -> Register {
+        let callee_id = self.determine_callee(call_type);
+
+        let callee = self.sa.fcts.idx(callee_id);
+        let callee = callee.read();
+
+        let callee_idx = self.specialize_call(&callee, call_type);
+        let return_type = self.specialize_type_for_call(call_type, callee.return_type.clone());
+        let return_type_bc = register_bty_from_ty(return_type.clone());
+
+        let dest = self.ensure_register(dest, return_type_bc);
+        self.emit_call_inst(
+            call_type,
+            return_type,
+            self.loc(expr.span),
+            callee_idx,
+            dest,
+        );
+
+        dest
+    }
+
     fn emit_new_enum(
         &mut self,
         enum_id: EnumDefinitionId,
@@ -784,6 +1085,19 @@ impl<'a> AstBytecodeGen<'a> {
         let object_type = self.ty(expr.object.id());
         let check_type = self.ty(expr.data_type.id());
 
+        if object_type.is_trait() && !check_type.is_trait() {
+            return self.visit_expr_conv_trait_object(expr, object_type, check_type, dest);
+        }
+
+        if is_checked_cast(&object_type, &check_type) {
+            let object = self.visit_expr(&expr.object, DataDest::Alloc);
+            let dest = self.ensure_register(dest, register_bty_from_ty(check_type));
+            self.builder
+                .emit_checked_cast(dest, object, self.loc(expr.span));
+            self.free_if_temp(object);
+            return dest;
+        }
+
         let (trait_id, type_params) = match check_type {
             SourceType::Trait(trait_id, ref type_params) => (trait_id, type_params.clone()),
             _ => unreachable!(),
@@ -805,9 +1119,48 @@ impl<'a> AstBytecodeGen<'a> {
         dest
     }
 
+    fn visit_expr_conv_trait_object(
+        &mut self,
+        expr: &ast::ExprConvType,
+        object_type: SourceType,
+        check_type: SourceType,
+        dest: DataDest,
+    ) -> Register {
+        let trait_id = match object_type {
+            SourceType::Trait(trait_id, _) => TraitId(trait_id.0),
+            _ => unreachable!(),
+        };
+
+        let object = self.visit_expr(&expr.object, DataDest::Alloc);
+        let idx = self.builder.add_const_trait(
+            trait_id,
+            bty_array_from_ty(&object_type.type_params()),
+            bty_from_ty(check_type.clone()),
+        );
+
+        let dest = if expr.is {
+            let dest = self.ensure_register(dest, BytecodeType::Bool);
+            self.builder.emit_test_trait_object(dest, idx, object);
+            dest
+        } else {
+            let dest = self.ensure_register(dest, register_bty_from_ty(check_type));
+            self.builder
+                .emit_cast_trait_object(dest, idx, object, self.loc(expr.span));
+            dest
+        };
+
+        self.free_if_temp(object);
+        dest
+    }
+
     fn visit_expr_match(&mut self, node: &ast::ExprMatchType, dest: DataDest) -> Register {
         let result_ty = self.ty(node.id);
         let enum_ty = self.ty(node.expr.id());
+
+        if !enum_ty.is_enum() {
+            return self.visit_expr_match_lit(node, dest);
+        }
+
         let enum_id = enum_ty.enum_id().expect("enum expected");
 
         let dest = if result_ty.is_unit() {
@@ -837,6 +1190,13 @@ impl<'a> AstBytecodeGen<'a> {
             match pattern.data {
                 ast::MatchPatternData::Underscore => {
                     self.builder.bind_label(next_lbl);
+                    next_lbl = self.builder.create_label();
+
+                    if let Some(ref guard) = case.guard {
+                        let guard_reg = self.visit_expr(guard, DataDest::Alloc);
+                        self.builder.emit_jump_if_false(guard_reg, next_lbl);
+                        self.free_if_temp(guard_reg);
+                    }
 
                     if let Some(dest) = dest {
                         self.visit_expr(&case.value, DataDest::Reg(dest));
@@ -844,85 +1204,568 @@ impl<'a> AstBytecodeGen<'a> {
                         self.visit_expr(&case.value, DataDest::Effect);
                     }
 
-                    self.builder.emit_jump(end_lbl);
-                }
+                    self.builder.emit_jump(end_lbl);
+                }
+
+                ast::MatchPatternData::Ident(ref ident) => {
+                    let variant_idx: u32 = {
+                        let ident_type = self.analysis.map_idents.get(pattern.id).unwrap();
+
+                        match ident_type {
+                            IdentType::EnumValue(_, _, variant_idx) => {
+                                (*variant_idx).try_into().unwrap()
+                            }
+                            _ => unreachable!(),
+                        }
+                    };
+
+                    self.builder.bind_label(next_lbl);
+                    next_lbl = self.builder.create_label();
+
+                    if idx != node.cases.len() - 1 {
+                        let tmp_reg = self.alloc_temp(BytecodeType::Int32);
+                        let cmp_reg = self.alloc_temp(BytecodeType::Bool);
+                        self.builder.emit_const_int32(tmp_reg, variant_idx as i32);
+                        self.builder.emit_test_eq(cmp_reg, variant_reg, tmp_reg);
+                        self.builder.emit_jump_if_false(cmp_reg, next_lbl);
+                        self.free_temp(tmp_reg);
+                        self.free_temp(cmp_reg);
+                    }
+
+                    self.push_scope();
+
+                    self.emit_match_pattern_params(
+                        enum_ty.clone(),
+                        variant_idx,
+                        expr_reg,
+                        ident,
+                        next_lbl,
+                    );
+
+                    if let Some(ref guard) = case.guard {
+                        let guard_reg = self.visit_expr(guard, DataDest::Alloc);
+                        self.builder.emit_jump_if_false(guard_reg, next_lbl);
+                        self.free_if_temp(guard_reg);
+                    }
+
+                    if let Some(dest) = dest {
+                        self.visit_expr(&case.value, DataDest::Reg(dest));
+                    } else {
+                        self.visit_expr(&case.value, DataDest::Effect);
+                    }
+
+                    self.pop_scope();
+
+                    self.builder.emit_jump(end_lbl);
+                }
+
+                ast::MatchPatternData::Tuple(_)
+                | ast::MatchPatternData::Lit(_)
+                | ast::MatchPatternData::Range(_) => unreachable!(),
+            }
+        }
+
+        // Only reachable from guards or nested patterns in arms that come
+        // after a wildcard, an earlier arm always matches at runtime.
+        self.builder.bind_label(next_lbl);
+        self.builder.bind_label(end_lbl);
+        self.free_temp(variant_reg);
+        self.free_if_temp(expr_reg);
+
+        dest.unwrap_or(Register::invalid())
+    }
+
+    // Loads the payload of the matched variant into the pattern's variables.
+    // Nested variant patterns test the loaded field and jump to `next_lbl`
+    // on a mismatch.
+    fn emit_match_pattern_params(
+        &mut self,
+        enum_ty: SourceType,
+        variant_idx: u32,
+        expr_reg: Register,
+        ident: &ast::MatchPatternIdent,
+        next_lbl: Label,
+    ) {
+        let params = match ident.params {
+            Some(ref params) => params,
+            None => return,
+        };
+
+        let enum_id = enum_ty.enum_id().expect("enum expected");
+
+        for (subtype_idx, param) in params.iter().enumerate() {
+            if param.name.is_none() && param.pattern.is_none() {
+                continue;
+            }
+
+            let idx = self.builder.add_const_enum_element(
+                EnumId(enum_id.0),
+                bty_array_from_ty(&enum_ty.type_params()),
+                variant_idx,
+                subtype_idx as u32,
+            );
+
+            if let Some(ref pattern) = param.pattern {
+                let nested_ident = match pattern.data {
+                    ast::MatchPatternData::Ident(ref ident) => ident,
+                    _ => unreachable!(),
+                };
+
+                let nested_variant_idx: u32 = match self.analysis.map_idents.get(pattern.id) {
+                    Some(IdentType::EnumValue(_, _, variant_idx)) => *variant_idx,
+                    _ => unreachable!(),
+                };
+
+                let nested_ty = self.ty(pattern.id);
+                let nested_reg = self.alloc_temp(register_bty_from_ty(nested_ty.clone()));
+                self.builder.emit_load_enum_element(
+                    nested_reg,
+                    expr_reg,
+                    idx,
+                    self.loc(pattern.span),
+                );
+
+                self.emit_enum_variant_test(
+                    nested_ty.clone(),
+                    nested_reg,
+                    nested_variant_idx,
+                    next_lbl,
+                    pattern.span,
+                );
+
+                self.emit_match_pattern_params(
+                    nested_ty,
+                    nested_variant_idx,
+                    nested_reg,
+                    nested_ident,
+                    next_lbl,
+                );
+
+                self.free_temp(nested_reg);
+            } else {
+                let var_id = *self.analysis.map_vars.get(param.id).unwrap();
+
+                let ty = self.var_ty(var_id);
+
+                if !ty.is_unit() {
+                    let ty: BytecodeType = register_bty_from_ty(ty);
+                    let var_reg = self.alloc_var(ty);
+
+                    self.var_registers.insert(var_id, var_reg);
+
+                    self.builder.emit_load_enum_element(
+                        var_reg,
+                        expr_reg,
+                        idx,
+                        self.loc(param.span),
+                    );
+                }
+            }
+        }
+    }
+
+    // Emit: if <value>.variant != <variant_idx> then goto fail_lbl
+    fn emit_enum_variant_test(
+        &mut self,
+        enum_ty: SourceType,
+        value_reg: Register,
+        variant_idx: u32,
+        fail_lbl: Label,
+        span: Span,
+    ) {
+        let enum_id = enum_ty.enum_id().expect("enum expected");
+        let enum_idx = self
+            .builder
+            .add_const_enum(EnumId(enum_id.0), bty_array_from_ty(&enum_ty.type_params()));
+        let variant_reg = self.alloc_temp(BytecodeType::Int32);
+        let tmp_reg = self.alloc_temp(BytecodeType::Int32);
+        let cmp_reg = self.alloc_temp(BytecodeType::Bool);
+        self.builder
+            .emit_load_enum_variant(variant_reg, value_reg, enum_idx, self.loc(span));
+        self.builder.emit_const_int32(tmp_reg, variant_idx as i32);
+        self.builder.emit_test_eq(cmp_reg, variant_reg, tmp_reg);
+        self.builder.emit_jump_if_false(cmp_reg, fail_lbl);
+        self.free_temp(variant_reg);
+        self.free_temp(tmp_reg);
+        self.free_temp(cmp_reg);
+    }
+
+    // Tests the pattern of `if let` or `while let` against the scrutinee and
+    // binds its variables, jumps to `fail_lbl` when the pattern doesn't match.
+    fn emit_cond_pattern(
+        &mut self,
+        pattern: &ast::MatchPattern,
+        cond: &ast::Expr,
+        cond_reg: Register,
+        fail_lbl: Label,
+    ) {
+        let ident = match pattern.data {
+            ast::MatchPatternData::Underscore => return,
+            ast::MatchPatternData::Ident(ref ident) => ident,
+            _ => unreachable!(),
+        };
+
+        let variant_idx: u32 = match self.analysis.map_idents.get(pattern.id) {
+            Some(IdentType::EnumValue(_, _, variant_idx)) => *variant_idx,
+            _ => unreachable!(),
+        };
+
+        let enum_ty = self.ty(cond.id());
+        self.emit_enum_variant_test(
+            enum_ty.clone(),
+            cond_reg,
+            variant_idx,
+            fail_lbl,
+            pattern.span,
+        );
+        self.emit_match_pattern_params(enum_ty, variant_idx, cond_reg, ident, fail_lbl);
+    }
+
+    fn visit_expr_match_lit(&mut self, node: &ast::ExprMatchType, dest: DataDest) -> Register {
+        let result_ty = self.ty(node.id);
+
+        let dest = if result_ty.is_unit() {
+            None
+        } else {
+            let result_bc_ty = register_bty_from_ty(result_ty);
+            let dest = self.ensure_register(dest, result_bc_ty);
+            Some(dest)
+        };
+
+        let end_lbl = self.builder.create_label();
+
+        let expr_reg = self.visit_expr(&node.expr, DataDest::Alloc);
+
+        if let Some((low, table)) = self.match_jump_table(node) {
+            self.emit_match_jump_table(node, dest, expr_reg, low, table, end_lbl);
+            self.free_if_temp(expr_reg);
+            return dest.unwrap_or(Register::invalid());
+        }
+
+        let cmp_reg = self.alloc_temp(BytecodeType::Bool);
+
+        for case in &node.cases {
+            debug_assert_eq!(case.patterns.len(), 1);
+            let pattern = case.patterns.first().expect("pattern missing");
+            let next_lbl = self.builder.create_label();
+
+            match pattern.data {
+                ast::MatchPatternData::Underscore => {}
+
+                ast::MatchPatternData::Lit(ref expr) => {
+                    let value_reg = self.visit_expr(expr, DataDest::Alloc);
+
+                    if let Some(call_type) = self.analysis.map_calls.get(pattern.id).cloned() {
+                        self.emit_match_equals(&call_type, expr_reg, value_reg, cmp_reg, pattern);
+                    } else {
+                        self.builder.emit_test_eq(cmp_reg, expr_reg, value_reg);
+                    }
+
+                    self.builder.emit_jump_if_false(cmp_reg, next_lbl);
+                    self.free_if_temp(value_reg);
+                }
+
+                ast::MatchPatternData::Range(ref range) => {
+                    let start_reg = self.visit_expr(&range.start, DataDest::Alloc);
+                    self.builder.emit_test_ge(cmp_reg, expr_reg, start_reg);
+                    self.builder.emit_jump_if_false(cmp_reg, next_lbl);
+                    self.free_if_temp(start_reg);
+
+                    let end_reg = self.visit_expr(&range.end, DataDest::Alloc);
+                    self.builder.emit_test_le(cmp_reg, expr_reg, end_reg);
+                    self.builder.emit_jump_if_false(cmp_reg, next_lbl);
+                    self.free_if_temp(end_reg);
+                }
+
+                ast::MatchPatternData::Ident(_) | ast::MatchPatternData::Tuple(_) => {
+                    unreachable!()
+                }
+            }
+
+            if let Some(ref guard) = case.guard {
+                let guard_reg = self.visit_expr(guard, DataDest::Alloc);
+                self.builder.emit_jump_if_false(guard_reg, next_lbl);
+                self.free_if_temp(guard_reg);
+            }
+
+            if let Some(dest) = dest {
+                self.visit_expr(&case.value, DataDest::Reg(dest));
+            } else {
+                self.visit_expr(&case.value, DataDest::Effect);
+            }
+
+            self.builder.emit_jump(end_lbl);
+            self.builder.bind_label(next_lbl);
+        }
+
+        self.builder.bind_label(end_lbl);
+        self.free_temp(cmp_reg);
+        self.free_if_temp(expr_reg);
+
+        dest.unwrap_or(Register::invalid())
+    }
+
+    // Returns the smallest value and the case for each value up to the largest
+    // value covered by the patterns, if the match is dense enough for a jump table.
+    fn match_jump_table(&self, node: &ast::ExprMatchType) -> Option<(i64, Vec<Option<usize>>)> {
+        let ty = self.ty(node.expr.id());
+
+        match ty {
+            SourceType::UInt8 | SourceType::Char | SourceType::Int32 | SourceType::Int64 => {}
+            _ => return None,
+        }
+
+        let mut ranges = Vec::new();
+
+        for (case_idx, case) in node.cases.iter().enumerate() {
+            if case.guard.is_some() {
+                return None;
+            }
+
+            let pattern = case.patterns.first().expect("pattern missing");
+
+            match pattern.data {
+                ast::MatchPatternData::Underscore => break,
+
+                ast::MatchPatternData::Lit(ref expr) => {
+                    let value = match_pattern_int_value(expr)?;
+                    ranges.push((value, value, case_idx));
+                }
+
+                ast::MatchPatternData::Range(ref range) => {
+                    let start = match_pattern_int_value(&range.start)?;
+                    let end = match_pattern_int_value(&range.end)?;
+                    ranges.push((start, end, case_idx));
+                }
+
+                ast::MatchPatternData::Ident(_) | ast::MatchPatternData::Tuple(_) => {
+                    return None;
+                }
+            }
+        }
+
+        jump_table(ranges)
+    }
+
+    fn emit_match_jump_table(
+        &mut self,
+        node: &ast::ExprMatchType,
+        dest: Option<Register>,
+        expr_reg: Register,
+        low: i64,
+        table: Vec<Option<usize>>,
+        end_lbl: Label,
+    ) {
+        let default_idx = node
+            .cases
+            .iter()
+            .position(|case| {
+                let pattern = case.patterns.first().expect("pattern missing");
+                matches!(pattern.data, ast::MatchPatternData::Underscore)
+            })
+            .expect("wildcard missing");
+
+        let case_lbls = (0..=default_idx)
+            .map(|_| self.builder.create_label())
+            .collect::<Vec<_>>();
+
+        let targets = table
+            .into_iter()
+            .map(|case_idx| case_lbls[case_idx.unwrap_or(default_idx)])
+            .collect();
+
+        self.builder.emit_switch(expr_reg, low, targets);
+        self.builder.emit_jump(case_lbls[default_idx]);
+
+        for (case, &case_lbl) in node.cases.iter().zip(&case_lbls) {
+            self.builder.bind_label(case_lbl);
+
+            if let Some(dest) = dest {
+                self.visit_expr(&case.value, DataDest::Reg(dest));
+            } else {
+                self.visit_expr(&case.value, DataDest::Effect);
+            }
+
+            self.builder.emit_jump(end_lbl);
+        }
+
+        self.builder.bind_label(end_lbl);
+    }
+
+    // Recognizes `if x == 0 {..} else if x == 1 {..} ...` chains on the lowered
+    // conditions. Each condition is emitted speculatively and has to compile to an
+    // integer constant compared against the same register. Such chains can use a
+    // jump table like `match`.
+    fn if_chain_jump_table<'e>(
+        &mut self,
+        expr: &'e ast::ExprIfType,
+    ) -> Option<IfChainJumpTable<'e>> {
+        if if_chain_len(expr) < MATCH_JUMP_TABLE_MIN_CASES {
+            return None;
+        }
+
+        let mut scrutinee = None;
+        let mut ranges = Vec::new();
+        let mut arms = Vec::new();
+        let mut current = expr;
+
+        let else_block = loop {
+            if current.pattern.is_some() {
+                return None;
+            }
+
+            let (reg, value) = self.lower_int_equals(&current.cond)?;
+
+            if *scrutinee.get_or_insert(reg) != reg {
+                return None;
+            }
+
+            ranges.push((value, value, arms.len()));
+            arms.push(&*current.then_block);
+
+            match current.else_block {
+                Some(ref else_block) => match else_block.to_if() {
+                    Some(next) => current = next,
+                    None => break Some(&**else_block),
+                },
+                None => break None,
+            }
+        };
+
+        let (low, table) = jump_table(ranges)?;
+
+        Some(IfChainJumpTable {
+            scrutinee: scrutinee?,
+            arms,
+            else_block,
+            low,
+            table,
+        })
+    }
+
+    // Lowers the condition and returns the compared register and constant if the
+    // emitted bytecode is an integer constant followed by `TestEq`. The emitted
+    // code is always discarded again.
+    fn lower_int_equals(&mut self, cond: &ast::Expr) -> Option<(Register, i64)> {
+        let checkpoint = self.builder.checkpoint();
+        let unit_register = self.unit_register;
+
+        let cond_reg = self.visit_expr(cond, DataDest::Alloc);
+        let insts = self.builder.instructions_since(&checkpoint);
+
+        let result = match insts.as_slice() {
+            [constant, BytecodeInstruction::TestEq { dest, lhs, rhs }] if *dest == cond_reg => self
+                .int_const_value(constant)
+                .and_then(|(const_reg, value)| {
+                    if *lhs == const_reg && *rhs != const_reg {
+                        Some((*rhs, value))
+                    } else if *rhs == const_reg && *lhs != const_reg {
+                        Some((*lhs, value))
+                    } else {
+                        None
+                    }
+                }),
+
+            _ => None,
+        };
+
+        self.builder.rollback(checkpoint);
+        self.unit_register = unit_register;
+
+        result
+    }
+
+    fn int_const_value(&self, inst: &BytecodeInstruction) -> Option<(Register, i64)> {
+        match *inst {
+            BytecodeInstruction::ConstUInt8 { dest, value } => Some((dest, value as i64)),
+            BytecodeInstruction::ConstChar { dest, idx } => {
+                let value = self.builder.const_pool(idx).to_char()?;
+                Some((dest, value as i64))
+            }
+            BytecodeInstruction::ConstInt32 { dest, idx } => {
+                let value = self.builder.const_pool(idx).to_int32()?;
+                Some((dest, value as i64))
+            }
+            BytecodeInstruction::ConstInt64 { dest, idx } => {
+                let value = self.builder.const_pool(idx).to_int64()?;
+                Some((dest, value))
+            }
+            _ => None,
+        }
+    }
+
+    fn emit_if_jump_table(
+        &mut self,
+        expr: &ast::ExprIfType,
+        chain: IfChainJumpTable,
+        dest: DataDest,
+    ) -> Register {
+        let ty = self.ty(expr.id);
 
-                ast::MatchPatternData::Ident(ref ident) => {
-                    let variant_idx: u32 = {
-                        let ident_type = self.analysis.map_idents.get(pattern.id).unwrap();
+        let dest = if ty.is_unit() {
+            Register::invalid()
+        } else {
+            self.ensure_register(dest, register_bty_from_ty(ty))
+        };
 
-                        match ident_type {
-                            IdentType::EnumValue(_, _, variant_idx) => {
-                                (*variant_idx).try_into().unwrap()
-                            }
-                            _ => unreachable!(),
-                        }
-                    };
+        let else_lbl = self.builder.create_label();
+        let end_lbl = self.builder.create_label();
 
-                    self.builder.bind_label(next_lbl);
-                    next_lbl = self.builder.create_label();
+        let arm_lbls = chain
+            .arms
+            .iter()
+            .map(|_| self.builder.create_label())
+            .collect::<Vec<_>>();
 
-                    if idx != node.cases.len() - 1 {
-                        let tmp_reg = self.alloc_temp(BytecodeType::Int32);
-                        let cmp_reg = self.alloc_temp(BytecodeType::Bool);
-                        self.builder.emit_const_int32(tmp_reg, variant_idx as i32);
-                        self.builder.emit_test_eq(cmp_reg, variant_reg, tmp_reg);
-                        self.builder.emit_jump_if_false(cmp_reg, next_lbl);
-                        self.free_temp(tmp_reg);
-                        self.free_temp(cmp_reg);
-                    }
+        let targets = chain
+            .table
+            .into_iter()
+            .map(|arm_idx| arm_idx.map_or(else_lbl, |arm_idx| arm_lbls[arm_idx]))
+            .collect();
 
-                    self.push_scope();
+        self.builder
+            .emit_switch(chain.scrutinee, chain.low, targets);
+        self.builder.emit_jump(else_lbl);
 
-                    if let Some(ref params) = ident.params {
-                        for (subtype_idx, param) in params.iter().enumerate() {
-                            if let Some(_) = param.name {
-                                let idx = self.builder.add_const_enum_element(
-                                    EnumId(enum_id.0),
-                                    bty_array_from_ty(&enum_ty.type_params()),
-                                    variant_idx,
-                                    subtype_idx as u32,
-                                );
-
-                                let var_id = *self.analysis.map_vars.get(param.id).unwrap();
-
-                                let ty = self.var_ty(var_id);
-
-                                if !ty.is_unit() {
-                                    let ty: BytecodeType = register_bty_from_ty(ty);
-                                    let var_reg = self.alloc_var(ty);
-
-                                    self.var_registers.insert(var_id, var_reg);
-
-                                    self.builder.emit_load_enum_element(
-                                        var_reg,
-                                        expr_reg,
-                                        idx,
-                                        self.loc(param.span),
-                                    );
-                                }
-                            }
-                        }
-                    }
+        for (arm, &arm_lbl) in chain.arms.iter().zip(&arm_lbls) {
+            self.builder.bind_label(arm_lbl);
+            self.visit_expr(arm, DataDest::Reg(dest));
 
-                    if let Some(dest) = dest {
-                        self.visit_expr(&case.value, DataDest::Reg(dest));
-                    } else {
-                        self.visit_expr(&case.value, DataDest::Effect);
-                    }
+            if !expr_always_returns(arm) {
+                self.builder.emit_jump(end_lbl);
+            }
+        }
 
-                    self.pop_scope();
+        self.builder.bind_label(else_lbl);
 
-                    self.builder.emit_jump(end_lbl);
-                }
-            }
+        if let Some(else_block) = chain.else_block {
+            self.visit_expr(else_block, DataDest::Reg(dest));
         }
 
         self.builder.bind_label(end_lbl);
-        self.free_temp(variant_reg);
-        self.free_if_temp(expr_reg);
 
-        dest.unwrap_or(Register::invalid())
+        dest
+    }
+
+    fn emit_match_equals(
+        &mut self,
+        call_type: &CallType,
+        lhs: Register,
+        rhs: Register,
+        dest: Register,
+        pattern: &ast::MatchPattern,
+    ) {
+        let callee_id = self.determine_callee(call_type);
+
+        let callee = self.sa.fcts.idx(callee_id);
+        let callee = callee.read();
+
+        let callee_idx = self.specialize_call(&callee, call_type);
+
+        self.builder.emit_push_register(lhs);
+        self.builder.emit_push_register(rhs);
+
+        self.emit_invoke_direct(SourceType::Bool, dest, callee_idx, self.loc(pattern.span));
     }
 
     fn visit_expr_lambda(&mut self, node: &ast::Function, dest: DataDest) -> Register {
@@ -953,6 +1796,14 @@ impl<'a> AstBytecodeGen<'a> {
     }
 
     fn visit_expr_if(&mut self, expr: &ast::ExprIfType, dest: DataDest) -> Register {
+        if let Some(ref pattern) = expr.pattern {
+            return self.visit_expr_if_let(expr, pattern, dest);
+        }
+
+        if let Some(chain) = self.if_chain_jump_table(expr) {
+            return self.emit_if_jump_table(expr, chain, dest);
+        }
+
         let ty = self.ty(expr.id);
 
         if let Some(ref else_block) = expr.else_block {
@@ -996,6 +1847,114 @@ impl<'a> AstBytecodeGen<'a> {
         }
     }
 
+    fn visit_expr_if_let(
+        &mut self,
+        expr: &ast::ExprIfType,
+        pattern: &ast::MatchPattern,
+        dest: DataDest,
+    ) -> Register {
+        let ty = self.ty(expr.id);
+
+        let dest = if ty.is_unit() {
+            Register::invalid()
+        } else {
+            self.ensure_register(dest, register_bty_from_ty(ty))
+        };
+
+        let else_lbl = self.builder.create_label();
+        let end_lbl = self.builder.create_label();
+
+        let cond_reg = self.visit_expr(&expr.cond, DataDest::Alloc);
+
+        self.push_scope();
+        self.emit_cond_pattern(pattern, &expr.cond, cond_reg, else_lbl);
+        self.free_if_temp(cond_reg);
+
+        if let Some(ref else_block) = expr.else_block {
+            self.visit_expr(&expr.then_block, DataDest::Reg(dest));
+            self.pop_scope();
+
+            if !expr_always_returns(&expr.then_block) {
+                self.builder.emit_jump(end_lbl);
+            }
+
+            self.builder.bind_label(else_lbl);
+            self.visit_expr(else_block, DataDest::Reg(dest));
+        } else {
+            self.emit_expr_for_effect(&expr.then_block);
+            self.pop_scope();
+            self.builder.bind_label(else_lbl);
+        }
+
+        self.builder.bind_label(end_lbl);
+
+        dest
+    }
+
+    fn visit_expr_try_catch(&mut self, expr: &ast::ExprTryCatchType, dest: DataDest) -> Register {
+        let ty = self.ty(expr.id);
+
+        let dest = if ty.is_unit() {
+            Register::invalid()
+        } else {
+            self.ensure_register(dest, register_bty_from_ty(ty))
+        };
+
+        let try_start_lbl = self.builder.define_label();
+        let try_end_lbl = self.builder.create_label();
+        let handler_lbl = self.builder.create_label();
+        let end_lbl = self.builder.create_label();
+
+        self.visit_expr(&expr.try_block, DataDest::Reg(dest));
+        self.builder.bind_label(try_end_lbl);
+
+        // the handler is only entered through the exception table
+        if !expr_always_returns(&expr.try_block) {
+            self.builder.emit_jump(end_lbl);
+        }
+
+        self.builder.bind_label(handler_lbl);
+        self.push_scope();
+
+        let var_id = *self.analysis.map_vars.get(expr.id).unwrap();
+        let var = self.analysis.vars.get_var(var_id);
+        let exception_type = bty_from_ty(var.ty.clone());
+
+        match var.location {
+            VarLocation::Context(context_idx) => {
+                let exception_reg = self.alloc_temp(BytecodeType::Ptr);
+                self.builder.add_exception_handler(
+                    try_start_lbl,
+                    try_end_lbl,
+                    handler_lbl,
+                    exception_type,
+                    exception_reg,
+                );
+                self.store_in_context(exception_reg, context_idx, self.loc(expr.span));
+                self.free_temp(exception_reg);
+            }
+
+            VarLocation::Stack => {
+                let var_reg = self.alloc_var(BytecodeType::Ptr);
+                self.var_registers.insert(var_id, var_reg);
+                self.builder.add_exception_handler(
+                    try_start_lbl,
+                    try_end_lbl,
+                    handler_lbl,
+                    exception_type,
+                    var_reg,
+                );
+            }
+        }
+
+        self.visit_expr(&expr.catch_block, DataDest::Reg(dest));
+        self.pop_scope();
+
+        self.builder.bind_label(end_lbl);
+
+        dest
+    }
+
     fn visit_expr_block(&mut self, block: &ast::ExprBlockType, dest: DataDest) -> Register {
         self.push_scope();
 
@@ -1939,6 +2898,11 @@ impl<'a> AstBytecodeGen<'a> {
         let callee = self.sa.fcts.idx(callee_id);
         let callee = callee.read();
 
+        self.trace_desugar(DesugarKind::Operator, expr.span, |gen| {
+            let name = gen.sa.interner.str(callee.name);
+            format!("{}.{}()", gen.snippet(expr.opnd.span()), name)
+        });
+
         // Create FctDefId for this callee
         let callee_idx = self.specialize_call(&callee, &call_type);
 
@@ -1992,6 +2956,23 @@ impl<'a> AstBytecodeGen<'a> {
         let callee = self.sa.fcts.idx(callee_id);
         let callee = callee.read();
 
+        self.trace_desugar(DesugarKind::Operator, expr.span, |gen| {
+            let name = gen.sa.interner.str(callee.name);
+            let call = format!(
+                "{}.{}({})",
+                gen.snippet(expr.lhs.span()),
+                name,
+                gen.snippet(expr.rhs.span())
+            );
+
+            match expr.op {
+                ast::BinOp::Cmp(ast::CmpOp::Eq) => call,
+                ast::BinOp::Cmp(ast::CmpOp::Ne) => format!("!{}", call),
+                ast::BinOp::Cmp(op) => format!("{} {} 0", call, op.as_str()),
+                _ => call,
+            }
+        });
+
         // Create FctDefId for this callee
         let callee_idx = self.specialize_call(&callee, &call_type);
 
@@ -2019,12 +3000,21 @@ impl<'a> AstBytecodeGen<'a> {
         self.builder.emit_push_register(lhs);
         self.builder.emit_push_register(rhs);
 
-        self.emit_invoke_direct(
-            function_return_type,
-            result,
-            callee_idx,
-            self.loc(expr.span),
-        );
+        if let CallType::GenericMethod(..) = *call_type.as_ref() {
+            self.emit_invoke_generic_direct(
+                function_return_type,
+                result,
+                callee_idx,
+                self.loc(expr.span),
+            );
+        } else {
+            self.emit_invoke_direct(
+                function_return_type,
+                result,
+                callee_idx,
+                self.loc(expr.span),
+            );
+        }
 
         self.free_if_temp(lhs);
         self.free_if_temp(rhs);
@@ -2111,6 +3101,7 @@ impl<'a> AstBytecodeGen<'a> {
                 ),
 
                 Intrinsic::ArrayNewOfSize => self.emit_intrinsic_new_array(expr, dest),
+                Intrinsic::ArrayFill => self.emit_intrinsic_array_fill(expr, dest),
 
                 Intrinsic::ArrayWithValues => {
                     let ty = self.ty(expr.id);
@@ -2124,11 +3115,80 @@ impl<'a> AstBytecodeGen<'a> {
                     self.emit_array_with_variadic_arguments(expr, &[element_ty], 0, dest)
                 }
 
+                Intrinsic::EnumOrdinal => self.emit_intrinsic_enum_ordinal(expr, dest),
+                Intrinsic::EnumValues => self.emit_intrinsic_enum_values(expr, dest),
+
                 _ => panic!("unimplemented intrinsic {:?}", intrinsic),
             }
         }
     }
 
+    fn emit_intrinsic_enum_ordinal(
+        &mut self,
+        expr: &ast::ExprCallType,
+        dest: DataDest,
+    ) -> Register {
+        let object = expr.object().expect("object expected");
+        let enum_ty = self.ty(object.id());
+        let enum_id = enum_ty.enum_id().expect("enum expected");
+
+        let object_reg = self.visit_expr(object, DataDest::Alloc);
+
+        let dest = self.ensure_register(dest, BytecodeType::Int32);
+        let idx = self
+            .builder
+            .add_const_enum(EnumId(enum_id.0), bty_array_from_ty(&enum_ty.type_params()));
+        self.builder
+            .emit_load_enum_variant(dest, object_reg, idx, self.loc(expr.span));
+
+        self.free_if_temp(object_reg);
+
+        dest
+    }
+
+    fn emit_intrinsic_enum_values(&mut self, expr: &ast::ExprCallType, dest: DataDest) -> Register {
+        let ty = self.ty(expr.id);
+        let cls_id = ty.cls_id().expect("class expected");
+        let type_params = ty.type_params();
+        let cls_idx = self
+            .builder
+            .add_const_cls_types(ClassId(cls_id.0 as u32), bty_array_from_ty(&type_params));
+
+        let enum_ty = type_params[0].clone();
+        let enum_id = enum_ty.enum_id().expect("enum expected");
+        let variants = self.sa.enums.idx(enum_id).read().variants.len();
+
+        let length_reg = self.alloc_temp(BytecodeType::Int64);
+        self.builder.emit_const_int64(length_reg, variants as i64);
+
+        let array_reg = self.ensure_register(dest, BytecodeType::Ptr);
+        self.builder
+            .emit_new_array(array_reg, cls_idx, length_reg, self.loc(expr.span));
+
+        // Store all variants in declaration order
+        let index_reg = self.alloc_temp(BytecodeType::Int64);
+        let variant_reg = self.alloc_temp(register_bty_from_ty(enum_ty.clone()));
+
+        for variant_idx in 0..variants {
+            let idx = self.builder.add_const_enum_variant(
+                EnumId(enum_id.0),
+                bty_array_from_ty(&enum_ty.type_params()),
+                variant_idx as u32,
+            );
+            self.builder
+                .emit_new_enum(variant_reg, idx, self.loc(expr.span));
+            self.builder.emit_const_int64(index_reg, variant_idx as i64);
+            self.builder
+                .emit_store_array(variant_reg, array_reg, index_reg, self.loc(expr.span));
+        }
+
+        self.free_if_temp(variant_reg);
+        self.free_if_temp(index_reg);
+        self.free_if_temp(length_reg);
+
+        array_reg
+    }
+
     fn emit_intrinsic_new_array(&mut self, expr: &ast::ExprCallType, dest: DataDest) -> Register {
         // We need array of elements
         let element_ty = self.ty(expr.id);
@@ -2149,6 +3209,29 @@ impl<'a> AstBytecodeGen<'a> {
         array_reg
     }
 
+    fn emit_intrinsic_array_fill(&mut self, expr: &ast::ExprCallType, dest: DataDest) -> Register {
+        let array_ty = self.ty(expr.id);
+        let cls_id = array_ty.cls_id().expect("class expected");
+        let type_params = array_ty.type_params();
+        let cls_idx = self
+            .builder
+            .add_const_cls_types(ClassId(cls_id.0 as u32), bty_array_from_ty(&type_params));
+
+        let array_reg = self.ensure_register(dest, BytecodeType::Ptr);
+        let length_reg = self.visit_expr(&expr.args[0], DataDest::Alloc);
+        let value_reg = self.visit_expr(&expr.args[1], DataDest::Alloc);
+
+        self.builder
+            .emit_new_array(array_reg, cls_idx, length_reg, self.loc(expr.span));
+        self.builder
+            .emit_array_fill(value_reg, array_reg, length_reg, self.loc(expr.span));
+
+        self.free_if_temp(value_reg);
+        self.free_if_temp(length_reg);
+
+        array_reg
+    }
+
     fn emit_bin_is(&mut self, expr: &ast::ExprBinType, dest: DataDest) -> Register {
         if dest.is_effect() {
             self.emit_expr_for_effect(&expr.lhs);
@@ -2350,7 +3433,7 @@ impl<'a> AstBytecodeGen<'a> {
             {
                 BytecodeType::Bool
             }
-            Intrinsic::EnumEq | Intrinsic::EnumNe => {
+            Intrinsic::EnumEq | Intrinsic::EnumNe | Intrinsic::ObjectEq | Intrinsic::ObjectNe => {
                 assert!(op.is_some());
                 assert!(info.fct_id.is_none());
                 BytecodeType::Bool
@@ -2453,6 +3536,11 @@ impl<'a> AstBytecodeGen<'a> {
             },
             Intrinsic::EnumEq => self.builder.emit_test_eq(dest, lhs_reg, rhs_reg),
             Intrinsic::EnumNe => self.builder.emit_test_ne(dest, lhs_reg, rhs_reg),
+            Intrinsic::ObjectEq => self.builder.emit_test_identity(dest, lhs_reg, rhs_reg),
+            Intrinsic::ObjectNe => {
+                self.builder.emit_test_identity(dest, lhs_reg, rhs_reg);
+                self.builder.emit_not(dest, dest);
+            }
             Intrinsic::Int32Eq => match op {
                 Some(ast::BinOp::Cmp(ast::CmpOp::Eq)) => {
                     self.builder.emit_test_eq(dest, lhs_reg, rhs_reg)
@@ -2603,7 +3691,13 @@ impl<'a> AstBytecodeGen<'a> {
                 _ => unreachable!(),
             },
             Intrinsic::Int32Add => self.builder.emit_add(dest, lhs_reg, rhs_reg, location),
+            Intrinsic::Int32AddSaturating => {
+                self.builder.emit_add_saturating(dest, lhs_reg, rhs_reg)
+            }
             Intrinsic::Int32Sub => self.builder.emit_sub(dest, lhs_reg, rhs_reg, location),
+            Intrinsic::Int32SubSaturating => {
+                self.builder.emit_sub_saturating(dest, lhs_reg, rhs_reg)
+            }
             Intrinsic::Int32Mul => self.builder.emit_mul(dest, lhs_reg, rhs_reg, location),
             Intrinsic::Int32Div => self.builder.emit_div(dest, lhs_reg, rhs_reg, location),
             Intrinsic::Int32Mod => self.builder.emit_mod(dest, lhs_reg, rhs_reg, location),
@@ -2615,7 +3709,13 @@ impl<'a> AstBytecodeGen<'a> {
             Intrinsic::Int32Sar => self.builder.emit_sar(dest, lhs_reg, rhs_reg),
 
             Intrinsic::Int64Add => self.builder.emit_add(dest, lhs_reg, rhs_reg, location),
+            Intrinsic::Int64AddSaturating => {
+                self.builder.emit_add_saturating(dest, lhs_reg, rhs_reg)
+            }
             Intrinsic::Int64Sub => self.builder.emit_sub(dest, lhs_reg, rhs_reg, location),
+            Intrinsic::Int64SubSaturating => {
+                self.builder.emit_sub_saturating(dest, lhs_reg, rhs_reg)
+            }
             Intrinsic::Int64Mul => self.builder.emit_mul(dest, lhs_reg, rhs_reg, location),
             Intrinsic::Int64Div => self.builder.emit_div(dest, lhs_reg, rhs_reg, location),
             Intrinsic::Int64Mod => self.builder.emit_mod(dest, lhs_reg, rhs_reg, location),
@@ -2630,11 +3730,13 @@ impl<'a> AstBytecodeGen<'a> {
             Intrinsic::Float32Sub => self.builder.emit_sub(dest, lhs_reg, rhs_reg, location),
             Intrinsic::Float32Mul => self.builder.emit_mul(dest, lhs_reg, rhs_reg, location),
             Intrinsic::Float32Div => self.builder.emit_div(dest, lhs_reg, rhs_reg, location),
+            Intrinsic::Float32Mod => self.builder.emit_mod(dest, lhs_reg, rhs_reg, location),
 
             Intrinsic::Float64Add => self.builder.emit_add(dest, lhs_reg, rhs_reg, location),
             Intrinsic::Float64Sub => self.builder.emit_sub(dest, lhs_reg, rhs_reg, location),
             Intrinsic::Float64Mul => self.builder.emit_mul(dest, lhs_reg, rhs_reg, location),
             Intrinsic::Float64Div => self.builder.emit_div(dest, lhs_reg, rhs_reg, location),
+            Intrinsic::Float64Mod => self.builder.emit_mod(dest, lhs_reg, rhs_reg, location),
 
             _ => unimplemented!(),
         }
@@ -2661,7 +3763,12 @@ impl<'a> AstBytecodeGen<'a> {
         } else {
             match *expr.lhs {
                 ast::Expr::Dot(ref dot) => self.visit_expr_assign_dot(expr, dot),
-                ast::Expr::Call(ref call) => self.visit_expr_assign_call(expr, call),
+                ast::Expr::Call(ref call) => {
+                    self.visit_expr_assign_call(expr, &call.callee, &call.args[0])
+                }
+                ast::Expr::Index(ref index) => {
+                    self.visit_expr_assign_call(expr, &index.object, &index.index)
+                }
                 _ => unreachable!(),
             };
         }
@@ -2669,9 +3776,13 @@ impl<'a> AstBytecodeGen<'a> {
         Register::invalid()
     }
 
-    fn visit_expr_assign_call(&mut self, expr: &ast::ExprBinType, call_expr: &ast::ExprCallType) {
-        let object = &call_expr.callee;
-        let index = &call_expr.args[0];
+    // Assignments `a(i) = v` and `a[i] = v` both call `set` on `a`.
+    fn visit_expr_assign_call(
+        &mut self,
+        expr: &ast::ExprBinType,
+        object: &ast::Expr,
+        index: &ast::Expr,
+    ) {
         let value = &expr.rhs;
 
         if let Some(info) = self.get_intrinsic(expr.id) {
@@ -2970,14 +4081,17 @@ impl<'a> AstBytecodeGen<'a> {
 
         let const_ = self.sa.consts.idx(const_id);
         let const_ = const_.read();
-        let ty = const_.ty.clone();
 
+        self.emit_const_value(const_.ty.clone(), &const_.value, dest)
+    }
+
+    fn emit_const_value(&mut self, ty: SourceType, value: &ConstValue, dest: DataDest) -> Register {
         let bytecode_ty = register_bty_from_ty(ty.clone());
         let dest = self.ensure_register(dest, bytecode_ty);
 
         match ty {
             SourceType::Bool => {
-                if const_.value.to_bool() {
+                if value.to_bool() {
                     self.builder.emit_const_true(dest);
                 } else {
                     self.builder.emit_const_false(dest);
@@ -2985,31 +4099,28 @@ impl<'a> AstBytecodeGen<'a> {
             }
 
             SourceType::Char => {
-                self.builder.emit_const_char(dest, const_.value.to_char());
+                self.builder.emit_const_char(dest, value.to_char());
             }
 
             SourceType::UInt8 => {
-                self.builder
-                    .emit_const_uint8(dest, const_.value.to_int() as u8);
+                self.builder.emit_const_uint8(dest, value.to_int() as u8);
             }
 
             SourceType::Int32 => {
-                self.builder
-                    .emit_const_int32(dest, const_.value.to_int() as i32);
+                self.builder.emit_const_int32(dest, value.to_int() as i32);
             }
 
             SourceType::Int64 => {
-                self.builder.emit_const_int64(dest, const_.value.to_int());
+                self.builder.emit_const_int64(dest, value.to_int());
             }
 
             SourceType::Float32 => {
                 self.builder
-                    .emit_const_float32(dest, const_.value.to_float() as f32);
+                    .emit_const_float32(dest, value.to_float() as f32);
             }
 
             SourceType::Float64 => {
-                self.builder
-                    .emit_const_float64(dest, const_.value.to_float());
+                self.builder.emit_const_float64(dest, value.to_float());
             }
 
             _ => unimplemented!(),
@@ -3150,7 +4261,7 @@ impl<'a> AstBytecodeGen<'a> {
 
             CallType::Expr(_, _, ref type_params) => type_params.clone(),
 
-            CallType::TraitObjectMethod(_, _) => SourceTypeArray::empty(),
+            CallType::TraitObjectMethod(ref trait_ty, _) => trait_ty.type_params(),
             CallType::GenericMethod(_, _, _) => SourceTypeArray::empty(),
             CallType::GenericStaticMethod(_, _, _) => SourceTypeArray::empty(),
 
@@ -3390,6 +4501,9 @@ pub fn bty_from_ty(ty: SourceType) -> BytecodeType {
         SourceType::Unit => BytecodeType::Unit,
         SourceType::Bool => BytecodeType::Bool,
         SourceType::UInt8 => BytecodeType::UInt8,
+        SourceType::UInt16 => BytecodeType::UInt16,
+        SourceType::UInt32 => BytecodeType::UInt32,
+        SourceType::UInt64 => BytecodeType::UInt64,
         SourceType::Char => BytecodeType::Char,
         SourceType::Int32 => BytecodeType::Int32,
         SourceType::Int64 => BytecodeType::Int64,
@@ -3416,6 +4530,7 @@ pub fn bty_from_ty(ty: SourceType) -> BytecodeType {
         ),
         SourceType::Ptr => BytecodeType::Ptr,
         SourceType::This => BytecodeType::This,
+        SourceType::Never => unreachable!("Never has no values and no BytecodeType"),
         _ => panic!("SourceType {:?} cannot be converted to BytecodeType", ty),
     }
 }
@@ -3433,6 +4548,9 @@ pub fn ty_from_bty(ty: BytecodeType) -> SourceType {
         BytecodeType::Unit => SourceType::Unit,
         BytecodeType::Bool => SourceType::Bool,
         BytecodeType::UInt8 => SourceType::UInt8,
+        BytecodeType::UInt16 => SourceType::UInt16,
+        BytecodeType::UInt32 => SourceType::UInt32,
+        BytecodeType::UInt64 => SourceType::UInt64,
         BytecodeType::Char => SourceType::Char,
         BytecodeType::Int32 => SourceType::Int32,
         BytecodeType::Int64 => SourceType::Int64,
@@ -3469,6 +4587,9 @@ pub fn register_bty_from_ty(ty: SourceType) -> BytecodeType {
         SourceType::Unit => BytecodeType::Unit,
         SourceType::Bool => BytecodeType::Bool,
         SourceType::UInt8 => BytecodeType::UInt8,
+        SourceType::UInt16 => BytecodeType::UInt16,
+        SourceType::UInt32 => BytecodeType::UInt32,
+        SourceType::UInt64 => BytecodeType::UInt64,
         SourceType::Char => BytecodeType::Char,
         SourceType::Int32 => BytecodeType::Int32,
         SourceType::Int64 => BytecodeType::Int64,
@@ -3488,10 +4609,76 @@ pub fn register_bty_from_ty(ty: SourceType) -> BytecodeType {
         SourceType::TypeParam(idx) => BytecodeType::TypeParam(idx.to_usize() as u32),
         SourceType::Lambda(_, _) => BytecodeType::Ptr,
         SourceType::Ptr => BytecodeType::Ptr,
+        SourceType::Never => unreachable!("Never has no values and no BytecodeType"),
         _ => panic!("SourceType {:?} cannot be converted to BytecodeType", ty),
     }
 }
 
+// Returns the smallest value and the case for each value up to the largest value
+// covered by the ranges, if the ranges are dense enough for a jump table.
+fn jump_table(ranges: Vec<(i64, i64, usize)>) -> Option<(i64, Vec<Option<usize>>)> {
+    if ranges.len() < MATCH_JUMP_TABLE_MIN_CASES {
+        return None;
+    }
+
+    let low = ranges.iter().map(|&(start, _, _)| start).min()?;
+    let high = ranges.iter().map(|&(_, end, _)| end).max()?;
+    let size = high as i128 - low as i128 + 1;
+    let covered: i128 = ranges
+        .iter()
+        .map(|&(start, end, _)| end as i128 - start as i128 + 1)
+        .sum();
+
+    if size > MATCH_JUMP_TABLE_MAX_SIZE as i128 || size > 2 * covered {
+        return None;
+    }
+
+    let mut table = vec![None; size as usize];
+
+    for (start, end, case_idx) in ranges {
+        for value in start..=end {
+            let slot = &mut table[(value - low) as usize];
+
+            if slot.is_none() {
+                *slot = Some(case_idx);
+            }
+        }
+    }
+
+    Some((low, table))
+}
+
+fn is_empty_lit_str(expr: &ast::Expr) -> bool {
+    expr.to_lit_str()
+        .map(|lit_str| lit_str.value.is_empty())
+        .unwrap_or(false)
+}
+
+// Counts the conditions of an `if/else if` chain.
+fn if_chain_len(expr: &ast::ExprIfType) -> usize {
+    let mut len = 1;
+    let mut current = expr;
+
+    while let Some(next) = current.else_block.as_ref().and_then(|block| block.to_if()) {
+        len += 1;
+        current = next;
+    }
+
+    len
+}
+
+fn match_pattern_int_value(expr: &ast::Expr) -> Option<i64> {
+    match expr {
+        ast::Expr::LitInt(lit) => Some(lit.value as i64),
+        ast::Expr::LitChar(lit) => Some(lit.value as i64),
+        ast::Expr::Un(un) if un.op == ast::UnOp::Neg => {
+            let lit = un.opnd.to_lit_int()?;
+            Some((lit.value as i64).wrapping_neg())
+        }
+        _ => None,
+    }
+}
+
 fn field_id_from_context_idx(context_idx: ContextIdx, has_outer_context_slot: bool) -> FieldId {
     let start_idx = if has_outer_context_slot { 1 } else { 0 };
     let ContextIdx(context_idx) = context_idx;
@@ -172,10 +172,73 @@ fn gen_position_store_field_uint8() {
 
 #[test]
 fn gen_add_int() {
-    let result = code("fn f(): Int32 { return 1i32 + 2i32; }");
+    let result = code("fn f(a: Int32, b: Int32): Int32 { return a + b; }");
+    let expected = vec![Add(r(2), r(0), r(1)), Ret(r(2))];
+    assert_eq!(expected, result);
+}
+
+#[test]
+fn gen_const_fold_arithmetic() {
+    let result = code("fn f(): Int32 { return 2i32 + 3i32 * 4i32; }");
+    let expected = vec![ConstInt32(r(0), 14), Ret(r(0))];
+    assert_eq!(expected, result);
+
+    let result = code("fn f(): Int64 { (1i64 << 40i32) - -(7i64 % 4i64) }");
+    let expected = vec![ConstInt64(r(0), (1i64 << 40) + 3), Ret(r(0))];
+    assert_eq!(expected, result);
+
+    let result = code("fn f(a: Int32): Int32 { a * (2i32 + 3i32) }");
+    let expected = vec![ConstInt32(r(2), 5), Mul(r(1), r(0), r(2)), Ret(r(1))];
+    assert_eq!(expected, result);
+
+    let result = code("fn f(): Bool { 1i32 + 1i32 < 3i32 }");
+    let expected = vec![ConstTrue(r(0)), Ret(r(0))];
+    assert_eq!(expected, result);
+}
+
+#[test]
+fn gen_const_fold_short_circuit() {
+    let result = code("fn f(): Bool { true && (false || 1i32 == 1i32) }");
+    let expected = vec![ConstTrue(r(0)), Ret(r(0))];
+    assert_eq!(expected, result);
+
+    let result = code("fn f(a: Bool): Bool { false && a }");
+    let expected = vec![ConstFalse(r(1)), Ret(r(1))];
+    assert_eq!(expected, result);
+
+    let result = code("fn f(a: Bool): Bool { true || a }");
+    let expected = vec![ConstTrue(r(1)), Ret(r(1))];
+    assert_eq!(expected, result);
+
+    let result = code("fn f(a: Bool): Bool { true && a }");
+    let expected = vec![
+        ConstTrue(r(1)),
+        JumpIfFalse(r(1), 3),
+        Mov(r(1), r(0)),
+        Ret(r(1)),
+    ];
+    assert_eq!(expected, result);
+}
+
+#[test]
+fn gen_const_fold_keeps_trapping_operations() {
+    let result = code("fn f(x: Int32): Int32 { x / 0i32 }");
+    let expected = vec![ConstInt32(r(2), 0), Div(r(1), r(0), r(2)), Ret(r(1))];
+    assert_eq!(expected, result);
+
+    let result = code("fn f(): Int32 { 1i32 / (1i32 - 1i32) }");
     let expected = vec![
         ConstInt32(r(1), 1),
-        ConstInt32(r(2), 2),
+        ConstInt32(r(2), 0),
+        Div(r(0), r(1), r(2)),
+        Ret(r(0)),
+    ];
+    assert_eq!(expected, result);
+
+    let result = code("fn f(): Int32 { 2147483647i32 + 1i32 }");
+    let expected = vec![
+        ConstInt32(r(1), 2147483647),
+        ConstInt32(r(2), 1),
         Add(r(0), r(1), r(2)),
         Ret(r(0)),
     ];
@@ -222,6 +285,17 @@ fn gen_ptr_is() {
     assert_eq!(expected, result);
 }
 
+#[test]
+fn gen_ptr_eq_without_equals() {
+    let result = code("class Object fn f(a: Object, b: Object): Bool { return a == b; }");
+    let expected = vec![TestIdentity(r(2), r(0), r(1)), Ret(r(2))];
+    assert_eq!(expected, result);
+
+    let result = code("class Object fn f(a: Object, b: Object): Bool { return a != b; }");
+    let expected = vec![TestIdentity(r(2), r(0), r(1)), Not(r(2), r(2)), Ret(r(2))];
+    assert_eq!(expected, result);
+}
+
 #[test]
 fn gen_ptr_is_not() {
     let result = code("class Object fn f(a: Object, b: Object): Bool { return a !== b; }");
@@ -236,6 +310,17 @@ fn gen_sub_int() {
     assert_eq!(expected, result);
 }
 
+#[test]
+fn gen_saturating_int() {
+    let result = code("fn f(a: Int32, b: Int32): Int32 { a.saturatingAdd(b) }");
+    let expected = vec![AddSaturating(r(2), r(0), r(1)), Ret(r(2))];
+    assert_eq!(expected, result);
+
+    let result = code("fn f(a: Int64, b: Int64): Int64 { a.saturatingSub(b) }");
+    let expected = vec![SubSaturating(r(2), r(0), r(1)), Ret(r(2))];
+    assert_eq!(expected, result);
+}
+
 #[test]
 fn gen_sub_float32() {
     let result = code("fn f(a: Float32, b: Float32): Float32 { return a - b; }");
@@ -562,6 +647,85 @@ fn gen_stmt_continue() {
     assert_eq!(expected, result);
 }
 
+#[test]
+fn gen_stmt_labeled_break_and_continue() {
+    let result = code("fn f() { 'outer: while true { while true { break 'outer; } } }");
+    let expected = vec![
+        LoopStart,
+        ConstTrue(r(0)),
+        JumpIfFalse(r(0), 9),
+        LoopStart,
+        ConstTrue(r(0)),
+        JumpIfFalse(r(0), 8),
+        Jump(9),
+        JumpLoop(3),
+        JumpLoop(0),
+        Ret(r(1)),
+    ];
+    assert_eq!(expected, result);
+
+    let result = code("fn f() { 'outer: while true { while true { continue 'outer; } } }");
+    let expected = vec![
+        LoopStart,
+        ConstTrue(r(0)),
+        JumpIfFalse(r(0), 9),
+        LoopStart,
+        ConstTrue(r(0)),
+        JumpIfFalse(r(0), 8),
+        JumpLoop(0),
+        JumpLoop(3),
+        JumpLoop(0),
+        Ret(r(1)),
+    ];
+    assert_eq!(expected, result);
+}
+
+#[test]
+fn gen_stmt_loop() {
+    let result = code("fn f() { loop { break; } }");
+    let expected = vec![LoopStart, Jump(3), JumpLoop(0), Ret(r(0))];
+    assert_eq!(expected, result);
+
+    let result = code("fn f(): Int32 { loop { } }");
+    let expected = vec![LoopStart, JumpLoop(0)];
+    assert_eq!(expected, result);
+}
+
+#[test]
+fn gen_stmt_do_while() {
+    let result = code("fn f() { do { 0i32; } while true; }");
+    let expected = vec![
+        LoopStart,
+        ConstTrue(r(0)),
+        JumpIfFalse(r(0), 4),
+        JumpLoop(0),
+        Ret(r(1)),
+    ];
+    assert_eq!(expected, result);
+
+    let result = code("fn f() { do { continue; } while false; }");
+    let expected = vec![
+        LoopStart,
+        Jump(2),
+        ConstFalse(r(0)),
+        JumpIfFalse(r(0), 5),
+        JumpLoop(0),
+        Ret(r(1)),
+    ];
+    assert_eq!(expected, result);
+
+    let result = code("fn f() { do { break; } while true; }");
+    let expected = vec![
+        LoopStart,
+        Jump(5),
+        ConstTrue(r(0)),
+        JumpIfFalse(r(0), 5),
+        JumpLoop(0),
+        Ret(r(1)),
+    ];
+    assert_eq!(expected, result);
+}
+
 #[test]
 fn gen_expr_lit_char() {
     let result = code("fn f(): Char { return '1'; }");
@@ -720,6 +884,13 @@ fn gen_expr_mod() {
     assert_eq!(expected, result);
 }
 
+#[test]
+fn gen_expr_mod_float64() {
+    let result = code("fn f(a: Float64, b: Float64): Float64 { return a % b; }");
+    let expected = vec![Mod(r(2), r(0), r(1)), Ret(r(2))];
+    assert_eq!(expected, result);
+}
+
 #[test]
 fn gen_position_mod_int32() {
     let result = position("fn f(a: Int32, b: Int32): Int32 { return a % b; }");
@@ -2601,6 +2772,48 @@ fn gen_new_object_initialized() {
     );
 }
 
+#[test]
+fn gen_expr_template() {
+    gen_fct(
+        "fn f(x: Int32): String { \"x = ${x}\" }",
+        |sa, code, fct| {
+            let expected = vec![
+                InvokeStatic(r(1), ConstPoolIdx(0)),
+                ConstString(r(2), "x = ".into()),
+                PushRegister(r(1)),
+                PushRegister(r(2)),
+                InvokeDirect(r(3), ConstPoolIdx(2)),
+                PushRegister(r(0)),
+                InvokeDirect(r(2), ConstPoolIdx(3)),
+                PushRegister(r(1)),
+                PushRegister(r(2)),
+                InvokeDirect(r(3), ConstPoolIdx(4)),
+                PushRegister(r(1)),
+                InvokeDirect(r(1), ConstPoolIdx(5)),
+                Ret(r(1)),
+            ];
+            assert_eq!(expected, code);
+
+            let functions = &sa.known.functions;
+            let fct_entry = |fct_id: FctDefinitionId| {
+                ConstPoolEntry::Fct(FunctionId(fct_id.0 as u32), BytecodeTypeArray::empty())
+            };
+            assert_eq!(
+                fct.const_pool(ConstPoolIdx(0)),
+                &fct_entry(functions.string_buffer_empty())
+            );
+            assert_eq!(
+                fct.const_pool(ConstPoolIdx(2)),
+                &fct_entry(functions.string_buffer_append())
+            );
+            assert_eq!(
+                fct.const_pool(ConstPoolIdx(5)),
+                &fct_entry(functions.string_buffer_to_string())
+            );
+        },
+    );
+}
+
 #[test]
 fn gen_position_new_object() {
     let result = position("class Object fn f(): Object { return Object(); }");
@@ -3812,6 +4025,134 @@ fn gen_while_with_break() {
     assert_eq!(expected, result);
 }
 
+#[test]
+fn gen_match_int() {
+    let result = code("fn f(x: Int32): Int32 { match x { 0 => 1i32, 2..=4 => 2i32, _ => 3i32 } }");
+    let expected = vec![
+        ConstInt32(r(3), 0),
+        TestEq(r(2), r(0), r(3)),
+        JumpIfFalse(r(2), 5),
+        ConstInt32(r(1), 1),
+        Jump(15),
+        ConstInt32(r(3), 2),
+        TestGe(r(2), r(0), r(3)),
+        JumpIfFalse(r(2), 13),
+        ConstInt32(r(3), 4),
+        TestLe(r(2), r(0), r(3)),
+        JumpIfFalse(r(2), 13),
+        ConstInt32(r(1), 2),
+        Jump(15),
+        ConstInt32(r(1), 3),
+        Jump(15),
+        Ret(r(1)),
+    ];
+    assert_eq!(expected, result);
+}
+
+#[test]
+fn gen_match_int_jump_table() {
+    let result = code(
+        "fn f(x: Int64): Int32 { match x {
+            0 => 0i32, 1 => 1i32, 2 => 2i32, 3 => 3i32, 4 => 4i32, 5 => 5i32, 6 => 6i32,
+            7 => 7i32, 8 => 8i32, 9 => 9i32, 10 => 10i32, 11 => 11i32, 12 => 12i32,
+            13 => 13i32, 14 => 14i32, 15 => 15i32, _ => -1i32 } }",
+    );
+    let targets = (0..16).map(|case| 2 + 2 * case).collect::<Vec<_>>();
+    assert_eq!(Switch(r(0), 0, targets), result[0]);
+    assert_eq!(Jump(34), result[1]);
+    assert_eq!(
+        1,
+        result
+            .iter()
+            .filter(|inst| matches!(inst, Switch(..)))
+            .count()
+    );
+    assert!(!result
+        .iter()
+        .any(|inst| matches!(inst, TestEq(..) | JumpIfFalse(..))));
+
+    let result = code(
+        "fn f(x: Int32): Int32 { match x {
+            0 => 0i32, 10 => 1i32, 200 => 2i32, 3000 => 3i32, _ => -1i32 } }",
+    );
+    assert!(!result.iter().any(|inst| matches!(inst, Switch(..))));
+    assert_eq!(
+        4,
+        result
+            .iter()
+            .filter(|inst| matches!(inst, TestEq(..)))
+            .count()
+    );
+
+    let result = code(
+        "fn f(x: Int32): Int32 { match x {
+            -2 => 0i32, -1 => 1i32, 0 => 2i32, 1..=3 => 3i32, _ => -1i32 } }",
+    );
+    let expected = vec![
+        Switch(r(0), -2, vec![2, 4, 6, 8, 8, 8]),
+        Jump(10),
+        ConstInt32(r(1), 0),
+        Jump(12),
+        ConstInt32(r(1), 1),
+        Jump(12),
+        ConstInt32(r(1), 2),
+        Jump(12),
+        ConstInt32(r(1), 3),
+        Jump(12),
+        ConstInt32(r(1), -1),
+        Jump(12),
+        Ret(r(1)),
+    ];
+    assert_eq!(expected, result);
+}
+
+#[test]
+fn gen_if_chain_jump_table() {
+    let result = code(
+        "fn f(x: Int64): Int32 {
+            if x == 0 { 0i32 } else if x == 1 { 1i32 } else if x == 2 { 2i32 }
+            else if x == 3 { 3i32 } else if x == 4 { 4i32 } else if x == 5 { 5i32 }
+            else if x == 6 { 6i32 } else if x == 7 { 7i32 } else if x == 8 { 8i32 }
+            else if 9 == x { 9i32 } else { -1i32 }
+        }",
+    );
+    let targets = (0..10).map(|arm| 2 + 2 * arm).collect::<Vec<_>>();
+    let mut expected = vec![Switch(r(0), 0, targets), Jump(22)];
+    for arm in 0..10 {
+        expected.push(ConstInt32(r(1), arm));
+        expected.push(Jump(23));
+    }
+    expected.push(ConstInt32(r(1), -1));
+    expected.push(Ret(r(1)));
+    assert_eq!(expected, result);
+
+    let result = code(
+        "const THREE: Int64 = 3;
+        fn f(x: Int64): Int32 {
+            if x == 0 { 0i32 } else if x.equals(1) { 1i32 } else if x == 1 + 1 { 2i32 }
+            else if x == THREE { 3i32 } else { -1i32 }
+        }",
+    );
+    let targets = (0..4).map(|arm| 2 + 2 * arm).collect::<Vec<_>>();
+    assert_eq!(Switch(r(0), 0, targets), result[0]);
+    assert!(!result.iter().any(|inst| matches!(inst, TestEq(..))));
+
+    let result = code(
+        "fn f(x: Int32, y: Int32): Int32 {
+            if x == 0i32 { 0i32 } else if x == 1i32 { 1i32 }
+            else if y == 2i32 { 2i32 } else if x == 3i32 { 3i32 } else { -1i32 }
+        }",
+    );
+    assert!(!result.iter().any(|inst| matches!(inst, Switch(..))));
+
+    let result = code(
+        "fn f(x: Int32): Int32 {
+            if x == 0i32 { 0i32 } else if x == 1i32 { 1i32 } else if x == 2i32 { 2i32 } else { -1i32 }
+        }",
+    );
+    assert!(!result.iter().any(|inst| matches!(inst, Switch(..))));
+}
+
 #[test]
 fn gen_vec_load() {
     gen_fct(
@@ -3972,6 +4313,51 @@ fn gen_trait_object() {
     );
 }
 
+#[test]
+fn gen_trait_object_downcast() {
+    gen_fct(
+        "
+        trait Foo { fn bar(): Int32; }
+        class Bar
+        impl Foo for Bar {
+            fn bar(): Int32 { 1i32 }
+        }
+        fn f(x: Foo): Bar { x as Bar }
+    ",
+        |sa, code, fct| {
+            let trait_id = sa.trait_by_name("Foo");
+            let cls_id = sa.cls_by_name("Bar");
+            let object_ty = SourceType::Class(cls_id, SourceTypeArray::empty());
+            let expected = vec![CastTraitObject(r(1), ConstPoolIdx(0), r(0)), Ret(r(1))];
+            assert_eq!(expected, code);
+
+            assert_eq!(
+                fct.const_pool(ConstPoolIdx(0)),
+                &ConstPoolEntry::Trait(
+                    TraitId(trait_id.0),
+                    BytecodeTypeArray::empty(),
+                    bty_from_ty(object_ty)
+                )
+            );
+        },
+    );
+
+    gen_fct(
+        "
+        trait Foo { fn bar(): Int32; }
+        class Bar
+        impl Foo for Bar {
+            fn bar(): Int32 { 1i32 }
+        }
+        fn f(x: Foo): Bool { x is Bar }
+    ",
+        |_sa, code, _fct| {
+            let expected = vec![TestTraitObject(r(1), ConstPoolIdx(0), r(0)), Ret(r(1))];
+            assert_eq!(expected, code);
+        },
+    );
+}
+
 #[test]
 fn gen_trait_object_copy() {
     gen(
@@ -4010,6 +4396,33 @@ fn gen_trait_object_method_call() {
     );
 }
 
+#[test]
+fn gen_generic_trait_object_method_call() {
+    gen_fct(
+        "
+        trait Foo[T] { fn bar(): T; }
+        fn f(x: Foo[Int32]): Int32 { x.bar() }
+    ",
+        |sa, code, fct| {
+            let fct_id = sa.trait_method_by_name("Foo", "bar");
+            let expected = vec![
+                PushRegister(r(0)),
+                InvokeVirtual(r(1), ConstPoolIdx(0)),
+                Ret(r(1)),
+            ];
+            assert_eq!(expected, code);
+
+            assert_eq!(
+                fct.const_pool(ConstPoolIdx(0)),
+                &ConstPoolEntry::Fct(
+                    FunctionId(fct_id.0 as u32),
+                    BytecodeTypeArray::one(BytecodeType::Int32)
+                )
+            );
+        },
+    );
+}
+
 #[test]
 fn gen_new_lambda() {
     gen_fct(
@@ -4114,6 +4527,40 @@ fn gen_invoke_lambda() {
     );
 }
 
+#[test]
+fn gen_try_catch() {
+    gen_fct(
+        "
+        class Foo
+        fn g(): Int64 { throw Foo(); }
+        fn f(): Int64 {
+            try { g() } catch e: Foo { 1 }
+        }
+    ",
+        |sa, code, fct| {
+            let fct_id = sa.fct_by_name("g").expect("g missing");
+            let expected = vec![
+                InvokeStatic(r(0), ConstPoolIdx(0)),
+                Jump(3),
+                ConstInt64(r(0), 1),
+                Ret(r(0)),
+            ];
+            assert_eq!(expected, code);
+            assert_eq!(
+                fct.const_pool(ConstPoolIdx(0)),
+                &ConstPoolEntry::Fct(FunctionId(fct_id.0 as u32), BytecodeTypeArray::empty())
+            );
+
+            let handlers = fct.exception_handlers();
+            assert_eq!(1, handlers.len());
+            assert_eq!(0, handlers[0].start.to_u32());
+            assert_eq!(3, handlers[0].end.to_u32());
+            assert_eq!(5, handlers[0].handler.to_u32());
+            assert_eq!(r(1), handlers[0].register);
+        },
+    );
+}
+
 fn r(val: usize) -> Register {
     Register(val)
 }
@@ -4121,7 +4568,9 @@ fn r(val: usize) -> Register {
 #[derive(PartialEq, Debug)]
 pub enum Bytecode {
     Add(Register, Register, Register),
+    AddSaturating(Register, Register, Register),
     Sub(Register, Register, Register),
+    SubSaturating(Register, Register, Register),
     Neg(Register, Register),
     Mul(Register, Register, Register),
     Div(Register, Register, Register),
@@ -4170,6 +4619,7 @@ pub enum Bytecode {
     Jump(usize),
     JumpIfFalse(Register, usize),
     JumpIfTrue(Register, usize),
+    Switch(Register, i64, Vec<usize>),
 
     InvokeDirect(Register, ConstPoolIdx),
     InvokeVirtual(Register, ConstPoolIdx),
@@ -4191,7 +4641,14 @@ pub enum Bytecode {
 
     LoadArray(Register, Register, Register),
     StoreArray(Register, Register, Register),
+    ArrayFill(Register, Register, Register),
+
+    TestTraitObject(Register, ConstPoolIdx, Register),
+    CastTraitObject(Register, ConstPoolIdx, Register),
+
+    CheckedCast(Register, Register),
 
+    Throw(Register),
     Ret(Register),
 }
 
@@ -4208,6 +4665,7 @@ struct BytecodeArrayBuilder<'a> {
     offset_to_index: HashMap<BytecodeOffset, usize>,
     pc: BytecodeOffset,
     jumps: Vec<(usize, BytecodeOffset)>,
+    switch_jumps: Vec<(usize, usize, BytecodeOffset)>,
 }
 
 impl<'a> BytecodeArrayBuilder<'a> {
@@ -4219,6 +4677,7 @@ impl<'a> BytecodeArrayBuilder<'a> {
             next_idx: 0,
             pc: BytecodeOffset(0),
             jumps: Vec::new(),
+            switch_jumps: Vec::new(),
         }
     }
 
@@ -4242,6 +4701,17 @@ impl<'a> BytecodeArrayBuilder<'a> {
                 _ => unreachable!(),
             }
         }
+
+        let switch_jumps = mem::replace(&mut self.switch_jumps, Vec::new());
+
+        for (location, slot, target) in switch_jumps {
+            let &idx = self.offset_to_index.get(&target).expect("offset not found");
+
+            match &mut self.code[location] {
+                Bytecode::Switch(_, _, ref mut targets) => targets[slot] = idx,
+                _ => unreachable!(),
+            }
+        }
     }
 
     fn emit(&mut self, inst: Bytecode) {
@@ -4260,10 +4730,18 @@ impl<'a> BytecodeVisitor for BytecodeArrayBuilder<'a> {
         self.emit(Bytecode::Add(dest, lhs, rhs));
     }
 
+    fn visit_add_saturating(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.emit(Bytecode::AddSaturating(dest, lhs, rhs));
+    }
+
     fn visit_sub(&mut self, dest: Register, lhs: Register, rhs: Register) {
         self.emit(Bytecode::Sub(dest, lhs, rhs));
     }
 
+    fn visit_sub_saturating(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.emit(Bytecode::SubSaturating(dest, lhs, rhs));
+    }
+
     fn visit_neg(&mut self, dest: Register, src: Register) {
         self.emit(Bytecode::Neg(dest, src));
     }
@@ -4425,6 +4903,20 @@ impl<'a> BytecodeVisitor for BytecodeArrayBuilder<'a> {
         let value = self.bc.const_pool(idx).to_int32().expect("int expected");
         self.visit_jump_if_true(opnd, value as u32);
     }
+    fn visit_switch(&mut self, opnd: Register, idx: ConstPoolIdx) {
+        let (low, targets) = self
+            .bc
+            .const_pool(idx)
+            .to_jump_table()
+            .expect("jump table expected");
+
+        for (slot, &offset) in targets.iter().enumerate() {
+            let offset = BytecodeOffset(self.pc.to_u32() + offset);
+            self.switch_jumps.push((self.next_idx - 1, slot, offset));
+        }
+
+        self.emit(Bytecode::Switch(opnd, low, vec![0; targets.len()]));
+    }
     fn visit_jump_loop(&mut self, offset: u32) {
         let offset = BytecodeOffset(self.pc.to_u32() - offset);
         let &idx = self.offset_to_index.get(&offset).expect("offset not found");
@@ -4504,6 +4996,26 @@ impl<'a> BytecodeVisitor for BytecodeArrayBuilder<'a> {
         self.emit(Bytecode::StoreArray(src, arr, idx));
     }
 
+    fn visit_array_fill(&mut self, src: Register, arr: Register, length: Register) {
+        self.emit(Bytecode::ArrayFill(src, arr, length));
+    }
+
+    fn visit_test_trait_object(&mut self, dest: Register, idx: ConstPoolIdx, src: Register) {
+        self.emit(Bytecode::TestTraitObject(dest, idx, src));
+    }
+
+    fn visit_cast_trait_object(&mut self, dest: Register, idx: ConstPoolIdx, src: Register) {
+        self.emit(Bytecode::CastTraitObject(dest, idx, src));
+    }
+
+    fn visit_checked_cast(&mut self, dest: Register, src: Register) {
+        self.emit(Bytecode::CheckedCast(dest, src));
+    }
+
+    fn visit_throw(&mut self, opnd: Register) {
+        self.emit(Bytecode::Throw(opnd));
+    }
+
     fn visit_ret(&mut self, opnd: Register) {
         self.emit(Bytecode::Ret(opnd));
     }
@@ -1,13 +1,20 @@
 use std::collections::{HashMap, HashSet};
 
 use crate::language::error::msg::ErrorMessage;
-use crate::language::sem_analysis::SemAnalysis;
+use crate::language::sem_analysis::{
+    FctDefinition, FctDefinitionId, FctParent, ImplDefinition, SemAnalysis, TypeParamId,
+};
+use crate::language::specialize::replace_type_param;
+use crate::language::ty::{SourceType, SourceTypeArray};
 
 pub fn check(sa: &mut SemAnalysis) {
     for impl_ in sa.impls.iter() {
+        let mut defaults = Vec::new();
+
         let impl_for = {
             let impl_ = impl_.read();
             let trait_ = sa.traits[impl_.trait_id()].read();
+            let trait_type_params = impl_.trait_ty.type_params();
 
             let all: HashSet<_> = trait_.methods.iter().cloned().collect();
             let mut defined = HashSet::new();
@@ -17,13 +24,19 @@ pub fn check(sa: &mut SemAnalysis) {
                 let method = sa.fcts.idx(method_id);
                 let method = method.read();
 
-                if let Some(fid) = trait_.find_method_with_replace(
-                    sa,
-                    method.is_static,
-                    method.name,
-                    Some(impl_.extended_ty.clone()),
-                    method.params_without_self(),
-                ) {
+                if let Some(fid) = trait_
+                    .find_method_with_replace(
+                        sa,
+                        method.is_static,
+                        method.name,
+                        Some(impl_.extended_ty.clone()),
+                        &trait_type_params,
+                        method.params_without_self(),
+                    )
+                    .filter(|&fid| {
+                        sa.fcts.idx(fid).read().is_associated_const == method.is_associated_const
+                    })
+                {
                     defined.insert(fid);
                     impl_for.insert(fid, method_id);
 
@@ -33,8 +46,15 @@ pub fn check(sa: &mut SemAnalysis) {
                     let return_type_valid = method.return_type
                         == if trait_method.return_type.is_self() {
                             impl_.extended_ty.clone()
-                        } else {
+                        } else if trait_type_params.is_empty() {
                             trait_method.return_type.clone()
+                        } else {
+                            replace_type_param(
+                                sa,
+                                trait_method.return_type.clone(),
+                                &trait_type_params,
+                                None,
+                            )
                         };
 
                     if !return_type_valid {
@@ -55,7 +75,9 @@ pub fn check(sa: &mut SemAnalysis) {
                     let mtd_name = sa.interner.str(method.name).to_string();
                     let trait_name = sa.interner.str(trait_.name).to_string();
 
-                    let msg = if method.is_static {
+                    let msg = if method.is_associated_const {
+                        ErrorMessage::ConstNotInTrait(trait_name, mtd_name)
+                    } else if method.is_static {
                         ErrorMessage::StaticMethodNotInTrait(trait_name, mtd_name, args)
                     } else {
                         ErrorMessage::MethodNotInTrait(trait_name, mtd_name, args)
@@ -70,8 +92,10 @@ pub fn check(sa: &mut SemAnalysis) {
                 let method = method.read();
 
                 if method.has_body() {
-                    // method has a default implementation, use that one
-                    impl_for.insert(method_id, method_id);
+                    // method has a default implementation, the impl gets its own copy
+                    let default_id = instantiate_default_method(sa, &impl_, &method);
+                    impl_for.insert(method_id, default_id);
+                    defaults.push((method.name, method.is_static, default_id));
                     continue;
                 }
 
@@ -83,7 +107,9 @@ pub fn check(sa: &mut SemAnalysis) {
                 let mtd_name = sa.interner.str(method.name).to_string();
                 let trait_name = sa.interner.str(trait_.name).to_string();
 
-                let msg = if method.is_static {
+                let msg = if method.is_associated_const {
+                    ErrorMessage::ConstMissingFromTrait(trait_name, mtd_name)
+                } else if method.is_static {
                     ErrorMessage::StaticMethodMissingFromTrait(trait_name, mtd_name, args)
                 } else {
                     ErrorMessage::MethodMissingFromTrait(trait_name, mtd_name, args)
@@ -95,10 +121,72 @@ pub fn check(sa: &mut SemAnalysis) {
             impl_for
         };
 
-        impl_.write().impl_for = impl_for;
+        let mut impl_ = impl_.write();
+        impl_.impl_for = impl_for;
+
+        for (name, is_static, default_id) in defaults {
+            impl_.methods.push(default_id);
+
+            if is_static {
+                impl_.static_names.insert(name, default_id);
+            } else {
+                impl_.instance_names.insert(name, default_id);
+            }
+        }
     }
 }
 
+// Creates the method of the impl for a default method of the trait. The
+// body is shared with the trait, but is type checked and compiled with
+// `Self` and the type params of the trait replaced by the types of the impl.
+fn instantiate_default_method(
+    sa: &SemAnalysis,
+    impl_: &ImplDefinition,
+    trait_method: &FctDefinition,
+) -> FctDefinitionId {
+    let mut fct = FctDefinition::new(
+        trait_method.package_id,
+        trait_method.module_id,
+        trait_method.file_id,
+        &trait_method.ast,
+        FctParent::Impl(impl_.id()),
+    );
+
+    fct.type_params.append(impl_.type_params());
+    fct.container_type_params = fct.type_params.len();
+
+    // Type params of the method itself follow the ones of the impl.
+    let mut replacement = impl_.trait_ty.type_params().types().to_vec();
+
+    for idx in trait_method.container_type_params..trait_method.type_params.len() {
+        let name = trait_method.type_params.name(TypeParamId(idx));
+        let id = fct.type_params.add_type_param(name);
+        replacement.push(SourceType::TypeParam(id));
+    }
+
+    let replacement = SourceTypeArray::with(replacement);
+    let replace =
+        |ty: SourceType| replace_type_param(sa, ty, &replacement, Some(impl_.extended_ty.clone()));
+
+    for bound in trait_method.type_params.bounds() {
+        if let SourceType::TypeParam(id) = replace(bound.ty.clone()) {
+            fct.type_params
+                .add_bound(id, replace(bound.trait_ty.clone()));
+        }
+    }
+
+    fct.param_types = trait_method
+        .param_types
+        .iter()
+        .map(|ty| replace(ty.clone()))
+        .collect();
+    fct.return_type = replace(trait_method.return_type.clone());
+    fct.is_variadic = trait_method.is_variadic;
+    fct.initialized = true;
+
+    sa.add_fct(fct)
+}
+
 #[cfg(test)]
 mod tests {
     use crate::language::error::msg::ErrorMessage;
@@ -192,6 +280,33 @@ mod tests {
         );
     }
 
+    #[test]
+    fn impl_generic_trait() {
+        ok("
+            trait Foo[T] {
+                fn get(): T;
+                fn set(value: T);
+            }
+            class A
+            impl Foo[Int32] for A {
+                fn get(): Int32 { 0i32 }
+                fn set(value: Int32) {}
+            }");
+
+        err(
+            "
+            trait Foo[T] {
+                fn get(): T;
+            }
+            class A
+            impl Foo[Int32] for A {
+                fn get(): String { \"\" }
+            }",
+            (7, 17),
+            ErrorMessage::ReturnTypeMismatch("String".into(), "T".into()),
+        );
+    }
+
     #[test]
     fn impl_method_with_default_body() {
         ok("
@@ -201,4 +316,78 @@ mod tests {
             class Bar {}
             impl Foo for Bar {}");
     }
+
+    #[test]
+    fn impl_inherits_default_method() {
+        ok("
+            trait Foo {
+                fn name(): String;
+                fn greet(): String { \"hello \" + self.name() }
+                fn me(): Self { self }
+            }
+            class Bar
+            impl Foo for Bar { fn name(): String { \"bar\" } }
+            struct Baz(value: Int32)
+            impl Foo for Baz {
+                fn name(): String { \"baz\" }
+                fn greet(): String { \"hi\" }
+            }
+            fn f(bar: Bar, baz: Baz): String { bar.me().greet() + baz.greet() }");
+
+        err(
+            "
+            trait Foo {
+                fn name(): String;
+                fn greet(): String { \"hello \" + self.name() }
+            }
+            class Bar
+            impl Foo for Bar {}",
+            (7, 13),
+            ErrorMessage::MethodMissingFromTrait("Foo".into(), "name".into(), vec![]),
+        );
+
+        err(
+            "
+            trait Foo {
+                fn greet(): String { self.name() }
+            }",
+            (3, 38),
+            ErrorMessage::UnknownMethod("Self".into(), "name".into(), vec![]),
+        );
+    }
+
+    #[test]
+    fn impl_associated_const() {
+        ok("
+            trait Foo { const MAX: Int32; }
+            class Bar
+            impl Foo for Bar { const MAX: Int32 = 1i32; }");
+
+        err(
+            "
+            trait Foo { const MAX: Int32; }
+            class Bar
+            impl Foo for Bar {}",
+            (4, 13),
+            ErrorMessage::ConstMissingFromTrait("Foo".into(), "MAX".into()),
+        );
+
+        err(
+            "
+            trait Foo {}
+            class Bar
+            impl Foo for Bar { const MAX: Int32 = 1i32; }",
+            (4, 32),
+            ErrorMessage::ConstNotInTrait("Foo".into(), "MAX".into()),
+        );
+
+        err(
+            "
+            trait Foo { const MAX: Int32; }
+            class Bar
+            impl Foo for Bar { const MAX: Int64 = 1i64; }",
+            (4, 32),
+            ErrorMessage::ReturnTypeMismatch("Int64".into(), "Int32".into()),
+        );
+    }
 }
@@ -123,6 +123,10 @@ fn create_functions(sa: &SemAnalysis) -> Vec<FunctionData> {
             Some(InternalFunction::BootsCompile)
         } else if fct.id() == sa.known.functions.stacktrace_retrieve() {
             Some(InternalFunction::StacktraceRetrieve)
+        } else if fct.id() == sa.known.functions.throwable_new() {
+            Some(InternalFunction::ThrowableNew)
+        } else if fct.id() == sa.known.functions.finalize() {
+            Some(InternalFunction::Finalize)
         } else {
             None
         };
@@ -156,6 +160,9 @@ fn create_functions(sa: &SemAnalysis) -> Vec<FunctionData> {
             intrinsic: fct.intrinsic,
             internal: internal_function,
             is_test: fct.is_test,
+            expected_trap: fct
+                .expected_trap
+                .map(|name| sa.interner.str(name).to_string()),
             vtable_index: fct.vtable_index,
             is_optimize_immediately: fct.is_optimize_immediately,
             is_variadic: fct.is_variadic,
@@ -200,6 +207,8 @@ fn create_classes(sa: &SemAnalysis) -> Vec<ClassData> {
             Some(InternalClass::Thread)
         } else if class.id() == sa.known.classes.stacktrace_element() {
             Some(InternalClass::StacktraceElement)
+        } else if class.id() == sa.known.classes.weak_ref() {
+            Some(InternalClass::WeakRef)
         } else {
             None
         };
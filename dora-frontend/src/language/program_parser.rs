@@ -366,6 +366,11 @@ impl<'a> ProgramParser<'a> {
             );
         }
 
+        self.sa
+            .diag
+            .lock()
+            .add_suppressions(file_id, &ast.suppressions);
+
         self.scan_file(
             package_id,
             module_id,
@@ -453,7 +458,7 @@ impl<'x> visit::Visitor for TopLevelDeclaration<'x> {
         let trait_ = TraitDefinition::new(self.package_id, self.module_id, self.file_id, node);
         let trait_id = self.sa.traits.push(trait_);
 
-        find_methods_in_trait(self.sa, trait_id, node);
+        find_methods_in_trait(self.sa, trait_id, &self.id_generator, node);
 
         let sym = Sym::Trait(trait_id);
         if let Some(sym) = self.insert(node.name, sym) {
@@ -483,7 +488,7 @@ impl<'x> visit::Visitor for TopLevelDeclaration<'x> {
             let impl_ = ImplDefinition::new(self.package_id, self.module_id, self.file_id, node);
             let impl_id = self.sa.impls.push(impl_);
 
-            find_methods_in_impl(self.sa, impl_id, node);
+            find_methods_in_impl(self.sa, impl_id, &self.id_generator, node);
         } else {
             let extension =
                 ExtensionDefinition::new(self.package_id, self.module_id, self.file_id, node);
@@ -571,6 +576,7 @@ impl<'x> visit::Visitor for TopLevelDeclaration<'x> {
 fn find_methods_in_trait(
     sa: &mut SemAnalysis,
     trait_id: TraitDefinitionId,
+    id_generator: &NodeIdGenerator,
     node: &Arc<ast::Trait>,
 ) {
     let trait_ = sa.traits.idx(trait_id);
@@ -588,9 +594,28 @@ fn find_methods_in_trait(
         let fct_id = sa.add_fct(fct);
         trait_.methods.push(fct_id);
     }
+
+    for const_node in &node.consts {
+        let mut fct = FctDefinition::new(
+            trait_.package_id,
+            trait_.module_id,
+            trait_.file_id,
+            &generate_function_for_associated_const(id_generator, const_node),
+            FctParent::Trait(trait_id),
+        );
+        fct.is_associated_const = true;
+
+        let fct_id = sa.add_fct(fct);
+        trait_.methods.push(fct_id);
+    }
 }
 
-fn find_methods_in_impl(sa: &mut SemAnalysis, impl_id: ImplDefinitionId, node: &Arc<ast::Impl>) {
+fn find_methods_in_impl(
+    sa: &mut SemAnalysis,
+    impl_id: ImplDefinitionId,
+    id_generator: &NodeIdGenerator,
+    node: &Arc<ast::Impl>,
+) {
     let impl_ = sa.impls.idx(impl_id);
     let mut impl_ = impl_.write();
 
@@ -606,6 +631,43 @@ fn find_methods_in_impl(sa: &mut SemAnalysis, impl_id: ImplDefinitionId, node: &
         let fct_id = sa.add_fct(fct);
         impl_.methods.push(fct_id);
     }
+
+    for const_node in &node.consts {
+        let mut fct = FctDefinition::new(
+            impl_.package_id,
+            impl_.module_id,
+            impl_.file_id,
+            &generate_function_for_associated_const(id_generator, const_node),
+            FctParent::Impl(impl_id),
+        );
+        fct.is_associated_const = true;
+
+        let fct_id = sa.add_fct(fct);
+        impl_.methods.push(fct_id);
+    }
+}
+
+// Associated consts are lowered to static getters, so that `T::CONST`
+// dispatches like any other static trait method.
+fn generate_function_for_associated_const(
+    id_generator: &NodeIdGenerator,
+    node: &Arc<ast::Const>,
+) -> Arc<ast::Function> {
+    let builder = Builder::new();
+    let mut fct = builder.build_fct(node.name);
+    fct.span(node.span)
+        .is_static(true)
+        .return_type(node.data_type.clone());
+
+    if let Some(ref expr) = node.expr {
+        let mut block = builder.build_block();
+        block.expr(expr.clone());
+        let mut block = block.build(id_generator.next());
+        block.span = expr.span();
+        fct.block(block);
+    }
+
+    Arc::new(fct.build(id_generator.next()))
 }
 
 fn find_methods_in_extension(
@@ -326,6 +326,9 @@ fn verify_type_basic(
 
         SourceType::Bool
         | SourceType::UInt8
+        | SourceType::UInt16
+        | SourceType::UInt32
+        | SourceType::UInt64
         | SourceType::Char
         | SourceType::Int32
         | SourceType::Int64
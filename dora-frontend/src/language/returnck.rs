@@ -1,14 +1,28 @@
+use std::sync::Arc;
+
+use dora_parser::ast::visit::{self, Visitor};
 use dora_parser::ast::*;
+use dora_parser::interner::Name;
 use dora_parser::Span;
 
 pub fn returns_value(s: &Stmt) -> Result<(), Span> {
     match *s {
         Stmt::Return(_) => Ok(()),
+        Stmt::Throw(_) => Ok(()),
         Stmt::For(ref stmt) => Err(stmt.span),
         Stmt::While(ref stmt) => Err(stmt.span),
+        Stmt::DoWhile(ref stmt) => Err(stmt.span),
+        Stmt::Loop(ref stmt) => {
+            if loop_breaks(stmt) {
+                Err(stmt.span)
+            } else {
+                Ok(())
+            }
+        }
         Stmt::Break(ref stmt) => Err(stmt.span),
         Stmt::Continue(ref stmt) => Err(stmt.span),
         Stmt::Let(ref stmt) => Err(stmt.span),
+        Stmt::Fct(ref fct) => Err(fct.span),
         Stmt::Expr(ref stmt) => expr_returns_value(&stmt.expr),
     }
 }
@@ -17,6 +31,7 @@ pub fn expr_returns_value(e: &Expr) -> Result<(), Span> {
     match *e {
         Expr::Block(ref block) => expr_block_returns_value(block),
         Expr::If(ref expr) => expr_if_returns_value(expr),
+        Expr::TryCatch(ref expr) => expr_try_catch_returns_value(expr),
         _ => Err(e.span()),
     }
 }
@@ -47,6 +62,50 @@ fn expr_if_returns_value(e: &ExprIfType) -> Result<(), Span> {
     }
 }
 
+fn expr_try_catch_returns_value(e: &ExprTryCatchType) -> Result<(), Span> {
+    expr_returns_value(&e.try_block)?;
+    expr_returns_value(&e.catch_block)
+}
+
+// A `loop` can only be left through a `break` targeting it, either an unlabeled
+// `break` outside of nested loops or one naming the loop's label.
+fn loop_breaks(stmt: &StmtLoopType) -> bool {
+    let mut finder = BreakFinder {
+        label: stmt.label,
+        depth: 0,
+        found: false,
+    };
+    finder.visit_stmt(&stmt.block);
+    finder.found
+}
+
+struct BreakFinder {
+    label: Option<Name>,
+    depth: usize,
+    found: bool,
+}
+
+impl Visitor for BreakFinder {
+    fn visit_fct(&mut self, _f: &Arc<Function>) {
+        // breaks in lambdas can't leave the loop
+    }
+
+    fn visit_stmt(&mut self, s: &Stmt) {
+        match *s {
+            Stmt::Break(ref stmt) => match stmt.label {
+                Some(label) => self.found |= self.label == Some(label),
+                None => self.found |= self.depth == 0,
+            },
+            Stmt::For(_) | Stmt::While(_) | Stmt::Loop(_) | Stmt::DoWhile(_) => {
+                self.depth += 1;
+                visit::walk_stmt(self, s);
+                self.depth -= 1;
+            }
+            _ => visit::walk_stmt(self, s),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::language::error::msg::ErrorMessage;
@@ -57,6 +116,7 @@ mod tests {
         ok("fn f() {}");
         ok("fn f() { if true { return; } }");
         ok("fn f() { while true { return; } }");
+        ok("fn f() { loop { break; } }");
     }
 
     #[test]
@@ -83,5 +143,12 @@ mod tests {
         );
         ok("fn f(): Int32 { if true { return 1; } else { return 2; } }");
         ok("fn f(): Int32 { return 1; 1+2; }");
+        ok("fn f(): Int32 { throw std::Throwable::new(\"fail\"); }");
+        ok("fn f(): Int32 { try { return 1; } catch e: std::Throwable { return 2; } }");
+        err(
+            "fn f(): Int32 { try { return 1; } catch e: std::Throwable { } }",
+            (1, 15),
+            ErrorMessage::ReturnType("Int32".into(), "()".into()),
+        );
     }
 }
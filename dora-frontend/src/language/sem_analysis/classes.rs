@@ -516,23 +516,47 @@ impl TypeParamId {
 #[derive(Copy, Clone, Debug)]
 pub enum Visibility {
     Public,
+    Restricted(ast::VisibilityRestriction),
     Module,
 }
 
 impl Visibility {
     pub fn from_ast(visibility: ast::Visibility) -> Visibility {
-        if visibility.is_public() {
-            Visibility::Public
-        } else {
-            assert!(visibility.is_default());
-            Visibility::Module
+        match visibility {
+            ast::Visibility::Public => Visibility::Public,
+            ast::Visibility::Restricted(restriction) => Visibility::Restricted(restriction),
+            ast::Visibility::Default => Visibility::Module,
         }
     }
 
     pub fn is_public(&self) -> bool {
         match self {
             Visibility::Public => true,
+            Visibility::Restricted(_) | Visibility::Module => false,
+        }
+    }
+
+    // Public or restricted, `accessible_from` decides whether the restriction
+    // allows a given user.
+    pub fn is_exported(&self) -> bool {
+        match self {
+            Visibility::Public | Visibility::Restricted(_) => true,
             Visibility::Module => false,
         }
     }
+
+    // Visibility of a member, which can't be more visible than its owner.
+    pub fn member(owner: Visibility, member: Visibility) -> Visibility {
+        match (owner, member) {
+            (Visibility::Module, _) | (_, Visibility::Module) => Visibility::Module,
+            (Visibility::Public, visibility) | (visibility, Visibility::Public) => visibility,
+            (Visibility::Restricted(lhs), Visibility::Restricted(rhs)) => {
+                if lhs == ast::VisibilityRestriction::Module {
+                    Visibility::Restricted(lhs)
+                } else {
+                    Visibility::Restricted(rhs)
+                }
+            }
+        }
+    }
 }
@@ -61,7 +61,7 @@ impl ConstDefinition {
             name: node.name,
             visibility: Visibility::from_ast(node.visibility),
             ty: SourceType::Error,
-            expr: node.expr.clone(),
+            expr: node.expr.clone().expect("missing const value"),
             value: ConstValue::None,
         }
     }
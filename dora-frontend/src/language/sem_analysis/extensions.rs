@@ -251,9 +251,13 @@ mod matching {
     ) -> bool {
         match check_ty {
             SourceType::Unit
+            | SourceType::Never
             | SourceType::Bool
             | SourceType::Char
             | SourceType::UInt8
+            | SourceType::UInt16
+            | SourceType::UInt32
+            | SourceType::UInt64
             | SourceType::Int32
             | SourceType::Int64
             | SourceType::Float32
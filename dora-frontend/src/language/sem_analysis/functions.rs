@@ -58,13 +58,18 @@ pub struct FctDefinition {
     pub is_static: bool,
     pub visibility: Visibility,
     pub is_test: bool,
+    pub expected_trap: Option<Name>,
     pub internal: bool,
     pub internal_resolved: bool,
     pub param_types: Vec<SourceType>,
     pub return_type: SourceType,
     pub is_constructor: bool,
     pub is_variadic: bool,
+    /// Getter generated for an associated const declared in a trait or impl.
+    pub is_associated_const: bool,
 
+    /// Slot in the trait object vtable, equal to the method's position in
+    /// `TraitDefinition::methods`.
     pub vtable_index: Option<u32>,
     pub initialized: bool,
     pub analysis: Option<AnalysisData>,
@@ -99,12 +104,14 @@ impl FctDefinition {
             visibility: Visibility::from_ast(ast.visibility),
             is_static: ast.is_static,
             is_test: ast.is_test,
+            expected_trap: ast.expected_trap,
             internal: ast.internal,
             internal_resolved: false,
             is_constructor: ast.is_constructor,
             vtable_index: None,
             initialized: false,
             is_variadic: false,
+            is_associated_const: false,
             analysis: None,
             type_params: TypeParamDefinition::new(),
             container_type_params: 0,
@@ -183,6 +190,10 @@ impl FctDefinition {
         self.ast.kind.is_lambda()
     }
 
+    pub fn is_nested(&self) -> bool {
+        self.ast.kind.is_nested()
+    }
+
     pub fn span(&self) -> Span {
         self.span
     }
@@ -294,6 +305,7 @@ pub fn emit_as_bytecode_operation(intrinsic: Intrinsic) -> bool {
     match intrinsic {
         Intrinsic::ArrayNewOfSize
         | Intrinsic::ArrayWithValues
+        | Intrinsic::ArrayFill
         | Intrinsic::ArrayLen
         | Intrinsic::ArrayGet
         | Intrinsic::ArraySet
@@ -311,10 +323,16 @@ pub fn emit_as_bytecode_operation(intrinsic: Intrinsic) -> bool {
         | Intrinsic::Int32ToInt32
         | Intrinsic::EnumEq
         | Intrinsic::EnumNe
+        | Intrinsic::ObjectEq
+        | Intrinsic::ObjectNe
+        | Intrinsic::EnumOrdinal
+        | Intrinsic::EnumValues
         | Intrinsic::Int32Eq
         | Intrinsic::Int32Cmp
         | Intrinsic::Int32Add
+        | Intrinsic::Int32AddSaturating
         | Intrinsic::Int32Sub
+        | Intrinsic::Int32SubSaturating
         | Intrinsic::Int32Mul
         | Intrinsic::Int32Div
         | Intrinsic::Int32Mod
@@ -330,7 +348,9 @@ pub fn emit_as_bytecode_operation(intrinsic: Intrinsic) -> bool {
         | Intrinsic::Int64Eq
         | Intrinsic::Int64Cmp
         | Intrinsic::Int64Add
+        | Intrinsic::Int64AddSaturating
         | Intrinsic::Int64Sub
+        | Intrinsic::Int64SubSaturating
         | Intrinsic::Int64Mul
         | Intrinsic::Int64Div
         | Intrinsic::Int64Mod
@@ -349,6 +369,7 @@ pub fn emit_as_bytecode_operation(intrinsic: Intrinsic) -> bool {
         | Intrinsic::Float32Sub
         | Intrinsic::Float32Mul
         | Intrinsic::Float32Div
+        | Intrinsic::Float32Mod
         | Intrinsic::Float32Plus
         | Intrinsic::Float32Neg
         | Intrinsic::Float32IsNan
@@ -358,6 +379,7 @@ pub fn emit_as_bytecode_operation(intrinsic: Intrinsic) -> bool {
         | Intrinsic::Float64Sub
         | Intrinsic::Float64Mul
         | Intrinsic::Float64Div
+        | Intrinsic::Float64Mod
         | Intrinsic::Float64Plus
         | Intrinsic::Float64Neg
         | Intrinsic::Float64IsNan => true,
@@ -167,11 +167,15 @@ pub fn implements_trait(
     match check_ty {
         SourceType::Tuple(_)
         | SourceType::Unit
+        | SourceType::Never
         | SourceType::Trait(_, _)
         | SourceType::Lambda(_, _) => false,
 
         SourceType::Bool
         | SourceType::UInt8
+        | SourceType::UInt16
+        | SourceType::UInt32
+        | SourceType::UInt64
         | SourceType::Char
         | SourceType::Int32
         | SourceType::Int64
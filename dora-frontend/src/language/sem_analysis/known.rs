@@ -53,6 +53,7 @@ pub struct KnownClasses {
     pub stacktrace_element: Option<ClassDefinitionId>,
     pub thread: Option<ClassDefinitionId>,
     pub lambda: Option<ClassDefinitionId>,
+    pub weak_ref: Option<ClassDefinitionId>,
 }
 
 impl KnownClasses {
@@ -67,6 +68,7 @@ impl KnownClasses {
             stacktrace_element: None,
             thread: None,
             lambda: None,
+            weak_ref: None,
         }
     }
 
@@ -105,12 +107,19 @@ impl KnownClasses {
     pub fn lambda(&self) -> ClassDefinitionId {
         self.lambda.expect("uninitialized")
     }
+
+    pub fn weak_ref(&self) -> ClassDefinitionId {
+        self.weak_ref.expect("uninitialized")
+    }
 }
 
 #[derive(Debug)]
 pub struct KnownStructs {
     pub bool: Option<StructDefinitionId>,
     pub uint8: Option<StructDefinitionId>,
+    pub uint16: Option<StructDefinitionId>,
+    pub uint32: Option<StructDefinitionId>,
+    pub uint64: Option<StructDefinitionId>,
     pub char: Option<StructDefinitionId>,
     pub int32: Option<StructDefinitionId>,
     pub int64: Option<StructDefinitionId>,
@@ -123,6 +132,9 @@ impl KnownStructs {
         KnownStructs {
             bool: None,
             uint8: None,
+            uint16: None,
+            uint32: None,
+            uint64: None,
             char: None,
             int32: None,
             int64: None,
@@ -139,6 +151,18 @@ impl KnownStructs {
         self.uint8.expect("uninitialized")
     }
 
+    pub fn uint16(&self) -> StructDefinitionId {
+        self.uint16.expect("uninitialized")
+    }
+
+    pub fn uint32(&self) -> StructDefinitionId {
+        self.uint32.expect("uninitialized")
+    }
+
+    pub fn uint64(&self) -> StructDefinitionId {
+        self.uint64.expect("uninitialized")
+    }
+
     pub fn char(&self) -> StructDefinitionId {
         self.char.expect("uninitialized")
     }
@@ -167,6 +191,13 @@ pub struct KnownTraits {
     pub stringable: Option<TraitDefinitionId>,
     pub iterator: Option<TraitDefinitionId>,
     pub zero: Option<TraitDefinitionId>,
+    pub finalizable: Option<TraitDefinitionId>,
+    pub add: Option<TraitDefinitionId>,
+    pub sub: Option<TraitDefinitionId>,
+    pub mul: Option<TraitDefinitionId>,
+    pub div: Option<TraitDefinitionId>,
+    pub index: Option<TraitDefinitionId>,
+    pub index_mut: Option<TraitDefinitionId>,
 }
 
 impl KnownTraits {
@@ -177,6 +208,13 @@ impl KnownTraits {
             stringable: None,
             iterator: None,
             zero: None,
+            finalizable: None,
+            add: None,
+            sub: None,
+            mul: None,
+            div: None,
+            index: None,
+            index_mut: None,
         }
     }
 
@@ -199,6 +237,34 @@ impl KnownTraits {
     pub fn zero(&self) -> TraitDefinitionId {
         self.zero.expect("uninitialized")
     }
+
+    pub fn finalizable(&self) -> TraitDefinitionId {
+        self.finalizable.expect("uninitialized")
+    }
+
+    pub fn add(&self) -> TraitDefinitionId {
+        self.add.expect("uninitialized")
+    }
+
+    pub fn sub(&self) -> TraitDefinitionId {
+        self.sub.expect("uninitialized")
+    }
+
+    pub fn mul(&self) -> TraitDefinitionId {
+        self.mul.expect("uninitialized")
+    }
+
+    pub fn div(&self) -> TraitDefinitionId {
+        self.div.expect("uninitialized")
+    }
+
+    pub fn index(&self) -> TraitDefinitionId {
+        self.index.expect("uninitialized")
+    }
+
+    pub fn index_mut(&self) -> TraitDefinitionId {
+        self.index_mut.expect("uninitialized")
+    }
 }
 
 #[derive(Debug)]
@@ -246,7 +312,9 @@ pub struct KnownFunctions {
     pub option_is_none: Option<FctDefinitionId>,
     pub option_unwrap: Option<FctDefinitionId>,
     pub stacktrace_retrieve: Option<FctDefinitionId>,
+    pub throwable_new: Option<FctDefinitionId>,
     pub compile: Option<FctDefinitionId>,
+    pub finalize: Option<FctDefinitionId>,
 }
 
 impl KnownFunctions {
@@ -260,7 +328,9 @@ impl KnownFunctions {
             option_is_some: None,
             option_unwrap: None,
             stacktrace_retrieve: None,
+            throwable_new: None,
             compile: None,
+            finalize: None,
         }
     }
 
@@ -296,9 +366,17 @@ impl KnownFunctions {
         self.stacktrace_retrieve.expect("uninitialized")
     }
 
+    pub fn throwable_new(&self) -> FctDefinitionId {
+        self.throwable_new.expect("uninitialized")
+    }
+
     pub fn compile(&self) -> FctDefinitionId {
         self.compile.expect("uninitialized")
     }
+
+    pub fn finalize(&self) -> FctDefinitionId {
+        self.finalize.expect("uninitialized")
+    }
 }
 
 impl KnownElements {
@@ -6,7 +6,7 @@ use dora_bytecode::Intrinsic;
 use dora_parser::ast;
 
 use crate::language::sem_analysis::{
-    ClassDefinitionId, ConstDefinitionId, EnumDefinitionId, FctDefinitionId, FieldId,
+    ClassDefinitionId, ConstDefinitionId, ConstValue, EnumDefinitionId, FctDefinitionId, FieldId,
     GlobalDefinitionId, StructDefinitionFieldId, StructDefinitionId, TraitDefinitionId,
     TypeParamId,
 };
@@ -21,7 +21,8 @@ pub struct AnalysisData {
     pub map_cls: NodeMap<ClassDefinitionId>,
     pub map_fors: NodeMap<ForTypeInfo>,
     pub map_lambdas: NodeMap<FctDefinitionId>,
-    pub vars: VarAccess, // variables in functions
+    pub map_consts: NodeMap<ConstValue>, // folded constant expressions
+    pub vars: VarAccess,                 // variables in functions
     pub context_cls_id: Option<ClassDefinitionId>,
     pub context_has_outer_context_slot: Option<bool>,
     pub outer_context_access: Option<bool>,
@@ -37,6 +38,7 @@ impl AnalysisData {
             map_cls: NodeMap::new(),
             map_fors: NodeMap::new(),
             map_lambdas: NodeMap::new(),
+            map_consts: NodeMap::new(),
 
             vars: VarAccess::empty(),
             context_cls_id: None,
@@ -198,7 +200,11 @@ pub struct ContextIdx(pub usize);
 #[derive(Debug, Clone)]
 pub struct ForTypeInfo {
     pub make_iterator: Option<FctDefinitionId>,
+    // type arguments of the trait bound, when makeIterator is called on a type param
+    pub make_iterator_type_params: SourceTypeArray,
     pub next: FctDefinitionId,
+    // type arguments of the trait bound, when next is called on a type param
+    pub next_type_params: SourceTypeArray,
     pub iterator_type: SourceType,
     pub next_type: SourceType,
     pub value_type: SourceType,
@@ -12,6 +12,7 @@ use crate::language::sem_analysis::{
     module_path, FctDefinitionId, ModuleDefinitionId, PackageDefinitionId, SemAnalysis,
     SourceFileId, TypeParamDefinition, Visibility,
 };
+use crate::language::specialize::replace_type_param;
 use crate::language::ty::{SourceType, SourceTypeArray};
 use crate::Id;
 
@@ -52,6 +53,9 @@ pub struct TraitDefinition {
     pub name: Name,
     pub is_trait_object: bool,
     pub type_params: Option<TypeParamDefinition>,
+    /// Methods in source declaration order. Vtable slots are assigned in
+    /// this order, so a method's slot only depends on its position in the
+    /// trait body.
     pub methods: Vec<FctDefinitionId>,
     pub instance_names: HashMap<Name, FctDefinitionId>,
     pub static_names: HashMap<Name, FctDefinitionId>,
@@ -133,6 +137,7 @@ impl TraitDefinition {
         is_static: bool,
         name: Name,
         replace: Option<SourceType>,
+        type_params: &SourceTypeArray,
         args: &[SourceType],
     ) -> Option<FctDefinitionId> {
         for &method in &self.methods {
@@ -141,7 +146,13 @@ impl TraitDefinition {
 
             if method.name == name
                 && method.is_static == is_static
-                && params_match(replace.clone(), method.params_without_self(), args)
+                && params_match(
+                    sa,
+                    replace.clone(),
+                    type_params,
+                    method.params_without_self(),
+                    args,
+                )
             {
                 return Some(method.id());
             }
@@ -152,7 +163,9 @@ impl TraitDefinition {
 }
 
 fn params_match(
+    sa: &SemAnalysis,
     replace: Option<SourceType>,
+    type_params: &SourceTypeArray,
     trait_args: &[SourceType],
     args: &[SourceType],
 ) -> bool {
@@ -161,7 +174,12 @@ fn params_match(
     }
 
     for (ind, ty) in trait_args.iter().enumerate() {
-        let ty = ty.clone();
+        // substitute the type arguments of generic traits
+        let ty = if type_params.is_empty() {
+            ty.clone()
+        } else {
+            replace_type_param(sa, ty.clone(), type_params, None)
+        };
         let other = args[ind].clone();
 
         let found = if ty.is_self() {
@@ -92,7 +92,11 @@ pub fn replace_type_param(
         }
 
         SourceType::Unit
+        | SourceType::Never
         | SourceType::UInt8
+        | SourceType::UInt16
+        | SourceType::UInt32
+        | SourceType::UInt64
         | SourceType::Bool
         | SourceType::Char
         | SourceType::Int32
@@ -51,6 +51,24 @@ pub fn resolve_internal_classes(sa: &mut SemAnalysis) {
         "primitives::UInt8",
         Some(SourceType::UInt8),
     ));
+    sa.known.structs.uint16 = Some(internal_struct(
+        sa,
+        stdlib_id,
+        "primitives::UInt16",
+        Some(SourceType::UInt16),
+    ));
+    sa.known.structs.uint32 = Some(internal_struct(
+        sa,
+        stdlib_id,
+        "primitives::UInt32",
+        Some(SourceType::UInt32),
+    ));
+    sa.known.structs.uint64 = Some(internal_struct(
+        sa,
+        stdlib_id,
+        "primitives::UInt64",
+        Some(SourceType::UInt64),
+    ));
     sa.known.structs.char = Some(internal_struct(
         sa,
         stdlib_id,
@@ -102,11 +120,19 @@ pub fn resolve_internal_classes(sa: &mut SemAnalysis) {
 
     sa.known.classes.stacktrace = Some(find_class(sa, stdlib_id, "Stacktrace"));
     sa.known.classes.stacktrace_element = Some(find_class(sa, stdlib_id, "StacktraceElement"));
+    sa.known.classes.weak_ref = Some(find_class(sa, stdlib_id, "WeakRef"));
     sa.known.classes.thread = Some(find_class(sa, stdlib_id, "thread::Thread"));
 
     sa.known.traits.stringable = Some(find_trait(sa, stdlib_id, "string::Stringable"));
     sa.known.traits.zero = Some(find_trait(sa, stdlib_id, "traits::Zero"));
     sa.known.traits.iterator = Some(find_trait(sa, stdlib_id, "traits::Iterator"));
+    sa.known.traits.finalizable = Some(find_trait(sa, stdlib_id, "Finalizable"));
+    sa.known.traits.add = Some(find_trait(sa, stdlib_id, "traits::Add"));
+    sa.known.traits.sub = Some(find_trait(sa, stdlib_id, "traits::Sub"));
+    sa.known.traits.mul = Some(find_trait(sa, stdlib_id, "traits::Mul"));
+    sa.known.traits.div = Some(find_trait(sa, stdlib_id, "traits::Div"));
+    sa.known.traits.index = Some(find_trait(sa, stdlib_id, "traits::Index"));
+    sa.known.traits.index_mut = Some(find_trait(sa, stdlib_id, "traits::IndexMut"));
 
     sa.known.enums.option = Some(find_enum(sa, stdlib_id, "primitives::Option"));
 }
@@ -117,6 +143,9 @@ pub fn fill_prelude(sa: &mut SemAnalysis) {
     let symbols = [
         "primitives::Bool",
         "primitives::UInt8",
+        "primitives::UInt16",
+        "primitives::UInt32",
+        "primitives::UInt64",
         "primitives::Char",
         "primitives::Int32",
         "primitives::Int64",
@@ -214,6 +243,12 @@ pub fn discover_known_methods(sa: &mut SemAnalysis) {
         "Stacktrace",
         "retrieveStacktrace",
     ));
+    sa.known.functions.throwable_new = Some(find_static_method(sa, stdlib_id, "Throwable", "new"));
+    sa.known.functions.finalize = Some(find_trait_method(
+        sa,
+        sa.known.traits.finalizable(),
+        "finalize",
+    ));
 
     if sa.has_boots_package() {
         sa.known.functions.compile = Some(find_function(sa, sa.boots_module_id(), "compile"));
@@ -360,12 +395,27 @@ pub fn resolve_internal_functions(sa: &mut SemAnalysis) {
     native_fct(sa, stdlib_id, "argv", NativeFunction::Argv);
     native_fct(sa, stdlib_id, "forceCollect", NativeFunction::ForceCollect);
     native_fct(sa, stdlib_id, "timestamp", NativeFunction::Timestamp);
+    native_fct(sa, stdlib_id, "hashSeed", NativeFunction::HashSeed);
+    native_fct(sa, stdlib_id, "hasHashSeed", NativeFunction::HasHashSeed);
     native_fct(
         sa,
         stdlib_id,
         "forceMinorCollect",
         NativeFunction::ForceMinorCollect,
     );
+    native_fct(sa, stdlib_id, "gcStat", NativeFunction::GcStat);
+    native_fct(
+        sa,
+        stdlib_id,
+        "gcSetYoungSize",
+        NativeFunction::GcSetYoungSize,
+    );
+    native_fct(
+        sa,
+        stdlib_id,
+        "registerFinalizer",
+        NativeFunction::RegisterFinalizer,
+    );
     native_fct(sa, stdlib_id, "sleep", NativeFunction::Sleep);
 
     intrinsic_fct(sa, stdlib_id, "unsafeKillRefs", Intrinsic::UnsafeKillRefs);
@@ -489,6 +539,27 @@ pub fn resolve_internal_functions(sa: &mut SemAnalysis) {
         NativeFunction::Float64ToString,
     );
 
+    native_fct(
+        sa,
+        stdlib_id,
+        "primitives::formatFloat64Fixed",
+        NativeFunction::Float64ToStringFixed,
+    );
+
+    native_fct(sa, stdlib_id, "math::sin", NativeFunction::MathSin);
+    native_fct(sa, stdlib_id, "math::cos", NativeFunction::MathCos);
+    native_fct(sa, stdlib_id, "math::tan", NativeFunction::MathTan);
+    native_fct(sa, stdlib_id, "math::exp", NativeFunction::MathExp);
+    native_fct(sa, stdlib_id, "math::ln", NativeFunction::MathLn);
+    native_fct(sa, stdlib_id, "math::pow", NativeFunction::MathPow);
+    native_fct(sa, stdlib_id, "rand::osSeed", NativeFunction::RandomOsSeed);
+    native_fct(
+        sa,
+        stdlib_id,
+        "timer::monotonicNanos",
+        NativeFunction::MonotonicNanos,
+    );
+
     native_static(
         sa,
         stdlib_id,
@@ -644,6 +715,13 @@ pub fn resolve_internal_functions(sa: &mut SemAnalysis) {
         "clone",
         NativeFunction::StringClone,
     );
+    native_method(
+        sa,
+        stdlib_id,
+        "string::String",
+        "copyBytesInto",
+        NativeFunction::StringCopyBytes,
+    );
 
     intrinsic_fct(sa, stdlib_id, "unreachable", Intrinsic::Unreachable);
 
@@ -817,6 +895,27 @@ pub fn resolve_internal_functions(sa: &mut SemAnalysis) {
         "wrappingAdd",
         Intrinsic::Int32AddUnchecked,
     );
+    intrinsic_method(
+        sa,
+        stdlib_id,
+        "primitives::Int32",
+        "addOverflows",
+        Intrinsic::Int32AddOverflows,
+    );
+    intrinsic_method(
+        sa,
+        stdlib_id,
+        "primitives::Int32",
+        "subOverflows",
+        Intrinsic::Int32SubOverflows,
+    );
+    intrinsic_method(
+        sa,
+        stdlib_id,
+        "primitives::Int32",
+        "mulOverflows",
+        Intrinsic::Int32MulOverflows,
+    );
     intrinsic_method(
         sa,
         stdlib_id,
@@ -824,6 +923,20 @@ pub fn resolve_internal_functions(sa: &mut SemAnalysis) {
         "wrappingSub",
         Intrinsic::Int32SubUnchecked,
     );
+    intrinsic_method(
+        sa,
+        stdlib_id,
+        "primitives::Int32",
+        "saturatingAdd",
+        Intrinsic::Int32AddSaturating,
+    );
+    intrinsic_method(
+        sa,
+        stdlib_id,
+        "primitives::Int32",
+        "saturatingSub",
+        Intrinsic::Int32SubSaturating,
+    );
     intrinsic_method(
         sa,
         stdlib_id,
@@ -1058,6 +1171,27 @@ pub fn resolve_internal_functions(sa: &mut SemAnalysis) {
         "wrappingAdd",
         Intrinsic::Int64AddUnchecked,
     );
+    intrinsic_method(
+        sa,
+        stdlib_id,
+        "primitives::Int64",
+        "addOverflows",
+        Intrinsic::Int64AddOverflows,
+    );
+    intrinsic_method(
+        sa,
+        stdlib_id,
+        "primitives::Int64",
+        "subOverflows",
+        Intrinsic::Int64SubOverflows,
+    );
+    intrinsic_method(
+        sa,
+        stdlib_id,
+        "primitives::Int64",
+        "mulOverflows",
+        Intrinsic::Int64MulOverflows,
+    );
     intrinsic_method(
         sa,
         stdlib_id,
@@ -1065,6 +1199,20 @@ pub fn resolve_internal_functions(sa: &mut SemAnalysis) {
         "wrappingSub",
         Intrinsic::Int64SubUnchecked,
     );
+    intrinsic_method(
+        sa,
+        stdlib_id,
+        "primitives::Int64",
+        "saturatingAdd",
+        Intrinsic::Int64AddSaturating,
+    );
+    intrinsic_method(
+        sa,
+        stdlib_id,
+        "primitives::Int64",
+        "saturatingSub",
+        Intrinsic::Int64SubSaturating,
+    );
     intrinsic_method(
         sa,
         stdlib_id,
@@ -1301,6 +1449,13 @@ pub fn resolve_internal_functions(sa: &mut SemAnalysis) {
         "div",
         Intrinsic::Float32Div,
     );
+    intrinsic_method(
+        sa,
+        stdlib_id,
+        "primitives::Float32",
+        "modulo",
+        Intrinsic::Float32Mod,
+    );
 
     intrinsic_method(
         sa,
@@ -1369,6 +1524,21 @@ pub fn resolve_internal_functions(sa: &mut SemAnalysis) {
         Intrinsic::Float32Sqrt,
     );
 
+    intrinsic_static(
+        sa,
+        stdlib_id,
+        "primitives::Float32",
+        "min",
+        Intrinsic::Float32Min,
+    );
+    intrinsic_static(
+        sa,
+        stdlib_id,
+        "primitives::Float32",
+        "max",
+        Intrinsic::Float32Max,
+    );
+
     intrinsic_method(
         sa,
         stdlib_id,
@@ -1442,6 +1612,13 @@ pub fn resolve_internal_functions(sa: &mut SemAnalysis) {
         "div",
         Intrinsic::Float64Div,
     );
+    intrinsic_method(
+        sa,
+        stdlib_id,
+        "primitives::Float64",
+        "modulo",
+        Intrinsic::Float64Mod,
+    );
 
     intrinsic_method(
         sa,
@@ -1501,6 +1678,34 @@ pub fn resolve_internal_functions(sa: &mut SemAnalysis) {
         "roundHalfEven",
         Intrinsic::Float64RoundHalfEven,
     );
+    intrinsic_method(
+        sa,
+        stdlib_id,
+        "primitives::Float64",
+        "floor",
+        Intrinsic::Float64RoundDown,
+    );
+    intrinsic_method(
+        sa,
+        stdlib_id,
+        "primitives::Float64",
+        "ceil",
+        Intrinsic::Float64RoundUp,
+    );
+    intrinsic_method(
+        sa,
+        stdlib_id,
+        "primitives::Float64",
+        "trunc",
+        Intrinsic::Float64RoundToZero,
+    );
+    intrinsic_method(
+        sa,
+        stdlib_id,
+        "primitives::Float64",
+        "round",
+        Intrinsic::Float64RoundHalfEven,
+    );
 
     intrinsic_method(
         sa,
@@ -1510,6 +1715,21 @@ pub fn resolve_internal_functions(sa: &mut SemAnalysis) {
         Intrinsic::Float64Sqrt,
     );
 
+    intrinsic_static(
+        sa,
+        stdlib_id,
+        "primitives::Float64",
+        "min",
+        Intrinsic::Float64Min,
+    );
+    intrinsic_static(
+        sa,
+        stdlib_id,
+        "primitives::Float64",
+        "max",
+        Intrinsic::Float64Max,
+    );
+
     intrinsic_method(
         sa,
         stdlib_id,
@@ -1546,6 +1766,20 @@ pub fn resolve_internal_functions(sa: &mut SemAnalysis) {
         "new",
         Intrinsic::ArrayWithValues,
     );
+    intrinsic_static(
+        sa,
+        stdlib_id,
+        "collections::Array",
+        "fill",
+        Intrinsic::ArrayFill,
+    );
+    intrinsic_static(
+        sa,
+        stdlib_id,
+        "collections::Array",
+        "copy",
+        Intrinsic::ArrayCopy,
+    );
 
     intrinsic_static(
         sa,
@@ -1658,6 +1892,18 @@ fn find_function(sa: &SemAnalysis, module_id: ModuleDefinitionId, name: &str) ->
     fct_id
 }
 
+fn find_trait_method(sa: &SemAnalysis, trait_id: TraitDefinitionId, name: &str) -> FctDefinitionId {
+    let trait_ = sa.traits.idx(trait_id);
+    let trait_ = trait_.read();
+    let intern_name = sa.interner.intern(name);
+
+    trait_
+        .instance_names
+        .get(&intern_name)
+        .cloned()
+        .expect("cannot find trait method")
+}
+
 fn find_static_method(
     sa: &SemAnalysis,
     module_id: ModuleDefinitionId,
@@ -58,6 +58,28 @@ impl ModuleSymTable {
         self.levels.len()
     }
 
+    // Nested functions can't access local variables of the enclosing
+    // function, but see all other symbols in scope.
+    pub fn for_nested_function(&self) -> ModuleSymTable {
+        let mut level = SymTable::new();
+
+        for outer_level in &self.levels {
+            for (&name, sym) in &outer_level.table {
+                if !sym.is_var() {
+                    level.insert(name, sym.clone());
+                }
+            }
+        }
+
+        ModuleSymTable {
+            module_id: self.module_id,
+            levels: vec![level],
+            outer: self.outer.clone(),
+            dependencies: self.dependencies.clone(),
+            prelude: self.prelude.clone(),
+        }
+    }
+
     pub fn get(&self, name: Name) -> Option<Sym> {
         for level in self.levels.iter().rev() {
             if let Some(val) = level.get(name) {
@@ -29,6 +29,8 @@ impl<'x> TraitCheck<'x> {
         }
     }
 
+    // Slots are handed out in declaration order. Never iterate the name
+    // tables here, their order is not stable across compilations.
     fn visit_method(&mut self, fct_id: FctDefinitionId) {
         let fct = self.sa.fcts.idx(fct_id);
         let mut fct = fct.write();
@@ -51,6 +53,7 @@ impl<'x> TraitCheck<'x> {
 #[cfg(test)]
 mod tests {
     use crate::language::error::msg::ErrorMessage;
+    use crate::language::sem_analysis::SemAnalysis;
     use crate::language::tests::*;
     use dora_parser::Span;
 
@@ -71,6 +74,60 @@ mod tests {
         );
     }
 
+    #[test]
+    fn trait_vtable_index() {
+        ok_with_test(
+            "trait Foo { fn a(): Int32; static fn b(); fn c(x: Int32); }",
+            |sa| {
+                let trait_id = sa.trait_by_name("Foo");
+                let trait_ = sa.traits[trait_id].read();
+                assert_eq!(3, trait_.methods.len());
+
+                for (idx, &fct_id) in trait_.methods.iter().enumerate() {
+                    let fct = sa.fcts.idx(fct_id);
+                    assert_eq!(Some(idx as u32), fct.read().vtable_index);
+                }
+            },
+        );
+    }
+
+    #[test]
+    fn trait_vtable_index_stable() {
+        let code = "trait Foo {
+            fn zeta(); fn alpha(): Int32; static fn mu(); fn beta(x: Int32);
+            fn omega(); fn gamma(); fn delta(): Bool; fn epsilon();
+        }";
+
+        let slots = |sa: &SemAnalysis| -> Vec<(String, Option<u32>)> {
+            let trait_id = sa.trait_by_name("Foo");
+            let trait_ = sa.traits[trait_id].read();
+
+            trait_
+                .methods
+                .iter()
+                .map(|&fct_id| {
+                    let fct = sa.fcts.idx(fct_id);
+                    let fct = fct.read();
+                    (sa.interner.str(fct.name).to_string(), fct.vtable_index)
+                })
+                .collect()
+        };
+
+        let first = ok_with_test(code, slots);
+        let second = ok_with_test(code, slots);
+        assert_eq!(first, second);
+
+        let names: Vec<&str> = first.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(
+            vec!["zeta", "alpha", "mu", "beta", "omega", "gamma", "delta", "epsilon"],
+            names
+        );
+
+        for (idx, (_, vtable_index)) in first.iter().enumerate() {
+            assert_eq!(Some(idx as u32), *vtable_index);
+        }
+    }
+
     #[test]
     fn trait_definitions() {
         ok("trait Foo {}");
@@ -1,3 +1,5 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::ops::Index;
 use std::sync::Arc;
 
@@ -18,10 +20,16 @@ pub enum SourceType {
     // type with only one value: ()
     Unit,
 
+    // type without any value, e.g. for diverging expressions
+    Never,
+
     // primitives
     Bool,
     Char,
     UInt8,
+    UInt16,
+    UInt32,
+    UInt64,
     Int32,
     Int64,
     Float32,
@@ -88,6 +96,13 @@ impl SourceType {
         }
     }
 
+    pub fn is_never(&self) -> bool {
+        match self {
+            SourceType::Never => true,
+            _ => false,
+        }
+    }
+
     pub fn is_self(&self) -> bool {
         match self {
             SourceType::This => true,
@@ -162,6 +177,9 @@ impl SourceType {
         match self {
             &SourceType::Bool
             | &SourceType::UInt8
+            | &SourceType::UInt16
+            | &SourceType::UInt32
+            | &SourceType::UInt64
             | &SourceType::Char
             | &SourceType::Int32
             | &SourceType::Int64
@@ -197,6 +215,9 @@ impl SourceType {
         match self {
             SourceType::Bool => Some(sa.known.structs.bool()),
             SourceType::UInt8 => Some(sa.known.structs.uint8()),
+            SourceType::UInt16 => Some(sa.known.structs.uint16()),
+            SourceType::UInt32 => Some(sa.known.structs.uint32()),
+            SourceType::UInt64 => Some(sa.known.structs.uint64()),
             SourceType::Char => Some(sa.known.structs.char()),
             SourceType::Int32 => Some(sa.known.structs.int32()),
             SourceType::Int64 => Some(sa.known.structs.int64()),
@@ -256,6 +277,9 @@ impl SourceType {
             SourceType::Unit
             | SourceType::Bool
             | SourceType::UInt8
+            | SourceType::UInt16
+            | SourceType::UInt32
+            | SourceType::UInt64
             | SourceType::Int32
             | SourceType::Int64
             | SourceType::Float32
@@ -265,10 +289,7 @@ impl SourceType {
     }
 
     pub fn name(&self, sa: &SemAnalysis) -> String {
-        let writer = SourceTypePrinter {
-            sa,
-            type_params: None,
-        };
+        let writer = SourceTypePrinter::new(sa, None);
 
         writer.name(self.clone())
     }
@@ -278,46 +299,31 @@ impl SourceType {
         sa: &SemAnalysis,
         type_params: &TypeParamDefinition,
     ) -> String {
-        let writer = SourceTypePrinter {
-            sa,
-            type_params: Some(type_params),
-        };
+        let writer = SourceTypePrinter::new(sa, Some(type_params));
 
         writer.name(self.clone())
     }
 
     pub fn name_fct(&self, sa: &SemAnalysis, fct: &FctDefinition) -> String {
-        let writer = SourceTypePrinter {
-            sa,
-            type_params: Some(&fct.type_params),
-        };
+        let writer = SourceTypePrinter::new(sa, Some(&fct.type_params));
 
         writer.name(self.clone())
     }
 
     pub fn name_cls(&self, sa: &SemAnalysis, cls: &ClassDefinition) -> String {
-        let writer = SourceTypePrinter {
-            sa,
-            type_params: Some(cls.type_params()),
-        };
+        let writer = SourceTypePrinter::new(sa, Some(cls.type_params()));
 
         writer.name(self.clone())
     }
 
     pub fn name_struct(&self, sa: &SemAnalysis, struct_: &StructDefinition) -> String {
-        let writer = SourceTypePrinter {
-            sa,
-            type_params: Some(struct_.type_params()),
-        };
+        let writer = SourceTypePrinter::new(sa, Some(struct_.type_params()));
 
         writer.name(self.clone())
     }
 
     pub fn name_enum(&self, sa: &SemAnalysis, enum_: &EnumDefinition) -> String {
-        let writer = SourceTypePrinter {
-            sa,
-            type_params: Some(enum_.type_params()),
-        };
+        let writer = SourceTypePrinter::new(sa, Some(enum_.type_params()));
 
         writer.name(self.clone())
     }
@@ -346,6 +352,11 @@ impl SourceType {
     }
 
     pub fn allows(&self, sa: &SemAnalysis, other: SourceType) -> bool {
+        // Never has no values and can therefore be coerced into every type
+        if other.is_never() {
+            return true;
+        }
+
         match self {
             // allow all types for Error, there is already an error,
             // don't report too many messages for the same error
@@ -355,8 +366,12 @@ impl SourceType {
             SourceType::Any => true,
 
             SourceType::Unit
+            | SourceType::Never
             | SourceType::Bool
             | SourceType::UInt8
+            | SourceType::UInt16
+            | SourceType::UInt32
+            | SourceType::UInt64
             | SourceType::Char
             | SourceType::Struct(_, _)
             | SourceType::Enum(_, _)
@@ -365,7 +380,7 @@ impl SourceType {
                 *self == other
             }
             SourceType::Ptr => panic!("ptr does not allow any other types"),
-            SourceType::This => unreachable!(),
+            SourceType::This => *self == other,
             SourceType::Class(self_cls_id, self_list) => {
                 if *self == other {
                     return true;
@@ -405,11 +420,34 @@ impl SourceType {
 
             SourceType::TypeParam(_) => *self == other,
 
-            SourceType::Lambda(_, _) => {
-                // for now expect the exact same params and return types
-                // possible improvement: allow super classes for params,
-                //                             sub class for return type
-                *self == other
+            SourceType::Lambda(params, return_type) => {
+                if *self == other {
+                    return true;
+                }
+
+                let (other_params, other_return_type) = match other {
+                    SourceType::Lambda(other_params, other_return_type) => {
+                        (other_params, other_return_type)
+                    }
+                    _ => {
+                        return false;
+                    }
+                };
+
+                if params.len() != other_params.len() {
+                    return false;
+                }
+
+                // parameters are contravariant: the given lambda needs to
+                // accept every argument the expected lambda accepts
+                for (param, other_param) in params.iter().zip(other_params.iter()) {
+                    if !other_param.allows(sa, param) {
+                        return false;
+                    }
+                }
+
+                // return types are covariant
+                return_type.allows(sa, *other_return_type)
             }
         }
     }
@@ -418,8 +456,12 @@ impl SourceType {
         match self {
             SourceType::Error | SourceType::This | SourceType::Any | SourceType::Ptr => false,
             SourceType::Unit
+            | SourceType::Never
             | SourceType::Bool
             | SourceType::UInt8
+            | SourceType::UInt16
+            | SourceType::UInt32
+            | SourceType::UInt64
             | SourceType::Char
             | SourceType::Int32
             | SourceType::Int64
@@ -453,10 +495,14 @@ impl SourceType {
 
     pub fn is_concrete_type(&self) -> bool {
         match self {
-            SourceType::Error | SourceType::This | SourceType::Any => false,
+            // Never carries no value and is therefore never instantiated
+            SourceType::Error | SourceType::This | SourceType::Any | SourceType::Never => false,
             SourceType::Unit
             | SourceType::Bool
             | SourceType::UInt8
+            | SourceType::UInt16
+            | SourceType::UInt32
+            | SourceType::UInt64
             | SourceType::Char
             | SourceType::Int32
             | SourceType::Int64
@@ -654,15 +700,32 @@ impl<'a> Iterator for SourceTypeArrayIter<'a> {
 struct SourceTypePrinter<'a> {
     sa: &'a SemAnalysis,
     type_params: Option<&'a TypeParamDefinition>,
+    // Names of already printed tuples, only valid for this printer's type params.
+    tuple_names: RefCell<HashMap<SourceTypeArray, String>>,
 }
 
 impl<'a> SourceTypePrinter<'a> {
+    fn new(
+        sa: &'a SemAnalysis,
+        type_params: Option<&'a TypeParamDefinition>,
+    ) -> SourceTypePrinter<'a> {
+        SourceTypePrinter {
+            sa,
+            type_params,
+            tuple_names: RefCell::new(HashMap::new()),
+        }
+    }
+
     pub fn name(&self, ty: SourceType) -> String {
         match ty {
             SourceType::Error => "<error>".into(),
             SourceType::Any => "Any".into(),
             SourceType::Unit => "()".into(),
+            SourceType::Never => "!".into(),
             SourceType::UInt8 => "UInt8".into(),
+            SourceType::UInt16 => "UInt16".into(),
+            SourceType::UInt32 => "UInt32".into(),
+            SourceType::UInt64 => "UInt64".into(),
             SourceType::Char => "Char".into(),
             SourceType::Int32 => "Int32".into(),
             SourceType::Int64 => "Int64".into(),
@@ -759,13 +822,19 @@ impl<'a> SourceTypePrinter<'a> {
             }
 
             SourceType::Tuple(subtypes) => {
+                if let Some(name) = self.tuple_names.borrow().get(&subtypes) {
+                    return name.clone();
+                }
+
                 let types = subtypes
                     .iter()
                     .map(|ty| self.name(ty.clone()))
                     .collect::<Vec<_>>()
                     .join(", ");
 
-                format!("({})", types)
+                let name = format!("({})", types);
+                self.tuple_names.borrow_mut().insert(subtypes, name.clone());
+                name
             }
         }
     }
@@ -774,6 +843,125 @@ impl<'a> SourceTypePrinter<'a> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::language::tests::ok_with_test;
+
+    #[test]
+    fn unsigned_int_types() {
+        ok_with_test("", |sa| {
+            assert_eq!(SourceType::UInt16.name(sa), "UInt16");
+            assert_eq!(SourceType::UInt32.name(sa), "UInt32");
+            assert_eq!(SourceType::UInt64.name(sa), "UInt64");
+
+            assert!(SourceType::UInt16.allows(sa, SourceType::UInt16));
+            assert!(SourceType::UInt32.allows(sa, SourceType::UInt32));
+            assert!(SourceType::UInt64.allows(sa, SourceType::UInt64));
+            assert!(!SourceType::UInt16.allows(sa, SourceType::UInt8));
+            assert!(!SourceType::UInt32.allows(sa, SourceType::Int32));
+            assert!(!SourceType::UInt64.allows(sa, SourceType::UInt32));
+            assert!(!SourceType::Int64.allows(sa, SourceType::UInt64));
+
+            for ty in [SourceType::UInt16, SourceType::UInt32, SourceType::UInt64] {
+                assert!(ty.is_primitive());
+                assert!(ty.value_type());
+                assert!(ty.primitive_struct_id(sa).is_some());
+            }
+        });
+    }
+
+    #[test]
+    fn lambda_is_concrete_type() {
+        let concrete = SourceType::Lambda(
+            SourceTypeArray::with(vec![SourceType::Int32, SourceType::Bool]),
+            Box::new(SourceType::Float64),
+        );
+        assert!(concrete.is_concrete_type());
+
+        let generic = SourceType::Lambda(
+            SourceTypeArray::with(vec![
+                SourceType::Int32,
+                SourceType::TypeParam(TypeParamId(0)),
+            ]),
+            Box::new(SourceType::Unit),
+        );
+        assert!(!generic.is_concrete_type());
+
+        let generic_return = SourceType::Lambda(
+            SourceTypeArray::empty(),
+            Box::new(SourceType::TypeParam(TypeParamId(0))),
+        );
+        assert!(!generic_return.is_concrete_type());
+    }
+
+    #[test]
+    fn lambda_allows() {
+        ok_with_test("", |sa| {
+            let lambda = |params: Vec<SourceType>, return_type: SourceType| {
+                SourceType::Lambda(SourceTypeArray::with(params), Box::new(return_type))
+            };
+
+            let expected = lambda(vec![SourceType::Int32], SourceType::Any);
+            assert!(expected.allows(sa, lambda(vec![SourceType::Int32], SourceType::Any)));
+            assert!(expected.allows(sa, lambda(vec![SourceType::Int32], SourceType::Bool)));
+            assert!(!expected.allows(sa, lambda(vec![SourceType::Int64], SourceType::Bool)));
+            assert!(!expected.allows(sa, lambda(vec![], SourceType::Bool)));
+
+            let expected = lambda(vec![SourceType::Int32], SourceType::Unit);
+            assert!(expected.allows(sa, lambda(vec![SourceType::Any], SourceType::Unit)));
+            assert!(!expected.allows(sa, lambda(vec![SourceType::Int32], SourceType::Any)));
+            assert!(!expected.allows(sa, SourceType::Int32));
+
+            let expected = lambda(vec![SourceType::Any], SourceType::Unit);
+            assert!(!expected.allows(sa, lambda(vec![SourceType::Int32], SourceType::Unit)));
+        });
+    }
+
+    #[test]
+    fn nested_tuple_names() {
+        ok_with_test("", |sa| {
+            let mut ty = SourceType::Int32;
+            let mut expected = "Int32".to_string();
+
+            for _ in 0..6 {
+                ty = SourceType::Tuple(SourceTypeArray::with(vec![ty.clone(), ty]));
+                expected = format!("({}, {})", expected, expected);
+            }
+
+            assert_eq!(ty.name(sa), expected);
+
+            let printer = SourceTypePrinter::new(sa, None);
+            assert_eq!(printer.name(ty.clone()), expected);
+            assert_eq!(printer.name(ty), expected);
+            assert_eq!(printer.tuple_names.borrow().len(), 6);
+        });
+    }
+
+    #[test]
+    fn never_type() {
+        ok_with_test("", |sa| {
+            assert_eq!(SourceType::Never.name(sa), "!");
+
+            let tuple = SourceType::Tuple(SourceTypeArray::with(vec![
+                SourceType::Int32,
+                SourceType::Bool,
+            ]));
+
+            for ty in [
+                SourceType::Int32,
+                SourceType::Unit,
+                tuple,
+                SourceType::Never,
+            ] {
+                assert!(ty.allows(sa, SourceType::Never));
+            }
+
+            for ty in [SourceType::Int32, SourceType::Unit, SourceType::Any] {
+                assert!(!SourceType::Never.allows(sa, ty));
+            }
+
+            assert!(!SourceType::Never.is_concrete_type());
+            assert!(SourceType::Never.is_defined_type(sa));
+        });
+    }
 
     #[test]
     fn append_type_lists() {
@@ -11,10 +11,20 @@ use crate::Span;
 
 pub mod dump;
 pub mod visit;
+pub mod visit_mut;
 
 #[derive(Clone, Debug)]
 pub struct File {
     pub elements: Vec<Elem>,
+    pub suppressions: Vec<Suppression>,
+}
+
+// Lints named in a `// dora:allow(...)` comment, the span covers the
+// element or statement following the comment.
+#[derive(Clone, Debug)]
+pub struct Suppression {
+    pub lints: Vec<(String, Span)>,
+    pub span: Span,
 }
 
 impl File {
@@ -279,7 +289,7 @@ pub struct Const {
     pub span: Span,
     pub name: Name,
     pub data_type: Type,
-    pub expr: Box<Expr>,
+    pub expr: Option<Box<Expr>>,
     pub visibility: Visibility,
 }
 
@@ -291,6 +301,7 @@ pub struct Enum {
     pub type_params: Option<Vec<TypeParam>>,
     pub variants: Vec<EnumVariant>,
     pub visibility: Visibility,
+    pub doc: Option<String>,
 }
 
 #[derive(Clone, Debug)]
@@ -319,6 +330,7 @@ pub struct Struct {
     pub visibility: Visibility,
     pub internal: bool,
     pub type_params: Option<Vec<TypeParam>>,
+    pub doc: Option<String>,
 }
 
 #[derive(Clone, Debug)]
@@ -484,6 +496,7 @@ pub struct Impl {
     pub trait_type: Option<Type>,
     pub extended_type: Type,
     pub methods: Vec<Arc<Function>>,
+    pub consts: Vec<Arc<Const>>,
 }
 
 #[derive(Clone, Debug)]
@@ -493,7 +506,9 @@ pub struct Trait {
     pub type_params: Option<Vec<TypeParam>>,
     pub span: Span,
     pub methods: Vec<Arc<Function>>,
+    pub consts: Vec<Arc<Const>>,
     pub visibility: Visibility,
+    pub doc: Option<String>,
 }
 
 #[derive(Clone, Debug)]
@@ -506,6 +521,7 @@ pub struct Class {
 
     pub fields: Vec<Field>,
     pub type_params: Option<Vec<TypeParam>>,
+    pub doc: Option<String>,
 }
 
 #[derive(Clone, Debug)]
@@ -557,6 +573,7 @@ pub struct Field {
 #[derive(Clone, Debug)]
 pub enum FunctionKind {
     Function,
+    Nested,
     Lambda,
 }
 
@@ -564,9 +581,13 @@ impl FunctionKind {
     pub fn is_lambda(&self) -> bool {
         match self {
             &FunctionKind::Lambda => true,
-            &FunctionKind::Function => false,
+            &FunctionKind::Function | &FunctionKind::Nested => false,
         }
     }
+
+    pub fn is_nested(&self) -> bool {
+        matches!(self, FunctionKind::Nested)
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -580,6 +601,7 @@ pub struct Function {
     pub visibility: Visibility,
     pub is_static: bool,
     pub is_test: bool,
+    pub expected_trap: Option<Name>,
     pub internal: bool,
     pub is_constructor: bool,
 
@@ -588,6 +610,7 @@ pub struct Function {
     pub return_type: Option<Type>,
     pub block: Option<Box<ExprBlockType>>,
     pub type_params: Option<Vec<TypeParam>>,
+    pub doc: Option<String>,
 }
 
 impl Function {
@@ -610,13 +633,38 @@ impl Modifiers {
         self.0.iter().find(|el| el.value == modifier).is_some()
     }
 
-    pub fn add(&mut self, modifier: Modifier, span: Span) {
+    pub fn arg(&self, modifier: Modifier) -> Option<Name> {
+        self.0
+            .iter()
+            .find(|el| el.value == modifier)
+            .and_then(|el| el.arg)
+    }
+
+    pub fn add(&mut self, modifier: Modifier, arg: Option<Name>, span: Span) {
         self.0.push(ModifierElement {
             value: modifier,
+            arg,
+            restriction: None,
+            span,
+        });
+    }
+
+    pub fn add_restricted_pub(&mut self, restriction: VisibilityRestriction, span: Span) {
+        self.0.push(ModifierElement {
+            value: Modifier::Pub,
+            arg: None,
+            restriction: Some(restriction),
             span,
         });
     }
 
+    pub fn restriction(&self, modifier: Modifier) -> Option<VisibilityRestriction> {
+        self.0
+            .iter()
+            .find(|el| el.value == modifier)
+            .and_then(|el| el.restriction)
+    }
+
     pub fn iter(&self) -> Iter<ModifierElement> {
         self.0.iter()
     }
@@ -626,6 +674,8 @@ impl Modifiers {
 #[derive(Clone, Debug)]
 pub struct ModifierElement {
     pub value: Modifier,
+    pub arg: Option<Name>,
+    pub restriction: Option<VisibilityRestriction>,
     pub span: Span,
 }
 
@@ -665,6 +715,7 @@ pub enum Modifier {
     Pub,
     Static,
     Test,
+    ExpectTrap,
     OptimizeImmediately,
 }
 
@@ -686,6 +737,7 @@ impl Modifier {
             Modifier::Pub => "pub",
             Modifier::Static => "static",
             Modifier::Test => "test",
+            Modifier::ExpectTrap => "Test.expectTrap",
             Modifier::OptimizeImmediately => "optimizeImmediately",
         }
     }
@@ -706,10 +758,14 @@ pub struct Param {
 pub enum Stmt {
     Let(StmtLetType),
     While(StmtWhileType),
+    Loop(StmtLoopType),
+    DoWhile(StmtDoWhileType),
+    Fct(Arc<Function>),
     Expr(StmtExprType),
     Break(StmtBreakType),
     Continue(StmtContinueType),
     Return(StmtReturnType),
+    Throw(StmtThrowType),
     For(StmtForType),
 }
 
@@ -734,6 +790,7 @@ impl Stmt {
     pub fn create_for(
         id: NodeId,
         span: Span,
+        label: Option<Name>,
         pattern: Box<LetPattern>,
         expr: Box<Expr>,
         block: Box<Stmt>,
@@ -742,47 +799,92 @@ impl Stmt {
             id,
             span,
 
+            label,
             pattern,
             expr,
             block,
         })
     }
 
-    pub fn create_while(id: NodeId, span: Span, cond: Box<Expr>, block: Box<Stmt>) -> Stmt {
+    pub fn create_while(
+        id: NodeId,
+        span: Span,
+        label: Option<Name>,
+        pattern: Option<Box<MatchPattern>>,
+        cond: Box<Expr>,
+        block: Box<Stmt>,
+    ) -> Stmt {
         Stmt::While(StmtWhileType {
             id,
             span,
 
+            label,
+            pattern,
             cond,
             block,
         })
     }
 
+    pub fn create_loop(id: NodeId, span: Span, label: Option<Name>, block: Box<Stmt>) -> Stmt {
+        Stmt::Loop(StmtLoopType {
+            id,
+            span,
+
+            label,
+            block,
+        })
+    }
+
+    pub fn create_do_while(
+        id: NodeId,
+        span: Span,
+        label: Option<Name>,
+        block: Box<Stmt>,
+        cond: Box<Expr>,
+    ) -> Stmt {
+        Stmt::DoWhile(StmtDoWhileType {
+            id,
+            span,
+
+            label,
+            block,
+            cond,
+        })
+    }
+
     pub fn create_expr(id: NodeId, span: Span, expr: Box<Expr>) -> Stmt {
         Stmt::Expr(StmtExprType { id, span, expr })
     }
 
-    pub fn create_break(id: NodeId, span: Span) -> Stmt {
-        Stmt::Break(StmtBreakType { id, span })
+    pub fn create_break(id: NodeId, span: Span, label: Option<Name>) -> Stmt {
+        Stmt::Break(StmtBreakType { id, span, label })
     }
 
-    pub fn create_continue(id: NodeId, span: Span) -> Stmt {
-        Stmt::Continue(StmtContinueType { id, span })
+    pub fn create_continue(id: NodeId, span: Span, label: Option<Name>) -> Stmt {
+        Stmt::Continue(StmtContinueType { id, span, label })
     }
 
     pub fn create_return(id: NodeId, span: Span, expr: Option<Box<Expr>>) -> Stmt {
         Stmt::Return(StmtReturnType { id, span, expr })
     }
 
+    pub fn create_throw(id: NodeId, span: Span, expr: Box<Expr>) -> Stmt {
+        Stmt::Throw(StmtThrowType { id, span, expr })
+    }
+
     pub fn id(&self) -> NodeId {
         match *self {
             Stmt::Let(ref stmt) => stmt.id,
             Stmt::While(ref stmt) => stmt.id,
+            Stmt::Loop(ref stmt) => stmt.id,
+            Stmt::DoWhile(ref stmt) => stmt.id,
+            Stmt::Fct(ref fct) => fct.id,
             Stmt::For(ref stmt) => stmt.id,
             Stmt::Expr(ref stmt) => stmt.id,
             Stmt::Break(ref stmt) => stmt.id,
             Stmt::Continue(ref stmt) => stmt.id,
             Stmt::Return(ref stmt) => stmt.id,
+            Stmt::Throw(ref stmt) => stmt.id,
         }
     }
 
@@ -790,11 +892,15 @@ impl Stmt {
         match *self {
             Stmt::Let(ref stmt) => stmt.span,
             Stmt::While(ref stmt) => stmt.span,
+            Stmt::Loop(ref stmt) => stmt.span,
+            Stmt::DoWhile(ref stmt) => stmt.span,
+            Stmt::Fct(ref fct) => fct.span,
             Stmt::For(ref stmt) => stmt.span,
             Stmt::Expr(ref stmt) => stmt.span,
             Stmt::Break(ref stmt) => stmt.span,
             Stmt::Continue(ref stmt) => stmt.span,
             Stmt::Return(ref stmt) => stmt.span,
+            Stmt::Throw(ref stmt) => stmt.span,
         }
     }
 
@@ -826,6 +932,39 @@ impl Stmt {
         }
     }
 
+    pub fn to_loop(&self) -> Option<&StmtLoopType> {
+        match *self {
+            Stmt::Loop(ref val) => Some(val),
+            _ => None,
+        }
+    }
+
+    pub fn is_loop(&self) -> bool {
+        matches!(*self, Stmt::Loop(_))
+    }
+
+    pub fn to_do_while(&self) -> Option<&StmtDoWhileType> {
+        match *self {
+            Stmt::DoWhile(ref val) => Some(val),
+            _ => None,
+        }
+    }
+
+    pub fn is_do_while(&self) -> bool {
+        matches!(*self, Stmt::DoWhile(_))
+    }
+
+    pub fn to_fct(&self) -> Option<&Arc<Function>> {
+        match *self {
+            Stmt::Fct(ref fct) => Some(fct),
+            _ => None,
+        }
+    }
+
+    pub fn is_fct(&self) -> bool {
+        matches!(*self, Stmt::Fct(_))
+    }
+
     pub fn to_for(&self) -> Option<&StmtForType> {
         match *self {
             Stmt::For(ref val) => Some(val),
@@ -868,6 +1007,17 @@ impl Stmt {
         }
     }
 
+    pub fn to_throw(&self) -> Option<&StmtThrowType> {
+        match *self {
+            Stmt::Throw(ref val) => Some(val),
+            _ => None,
+        }
+    }
+
+    pub fn is_throw(&self) -> bool {
+        matches!(*self, Stmt::Throw(_))
+    }
+
     pub fn to_break(&self) -> Option<&StmtBreakType> {
         match *self {
             Stmt::Break(ref val) => Some(val),
@@ -957,6 +1107,14 @@ impl LetPattern {
             _ => None,
         }
     }
+
+    pub fn span(&self) -> Span {
+        match self {
+            LetPattern::Ident(ref ident) => ident.span,
+            LetPattern::Tuple(ref tuple) => tuple.span,
+            LetPattern::Underscore(ref underscore) => underscore.span,
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -985,6 +1143,7 @@ pub struct StmtForType {
     pub id: NodeId,
     pub span: Span,
 
+    pub label: Option<Name>,
     pub pattern: Box<LetPattern>,
     pub expr: Box<Expr>,
     pub block: Box<Stmt>,
@@ -995,10 +1154,32 @@ pub struct StmtWhileType {
     pub id: NodeId,
     pub span: Span,
 
+    pub label: Option<Name>,
+    // Set for `while let <pattern> = <cond>`.
+    pub pattern: Option<Box<MatchPattern>>,
     pub cond: Box<Expr>,
     pub block: Box<Stmt>,
 }
 
+#[derive(Clone, Debug)]
+pub struct StmtLoopType {
+    pub id: NodeId,
+    pub span: Span,
+
+    pub label: Option<Name>,
+    pub block: Box<Stmt>,
+}
+
+#[derive(Clone, Debug)]
+pub struct StmtDoWhileType {
+    pub id: NodeId,
+    pub span: Span,
+
+    pub label: Option<Name>,
+    pub block: Box<Stmt>,
+    pub cond: Box<Expr>,
+}
+
 #[derive(Clone, Debug)]
 pub struct StmtExprType {
     pub id: NodeId,
@@ -1015,16 +1196,28 @@ pub struct StmtReturnType {
     pub expr: Option<Box<Expr>>,
 }
 
+#[derive(Clone, Debug)]
+pub struct StmtThrowType {
+    pub id: NodeId,
+    pub span: Span,
+
+    pub expr: Box<Expr>,
+}
+
 #[derive(Clone, Debug)]
 pub struct StmtBreakType {
     pub id: NodeId,
     pub span: Span,
+
+    pub label: Option<Name>,
 }
 
 #[derive(Clone, Debug)]
 pub struct StmtContinueType {
     pub id: NodeId,
     pub span: Span,
+
+    pub label: Option<Name>,
 }
 
 #[derive(PartialEq, Eq, Debug, Copy, Clone)]
@@ -1139,6 +1332,7 @@ pub enum Expr {
     Ident(ExprIdentType),
     Call(ExprCallType),
     TypeParam(ExprTypeParamType),
+    Index(ExprIndexType),
     Path(ExprPathType),
     Dot(ExprDotType),
     This(ExprSelfType),
@@ -1149,6 +1343,9 @@ pub enum Expr {
     Tuple(ExprTupleType),
     Paren(ExprParenType),
     Match(ExprMatchType),
+    Range(ExprRangeType),
+    Try(ExprTryType),
+    TryCatch(ExprTryCatchType),
 }
 
 impl Expr {
@@ -1170,6 +1367,7 @@ impl Expr {
     pub fn create_if(
         id: NodeId,
         span: Span,
+        pattern: Option<Box<MatchPattern>>,
         cond: Box<Expr>,
         then_block: Box<Expr>,
         else_block: Option<Box<Expr>>,
@@ -1178,12 +1376,32 @@ impl Expr {
             id,
             span,
 
+            pattern,
             cond,
             then_block,
             else_block,
         })
     }
 
+    pub fn create_try_catch(
+        id: NodeId,
+        span: Span,
+        try_block: Box<Expr>,
+        catch_name: Name,
+        catch_type: Type,
+        catch_block: Box<Expr>,
+    ) -> Expr {
+        Expr::TryCatch(ExprTryCatchType {
+            id,
+            span,
+
+            try_block,
+            catch_name,
+            catch_type,
+            catch_block,
+        })
+    }
+
     pub fn create_match(
         id: NodeId,
         span: Span,
@@ -1214,13 +1432,20 @@ impl Expr {
         })
     }
 
-    pub fn create_conv(id: NodeId, span: Span, object: Box<Expr>, data_type: Box<Type>) -> Expr {
+    pub fn create_conv(
+        id: NodeId,
+        span: Span,
+        object: Box<Expr>,
+        data_type: Box<Type>,
+        is: bool,
+    ) -> Expr {
         Expr::Conv(ExprConvType {
             id,
             span,
 
             object,
             data_type,
+            is,
         })
     }
 
@@ -1289,6 +1514,27 @@ impl Expr {
         Expr::Paren(ExprParenType { id, span, expr })
     }
 
+    pub fn create_range(
+        id: NodeId,
+        span: Span,
+        start: Option<Box<Expr>>,
+        end: Option<Box<Expr>>,
+        inclusive: bool,
+    ) -> Expr {
+        Expr::Range(ExprRangeType {
+            id,
+            span,
+
+            start,
+            end,
+            inclusive,
+        })
+    }
+
+    pub fn create_try(id: NodeId, span: Span, expr: Box<Expr>) -> Expr {
+        Expr::Try(ExprTryType { id, span, expr })
+    }
+
     pub fn create_call(id: NodeId, span: Span, callee: Box<Expr>, args: Vec<Box<Expr>>) -> Expr {
         Expr::Call(ExprCallType {
             id,
@@ -1316,6 +1562,23 @@ impl Expr {
         })
     }
 
+    pub fn create_index(
+        id: NodeId,
+        span: Span,
+        op_span: Span,
+        object: Box<Expr>,
+        index: Box<Expr>,
+    ) -> Expr {
+        Expr::Index(ExprIndexType {
+            id,
+            span,
+            op_span,
+
+            object,
+            index,
+        })
+    }
+
     pub fn create_path(
         id: NodeId,
         span: Span,
@@ -1399,6 +1662,34 @@ impl Expr {
         }
     }
 
+    pub fn to_range(&self) -> Option<&ExprRangeType> {
+        match *self {
+            Expr::Range(ref val) => Some(val),
+            _ => None,
+        }
+    }
+
+    pub fn is_range(&self) -> bool {
+        match *self {
+            Expr::Range(_) => true,
+            _ => false,
+        }
+    }
+
+    pub fn to_try(&self) -> Option<&ExprTryType> {
+        match *self {
+            Expr::Try(ref val) => Some(val),
+            _ => None,
+        }
+    }
+
+    pub fn is_try(&self) -> bool {
+        match *self {
+            Expr::Try(_) => true,
+            _ => false,
+        }
+    }
+
     pub fn to_ident(&self) -> Option<&ExprIdentType> {
         match *self {
             Expr::Ident(ref val) => Some(val),
@@ -1455,6 +1746,20 @@ impl Expr {
         }
     }
 
+    pub fn to_index(&self) -> Option<&ExprIndexType> {
+        match *self {
+            Expr::Index(ref val) => Some(val),
+            _ => None,
+        }
+    }
+
+    pub fn is_index(&self) -> bool {
+        match *self {
+            Expr::Index(_) => true,
+            _ => false,
+        }
+    }
+
     pub fn to_lit_char(&self) -> Option<&ExprLitCharType> {
         match *self {
             Expr::LitChar(ref val) => Some(val),
@@ -1609,6 +1914,13 @@ impl Expr {
         }
     }
 
+    pub fn to_match(&self) -> Option<&ExprMatchType> {
+        match *self {
+            Expr::Match(ref val) => Some(val),
+            _ => None,
+        }
+    }
+
     pub fn to_block(&self) -> Option<&ExprBlockType> {
         match *self {
             Expr::Block(ref val) => Some(val),
@@ -1637,10 +1949,22 @@ impl Expr {
         }
     }
 
+    pub fn to_try_catch(&self) -> Option<&ExprTryCatchType> {
+        match *self {
+            Expr::TryCatch(ref val) => Some(val),
+            _ => None,
+        }
+    }
+
+    pub fn is_try_catch(&self) -> bool {
+        matches!(*self, Expr::TryCatch(_))
+    }
+
     pub fn needs_semicolon(&self) -> bool {
         match self {
             &Expr::Block(_) => false,
             &Expr::If(_) => false,
+            &Expr::TryCatch(_) => false,
             &Expr::Match(_) => false,
             _ => true,
         }
@@ -1659,6 +1983,7 @@ impl Expr {
             Expr::Ident(ref val) => val.span,
             Expr::Call(ref val) => val.span,
             Expr::TypeParam(ref val) => val.span,
+            Expr::Index(ref val) => val.span,
             Expr::Path(ref val) => val.span,
             Expr::Dot(ref val) => val.span,
             Expr::This(ref val) => val.span,
@@ -1669,6 +1994,9 @@ impl Expr {
             Expr::Tuple(ref val) => val.span,
             Expr::Paren(ref val) => val.span,
             Expr::Match(ref val) => val.span,
+            Expr::Range(ref val) => val.span,
+            Expr::Try(ref val) => val.span,
+            Expr::TryCatch(ref val) => val.span,
         }
     }
 
@@ -1685,6 +2013,7 @@ impl Expr {
             Expr::Ident(ref val) => val.id,
             Expr::Call(ref val) => val.id,
             Expr::TypeParam(ref val) => val.id,
+            Expr::Index(ref val) => val.id,
             Expr::Path(ref val) => val.id,
             Expr::Dot(ref val) => val.id,
             Expr::This(ref val) => val.id,
@@ -1695,6 +2024,9 @@ impl Expr {
             Expr::Tuple(ref val) => val.id,
             Expr::Paren(ref val) => val.id,
             Expr::Match(ref val) => val.id,
+            Expr::Range(ref val) => val.id,
+            Expr::Try(ref val) => val.id,
+            Expr::TryCatch(ref val) => val.id,
         }
     }
 }
@@ -1704,6 +2036,8 @@ pub struct ExprIfType {
     pub id: NodeId,
     pub span: Span,
 
+    // Set for `if let <pattern> = <cond>`.
+    pub pattern: Option<Box<MatchPattern>>,
     pub cond: Box<Expr>,
     pub then_block: Box<Expr>,
     pub else_block: Option<Box<Expr>>,
@@ -1724,6 +2058,8 @@ pub struct ExprConvType {
 
     pub object: Box<Expr>,
     pub data_type: Box<Type>,
+    // `object is Type` instead of `object as Type`
+    pub is: bool,
 }
 
 #[derive(Clone, Debug)]
@@ -1858,6 +2194,37 @@ pub struct ExprParenType {
     pub expr: Box<Expr>,
 }
 
+#[derive(Clone, Debug)]
+pub struct ExprRangeType {
+    pub id: NodeId,
+    pub span: Span,
+
+    pub start: Option<Box<Expr>>,
+    pub end: Option<Box<Expr>>,
+    pub inclusive: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct ExprTryType {
+    pub id: NodeId,
+    pub span: Span,
+
+    pub expr: Box<Expr>,
+}
+
+// `try { ... } catch name: Type { ... }`, the catch block runs
+// when a thrown object of the given class reaches this expression.
+#[derive(Clone, Debug)]
+pub struct ExprTryCatchType {
+    pub id: NodeId,
+    pub span: Span,
+
+    pub try_block: Box<Expr>,
+    pub catch_name: Name,
+    pub catch_type: Type,
+    pub catch_block: Box<Expr>,
+}
+
 #[derive(Clone, Debug)]
 pub struct ExprMatchType {
     pub id: NodeId,
@@ -1873,6 +2240,7 @@ pub struct MatchCaseType {
     pub span: Span,
 
     pub patterns: Vec<MatchPattern>,
+    pub guard: Option<Box<Expr>>,
     pub value: Box<Expr>,
 }
 
@@ -1887,6 +2255,15 @@ pub struct MatchPattern {
 pub enum MatchPatternData {
     Underscore,
     Ident(MatchPatternIdent),
+    Tuple(Vec<MatchPattern>),
+    Lit(Box<Expr>),
+    Range(MatchPatternRange),
+}
+
+#[derive(Clone, Debug)]
+pub struct MatchPatternRange {
+    pub start: Box<Expr>,
+    pub end: Box<Expr>,
 }
 
 #[derive(Clone, Debug)]
@@ -1901,6 +2278,8 @@ pub struct MatchPatternParam {
     pub span: Span,
     pub name: Option<Name>,
     pub mutable: bool,
+    // Nested variant pattern, e.g. `Foo::A(x)` in `Some(Foo::A(x))`.
+    pub pattern: Option<Box<MatchPattern>>,
 }
 
 #[derive(Clone, Debug)]
@@ -1920,6 +2299,16 @@ pub struct ExprTypeParamType {
     pub args: Vec<Type>,
 }
 
+#[derive(Clone, Debug)]
+pub struct ExprIndexType {
+    pub id: NodeId,
+    pub span: Span,
+    pub op_span: Span,
+
+    pub object: Box<Expr>,
+    pub index: Box<Expr>,
+}
+
 #[derive(Clone, Debug)]
 pub struct ExprPathType {
     pub id: NodeId,
@@ -1943,13 +2332,17 @@ pub struct ExprDotType {
 #[derive(Copy, Clone, Debug)]
 pub enum Visibility {
     Public,
+    Restricted(VisibilityRestriction),
     Default,
 }
 
 impl Visibility {
     pub fn from_modifiers(modifiers: &Modifiers) -> Visibility {
         if modifiers.contains(Modifier::Pub) {
-            Visibility::Public
+            match modifiers.restriction(Modifier::Pub) {
+                Some(restriction) => Visibility::Restricted(restriction),
+                None => Visibility::Public,
+            }
         } else {
             Visibility::Default
         }
@@ -1957,14 +2350,29 @@ impl Visibility {
     pub fn is_public(self) -> bool {
         match self {
             Visibility::Public => true,
-            Visibility::Default => false,
+            Visibility::Restricted(_) | Visibility::Default => false,
+        }
+    }
+
+    pub fn is_restricted(self) -> bool {
+        match self {
+            Visibility::Restricted(_) => true,
+            Visibility::Public | Visibility::Default => false,
         }
     }
 
     pub fn is_default(self) -> bool {
         match self {
-            Visibility::Public => false,
+            Visibility::Public | Visibility::Restricted(_) => false,
             Visibility::Default => true,
         }
     }
 }
+
+// Limits a `pub` element to part of the module tree: `pub(mod)` to the module
+// containing the element's module, `pub(super)` to the module above that.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum VisibilityRestriction {
+    Module,
+    Super,
+}
@@ -122,7 +122,9 @@ impl<'a> AstDumper<'a> {
 
         self.indent(|d| {
             d.dump_type(&const_.data_type);
-            d.dump_expr(&const_.expr);
+            if let Some(ref expr) = const_.expr {
+                d.dump_expr(expr);
+            }
         });
     }
 
@@ -201,6 +203,10 @@ impl<'a> AstDumper<'a> {
 
             d.dump_type(&impl_.extended_type);
 
+            for const_ in &impl_.consts {
+                d.dump_const(const_);
+            }
+
             for mtd in &impl_.methods {
                 d.dump_fct(mtd);
             }
@@ -237,6 +243,10 @@ impl<'a> AstDumper<'a> {
     fn dump_trait(&mut self, t: &Trait) {
         dump!(self, "trait {} @ {} {}", self.str(t.name), t.span, t.id);
         self.indent(|d| {
+            for const_ in &t.consts {
+                d.dump_const(const_);
+            }
+
             for m in &t.methods {
                 d.dump_fct(m);
             }
@@ -362,11 +372,15 @@ impl<'a> AstDumper<'a> {
     fn dump_stmt(&mut self, stmt: &Stmt) {
         match *stmt {
             Stmt::Return(ref ret) => self.dump_stmt_return(ret),
+            Stmt::Throw(ref stmt) => self.dump_stmt_throw(stmt),
             Stmt::Break(ref stmt) => self.dump_stmt_break(stmt),
             Stmt::Continue(ref stmt) => self.dump_stmt_continue(stmt),
             Stmt::Expr(ref expr) => self.dump_stmt_expr(expr),
             Stmt::Let(ref stmt) => self.dump_stmt_let(stmt),
             Stmt::While(ref stmt) => self.dump_stmt_while(stmt),
+            Stmt::Loop(ref stmt) => self.dump_stmt_loop(stmt),
+            Stmt::DoWhile(ref stmt) => self.dump_stmt_do_while(stmt),
+            Stmt::Fct(ref fct) => self.dump_fct(fct),
             Stmt::For(ref stmt) => self.dump_stmt_for(stmt),
         }
     }
@@ -412,7 +426,7 @@ impl<'a> AstDumper<'a> {
     }
 
     fn dump_stmt_for(&mut self, stmt: &StmtForType) {
-        dump!(self, "for @ {} {}", stmt.span, stmt.id);
+        self.dump_label("for", stmt.label, stmt.span, stmt.id);
 
         self.indent(|d| {
             d.dump_stmt_let_pattern(&stmt.pattern);
@@ -428,9 +442,14 @@ impl<'a> AstDumper<'a> {
     }
 
     fn dump_stmt_while(&mut self, stmt: &StmtWhileType) {
-        dump!(self, "while @ {} {}", stmt.span, stmt.id);
+        self.dump_label("while", stmt.label, stmt.span, stmt.id);
 
         self.indent(|d| {
+            if let Some(ref pattern) = stmt.pattern {
+                dump!(d, "let");
+                d.indent(|d| d.dump_match_pattern(pattern));
+            }
+
             dump!(d, "cond");
             d.indent(|d| {
                 d.dump_expr(&stmt.cond);
@@ -462,12 +481,49 @@ impl<'a> AstDumper<'a> {
         });
     }
 
+    fn dump_stmt_throw(&mut self, stmt: &StmtThrowType) {
+        dump!(self, "throw @ {} {}", stmt.span, stmt.id);
+        self.indent(|d| d.dump_expr(&stmt.expr));
+    }
+
+    fn dump_stmt_loop(&mut self, stmt: &StmtLoopType) {
+        self.dump_label("loop", stmt.label, stmt.span, stmt.id);
+
+        self.indent(|d| {
+            d.dump_stmt(&stmt.block);
+        });
+    }
+
+    fn dump_stmt_do_while(&mut self, stmt: &StmtDoWhileType) {
+        self.dump_label("do while", stmt.label, stmt.span, stmt.id);
+
+        self.indent(|d| {
+            dump!(d, "body");
+            d.indent(|d| {
+                d.dump_stmt(&stmt.block);
+            });
+
+            dump!(d, "cond");
+            d.indent(|d| {
+                d.dump_expr(&stmt.cond);
+            });
+        });
+    }
+
     fn dump_stmt_break(&mut self, stmt: &StmtBreakType) {
-        dump!(self, "break @ {} {}", stmt.span, stmt.id);
+        self.dump_label("break", stmt.label, stmt.span, stmt.id);
     }
 
     fn dump_stmt_continue(&mut self, stmt: &StmtContinueType) {
-        dump!(self, "continue @ {} {}", stmt.span, stmt.id);
+        self.dump_label("continue", stmt.label, stmt.span, stmt.id);
+    }
+
+    fn dump_label(&mut self, kind: &str, label: Option<Name>, span: Span, id: NodeId) {
+        if let Some(label) = label {
+            dump!(self, "{} '{} @ {} {}", kind, self.str(label), span, id);
+        } else {
+            dump!(self, "{} @ {} {}", kind, span, id);
+        }
     }
 
     fn dump_expr(&mut self, expr: &Expr) {
@@ -484,6 +540,7 @@ impl<'a> AstDumper<'a> {
             Expr::Ident(ref ident) => self.dump_expr_ident(ident),
             Expr::Call(ref call) => self.dump_expr_call(call),
             Expr::TypeParam(ref expr) => self.dump_expr_type_param(expr),
+            Expr::Index(ref expr) => self.dump_expr_index(expr),
             Expr::Path(ref path) => self.dump_expr_path(path),
             Expr::This(ref selfie) => self.dump_expr_self(selfie),
             Expr::Conv(ref expr) => self.dump_expr_conv(expr),
@@ -493,6 +550,9 @@ impl<'a> AstDumper<'a> {
             Expr::Tuple(ref expr) => self.dump_expr_tuple(expr),
             Expr::Paren(ref expr) => self.dump_expr_paren(expr),
             Expr::Match(ref expr) => self.dump_expr_match(expr),
+            Expr::Range(ref expr) => self.dump_expr_range(expr),
+            Expr::Try(ref expr) => self.dump_expr_try(expr),
+            Expr::TryCatch(ref expr) => self.dump_expr_try_catch(expr),
         }
     }
 
@@ -527,6 +587,11 @@ impl<'a> AstDumper<'a> {
         dump!(self, "if @ {} {}", expr.span, expr.id);
 
         self.indent(|d| {
+            if let Some(ref pattern) = expr.pattern {
+                dump!(d, "let");
+                d.indent(|d| d.dump_match_pattern(pattern));
+            }
+
             d.indent(|d| {
                 d.dump_expr(&expr.cond);
             });
@@ -543,7 +608,8 @@ impl<'a> AstDumper<'a> {
 
     fn dump_expr_conv(&mut self, expr: &ExprConvType) {
         self.indent(|d| d.dump_expr(&expr.object));
-        dump!(self, "as @ {} {}", expr.span, expr.id);
+        let op = if expr.is { "is" } else { "as" };
+        dump!(self, "{} @ {} {}", op, expr.span, expr.id);
         self.indent(|d| d.dump_type(&expr.data_type));
     }
 
@@ -654,13 +720,121 @@ impl<'a> AstDumper<'a> {
         });
     }
 
+    fn dump_expr_range(&mut self, expr: &ExprRangeType) {
+        let op = if expr.inclusive { "..=" } else { ".." };
+        dump!(self, "range {} @ {} {}", op, expr.span, expr.id);
+        self.indent(|d| {
+            if let Some(ref start) = expr.start {
+                d.dump_expr(start);
+            }
+
+            if let Some(ref end) = expr.end {
+                d.dump_expr(end);
+            }
+        });
+    }
+
+    fn dump_expr_try(&mut self, expr: &ExprTryType) {
+        dump!(self, "try (?) @ {} {}", expr.span, expr.id);
+        self.indent(|d| d.dump_expr(&expr.expr));
+    }
+
+    fn dump_expr_try_catch(&mut self, expr: &ExprTryCatchType) {
+        dump!(self, "try @ {} {}", expr.span, expr.id);
+
+        self.indent(|d| {
+            d.indent(|d| {
+                d.dump_expr(&expr.try_block);
+            });
+            dump!(d, "catch {}", d.str(expr.catch_name));
+            d.indent(|d| {
+                d.dump_type(&expr.catch_type);
+                d.dump_expr(&expr.catch_block);
+            });
+        });
+    }
+
     fn dump_expr_match(&mut self, expr: &ExprMatchType) {
         dump!(self, "match @ {} {}", expr.span, expr.id);
         self.indent(|d| {
             d.dump_expr(&expr.expr);
+
+            for case in &expr.cases {
+                d.dump_match_case(case);
+            }
+        });
+    }
+
+    fn dump_match_case(&mut self, case: &MatchCaseType) {
+        dump!(self, "case @ {} {}", case.span, case.id);
+        self.indent(|d| {
+            for pattern in &case.patterns {
+                d.dump_match_pattern(pattern);
+            }
+
+            if let Some(ref guard) = case.guard {
+                dump!(d, "guard");
+                d.indent(|d| d.dump_expr(guard));
+            }
+
+            dump!(d, "value");
+            d.indent(|d| d.dump_expr(&case.value));
         });
     }
 
+    fn dump_match_pattern(&mut self, pattern: &MatchPattern) {
+        match pattern.data {
+            MatchPatternData::Underscore => {
+                dump!(self, "_ @ {} {}", pattern.span, pattern.id);
+            }
+            MatchPatternData::Ident(ref ident) => {
+                let path = ident
+                    .path
+                    .names
+                    .iter()
+                    .map(|&name| self.str(name).to_string())
+                    .collect::<Vec<_>>()
+                    .join("::");
+                dump!(self, "ident {} @ {} {}", path, pattern.span, pattern.id);
+
+                if let Some(ref params) = ident.params {
+                    self.indent(|d| {
+                        for param in params {
+                            if let Some(ref pattern) = param.pattern {
+                                d.dump_match_pattern(pattern);
+                                continue;
+                            }
+
+                            match param.name {
+                                Some(name) => dump!(d, "param {}", d.str(name)),
+                                None => dump!(d, "param _"),
+                            }
+                        }
+                    });
+                }
+            }
+            MatchPatternData::Tuple(ref patterns) => {
+                dump!(self, "tuple @ {} {}", pattern.span, pattern.id);
+                self.indent(|d| {
+                    for pattern in patterns {
+                        d.dump_match_pattern(pattern);
+                    }
+                });
+            }
+            MatchPatternData::Lit(ref expr) => {
+                dump!(self, "lit @ {} {}", pattern.span, pattern.id);
+                self.indent(|d| d.dump_expr(expr));
+            }
+            MatchPatternData::Range(ref range) => {
+                dump!(self, "range @ {} {}", pattern.span, pattern.id);
+                self.indent(|d| {
+                    d.dump_expr(&range.start);
+                    d.dump_expr(&range.end);
+                });
+            }
+        }
+    }
+
     fn dump_expr_type_param(&mut self, expr: &ExprTypeParamType) {
         dump!(self, "type param @ {} {}", expr.span, expr.id);
 
@@ -674,6 +848,15 @@ impl<'a> AstDumper<'a> {
         });
     }
 
+    fn dump_expr_index(&mut self, expr: &ExprIndexType) {
+        dump!(self, "index @ {} {}", expr.span, expr.id);
+
+        self.indent(|d| {
+            d.dump_expr(&expr.object);
+            d.dump_expr(&expr.index);
+        });
+    }
+
     fn indent<F>(&mut self, fct: F)
     where
         F: Fn(&mut AstDumper) -> (),
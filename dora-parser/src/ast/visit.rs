@@ -144,7 +144,10 @@ pub fn walk_annotation<V: Visitor>(_v: &mut V, _a: &Arc<Annotation>) {}
 
 pub fn walk_const<V: Visitor>(v: &mut V, c: &Arc<Const>) {
     v.visit_type(&c.data_type);
-    v.visit_expr(&c.expr);
+
+    if let Some(ref expr) = c.expr {
+        v.visit_expr(expr);
+    }
 }
 
 pub fn walk_enum<V: Visitor>(_v: &mut V, _e: &Arc<Enum>) {
@@ -251,6 +254,17 @@ pub fn walk_stmt<V: Visitor>(v: &mut V, s: &Stmt) {
             v.visit_stmt(&value.block);
         }
 
+        Stmt::Loop(ref value) => {
+            v.visit_stmt(&value.block);
+        }
+
+        Stmt::DoWhile(ref value) => {
+            v.visit_stmt(&value.block);
+            v.visit_expr(&value.cond);
+        }
+
+        Stmt::Fct(ref fct) => v.visit_fct(fct),
+
         Stmt::Expr(ref value) => {
             v.visit_expr(&value.expr);
         }
@@ -261,6 +275,10 @@ pub fn walk_stmt<V: Visitor>(v: &mut V, s: &Stmt) {
             }
         }
 
+        Stmt::Throw(ref value) => {
+            v.visit_expr(&value.expr);
+        }
+
         Stmt::Break(_) => {}
         Stmt::Continue(_) => {}
     }
@@ -293,6 +311,11 @@ pub fn walk_expr<V: Visitor>(v: &mut V, e: &Expr) {
             }
         }
 
+        Expr::Index(ref expr) => {
+            v.visit_expr(&expr.object);
+            v.visit_expr(&expr.index);
+        }
+
         Expr::Path(ref path) => {
             v.visit_expr(&path.lhs);
             v.visit_expr(&path.rhs);
@@ -347,6 +370,34 @@ pub fn walk_expr<V: Visitor>(v: &mut V, e: &Expr) {
 
         Expr::Match(ref value) => {
             v.visit_expr(&value.expr);
+
+            for case in &value.cases {
+                if let Some(ref guard) = case.guard {
+                    v.visit_expr(guard);
+                }
+
+                v.visit_expr(&case.value);
+            }
+        }
+
+        Expr::Range(ref value) => {
+            if let Some(ref start) = value.start {
+                v.visit_expr(start);
+            }
+
+            if let Some(ref end) = value.end {
+                v.visit_expr(end);
+            }
+        }
+
+        Expr::Try(ref value) => {
+            v.visit_expr(&value.expr);
+        }
+
+        Expr::TryCatch(ref value) => {
+            v.visit_expr(&value.try_block);
+            v.visit_type(&value.catch_type);
+            v.visit_expr(&value.catch_block);
         }
 
         Expr::This(_) => {}
@@ -0,0 +1,494 @@
+use crate::ast::*;
+
+// Mutable counterpart of `Visitor`. Nodes shared through an `Arc` are
+// unshared with `Arc::make_mut` before they are walked, so rewriting a tree
+// never changes other trees that still share its nodes.
+pub trait VisitorMut: Sized {
+    fn visit_file(&mut self, a: &mut File) {
+        walk_file(self, a);
+    }
+
+    fn visit_extern(&mut self, stmt: &mut Arc<ExternPackage>) {
+        walk_extern(self, stmt);
+    }
+
+    fn visit_global(&mut self, g: &mut Arc<Global>) {
+        walk_global(self, Arc::make_mut(g));
+    }
+
+    fn visit_trait(&mut self, t: &mut Arc<Trait>) {
+        walk_trait(self, t);
+    }
+
+    fn visit_impl(&mut self, i: &mut Arc<Impl>) {
+        walk_impl(self, i);
+    }
+
+    fn visit_class(&mut self, c: &mut Arc<Class>) {
+        walk_class(self, c);
+    }
+
+    fn visit_struct(&mut self, s: &mut Arc<Struct>) {
+        walk_struct(self, Arc::make_mut(s));
+    }
+
+    fn visit_annotation(&mut self, a: &mut Arc<Annotation>) {
+        walk_annotation(self, a);
+    }
+
+    fn visit_const(&mut self, c: &mut Arc<Const>) {
+        walk_const(self, c);
+    }
+
+    fn visit_enum(&mut self, e: &mut Arc<Enum>) {
+        walk_enum(self, e);
+    }
+
+    fn visit_alias(&mut self, e: &mut Arc<Alias>) {
+        walk_alias(self, e);
+    }
+
+    fn visit_module(&mut self, e: &mut Arc<Module>) {
+        walk_module(self, e);
+    }
+
+    fn visit_use(&mut self, i: &mut Arc<Use>) {
+        walk_use(self, i);
+    }
+
+    fn visit_struct_field(&mut self, f: &mut StructField) {
+        walk_struct_field(self, f);
+    }
+
+    fn visit_ctor(&mut self, m: &mut Arc<Function>) {
+        walk_fct(self, Arc::make_mut(m));
+    }
+
+    fn visit_method(&mut self, m: &mut Arc<Function>) {
+        walk_fct(self, Arc::make_mut(m));
+    }
+
+    fn visit_field(&mut self, p: &mut Field) {
+        walk_field(self, p);
+    }
+
+    fn visit_fct(&mut self, f: &mut Arc<Function>) {
+        walk_fct(self, Arc::make_mut(f));
+    }
+
+    fn visit_param(&mut self, p: &mut Param) {
+        walk_param(self, p);
+    }
+
+    fn visit_type(&mut self, t: &mut Type) {
+        walk_type(self, t);
+    }
+
+    fn visit_stmt(&mut self, s: &mut Stmt) {
+        walk_stmt(self, s);
+    }
+
+    fn visit_expr(&mut self, e: &mut Expr) {
+        walk_expr(self, e);
+    }
+}
+
+pub fn walk_file<V: VisitorMut>(v: &mut V, f: &mut File) {
+    for e in &mut f.elements {
+        walk_elem(v, e);
+    }
+}
+
+pub fn walk_elem<V: VisitorMut>(v: &mut V, e: &mut Elem) {
+    match e {
+        Elem::Function(f) => v.visit_fct(f),
+        Elem::Class(ref mut c) => v.visit_class(c),
+        Elem::Struct(ref mut s) => v.visit_struct(s),
+        Elem::Trait(ref mut t) => v.visit_trait(t),
+        Elem::Impl(ref mut i) => v.visit_impl(i),
+        Elem::Annotation(ref mut a) => v.visit_annotation(a),
+        Elem::Global(ref mut g) => v.visit_global(g),
+        Elem::Const(ref mut c) => v.visit_const(c),
+        Elem::Enum(ref mut e) => v.visit_enum(e),
+        Elem::Alias(ref mut e) => v.visit_alias(e),
+        Elem::Module(ref mut e) => v.visit_module(e),
+        Elem::Use(ref mut i) => v.visit_use(i),
+        Elem::Extern(ref mut stmt) => v.visit_extern(stmt),
+    }
+}
+
+pub fn walk_global<V: VisitorMut>(v: &mut V, g: &mut Global) {
+    v.visit_type(&mut g.data_type);
+
+    if let Some(ref mut initial_value) = g.initial_value {
+        v.visit_expr(initial_value);
+    }
+}
+
+pub fn walk_trait<V: VisitorMut>(v: &mut V, t: &mut Arc<Trait>) {
+    for m in &mut Arc::make_mut(t).methods {
+        v.visit_method(m);
+    }
+}
+
+pub fn walk_impl<V: VisitorMut>(v: &mut V, i: &mut Arc<Impl>) {
+    for m in &mut Arc::make_mut(i).methods {
+        v.visit_method(m);
+    }
+}
+
+pub fn walk_class<V: VisitorMut>(v: &mut V, c: &mut Arc<Class>) {
+    for f in &mut Arc::make_mut(c).fields {
+        v.visit_field(f);
+    }
+}
+
+pub fn walk_annotation<V: VisitorMut>(_v: &mut V, _a: &mut Arc<Annotation>) {}
+
+pub fn walk_const<V: VisitorMut>(v: &mut V, c: &mut Arc<Const>) {
+    let c = Arc::make_mut(c);
+    v.visit_type(&mut c.data_type);
+
+    if let Some(ref mut expr) = c.expr {
+        v.visit_expr(expr);
+    }
+}
+
+pub fn walk_enum<V: VisitorMut>(_v: &mut V, _e: &mut Arc<Enum>) {
+    // nothing to do
+}
+
+pub fn walk_alias<V: VisitorMut>(v: &mut V, a: &mut Arc<Alias>) {
+    v.visit_type(&mut Arc::make_mut(a).ty);
+}
+
+pub fn walk_module<V: VisitorMut>(v: &mut V, node: &mut Arc<Module>) {
+    if let Some(ref mut elements) = Arc::make_mut(node).elements {
+        for e in elements {
+            walk_elem(v, e);
+        }
+    }
+}
+
+pub fn walk_use<V: VisitorMut>(_v: &mut V, _use: &mut Arc<Use>) {
+    // nothing to do
+}
+
+pub fn walk_extern<V: VisitorMut>(_v: &mut V, _use: &mut Arc<ExternPackage>) {
+    // nothing to do
+}
+
+pub fn walk_struct<V: VisitorMut>(v: &mut V, s: &mut Struct) {
+    for f in &mut s.fields {
+        v.visit_struct_field(f);
+    }
+}
+
+pub fn walk_struct_field<V: VisitorMut>(v: &mut V, f: &mut StructField) {
+    v.visit_type(&mut f.data_type);
+}
+
+pub fn walk_field<V: VisitorMut>(v: &mut V, f: &mut Field) {
+    v.visit_type(&mut f.data_type);
+}
+
+pub fn walk_fct<V: VisitorMut>(v: &mut V, f: &mut Function) {
+    for p in &mut f.params {
+        v.visit_param(p);
+    }
+
+    if let Some(ref mut ty) = f.return_type {
+        v.visit_type(ty);
+    }
+
+    if let Some(ref mut block) = f.block {
+        for stmt in &mut block.stmts {
+            v.visit_stmt(stmt);
+        }
+
+        if let Some(ref mut value) = block.expr {
+            v.visit_expr(value);
+        }
+    }
+}
+
+pub fn walk_param<V: VisitorMut>(v: &mut V, p: &mut Param) {
+    v.visit_type(&mut p.data_type);
+}
+
+pub fn walk_type<V: VisitorMut>(v: &mut V, t: &mut Type) {
+    match *t {
+        Type::This(_) => {}
+        Type::Basic(_) => {}
+        Type::Tuple(ref mut tuple) => {
+            for ty in &mut tuple.subtypes {
+                v.visit_type(ty);
+            }
+        }
+
+        Type::Lambda(ref mut fct) => {
+            for ty in &mut fct.params {
+                v.visit_type(ty);
+            }
+
+            v.visit_type(&mut fct.ret);
+        }
+    }
+}
+
+pub fn walk_stmt<V: VisitorMut>(v: &mut V, s: &mut Stmt) {
+    match *s {
+        Stmt::Let(ref mut value) => {
+            if let Some(ref mut ty) = value.data_type {
+                v.visit_type(ty);
+            }
+
+            if let Some(ref mut e) = value.expr {
+                v.visit_expr(e);
+            }
+        }
+
+        Stmt::For(ref mut value) => {
+            v.visit_expr(&mut value.expr);
+            v.visit_stmt(&mut value.block);
+        }
+
+        Stmt::While(ref mut value) => {
+            v.visit_expr(&mut value.cond);
+            v.visit_stmt(&mut value.block);
+        }
+
+        Stmt::Loop(ref mut value) => {
+            v.visit_stmt(&mut value.block);
+        }
+
+        Stmt::DoWhile(ref mut value) => {
+            v.visit_stmt(&mut value.block);
+            v.visit_expr(&mut value.cond);
+        }
+
+        Stmt::Fct(ref mut fct) => v.visit_fct(fct),
+
+        Stmt::Expr(ref mut value) => {
+            v.visit_expr(&mut value.expr);
+        }
+
+        Stmt::Return(ref mut value) => {
+            if let Some(ref mut e) = value.expr {
+                v.visit_expr(e);
+            }
+        }
+
+        Stmt::Throw(ref mut value) => {
+            v.visit_expr(&mut value.expr);
+        }
+
+        Stmt::Break(_) => {}
+        Stmt::Continue(_) => {}
+    }
+}
+
+pub fn walk_expr<V: VisitorMut>(v: &mut V, e: &mut Expr) {
+    match *e {
+        Expr::Un(ref mut value) => {
+            v.visit_expr(&mut value.opnd);
+        }
+
+        Expr::Bin(ref mut value) => {
+            v.visit_expr(&mut value.lhs);
+            v.visit_expr(&mut value.rhs);
+        }
+
+        Expr::Call(ref mut call) => {
+            v.visit_expr(&mut call.callee);
+
+            for arg in &mut call.args {
+                v.visit_expr(arg);
+            }
+        }
+
+        Expr::TypeParam(ref mut expr) => {
+            v.visit_expr(&mut expr.callee);
+
+            for arg in &mut expr.args {
+                v.visit_type(arg);
+            }
+        }
+
+        Expr::Index(ref mut expr) => {
+            v.visit_expr(&mut expr.object);
+            v.visit_expr(&mut expr.index);
+        }
+
+        Expr::Path(ref mut path) => {
+            v.visit_expr(&mut path.lhs);
+            v.visit_expr(&mut path.rhs);
+        }
+
+        Expr::Dot(ref mut value) => {
+            v.visit_expr(&mut value.lhs);
+            v.visit_expr(&mut value.rhs);
+        }
+
+        Expr::Conv(ref mut value) => {
+            v.visit_expr(&mut value.object);
+            v.visit_type(&mut value.data_type);
+        }
+
+        Expr::Lambda(ref mut fct) => v.visit_fct(fct),
+
+        Expr::Block(ref mut value) => {
+            for stmt in &mut value.stmts {
+                v.visit_stmt(stmt);
+            }
+
+            if let Some(ref mut expr) = value.expr {
+                v.visit_expr(expr);
+            }
+        }
+
+        Expr::Template(ref mut value) => {
+            for part in &mut value.parts {
+                v.visit_expr(part);
+            }
+        }
+
+        Expr::If(ref mut value) => {
+            v.visit_expr(&mut value.cond);
+            v.visit_expr(&mut value.then_block);
+
+            if let Some(ref mut b) = value.else_block {
+                v.visit_expr(b);
+            }
+        }
+
+        Expr::Tuple(ref mut value) => {
+            for expr in &mut value.values {
+                v.visit_expr(expr);
+            }
+        }
+
+        Expr::Paren(ref mut value) => {
+            v.visit_expr(&mut value.expr);
+        }
+
+        Expr::Match(ref mut value) => {
+            v.visit_expr(&mut value.expr);
+
+            for case in &mut value.cases {
+                if let Some(ref mut guard) = case.guard {
+                    v.visit_expr(guard);
+                }
+
+                v.visit_expr(&mut case.value);
+            }
+        }
+
+        Expr::Range(ref mut value) => {
+            if let Some(ref mut start) = value.start {
+                v.visit_expr(start);
+            }
+
+            if let Some(ref mut end) = value.end {
+                v.visit_expr(end);
+            }
+        }
+
+        Expr::Try(ref mut value) => {
+            v.visit_expr(&mut value.expr);
+        }
+
+        Expr::TryCatch(ref mut value) => {
+            v.visit_expr(&mut value.try_block);
+            v.visit_type(&mut value.catch_type);
+            v.visit_expr(&mut value.catch_block);
+        }
+
+        Expr::This(_) => {}
+        Expr::LitChar(_) => {}
+        Expr::LitInt(_) => {}
+        Expr::LitFloat(_) => {}
+        Expr::LitStr(_) => {}
+        Expr::LitBool(_) => {}
+        Expr::Ident(_) => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ast::visit::{self, Visitor};
+    use crate::ast::visit_mut::{self, VisitorMut};
+    use crate::ast::*;
+    use crate::interner::Interner;
+    use crate::parser::Parser;
+
+    struct ZeroInts;
+
+    impl VisitorMut for ZeroInts {
+        fn visit_expr(&mut self, e: &mut Expr) {
+            if let Expr::LitInt(ref mut lit) = *e {
+                lit.value = 0;
+            }
+
+            visit_mut::walk_expr(self, e);
+        }
+    }
+
+    #[derive(Default)]
+    struct CountInts {
+        total: usize,
+        non_zero: usize,
+    }
+
+    impl Visitor for CountInts {
+        fn visit_expr(&mut self, e: &Expr) {
+            if let Expr::LitInt(ref lit) = *e {
+                self.total += 1;
+
+                if lit.value != 0 {
+                    self.non_zero += 1;
+                }
+            }
+
+            visit::walk_expr(self, e);
+        }
+    }
+
+    fn count_ints(file: &File) -> CountInts {
+        let mut counter = CountInts::default();
+        counter.visit_file(file);
+        counter
+    }
+
+    #[test]
+    fn visit_mut_rewrites_all_literals() {
+        let code = "
+            let x: Int64 = 1 + 2;
+            const C: Int64 = 3;
+            fn f(a: Int64): Int64 {
+                let y = (4, -5);
+                while a < 6 { a = a + 7; }
+                for i in 8..9 { g[Int64](i * 10); }
+                let l = |b: Int64|: Int64 { b + 11 };
+                if a == 12 { 13 } else { match a { _ if a > 14 => 15, _ => \"${16}\".size() } }
+            }
+            impl Foo { fn m(): Int64 { self.x(17) } }
+            mod m { fn h() { return 18; } }
+        ";
+        let mut interner = Interner::new();
+        let (mut file, _id_generator, errors) = Parser::from_string(code, &mut interner).parse();
+        assert!(errors.is_empty());
+
+        let shared = file.clone();
+        assert_eq!(18, count_ints(&file).non_zero);
+
+        ZeroInts.visit_file(&mut file);
+
+        let counter = count_ints(&file);
+        assert_eq!(18, counter.total);
+        assert_eq!(0, counter.non_zero);
+
+        // nodes shared with the original tree are copied before rewriting
+        assert_eq!(18, count_ints(&shared).non_zero);
+    }
+}
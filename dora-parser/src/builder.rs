@@ -48,7 +48,9 @@ impl Builder {
 
 pub struct BuilderFct {
     name: Name,
+    span: Span,
     is_method: bool,
+    is_static: bool,
     visibility: Visibility,
     is_constructor: bool,
     return_type: Option<Type>,
@@ -60,7 +62,9 @@ impl<'a> BuilderFct {
     pub fn new(name: Name) -> BuilderFct {
         BuilderFct {
             name,
+            span: Span::invalid(),
             is_method: false,
+            is_static: false,
             visibility: Visibility::Public,
             is_constructor: false,
             return_type: None,
@@ -69,6 +73,21 @@ impl<'a> BuilderFct {
         }
     }
 
+    pub fn span(&mut self, span: Span) -> &mut BuilderFct {
+        self.span = span;
+        self
+    }
+
+    pub fn is_static(&mut self, is_static: bool) -> &mut BuilderFct {
+        self.is_static = is_static;
+        self
+    }
+
+    pub fn return_type(&mut self, ty: Type) -> &mut BuilderFct {
+        self.return_type = Some(ty);
+        self
+    }
+
     pub fn block(&mut self, block: Box<ExprBlockType>) -> &mut BuilderFct {
         self.block = Some(block);
         self
@@ -78,30 +97,36 @@ impl<'a> BuilderFct {
         Function {
             id,
             kind: FunctionKind::Function,
-            span: Span::invalid(),
+            span: self.span,
             name: self.name,
             method: self.is_method,
             is_optimize_immediately: false,
             visibility: self.visibility,
-            is_static: false,
+            is_static: self.is_static,
             internal: false,
             is_constructor: self.is_constructor,
             is_test: false,
+            expected_trap: None,
             params: self.params,
             return_type: self.return_type,
             block: self.block,
             type_params: None,
+            doc: None,
         }
     }
 }
 
 pub struct BuilderBlock {
     stmts: Vec<Box<Stmt>>,
+    expr: Option<Box<Expr>>,
 }
 
 impl<'a> BuilderBlock {
     pub fn new() -> BuilderBlock {
-        BuilderBlock { stmts: Vec::new() }
+        BuilderBlock {
+            stmts: Vec::new(),
+            expr: None,
+        }
     }
 
     pub fn add_expr(&mut self, id: NodeId, expr: Box<Expr>) -> &mut BuilderBlock {
@@ -115,12 +140,17 @@ impl<'a> BuilderBlock {
         self
     }
 
+    pub fn expr(&mut self, expr: Box<Expr>) -> &mut BuilderBlock {
+        self.expr = Some(expr);
+        self
+    }
+
     pub fn build(self, id: NodeId) -> Box<ExprBlockType> {
         Box::new(ExprBlockType {
             id,
             span: Span::invalid(),
             stmts: self.stmts,
-            expr: None,
+            expr: self.expr,
         })
     }
 }
@@ -8,6 +8,9 @@ pub enum ParseError {
     UnclosedString,
     UnclosedChar,
     InvalidEscapeSequence(char),
+    InvalidUnicodeEscape,
+    InvalidUtf8,
+    ReadFailed(String),
 
     // Parser errors
     ExpectedTopLevelElement(String),
@@ -22,6 +25,9 @@ pub enum ParseError {
     UnclosedStringTemplate,
     ExpectedIdentifier(String),
     InvalidSuffix(String),
+    UnsupportedCompoundAssignment(String),
+    NestingTooDeep,
+    UnknownTypeParamInWhere(String),
 }
 
 impl ParseError {
@@ -34,6 +40,9 @@ impl ParseError {
             ParseError::UnclosedString => "unclosed string.".into(),
             ParseError::UnclosedChar => "unclosed char.".into(),
             ParseError::InvalidEscapeSequence(ch) => format!("unknown escape sequence `\\{}`.", ch),
+            ParseError::InvalidUnicodeEscape => "invalid unicode escape sequence.".into(),
+            ParseError::InvalidUtf8 => "file is not valid UTF-8.".into(),
+            ParseError::ReadFailed(ref msg) => format!("could not read input: {}.", msg),
 
             // Parser errors
             ParseError::ExpectedTopLevelElement(ref token) => {
@@ -58,6 +67,13 @@ impl ParseError {
                 format!("identifier expected but got {}.", tok)
             }
             ParseError::InvalidSuffix(ref suffix) => format!("invalid suffix `{}`", suffix),
+            ParseError::UnsupportedCompoundAssignment(ref op) => {
+                format!("left-hand side of `{}` is not supported.", op)
+            }
+            ParseError::NestingTooDeep => "nesting too deep.".into(),
+            ParseError::UnknownTypeParamInWhere(ref name) => {
+                format!("`where` clause refers to unknown type param `{}`.", name)
+            }
         }
     }
 }
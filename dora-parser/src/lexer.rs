@@ -1,5 +1,6 @@
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::io::{self, Read};
 use std::rc::Rc;
 use std::sync::Arc;
 
@@ -11,20 +12,69 @@ pub mod span;
 pub mod token;
 
 pub struct Lexer {
-    content: Arc<String>,
+    content: Content,
     offset: usize,
+    // chars decoded from `offset` on, `nth` indexes into it
+    lookahead: VecDeque<char>,
+    lookahead_end: usize,
+    input: Option<Input>,
+    input_error: Option<ParseErrorWithLocation>,
     keywords: HashMap<&'static str, TokenKind>,
     _errors: Rc<RefCell<Vec<ParseErrorWithLocation>>>,
 }
 
+enum Content {
+    // complete source passed in up front
+    Complete(Arc<String>),
+    // source read from `Input` so far, only owned by the lexer
+    Buffered(String),
+}
+
+impl Content {
+    fn as_str(&self) -> &str {
+        match self {
+            Content::Complete(content) => content,
+            Content::Buffered(content) => content,
+        }
+    }
+}
+
+// Source that is appended to `content` chunk by chunk while lexing.
+struct Input {
+    reader: Box<dyn Read>,
+    // trailing bytes of a UTF-8 sequence split across reads
+    incomplete: Vec<u8>,
+}
+
+const INPUT_CHUNK_SIZE: usize = 4096;
+
 impl Lexer {
     pub fn from_str(code: &str) -> Lexer {
         let errors = Rc::new(RefCell::new(Vec::new()));
         Lexer::new(Arc::new(String::from(code)), errors)
     }
 
+    // Lexes the source read from the given reader. Input is only read as
+    // far as the lexer needs to look ahead. Spans, `source()` and rewinding
+    // refer back to already lexed input, which therefore stays buffered.
+    // Invalid UTF-8 and read failures are reported as an error token.
+    pub fn from_reader<R: Read + 'static>(reader: R) -> Lexer {
+        let errors = Rc::new(RefCell::new(Vec::new()));
+        let mut lexer = Lexer::new(Arc::new(String::new()), errors);
+        lexer.content = Content::Buffered(String::new());
+        lexer.input = Some(Input {
+            reader: Box::new(reader),
+            incomplete: Vec::new(),
+        });
+        lexer
+    }
+
+    // For input from a reader this copies the input read so far.
     pub fn source(&self) -> Arc<String> {
-        self.content.clone()
+        match self.content {
+            Content::Complete(ref content) => content.clone(),
+            Content::Buffered(ref content) => Arc::new(content.clone()),
+        }
     }
 
     pub fn new(content: Arc<String>, errors: Rc<RefCell<Vec<ParseErrorWithLocation>>>) -> Lexer {
@@ -32,12 +82,27 @@ impl Lexer {
 
         Lexer {
             offset: 0,
-            content,
+            content: Content::Complete(content),
+            lookahead: VecDeque::new(),
+            lookahead_end: 0,
+            input: None,
+            input_error: None,
             keywords,
             _errors: errors,
         }
     }
 
+    // Position of the next token, lets the parser look ahead and rewind.
+    pub fn position(&self) -> usize {
+        self.offset
+    }
+
+    pub fn rewind(&mut self, position: usize) {
+        self.offset = position;
+        self.lookahead.clear();
+        self.lookahead_end = position;
+    }
+
     pub fn read_token(&mut self) -> Result<Token, ParseErrorWithLocation> {
         loop {
             self.skip_white();
@@ -46,19 +111,31 @@ impl Lexer {
             let ch = self.curr();
 
             if let None = ch {
+                if let Some(error) = self.input_error.take() {
+                    return Err(error);
+                }
+
                 return Ok(Token::new(TokenKind::End, Span::at(start)));
             }
 
             if is_digit(ch) {
                 return self.read_number();
+            } else if self.is_doc_comment_start() {
+                return Ok(self.read_doc_comment());
             } else if self.is_comment_start() {
-                self.read_comment()?;
+                if let Some(token) = self.read_comment() {
+                    return Ok(token);
+                }
             } else if self.is_multi_comment_start() {
                 self.read_multi_comment()?;
+            } else if self.is_raw_string_start() {
+                return self.read_raw_string();
             } else if is_identifier_start(ch) {
                 return Ok(self.read_identifier());
             } else if is_quote(ch) {
                 return self.read_string(true);
+            } else if self.is_label_start() {
+                return Ok(self.read_label());
             } else if is_char_quote(ch) {
                 return self.read_char_literal();
             } else if is_operator(ch) {
@@ -82,35 +159,95 @@ impl Lexer {
         }
     }
 
-    fn read_comment(&mut self) -> Result<(), ParseErrorWithLocation> {
+    fn read_comment(&mut self) -> Option<Token> {
+        let start = self.offset;
+
         while !self.curr().is_none() && !is_newline(self.curr()) {
             self.eat_char();
         }
 
-        Ok(())
+        self.read_allow_comment(start)
     }
 
-    fn read_multi_comment(&mut self) -> Result<(), ParseErrorWithLocation> {
+    // `// dora:allow(lint1, lint2)` is the only line comment turned into a token.
+    fn read_allow_comment(&self, start: usize) -> Option<Token> {
+        let comment = &self.content.as_str()[start + 2..self.offset];
+        let text = comment.trim_start();
+        let list = text.strip_prefix("dora:allow(")?;
+        let list_end = list.find(')')?;
+
+        let mut offset = start + 2 + (comment.len() - text.len()) + "dora:allow(".len();
+        let mut lints = Vec::new();
+
+        for name in list[..list_end].split(',') {
+            let trimmed = name.trim_start();
+            let name_start = offset + (name.len() - trimmed.len());
+            let trimmed = trimmed.trim_end();
+
+            if !trimmed.is_empty() {
+                let span = Span::new(name_start as u32, trimmed.len() as u32);
+                lints.push((trimmed.to_string(), span));
+            }
+
+            offset += name.len() + 1;
+        }
+
+        let span = self.span_from(start as u32);
+        Some(Token::new(TokenKind::AllowComment(lints), span))
+    }
+
+    fn read_doc_comment(&mut self) -> Token {
         let start = self.offset();
 
+        self.eat_char();
         self.eat_char();
         self.eat_char();
 
-        while !self.curr().is_none() && !self.is_multi_comment_end() {
+        if self.curr() == Some(' ') {
             self.eat_char();
         }
 
-        if self.curr().is_none() {
-            let span = self.span_from(start);
-            return Err(ParseErrorWithLocation::new(
-                span,
-                ParseError::UnclosedComment,
-            ));
+        let mut value = String::new();
+
+        while !self.curr().is_none() && !is_newline(self.curr()) {
+            value.push(self.curr().unwrap());
+            self.eat_char();
         }
 
+        let span = self.span_from(start);
+        Token::new(TokenKind::DocComment(value), span)
+    }
+
+    fn read_multi_comment(&mut self) -> Result<(), ParseErrorWithLocation> {
+        let start = self.offset();
+
         self.eat_char();
         self.eat_char();
 
+        let mut depth = 1;
+
+        while depth > 0 {
+            if self.curr().is_none() {
+                let span = self.span_from(start);
+                return Err(ParseErrorWithLocation::new(
+                    span,
+                    ParseError::UnclosedComment,
+                ));
+            }
+
+            if self.is_multi_comment_start() {
+                self.eat_char();
+                self.eat_char();
+                depth += 1;
+            } else if self.is_multi_comment_end() {
+                self.eat_char();
+                self.eat_char();
+                depth -= 1;
+            } else {
+                self.eat_char();
+            }
+        }
+
         Ok(())
     }
 
@@ -143,6 +280,15 @@ impl Lexer {
         value
     }
 
+    fn read_label(&mut self) -> Token {
+        let start = self.offset();
+        self.eat_char();
+        self.read_identifier_as_string();
+
+        let span = self.span_from(start);
+        Token::new(TokenKind::Label, span)
+    }
+
     fn read_char_literal(&mut self) -> Result<Token, ParseErrorWithLocation> {
         let start = self.offset();
 
@@ -188,6 +334,7 @@ impl Lexer {
                     '\'' => Ok('\''),
                     '0' => Ok('\0'),
                     '$' => Ok('$'),
+                    'u' => self.read_unicode_escape(start, unclosed),
                     _ => {
                         let msg = ParseError::InvalidEscapeSequence(ch);
                         let span = self.span_from(start);
@@ -203,6 +350,65 @@ impl Lexer {
         }
     }
 
+    fn read_unicode_escape(
+        &mut self,
+        start: u32,
+        unclosed: ParseError,
+    ) -> Result<char, ParseErrorWithLocation> {
+        if self.curr() != Some('{') {
+            let span = self.span_from(start);
+            return Err(ParseErrorWithLocation::new(
+                span,
+                ParseError::InvalidUnicodeEscape,
+            ));
+        }
+
+        self.eat_char();
+        let mut value = String::new();
+
+        while is_hex_digit(self.curr()) {
+            value.push(self.curr().unwrap());
+            self.eat_char();
+        }
+
+        match self.curr() {
+            Some('}') => {
+                self.eat_char();
+            }
+
+            Some(_) => {
+                let span = self.span_from(start);
+                return Err(ParseErrorWithLocation::new(
+                    span,
+                    ParseError::InvalidUnicodeEscape,
+                ));
+            }
+
+            None => {
+                let span = self.span_from(start);
+                return Err(ParseErrorWithLocation::new(span, unclosed));
+            }
+        }
+
+        let ch = if value.is_empty() || value.len() > 6 {
+            None
+        } else {
+            u32::from_str_radix(&value, 16)
+                .ok()
+                .and_then(char::from_u32)
+        };
+
+        if let Some(ch) = ch {
+            Ok(ch)
+        } else {
+            let span = self.span_from(start);
+            Err(ParseErrorWithLocation::new(
+                span,
+                ParseError::InvalidUnicodeEscape,
+            ))
+        }
+    }
+
     fn read_string(&mut self, skip_quote: bool) -> Result<Token, ParseErrorWithLocation> {
         let start = self.offset();
         let mut value = String::new();
@@ -241,6 +447,46 @@ impl Lexer {
         }
     }
 
+    fn read_raw_string(&mut self) -> Result<Token, ParseErrorWithLocation> {
+        let start = self.offset();
+        let mut value = String::new();
+
+        assert_eq!(self.curr(), Some('r'));
+        self.eat_char();
+
+        let mut hashes = 0;
+
+        while self.curr() == Some('#') {
+            self.eat_char();
+            hashes += 1;
+        }
+
+        assert_eq!(self.curr(), Some('\"'));
+        self.eat_char();
+
+        while let Some(ch) = self.curr() {
+            self.eat_char();
+
+            if ch == '\"' && self.is_raw_string_end(hashes) {
+                for _ in 0..hashes {
+                    self.eat_char();
+                }
+
+                let ttype = TokenKind::StringTail(value);
+                let span = self.span_from(start);
+                return Ok(Token::new(ttype, span));
+            }
+
+            value.push(ch);
+        }
+
+        let span = self.span_from(start);
+        Err(ParseErrorWithLocation::new(
+            span,
+            ParseError::UnclosedString,
+        ))
+    }
+
     pub fn read_string_continuation(&mut self) -> Result<Token, ParseErrorWithLocation> {
         self.read_string(false)
     }
@@ -254,18 +500,49 @@ impl Lexer {
         let nnch = self.next().unwrap_or('x');
 
         let kind = match ch {
-            '+' => TokenKind::Add,
+            '+' => {
+                if nch == '=' {
+                    self.eat_char();
+                    TokenKind::AddEq
+                } else {
+                    TokenKind::Add
+                }
+            }
             '-' => {
                 if nch == '>' {
                     self.eat_char();
                     TokenKind::Arrow
+                } else if nch == '=' {
+                    self.eat_char();
+                    TokenKind::SubEq
                 } else {
                     TokenKind::Sub
                 }
             }
-            '*' => TokenKind::Mul,
-            '/' => TokenKind::Div,
-            '%' => TokenKind::Modulo,
+            '*' => {
+                if nch == '=' {
+                    self.eat_char();
+                    TokenKind::MulEq
+                } else {
+                    TokenKind::Mul
+                }
+            }
+            '/' => {
+                if nch == '=' {
+                    self.eat_char();
+                    TokenKind::DivEq
+                } else {
+                    TokenKind::Div
+                }
+            }
+            '%' => {
+                if nch == '=' {
+                    self.eat_char();
+                    TokenKind::ModuloEq
+                } else {
+                    TokenKind::Modulo
+                }
+            }
 
             '(' => TokenKind::LParen,
             ')' => TokenKind::RParen,
@@ -278,6 +555,9 @@ impl Lexer {
                 if nch == '|' {
                     self.eat_char();
                     TokenKind::OrOr
+                } else if nch == '=' {
+                    self.eat_char();
+                    TokenKind::OrEq
                 } else {
                     TokenKind::Or
                 }
@@ -287,12 +567,22 @@ impl Lexer {
                 if nch == '&' {
                     self.eat_char();
                     TokenKind::AndAnd
+                } else if nch == '=' {
+                    self.eat_char();
+                    TokenKind::AndEq
                 } else {
                     TokenKind::And
                 }
             }
 
-            '^' => TokenKind::Caret,
+            '^' => {
+                if nch == '=' {
+                    self.eat_char();
+                    TokenKind::CaretEq
+                } else {
+                    TokenKind::Caret
+                }
+            }
             ',' => TokenKind::Comma,
             ';' => TokenKind::Semicolon,
             ':' => {
@@ -309,6 +599,15 @@ impl Lexer {
                     self.eat_char();
 
                     TokenKind::DotDotDot
+                } else if nch == '.' && nnch == '=' {
+                    self.eat_char();
+                    self.eat_char();
+
+                    TokenKind::DotDotEq
+                } else if nch == '.' {
+                    self.eat_char();
+
+                    TokenKind::DotDot
                 } else {
                     TokenKind::Dot
                 }
@@ -339,7 +638,13 @@ impl Lexer {
 
                 '<' => {
                     self.eat_char();
-                    TokenKind::LtLt
+
+                    if nnch == '=' {
+                        self.eat_char();
+                        TokenKind::LtLtEq
+                    } else {
+                        TokenKind::LtLt
+                    }
                 }
 
                 _ => TokenKind::Lt,
@@ -356,7 +661,16 @@ impl Lexer {
 
                     if nnch == '>' {
                         self.eat_char();
-                        TokenKind::GtGtGt
+
+                        if self.curr() == Some('=') {
+                            self.eat_char();
+                            TokenKind::GtGtGtEq
+                        } else {
+                            TokenKind::GtGtGt
+                        }
+                    } else if nnch == '=' {
+                        self.eat_char();
+                        TokenKind::GtGtEq
                     } else {
                         TokenKind::GtGt
                     }
@@ -379,6 +693,7 @@ impl Lexer {
                 }
             }
             '@' => TokenKind::At,
+            '?' => TokenKind::Question,
 
             _ => {
                 self.eat_char();
@@ -434,6 +749,9 @@ impl Lexer {
 
             match suffix.as_str() {
                 "u8" => TokenKind::LitInt(value, base, IntSuffix::UInt8),
+                "u16" => TokenKind::LitInt(value, base, IntSuffix::UInt16),
+                "u32" => TokenKind::LitInt(value, base, IntSuffix::UInt32),
+                "u64" => TokenKind::LitInt(value, base, IntSuffix::UInt64),
                 "i32" => TokenKind::LitInt(value, base, IntSuffix::Int32),
                 "i64" => TokenKind::LitInt(value, base, IntSuffix::Int64),
                 "f32" if base == IntBase::Dec => TokenKind::LitFloat(value, FloatSuffix::Float32),
@@ -518,47 +836,157 @@ impl Lexer {
         let curr = self.curr();
 
         if let Some(ch) = curr {
+            self.lookahead.pop_front();
             self.offset += ch.len_utf8();
         }
 
         self.curr()
     }
 
-    fn curr(&self) -> Option<char> {
-        if self.offset < self.content.len() {
-            self.content[self.offset..].chars().next()
-        } else {
-            None
+    fn curr(&mut self) -> Option<char> {
+        self.nth(0)
+    }
+
+    fn next(&mut self) -> Option<char> {
+        self.nth(1)
+    }
+
+    // Returns the n-th char from the current offset on.
+    fn nth(&mut self, n: usize) -> Option<char> {
+        while self.lookahead.len() <= n {
+            self.fill(self.lookahead_end + 4);
+            let ch = self.content.as_str()[self.lookahead_end..].chars().next()?;
+            self.lookahead_end += ch.len_utf8();
+            self.lookahead.push_back(ch);
         }
+
+        Some(self.lookahead[n])
     }
 
-    fn next(&self) -> Option<char> {
-        let pos = self.offset + 1;
+    // Reads input until at least `end` bytes are buffered or the
+    // input is exhausted. Only complete chars are appended to `content`.
+    fn fill(&mut self, end: usize) {
+        while self.content.as_str().len() < end {
+            let input = match self.input {
+                Some(ref mut input) => input,
+                None => return,
+            };
+
+            let mut buffer = [0; INPUT_CHUNK_SIZE];
+
+            let read = match input.reader.read(&mut buffer) {
+                Ok(read) => read,
+                Err(ref err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => {
+                    self.stop_input(ParseError::ReadFailed(err.to_string()));
+                    return;
+                }
+            };
 
-        if pos < self.content.len() {
-            self.content[pos..].chars().next()
-        } else {
-            None
+            if read == 0 {
+                if input.incomplete.is_empty() {
+                    self.input = None;
+                } else {
+                    self.stop_input(ParseError::InvalidUtf8);
+                }
+
+                return;
+            }
+
+            input.incomplete.extend_from_slice(&buffer[..read]);
+
+            let (valid, invalid) = match std::str::from_utf8(&input.incomplete) {
+                Ok(_) => (input.incomplete.len(), false),
+                Err(err) => (err.valid_up_to(), err.error_len().is_some()),
+            };
+
+            let chunk = std::str::from_utf8(&input.incomplete[..valid]).expect("invalid utf8");
+            match self.content {
+                Content::Buffered(ref mut content) => content.push_str(chunk),
+                Content::Complete(_) => unreachable!(),
+            }
+
+            input.incomplete.drain(..valid);
+
+            if invalid {
+                self.stop_input(ParseError::InvalidUtf8);
+                return;
+            }
         }
     }
 
-    fn is_comment_start(&self) -> bool {
+    // Stops reading input, the error is reported once all buffered
+    // input is lexed.
+    fn stop_input(&mut self, error: ParseError) {
+        let offset = self.content.as_str().len().try_into().expect("overflow");
+        self.input = None;
+        self.input_error = Some(ParseErrorWithLocation::new(Span::at(offset), error));
+    }
+
+    fn is_comment_start(&mut self) -> bool {
         self.curr() == Some('/') && self.next() == Some('/')
     }
 
-    fn is_multi_comment_start(&self) -> bool {
+    fn is_doc_comment_start(&mut self) -> bool {
+        self.fill(self.offset + 4);
+        let rest = &self.content.as_str()[self.offset..];
+        rest.starts_with("///") && !rest.starts_with("////")
+    }
+
+    fn is_multi_comment_start(&mut self) -> bool {
         self.curr() == Some('/') && self.next() == Some('*')
     }
 
-    fn is_multi_comment_end(&self) -> bool {
+    fn is_multi_comment_end(&mut self) -> bool {
         self.curr() == Some('*') && self.next() == Some('/')
     }
+
+    // A label is a quote followed by an identifier, e.g. `'outer`. Unlike char
+    // literals it is never closed by another quote.
+    fn is_label_start(&mut self) -> bool {
+        if self.curr() != Some('\'') || !is_identifier_start(self.next()) {
+            return false;
+        }
+
+        let mut idx = 2;
+
+        while is_identifier(self.nth(idx)) {
+            idx += 1;
+        }
+
+        let ch = self.nth(idx);
+        matches!(ch, Some(':') | Some(';') | Some('}')) || is_whitespace(ch)
+    }
+
+    fn is_raw_string_start(&mut self) -> bool {
+        if self.curr() != Some('r') {
+            return false;
+        }
+
+        let mut idx = 1;
+
+        while self.nth(idx) == Some('#') {
+            idx += 1;
+        }
+
+        self.nth(idx) == Some('\"')
+    }
+
+    fn is_raw_string_end(&mut self, hashes: usize) -> bool {
+        self.fill(self.offset + hashes);
+        let rest = &self.content.as_str()[self.offset..];
+        rest.len() >= hashes && rest.bytes().take(hashes).all(|b| b == b'#')
+    }
 }
 
 fn is_digit(ch: Option<char>) -> bool {
     ch.map(|ch| ch.is_digit(10)).unwrap_or(false)
 }
 
+fn is_hex_digit(ch: Option<char>) -> bool {
+    ch.map(|ch| ch.is_ascii_hexdigit()).unwrap_or(false)
+}
+
 fn is_digit_or_underscore(ch: Option<char>, base: IntBase) -> bool {
     ch.map(|ch| ch.is_digit(base.num()) || ch == '_')
         .unwrap_or(false)
@@ -581,7 +1009,7 @@ fn is_char_quote(ch: Option<char>) -> bool {
 }
 
 fn is_operator(ch: Option<char>) -> bool {
-    ch.map(|ch| "^+-*/%&|,=!~;:.()[]{}<>@".contains(ch))
+    ch.map(|ch| "^+-*/%&|,=!~;:.()[]{}<>@?".contains(ch))
         .unwrap_or(false)
 }
 
@@ -619,17 +1047,23 @@ fn keywords_in_map() -> HashMap<&'static str, TokenKind> {
     keywords.insert("let", TokenKind::Let);
     keywords.insert("mut", TokenKind::Mut);
     keywords.insert("const", TokenKind::Const);
+    keywords.insert("where", TokenKind::Where);
 
     // control flow
     keywords.insert("return", TokenKind::Return);
     keywords.insert("if", TokenKind::If);
     keywords.insert("else", TokenKind::Else);
     keywords.insert("while", TokenKind::While);
+    keywords.insert("loop", TokenKind::Loop);
+    keywords.insert("do", TokenKind::Do);
     keywords.insert("for", TokenKind::For);
     keywords.insert("in", TokenKind::In);
     keywords.insert("break", TokenKind::Break);
     keywords.insert("continue", TokenKind::Continue);
     keywords.insert("match", TokenKind::Match);
+    keywords.insert("throw", TokenKind::Throw);
+    keywords.insert("try", TokenKind::Try);
+    keywords.insert("catch", TokenKind::Catch);
 
     // qualifiers
     keywords.insert("self", TokenKind::This);
@@ -639,6 +1073,7 @@ fn keywords_in_map() -> HashMap<&'static str, TokenKind> {
 
     // casting
     keywords.insert("as", TokenKind::As);
+    keywords.insert("is", TokenKind::Is);
 
     // unused
     keywords.insert("type", TokenKind::Type);
@@ -758,6 +1193,33 @@ mod tests {
         assert_end(&mut reader, 13);
     }
 
+    #[test]
+    fn test_read_numbers_with_unsigned_suffix() {
+        let mut reader = Lexer::from_str("1u16 2u32 0xFFu64");
+        assert_tok(
+            &mut reader,
+            TokenKind::LitInt("1".into(), IntBase::Dec, IntSuffix::UInt16),
+            0,
+            4,
+        );
+        assert_tok(
+            &mut reader,
+            TokenKind::LitInt("2".into(), IntBase::Dec, IntSuffix::UInt32),
+            5,
+            4,
+        );
+        assert_tok(
+            &mut reader,
+            TokenKind::LitInt("FF".into(), IntBase::Hex, IntSuffix::UInt64),
+            10,
+            7,
+        );
+        assert_end(&mut reader, 17);
+
+        let mut reader = Lexer::from_str("1u128");
+        assert_err(&mut reader, ParseError::InvalidSuffix("u128".into()), 0, 5);
+    }
+
     #[test]
     fn test_skip_single_line_comment() {
         let mut reader = Lexer::from_str("//test\n1");
@@ -770,6 +1232,43 @@ mod tests {
         assert_end(&mut reader, 8);
     }
 
+    #[test]
+    fn test_doc_comment() {
+        let mut reader = Lexer::from_str("/// abc\n///def\n//// ghi\n1");
+        assert_tok(&mut reader, TokenKind::DocComment("abc".into()), 0, 7);
+        assert_tok(&mut reader, TokenKind::DocComment("def".into()), 8, 6);
+        assert_tok(
+            &mut reader,
+            TokenKind::LitInt("1".into(), IntBase::Dec, IntSuffix::None),
+            24,
+            1,
+        );
+        assert_end(&mut reader, 25);
+    }
+
+    #[test]
+    fn test_allow_comment() {
+        let mut reader =
+            Lexer::from_str("// dora:allow(a, b_c)\n//dora:allow()\n// dora:allow x\n1");
+        assert_tok(
+            &mut reader,
+            TokenKind::AllowComment(vec![
+                ("a".into(), Span::new(14, 1)),
+                ("b_c".into(), Span::new(17, 3)),
+            ]),
+            0,
+            21,
+        );
+        assert_tok(&mut reader, TokenKind::AllowComment(Vec::new()), 22, 14);
+        assert_tok(
+            &mut reader,
+            TokenKind::LitInt("1".into(), IntBase::Dec, IntSuffix::None),
+            53,
+            1,
+        );
+        assert_end(&mut reader, 54);
+    }
+
     #[test]
     fn test_unfinished_line_comment() {
         let mut reader = Lexer::from_str("//abc");
@@ -803,6 +1302,48 @@ mod tests {
         assert_err(&mut reader, ParseError::UnclosedComment, 1, 6);
     }
 
+    #[test]
+    fn test_nested_multi_comment() {
+        let mut reader = Lexer::from_str("1/* a /* b */ c */2");
+        assert_tok(
+            &mut reader,
+            TokenKind::LitInt("1".into(), IntBase::Dec, IntSuffix::None),
+            0,
+            1,
+        );
+        assert_tok(
+            &mut reader,
+            TokenKind::LitInt("2".into(), IntBase::Dec, IntSuffix::None),
+            18,
+            1,
+        );
+        assert_end(&mut reader, 19);
+
+        let mut reader = Lexer::from_str("/* a /* b /* c */ b */ a */1");
+        assert_tok(
+            &mut reader,
+            TokenKind::LitInt("1".into(), IntBase::Dec, IntSuffix::None),
+            27,
+            1,
+        );
+        assert_end(&mut reader, 28);
+    }
+
+    #[test]
+    fn test_unfinished_nested_multi_comment() {
+        let mut reader = Lexer::from_str("/* a /* b */ c");
+        assert_err(&mut reader, ParseError::UnclosedComment, 0, 14);
+
+        let mut reader = Lexer::from_str("1 /* /* /* */ */");
+        assert_tok(
+            &mut reader,
+            TokenKind::LitInt("1".into(), IntBase::Dec, IntSuffix::None),
+            0,
+            1,
+        );
+        assert_err(&mut reader, ParseError::UnclosedComment, 2, 14);
+    }
+
     #[test]
     fn test_read_identifier() {
         let mut reader = Lexer::from_str("abc ident test");
@@ -978,6 +1519,118 @@ mod tests {
         assert_end(&mut reader, 5);
     }
 
+    #[test]
+    fn test_from_reader() {
+        let mut code = String::new();
+
+        for idx in 0..2000 {
+            code.push_str(&format!(
+                "fn f{idx}(a: Int64): Int64 {{\n\tlet x = a + {idx}; // comment\n\t\"s{idx}\"; x * 0x{idx}\n}}\n"
+            ));
+        }
+
+        let mut expected = Lexer::from_str(&code);
+        let mut reader = Lexer::from_reader(std::io::Cursor::new(code.clone().into_bytes()));
+        assert_same_tokens(&mut expected, &mut reader);
+        assert_eq!(reader.source(), expected.source());
+    }
+
+    struct OneByteReader {
+        data: Vec<u8>,
+        pos: usize,
+    }
+
+    impl std::io::Read for OneByteReader {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            if self.pos == self.data.len() || buf.is_empty() {
+                return Ok(0);
+            }
+
+            buf[0] = self.data[self.pos];
+            self.pos += 1;
+            Ok(1)
+        }
+    }
+
+    #[test]
+    fn test_from_reader_one_byte_per_read() {
+        let code = "fn f() { 'outer: loop { break 'outer; } }\n/// doc\n\
+                    let s = r##\"a \"# ä\"##; let c = '€'; // 😀\n/* ü */ 1.5e3 \"🦀\"";
+
+        let mut reader = Lexer::from_reader(OneByteReader {
+            data: code.as_bytes().to_vec(),
+            pos: 0,
+        });
+        assert_tok(&mut reader, TokenKind::Fn, 0, 2);
+        assert!(reader.source().len() < 8);
+
+        let mut expected = Lexer::from_str(code);
+        let mut reader = Lexer::from_reader(OneByteReader {
+            data: code.as_bytes().to_vec(),
+            pos: 0,
+        });
+        assert_same_tokens(&mut expected, &mut reader);
+        assert_eq!(reader.source(), expected.source());
+    }
+
+    #[test]
+    fn test_from_reader_long_lookahead() {
+        let n = 200_000;
+        let code = format!(
+            "'{}: r{}\"a\"{}",
+            "a".repeat(n),
+            "#".repeat(n),
+            "#".repeat(n)
+        );
+
+        let mut reader = Lexer::from_reader(std::io::Cursor::new(code.into_bytes()));
+        assert_tok(&mut reader, TokenKind::Label, 0, n as u32 + 1);
+        assert_tok(&mut reader, TokenKind::Colon, n as u32 + 1, 1);
+        assert_tok(
+            &mut reader,
+            TokenKind::StringTail("a".into()),
+            n as u32 + 3,
+            2 * n as u32 + 4,
+        );
+        assert_end(&mut reader, 3 * n as u32 + 7);
+    }
+
+    fn assert_same_tokens(expected: &mut Lexer, reader: &mut Lexer) {
+        loop {
+            let expected_tok = expected.read_token().unwrap();
+            let tok = reader.read_token().unwrap();
+            assert_eq!(expected_tok.kind, tok.kind);
+            assert_eq!(expected_tok.span, tok.span);
+
+            if tok.kind == TokenKind::End {
+                break;
+            }
+        }
+    }
+
+    #[test]
+    fn test_from_reader_invalid_utf8() {
+        let bytes: &[u8] = &[b'f', b'n', b' ', 0xff, b'x'];
+        let mut reader = Lexer::from_reader(bytes);
+        assert_tok(&mut reader, TokenKind::Fn, 0, 2);
+
+        let err = reader.read_token().unwrap_err();
+        assert_eq!(err.error, ParseError::InvalidUtf8);
+        assert_eq!(err.span, Span::at(3));
+        assert_tok(&mut reader, TokenKind::End, 3, 0);
+    }
+
+    #[test]
+    fn test_from_reader_truncated_utf8() {
+        let bytes: &[u8] = &[b'a', 0xe2, 0x82];
+        let mut reader = Lexer::from_reader(bytes);
+        assert_tok(&mut reader, TokenKind::Identifier, 0, 1);
+        assert_eq!(
+            reader.read_token().unwrap_err().error,
+            ParseError::InvalidUtf8
+        );
+    }
+
     #[test]
     fn test_string_with_newline() {
         let mut reader = Lexer::from_str("\"abc\ndef\"");
@@ -1011,12 +1664,107 @@ mod tests {
         assert_err(&mut reader, ParseError::UnclosedString, 0, 2);
     }
 
+    #[test]
+    fn test_unicode_escape_sequences() {
+        let mut reader = Lexer::from_str("'\\u{41}'");
+        assert_tok(&mut reader, TokenKind::LitChar('A'), 0, 8);
+
+        let mut reader = Lexer::from_str("'\\u{1F600}'");
+        assert_tok(&mut reader, TokenKind::LitChar('\u{1F600}'), 0, 11);
+
+        let mut reader = Lexer::from_str("'\\u{10ffff}'");
+        assert_tok(&mut reader, TokenKind::LitChar('\u{10FFFF}'), 0, 12);
+
+        let mut reader = Lexer::from_str("\"a\\u{263A}b\"");
+        assert_tok(
+            &mut reader,
+            TokenKind::StringTail("a\u{263A}b".into()),
+            0,
+            12,
+        );
+
+        let mut reader = Lexer::from_str("\"\\u{0}\\u{00000A}\"");
+        assert_tok(&mut reader, TokenKind::StringTail("\0\n".into()), 0, 17);
+    }
+
+    #[test]
+    fn test_invalid_unicode_escape_sequences() {
+        let mut reader = Lexer::from_str("'\\u{}'");
+        assert_err(&mut reader, ParseError::InvalidUnicodeEscape, 0, 5);
+
+        let mut reader = Lexer::from_str("'\\u{1234567}'");
+        assert_err(&mut reader, ParseError::InvalidUnicodeEscape, 0, 12);
+
+        let mut reader = Lexer::from_str("'\\u{D800}'");
+        assert_err(&mut reader, ParseError::InvalidUnicodeEscape, 0, 9);
+
+        let mut reader = Lexer::from_str("'\\u{110000}'");
+        assert_err(&mut reader, ParseError::InvalidUnicodeEscape, 0, 11);
+
+        let mut reader = Lexer::from_str("\"ab\\u41\"");
+        assert_err(&mut reader, ParseError::InvalidUnicodeEscape, 0, 5);
+
+        let mut reader = Lexer::from_str("\"\\u{4g}\"");
+        assert_err(&mut reader, ParseError::InvalidUnicodeEscape, 0, 5);
+
+        let mut reader = Lexer::from_str("\"\\u{41");
+        assert_err(&mut reader, ParseError::UnclosedString, 0, 6);
+    }
+
+    #[test]
+    fn test_raw_strings() {
+        let mut reader = Lexer::from_str("r\"a\\n\"");
+        assert_tok(&mut reader, TokenKind::StringTail("a\\n".into()), 0, 6);
+        assert_end(&mut reader, 6);
+
+        let mut reader = Lexer::from_str("r#\"he said \"hi\"\"#");
+        assert_tok(
+            &mut reader,
+            TokenKind::StringTail("he said \"hi\"".into()),
+            0,
+            17,
+        );
+        assert_end(&mut reader, 17);
+
+        let mut reader = Lexer::from_str("r##\"a\"#b\"##");
+        assert_tok(&mut reader, TokenKind::StringTail("a\"#b".into()), 0, 11);
+
+        let mut reader = Lexer::from_str("r\"${x}\"");
+        assert_tok(&mut reader, TokenKind::StringTail("${x}".into()), 0, 7);
+
+        let mut reader = Lexer::from_str("r #");
+        assert_tok(&mut reader, TokenKind::Identifier, 0, 1);
+    }
+
+    #[test]
+    fn test_unclosed_raw_string() {
+        let mut reader = Lexer::from_str("r#\"x");
+        assert_err(&mut reader, ParseError::UnclosedString, 0, 4);
+
+        let mut reader = Lexer::from_str("r#\"x\"");
+        assert_err(&mut reader, ParseError::UnclosedString, 0, 5);
+    }
+
     #[test]
     fn test_unclosed_string() {
         let mut reader = Lexer::from_str("\"abc");
         assert_err(&mut reader, ParseError::UnclosedString, 0, 4);
     }
 
+    #[test]
+    fn test_read_label() {
+        let mut reader = Lexer::from_str("'outer: break 'outer;");
+        assert_tok(&mut reader, TokenKind::Label, 0, 6);
+        assert_tok(&mut reader, TokenKind::Colon, 6, 1);
+        assert_tok(&mut reader, TokenKind::Break, 8, 5);
+        assert_tok(&mut reader, TokenKind::Label, 14, 6);
+        assert_tok(&mut reader, TokenKind::Semicolon, 20, 1);
+
+        let mut reader = Lexer::from_str("'a' 'a:");
+        assert_tok(&mut reader, TokenKind::LitChar('a'), 0, 3);
+        assert_tok(&mut reader, TokenKind::Label, 4, 2);
+    }
+
     #[test]
     fn test_unclosed_char() {
         let mut reader = Lexer::from_str("'a");
@@ -1063,6 +1811,11 @@ mod tests {
         assert_tok(&mut reader, TokenKind::Continue, 6, 8);
         assert_tok(&mut reader, TokenKind::Return, 15, 6);
 
+        let mut reader = Lexer::from_str("throw try catch");
+        assert_tok(&mut reader, TokenKind::Throw, 0, 5);
+        assert_tok(&mut reader, TokenKind::Try, 6, 3);
+        assert_tok(&mut reader, TokenKind::Catch, 10, 5);
+
         let mut reader = Lexer::from_str("type struct enum alias trait const");
         assert_tok(&mut reader, TokenKind::Type, 0, 4);
         assert_tok(&mut reader, TokenKind::Struct, 5, 6);
@@ -1092,6 +1845,59 @@ mod tests {
         assert_tok(&mut reader, TokenKind::DotDotDot, 8, 3);
         assert_tok(&mut reader, TokenKind::Comma, 11, 1);
 
+        let mut reader = Lexer::from_str("1..=5");
+        assert_tok(
+            &mut reader,
+            TokenKind::LitInt("1".into(), IntBase::Dec, IntSuffix::None),
+            0,
+            1,
+        );
+        assert_tok(&mut reader, TokenKind::DotDotEq, 1, 3);
+        assert_tok(
+            &mut reader,
+            TokenKind::LitInt("5".into(), IntBase::Dec, IntSuffix::None),
+            4,
+            1,
+        );
+
+        let mut reader = Lexer::from_str("1..5 ..");
+        assert_tok(
+            &mut reader,
+            TokenKind::LitInt("1".into(), IntBase::Dec, IntSuffix::None),
+            0,
+            1,
+        );
+        assert_tok(&mut reader, TokenKind::DotDot, 1, 2);
+        assert_tok(
+            &mut reader,
+            TokenKind::LitInt("5".into(), IntBase::Dec, IntSuffix::None),
+            3,
+            1,
+        );
+        assert_tok(&mut reader, TokenKind::DotDot, 5, 2);
+
+        let mut reader = Lexer::from_str("a?.b?");
+        assert_tok(&mut reader, TokenKind::Identifier, 0, 1);
+        assert_tok(&mut reader, TokenKind::Question, 1, 1);
+        assert_tok(&mut reader, TokenKind::Dot, 2, 1);
+        assert_tok(&mut reader, TokenKind::Identifier, 3, 1);
+        assert_tok(&mut reader, TokenKind::Question, 4, 1);
+
+        let mut reader = Lexer::from_str("1...3");
+        assert_tok(
+            &mut reader,
+            TokenKind::LitInt("1".into(), IntBase::Dec, IntSuffix::None),
+            0,
+            1,
+        );
+        assert_tok(&mut reader, TokenKind::DotDotDot, 1, 3);
+        assert_tok(
+            &mut reader,
+            TokenKind::LitInt("3".into(), IntBase::Dec, IntSuffix::None),
+            4,
+            1,
+        );
+
         let mut reader = Lexer::from_str("<=<>=><");
         assert_tok(&mut reader, TokenKind::Le, 0, 2);
         assert_tok(&mut reader, TokenKind::Lt, 2, 1);
@@ -1119,4 +1925,33 @@ mod tests {
         assert_tok(&mut reader, TokenKind::Underscore, 7, 1);
         assert_tok(&mut reader, TokenKind::ColonColon, 8, 2);
     }
+
+    #[test]
+    fn test_compound_assignment_operators() {
+        let mut reader = Lexer::from_str("+=-=*=/=%=");
+        assert_tok(&mut reader, TokenKind::AddEq, 0, 2);
+        assert_tok(&mut reader, TokenKind::SubEq, 2, 2);
+        assert_tok(&mut reader, TokenKind::MulEq, 4, 2);
+        assert_tok(&mut reader, TokenKind::DivEq, 6, 2);
+        assert_tok(&mut reader, TokenKind::ModuloEq, 8, 2);
+        assert_end(&mut reader, 10);
+
+        let mut reader = Lexer::from_str("&=|=^=");
+        assert_tok(&mut reader, TokenKind::AndEq, 0, 2);
+        assert_tok(&mut reader, TokenKind::OrEq, 2, 2);
+        assert_tok(&mut reader, TokenKind::CaretEq, 4, 2);
+        assert_end(&mut reader, 6);
+
+        let mut reader = Lexer::from_str("<<=>>=>>>=");
+        assert_tok(&mut reader, TokenKind::LtLtEq, 0, 3);
+        assert_tok(&mut reader, TokenKind::GtGtEq, 3, 3);
+        assert_tok(&mut reader, TokenKind::GtGtGtEq, 6, 4);
+        assert_end(&mut reader, 10);
+
+        let mut reader = Lexer::from_str("&&=||=");
+        assert_tok(&mut reader, TokenKind::AndAnd, 0, 2);
+        assert_tok(&mut reader, TokenKind::Eq, 2, 1);
+        assert_tok(&mut reader, TokenKind::OrOr, 3, 2);
+        assert_tok(&mut reader, TokenKind::Eq, 5, 1);
+    }
 }
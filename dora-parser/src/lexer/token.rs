@@ -12,10 +12,15 @@ pub enum TokenKind {
     LitInt(String, IntBase, IntSuffix),
     LitFloat(String, FloatSuffix),
     Identifier,
+    Label,
     True,
     False,
     End,
 
+    // comments
+    DocComment(String),
+    AllowComment(Vec<(String, Span)>),
+
     // "big" shapes
     Class,
     Enum,
@@ -33,17 +38,23 @@ pub enum TokenKind {
     Let,
     Mut,
     Const,
+    Where,
 
     // control flow
     Return,
     If,
     Else,
     While,
+    Loop,
+    Do,
     For,
     In,
     Break,
     Continue,
     Match,
+    Throw,
+    Try,
+    Catch,
 
     // qualifiers
     This,
@@ -53,6 +64,7 @@ pub enum TokenKind {
 
     // casting
     As,
+    Is,
 
     // operators – numbers
     Add,
@@ -84,15 +96,31 @@ pub enum TokenKind {
     GtGtGt,
     LtLt,
 
+    // operators – compound assignment
+    AddEq,
+    SubEq,
+    MulEq,
+    DivEq,
+    ModuloEq,
+    OrEq,
+    AndEq,
+    CaretEq,
+    LtLtEq,
+    GtGtEq,
+    GtGtGtEq,
+
     // basic syntax
     Eq,
     Comma,
     Semicolon,
     Dot,
+    DotDot,
     DotDotDot,
+    DotDotEq,
     Colon,
     ColonColon,
     At,
+    Question,
     Arrow,
     DoubleArrow,
 
@@ -119,6 +147,9 @@ impl TokenKind {
             TokenKind::StringExpr(_) => "string epxr",
             TokenKind::LitInt(_, _, suffix) => match suffix {
                 IntSuffix::UInt8 => "byte number",
+                IntSuffix::UInt16 => "uint16 number",
+                IntSuffix::UInt32 => "uint32 number",
+                IntSuffix::UInt64 => "uint64 number",
                 IntSuffix::Int32 => "int32 number",
                 IntSuffix::Int64 => "int64 number",
                 IntSuffix::None => "untyped number",
@@ -129,9 +160,14 @@ impl TokenKind {
                 FloatSuffix::Float64 => "float64 number",
             },
             TokenKind::Identifier => "identifier",
+            TokenKind::Label => "label",
             TokenKind::True => "true",
             TokenKind::False => "false",
 
+            // comments
+            TokenKind::DocComment(_) => "doc comment",
+            TokenKind::AllowComment(_) => "allow comment",
+
             // "big" shapes
             TokenKind::Class => "class",
             TokenKind::Enum => "enum",
@@ -149,17 +185,23 @@ impl TokenKind {
             TokenKind::Let => "let",
             TokenKind::Mut => "mut",
             TokenKind::Const => "const",
+            TokenKind::Where => "where",
 
             // control flow
             TokenKind::Return => "return",
             TokenKind::If => "if",
             TokenKind::Else => "else",
             TokenKind::While => "while",
+            TokenKind::Loop => "loop",
+            TokenKind::Do => "do",
             TokenKind::For => "for",
             TokenKind::In => "in",
             TokenKind::Break => "break",
             TokenKind::Continue => "continue",
             TokenKind::Match => "match",
+            TokenKind::Throw => "throw",
+            TokenKind::Try => "try",
+            TokenKind::Catch => "catch",
 
             // qualifiers
             TokenKind::This => "self",
@@ -169,6 +211,7 @@ impl TokenKind {
 
             // casting
             TokenKind::As => "as",
+            TokenKind::Is => "is",
 
             // operators – arithmetic
             TokenKind::Add => "+",
@@ -200,15 +243,31 @@ impl TokenKind {
             TokenKind::GtGtGt => ">>>",
             TokenKind::LtLt => "<<",
 
+            // operators – compound assignment
+            TokenKind::AddEq => "+=",
+            TokenKind::SubEq => "-=",
+            TokenKind::MulEq => "*=",
+            TokenKind::DivEq => "/=",
+            TokenKind::ModuloEq => "%=",
+            TokenKind::OrEq => "|=",
+            TokenKind::AndEq => "&=",
+            TokenKind::CaretEq => "^=",
+            TokenKind::LtLtEq => "<<=",
+            TokenKind::GtGtEq => ">>=",
+            TokenKind::GtGtGtEq => ">>>=",
+
             // basic syntax
             TokenKind::Eq => "=",
             TokenKind::Comma => ",",
             TokenKind::Semicolon => ";",
             TokenKind::Dot => ".",
+            TokenKind::DotDot => "..",
             TokenKind::DotDotDot => "...",
+            TokenKind::DotDotEq => "..=",
             TokenKind::Colon => ":",
             TokenKind::ColonColon => "::",
             TokenKind::At => "@",
+            TokenKind::Question => "?",
             TokenKind::Arrow => "->",
             TokenKind::DoubleArrow => "=>",
 
@@ -235,6 +294,9 @@ impl TokenKind {
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
 pub enum IntSuffix {
     UInt8,
+    UInt16,
+    UInt32,
+    UInt64,
     Int32,
     Int64,
     None,
@@ -277,6 +339,9 @@ impl Token {
             TokenKind::LitInt(ref val, _, suffix) => {
                 let suffix = match suffix {
                     IntSuffix::UInt8 => "B",
+                    IntSuffix::UInt16 => "u16",
+                    IntSuffix::UInt32 => "u32",
+                    IntSuffix::UInt64 => "u64",
                     IntSuffix::Int32 => "",
                     IntSuffix::Int64 => "L",
                     IntSuffix::None => "",
@@ -1,3 +1,8 @@
+use std::sync::Arc;
+
+use self::error::{ParseError, ParseErrorWithLocation};
+use self::interner::Interner;
+
 pub use self::lexer::span::Span;
 pub use self::parser::Parser;
 
@@ -8,6 +13,32 @@ pub mod interner;
 pub mod lexer;
 pub mod parser;
 
+/// Parses arbitrary bytes, this is the entry point for fuzzing the parser.
+/// Malformed input of any kind results in an error and must never panic.
+pub fn parse_bytes(data: &[u8]) -> Result<ast::File, Vec<ParseErrorWithLocation>> {
+    let code = match std::str::from_utf8(data) {
+        Ok(code) => code,
+        Err(err) => {
+            let offset = err.valid_up_to().try_into().unwrap_or(u32::MAX);
+            let span = Span::new(offset, 1);
+            return Err(vec![ParseErrorWithLocation::new(
+                span,
+                ParseError::InvalidUtf8,
+            )]);
+        }
+    };
+
+    let mut interner = Interner::new();
+    let parser = Parser::from_shared_string(Arc::new(code.to_string()), &mut interner);
+    let (file, _, errors) = parser.parse();
+
+    if errors.is_empty() {
+        Ok(file)
+    } else {
+        Err(errors)
+    }
+}
+
 pub fn compute_line_starts(content: &str) -> Vec<u32> {
     let mut pos: u32 = 0;
     let mut line_starts = vec![0];
@@ -36,7 +67,88 @@ pub fn compute_line_column(line_starts: &[u32], offset: u32) -> (u32, u32) {
 
 #[cfg(test)]
 mod tests {
-    use super::{compute_line_column, compute_line_starts};
+    use super::{compute_line_column, compute_line_starts, parse_bytes};
+    use crate::error::ParseError;
+
+    // Inputs that used to crash the parser.
+    const CRASHES: &[&[u8]] = &[
+        b"\xff\xfe",
+        b"fn f() { \"\xc3\x28\"; }",
+        "*/\u{e9}".as_bytes(),
+        ":\u{3bb}".as_bytes(),
+        "]\u{e9}".as_bytes(),
+        "\"a${ astrait=\u{3bb}".as_bytes(),
+        "=>implArray[ %if[\u{3bb}*  ".as_bytes(),
+        b"fn f() { ((((1; }",
+        b"fn f() { 1 + (2 * 3; }",
+        b"fn f() { a)]; }",
+        b"}}}",
+    ];
+
+    fn deeply_nested() -> Vec<String> {
+        let n = 100_000;
+        vec![
+            format!("fn f() {{ {}1; }}", "(".repeat(n)),
+            format!("fn f() {{ {} }}", "{".repeat(n)),
+            format!("fn f() {{ {} }}", "if a { ".repeat(n)),
+            format!("fn f() {{ {} }}", "while x { ".repeat(n)),
+            format!("fn f() {{ {} }}", "for x in y { ".repeat(n)),
+            format!("fn f() {{ {} }}", "loop { ".repeat(n)),
+            format!("fn f() {{ {} }}", "do { ".repeat(n)),
+            format!("fn f() {{ {} }}", "fn f() { ".repeat(n)),
+            format!("fn f() {{ {}1; }}", "\"${".repeat(n)),
+            format!("fn f() {{ let {}a = 1; }}", "(".repeat(n)),
+            format!("fn f(x: {}) {{}}", "Array[".repeat(n)),
+            format!("fn f(x: {}) {{}}", "(".repeat(n)),
+            "mod a { ".repeat(n),
+            format!("fn f() {{ {}1; }}", "1 + ".repeat(n)),
+            format!("fn f() {{ {}1; }}", "a = ".repeat(n)),
+            format!("fn f() {{ a{}; }}", ".b".repeat(n)),
+            format!("fn f() {{ a{}; }}", "()".repeat(n)),
+            format!("fn f() {{ a{}; }}", "?".repeat(n)),
+        ]
+    }
+
+    #[test]
+    fn test_parse_bytes_corpus() {
+        for input in CRASHES {
+            assert!(parse_bytes(input).is_err());
+        }
+
+        for input in deeply_nested() {
+            let errors = parse_bytes(input.as_bytes()).unwrap_err();
+            assert_eq!(errors.last().unwrap().error, ParseError::NestingTooDeep);
+        }
+    }
+
+    #[test]
+    fn test_parse_bytes_long_flat_chains() {
+        let n = 200;
+        let inputs = vec![
+            format!("fn f() {{ {}1; }}", "1 + ".repeat(n)),
+            format!("fn f() {{ {}1; }}", "a = ".repeat(n)),
+            format!("fn f() {{ a{}; }}", ".b".repeat(n)),
+            format!("fn f() {{ a{}; }}", "()".repeat(n)),
+            format!("fn f() {{ ({}1); }}", "1 * ".repeat(n)),
+        ];
+
+        for input in inputs {
+            assert!(parse_bytes(input.as_bytes()).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_parse_bytes_invalid_utf8() {
+        let errors = parse_bytes(b"fn f() {}\xff").unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].error, ParseError::InvalidUtf8);
+        assert_eq!(errors[0].span.start(), 9);
+    }
+
+    #[test]
+    fn test_parse_bytes_valid() {
+        assert!(parse_bytes(b"fn f() { let x = ((1 + 2) * 3).foo()?; }").is_ok());
+    }
 
     #[test]
     fn test_line_starts() {
@@ -13,6 +13,18 @@ use crate::lexer::token::*;
 use crate::lexer::*;
 use crate::Span;
 
+// Binding power of `..` and `..=`: below `||`, above assignment.
+const RANGE_PRECEDENCE: u32 = 2;
+
+// Limits how deeply the parser may recurse into expressions, types, patterns
+// and modules. Deeper input is rejected instead of overflowing the stack.
+const MAX_NESTING_DEPTH: u32 = 128;
+
+// Operator and postfix chains like `a + b + c` or `a.b.c` are parsed in a
+// loop but nest to the left in the AST. Limits the open links of all chains,
+// so walking or dropping the AST can't overflow the stack either.
+const MAX_CHAIN_DEPTH: u32 = 1024;
+
 pub struct Parser<'a> {
     lexer: Lexer,
     token: Token,
@@ -21,6 +33,12 @@ pub struct Parser<'a> {
     param_idx: u32,
     in_class_or_module: bool,
     last_end: Option<u32>,
+    doc_comment: Option<String>,
+    allow_comment: Option<Vec<(String, Span)>>,
+    suppressions: Vec<ast::Suppression>,
+    nesting_depth: u32,
+    chain_depth: u32,
+    brace_depth: u32,
     errors: Rc<RefCell<Vec<ParseErrorWithLocation>>>,
 }
 
@@ -56,6 +74,12 @@ impl<'a> Parser<'a> {
             param_idx: 0,
             in_class_or_module: false,
             last_end: Some(0),
+            doc_comment: None,
+            allow_comment: None,
+            suppressions: Vec::new(),
+            nesting_depth: 0,
+            chain_depth: 0,
+            brace_depth: 0,
             errors,
         };
 
@@ -66,6 +90,38 @@ impl<'a> Parser<'a> {
         self.id_generator.next()
     }
 
+    fn enter_nesting(&mut self) -> Result<(), ParseErrorWithLocation> {
+        if self.nesting_depth >= MAX_NESTING_DEPTH {
+            return Err(ParseErrorWithLocation::new(
+                self.token.span,
+                ParseError::NestingTooDeep,
+            ));
+        }
+
+        self.nesting_depth += 1;
+        Ok(())
+    }
+
+    fn leave_nesting(&mut self, levels: u32) {
+        self.nesting_depth -= levels;
+    }
+
+    fn enter_chain(&mut self) -> Result<(), ParseErrorWithLocation> {
+        if self.chain_depth >= MAX_CHAIN_DEPTH {
+            return Err(ParseErrorWithLocation::new(
+                self.token.span,
+                ParseError::NestingTooDeep,
+            ));
+        }
+
+        self.chain_depth += 1;
+        Ok(())
+    }
+
+    fn leave_chain(&mut self, links: u32) {
+        self.chain_depth -= links;
+    }
+
     pub fn parse(mut self) -> (ast::File, NodeIdGenerator, Vec<ParseErrorWithLocation>) {
         if let Err(msg) = self.init() {
             let mut cloned_errors = self.errors.borrow().clone();
@@ -74,6 +130,7 @@ impl<'a> Parser<'a> {
             return (
                 ast::File {
                     elements: Vec::new(),
+                    suppressions: Vec::new(),
                 },
                 self.id_generator,
                 cloned_errors,
@@ -92,6 +149,7 @@ impl<'a> Parser<'a> {
                 (
                     ast::File {
                         elements: Vec::new(),
+                        suppressions: Vec::new(),
                     },
                     self.id_generator,
                     cloned_errors,
@@ -104,10 +162,19 @@ impl<'a> Parser<'a> {
         let mut elements = vec![];
 
         while !self.token.is_eof() {
-            self.parse_top_level_element(&mut elements)?;
+            let start = self.token.span;
+
+            if let Err(error) = self.parse_top_level_element(&mut elements) {
+                self.errors.borrow_mut().push(error);
+                self.recover_top_level(start);
+            }
         }
 
-        Ok(ast::File { elements })
+        let suppressions = std::mem::take(&mut self.suppressions);
+        Ok(ast::File {
+            elements,
+            suppressions,
+        })
     }
 
     fn init(&mut self) -> Result<(), ParseErrorWithLocation> {
@@ -120,6 +187,9 @@ impl<'a> Parser<'a> {
         &mut self,
         elements: &mut Vec<Elem>,
     ) -> Result<(), ParseErrorWithLocation> {
+        let allow = self.allow_comment.take();
+        let start = self.token.span.start();
+        let doc = self.doc_comment.take();
         let modifiers = self.parse_annotation_usages()?;
 
         match self.token.kind {
@@ -130,28 +200,33 @@ impl<'a> Parser<'a> {
                         Modifier::Internal,
                         Modifier::OptimizeImmediately,
                         Modifier::Test,
+                        Modifier::ExpectTrap,
                         Modifier::Pub,
                     ],
                 );
-                let fct = self.parse_function(&modifiers)?;
+                let mut fct = self.parse_function(&modifiers)?;
+                fct.doc = doc;
                 elements.push(Elem::Function(Arc::new(fct)));
             }
 
             TokenKind::Class => {
                 self.restrict_modifiers(&modifiers, &[Modifier::Internal, Modifier::Pub]);
-                let class = self.parse_class(&modifiers)?;
+                let mut class = self.parse_class(&modifiers)?;
+                class.doc = doc;
                 elements.push(Elem::Class(Arc::new(class)));
             }
 
             TokenKind::Struct => {
                 self.restrict_modifiers(&modifiers, &[Modifier::Pub, Modifier::Internal]);
-                let struc = self.parse_struct(&modifiers)?;
+                let mut struc = self.parse_struct(&modifiers)?;
+                struc.doc = doc;
                 elements.push(Elem::Struct(Arc::new(struc)));
             }
 
             TokenKind::Trait => {
                 self.restrict_modifiers(&modifiers, &[Modifier::Pub]);
-                let trait_ = self.parse_trait(&modifiers)?;
+                let mut trait_ = self.parse_trait(&modifiers)?;
+                trait_.doc = doc;
                 elements.push(Elem::Trait(Arc::new(trait_)));
             }
 
@@ -180,13 +255,14 @@ impl<'a> Parser<'a> {
 
             TokenKind::Const => {
                 self.restrict_modifiers(&modifiers, &[Modifier::Pub]);
-                let const_ = self.parse_const(&modifiers)?;
+                let const_ = self.parse_const(&modifiers, true)?;
                 elements.push(Elem::Const(Arc::new(const_)));
             }
 
             TokenKind::Enum => {
                 self.restrict_modifiers(&modifiers, &[Modifier::Pub]);
-                let enum_ = self.parse_enum(&modifiers)?;
+                let mut enum_ = self.parse_enum(&modifiers)?;
+                enum_.doc = doc;
                 elements.push(Elem::Enum(Arc::new(enum_)));
             }
 
@@ -214,9 +290,73 @@ impl<'a> Parser<'a> {
             }
         }
 
+        self.add_suppression(allow, start);
+
         Ok(())
     }
 
+    // Skips tokens after a syntax error in a top-level element until the
+    // next element could start: a top-level keyword outside of any braces,
+    // or the token after a `}` or `;` that closes the broken element. Lexer
+    // errors while skipping are follow-up errors and aren't reported.
+    fn recover_top_level(&mut self, start: Span) {
+        self.nesting_depth = 0;
+        self.chain_depth = 0;
+
+        // always make progress, even if the element failed on its first token
+        if self.token.span == start {
+            let _ = self.advance_token();
+        }
+
+        while !self.token.is_eof() {
+            if self.brace_depth == 0 && self.is_top_level_start() {
+                break;
+            }
+
+            let token = match self.advance_token() {
+                Ok(token) => token,
+                Err(_) => continue,
+            };
+
+            if self.brace_depth == 0
+                && (token.is(TokenKind::RBrace) || token.is(TokenKind::Semicolon))
+            {
+                break;
+            }
+        }
+
+        self.brace_depth = 0;
+    }
+
+    fn is_top_level_start(&self) -> bool {
+        matches!(
+            self.token.kind,
+            TokenKind::Fn
+                | TokenKind::Class
+                | TokenKind::Struct
+                | TokenKind::Trait
+                | TokenKind::Impl
+                | TokenKind::Annotation
+                | TokenKind::Alias
+                | TokenKind::Let
+                | TokenKind::Const
+                | TokenKind::Enum
+                | TokenKind::Mod
+                | TokenKind::Use
+                | TokenKind::Extern
+                | TokenKind::Pub
+                | TokenKind::Static
+                | TokenKind::At
+        )
+    }
+
+    fn add_suppression(&mut self, allow: Option<Vec<(String, Span)>>, start: u32) {
+        if let Some(lints) = allow {
+            let span = self.span_from(start);
+            self.suppressions.push(ast::Suppression { lints, span });
+        }
+    }
+
     fn parse_extern(&mut self) -> Result<ExternPackage, ParseErrorWithLocation> {
         let start = self.token.span.start();
 
@@ -358,6 +498,7 @@ impl<'a> Parser<'a> {
             type_params,
             variants,
             visibility: Visibility::from_modifiers(modifiers),
+            doc: None,
         })
     }
 
@@ -368,6 +509,7 @@ impl<'a> Parser<'a> {
 
         let elements = if self.token.is(TokenKind::LBrace) {
             self.expect_token(TokenKind::LBrace)?;
+            self.enter_nesting()?;
 
             let mut elements = Vec::new();
 
@@ -375,6 +517,7 @@ impl<'a> Parser<'a> {
                 self.parse_top_level_element(&mut elements)?;
             }
 
+            self.leave_nesting(1);
             self.expect_token(TokenKind::RBrace)?;
             Some(elements)
         } else {
@@ -414,14 +557,25 @@ impl<'a> Parser<'a> {
         })
     }
 
-    fn parse_const(&mut self, modifiers: &Modifiers) -> Result<Const, ParseErrorWithLocation> {
+    fn parse_const(
+        &mut self,
+        modifiers: &Modifiers,
+        value_required: bool,
+    ) -> Result<Const, ParseErrorWithLocation> {
         let start = self.token.span.start();
         self.expect_token(TokenKind::Const)?;
         let name = self.expect_identifier()?;
         self.expect_token(TokenKind::Colon)?;
         let ty = self.parse_type()?;
-        self.expect_token(TokenKind::Eq)?;
-        let expr = self.parse_expression()?;
+
+        // trait bodies may declare a const without providing its value
+        let expr = if value_required || self.token.is(TokenKind::Eq) {
+            self.expect_token(TokenKind::Eq)?;
+            Some(self.parse_expression()?)
+        } else {
+            None
+        };
+
         self.expect_semicolon()?;
         let span = self.span_from(start);
 
@@ -454,14 +608,29 @@ impl<'a> Parser<'a> {
         self.expect_token(TokenKind::LBrace)?;
 
         let mut methods = Vec::new();
+        let mut consts = Vec::new();
 
         while !self.token.is(TokenKind::RBrace) {
+            let allow = self.allow_comment.take();
+            let method_start = self.token.span.start();
+            let doc = self.doc_comment.take();
             let modifiers = self.parse_annotation_usages()?;
+
+            if self.token.is(TokenKind::Const) {
+                self.restrict_modifiers(&modifiers, &[]);
+                let const_ = self.parse_const(&modifiers, true)?;
+                consts.push(Arc::new(const_));
+                self.add_suppression(allow, method_start);
+                continue;
+            }
+
             let mods = &[Modifier::Static, Modifier::Internal, Modifier::Pub];
             self.restrict_modifiers(&modifiers, mods);
 
-            let method = self.parse_function(&modifiers)?;
+            let mut method = self.parse_function(&modifiers)?;
+            method.doc = doc;
             methods.push(Arc::new(method));
+            self.add_suppression(allow, method_start);
         }
 
         self.expect_token(TokenKind::RBrace)?;
@@ -474,6 +643,7 @@ impl<'a> Parser<'a> {
             trait_type,
             extended_type: class_type,
             methods,
+            consts,
         })
     }
 
@@ -525,13 +695,24 @@ impl<'a> Parser<'a> {
         self.expect_token(TokenKind::LBrace)?;
 
         let mut methods = Vec::new();
+        let mut consts = Vec::new();
 
         while !self.token.is(TokenKind::RBrace) {
+            let doc = self.doc_comment.take();
             let modifiers = self.parse_annotation_usages()?;
+
+            if self.token.is(TokenKind::Const) {
+                self.restrict_modifiers(&modifiers, &[]);
+                let const_ = self.parse_const(&modifiers, false)?;
+                consts.push(Arc::new(const_));
+                continue;
+            }
+
             let mods = &[Modifier::Static];
             self.restrict_modifiers(&modifiers, mods);
 
-            let method = self.parse_function(&modifiers)?;
+            let mut method = self.parse_function(&modifiers)?;
+            method.doc = doc;
             methods.push(Arc::new(method));
         }
 
@@ -544,7 +725,9 @@ impl<'a> Parser<'a> {
             type_params,
             span,
             methods,
+            consts,
             visibility: Visibility::from_modifiers(modifiers),
+            doc: None,
         })
     }
 
@@ -578,6 +761,7 @@ impl<'a> Parser<'a> {
             visibility: Visibility::from_modifiers(modifiers),
             internal: modifiers.contains(Modifier::Internal),
             type_params,
+            doc: None,
         })
     }
 
@@ -634,6 +818,7 @@ impl<'a> Parser<'a> {
             visibility: Visibility::from_modifiers(modifiers),
             fields,
             type_params,
+            doc: None,
         })
     }
 
@@ -763,20 +948,7 @@ impl<'a> Parser<'a> {
 
         let bounds = if self.token.is(TokenKind::Colon) {
             self.advance_token()?;
-
-            let mut bounds = Vec::new();
-
-            loop {
-                bounds.push(self.parse_type()?);
-
-                if self.token.is(TokenKind::Add) {
-                    self.advance_token()?;
-                } else {
-                    break;
-                }
-            }
-
-            bounds
+            self.parse_type_param_bounds()?
         } else {
             Vec::new()
         };
@@ -786,6 +958,61 @@ impl<'a> Parser<'a> {
         Ok(TypeParam { name, span, bounds })
     }
 
+    fn parse_type_param_bounds(&mut self) -> Result<Vec<Type>, ParseErrorWithLocation> {
+        let mut bounds = Vec::new();
+
+        loop {
+            bounds.push(self.parse_type()?);
+
+            if self.token.is(TokenKind::Add) {
+                self.advance_token()?;
+            } else {
+                break;
+            }
+        }
+
+        Ok(bounds)
+    }
+
+    fn parse_where_clause(
+        &mut self,
+        type_params: &mut Option<Vec<TypeParam>>,
+    ) -> Result<(), ParseErrorWithLocation> {
+        if !self.token.is(TokenKind::Where) {
+            return Ok(());
+        }
+
+        self.advance_token()?;
+
+        loop {
+            let span = self.token.span;
+            let name = self.expect_identifier()?;
+            self.expect_token(TokenKind::Colon)?;
+            let bounds = self.parse_type_param_bounds()?;
+
+            // bounds from the where clause are merged into the type param
+            let type_param = type_params
+                .iter_mut()
+                .flatten()
+                .find(|type_param| type_param.name == name);
+
+            if let Some(type_param) = type_param {
+                type_param.bounds.extend(bounds);
+            } else {
+                let name = self.interner.str(name).to_string();
+                self.report_error_at(ParseError::UnknownTypeParamInWhere(name), span);
+            }
+
+            if self.token.is(TokenKind::Comma) {
+                self.advance_token()?;
+            } else {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
     fn parse_annotation_usages(&mut self) -> Result<Modifiers, ParseErrorWithLocation> {
         let mut modifiers = Modifiers::new();
         loop {
@@ -795,7 +1022,7 @@ impl<'a> Parser<'a> {
                 break;
             }
 
-            let modifier = modifier.unwrap();
+            let (modifier, arg) = modifier.unwrap();
 
             if modifiers.contains(modifier) {
                 return Err(ParseErrorWithLocation::new(
@@ -804,19 +1031,26 @@ impl<'a> Parser<'a> {
                 ));
             }
 
-            modifiers.add(modifier, self.token.span);
+            if modifier == Modifier::Pub && self.token.is(TokenKind::LParen) {
+                let restriction = self.parse_visibility_restriction()?;
+                modifiers.add_restricted_pub(restriction, self.token.span);
+            } else {
+                modifiers.add(modifier, arg, self.token.span);
+            }
         }
 
         Ok(modifiers)
     }
 
-    fn parse_annotation_usage(&mut self) -> Result<Option<Modifier>, ParseErrorWithLocation> {
+    fn parse_annotation_usage(
+        &mut self,
+    ) -> Result<Option<(Modifier, Option<Name>)>, ParseErrorWithLocation> {
         if self.token.is(TokenKind::Pub) {
             self.advance_token()?;
-            Ok(Some(Modifier::Pub))
+            Ok(Some((Modifier::Pub, None)))
         } else if self.token.is(TokenKind::Static) {
             self.advance_token()?;
-            Ok(Some(Modifier::Static))
+            Ok(Some((Modifier::Static, None)))
         } else {
             if !self.token.is(TokenKind::At) {
                 return Ok(None);
@@ -825,25 +1059,75 @@ impl<'a> Parser<'a> {
 
             if self.token.is(TokenKind::Pub) {
                 self.advance_token()?;
-                return Ok(Some(Modifier::Pub));
+                return Ok(Some((Modifier::Pub, None)));
             } else if self.token.is(TokenKind::Static) {
                 self.advance_token()?;
-                return Ok(Some(Modifier::Static));
+                return Ok(Some((Modifier::Static, None)));
             }
 
             let ident = self.expect_identifier()?;
-            match self.interner.str(ident).as_str() {
-                "internal" => Ok(Some(Modifier::Internal)),
-                "pub" => Ok(Some(Modifier::Pub)),
-                "static" => Ok(Some(Modifier::Static)),
-                "Test" => Ok(Some(Modifier::Test)),
-                "optimizeImmediately" => Ok(Some(Modifier::OptimizeImmediately)),
-                annotation => Err(ParseErrorWithLocation::new(
+            let modifier = match self.interner.str(ident).as_str() {
+                "internal" => Modifier::Internal,
+                "pub" => Modifier::Pub,
+                "static" => Modifier::Static,
+                "Test" if self.token.is(TokenKind::Dot) => {
+                    return self.parse_test_annotation_usage();
+                }
+                "Test" => Modifier::Test,
+                "optimizeImmediately" => Modifier::OptimizeImmediately,
+                annotation => {
+                    return Err(ParseErrorWithLocation::new(
+                        self.token.span,
+                        ParseError::UnknownAnnotation(annotation.into()),
+                    ))
+                }
+            };
+
+            Ok(Some((modifier, None)))
+        }
+    }
+
+    fn parse_visibility_restriction(
+        &mut self,
+    ) -> Result<VisibilityRestriction, ParseErrorWithLocation> {
+        self.expect_token(TokenKind::LParen)?;
+
+        let restriction = match self.token.kind {
+            TokenKind::Mod => VisibilityRestriction::Module,
+            TokenKind::Super => VisibilityRestriction::Super,
+            _ => {
+                return Err(ParseErrorWithLocation::new(
                     self.token.span,
-                    ParseError::UnknownAnnotation(annotation.into()),
-                )),
+                    ParseError::ExpectedToken("mod".into(), self.token.name()),
+                ))
             }
+        };
+
+        self.advance_token()?;
+        self.expect_token(TokenKind::RParen)?;
+
+        Ok(restriction)
+    }
+
+    fn parse_test_annotation_usage(
+        &mut self,
+    ) -> Result<Option<(Modifier, Option<Name>)>, ParseErrorWithLocation> {
+        self.expect_token(TokenKind::Dot)?;
+        let ident = self.expect_identifier()?;
+        let name = self.interner.str(ident);
+
+        if name.as_str() != "expectTrap" {
+            return Err(ParseErrorWithLocation::new(
+                self.token.span,
+                ParseError::UnknownAnnotation(format!("Test.{}", name)),
+            ));
         }
+
+        self.expect_token(TokenKind::LParen)?;
+        let trap = self.expect_identifier()?;
+        self.expect_token(TokenKind::RParen)?;
+
+        Ok(Some((Modifier::ExpectTrap, Some(trap))))
     }
 
     fn ban_modifiers(&mut self, modifiers: &Modifiers) {
@@ -868,9 +1152,10 @@ impl<'a> Parser<'a> {
         let start = self.token.span.start();
         self.expect_token(TokenKind::Fn)?;
         let ident = self.expect_identifier()?;
-        let type_params = self.parse_type_params()?;
+        let mut type_params = self.parse_type_params()?;
         let params = self.parse_function_params()?;
         let return_type = self.parse_function_type()?;
+        self.parse_where_clause(&mut type_params)?;
         let block = self.parse_function_block()?;
         let span = self.span_from(start);
 
@@ -885,14 +1170,24 @@ impl<'a> Parser<'a> {
             is_static: modifiers.contains(Modifier::Static),
             internal: modifiers.contains(Modifier::Internal),
             is_constructor: false,
-            is_test: modifiers.contains(Modifier::Test),
+            is_test: modifiers.contains(Modifier::Test) || modifiers.contains(Modifier::ExpectTrap),
+            expected_trap: modifiers.arg(Modifier::ExpectTrap),
             params,
             return_type,
             block,
             type_params,
+            doc: None,
         })
     }
 
+    fn parse_nested_function(&mut self) -> StmtResult {
+        let mut fct = self.parse_function(&Modifiers::new())?;
+        fct.kind = FunctionKind::Nested;
+        fct.method = false;
+
+        Ok(Box::new(Stmt::Fct(Arc::new(fct))))
+    }
+
     fn parse_function_params(&mut self) -> Result<Vec<Param>, ParseErrorWithLocation> {
         self.expect_token(TokenKind::LParen)?;
         self.param_idx = 0;
@@ -1006,6 +1301,13 @@ impl<'a> Parser<'a> {
     }
 
     fn parse_type(&mut self) -> Result<Type, ParseErrorWithLocation> {
+        self.enter_nesting()?;
+        let ty = self.parse_type_inner();
+        self.leave_nesting(1);
+        ty
+    }
+
+    fn parse_type_inner(&mut self) -> Result<Type, ParseErrorWithLocation> {
         match self.token.kind {
             TokenKind::CapitalThis => {
                 let span = self.token.span;
@@ -1061,6 +1363,10 @@ impl<'a> Parser<'a> {
     fn parse_path(&mut self) -> Result<Path, ParseErrorWithLocation> {
         let start = self.token.span.start();
         let name = self.expect_identifier()?;
+        self.parse_path_from(start, name)
+    }
+
+    fn parse_path_from(&mut self, start: u32, name: Name) -> Result<Path, ParseErrorWithLocation> {
         let mut names = vec![name];
 
         while self.token.is(TokenKind::ColonColon) {
@@ -1122,11 +1428,14 @@ impl<'a> Parser<'a> {
         if self.token.is(TokenKind::LParen) {
             let start = self.token.span.start();
             self.advance_token()?;
+            self.enter_nesting()?;
 
             let parts = self.parse_list(TokenKind::Comma, TokenKind::RParen, |p| {
                 p.parse_let_pattern()
             })?;
 
+            self.leave_nesting(1);
+
             let span = self.span_from(start);
 
             Ok(Box::new(LetPattern::Tuple(LetTupleType {
@@ -1193,6 +1502,13 @@ impl<'a> Parser<'a> {
     }
 
     fn parse_block(&mut self) -> ExprResult {
+        self.enter_nesting()?;
+        let block = self.parse_block_inner();
+        self.leave_nesting(1);
+        block
+    }
+
+    fn parse_block_inner(&mut self) -> ExprResult {
         let start = self.token.span.start();
         self.expect_token(TokenKind::LBrace)?;
         let mut stmts = vec![];
@@ -1232,17 +1548,25 @@ impl<'a> Parser<'a> {
     }
 
     fn parse_statement_or_expression(&mut self) -> StmtOrExprResult {
-        match self.token.kind {
+        let allow = self.allow_comment.take();
+        let start = self.token.span.start();
+
+        let stmt_or_expr = match self.token.kind {
             TokenKind::Let => Ok(StmtOrExpr::Stmt(self.parse_let()?)),
-            TokenKind::While => Ok(StmtOrExpr::Stmt(self.parse_while()?)),
+            TokenKind::While => Ok(StmtOrExpr::Stmt(self.parse_while(None)?)),
+            TokenKind::Loop => Ok(StmtOrExpr::Stmt(self.parse_loop(None)?)),
+            TokenKind::Do => Ok(StmtOrExpr::Stmt(self.parse_do_while(None)?)),
             TokenKind::Break => Ok(StmtOrExpr::Stmt(self.parse_break()?)),
             TokenKind::Continue => Ok(StmtOrExpr::Stmt(self.parse_continue()?)),
             TokenKind::Return => Ok(StmtOrExpr::Stmt(self.parse_return()?)),
+            TokenKind::Throw => Ok(StmtOrExpr::Stmt(self.parse_throw()?)),
             TokenKind::Else => Err(ParseErrorWithLocation::new(
                 self.token.span,
                 ParseError::MisplacedElse,
             )),
-            TokenKind::For => Ok(StmtOrExpr::Stmt(self.parse_for()?)),
+            TokenKind::For => Ok(StmtOrExpr::Stmt(self.parse_for(None)?)),
+            TokenKind::Label => Ok(StmtOrExpr::Stmt(self.parse_labeled_loop()?)),
+            TokenKind::Fn => Ok(StmtOrExpr::Stmt(self.parse_nested_function()?)),
             _ => {
                 let expr = self.parse_expression()?;
 
@@ -1259,14 +1583,18 @@ impl<'a> Parser<'a> {
                     Ok(StmtOrExpr::Expr(expr))
                 }
             }
-        }
+        }?;
+
+        self.add_suppression(allow, start);
+
+        Ok(stmt_or_expr)
     }
 
     fn parse_if(&mut self) -> ExprResult {
         let start = self.token.span.start();
         self.expect_token(TokenKind::If)?;
 
-        let cond = self.parse_expression()?;
+        let (pattern, cond) = self.parse_cond()?;
 
         let then_block = self.parse_block()?;
 
@@ -1287,12 +1615,53 @@ impl<'a> Parser<'a> {
         Ok(Box::new(Expr::create_if(
             self.generate_id(),
             span,
+            pattern,
             cond,
             then_block,
             else_block,
         )))
     }
 
+    // Parses either a plain condition or `let <pattern> = <expr>`.
+    fn parse_cond(
+        &mut self,
+    ) -> Result<(Option<Box<MatchPattern>>, Box<Expr>), ParseErrorWithLocation> {
+        if self.token.is(TokenKind::Let) {
+            self.expect_token(TokenKind::Let)?;
+            let pattern = self.parse_match_pattern()?;
+            self.expect_token(TokenKind::Eq)?;
+            let expr = self.parse_expression()?;
+
+            Ok((Some(Box::new(pattern)), expr))
+        } else {
+            Ok((None, self.parse_expression()?))
+        }
+    }
+
+    fn parse_try_catch(&mut self) -> ExprResult {
+        let start = self.token.span.start();
+        self.expect_token(TokenKind::Try)?;
+
+        let try_block = self.parse_block()?;
+
+        self.expect_token(TokenKind::Catch)?;
+        let catch_name = self.expect_identifier()?;
+        self.expect_token(TokenKind::Colon)?;
+        let catch_type = self.parse_type()?;
+        let catch_block = self.parse_block()?;
+
+        let span = self.span_from(start);
+
+        Ok(Box::new(Expr::create_try_catch(
+            self.generate_id(),
+            span,
+            try_block,
+            catch_name,
+            catch_type,
+            catch_block,
+        )))
+    }
+
     fn parse_match(&mut self) -> ExprResult {
         let start = self.token.span.start();
         self.expect_token(TokenKind::Match)?;
@@ -1342,6 +1711,13 @@ impl<'a> Parser<'a> {
             patterns.push(self.parse_match_pattern()?);
         }
 
+        let guard = if self.token.is(TokenKind::If) {
+            self.advance_token()?;
+            Some(self.parse_expression()?)
+        } else {
+            None
+        };
+
         self.expect_token(TokenKind::DoubleArrow)?;
 
         let value = self.parse_expression()?;
@@ -1351,6 +1727,7 @@ impl<'a> Parser<'a> {
             id: self.generate_id(),
             span,
             patterns,
+            guard,
             value,
         })
     }
@@ -1361,21 +1738,27 @@ impl<'a> Parser<'a> {
         let data = if self.token.is(TokenKind::Underscore) {
             self.expect_token(TokenKind::Underscore)?;
             MatchPatternData::Underscore
-        } else {
-            let path = self.parse_path()?;
+        } else if self.token.is(TokenKind::LParen) {
+            self.expect_token(TokenKind::LParen)?;
+            let patterns = self.parse_list(TokenKind::Comma, TokenKind::RParen, |this| {
+                this.parse_match_pattern()
+            })?;
 
-            let params = if self.token.is(TokenKind::LParen) {
-                self.expect_token(TokenKind::LParen)?;
-                let params = self.parse_list(TokenKind::Comma, TokenKind::RParen, |this| {
-                    this.parse_match_pattern_param()
-                })?;
+            MatchPatternData::Tuple(patterns)
+        } else if self.is_match_pattern_lit() {
+            let start = self.parse_match_pattern_lit()?;
 
-                Some(params)
-            } else {
-                None
-            };
+            if self.token.is(TokenKind::DotDotEq) {
+                self.advance_token()?;
+                let end = self.parse_match_pattern_lit()?;
 
-            MatchPatternData::Ident(MatchPatternIdent { path, params })
+                MatchPatternData::Range(MatchPatternRange { start, end })
+            } else {
+                MatchPatternData::Lit(start)
+            }
+        } else {
+            let path = self.parse_path()?;
+            self.parse_match_pattern_ident(path)?
         };
 
         let span = self.span_from(start);
@@ -1387,13 +1770,81 @@ impl<'a> Parser<'a> {
         })
     }
 
+    fn parse_match_pattern_ident(
+        &mut self,
+        path: Path,
+    ) -> Result<MatchPatternData, ParseErrorWithLocation> {
+        let params = if self.token.is(TokenKind::LParen) {
+            self.expect_token(TokenKind::LParen)?;
+            let params = self.parse_list(TokenKind::Comma, TokenKind::RParen, |this| {
+                this.parse_match_pattern_param()
+            })?;
+
+            Some(params)
+        } else {
+            None
+        };
+
+        Ok(MatchPatternData::Ident(MatchPatternIdent { path, params }))
+    }
+
+    fn is_match_pattern_lit(&self) -> bool {
+        matches!(
+            self.token.kind,
+            TokenKind::LitChar(_)
+                | TokenKind::LitInt(_, _, _)
+                | TokenKind::LitFloat(_, _)
+                | TokenKind::StringTail(_)
+                | TokenKind::True
+                | TokenKind::False
+                | TokenKind::Sub
+        )
+    }
+
+    fn parse_match_pattern_lit(&mut self) -> ExprResult {
+        match self.token.kind {
+            TokenKind::LitChar(_) => self.parse_lit_char(),
+            TokenKind::LitInt(_, _, _) => self.parse_lit_int(),
+            TokenKind::LitFloat(_, _) => self.parse_lit_float(),
+            TokenKind::StringTail(_) => self.parse_string(),
+            TokenKind::True | TokenKind::False => self.parse_bool_literal(),
+            TokenKind::Sub => {
+                let start = self.token.span.start();
+                self.advance_token()?;
+
+                let expr = match self.token.kind {
+                    TokenKind::LitInt(_, _, _) => self.parse_lit_int()?,
+                    TokenKind::LitFloat(_, _) => self.parse_lit_float()?,
+                    _ => {
+                        return Err(ParseErrorWithLocation::new(
+                            self.token.span,
+                            ParseError::ExpectedFactor(self.token.name()),
+                        ))
+                    }
+                };
+
+                let span = self.span_from(start);
+                Ok(Box::new(Expr::create_un(
+                    self.generate_id(),
+                    span,
+                    UnOp::Neg,
+                    expr,
+                )))
+            }
+            _ => Err(ParseErrorWithLocation::new(
+                self.token.span,
+                ParseError::ExpectedFactor(self.token.name()),
+            )),
+        }
+    }
+
     fn parse_match_pattern_param(&mut self) -> Result<MatchPatternParam, ParseErrorWithLocation> {
         let start = self.token.span.start();
 
-        let (mutable, name) = if self.token.is(TokenKind::Underscore) {
+        let (mutable, name, pattern) = if self.token.is(TokenKind::Underscore) {
             self.expect_token(TokenKind::Underscore)?;
 
-            (false, None)
+            (false, None, None)
         } else {
             let mutable = if self.token.is(TokenKind::Mut) {
                 self.expect_token(TokenKind::Mut)?;
@@ -1404,7 +1855,25 @@ impl<'a> Parser<'a> {
 
             let ident = self.expect_identifier()?;
 
-            (mutable, Some(ident))
+            // A path or parameter list turns the identifier into a nested
+            // variant pattern, a plain identifier is always a binding.
+            if !mutable
+                && (self.token.is(TokenKind::ColonColon) || self.token.is(TokenKind::LParen))
+            {
+                let path = self.parse_path_from(start, ident)?;
+                let data = self.parse_match_pattern_ident(path)?;
+                let span = self.span_from(start);
+
+                let pattern = MatchPattern {
+                    id: self.generate_id(),
+                    span,
+                    data,
+                };
+
+                (false, None, Some(Box::new(pattern)))
+            } else {
+                (mutable, Some(ident), None)
+            }
         };
 
         let span = self.span_from(start);
@@ -1414,10 +1883,27 @@ impl<'a> Parser<'a> {
             span,
             mutable,
             name,
+            pattern,
         })
     }
 
-    fn parse_for(&mut self) -> StmtResult {
+    fn parse_labeled_loop(&mut self) -> StmtResult {
+        let label = self.expect_label()?;
+        self.expect_token(TokenKind::Colon)?;
+
+        match self.token.kind {
+            TokenKind::While => self.parse_while(Some(label)),
+            TokenKind::Loop => self.parse_loop(Some(label)),
+            TokenKind::Do => self.parse_do_while(Some(label)),
+            TokenKind::For => self.parse_for(Some(label)),
+            _ => Err(ParseErrorWithLocation::new(
+                self.token.span,
+                ParseError::ExpectedToken("loop".into(), self.token.name()),
+            )),
+        }
+    }
+
+    fn parse_for(&mut self, label: Option<Name>) -> StmtResult {
         let start = self.token.span.start();
         self.expect_token(TokenKind::For)?;
         let pattern = self.parse_let_pattern()?;
@@ -1429,43 +1915,96 @@ impl<'a> Parser<'a> {
         Ok(Box::new(Stmt::create_for(
             self.generate_id(),
             span,
+            label,
             pattern,
             expr,
             block,
         )))
     }
 
-    fn parse_while(&mut self) -> StmtResult {
+    fn parse_while(&mut self, label: Option<Name>) -> StmtResult {
         let start = self.token.span.start();
         self.expect_token(TokenKind::While)?;
-        let expr = self.parse_expression()?;
+        let (pattern, expr) = self.parse_cond()?;
         let block = self.parse_block_stmt()?;
         let span = self.span_from(start);
 
         Ok(Box::new(Stmt::create_while(
             self.generate_id(),
             span,
+            label,
+            pattern,
             expr,
             block,
         )))
     }
 
-    fn parse_break(&mut self) -> StmtResult {
+    fn parse_loop(&mut self, label: Option<Name>) -> StmtResult {
         let start = self.token.span.start();
-        self.expect_token(TokenKind::Break)?;
-        self.expect_semicolon()?;
+        self.expect_token(TokenKind::Loop)?;
+        let block = self.parse_block_stmt()?;
         let span = self.span_from(start);
 
-        Ok(Box::new(Stmt::create_break(self.generate_id(), span)))
+        Ok(Box::new(Stmt::create_loop(
+            self.generate_id(),
+            span,
+            label,
+            block,
+        )))
     }
 
-    fn parse_continue(&mut self) -> StmtResult {
+    fn parse_do_while(&mut self, label: Option<Name>) -> StmtResult {
         let start = self.token.span.start();
-        self.expect_token(TokenKind::Continue)?;
-        self.expect_semicolon()?;
-        let span = self.span_from(start);
+        self.expect_token(TokenKind::Do)?;
+        let block = self.parse_block_stmt()?;
+        self.expect_token(TokenKind::While)?;
+        let expr = self.parse_expression()?;
+        self.expect_semicolon()?;
+        let span = self.span_from(start);
+
+        Ok(Box::new(Stmt::create_do_while(
+            self.generate_id(),
+            span,
+            label,
+            block,
+            expr,
+        )))
+    }
+
+    fn parse_break(&mut self) -> StmtResult {
+        let start = self.token.span.start();
+        self.expect_token(TokenKind::Break)?;
+        let label = self.parse_optional_label()?;
+        self.expect_semicolon()?;
+        let span = self.span_from(start);
+
+        Ok(Box::new(Stmt::create_break(
+            self.generate_id(),
+            span,
+            label,
+        )))
+    }
+
+    fn parse_continue(&mut self) -> StmtResult {
+        let start = self.token.span.start();
+        self.expect_token(TokenKind::Continue)?;
+        let label = self.parse_optional_label()?;
+        self.expect_semicolon()?;
+        let span = self.span_from(start);
+
+        Ok(Box::new(Stmt::create_continue(
+            self.generate_id(),
+            span,
+            label,
+        )))
+    }
 
-        Ok(Box::new(Stmt::create_continue(self.generate_id(), span)))
+    fn parse_optional_label(&mut self) -> Result<Option<Name>, ParseErrorWithLocation> {
+        if self.token.is(TokenKind::Label) {
+            Ok(Some(self.expect_label()?))
+        } else {
+            Ok(None)
+        }
     }
 
     fn parse_return(&mut self) -> StmtResult {
@@ -1488,26 +2027,59 @@ impl<'a> Parser<'a> {
         )))
     }
 
+    fn parse_throw(&mut self) -> StmtResult {
+        let start = self.token.span.start();
+        self.expect_token(TokenKind::Throw)?;
+        let expr = self.parse_expression()?;
+
+        self.expect_semicolon()?;
+        let span = self.span_from(start);
+
+        Ok(Box::new(Stmt::create_throw(self.generate_id(), span, expr)))
+    }
+
     fn parse_expression(&mut self) -> ExprResult {
+        self.enter_nesting()?;
+
         let result = match self.token.kind {
             TokenKind::LBrace => self.parse_block(),
             TokenKind::If => self.parse_if(),
+            TokenKind::Try => self.parse_try_catch(),
             TokenKind::Match => self.parse_match(),
             _ => self.parse_binary(0),
         };
 
+        self.leave_nesting(1);
         result
     }
 
     fn parse_binary(&mut self, precedence: u32) -> ExprResult {
         let start = self.token.span.start();
-        let mut left = self.parse_unary()?;
+        let mut left = if self.is_range_op() && precedence < RANGE_PRECEDENCE {
+            self.parse_prefix_range(start)?
+        } else {
+            self.parse_unary()?
+        };
+
+        let mut chain = 0;
 
         loop {
             let right_precedence = match self.token.kind {
-                TokenKind::Eq => 1,
-                TokenKind::OrOr => 2,
-                TokenKind::AndAnd => 3,
+                TokenKind::Eq
+                | TokenKind::AddEq
+                | TokenKind::SubEq
+                | TokenKind::MulEq
+                | TokenKind::DivEq
+                | TokenKind::ModuloEq
+                | TokenKind::OrEq
+                | TokenKind::AndEq
+                | TokenKind::CaretEq
+                | TokenKind::LtLtEq
+                | TokenKind::GtGtEq
+                | TokenKind::GtGtGtEq => 1,
+                TokenKind::DotDot | TokenKind::DotDotEq => RANGE_PRECEDENCE,
+                TokenKind::OrOr => 3,
+                TokenKind::AndAnd => 4,
                 TokenKind::EqEq
                 | TokenKind::NotEq
                 | TokenKind::Lt
@@ -1515,44 +2087,113 @@ impl<'a> Parser<'a> {
                 | TokenKind::Gt
                 | TokenKind::Ge
                 | TokenKind::EqEqEq
-                | TokenKind::NeEqEq => 4,
-                TokenKind::Add | TokenKind::Sub | TokenKind::Or | TokenKind::Caret => 5,
+                | TokenKind::NeEqEq => 5,
+                TokenKind::Add | TokenKind::Sub | TokenKind::Or | TokenKind::Caret => 6,
                 TokenKind::Mul
                 | TokenKind::Div
                 | TokenKind::Modulo
                 | TokenKind::And
                 | TokenKind::LtLt
                 | TokenKind::GtGt
-                | TokenKind::GtGtGt => 6,
-                TokenKind::As => 7,
+                | TokenKind::GtGtGt => 7,
+                TokenKind::As | TokenKind::Is => 8,
                 _ => {
+                    self.leave_chain(chain);
                     return Ok(left);
                 }
             };
 
             if precedence >= right_precedence {
+                self.leave_chain(chain);
                 return Ok(left);
             }
 
+            self.enter_chain()?;
+            chain += 1;
+
             let tok = self.advance_token()?;
 
             left = match tok.kind {
-                TokenKind::As => {
+                TokenKind::As | TokenKind::Is => {
+                    let is = tok.is(TokenKind::Is);
                     let right = Box::new(self.parse_type()?);
                     let span = self.span_from(start);
-                    let expr = Expr::create_conv(self.generate_id(), span, left, right);
+                    let expr = Expr::create_conv(self.generate_id(), span, left, right, is);
 
                     Box::new(expr)
                 }
 
+                TokenKind::DotDot | TokenKind::DotDotEq => {
+                    let inclusive = tok.is(TokenKind::DotDotEq);
+                    self.parse_range_end(start, Some(left), inclusive)?
+                }
+
                 _ => {
-                    let right = self.parse_binary(right_precedence)?;
-                    self.create_binary(tok, start, left, right)
+                    self.enter_nesting()?;
+                    let right = self.parse_binary(right_precedence);
+                    self.leave_nesting(1);
+                    let right = right?;
+
+                    if let Some(op) = compound_assign_op(&tok.kind) {
+                        self.create_compound_assign(tok, op, start, left, right)?
+                    } else {
+                        self.create_binary(tok, start, left, right)
+                    }
                 }
             };
         }
     }
 
+    fn is_range_op(&self) -> bool {
+        self.token.is(TokenKind::DotDot) || self.token.is(TokenKind::DotDotEq)
+    }
+
+    fn parse_prefix_range(&mut self, start: u32) -> ExprResult {
+        let tok = self.advance_token()?;
+        let inclusive = tok.is(TokenKind::DotDotEq);
+        self.parse_range_end(start, None, inclusive)
+    }
+
+    fn parse_range_end(
+        &mut self,
+        start: u32,
+        range_start: Option<Box<Expr>>,
+        inclusive: bool,
+    ) -> ExprResult {
+        // `a..` and `..` are open-ended, `..=` always needs an upper bound.
+        let range_end = if inclusive || self.is_range_end_start() {
+            Some(self.parse_binary(RANGE_PRECEDENCE)?)
+        } else {
+            None
+        };
+
+        let span = self.span_from(start);
+        Ok(Box::new(Expr::create_range(
+            self.generate_id(),
+            span,
+            range_start,
+            range_end,
+            inclusive,
+        )))
+    }
+
+    fn is_range_end_start(&self) -> bool {
+        !matches!(
+            self.token.kind,
+            TokenKind::RParen
+                | TokenKind::RBracket
+                | TokenKind::RBrace
+                | TokenKind::LBrace
+                | TokenKind::Comma
+                | TokenKind::Semicolon
+                | TokenKind::Eq
+                | TokenKind::DoubleArrow
+                | TokenKind::DotDot
+                | TokenKind::DotDotEq
+                | TokenKind::End
+        )
+    }
+
     fn parse_unary(&mut self) -> ExprResult {
         match self.token.kind {
             TokenKind::Add | TokenKind::Sub | TokenKind::Not => {
@@ -1582,8 +2223,14 @@ impl<'a> Parser<'a> {
     fn parse_primary(&mut self) -> ExprResult {
         let start = self.token.span.start();
         let mut left = self.parse_factor()?;
+        let mut chain = 0;
 
         loop {
+            if self.is_postfix_op() {
+                self.enter_chain()?;
+                chain += 1;
+            }
+
             left = match self.token.kind {
                 TokenKind::Dot => {
                     let op_span = self.advance_token()?.span;
@@ -1610,18 +2257,43 @@ impl<'a> Parser<'a> {
                 }
 
                 TokenKind::LBracket => {
+                    let is_index = self.is_index_bracket(&left);
                     let op_span = self.advance_token()?.span;
-                    let types =
-                        self.parse_list(TokenKind::Comma, TokenKind::RBracket, |p| p.parse_type())?;
+
+                    if is_index {
+                        let index = self.parse_expression()?;
+                        self.expect_token(TokenKind::RBracket)?;
+                        let span = self.span_from(start);
+
+                        Box::new(Expr::create_index(
+                            self.generate_id(),
+                            span,
+                            op_span,
+                            left,
+                            index,
+                        ))
+                    } else {
+                        let types =
+                            self.parse_list(TokenKind::Comma, TokenKind::RBracket, |p| {
+                                p.parse_type()
+                            })?;
+                        let span = self.span_from(start);
+
+                        Box::new(Expr::create_type_param(
+                            self.generate_id(),
+                            span,
+                            op_span,
+                            left,
+                            types,
+                        ))
+                    }
+                }
+
+                TokenKind::Question => {
+                    self.advance_token()?;
                     let span = self.span_from(start);
 
-                    Box::new(Expr::create_type_param(
-                        self.generate_id(),
-                        span,
-                        op_span,
-                        left,
-                        types,
-                    ))
+                    Box::new(Expr::create_try(self.generate_id(), span, left))
                 }
 
                 TokenKind::ColonColon => {
@@ -1639,12 +2311,91 @@ impl<'a> Parser<'a> {
                 }
 
                 _ => {
+                    self.leave_chain(chain);
                     return Ok(left);
                 }
             }
         }
     }
 
+    // `a[...]` is either an index expression or a list of type arguments. The
+    // brackets hold type arguments when their content only consists of
+    // tokens that may appear in types and they are followed by a call or
+    // path (`f[T](x)`, `Vec[T]::new()`) or follow a capitalized name
+    // (`None[Int32]`). Everything else is an index expression.
+    fn is_index_bracket(&mut self, callee: &Expr) -> bool {
+        let position = self.lexer.position();
+        let mut depth = 1;
+        let mut is_type = true;
+
+        let next = loop {
+            let token = match self.lexer.read_token() {
+                Ok(token) => token,
+                Err(_) => break TokenKind::End,
+            };
+
+            match token.kind {
+                TokenKind::LBracket => depth += 1,
+                TokenKind::RBracket => {
+                    depth -= 1;
+
+                    if depth == 0 {
+                        break match self.lexer.read_token() {
+                            Ok(token) => token.kind,
+                            Err(_) => TokenKind::End,
+                        };
+                    }
+                }
+                TokenKind::Identifier
+                | TokenKind::CapitalThis
+                | TokenKind::ColonColon
+                | TokenKind::Colon
+                | TokenKind::Comma
+                | TokenKind::LParen
+                | TokenKind::RParen
+                | TokenKind::DocComment(_)
+                | TokenKind::AllowComment(_) => {}
+                TokenKind::End => break TokenKind::End,
+                _ => is_type = false,
+            }
+        };
+
+        self.lexer.rewind(position);
+
+        if !is_type {
+            return true;
+        }
+
+        if next == TokenKind::LParen || next == TokenKind::ColonColon {
+            return false;
+        }
+
+        let name = match callee {
+            Expr::Ident(ref ident) => ident.name,
+            Expr::Path(ref path) => match path.rhs.to_ident() {
+                Some(ident) => ident.name,
+                None => return true,
+            },
+            _ => return true,
+        };
+
+        !self
+            .interner
+            .str(name)
+            .starts_with(|c: char| c.is_ascii_uppercase())
+    }
+
+    fn is_postfix_op(&self) -> bool {
+        matches!(
+            self.token.kind,
+            TokenKind::Dot
+                | TokenKind::LParen
+                | TokenKind::LBracket
+                | TokenKind::Question
+                | TokenKind::ColonColon
+        )
+    }
+
     fn create_binary(
         &mut self,
         tok: Token,
@@ -1683,11 +2434,128 @@ impl<'a> Parser<'a> {
         Box::new(Expr::create_bin(self.generate_id(), span, op, left, right))
     }
 
+    // Desugars `a op= b` into `a = a op b`. The left-hand side is duplicated,
+    // so only targets without side effects are accepted: variables, fields
+    // and index expressions with simple indices.
+    fn create_compound_assign(
+        &mut self,
+        tok: Token,
+        op: BinOp,
+        start: u32,
+        left: Box<Expr>,
+        right: Box<Expr>,
+    ) -> ExprResult {
+        let mut value = left.clone();
+
+        if !self.renumber_assign_target(&mut value) {
+            return Err(ParseErrorWithLocation::new(
+                left.span(),
+                ParseError::UnsupportedCompoundAssignment(tok.name()),
+            ));
+        }
+
+        let span = self.span_from(start);
+        let value = Box::new(Expr::create_bin(self.generate_id(), span, op, value, right));
+
+        Ok(Box::new(Expr::create_bin(
+            self.generate_id(),
+            span,
+            BinOp::Assign,
+            left,
+            value,
+        )))
+    }
+
+    fn renumber_assign_target(&mut self, expr: &mut Expr) -> bool {
+        match expr {
+            Expr::Call(ref mut call) => {
+                call.id = self.generate_id();
+
+                if !self.renumber_place(&mut call.callee) {
+                    return false;
+                }
+
+                for arg in &mut call.args {
+                    if !self.renumber_pure(arg) {
+                        return false;
+                    }
+                }
+
+                true
+            }
+
+            Expr::Index(ref mut index) => {
+                index.id = self.generate_id();
+                self.renumber_place(&mut index.object) && self.renumber_pure(&mut index.index)
+            }
+
+            _ => self.renumber_place(expr),
+        }
+    }
+
+    fn renumber_place(&mut self, expr: &mut Expr) -> bool {
+        match expr {
+            Expr::Ident(ref mut ident) if ident.type_params.is_none() => {
+                ident.id = self.generate_id();
+                true
+            }
+
+            Expr::This(ref mut this) => {
+                this.id = self.generate_id();
+                true
+            }
+
+            Expr::Dot(ref mut dot) if dot.rhs.is_ident() || dot.rhs.is_lit_int() => {
+                dot.id = self.generate_id();
+                self.renumber_pure(&mut dot.rhs) && self.renumber_place(&mut dot.lhs)
+            }
+
+            Expr::Paren(ref mut paren) => {
+                paren.id = self.generate_id();
+                self.renumber_place(&mut paren.expr)
+            }
+
+            _ => false,
+        }
+    }
+
+    fn renumber_pure(&mut self, expr: &mut Expr) -> bool {
+        match expr {
+            Expr::LitChar(ref mut lit) => {
+                lit.id = self.generate_id();
+                true
+            }
+
+            Expr::LitInt(ref mut lit) => {
+                lit.id = self.generate_id();
+                true
+            }
+
+            Expr::LitBool(ref mut lit) => {
+                lit.id = self.generate_id();
+                true
+            }
+
+            Expr::Un(ref mut un) => {
+                un.id = self.generate_id();
+                self.renumber_pure(&mut un.opnd)
+            }
+
+            Expr::Bin(ref mut bin) if !bin.op.is_any_assign() => {
+                bin.id = self.generate_id();
+                self.renumber_pure(&mut bin.lhs) && self.renumber_pure(&mut bin.rhs)
+            }
+
+            _ => self.renumber_place(expr),
+        }
+    }
+
     fn parse_factor(&mut self) -> ExprResult {
         match self.token.kind {
             TokenKind::LParen => self.parse_parentheses(),
             TokenKind::LBrace => self.parse_block(),
             TokenKind::If => self.parse_if(),
+            TokenKind::Try => self.parse_try_catch(),
             TokenKind::LitChar(_) => self.parse_lit_char(),
             TokenKind::LitInt(_, _, _) => self.parse_lit_int(),
             TokenKind::LitFloat(_, _) => self.parse_lit_float(),
@@ -1837,7 +2705,7 @@ impl<'a> Parser<'a> {
             ))),
 
             TokenKind::StringExpr(value) => {
-                let start = self.token.span.start();
+                let start = span.start();
                 let mut parts: Vec<Box<Expr>> = Vec::new();
                 parts.push(Box::new(Expr::create_lit_str(
                     self.generate_id(),
@@ -1957,10 +2825,12 @@ impl<'a> Parser<'a> {
             internal: false,
             is_constructor: false,
             is_test: false,
+            expected_trap: None,
             params,
             return_type,
             block,
             type_params: None,
+            doc: None,
         });
 
         Ok(Box::new(Expr::create_lambda(function)))
@@ -1982,6 +2852,15 @@ impl<'a> Parser<'a> {
         }
     }
 
+    // Labels are interned without their leading quote.
+    fn expect_label(&mut self) -> Result<Name, ParseErrorWithLocation> {
+        let tok = self.expect_token(TokenKind::Label)?;
+        let value = self.source_span(tok.span);
+        let interned = self.interner.intern(&value[1..]);
+
+        Ok(interned)
+    }
+
     fn expect_semicolon(&mut self) -> Result<Token, ParseErrorWithLocation> {
         self.expect_token(TokenKind::Semicolon)
     }
@@ -2018,8 +2897,49 @@ impl<'a> Parser<'a> {
     }
 
     fn advance_token(&mut self) -> Result<Token, ParseErrorWithLocation> {
-        let token = self.lexer.read_token()?;
-        Ok(self.advance_token_with(token))
+        let mut doc_comment: Option<(String, u32)> = None;
+        let mut allow_comment: Option<Vec<(String, Span)>> = None;
+
+        loop {
+            let token = self.lexer.read_token()?;
+
+            if let TokenKind::AllowComment(ref lints) = token.kind {
+                // Consecutive allow comments apply to the same element.
+                if let Some((_, ref mut end)) = doc_comment {
+                    *end = token.span.end();
+                }
+
+                allow_comment
+                    .get_or_insert_with(Vec::new)
+                    .extend(lints.iter().cloned());
+                continue;
+            }
+
+            if let TokenKind::DocComment(ref text) = token.kind {
+                // Consecutive doc comments are joined, a blank line starts a new one.
+                doc_comment = match doc_comment {
+                    Some((mut doc, end)) if !self.has_blank_line(end, token.span.start()) => {
+                        doc.push('\n');
+                        doc.push_str(text);
+                        Some((doc, token.span.end()))
+                    }
+
+                    _ => Some((text.clone(), token.span.end())),
+                };
+
+                continue;
+            }
+
+            // A doc comment only documents the token directly following it.
+            let doc_comment = doc_comment
+                .filter(|&(_, end)| !self.has_blank_line(end, token.span.start()))
+                .map(|(doc, _)| doc);
+
+            let token = self.advance_token_with(token);
+            self.doc_comment = doc_comment;
+            self.allow_comment = allow_comment;
+            return Ok(token);
+        }
     }
 
     fn advance_token_with(&mut self, token: Token) -> Token {
@@ -2029,9 +2949,22 @@ impl<'a> Parser<'a> {
             None
         };
 
+        match self.token.kind {
+            TokenKind::LBrace => self.brace_depth += 1,
+            TokenKind::RBrace => self.brace_depth = self.brace_depth.saturating_sub(1),
+            _ => {}
+        }
+
+        self.doc_comment = None;
+        self.allow_comment = None;
         mem::replace(&mut self.token, token)
     }
 
+    fn has_blank_line(&self, start: u32, end: u32) -> bool {
+        let source = self.lexer.source();
+        source[start as usize..end as usize].matches('\n').count() > 1
+    }
+
     fn source_span(&self, span: Span) -> String {
         let start = span.start() as usize;
         let end = span.end() as usize;
@@ -2043,6 +2976,25 @@ impl<'a> Parser<'a> {
     }
 }
 
+fn compound_assign_op(kind: &TokenKind) -> Option<BinOp> {
+    let op = match kind {
+        TokenKind::AddEq => BinOp::Add,
+        TokenKind::SubEq => BinOp::Sub,
+        TokenKind::MulEq => BinOp::Mul,
+        TokenKind::DivEq => BinOp::Div,
+        TokenKind::ModuloEq => BinOp::Mod,
+        TokenKind::OrEq => BinOp::BitOr,
+        TokenKind::AndEq => BinOp::BitAnd,
+        TokenKind::CaretEq => BinOp::BitXor,
+        TokenKind::LtLtEq => BinOp::ShiftL,
+        TokenKind::GtGtEq => BinOp::ArithShiftR,
+        TokenKind::GtGtGtEq => BinOp::LogicalShiftR,
+        _ => return None,
+    };
+
+    Some(op)
+}
+
 #[derive(Debug)]
 pub struct NodeIdGenerator {
     value: RefCell<usize>,
@@ -2070,7 +3022,7 @@ mod tests {
 
     use crate::error::ParseError;
     use crate::parser::Parser;
-    use crate::{compute_line_column, compute_line_starts};
+    use crate::{compute_line_column, compute_line_starts, Span};
 
     fn parse_expr(code: &'static str) -> (Box<Expr>, Interner) {
         let mut interner = Interner::new();
@@ -2116,6 +3068,19 @@ mod tests {
         parser.parse_statement().unwrap()
     }
 
+    fn parse_stmt_with_interner(code: &'static str) -> (Box<Stmt>, Interner) {
+        let mut interner = Interner::new();
+
+        let stmt = {
+            let mut parser = Parser::from_string(code, &mut interner);
+            assert!(parser.init().is_ok());
+
+            parser.parse_statement().unwrap()
+        };
+
+        (stmt, interner)
+    }
+
     fn err_stmt(code: &'static str, msg: ParseError, line: u32, col: u32) {
         let err = {
             let mut interner = Interner::new();
@@ -2536,6 +3501,164 @@ mod tests {
         assert_eq!(4, assign.rhs.to_lit_int().unwrap().value);
     }
 
+    #[test]
+    fn parse_compound_assign() {
+        let (expr, _) = parse_expr("a+=4");
+
+        let assign = expr.to_bin().unwrap();
+        assert_eq!(BinOp::Assign, assign.op);
+        assert!(assign.lhs.is_ident());
+
+        let add = assign.rhs.to_bin().unwrap();
+        assert_eq!(BinOp::Add, add.op);
+        assert!(add.lhs.is_ident());
+        assert_eq!(4, add.rhs.to_lit_int().unwrap().value);
+        assert_ne!(assign.lhs.id(), add.lhs.id());
+
+        let (expr, _) = parse_expr("a.b(i+1) >>>= 2");
+        let assign = expr.to_bin().unwrap();
+        assert_eq!(BinOp::Assign, assign.op);
+        assert!(assign.lhs.is_call());
+
+        let shift = assign.rhs.to_bin().unwrap();
+        assert_eq!(BinOp::LogicalShiftR, shift.op);
+        let index = shift.lhs.to_call().unwrap();
+        assert!(index.callee.is_dot());
+        assert_ne!(assign.lhs.id(), shift.lhs.id());
+        assert_ne!(
+            assign.lhs.to_call().unwrap().args[0].id(),
+            index.args[0].id()
+        );
+
+        err_expr(
+            "f().x += 1",
+            ParseError::UnsupportedCompoundAssignment("+=".into()),
+            1,
+            1,
+        );
+        err_expr(
+            "a[f()] |= 1",
+            ParseError::UnsupportedCompoundAssignment("|=".into()),
+            1,
+            1,
+        );
+    }
+
+    #[test]
+    fn parse_range() {
+        let (expr, _) = parse_expr("a..b");
+        let range = expr.to_range().unwrap();
+        assert!(!range.inclusive);
+        assert!(range.start.as_ref().unwrap().is_ident());
+        assert!(range.end.as_ref().unwrap().is_ident());
+
+        let (expr, _) = parse_expr("1..=3");
+        let range = expr.to_range().unwrap();
+        assert!(range.inclusive);
+        assert_eq!(1, range.start.as_ref().unwrap().to_lit_int().unwrap().value);
+        assert_eq!(3, range.end.as_ref().unwrap().to_lit_int().unwrap().value);
+    }
+
+    #[test]
+    fn parse_range_open_ended() {
+        let (expr, _) = parse_expr("..b");
+        let range = expr.to_range().unwrap();
+        assert!(!range.inclusive);
+        assert!(range.start.is_none());
+        assert!(range.end.as_ref().unwrap().is_ident());
+
+        let (expr, _) = parse_expr("..=b");
+        let range = expr.to_range().unwrap();
+        assert!(range.inclusive);
+        assert!(range.start.is_none());
+        assert!(range.end.as_ref().unwrap().is_ident());
+
+        let (expr, _) = parse_expr("a..");
+        let range = expr.to_range().unwrap();
+        assert!(!range.inclusive);
+        assert!(range.start.as_ref().unwrap().is_ident());
+        assert!(range.end.is_none());
+
+        let (expr, _) = parse_expr("..");
+        let range = expr.to_range().unwrap();
+        assert!(!range.inclusive);
+        assert!(range.start.is_none());
+        assert!(range.end.is_none());
+
+        let (expr, _) = parse_expr("f(a.., ..)");
+        let call = expr.to_call().unwrap();
+        assert_eq!(2, call.args.len());
+        assert!(call.args[0].to_range().unwrap().end.is_none());
+        assert!(call.args[1].to_range().unwrap().start.is_none());
+
+        err_expr("a..=", ParseError::ExpectedFactor("<<EOF>>".into()), 1, 5);
+    }
+
+    #[test]
+    fn parse_range_precedence() {
+        let (expr, _) = parse_expr("a+1..b*2");
+        let range = expr.to_range().unwrap();
+        assert_eq!(
+            BinOp::Add,
+            range.start.as_ref().unwrap().to_bin().unwrap().op
+        );
+        assert_eq!(BinOp::Mul, range.end.as_ref().unwrap().to_bin().unwrap().op);
+
+        let (expr, _) = parse_expr("x = 0..n");
+        let assign = expr.to_bin().unwrap();
+        assert_eq!(BinOp::Assign, assign.op);
+        assert!(assign.rhs.is_range());
+
+        let (expr, _) = parse_expr("a || b..c");
+        let range = expr.to_range().unwrap();
+        assert_eq!(
+            BinOp::Or,
+            range.start.as_ref().unwrap().to_bin().unwrap().op
+        );
+    }
+
+    #[test]
+    fn parse_dot_dot_dot_is_not_range() {
+        let (expr, _) = parse_expr("1...3");
+        assert!(!expr.is_range());
+        assert_eq!(1, expr.to_lit_int().unwrap().value);
+    }
+
+    #[test]
+    fn parse_try() {
+        let (expr, _) = parse_expr("a?");
+        let expr = expr.to_try().unwrap();
+        assert!(expr.expr.is_ident());
+
+        let (expr, _) = parse_expr("a.b()?");
+        let expr = expr.to_try().unwrap();
+        let call = expr.expr.to_call().unwrap();
+        assert!(call.callee.is_dot());
+
+        let (expr, _) = parse_expr("a? + b?");
+        let bin = expr.to_bin().unwrap();
+        assert_eq!(BinOp::Add, bin.op);
+        assert!(bin.lhs.is_try());
+        assert!(bin.rhs.is_try());
+    }
+
+    #[test]
+    fn parse_try_chained() {
+        let (expr, _) = parse_expr("foo()?.bar()?");
+        let outer = expr.to_try().unwrap();
+        let call = outer.expr.to_call().unwrap();
+        let dot = call.callee.to_dot().unwrap();
+        assert!(dot.rhs.is_ident());
+
+        let inner = dot.lhs.to_try().unwrap();
+        let call = inner.expr.to_call().unwrap();
+        assert!(call.callee.is_ident());
+
+        let (expr, _) = parse_expr("a??");
+        let outer = expr.to_try().unwrap();
+        assert!(outer.expr.to_try().unwrap().expr.is_ident());
+    }
+
     #[test]
     fn parse_shift_right() {
         let (expr, _) = parse_expr("a>>4");
@@ -2569,6 +3692,43 @@ mod tests {
         assert_eq!(0, call.args.len());
     }
 
+    #[test]
+    fn parse_index() {
+        let (expr, interner) = parse_expr("a[i]");
+        let index = expr.to_index().unwrap();
+        assert_eq!("a", *interner.str(index.object.to_ident().unwrap().name));
+        assert_eq!("i", *interner.str(index.index.to_ident().unwrap().name));
+
+        let (expr, _) = parse_expr("a.b[i + 1][0]");
+        let outer = expr.to_index().unwrap();
+        assert_eq!(0, outer.index.to_lit_int().unwrap().value);
+        let inner = outer.object.to_index().unwrap();
+        assert!(inner.object.is_dot());
+        assert!(inner.index.is_bin());
+
+        let (expr, _) = parse_expr("a[i] = 2");
+        let assign = expr.to_bin().unwrap();
+        assert_eq!(BinOp::Assign, assign.op);
+        assert!(assign.lhs.is_index());
+        assert_eq!(2, assign.rhs.to_lit_int().unwrap().value);
+
+        let (expr, _) = parse_expr("a[i] += 2");
+        let assign = expr.to_bin().unwrap();
+        assert!(assign.lhs.is_index());
+        let add = assign.rhs.to_bin().unwrap();
+        assert_eq!(BinOp::Add, add.op);
+        assert!(add.lhs.is_index());
+        assert_ne!(assign.lhs.id(), add.lhs.id());
+
+        // type arguments are still parsed as before
+        let (expr, _) = parse_expr("f[Int32](1)");
+        assert!(expr.to_call().unwrap().callee.is_type_param());
+        let (expr, _) = parse_expr("Vec[T]::new()");
+        assert!(expr.to_call().unwrap().callee.is_path());
+        let (expr, _) = parse_expr("None[Int32]");
+        assert!(expr.is_type_param());
+    }
+
     #[test]
     fn parse_call_with_params() {
         let (expr, interner) = parse_expr("fname2(1,2,3)");
@@ -2596,6 +3756,28 @@ mod tests {
         assert!(fct.return_type.is_none());
     }
 
+    #[test]
+    fn parse_nested_function() {
+        let (prog, interner) = parse("fn f() { fn g(a: Int32): Int32 { a } g(1) }");
+        let fct = prog.fct0();
+        assert!(!fct.kind.is_nested());
+
+        let block = fct.block();
+        assert_eq!(1, block.stmts.len());
+        assert!(block.expr.as_ref().unwrap().is_call());
+
+        let nested = block.stmts[0].to_fct().unwrap();
+        assert!(nested.kind.is_nested());
+        assert_eq!("g", *interner.str(nested.name));
+        assert_eq!(1, nested.params.len());
+        assert!(nested.return_type.is_some());
+
+        let stmt = parse_stmt("{ fn g() {} fn h() { g(); } }");
+        let block = stmt.to_expr().unwrap().expr.to_block().unwrap();
+        assert_eq!(2, block.stmts.len());
+        assert!(block.stmts.iter().all(|stmt| stmt.is_fct()));
+    }
+
     #[test]
     fn parse_function_with_single_param() {
         let (p1, interner1) = parse("fn f(a:int) { }");
@@ -2784,6 +3966,58 @@ mod tests {
         assert!(ifexpr.else_block.is_none());
     }
 
+    #[test]
+    fn parse_if_let() {
+        let (expr, interner) = parse_expr("if let Some(x) = foo { x; } else { 3; }");
+        let ifexpr = expr.to_if().unwrap();
+
+        match ifexpr.pattern.as_ref().unwrap().data {
+            MatchPatternData::Ident(ref ident) => {
+                assert_eq!(ident.path.names.len(), 1);
+                let params = ident.params.as_ref().unwrap();
+                assert_eq!("x", *interner.str(params[0].name.unwrap()));
+            }
+            _ => unreachable!(),
+        }
+        assert!(ifexpr.cond.is_ident());
+        assert!(ifexpr.else_block.is_some());
+
+        let (expr, _) = parse_expr("if x { 1; } else if let _ = y { 2; }");
+        let ifexpr = expr.to_if().unwrap();
+        assert!(ifexpr.pattern.is_none());
+        let else_if = ifexpr.else_block.as_ref().unwrap().to_if().unwrap();
+        assert!(matches!(
+            else_if.pattern.as_ref().unwrap().data,
+            MatchPatternData::Underscore
+        ));
+
+        parse_err(
+            "fn f() { if let Some(x) foo {} }",
+            ParseError::ExpectedToken("=".into(), "identifier".into()),
+            1,
+            25,
+        );
+    }
+
+    #[test]
+    fn parse_while_let() {
+        let stmt = parse_stmt("while let Foo::A(a, _) = it.next() { a; }");
+        let whilestmt = stmt.to_while().unwrap();
+
+        match whilestmt.pattern.as_ref().unwrap().data {
+            MatchPatternData::Ident(ref ident) => {
+                assert_eq!(ident.path.names.len(), 2);
+                assert_eq!(ident.params.as_ref().unwrap().len(), 2);
+            }
+            _ => unreachable!(),
+        }
+        assert!(whilestmt.cond.is_call());
+        assert!(whilestmt.block.is_expr());
+
+        let stmt = parse_stmt("while x { 2; }");
+        assert!(stmt.to_while().unwrap().pattern.is_none());
+    }
+
     #[test]
     fn parse_while() {
         let stmt = parse_stmt("while true { 2; }");
@@ -2837,6 +4071,77 @@ mod tests {
         assert!(stmt.is_break());
     }
 
+    #[test]
+    fn parse_labeled_loops() {
+        let (stmt, interner) = parse_stmt_with_interner("'outer: while true { break 'outer; }");
+        let whilestmt = stmt.to_while().unwrap();
+        assert_eq!("outer", *interner.str(whilestmt.label.unwrap()));
+
+        let block = whilestmt.block.to_expr().unwrap().expr.to_block().unwrap();
+        let breakstmt = block.stmts[0].to_break().unwrap();
+        assert_eq!("outer", *interner.str(breakstmt.label.unwrap()));
+
+        let (stmt, interner) = parse_stmt_with_interner("'items: for i in a { continue 'items; }");
+        let forstmt = stmt.to_for().unwrap();
+        assert_eq!("items", *interner.str(forstmt.label.unwrap()));
+
+        let block = forstmt.block.to_expr().unwrap().expr.to_block().unwrap();
+        let continuestmt = block.stmts[0].to_continue().unwrap();
+        assert_eq!("items", *interner.str(continuestmt.label.unwrap()));
+
+        let stmt = parse_stmt("while true { break; }");
+        assert!(stmt.to_while().unwrap().label.is_none());
+    }
+
+    #[test]
+    fn parse_loop() {
+        let stmt = parse_stmt("loop { break; }");
+        let loopstmt = stmt.to_loop().unwrap();
+        assert!(loopstmt.label.is_none());
+
+        let block = loopstmt.block.to_expr().unwrap().expr.to_block().unwrap();
+        assert!(block.stmts[0].is_break());
+
+        let (stmt, interner) = parse_stmt_with_interner("'outer: loop { loop { break 'outer; } }");
+        let loopstmt = stmt.to_loop().unwrap();
+        assert_eq!("outer", *interner.str(loopstmt.label.unwrap()));
+    }
+
+    #[test]
+    fn parse_do_while() {
+        let stmt = parse_stmt("do { 2; } while x < 10;");
+        let dowhile = stmt.to_do_while().unwrap();
+        assert!(dowhile.label.is_none());
+        assert!(dowhile.block.is_expr());
+        assert!(dowhile.cond.is_bin());
+
+        let (stmt, interner) =
+            parse_stmt_with_interner("'outer: do { break 'outer; } while (true);");
+        let dowhile = stmt.to_do_while().unwrap();
+        assert_eq!("outer", *interner.str(dowhile.label.unwrap()));
+        assert!(dowhile.cond.is_paren());
+    }
+
+    #[test]
+    fn parse_do_while_without_semicolon() {
+        err_stmt(
+            "do { } while true 1;",
+            ParseError::ExpectedToken(";".into(), "1".into()),
+            1,
+            19,
+        );
+    }
+
+    #[test]
+    fn parse_label_without_loop() {
+        err_stmt(
+            "'outer: 1;",
+            ParseError::ExpectedToken("loop".into(), "1".into()),
+            1,
+            9,
+        );
+    }
+
     #[test]
     fn parse_continue() {
         let stmt = parse_stmt("continue;");
@@ -2860,6 +4165,25 @@ mod tests {
         assert!(ret.expr.is_none());
     }
 
+    #[test]
+    fn parse_throw() {
+        let stmt = parse_stmt("throw foo;");
+        let throw = stmt.to_throw().unwrap();
+
+        assert!(throw.expr.is_ident());
+    }
+
+    #[test]
+    fn parse_try_catch() {
+        let (expr, interner) = parse_expr("try { 1 } catch e: Throwable { 2 }");
+        let try_catch = expr.to_try_catch().unwrap();
+
+        assert!(try_catch.try_block.is_block());
+        assert_eq!("e", *interner.str(try_catch.catch_name));
+        assert!(try_catch.catch_type.to_basic().is_some());
+        assert!(try_catch.catch_block.is_block());
+    }
+
     #[test]
     fn parse_else() {
         err_stmt("else", ParseError::MisplacedElse, 1, 1);
@@ -3050,6 +4374,15 @@ mod tests {
         let (expr, _) = parse_expr("a as String");
         let expr = expr.to_conv().unwrap();
         assert_eq!(true, expr.object.is_ident());
+        assert!(!expr.is);
+    }
+
+    #[test]
+    fn parse_is_expr() {
+        let (expr, _) = parse_expr("a is String");
+        let expr = expr.to_conv().unwrap();
+        assert_eq!(true, expr.object.is_ident());
+        assert!(expr.is);
     }
 
     #[test]
@@ -3059,6 +4392,29 @@ mod tests {
         assert!(fct.internal);
     }
 
+    #[test]
+    fn parse_test_expect_trap() {
+        let (prog, interner) = parse("@Test.expectTrap(INDEX_OUT_OF_BOUNDS) fn foo() {}");
+        let fct = prog.fct0();
+        assert!(fct.is_test);
+        assert_eq!(
+            "INDEX_OUT_OF_BOUNDS",
+            *interner.str(fct.expected_trap.unwrap())
+        );
+
+        let (prog, _) = parse("@Test fn foo() {}");
+        let fct = prog.fct0();
+        assert!(fct.is_test);
+        assert!(fct.expected_trap.is_none());
+
+        parse_err(
+            "@Test.expectPanic(DIV0) fn foo() {}",
+            ParseError::UnknownAnnotation("Test.expectPanic".into()),
+            1,
+            18,
+        );
+    }
+
     #[test]
     fn parse_function_without_body() {
         let (prog, _) = parse("fn foo();");
@@ -3066,6 +4422,36 @@ mod tests {
         assert!(fct.block.is_none());
     }
 
+    #[test]
+    fn parse_visibility_restrictions() {
+        let (prog, _) = parse("pub fn foo() {}");
+        assert!(prog.fct0().visibility.is_public());
+
+        let (prog, _) = parse("pub(mod) fn foo() {}");
+        assert!(matches!(
+            prog.fct0().visibility,
+            Visibility::Restricted(VisibilityRestriction::Module)
+        ));
+
+        let (prog, _) = parse("pub(super) struct Foo { pub(mod) a: Int32 }");
+        let struc = prog.struct0();
+        assert!(matches!(
+            struc.visibility,
+            Visibility::Restricted(VisibilityRestriction::Super)
+        ));
+        assert!(matches!(
+            struc.fields[0].visibility,
+            Visibility::Restricted(VisibilityRestriction::Module)
+        ));
+
+        parse_err(
+            "pub(foo) fn foo() {}",
+            ParseError::ExpectedToken("mod".into(), "identifier".into()),
+            1,
+            5,
+        );
+    }
+
     #[test]
     fn parse_struct_empty() {
         let (prog, interner) = parse("struct Foo {}");
@@ -3165,6 +4551,9 @@ mod tests {
 
         let (expr, _) = parse_expr("\"a\\${1}b\"");
         assert!(expr.is_lit_str());
+
+        let (expr, _) = parse_expr("\"a${1}b\"");
+        assert_eq!(Span::new(0, 8), expr.span());
     }
 
     #[test]
@@ -3265,6 +4654,25 @@ mod tests {
         assert_eq!(true, impl_.methods[0].is_static);
     }
 
+    #[test]
+    fn parse_trait_and_impl_with_const() {
+        let (prog, interner) = parse("trait Foo { const MAX: Int32; const MIN: Int32 = 0i32; }");
+        let trait_ = prog.trait0();
+
+        assert_eq!(0, trait_.methods.len());
+        assert_eq!(2, trait_.consts.len());
+        assert_eq!("MAX", *interner.str(trait_.consts[0].name));
+        assert!(trait_.consts[0].expr.is_none());
+        assert!(trait_.consts[1].expr.is_some());
+
+        let (prog, interner) = parse("impl Foo for A { const MAX: Int32 = 10i32; fn foo(); }");
+        let impl_ = prog.impl0();
+
+        assert_eq!(1, impl_.methods.len());
+        assert_eq!(1, impl_.consts.len());
+        assert_eq!("MAX", *interner.str(impl_.consts[0].name));
+    }
+
     #[test]
     fn parse_global_let() {
         let (prog, interner) = parse("let b: int = 0;");
@@ -3342,6 +4750,32 @@ mod tests {
         assert_eq!(1, type_param.bounds.len());
     }
 
+    #[test]
+    fn parse_where_clause() {
+        let (prog, interner) = parse("fn f[T](x: T): Int32 where T: Foo { 0i32 }");
+        let fct = prog.fct0();
+
+        let type_param = &fct.type_params.as_ref().unwrap()[0];
+        assert_eq!(1, type_param.bounds.len());
+        assert_eq!("Foo", type_param.bounds[0].to_string(&interner));
+        assert!(fct.block.is_some());
+
+        let (prog, interner) = parse("fn f[T: Foo, U](x: T, y: U) where T: Bar + Baz, U: Foo;");
+        let fct = prog.fct0();
+
+        let type_params = fct.type_params.as_ref().unwrap();
+        assert_eq!(3, type_params[0].bounds.len());
+        assert_eq!("Baz", type_params[0].bounds[2].to_string(&interner));
+        assert_eq!(1, type_params[1].bounds.len());
+
+        parse_err(
+            "fn f[T](x: T) where U: Foo {}",
+            ParseError::UnknownTypeParamInWhere("U".into()),
+            1,
+            21,
+        );
+    }
+
     #[test]
     fn parse_generic_with_multiple_bounds() {
         let (prog, _) = parse("class A[T: Foo + Bar]");
@@ -3530,6 +4964,198 @@ mod tests {
         parse_expr("match x { A(x, b) => 1, B | C => 2 }");
     }
 
+    #[test]
+    fn parse_doc_comment() {
+        let (prog, _) = parse("/// Adds.\nfn f() {}");
+        assert_eq!(prog.fct0().doc.as_deref(), Some("Adds."));
+
+        let (prog, _) = parse("/// a\n/// b\n@pub fn f() {}");
+        assert_eq!(prog.fct0().doc.as_deref(), Some("a\nb"));
+
+        let (prog, _) = parse("/// a\n\nfn f() {}");
+        assert!(prog.fct0().doc.is_none());
+
+        let (prog, _) = parse("// a\nfn f() {}");
+        assert!(prog.fct0().doc.is_none());
+
+        let (prog, _) = parse("/// a\nclass Foo");
+        assert_eq!(prog.cls0().doc.as_deref(), Some("a"));
+
+        let (prog, _) = parse("/// a\nstruct Foo {}");
+        assert_eq!(prog.struct0().doc.as_deref(), Some("a"));
+
+        let (prog, _) = parse("/// a\nenum Foo { A }");
+        assert_eq!(prog.enum0().doc.as_deref(), Some("a"));
+
+        let (prog, _) = parse("/// a\ntrait Foo { /// b\nfn f(); }");
+        let trait_ = prog.trait0();
+        assert_eq!(trait_.doc.as_deref(), Some("a"));
+        assert_eq!(trait_.methods[0].doc.as_deref(), Some("b"));
+
+        let (prog, _) = parse("impl Foo { /// a\nfn f() {} fn g() {} }");
+        let impl_ = prog.impl0();
+        assert_eq!(impl_.methods[0].doc.as_deref(), Some("a"));
+        assert!(impl_.methods[1].doc.is_none());
+    }
+
+    #[test]
+    fn parse_allow_comment() {
+        let (prog, _) = parse("// dora:allow(a)\nfn f() {\n    // dora:allow(b, c)\n    let x = 1;\n    let y = 2;\n}");
+        assert_eq!(prog.suppressions.len(), 2);
+
+        let stmt = &prog.suppressions[0];
+        assert_eq!(
+            stmt.lints,
+            vec![
+                ("b".to_string(), Span::new(44, 1)),
+                ("c".to_string(), Span::new(47, 1))
+            ]
+        );
+        assert_eq!(stmt.span, Span::new(54, 10));
+
+        let fct = &prog.suppressions[1];
+        assert_eq!(fct.lints, vec![("a".to_string(), Span::new(14, 1))]);
+        assert_eq!(fct.span, prog.fct0().span);
+
+        let (prog, _) = parse("/// a\n// dora:allow(b)\nfn f() {}");
+        assert_eq!(prog.fct0().doc.as_deref(), Some("a"));
+        assert_eq!(prog.suppressions.len(), 1);
+
+        let (prog, _) = parse("impl Foo {\n    // dora:allow(a)\n    fn f() {}\n    fn g() {}\n}");
+        assert_eq!(prog.suppressions.len(), 1);
+        assert_eq!(prog.suppressions[0].span, prog.impl0().methods[0].span);
+    }
+
+    #[test]
+    fn parse_match_cases() {
+        let (expr, interner) = parse_expr("match x { Option::Some(v) => v, Option::None => 0 }");
+        let match_ = expr.to_match().unwrap();
+        assert_eq!(match_.cases.len(), 2);
+
+        let case = &match_.cases[0];
+        assert!(case.guard.is_none());
+        assert!(case.value.is_ident());
+        match case.patterns[0].data {
+            MatchPatternData::Ident(ref ident) => {
+                assert_eq!(ident.path.names.len(), 2);
+                let params = ident.params.as_ref().unwrap();
+                assert_eq!(params.len(), 1);
+                assert_eq!("v", *interner.str(params[0].name.unwrap()));
+            }
+            _ => unreachable!(),
+        }
+
+        let case = &match_.cases[1];
+        match case.patterns[0].data {
+            MatchPatternData::Ident(ref ident) => assert!(ident.params.is_none()),
+            _ => unreachable!(),
+        }
+        assert!(case.value.is_lit_int());
+    }
+
+    #[test]
+    fn parse_match_nested_patterns() {
+        let (expr, interner) =
+            parse_expr("match x { Some(Foo::A(a, _), b) => 1, Some(B(c)) => 2, Some(y) => 3 }");
+        let match_ = expr.to_match().unwrap();
+
+        let params = match match_.cases[0].patterns[0].data {
+            MatchPatternData::Ident(ref ident) => ident.params.as_ref().unwrap(),
+            _ => unreachable!(),
+        };
+        assert_eq!(params.len(), 2);
+        assert!(params[0].name.is_none());
+        match params[0].pattern.as_ref().unwrap().data {
+            MatchPatternData::Ident(ref ident) => {
+                assert_eq!(ident.path.names.len(), 2);
+                let nested = ident.params.as_ref().unwrap();
+                assert_eq!("a", *interner.str(nested[0].name.unwrap()));
+                assert!(nested[1].name.is_none());
+            }
+            _ => unreachable!(),
+        }
+        assert_eq!("b", *interner.str(params[1].name.unwrap()));
+        assert!(params[1].pattern.is_none());
+
+        let params = match match_.cases[1].patterns[0].data {
+            MatchPatternData::Ident(ref ident) => ident.params.as_ref().unwrap(),
+            _ => unreachable!(),
+        };
+        assert!(params[0].pattern.is_some());
+
+        let params = match match_.cases[2].patterns[0].data {
+            MatchPatternData::Ident(ref ident) => ident.params.as_ref().unwrap(),
+            _ => unreachable!(),
+        };
+        assert_eq!("y", *interner.str(params[0].name.unwrap()));
+        assert!(params[0].pattern.is_none());
+    }
+
+    #[test]
+    fn parse_match_guard() {
+        let (expr, _) = parse_expr("match x { n if n > 0 => 1, _ => 0 }");
+        let match_ = expr.to_match().unwrap();
+        assert_eq!(match_.cases.len(), 2);
+
+        let guard = match_.cases[0].guard.as_ref().unwrap();
+        assert_eq!(guard.to_bin().unwrap().op, BinOp::Cmp(CmpOp::Gt));
+        assert!(match_.cases[1].guard.is_none());
+    }
+
+    #[test]
+    fn parse_match_lit_and_tuple_patterns() {
+        let (expr, _) =
+            parse_expr("match x { 1 => 1, -2 => 2, 'a' => 3, \"s\" => 4, true => 5, _ => 6 }");
+        let match_ = expr.to_match().unwrap();
+        assert_eq!(match_.cases.len(), 6);
+
+        for case in &match_.cases[0..5] {
+            assert!(matches!(case.patterns[0].data, MatchPatternData::Lit(_)));
+        }
+
+        let (expr, _) = parse_expr("match x { (A, _) => 1, (_, B(y)) => 2 }");
+        let match_ = expr.to_match().unwrap();
+
+        match match_.cases[1].patterns[0].data {
+            MatchPatternData::Tuple(ref patterns) => {
+                assert_eq!(patterns.len(), 2);
+                assert!(matches!(patterns[0].data, MatchPatternData::Underscore));
+                assert!(matches!(patterns[1].data, MatchPatternData::Ident(_)));
+            }
+            _ => unreachable!(),
+        }
+
+        parse_err(
+            "fn f() { match x { -a => 1 } }",
+            ParseError::ExpectedFactor("identifier".into()),
+            1,
+            21,
+        );
+    }
+
+    #[test]
+    fn parse_match_range_pattern() {
+        let (expr, _) = parse_expr("match x { 1..=5 => 1, -3..=-1 => 2, 'a'..='z' => 3, _ => 4 }");
+        let match_ = expr.to_match().unwrap();
+
+        for case in &match_.cases[0..3] {
+            match case.patterns[0].data {
+                MatchPatternData::Range(ref range) => {
+                    assert!(!range.start.is_ident());
+                    assert!(!range.end.is_ident());
+                }
+                _ => unreachable!(),
+            }
+        }
+
+        parse_err(
+            "fn f() { match x { 1..=a => 1 } }",
+            ParseError::ExpectedFactor("identifier".into()),
+            1,
+            24,
+        );
+    }
+
     #[test]
     fn parse_use_declaration() {
         parse_err(
@@ -3546,4 +5172,31 @@ mod tests {
             5,
         );
     }
+
+    #[test]
+    fn parse_recovers_from_multiple_errors() {
+        let code = "fn f() { let x = ; }\nfn g(): Int64 { 1 }\nfn h(a: ) {}\nclass Foo";
+        let mut interner = Interner::new();
+
+        let (file, _id_generator, errors) = Parser::from_string(code, &mut interner).parse();
+        assert_eq!(errors.len(), 2);
+
+        assert_eq!(ParseError::ExpectedFactor(";".into()), errors[0].error);
+        assert_eq!(ParseError::ExpectedType(")".into()), errors[1].error);
+
+        let line_starts = compute_line_starts(code);
+        assert_eq!(
+            (1, 18),
+            compute_line_column(&line_starts, errors[0].span.start())
+        );
+        assert_eq!(
+            (3, 9),
+            compute_line_column(&line_starts, errors[1].span.start())
+        );
+
+        // elements parsed without errors are kept
+        assert_eq!(2, file.elements.len());
+        assert!(file.elements[0].to_function().is_some());
+        assert!(file.elements[1].to_class().is_some());
+    }
 }
@@ -84,6 +84,15 @@ fn encode_bytecode_type(vm: &VM, ty: &BytecodeType, buffer: &mut ByteBuffer) {
         BytecodeType::UInt8 => {
             buffer.emit_u8(BytecodeTypeKind::UInt8 as u8);
         }
+        BytecodeType::UInt16 => {
+            buffer.emit_u8(BytecodeTypeKind::UInt16 as u8);
+        }
+        BytecodeType::UInt32 => {
+            buffer.emit_u8(BytecodeTypeKind::UInt32 as u8);
+        }
+        BytecodeType::UInt64 => {
+            buffer.emit_u8(BytecodeTypeKind::UInt64 as u8);
+        }
         BytecodeType::Int32 => {
             buffer.emit_u8(BytecodeTypeKind::Int32 as u8);
         }
@@ -245,6 +254,15 @@ fn encode_constpool_entry(vm: &VM, const_entry: &ConstPoolEntry, buffer: &mut By
             encode_bytecode_type_array(vm, params, buffer);
             encode_bytecode_type(vm, return_type, buffer);
         }
+        &ConstPoolEntry::JumpTable(low, ref targets) => {
+            buffer.emit_u8(ConstPoolOpcode::JumpTable.into());
+            buffer.emit_u64(low as u64);
+            buffer.emit_u32(targets.len() as u32);
+
+            for &target in targets {
+                buffer.emit_u32(target);
+            }
+        }
     }
 }
 
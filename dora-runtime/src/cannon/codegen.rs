@@ -1,14 +1,14 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use crate::cannon::liveness::BytecodeLiveness;
 use crate::compiler::asm::BaselineAssembler;
 use crate::compiler::codegen::{ensure_native_stub, AllocationSize, AnyReg, CompilationData};
 use crate::compiler::dora_exit_stubs::{NativeFct, NativeFctKind};
 use crate::cpu::{
-    has_lzcnt, has_popcnt, has_tzcnt, Reg, FREG_PARAMS, FREG_RESULT, FREG_TMP1, REG_PARAMS,
-    REG_RESULT, REG_SP, REG_TMP1, REG_TMP2, STACK_FRAME_ALIGNMENT,
+    Reg, FREG_PARAMS, FREG_RESULT, FREG_TMP1, REG_PARAMS, REG_RESULT, REG_SP, REG_TMP1, REG_TMP2,
+    STACK_FRAME_ALIGNMENT,
 };
-use crate::gc::Address;
+use crate::gc::{finalizer, Address};
 use crate::masm::{CodeDescriptor, CondCode, Label, Mem};
 use crate::mem::{self, align_i32};
 use crate::mode::MachineMode;
@@ -16,21 +16,25 @@ use crate::object::{offset_of_array_data, Header, Str};
 use crate::size::InstanceSize;
 use crate::stdlib;
 use crate::vm::{
-    create_class_instance, create_enum_instance, create_struct_instance, display_fct, display_ty,
-    ensure_class_instance_for_enum_variant, ensure_class_instance_for_lambda,
-    ensure_class_instance_for_trait_object, find_trait_impl, get_concrete_tuple_bty,
-    get_concrete_tuple_bty_array, specialize_bty, specialize_bty_array, EnumLayout, GcPoint,
-    LazyCompilationSite, Trap, VM,
+    add_ref_fields, create_class_instance, create_enum_instance, create_struct_instance,
+    display_fct, display_ty, ensure_class_instance_for_enum_variant,
+    ensure_class_instance_for_lambda, ensure_class_instance_for_trait_object, find_trait_impl,
+    get_concrete_tuple_bty, get_concrete_tuple_bty_array, specialize_bty, specialize_bty_array,
+    ClassInstanceId, EnumLayout, ExceptionHandler, GcPoint, LazyCompilationSite, Trap, VM,
 };
 use crate::vtable::VTable;
 use dora_bytecode::{
-    read, BytecodeFunction, BytecodeOffset, BytecodeType, BytecodeTypeArray, BytecodeVisitor,
-    ConstPoolEntry, ConstPoolIdx, FunctionId, FunctionKind, GlobalId, Intrinsic, Location,
-    Register, TraitId,
+    read, BoundsCheckElimination, BytecodeFunction, BytecodeInstruction, BytecodeOffset,
+    BytecodeReader, BytecodeType, BytecodeTypeArray, BytecodeVisitor, ClassId, ConstPoolEntry,
+    ConstPoolIdx, EscapeAnalysis, FunctionId, FunctionKind, GlobalId, Intrinsic, Location,
+    Register, SourceFileId, TraitId,
 };
 
 use super::CompilationFlags;
 
+// Non-escaping objects up to this size are allocated in the stack frame.
+const MAX_STACK_OBJECT_SIZE: usize = 256;
+
 macro_rules! comment {
     (
         $cannon:expr,
@@ -58,19 +62,30 @@ pub struct CannonCodeGen<'a> {
     return_type: BytecodeType,
     emit_debug: bool,
     emit_code_comments: bool,
+    line_starts: HashMap<BytecodeOffset, u32>,
+    coverage_file: Option<SourceFileId>,
+    after_conditional_jump: bool,
+    coverage_pending: bool,
 
     type_params: BytecodeTypeArray,
 
     offset_to_address: HashMap<BytecodeOffset, usize>,
+    handler_to_address: HashMap<BytecodeOffset, usize>,
     offset_to_label: HashMap<BytecodeOffset, Label>,
     liveness: BytecodeLiveness,
 
+    fct_id: Option<FunctionId>,
+    bounds_checks: Option<BoundsCheckElimination>,
+    tail_calls: HashSet<BytecodeOffset>,
+    tail_call_entry: Option<Label>,
+
     current_offset: BytecodeOffset,
     argument_stack: Vec<Register>,
 
     references: Vec<i32>,
 
     offsets: Vec<Option<i32>>,
+    stack_objects: HashMap<Register, (i32, ClassInstanceId)>,
     framesize: i32,
     register_start_offset: i32,
 
@@ -95,6 +110,10 @@ impl<'a> CannonCodeGen<'a> {
     ) -> CannonCodeGen<'a> {
         CannonCodeGen {
             vm,
+            fct_id: compilation_data.fct_id,
+            bounds_checks: None,
+            tail_calls: HashSet::new(),
+            tail_call_entry: None,
             params: compilation_data.params,
             has_variadic_parameter: compilation_data.has_variadic_parameter,
             return_type: compilation_data.return_type,
@@ -103,13 +122,27 @@ impl<'a> CannonCodeGen<'a> {
             asm: BaselineAssembler::new(vm),
             bytecode: compilation_data.bytecode_fct,
             emit_code_comments: compilation_data.emit_code_comments,
+            line_starts: if compilation_data.emit_code_lines {
+                compilation_data
+                    .bytecode_fct
+                    .line_starts()
+                    .into_iter()
+                    .collect()
+            } else {
+                HashMap::new()
+            },
+            coverage_file: compilation_data.coverage_file,
+            after_conditional_jump: false,
+            coverage_pending: false,
             type_params: compilation_data.type_params,
             offset_to_address: HashMap::new(),
+            handler_to_address: HashMap::new(),
             offset_to_label: HashMap::new(),
             current_offset: BytecodeOffset(0),
             argument_stack: Vec::new(),
             references: Vec::new(),
             offsets: Vec::new(),
+            stack_objects: HashMap::new(),
             liveness,
             framesize: 0,
             register_start_offset: 0,
@@ -125,9 +158,21 @@ impl<'a> CannonCodeGen<'a> {
 
         self.compute_register_offsets();
         self.compute_reference_objects();
+        self.tail_calls = self.find_self_tail_calls();
+
+        if !self.vm.args.flag_disable_bce {
+            self.bounds_checks = Some(BoundsCheckElimination::compute(self.bytecode));
+        }
 
         self.emit_prolog();
         self.emit_stack_guard();
+
+        if !self.tail_calls.is_empty() {
+            // Self tail calls reuse the frame and restart here with the new
+            // arguments in the parameter registers.
+            self.tail_call_entry = Some(self.asm.create_and_bind_label());
+        }
+
         self.emit_clear_registers();
         self.store_params_in_registers();
         self.emit_safepoint();
@@ -138,10 +183,101 @@ impl<'a> CannonCodeGen<'a> {
         self.asm.debug();
 
         self.emit_slow_paths();
+        self.emit_exception_handlers();
 
         self.asm.code()
     }
 
+    // Finds calls to this very function (with the same type params) whose
+    // result is returned right away. These calls are turned into jumps.
+    fn find_self_tail_calls(&self) -> HashSet<BytecodeOffset> {
+        let mut tail_calls = HashSet::new();
+
+        if self.fct_id.is_none() || !self.params_passed_in_registers() {
+            return tail_calls;
+        }
+
+        let mut reader = BytecodeReader::new(self.bytecode.code());
+        let mut last_call = None;
+
+        loop {
+            let offset = BytecodeOffset(reader.offset() as u32);
+
+            let inst = match reader.next() {
+                Some(inst) => inst,
+                None => break,
+            };
+
+            last_call = match inst {
+                BytecodeInstruction::InvokeStatic { dest, fct }
+                | BytecodeInstruction::InvokeDirect { dest, fct } => Some((offset, dest, fct)),
+
+                BytecodeInstruction::Ret { opnd } => {
+                    if let Some((call_offset, dest, fct_idx)) = last_call {
+                        if dest == opnd
+                            && self.is_self_call(fct_idx)
+                            && !self.in_try_region(call_offset)
+                        {
+                            tail_calls.insert(call_offset);
+                        }
+                    }
+
+                    None
+                }
+
+                _ => None,
+            };
+        }
+
+        tail_calls
+    }
+
+    fn params_passed_in_registers(&self) -> bool {
+        if self.has_result_address() || self.has_variadic_parameter {
+            return false;
+        }
+
+        let mut regs = 0;
+        let mut fregs = 0;
+
+        for param_ty in self.params.iter() {
+            match self.specialize_bty(param_ty) {
+                BytecodeType::Unit => {}
+                BytecodeType::Tuple(_) | BytecodeType::Struct(_, _) => return false,
+                BytecodeType::Float32 | BytecodeType::Float64 => fregs += 1,
+                _ => regs += 1,
+            }
+        }
+
+        regs <= REG_PARAMS.len() && fregs <= FREG_PARAMS.len()
+    }
+
+    fn is_self_call(&self, fct_idx: ConstPoolIdx) -> bool {
+        match self.bytecode.const_pool(fct_idx) {
+            ConstPoolEntry::Fct(fct_id, type_params) => {
+                Some(*fct_id) == self.fct_id
+                    && self.specialize_bty_array(type_params) == self.type_params
+            }
+            _ => false,
+        }
+    }
+
+    fn in_try_region(&self, offset: BytecodeOffset) -> bool {
+        self.bytecode
+            .exception_handlers()
+            .iter()
+            .any(|handler| handler.start <= offset && offset < handler.end)
+    }
+
+    fn emit_self_tail_call(&mut self, dest: Register, arguments: Vec<Register>) {
+        let return_type = self.specialize_bty(self.return_type.clone());
+        let argsize = self.emit_invoke_arguments(dest, return_type, arguments);
+        assert_eq!(argsize, 0);
+
+        let entry = self.tail_call_entry.expect("missing tail call entry");
+        self.asm.jump(entry);
+    }
+
     fn emit_safepoint(&mut self) {
         let gcpoint = self.create_gcpoint();
         self.asm.safepoint(self.location, gcpoint);
@@ -156,6 +292,38 @@ impl<'a> CannonCodeGen<'a> {
         }
     }
 
+    fn emit_exception_handlers(&mut self) {
+        for handler in self.bytecode.exception_handlers() {
+            let (cls_id, type_params) = match self.specialize_bty(handler.exception_type.clone()) {
+                BytecodeType::Class(cls_id, type_params) => (cls_id, type_params),
+                _ => unreachable!(),
+            };
+
+            let class_instance_id = create_class_instance(self.vm, cls_id, &type_params);
+
+            let try_start = self.address_for_offset(handler.start);
+            let try_end = self.address_for_offset(handler.end);
+            let handler_address = self.handler_to_address[&handler.handler];
+
+            self.asm.add_exception_handler(ExceptionHandler {
+                try_start: try_start as u32,
+                try_end: try_end as u32,
+                handler: handler_address as u32,
+                class_instance_id,
+                framesize: self.framesize,
+            });
+        }
+    }
+
+    // the end of a try region can also be the end of the bytecode
+    fn address_for_offset(&self, offset: BytecodeOffset) -> usize {
+        if offset.to_usize() == self.bytecode.code().len() {
+            self.asm.pos()
+        } else {
+            self.offset_to_address[&offset]
+        }
+    }
+
     fn compute_register_offsets(&mut self) {
         self.register_start_offset = if self.has_result_address() {
             mem::ptr_width()
@@ -165,7 +333,50 @@ impl<'a> CannonCodeGen<'a> {
 
         let (offsets, stacksize) = self.determine_offsets(self.register_start_offset);
         self.offsets = offsets;
-        self.framesize = stacksize;
+        self.framesize = self.determine_stack_objects(stacksize);
+    }
+
+    // Reserves space in the frame for objects that don't escape this
+    // function. The register of such an object holds the address of that
+    // space instead of a heap object, the fields become roots of the frame.
+    fn determine_stack_objects(&mut self, start: i32) -> i32 {
+        let escape_analysis = EscapeAnalysis::compute(self.bytecode);
+        let mut candidates: Vec<(Register, ConstPoolIdx)> = escape_analysis
+            .non_escaping()
+            .iter()
+            .map(|(&reg, &cls)| (reg, cls))
+            .collect();
+        candidates.sort_by_key(|(reg, _)| reg.0);
+
+        let mut stacksize = start;
+
+        for (reg, idx) in candidates {
+            let (cls_id, type_params) = match self.bytecode.const_pool(idx) {
+                ConstPoolEntry::Class(cls_id, type_params) => (*cls_id, type_params.clone()),
+                _ => unreachable!(),
+            };
+
+            let type_params = self.specialize_bty_array(&type_params);
+            let class_instance_id = create_class_instance(self.vm, cls_id, &type_params);
+            let class_instance = self.vm.class_instances.idx(class_instance_id);
+
+            let size = match class_instance.size {
+                InstanceSize::Fixed(size) if size as usize <= MAX_STACK_OBJECT_SIZE => size,
+                _ => continue,
+            };
+
+            if class_instance.weak_field.is_some()
+                || finalizer::is_finalizable(self.vm, cls_id, &type_params)
+            {
+                continue;
+            }
+
+            stacksize = align_i32(stacksize + size, mem::ptr_width());
+            self.stack_objects
+                .insert(reg, (-stacksize, class_instance_id));
+        }
+
+        align_i32(stacksize, STACK_FRAME_ALIGNMENT as i32)
     }
 
     fn determine_offsets(&self, start: i32) -> (Vec<Option<i32>>, i32) {
@@ -209,6 +420,15 @@ impl<'a> CannonCodeGen<'a> {
         for (idx, ty) in self.bytecode.registers().iter().enumerate() {
             let ty = register_bty(self.specialize_bty(ty.clone()));
             match ty {
+                BytecodeType::Ptr if self.stack_objects.contains_key(&Register(idx)) => {
+                    let (offset, class_instance_id) = self.stack_objects[&Register(idx)];
+                    let class_instance = self.vm.class_instances.idx(class_instance_id);
+
+                    for &ref_offset in &class_instance.ref_fields {
+                        self.references.push(offset + ref_offset);
+                    }
+                }
+
                 BytecodeType::Ptr | BytecodeType::Trait(_, _) => {
                     let offset = self.register_offset(Register(idx));
                     self.references.push(offset);
@@ -256,6 +476,9 @@ impl<'a> CannonCodeGen<'a> {
                 }
 
                 BytecodeType::UInt8
+                | BytecodeType::UInt16
+                | BytecodeType::UInt32
+                | BytecodeType::UInt64
                 | BytecodeType::Int32
                 | BytecodeType::Bool
                 | BytecodeType::Char
@@ -356,6 +579,9 @@ impl<'a> CannonCodeGen<'a> {
 
                 BytecodeType::Ptr
                 | BytecodeType::UInt8
+                | BytecodeType::UInt16
+                | BytecodeType::UInt32
+                | BytecodeType::UInt64
                 | BytecodeType::Bool
                 | BytecodeType::Char
                 | BytecodeType::Int32
@@ -640,6 +866,35 @@ impl<'a> CannonCodeGen<'a> {
         }
     }
 
+    fn emit_saturating(&mut self, dest: Register, lhs: Register, rhs: Register, add: bool) {
+        assert_eq!(
+            self.bytecode.register_type(lhs),
+            self.bytecode.register_type(rhs)
+        );
+        assert_eq!(
+            self.bytecode.register_type(lhs),
+            self.bytecode.register_type(dest)
+        );
+
+        let bytecode_type = self.bytecode.register_type(dest);
+        assert!(bytecode_type == BytecodeType::Int32 || bytecode_type == BytecodeType::Int64);
+
+        self.emit_load_register(lhs, REG_RESULT.into());
+        self.emit_load_register(rhs, REG_TMP1.into());
+
+        let mode = mode(self.vm, bytecode_type);
+
+        if add {
+            self.asm
+                .int_add_saturating(mode, REG_RESULT, REG_RESULT, REG_TMP1);
+        } else {
+            self.asm
+                .int_sub_saturating(mode, REG_RESULT, REG_RESULT, REG_TMP1);
+        }
+
+        self.emit_store_register(REG_RESULT.into(), dest);
+    }
+
     fn emit_neg(&mut self, dest: Register, src: Register) {
         assert_eq!(
             self.bytecode.register_type(src),
@@ -780,22 +1035,36 @@ impl<'a> CannonCodeGen<'a> {
         );
 
         let bytecode_type = self.bytecode.register_type(dest);
-        assert!(bytecode_type == BytecodeType::Int32 || bytecode_type == BytecodeType::Int64);
 
-        self.emit_load_register(lhs, REG_RESULT.into());
-        self.emit_load_register(rhs, REG_TMP1.into());
+        if bytecode_type.is_any_float() {
+            self.emit_load_register(lhs, FREG_RESULT.into());
+            self.emit_load_register(rhs, FREG_TMP1.into());
 
-        let position = self.bytecode.offset_location(self.current_offset.to_u32());
+            self.asm.float_rem(
+                mode(self.vm, bytecode_type),
+                FREG_RESULT,
+                FREG_RESULT,
+                FREG_TMP1,
+            );
 
-        self.asm.int_mod(
-            mode(self.vm, bytecode_type),
-            REG_RESULT,
-            REG_RESULT,
-            REG_TMP1,
-            position,
-        );
+            self.emit_store_register(FREG_RESULT.into(), dest);
+        } else {
+            assert!(bytecode_type == BytecodeType::Int32 || bytecode_type == BytecodeType::Int64);
+            self.emit_load_register(lhs, REG_RESULT.into());
+            self.emit_load_register(rhs, REG_TMP1.into());
 
-        self.emit_store_register(REG_RESULT.into(), dest);
+            let position = self.bytecode.offset_location(self.current_offset.to_u32());
+
+            self.asm.int_mod(
+                mode(self.vm, bytecode_type),
+                REG_RESULT,
+                REG_RESULT,
+                REG_TMP1,
+                position,
+            );
+
+            self.emit_store_register(REG_RESULT.into(), dest);
+        }
     }
 
     fn emit_and(&mut self, dest: Register, lhs: Register, rhs: Register) {
@@ -1377,10 +1646,56 @@ impl<'a> CannonCodeGen<'a> {
         let bytecode_type = self.specialize_register_type(src);
         assert_eq!(bytecode_type, register_ty(field.ty.clone()));
 
-        self.emit_store_field_raw(obj_reg, field.offset, src);
+        let offset = field.offset;
+
+        // fields of objects in the frame are roots and need no barriers
+        let on_heap = !self.stack_objects.contains_key(&obj);
+
+        if on_heap
+            && self.emit_pre_write_barrier(bytecode_type, offset, |codegen| {
+                codegen.emit_load_register(obj, REG_TMP1.into());
+            })
+        {
+            self.emit_load_register(obj, obj_reg.into());
+        }
+
+        self.emit_store_field_raw(obj_reg, offset, src, on_heap);
     }
 
-    fn emit_store_field_raw(&mut self, obj_reg: Reg, offset: i32, value: Register) {
+    // Emits the SATB pre-write barrier for all references in a value of
+    // the given type stored at offset relative to the address in REG_TMP1.
+    // load_base loads that address and is invoked for every reference since
+    // the barrier clobbers registers. Returns whether code was emitted.
+    fn emit_pre_write_barrier<F>(&mut self, ty: BytecodeType, offset: i32, load_base: F) -> bool
+    where
+        F: Fn(&mut CannonCodeGen<'a>),
+    {
+        let marking_flag = match self.vm.gc.concurrent_marking_flag() {
+            Some(marking_flag) => marking_flag,
+            None => return false,
+        };
+
+        let mut ref_fields = Vec::new();
+        add_ref_fields(self.vm, &mut ref_fields, offset, ty);
+
+        if ref_fields.is_empty() {
+            return false;
+        }
+
+        let lbl_inactive = self.asm.create_label();
+        self.asm.test_concurrent_marking(marking_flag, lbl_inactive);
+
+        for ref_offset in ref_fields {
+            load_base(self);
+            self.asm
+                .emit_pre_write_barrier(Mem::Base(REG_TMP1, ref_offset));
+        }
+
+        self.asm.bind_label(lbl_inactive);
+        true
+    }
+
+    fn emit_store_field_raw(&mut self, obj_reg: Reg, offset: i32, value: Register, on_heap: bool) {
         let ty = self.specialize_register_type(value);
         let needs_write_barrier;
 
@@ -1439,6 +1754,9 @@ impl<'a> CannonCodeGen<'a> {
                 unreachable!()
             }
             BytecodeType::UInt8
+            | BytecodeType::UInt16
+            | BytecodeType::UInt32
+            | BytecodeType::UInt64
             | BytecodeType::Bool
             | BytecodeType::Char
             | BytecodeType::Int32
@@ -1467,7 +1785,7 @@ impl<'a> CannonCodeGen<'a> {
             }
         }
 
-        if self.vm.gc.needs_write_barrier() && needs_write_barrier {
+        if self.vm.gc.needs_write_barrier() && needs_write_barrier && on_heap {
             let card_table_offset = self.vm.gc.card_table_offset();
             self.asm.emit_barrier(obj_reg, card_table_offset);
         }
@@ -1703,6 +2021,9 @@ impl<'a> CannonCodeGen<'a> {
 
             BytecodeType::Bool
             | BytecodeType::UInt8
+            | BytecodeType::UInt16
+            | BytecodeType::UInt32
+            | BytecodeType::UInt64
             | BytecodeType::Char
             | BytecodeType::Int32
             | BytecodeType::Int64
@@ -1741,6 +2062,46 @@ impl<'a> CannonCodeGen<'a> {
         self.asm.jump(lbl);
     }
 
+    fn emit_switch(&mut self, opnd: Register, low: i64, targets: Vec<BytecodeOffset>) {
+        let mode = match self.bytecode.register_type(opnd) {
+            BytecodeType::UInt8 | BytecodeType::Char | BytecodeType::Int32 => MachineMode::Int32,
+            BytecodeType::Int64 => MachineMode::Int64,
+            ty => panic!("unexpected type {:?} for switch", ty),
+        };
+
+        self.emit_load_register(opnd, REG_RESULT.into());
+
+        if low != 0 {
+            let adjustment = if mode == MachineMode::Int32 {
+                (low as i32).wrapping_neg() as i64
+            } else {
+                low.wrapping_neg()
+            };
+
+            self.asm
+                .int_add_imm(mode, REG_RESULT, REG_RESULT, adjustment);
+        }
+
+        // Values outside of the table fall through to the next instruction.
+        let lbl_default = self.asm.create_label();
+        self.asm.cmp_reg_imm(mode, REG_RESULT, targets.len() as i32);
+        self.asm.jump_if(CondCode::UnsignedGreaterEq, lbl_default);
+
+        let labels = targets
+            .into_iter()
+            .map(|target| self.ensure_forward_label(target))
+            .collect();
+        self.asm.jump_table(REG_RESULT, labels);
+        self.asm.bind_label(lbl_default);
+    }
+
+    fn emit_coverage_counter(&mut self) {
+        let file = self.coverage_file.expect("coverage disabled");
+        let location = self.bytecode.offset_location(self.current_offset.to_u32());
+        let counter = self.vm.coverage.add_counter(file, location.line());
+        self.asm.increment_counter(counter);
+    }
+
     fn emit_jump_loop(&mut self, target: BytecodeOffset) {
         assert!(target < self.current_offset);
 
@@ -1842,6 +2203,9 @@ impl<'a> CannonCodeGen<'a> {
             }
 
             BytecodeType::UInt8
+            | BytecodeType::UInt16
+            | BytecodeType::UInt32
+            | BytecodeType::UInt64
             | BytecodeType::Int32
             | BytecodeType::Bool
             | BytecodeType::Char
@@ -1887,10 +2251,15 @@ impl<'a> CannonCodeGen<'a> {
             ),
         };
 
-        let gcpoint = self.create_gcpoint();
-        let position = self.bytecode.offset_location(self.current_offset.to_u32());
-        self.asm
-            .allocate(REG_RESULT.into(), alloc_size, position, false, gcpoint);
+        if let Some(&(offset, _)) = self.stack_objects.get(&dest) {
+            // object doesn't escape, use the space reserved in the frame
+            self.asm.lea(REG_RESULT, Mem::Local(offset));
+        } else {
+            let gcpoint = self.create_gcpoint();
+            let position = self.bytecode.offset_location(self.current_offset.to_u32());
+            self.asm
+                .allocate(REG_RESULT.into(), alloc_size, position, false, gcpoint);
+        }
 
         // store gc object in temporary storage
         self.emit_store_register(REG_RESULT.into(), dest);
@@ -1920,6 +2289,8 @@ impl<'a> CannonCodeGen<'a> {
             }
             _ => unreachable!(),
         }
+
+        self.emit_register_finalizable(dest, cls_id, &type_params);
     }
 
     fn emit_new_object_initialized(&mut self, dest: Register, idx: ConstPoolIdx) {
@@ -1948,10 +2319,15 @@ impl<'a> CannonCodeGen<'a> {
             ),
         };
 
-        let gcpoint = self.create_gcpoint();
-        let position = self.bytecode.offset_location(self.current_offset.to_u32());
-        self.asm
-            .allocate(REG_RESULT.into(), alloc_size, position, false, gcpoint);
+        if let Some(&(offset, _)) = self.stack_objects.get(&dest) {
+            // object doesn't escape, use the space reserved in the frame
+            self.asm.lea(REG_RESULT, Mem::Local(offset));
+        } else {
+            let gcpoint = self.create_gcpoint();
+            let position = self.bytecode.offset_location(self.current_offset.to_u32());
+            self.asm
+                .allocate(REG_RESULT.into(), alloc_size, position, false, gcpoint);
+        }
 
         // store gc object in temporary storage
         self.emit_store_register(REG_RESULT.into(), dest);
@@ -1983,6 +2359,8 @@ impl<'a> CannonCodeGen<'a> {
             _ => unreachable!(),
         }
 
+        self.emit_register_finalizable(dest, cls_id, &type_params);
+
         let obj_reg = REG_TMP1;
         self.emit_load_register(dest, obj_reg.into());
 
@@ -1992,12 +2370,28 @@ impl<'a> CannonCodeGen<'a> {
 
         assert_eq!(arguments.len(), class_instance.fields.len());
 
+        let on_heap = !self.stack_objects.contains_key(&dest);
+
         // Initialize all class fields.
         for (&argument, field) in arguments.iter().zip(class_instance.fields.iter()) {
             // Reinitialize obj_reg for each field since write barrier overwrites it on x64.
             self.asm.copy_reg(MachineMode::Ptr, obj_reg, *scratch_reg);
 
-            self.emit_store_field_raw(obj_reg, field.offset, argument);
+            self.emit_store_field_raw(obj_reg, field.offset, argument, on_heap);
+        }
+    }
+
+    fn emit_register_finalizable(
+        &mut self,
+        dest: Register,
+        cls_id: ClassId,
+        type_params: &BytecodeTypeArray,
+    ) {
+        if self.vm.gc.supports_finalizers()
+            && finalizer::is_finalizable(self.vm, cls_id, type_params)
+        {
+            self.emit_load_register(dest, REG_TMP1.into());
+            self.asm.emit_register_finalizable(REG_TMP1);
         }
     }
 
@@ -2385,6 +2779,79 @@ impl<'a> CannonCodeGen<'a> {
         self.asm.copy_bytecode_ty(object_ty, dest, src);
     }
 
+    fn emit_test_trait_object(&mut self, dest: Register, idx: ConstPoolIdx, src: Register) {
+        assert_eq!(self.bytecode.register_type(dest), BytecodeType::Bool);
+        self.emit_cmp_trait_object_vtable(idx, src);
+        self.asm.set(REG_RESULT, CondCode::Equal);
+        self.emit_store_register(REG_RESULT.into(), dest);
+    }
+
+    fn emit_cast_trait_object(&mut self, dest: Register, idx: ConstPoolIdx, src: Register) {
+        let (object_ty, offset) = self.emit_cmp_trait_object_vtable(idx, src);
+
+        let position = self.bytecode.offset_location(self.current_offset.to_u32());
+        self.asm
+            .bailout_if(CondCode::NotEqual, Trap::CAST, position);
+
+        let bytecode_type = self.specialize_register_type(dest);
+        assert_eq!(bytecode_type, register_bty(object_ty.clone()));
+
+        let dest = self.reg(dest);
+        let src = RegOrOffset::RegWithOffset(REG_TMP1, offset);
+        self.asm.copy_bytecode_ty(object_ty, dest, src);
+    }
+
+    // Loads the trait object into REG_TMP1 and compares its vtable against
+    // the vtable used for trait objects wrapping the object type in the
+    // const pool entry. Returns the object type and the offset of the
+    // wrapped value.
+    fn emit_cmp_trait_object_vtable(
+        &mut self,
+        idx: ConstPoolIdx,
+        src: Register,
+    ) -> (BytecodeType, i32) {
+        assert!(self.bytecode.register_type(src).is_trait());
+
+        let (trait_id, type_params, object_ty) = match self.bytecode.const_pool(idx) {
+            ConstPoolEntry::Trait(trait_id, type_params, object_ty) => {
+                (*trait_id, type_params, object_ty.clone())
+            }
+            _ => unreachable!(),
+        };
+
+        let trait_id = TraitId(trait_id.0);
+
+        let type_params = self.specialize_bty_array(type_params);
+        debug_assert!(type_params.iter().all(|ty| ty.is_concrete_type()));
+
+        let object_ty = self.specialize_bty(object_ty);
+        debug_assert!(object_ty.is_concrete_type());
+
+        let class_instance_id = ensure_class_instance_for_trait_object(
+            self.vm,
+            trait_id,
+            &type_params,
+            object_ty.clone(),
+        );
+
+        let cls = self.vm.class_instances.idx(class_instance_id);
+        assert_eq!(cls.fields.len(), 1);
+        let offset = cls.fields[0].offset;
+
+        self.emit_load_register(src, REG_TMP1.into());
+
+        let vtable = cls.vtable.read();
+        let vtable: &VTable = vtable.as_ref().unwrap();
+        let disp = self.asm.add_addr(Address::from_ptr(vtable as *const _));
+        let pos = self.asm.pos() as i32;
+
+        self.asm.load_constpool(REG_RESULT, disp + pos);
+        self.asm
+            .cmp_mem(MachineMode::Ptr, Mem::Base(REG_TMP1, 0), REG_RESULT);
+
+        (object_ty, offset)
+    }
+
     fn emit_new_lambda(&mut self, dest: Register, idx: ConstPoolIdx) {
         assert_eq!(self.bytecode.register_type(dest), BytecodeType::Ptr);
 
@@ -2486,6 +2953,98 @@ impl<'a> CannonCodeGen<'a> {
         self.emit_store_register(REG_RESULT.into(), dest);
     }
 
+    fn emit_throw(&mut self, opnd: Register) {
+        let location = self.bytecode.offset_location(self.current_offset.to_u32());
+
+        self.emit_load_register(opnd, REG_PARAMS[0].into());
+        self.asm
+            .test_if_nil_bailout(location, REG_PARAMS[0], Trap::NIL);
+
+        let gcpoint = self.create_gcpoint();
+        self.asm.throw(location, gcpoint);
+    }
+
+    fn emit_checked_cast(&mut self, dest: Register, src: Register) {
+        let position = self.bytecode.offset_location(self.current_offset.to_u32());
+
+        match (
+            self.bytecode.register_type(src),
+            self.bytecode.register_type(dest),
+        ) {
+            (BytecodeType::Int64, BytecodeType::Int32) => {
+                self.emit_load_register(src, REG_RESULT.into());
+                self.asm.check_int64_to_int32(position, REG_RESULT);
+                self.emit_store_register(REG_RESULT.into(), dest);
+            }
+
+            (BytecodeType::Int32, BytecodeType::UInt8) => {
+                self.emit_load_register(src, REG_RESULT.into());
+                self.asm.check_int32_to_uint8(position, REG_RESULT);
+                self.emit_store_register(REG_RESULT.into(), dest);
+            }
+
+            (BytecodeType::Float64, BytecodeType::Int32) => {
+                self.emit_load_register(src, FREG_RESULT.into());
+                self.asm
+                    .checked_float64_to_int32(position, REG_RESULT, FREG_RESULT);
+                self.emit_store_register(REG_RESULT.into(), dest);
+            }
+
+            (src_type, dest_type) => panic!(
+                "unsupported checked cast from {:?} to {:?}",
+                src_type, dest_type
+            ),
+        }
+    }
+
+    fn emit_array_fill(&mut self, src: Register, arr: Register, length: Register) {
+        assert_eq!(self.bytecode.register_type(length), BytecodeType::Int64);
+        assert_eq!(self.bytecode.register_type(arr), BytecodeType::Ptr);
+
+        let position = self.bytecode.offset_location(self.current_offset.to_u32());
+
+        self.emit_load_register(arr, REG_RESULT.into());
+        self.asm
+            .test_if_nil_bailout(position, REG_RESULT, Trap::NIL);
+
+        self.emit_load_register(length, REG_TMP1.into());
+
+        if !self.vm.args.flag_omit_bounds_check {
+            self.asm
+                .check_length_out_of_bounds(position, REG_RESULT, REG_TMP1);
+        }
+
+        let src_type = self.specialize_register_type(src);
+
+        let mode = match src_type {
+            BytecodeType::Unit => return,
+            BytecodeType::Bool | BytecodeType::UInt8 => Some(MachineMode::Int8),
+            BytecodeType::UInt16 => Some(MachineMode::Int16),
+            BytecodeType::Char
+            | BytecodeType::Int32
+            | BytecodeType::UInt32
+            | BytecodeType::Float32 => Some(MachineMode::Int32),
+            BytecodeType::Int64 | BytecodeType::UInt64 | BytecodeType::Float64 => {
+                Some(MachineMode::Int64)
+            }
+            _ => None,
+        };
+
+        if let Some(mode) = mode {
+            // primitive values don't need barriers, just store the bits of the value
+            self.emit_load_register(arr, REG_TMP1.into());
+            self.emit_load_register(length, REG_TMP2.into());
+            self.emit_load_register_as(src, REG_RESULT.into(), mode);
+            self.asm.fill_array(mode, REG_TMP1, REG_TMP2, REG_RESULT);
+        } else {
+            let src_offset = self.register_offset(src);
+            self.emit_load_register(arr, REG_TMP1.into());
+            self.emit_load_register(length, REG_TMP2.into());
+            self.asm
+                .fill_array_with_references(REG_TMP1, REG_TMP2, Mem::Local(src_offset));
+        }
+    }
+
     fn emit_store_array(&mut self, src: Register, arr: Register, idx: Register) {
         assert_eq!(self.bytecode.register_type(idx), BytecodeType::Int64);
         assert_eq!(self.bytecode.register_type(arr), BytecodeType::Ptr);
@@ -2498,12 +3057,24 @@ impl<'a> CannonCodeGen<'a> {
 
         self.emit_load_register(idx, REG_TMP1.into());
 
-        if !self.vm.args.flag_omit_bounds_check {
+        if self.needs_index_check() {
             self.asm
                 .check_index_out_of_bounds(position, REG_RESULT, REG_TMP1);
         }
 
         let src_type = self.specialize_register_type(src);
+        let element_size = size(self.vm, src_type.clone());
+
+        if self.emit_pre_write_barrier(src_type.clone(), 0, |codegen| {
+            codegen.emit_load_register(arr, REG_RESULT.into());
+            codegen.emit_load_register(idx, REG_TMP1.into());
+            codegen
+                .asm
+                .array_address(REG_TMP1, REG_RESULT, REG_TMP1, element_size);
+        }) {
+            self.emit_load_register(arr, REG_RESULT.into());
+            self.emit_load_register(idx, REG_TMP1.into());
+        }
 
         match src_type {
             BytecodeType::Unit => {}
@@ -2592,6 +3163,9 @@ impl<'a> CannonCodeGen<'a> {
                 unreachable!()
             }
             BytecodeType::UInt8
+            | BytecodeType::UInt16
+            | BytecodeType::UInt32
+            | BytecodeType::UInt64
             | BytecodeType::Int32
             | BytecodeType::Bool
             | BytecodeType::Char
@@ -2641,6 +3215,19 @@ impl<'a> CannonCodeGen<'a> {
         }
     }
 
+    // LoadArray and StoreArray skip the bounds check if the index was
+    // already checked against the array's length by the loop condition.
+    fn needs_index_check(&self) -> bool {
+        if self.vm.args.flag_omit_bounds_check {
+            return false;
+        }
+
+        !self
+            .bounds_checks
+            .as_ref()
+            .is_some_and(|bounds_checks| bounds_checks.is_in_bounds(self.current_offset))
+    }
+
     fn emit_load_array(&mut self, dest: Register, arr: Register, idx: Register) {
         assert_eq!(self.bytecode.register_type(idx), BytecodeType::Int64);
         assert_eq!(self.bytecode.register_type(arr), BytecodeType::Ptr);
@@ -2653,7 +3240,7 @@ impl<'a> CannonCodeGen<'a> {
 
         self.emit_load_register(idx, REG_TMP1.into());
 
-        if !self.vm.args.flag_omit_bounds_check {
+        if self.needs_index_check() {
             self.asm
                 .check_index_out_of_bounds(position, REG_RESULT, REG_TMP1);
         }
@@ -2715,6 +3302,9 @@ impl<'a> CannonCodeGen<'a> {
                 unreachable!()
             }
             BytecodeType::UInt8
+            | BytecodeType::UInt16
+            | BytecodeType::UInt32
+            | BytecodeType::UInt64
             | BytecodeType::Int32
             | BytecodeType::Bool
             | BytecodeType::Char
@@ -2851,7 +3441,7 @@ impl<'a> CannonCodeGen<'a> {
             type_params.clone(),
         );
 
-        self.asm.virtual_call(
+        self.asm.virtual_call_cached(
             vtable_index,
             self_index,
             location,
@@ -2878,6 +3468,19 @@ impl<'a> CannonCodeGen<'a> {
         let location = self.bytecode.offset_location(self.current_offset.to_u32());
         let arguments = self.argument_stack.drain(..).collect::<Vec<_>>();
 
+        if self.tail_calls.contains(&self.current_offset) {
+            let self_register = arguments[0];
+
+            if self.bytecode.register_type(self_register).is_ptr() {
+                self.emit_load_register(self_register, REG_RESULT.into());
+                self.asm
+                    .test_if_nil_bailout(location, REG_RESULT, Trap::NIL);
+            }
+
+            self.emit_self_tail_call(dest, arguments);
+            return;
+        }
+
         self.emit_invoke_direct_or_intrinsic(dest, fct_id, type_params, arguments, location);
     }
 
@@ -2959,6 +3562,11 @@ impl<'a> CannonCodeGen<'a> {
         let location = self.bytecode.offset_location(self.current_offset.to_u32());
         let arguments = self.argument_stack.drain(..).collect::<Vec<_>>();
 
+        if self.tail_calls.contains(&self.current_offset) {
+            self.emit_self_tail_call(dest, arguments);
+            return;
+        }
+
         self.emit_invoke_static_or_intrinsic(dest, fct_id, type_params, arguments, location);
     }
 
@@ -3051,7 +3659,17 @@ impl<'a> CannonCodeGen<'a> {
             FunctionKind::Trait(trait_id) => trait_id,
             _ => unreachable!(),
         };
-        let trait_ty = BytecodeType::Trait(trait_id, BytecodeTypeArray::empty());
+
+        // type_params starts with the type arguments of the trait
+        // followed by the type arguments of the method itself.
+        let type_params = self.specialize_bty_array(&type_params);
+        let trait_ = &self.vm.program.traits[trait_id.0 as usize];
+        let trait_type_params_len = trait_.type_params.names.len();
+        let trait_type_params =
+            BytecodeTypeArray::new(type_params.iter().take(trait_type_params_len).collect());
+        let type_params =
+            BytecodeTypeArray::new(type_params.iter().skip(trait_type_params_len).collect());
+        let trait_ty = BytecodeType::Trait(trait_id, trait_type_params);
 
         let ty = self.type_params[id as usize].clone();
         let callee_id = find_trait_impl(self.vm, trait_fct_id, trait_ty, ty);
@@ -3134,6 +3752,20 @@ impl<'a> CannonCodeGen<'a> {
                 );
             }
 
+            Intrinsic::Float32Min
+            | Intrinsic::Float32Max
+            | Intrinsic::Float64Min
+            | Intrinsic::Float64Max => {
+                self.emit_intrinsic_float_min_max(
+                    dest,
+                    fct_id,
+                    intrinsic,
+                    arguments,
+                    type_params,
+                    location,
+                );
+            }
+
             Intrinsic::Int32CountZeroBits
             | Intrinsic::Int32CountZeroBitsLeading
             | Intrinsic::Int32CountZeroBitsTrailing
@@ -3281,6 +3913,20 @@ impl<'a> CannonCodeGen<'a> {
                 self.emit_store_register(FREG_RESULT.into(), dest);
             }
 
+            Intrinsic::Int32AddOverflows
+            | Intrinsic::Int32SubOverflows
+            | Intrinsic::Int32MulOverflows
+            | Intrinsic::Int64AddOverflows
+            | Intrinsic::Int64SubOverflows
+            | Intrinsic::Int64MulOverflows => {
+                assert_eq!(arguments.len(), 2);
+                self.emit_intrinsic_overflows(dest, intrinsic, arguments[0], arguments[1]);
+            }
+
+            Intrinsic::ArrayCopy => {
+                self.emit_intrinsic_array_copy(dest, arguments, type_params, location);
+            }
+
             Intrinsic::UnsafeKillRefs => {
                 self.emit_intrinsic_unsafe_kill_refs(
                     dest,
@@ -3643,11 +4289,11 @@ impl<'a> CannonCodeGen<'a> {
     fn emit_intrinsic_count_bits(
         &mut self,
         dest: Register,
-        fct_id: FunctionId,
+        _fct_id: FunctionId,
         intrinsic: Intrinsic,
         arguments: Vec<Register>,
         type_params: BytecodeTypeArray,
-        location: Location,
+        _location: Location,
     ) {
         debug_assert_eq!(arguments.len(), 1);
         debug_assert!(type_params.is_empty());
@@ -3671,59 +4317,35 @@ impl<'a> CannonCodeGen<'a> {
 
         match intrinsic {
             Intrinsic::Int32CountZeroBits | Intrinsic::Int64CountZeroBits => {
-                if has_popcnt() {
-                    self.emit_load_register(arguments[0], reg.into());
-                    self.asm.count_bits(mode, reg, reg, false);
-                    self.emit_store_register(reg.into(), dest);
-                } else {
-                    self.emit_invoke_direct(dest, fct_id, type_params, arguments, location);
-                }
+                self.emit_load_register(arguments[0], reg.into());
+                self.asm.count_bits(mode, reg, reg, false);
+                self.emit_store_register(reg.into(), dest);
             }
             Intrinsic::Int32CountOneBits | Intrinsic::Int64CountOneBits => {
-                if has_popcnt() {
-                    self.emit_load_register(arguments[0], reg.into());
-                    self.asm.count_bits(mode, reg, reg, true);
-                    self.emit_store_register(reg.into(), dest);
-                } else {
-                    self.emit_invoke_direct(dest, fct_id, type_params, arguments, location);
-                }
+                self.emit_load_register(arguments[0], reg.into());
+                self.asm.count_bits(mode, reg, reg, true);
+                self.emit_store_register(reg.into(), dest);
             }
             Intrinsic::Int32CountZeroBitsLeading | Intrinsic::Int64CountZeroBitsLeading => {
-                if has_lzcnt() {
-                    self.emit_load_register(arguments[0], reg.into());
-                    self.asm.count_bits_leading(mode, reg, reg, false);
-                    self.emit_store_register(reg.into(), dest);
-                } else {
-                    self.emit_invoke_direct(dest, fct_id, type_params, arguments, location);
-                }
+                self.emit_load_register(arguments[0], reg.into());
+                self.asm.count_bits_leading(mode, reg, reg, false);
+                self.emit_store_register(reg.into(), dest);
             }
             Intrinsic::Int32CountOneBitsLeading | Intrinsic::Int64CountOneBitsLeading => {
-                if has_lzcnt() {
-                    self.emit_load_register(arguments[0], reg.into());
-                    self.asm.count_bits_leading(mode, reg, reg, true);
-                    self.emit_store_register(reg.into(), dest);
-                } else {
-                    self.emit_invoke_direct(dest, fct_id, type_params, arguments, location);
-                }
+                self.emit_load_register(arguments[0], reg.into());
+                self.asm.count_bits_leading(mode, reg, reg, true);
+                self.emit_store_register(reg.into(), dest);
             }
 
             Intrinsic::Int32CountZeroBitsTrailing | Intrinsic::Int64CountZeroBitsTrailing => {
-                if has_tzcnt() {
-                    self.emit_load_register(arguments[0], reg.into());
-                    self.asm.count_bits_trailing(mode, reg, reg, false);
-                    self.emit_store_register(reg.into(), dest);
-                } else {
-                    self.emit_invoke_direct(dest, fct_id, type_params, arguments, location);
-                }
+                self.emit_load_register(arguments[0], reg.into());
+                self.asm.count_bits_trailing(mode, reg, reg, false);
+                self.emit_store_register(reg.into(), dest);
             }
             Intrinsic::Int32CountOneBitsTrailing | Intrinsic::Int64CountOneBitsTrailing => {
-                if has_tzcnt() {
-                    self.emit_load_register(arguments[0], reg.into());
-                    self.asm.count_bits_trailing(mode, reg, reg, true);
-                    self.emit_store_register(reg.into(), dest);
-                } else {
-                    self.emit_invoke_direct(dest, fct_id, type_params, arguments, location);
-                }
+                self.emit_load_register(arguments[0], reg.into());
+                self.asm.count_bits_trailing(mode, reg, reg, true);
+                self.emit_store_register(reg.into(), dest);
             }
             _ => unreachable!(),
         }
@@ -3845,6 +4467,42 @@ impl<'a> CannonCodeGen<'a> {
         self.emit_store_register(FREG_RESULT.into(), dest);
     }
 
+    fn emit_intrinsic_float_min_max(
+        &mut self,
+        dest: Register,
+        _fct_id: FunctionId,
+        intrinsic: Intrinsic,
+        arguments: Vec<Register>,
+        type_params: BytecodeTypeArray,
+        _location: Location,
+    ) {
+        debug_assert_eq!(arguments.len(), 2);
+        debug_assert!(type_params.is_empty());
+
+        let mode = match intrinsic {
+            Intrinsic::Float32Min | Intrinsic::Float32Max => MachineMode::Float32,
+            Intrinsic::Float64Min | Intrinsic::Float64Max => MachineMode::Float64,
+            _ => unreachable!(),
+        };
+
+        self.emit_load_register(arguments[0], FREG_RESULT.into());
+        self.emit_load_register(arguments[1], FREG_TMP1.into());
+
+        match intrinsic {
+            Intrinsic::Float32Min | Intrinsic::Float64Min => {
+                self.asm
+                    .float_min(mode, FREG_RESULT, FREG_RESULT, FREG_TMP1)
+            }
+            Intrinsic::Float32Max | Intrinsic::Float64Max => {
+                self.asm
+                    .float_max(mode, FREG_RESULT, FREG_RESULT, FREG_TMP1)
+            }
+            _ => unreachable!(),
+        }
+
+        self.emit_store_register(FREG_RESULT.into(), dest);
+    }
+
     fn emit_intrinsic_option_get_or_panic(
         &mut self,
         dest: Register,
@@ -3949,6 +4607,119 @@ impl<'a> CannonCodeGen<'a> {
             .push((lbl, dest, fct_id, arguments, type_params, location));
     }
 
+    fn emit_intrinsic_overflows(
+        &mut self,
+        dest: Register,
+        intrinsic: Intrinsic,
+        lhs: Register,
+        rhs: Register,
+    ) {
+        assert_eq!(self.bytecode.register_type(dest), BytecodeType::Bool);
+        let mode = match intrinsic {
+            Intrinsic::Int32AddOverflows
+            | Intrinsic::Int32SubOverflows
+            | Intrinsic::Int32MulOverflows => MachineMode::Int32,
+            _ => MachineMode::Int64,
+        };
+
+        self.emit_load_register(lhs, REG_RESULT.into());
+        self.emit_load_register(rhs, REG_TMP1.into());
+
+        match intrinsic {
+            Intrinsic::Int32AddOverflows | Intrinsic::Int64AddOverflows => self
+                .asm
+                .int_add_overflows(mode, REG_RESULT, REG_RESULT, REG_TMP1),
+            Intrinsic::Int32SubOverflows | Intrinsic::Int64SubOverflows => self
+                .asm
+                .int_sub_overflows(mode, REG_RESULT, REG_RESULT, REG_TMP1),
+            _ => self
+                .asm
+                .int_mul_overflows(mode, REG_RESULT, REG_RESULT, REG_TMP1),
+        }
+
+        self.emit_store_register(REG_RESULT.into(), dest);
+    }
+
+    fn emit_intrinsic_array_copy(
+        &mut self,
+        dest: Register,
+        arguments: Vec<Register>,
+        type_params: BytecodeTypeArray,
+        location: Location,
+    ) {
+        assert_eq!(1, type_params.len());
+        assert_eq!(5, arguments.len());
+        assert!(self.bytecode.register_type(dest).is_unit());
+
+        let (src, src_pos, dest_array, dest_pos, length) = (
+            arguments[0],
+            arguments[1],
+            arguments[2],
+            arguments[3],
+            arguments[4],
+        );
+
+        self.emit_array_range_check(location, src, src_pos, length);
+        self.emit_array_range_check(location, dest_array, dest_pos, length);
+
+        let ty = type_params[0].clone();
+
+        if ty.is_unit() {
+            return;
+        }
+
+        let element_size = size(self.vm, register_bty(ty));
+
+        // array_address might use the parameter registers as scratch registers,
+        // so only move the arguments into place once all addresses are computed.
+        self.emit_load_register(src, REG_RESULT.into());
+        self.emit_load_register(src_pos, REG_TMP2.into());
+        self.asm
+            .array_address(REG_TMP2, REG_RESULT, REG_TMP2, element_size);
+        self.emit_load_register(dest_array, REG_RESULT.into());
+        self.emit_load_register(dest_pos, REG_TMP1.into());
+        self.asm
+            .array_address(REG_TMP1, REG_RESULT, REG_TMP1, element_size);
+
+        self.asm
+            .copy_reg(MachineMode::Ptr, REG_PARAMS[0], REG_RESULT);
+        self.asm.copy_reg(MachineMode::Ptr, REG_PARAMS[1], REG_TMP1);
+        self.asm.copy_reg(MachineMode::Ptr, REG_PARAMS[2], REG_TMP2);
+        self.emit_load_register(length, REG_PARAMS[3].into());
+        self.asm.copy_array();
+    }
+
+    // checks that the range of length elements starting at pos lies within
+    // the array, the range might end right after the last element.
+    fn emit_array_range_check(
+        &mut self,
+        location: Location,
+        array: Register,
+        pos: Register,
+        length: Register,
+    ) {
+        self.emit_load_register(array, REG_RESULT.into());
+        self.asm
+            .test_if_nil_bailout(location, REG_RESULT, Trap::NIL);
+
+        if self.vm.args.flag_omit_bounds_check {
+            return;
+        }
+
+        self.emit_load_register(pos, REG_TMP1.into());
+        self.asm
+            .check_length_out_of_bounds(location, REG_RESULT, REG_TMP1);
+        self.emit_load_register(length, REG_TMP2.into());
+        self.asm
+            .check_length_out_of_bounds(location, REG_RESULT, REG_TMP2);
+
+        // pos and length are both at most the array length, the sum can't overflow
+        self.asm
+            .int_add(MachineMode::Int64, REG_TMP1, REG_TMP1, REG_TMP2);
+        self.asm
+            .check_length_out_of_bounds(location, REG_RESULT, REG_TMP1);
+    }
+
     fn emit_intrinsic_unsafe_kill_refs(
         &mut self,
         dest: Register,
@@ -3969,12 +4740,19 @@ impl<'a> CannonCodeGen<'a> {
         }
 
         let bytecode_type: BytecodeType = register_bty(ty.clone());
+        let element_size = size(self.vm, bytecode_type.clone());
 
-        self.emit_load_register(arguments[0], REG_RESULT.into());
-        self.emit_load_register(arguments[1], REG_TMP1.into());
+        let load_element_address = |codegen: &mut CannonCodeGen<'a>| {
+            codegen.emit_load_register(arguments[0], REG_RESULT.into());
+            codegen.emit_load_register(arguments[1], REG_TMP1.into());
 
-        self.asm
-            .array_address(REG_TMP1, REG_RESULT, REG_TMP1, size(self.vm, bytecode_type));
+            codegen
+                .asm
+                .array_address(REG_TMP1, REG_RESULT, REG_TMP1, element_size);
+        };
+
+        self.emit_pre_write_barrier(bytecode_type, 0, load_element_address);
+        load_element_address(self);
 
         self.asm.zero_ty(ty, RegOrOffset::Reg(REG_TMP1));
     }
@@ -4108,6 +4886,9 @@ impl<'a> CannonCodeGen<'a> {
 
                 BytecodeType::Bool
                 | BytecodeType::UInt8
+                | BytecodeType::UInt16
+                | BytecodeType::UInt32
+                | BytecodeType::UInt64
                 | BytecodeType::Char
                 | BytecodeType::Int32
                 | BytecodeType::Int64
@@ -4218,10 +4999,49 @@ impl<'a> BytecodeVisitor for CannonCodeGen<'a> {
         self.offset_to_address.insert(offset, self.asm.pos());
         self.current_offset = offset;
 
+        if let Some(&line) = self.line_starts.get(&offset) {
+            self.asm.emit_comment(format!("line {}", line));
+        }
+
+        // The resume stub passes the thrown object in REG_RESULT. Regular
+        // jumps to this offset need to skip storing it.
+        if let Some(handler) = self
+            .bytecode
+            .exception_handlers()
+            .iter()
+            .find(|handler| handler.handler == offset)
+        {
+            let register = handler.register;
+            self.handler_to_address.insert(offset, self.asm.pos());
+            self.emit_store_register(REG_RESULT.into(), register);
+        }
+
         if let Some(&label) = self.offset_to_label.get(&offset) {
             self.asm.bind_label(label);
         }
 
+        if self.coverage_file.is_some() {
+            let opcode = self.bytecode.read_opcode(offset);
+
+            if offset.to_u32() == 0
+                || self.after_conditional_jump
+                || self.offset_to_label.contains_key(&offset)
+                || opcode.is_loop_start()
+            {
+                self.coverage_pending = true;
+            }
+
+            // Only instructions with a location are in the location table, the
+            // block's counter is placed at the first of them. Blocks without any
+            // of these instructions are not counted.
+            if self.coverage_pending && opcode.needs_location() {
+                self.emit_coverage_counter();
+                self.coverage_pending = false;
+            }
+
+            self.after_conditional_jump = opcode.is_conditional_jump();
+        }
+
         // Ensure that PushRegister instructions are only followed by InvokeXXX,
         // NewTuple, NewEnum or NewStruct.
         if !self.argument_stack.is_empty() {
@@ -4243,11 +5063,21 @@ impl<'a> BytecodeVisitor for CannonCodeGen<'a> {
         self.emit_add(dest, lhs, rhs);
     }
 
+    fn visit_add_saturating(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        comment!(self, format!("AddSaturating {}, {}, {}", dest, lhs, rhs));
+        self.emit_saturating(dest, lhs, rhs, true);
+    }
+
     fn visit_sub(&mut self, dest: Register, lhs: Register, rhs: Register) {
         comment!(self, format!("Sub {}, {}, {}", dest, lhs, rhs));
         self.emit_sub(dest, lhs, rhs);
     }
 
+    fn visit_sub_saturating(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        comment!(self, format!("SubSaturating {}, {}, {}", dest, lhs, rhs));
+        self.emit_saturating(dest, lhs, rhs, false);
+    }
+
     fn visit_neg(&mut self, dest: Register, src: Register) {
         comment!(self, format!("Neg {}, {}", dest, src));
         self.emit_neg(dest, src);
@@ -4656,6 +5486,28 @@ impl<'a> BytecodeVisitor for CannonCodeGen<'a> {
         );
         self.emit_jump_loop(target);
     }
+    fn visit_switch(&mut self, opnd: Register, idx: ConstPoolIdx) {
+        let (low, targets) = self
+            .bytecode
+            .const_pool(idx)
+            .to_jump_table()
+            .expect("jump table expected");
+        let targets = targets
+            .iter()
+            .map(|&offset| BytecodeOffset(self.current_offset.to_u32() + offset))
+            .collect::<Vec<_>>();
+        comment!(
+            self,
+            format!(
+                "Switch {}, ConstPoolId({}) # low {}, targets {:?}",
+                opnd,
+                idx.0,
+                low,
+                targets.iter().map(|t| t.to_usize()).collect::<Vec<_>>()
+            )
+        );
+        self.emit_switch(opnd, low, targets);
+    }
     fn visit_jump(&mut self, offset: u32) {
         let target = BytecodeOffset(self.current_offset.to_u32() + offset);
         comment!(
@@ -4829,6 +5681,27 @@ impl<'a> BytecodeVisitor for CannonCodeGen<'a> {
         self.emit_new_trait_object(dest, idx, src);
     }
 
+    fn visit_test_trait_object(&mut self, dest: Register, idx: ConstPoolIdx, src: Register) {
+        comment!(
+            self,
+            format!("TestTraitObject {}, ConstPoolIdx({}), {}", dest, idx.0, src)
+        );
+        self.emit_test_trait_object(dest, idx, src);
+    }
+
+    fn visit_cast_trait_object(&mut self, dest: Register, idx: ConstPoolIdx, src: Register) {
+        comment!(
+            self,
+            format!("CastTraitObject {}, ConstPoolIdx({}), {}", dest, idx.0, src)
+        );
+        self.emit_cast_trait_object(dest, idx, src);
+    }
+
+    fn visit_checked_cast(&mut self, dest: Register, src: Register) {
+        comment!(self, format!("CheckedCast {}, {}", dest, src));
+        self.emit_checked_cast(dest, src);
+    }
+
     fn visit_new_lambda(&mut self, dest: Register, idx: ConstPoolIdx) {
         comment!(self, {
             let (fct_id, _type_params) = match self.bytecode.const_pool(idx) {
@@ -4856,11 +5729,21 @@ impl<'a> BytecodeVisitor for CannonCodeGen<'a> {
         self.emit_store_array(src, arr, idx);
     }
 
+    fn visit_array_fill(&mut self, src: Register, arr: Register, length: Register) {
+        comment!(self, format!("ArrayFill {}, {}, {}", src, arr, length));
+        self.emit_array_fill(src, arr, length);
+    }
+
     fn visit_load_trait_object_value(&mut self, dest: Register, object: Register) {
         comment!(self, format!("LoadTraitObjectValue {}, {}", dest, object));
         self.emit_load_trait_object_value(dest, object);
     }
 
+    fn visit_throw(&mut self, opnd: Register) {
+        comment!(self, format!("Throw {}", opnd));
+        self.emit_throw(opnd);
+    }
+
     fn visit_ret(&mut self, opnd: Register) {
         comment!(self, format!("Ret {}", opnd));
         self.emit_return_generic(opnd);
@@ -4879,6 +5762,9 @@ fn result_passed_as_argument(ty: BytecodeType) -> bool {
         BytecodeType::Unit
         | BytecodeType::Bool
         | BytecodeType::UInt8
+        | BytecodeType::UInt16
+        | BytecodeType::UInt32
+        | BytecodeType::UInt64
         | BytecodeType::Char
         | BytecodeType::Int32
         | BytecodeType::Int64
@@ -4945,6 +5831,9 @@ pub fn mode(vm: &VM, ty: BytecodeType) -> MachineMode {
     match ty {
         BytecodeType::Bool => MachineMode::Int8,
         BytecodeType::UInt8 => MachineMode::Int8,
+        BytecodeType::UInt16 => MachineMode::Int16,
+        BytecodeType::UInt32 => MachineMode::Int32,
+        BytecodeType::UInt64 => MachineMode::Int64,
         BytecodeType::Char => MachineMode::Int32,
         BytecodeType::Int32 => MachineMode::Int32,
         BytecodeType::Int64 => MachineMode::Int64,
@@ -4978,6 +5867,9 @@ pub fn size(vm: &VM, ty: BytecodeType) -> i32 {
         BytecodeType::Unit => 0,
         BytecodeType::Bool => 1,
         BytecodeType::UInt8 => 1,
+        BytecodeType::UInt16 => 2,
+        BytecodeType::UInt32 => 4,
+        BytecodeType::UInt64 => 8,
         BytecodeType::Char => 4,
         BytecodeType::Int32 => 4,
         BytecodeType::Int64 => 8,
@@ -5012,6 +5904,9 @@ pub fn align(vm: &VM, ty: BytecodeType) -> i32 {
         BytecodeType::Unit => 0,
         BytecodeType::Bool => 1,
         BytecodeType::UInt8 => 1,
+        BytecodeType::UInt16 => 2,
+        BytecodeType::UInt32 => 4,
+        BytecodeType::UInt64 => 8,
         BytecodeType::Char => 4,
         BytecodeType::Int32 => 4,
         BytecodeType::Int64 => 8,
@@ -6,4 +6,5 @@ pub mod codegen;
 pub mod dora_entry_stub;
 pub mod dora_exit_stubs;
 pub mod lazy_compilation_stub;
+pub mod resume_stub;
 pub mod trait_object_thunk;
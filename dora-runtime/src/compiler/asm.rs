@@ -12,7 +12,7 @@ use crate::stdlib;
 use crate::threads::ThreadLocalData;
 use crate::vm::{
     create_enum_instance, create_struct_instance, get_concrete_tuple_bty_array, EnumLayout,
-    GcPoint, LazyCompilationSite, Trap, VM,
+    ExceptionHandler, GcPoint, LazyCompilationSite, Trap, VM,
 };
 use dora_bytecode::{BytecodeType, BytecodeTypeArray, FunctionId, GlobalId, Location, StructId};
 
@@ -104,6 +104,19 @@ impl<'a> BaselineAssembler<'a> {
         self.masm.emit_gcpoint(gcpoint);
     }
 
+    pub fn add_exception_handler(&mut self, handler: ExceptionHandler) {
+        self.masm.add_exception_handler(handler);
+    }
+
+    // the thrown object needs to be in the first argument register,
+    // the throw stub never returns.
+    pub fn throw(&mut self, location: Location, gcpoint: GcPoint) {
+        self.masm.raw_call(self.vm.stubs.throw());
+        self.masm.emit_gcpoint(gcpoint);
+        self.masm.emit_position(location);
+        self.masm.debug();
+    }
+
     pub fn bind_label(&mut self, label: Label) {
         self.masm.bind_label(label);
     }
@@ -124,6 +137,14 @@ impl<'a> BaselineAssembler<'a> {
         self.masm.jump_if(cond, label);
     }
 
+    pub fn jump_table(&mut self, index: Reg, targets: Vec<Label>) {
+        self.masm.jump_table(index, targets);
+    }
+
+    pub fn increment_counter(&mut self, counter: Address) {
+        self.masm.increment_counter(counter);
+    }
+
     pub fn bailout_if(&mut self, cond: CondCode, trap: Trap, location: Location) {
         self.masm.bailout_if(cond, trap, location);
     }
@@ -173,6 +194,9 @@ impl<'a> BaselineAssembler<'a> {
             }
 
             BytecodeType::UInt8
+            | BytecodeType::UInt16
+            | BytecodeType::UInt32
+            | BytecodeType::UInt64
             | BytecodeType::Bool
             | BytecodeType::Char
             | BytecodeType::Int32
@@ -324,6 +348,31 @@ impl<'a> BaselineAssembler<'a> {
         self.masm.emit_barrier(src, card_table_offset);
     }
 
+    pub fn test_concurrent_marking(&mut self, marking_flag: Address, lbl_inactive: Label) {
+        self.masm
+            .test_concurrent_marking(marking_flag, lbl_inactive);
+    }
+
+    pub fn emit_pre_write_barrier(&mut self, slot: Mem) {
+        self.masm.emit_pre_write_barrier(slot);
+    }
+
+    pub fn fill_array(&mut self, mode: MachineMode, array: Reg, length: Reg, value: Reg) {
+        self.masm.fill_array(mode, array, length, value);
+    }
+
+    pub fn fill_array_with_references(&mut self, array: Reg, length: Reg, value: Mem) {
+        self.masm.fill_array_with_references(array, length, value);
+    }
+
+    pub fn copy_array(&mut self) {
+        self.masm.copy_array();
+    }
+
+    pub fn emit_register_finalizable(&mut self, obj: Reg) {
+        self.masm.emit_register_finalizable(obj);
+    }
+
     pub fn emit_bailout(&mut self, lbl: Label, trap: Trap, location: Location) {
         self.masm.emit_bailout(lbl, trap, location);
     }
@@ -379,6 +428,26 @@ impl<'a> BaselineAssembler<'a> {
         self.masm.int_add_imm(mode, dest, lhs, value);
     }
 
+    pub fn int_add_overflows(&mut self, mode: MachineMode, dest: Reg, lhs: Reg, rhs: Reg) {
+        self.masm.int_add_overflows(mode, dest, lhs, rhs);
+    }
+
+    pub fn int_sub_overflows(&mut self, mode: MachineMode, dest: Reg, lhs: Reg, rhs: Reg) {
+        self.masm.int_sub_overflows(mode, dest, lhs, rhs);
+    }
+
+    pub fn int_mul_overflows(&mut self, mode: MachineMode, dest: Reg, lhs: Reg, rhs: Reg) {
+        self.masm.int_mul_overflows(mode, dest, lhs, rhs);
+    }
+
+    pub fn int_add_saturating(&mut self, mode: MachineMode, dest: Reg, lhs: Reg, rhs: Reg) {
+        self.masm.int_add_saturating(mode, dest, lhs, rhs);
+    }
+
+    pub fn int_sub_saturating(&mut self, mode: MachineMode, dest: Reg, lhs: Reg, rhs: Reg) {
+        self.masm.int_sub_saturating(mode, dest, lhs, rhs);
+    }
+
     pub fn int_sub(&mut self, mode: MachineMode, dest: Reg, lhs: Reg, rhs: Reg) {
         self.masm.int_sub(mode, dest, lhs, rhs);
     }
@@ -398,6 +467,10 @@ impl<'a> BaselineAssembler<'a> {
         self.masm.int_mul(mode, dest, lhs, rhs);
     }
 
+    pub fn int_mul_imm(&mut self, mode: MachineMode, dest: Reg, lhs: Reg, value: i64) {
+        self.masm.int_mul_imm(mode, dest, lhs, value);
+    }
+
     pub fn int_mul_checked(
         &mut self,
         mode: MachineMode,
@@ -517,6 +590,18 @@ impl<'a> BaselineAssembler<'a> {
         self.masm.float_div(mode, dest, lhs, rhs);
     }
 
+    pub fn float_min(&mut self, mode: MachineMode, dest: FReg, lhs: FReg, rhs: FReg) {
+        self.masm.float_min(mode, dest, lhs, rhs);
+    }
+
+    pub fn float_max(&mut self, mode: MachineMode, dest: FReg, lhs: FReg, rhs: FReg) {
+        self.masm.float_max(mode, dest, lhs, rhs);
+    }
+
+    pub fn float_rem(&mut self, mode: MachineMode, dest: FReg, lhs: FReg, rhs: FReg) {
+        self.masm.float_rem(mode, dest, lhs, rhs);
+    }
+
     pub fn float_abs(&mut self, mode: MachineMode, dest: FReg, src: FReg) {
         self.masm.float_abs(mode, dest, src);
     }
@@ -599,6 +684,23 @@ impl<'a> BaselineAssembler<'a> {
         self.masm.check_index_out_of_bounds(location, array, index);
     }
 
+    pub fn check_int64_to_int32(&mut self, location: Location, src: Reg) {
+        self.masm.check_int64_to_int32(location, src);
+    }
+
+    pub fn check_int32_to_uint8(&mut self, location: Location, src: Reg) {
+        self.masm.check_int32_to_uint8(location, src);
+    }
+
+    pub fn checked_float64_to_int32(&mut self, location: Location, dest: Reg, src: FReg) {
+        self.masm.checked_float64_to_int32(location, dest, src);
+    }
+
+    pub fn check_length_out_of_bounds(&mut self, location: Location, array: Reg, length: Reg) {
+        self.masm
+            .check_length_out_of_bounds(location, array, length);
+    }
+
     pub fn extend_byte(&mut self, mode: MachineMode, dest: Reg, src: Reg) {
         self.masm.extend_byte(mode, dest, src);
     }
@@ -715,6 +817,27 @@ impl<'a> BaselineAssembler<'a> {
         self.call_epilog(location, return_mode, dest, gcpoint);
     }
 
+    #[allow(clippy::too_many_arguments)]
+    pub fn virtual_call_cached(
+        &mut self,
+        vtable_index: u32,
+        self_index: u32,
+        location: Location,
+        gcpoint: GcPoint,
+        return_mode: Option<MachineMode>,
+        dest: AnyReg,
+        lazy_compilation_site: LazyCompilationSite,
+    ) {
+        self.masm.virtual_call_cached(
+            location,
+            gcpoint,
+            vtable_index,
+            self_index,
+            lazy_compilation_site,
+        );
+        self.copy_result(return_mode, dest);
+    }
+
     fn call_epilog(
         &mut self,
         location: Location,
@@ -980,6 +1103,9 @@ impl<'a> BaselineAssembler<'a> {
 
             BytecodeType::Ptr
             | BytecodeType::UInt8
+            | BytecodeType::UInt16
+            | BytecodeType::UInt32
+            | BytecodeType::UInt64
             | BytecodeType::Bool
             | BytecodeType::Char
             | BytecodeType::Int32
@@ -10,7 +10,9 @@ use crate::masm::CodeDescriptor;
 use crate::os;
 use crate::vm::CompilerName;
 use crate::vm::{display_fct, install_code, CodeKind, VM};
-use dora_bytecode::{BytecodeFunction, BytecodeType, BytecodeTypeArray, FunctionId, Location};
+use dora_bytecode::{
+    BytecodeFunction, BytecodeType, BytecodeTypeArray, FunctionId, Location, SourceFileId,
+};
 
 pub fn generate_fct(vm: &VM, fct_id: FunctionId, type_params: &BytecodeTypeArray) -> Address {
     debug_assert!(type_params.iter().all(|ty| ty.is_concrete_type()));
@@ -24,11 +26,14 @@ pub fn generate_fct(vm: &VM, fct_id: FunctionId, type_params: &BytecodeTypeArray
         return instruction_start;
     }
 
-    let compiler = if program_fct.is_optimize_immediately {
-        CompilerName::Boots
-    } else {
-        CompilerName::Cannon
-    };
+    // boots emits no SATB pre-write barriers, so all code needs to be
+    // compiled by cannon while concurrent marking is enabled.
+    let compiler =
+        if program_fct.is_optimize_immediately && vm.gc.concurrent_marking_flag().is_none() {
+            CompilerName::Boots
+        } else {
+            CompilerName::Cannon
+        };
 
     let bytecode_fct = program_fct.bytecode.as_ref().expect("bytecode missing");
 
@@ -41,6 +46,7 @@ pub fn generate_fct(vm: &VM, fct_id: FunctionId, type_params: &BytecodeTypeArray
     }
 
     let compilation_data = CompilationData {
+        fct_id: Some(fct_id),
         bytecode_fct,
         params: BytecodeTypeArray::new(program_fct.params.clone()),
         has_variadic_parameter: program_fct.is_variadic,
@@ -50,6 +56,14 @@ pub fn generate_fct(vm: &VM, fct_id: FunctionId, type_params: &BytecodeTypeArray
 
         emit_debug,
         emit_code_comments: emit_asm,
+        emit_code_lines: emit_asm && vm.args.flag_emit_asm_lines,
+        coverage_file: if vm.args.coverage()
+            && program_fct.package_id == vm.program.program_package_id
+        {
+            program_fct.source_file_id
+        } else {
+            None
+        },
     };
 
     let compilation_flags = CompilationFlags::jit();
@@ -85,8 +99,13 @@ pub fn generate_fct(vm: &VM, fct_id: FunctionId, type_params: &BytecodeTypeArray
         os::perf::register_with_perf(&code, &name);
     }
 
+    if vm.args.flag_enable_gdb {
+        let name = display_fct(vm, fct_id);
+        os::gdb::register_with_gdb(&code, &name);
+    }
+
     if emit_asm {
-        disassembler::disassemble(vm, fct_id, &type_params, &code);
+        disassembler::disassemble(vm, fct_id, &type_params, &code, vm.args.asm_syntax());
     }
 
     code.instruction_start()
@@ -110,7 +129,10 @@ pub fn generate_thunk(
         return instruction_start;
     }
 
-    let compiler = if trait_fct.is_optimize_immediately {
+    // boots emits no SATB pre-write barriers, so all code needs to be
+    // compiled by cannon while concurrent marking is enabled.
+    let compiler = if trait_fct.is_optimize_immediately && vm.gc.concurrent_marking_flag().is_none()
+    {
         CompilerName::Boots
     } else {
         CompilerName::Cannon
@@ -133,6 +155,7 @@ pub fn generate_thunk(
     let has_variadic_parameter = trait_fct.is_variadic;
 
     let compilation_data = CompilationData {
+        fct_id: None,
         bytecode_fct: &bytecode_fct,
         params,
         has_variadic_parameter,
@@ -142,6 +165,8 @@ pub fn generate_thunk(
 
         emit_debug,
         emit_code_comments: emit_asm,
+        emit_code_lines: emit_asm && vm.args.flag_emit_asm_lines,
+        coverage_file: None,
     };
 
     let code_descriptor = match compiler {
@@ -175,8 +200,13 @@ pub fn generate_thunk(
         os::perf::register_with_perf(&code, &name);
     }
 
+    if vm.args.flag_enable_gdb {
+        let name = display_fct(vm, trait_fct_id);
+        os::gdb::register_with_gdb(&code, &name);
+    }
+
     if emit_asm {
-        disassembler::disassemble(vm, trait_fct_id, &type_params, &code);
+        disassembler::disassemble(vm, trait_fct_id, &type_params, &code, vm.args.asm_syntax());
     }
 
     code.instruction_start()
@@ -293,7 +323,13 @@ pub fn ensure_native_stub(vm: &VM, fct_id: Option<FunctionId>, native_fct: Nativ
 
         if let Some(fct_id) = fct_id {
             if should_emit_asm(vm, fct_id) {
-                disassembler::disassemble(vm, fct_id, &BytecodeTypeArray::empty(), &code);
+                disassembler::disassemble(
+                    vm,
+                    fct_id,
+                    &BytecodeTypeArray::empty(),
+                    &code,
+                    vm.args.asm_syntax(),
+                );
             }
         }
 
@@ -303,6 +339,8 @@ pub fn ensure_native_stub(vm: &VM, fct_id: Option<FunctionId>, native_fct: Nativ
 }
 
 pub struct CompilationData<'a> {
+    // None for generated code without a function of its own, e.g. thunks.
+    pub fct_id: Option<FunctionId>,
     pub bytecode_fct: &'a BytecodeFunction,
     pub params: BytecodeTypeArray,
     pub has_variadic_parameter: bool,
@@ -312,4 +350,6 @@ pub struct CompilationData<'a> {
 
     pub emit_debug: bool,
     pub emit_code_comments: bool,
+    pub emit_code_lines: bool,
+    pub coverage_file: Option<SourceFileId>,
 }
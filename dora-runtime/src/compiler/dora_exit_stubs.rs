@@ -44,6 +44,7 @@ pub enum NativeFctKind {
     AllocStub,
     VerifyStub,
     TrapStub,
+    ThrowStub,
     GuardCheckStub,
     SafepointStub,
 }
@@ -239,6 +240,7 @@ impl<'a> NativeGen<'a> {
             NativeFctKind::AllocStub => CodeKind::AllocStub,
             NativeFctKind::VerifyStub => CodeKind::VerifyStub,
             NativeFctKind::TrapStub => CodeKind::TrapStub,
+            NativeFctKind::ThrowStub => CodeKind::ThrowStub,
             NativeFctKind::GuardCheckStub => CodeKind::GuardCheckStub,
             NativeFctKind::SafepointStub => CodeKind::SafepointStub,
         };
@@ -1,6 +1,8 @@
 use std::mem::size_of;
 use std::sync::Arc;
 
+use parking_lot::Mutex;
+
 use crate::compiler;
 use crate::cpu::{
     CCALL_REG_PARAMS, FREG_PARAMS, REG_FP, REG_PARAMS, REG_RESULT, REG_SP, REG_THREAD, REG_TMP1,
@@ -218,6 +220,26 @@ fn compile_request(ra: usize, receiver1: Address, receiver2: Address) -> Address
             )
         }
 
+        LazyCompilationSite::VirtualCache(
+            receiver_is_first,
+            fct_id,
+            vtable_index,
+            ref type_params,
+            cache_vtable_disp,
+            cache_target_disp,
+        ) => patch_virtual_call_cache(
+            vm,
+            ra,
+            receiver_is_first,
+            receiver1,
+            receiver2,
+            fct_id,
+            vtable_index,
+            type_params,
+            cache_vtable_disp,
+            cache_target_disp,
+        ),
+
         LazyCompilationSite::Lambda(receiver_is_first) => {
             patch_lambda_call(vm, receiver_is_first, receiver1, receiver2)
         }
@@ -273,12 +295,21 @@ fn patch_virtual_call(
     let class_instance = vtable.class_instance();
 
     let fct_ptr = match &class_instance.kind {
-        ShapeKind::TraitObject { object_ty, .. } => compiler::trait_object_thunk::ensure_compiled(
-            vm,
-            trait_fct_id,
-            type_params.clone(),
-            object_ty.clone(),
-        ),
+        ShapeKind::TraitObject {
+            object_ty,
+            combined_type_params,
+            ..
+        } => {
+            // the trait object was created for the same trait type arguments
+            // the call site uses
+            debug_assert_eq!(combined_type_params, &type_params.append(object_ty.clone()));
+            compiler::trait_object_thunk::ensure_compiled(
+                vm,
+                trait_fct_id,
+                type_params.clone(),
+                object_ty.clone(),
+            )
+        }
 
         _ => unreachable!(),
     };
@@ -289,6 +320,66 @@ fn patch_virtual_call(
     fct_ptr
 }
 
+// Serializes updates of inline caches, both slots need to be filled by
+// the same thread.
+static INLINE_CACHE_LOCK: Mutex<()> = Mutex::new(());
+
+#[allow(clippy::too_many_arguments)]
+fn patch_virtual_call_cache(
+    vm: &VM,
+    ra: usize,
+    receiver_is_first: bool,
+    receiver1: Address,
+    receiver2: Address,
+    trait_fct_id: FunctionId,
+    vtable_index: u32,
+    type_params: &BytecodeTypeArray,
+    cache_vtable_disp: i32,
+    cache_target_disp: i32,
+) -> Address {
+    let receiver = if receiver_is_first {
+        receiver1
+    } else {
+        receiver2
+    };
+
+    let obj = unsafe { &mut *receiver.to_mut_ptr::<Obj>() };
+    let vtable = obj.header().vtbl();
+    let entry = vtable.table()[vtable_index as usize];
+
+    let fct_ptr = if entry == vm.stubs.lazy_compilation().to_usize() {
+        patch_virtual_call(
+            vm,
+            receiver_is_first,
+            receiver1,
+            receiver2,
+            trait_fct_id,
+            vtable_index,
+            type_params,
+        )
+    } else {
+        Address::from(entry)
+    };
+
+    let cache_vtable: *mut usize = (ra as isize - cache_vtable_disp as isize) as *mut _;
+    let cache_target: *mut usize = (ra as isize - cache_target_disp as isize) as *mut _;
+
+    let _lock = INLINE_CACHE_LOCK.lock();
+
+    // the target needs to be written before the vtable, the call site only
+    // reads the target after the vtable matched
+    os::jit_writable();
+    unsafe {
+        if *cache_vtable == 0 {
+            *cache_target = fct_ptr.to_usize();
+            *cache_vtable = obj.header().vtblptr().to_usize();
+        }
+    }
+    os::jit_executable();
+
+    fct_ptr
+}
+
 fn patch_direct_call(
     vm: &VM,
     ra: usize,
@@ -0,0 +1,51 @@
+use std::sync::Arc;
+
+use crate::cpu::{CCALL_REG_PARAMS, REG_FP, REG_RESULT, REG_THREAD, REG_TMP1, REG_TMP2};
+use crate::masm::{MacroAssembler, Mem};
+use crate::mode::MachineMode;
+use crate::stack::ExceptionResumeInfo;
+use crate::vm::{install_code_stub, Code, CodeKind, VM};
+
+// This stub continues execution at an exception handler. It gets passed
+// a pointer to an ExceptionResumeInfo, restores frame and stack pointer of the
+// handler's function and jumps to the handler with the thrown object in
+// REG_RESULT. The stub never returns, all frames below the handler's
+// frame (including native ones) are simply dropped.
+
+pub fn generate(vm: &VM) -> Arc<Code> {
+    let mut masm = MacroAssembler::new();
+    let info = CCALL_REG_PARAMS[0];
+
+    masm.load_mem(
+        MachineMode::Ptr,
+        REG_TMP1.into(),
+        Mem::Base(info, ExceptionResumeInfo::pc_offset()),
+    );
+    masm.load_mem(
+        MachineMode::Ptr,
+        REG_TMP2.into(),
+        Mem::Base(info, ExceptionResumeInfo::sp_offset()),
+    );
+    masm.load_mem(
+        MachineMode::Ptr,
+        REG_FP.into(),
+        Mem::Base(info, ExceptionResumeInfo::fp_offset()),
+    );
+    masm.load_mem(
+        MachineMode::Ptr,
+        REG_THREAD.into(),
+        Mem::Base(info, ExceptionResumeInfo::tld_offset()),
+    );
+
+    // REG_RESULT might be the same register as `info`
+    masm.load_mem(
+        MachineMode::Ptr,
+        REG_RESULT.into(),
+        Mem::Base(info, ExceptionResumeInfo::exception_offset()),
+    );
+
+    masm.set_sp(REG_TMP2);
+    masm.jump_reg(REG_TMP1);
+
+    install_code_stub(vm, masm.code(), CodeKind::ResumeStub)
+}
@@ -27,6 +27,7 @@ pub fn ensure_compiled(
         vm,
         trait_fct_id,
         trait_object_ty.clone(),
+        &type_params,
         trait_object_type_param_id,
         actual_ty.clone(),
     );
@@ -44,6 +45,7 @@ fn generate_bytecode_for_thunk(
     vm: &VM,
     fct_id: FunctionId,
     trait_object_ty: BytecodeType,
+    trait_type_params: &BytecodeTypeArray,
     trait_object_type_param_id: usize,
     actual_ty: BytecodeType,
 ) -> BytecodeFunction {
@@ -73,10 +75,18 @@ fn generate_bytecode_for_thunk(
         gen.emit_push_register(Register(idx));
     }
 
+    // The type arguments of the trait are the first type params of the thunk,
+    // pass them on so the right impl for generic traits can be selected.
+    let trait_type_params = BytecodeTypeArray::new(
+        (0..trait_type_params.len())
+            .map(|idx| BytecodeType::TypeParam(idx as u32))
+            .collect(),
+    );
+
     let target_fct_idx = gen.add_const_generic(
         trait_object_type_param_id.try_into().expect("does not fit"),
         fct_id,
-        BytecodeTypeArray::empty(),
+        trait_type_params,
     );
 
     let ty = register_ty(program_trait_fct.return_type.clone());
@@ -100,6 +100,29 @@ impl ConstPool {
         self.add_value(ConstPoolValue::Int32(value))
     }
 
+    // Reserves `entries` consecutive Int32 slots. Returns the displacement of
+    // the first slot, slot `i` is located at displacement `disp - 4 * i`.
+    pub fn add_jump_table(&mut self, entries: usize) -> i32 {
+        assert!(entries > 0);
+        let mut disp = 0;
+
+        for _ in 0..entries {
+            disp = self.add_i32(0);
+        }
+
+        disp
+    }
+
+    pub fn patch_i32(&mut self, disp: i32, value: i32) {
+        let entry = self
+            .entries
+            .iter_mut()
+            .find(|entry| entry.disp == disp)
+            .expect("missing const pool entry");
+        assert!(matches!(entry.value, ConstPoolValue::Int32(_)));
+        entry.value = ConstPoolValue::Int32(value);
+    }
+
     fn add_value(&mut self, value: ConstPoolValue) -> i32 {
         let size = value.size();
         self.size = mem::align_i32(self.size + size, size);
@@ -133,6 +156,19 @@ mod tests {
         assert_eq!(2 * mem::ptr_width(), constpool.add_addr(1.into()));
     }
 
+    #[test]
+    fn test_add_jump_table() {
+        let mut constpool = ConstPool::new();
+        assert_eq!(4, constpool.add_i32(1));
+        let disp = constpool.add_jump_table(3);
+        assert_eq!(16, disp);
+        constpool.patch_i32(disp - 8, 7);
+
+        let mut data = vec![0i32; 4];
+        constpool.install(data.as_mut_ptr() as *const u8);
+        assert_eq!(data, vec![0, 0, 7, 1]);
+    }
+
     #[test]
     fn test_add_addr_reuse() {
         let mut constpool = ConstPool::new();
@@ -164,6 +164,7 @@ pub const FREG_RESULT: FReg = F0;
 
 // shall not overlap with param registers
 pub const FREG_TMP1: FReg = F16;
+pub const FREG_TMP2: FReg = F17;
 
 pub const STACK_FRAME_ALIGNMENT: usize = 16;
 
@@ -8,22 +8,50 @@ pub fn flush_icache(_: *const u8, _: usize) {
     compiler_fence(Ordering::SeqCst);
 }
 
+// Instruction set extensions the JIT is allowed to use. Detected once
+// on first use, the MacroAssembler falls back to plain x86-64 instruction
+// sequences for everything that is missing.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CpuFeatures {
+    pub popcnt: bool,
+    pub lzcnt: bool,
+    pub tzcnt: bool,
+    pub fma: bool,
+}
+
+impl CpuFeatures {
+    fn detect() -> CpuFeatures {
+        CpuFeatures {
+            popcnt: is_x86_feature_detected!("popcnt"),
+            lzcnt: is_x86_feature_detected!("lzcnt"),
+            tzcnt: is_x86_feature_detected!("bmi1"),
+            fma: is_x86_feature_detected!("fma"),
+        }
+    }
+}
+
+pub fn cpu_features() -> CpuFeatures {
+    *FEATURES
+}
+
 pub fn has_popcnt() -> bool {
-    *HAS_POPCNT
+    FEATURES.popcnt
 }
 
 pub fn has_lzcnt() -> bool {
-    *HAS_LZCNT
+    FEATURES.lzcnt
 }
 
 pub fn has_tzcnt() -> bool {
-    *HAS_TZCNT
+    FEATURES.tzcnt
+}
+
+pub fn has_fma() -> bool {
+    FEATURES.fma
 }
 
 lazy_static! {
-    static ref HAS_POPCNT: bool = is_x86_feature_detected!("popcnt");
-    static ref HAS_LZCNT: bool = is_x86_feature_detected!("lzcnt");
-    static ref HAS_TZCNT: bool = is_x86_feature_detected!("bmi1");
+    static ref FEATURES: CpuFeatures = CpuFeatures::detect();
 }
 
 // first param offset to rbp is +16,
@@ -77,6 +105,11 @@ pub const FREG_TMP1: FReg = XMM8; // shall not overlap with argument registers
 #[cfg(target_family = "windows")]
 pub const FREG_TMP1: FReg = XMM4; // shall not overlap with argument registers
 
+#[cfg(target_family = "unix")]
+pub const FREG_TMP2: FReg = XMM9;
+#[cfg(target_family = "windows")]
+pub const FREG_TMP2: FReg = XMM5;
+
 pub const STACK_FRAME_ALIGNMENT: usize = 16;
 
 pub const RAX: Reg = Reg(0);
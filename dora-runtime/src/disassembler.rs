@@ -1,11 +1,20 @@
 #[cfg(feature = "default")]
-pub use self::capstone::{disassemble, supported};
+pub use self::capstone::{decode, disassemble, supported};
 
 #[cfg(not(feature = "default"))]
-pub use self::none::{disassemble, supported};
+pub use self::none::{decode, disassemble, supported};
 
 #[cfg(feature = "default")]
 mod capstone;
 
 #[cfg(not(feature = "default"))]
 mod none;
+
+/// A single decoded machine instruction.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DisasmInsn {
+    pub address: u64,
+    pub size: usize,
+    pub mnemonic: String,
+    pub operands: String,
+}
@@ -6,20 +6,43 @@ use std::slice;
 
 use capstone::prelude::*;
 
-use crate::vm::{display_fct, display_ty, Code, VM};
+use crate::disassembler::DisasmInsn;
+use crate::vm::{display_fct, display_ty, Code, DisasmSyntax, VM};
 use dora_bytecode::{BytecodeTypeArray, FunctionId};
 
 pub fn supported() -> bool {
     true
 }
 
-pub fn disassemble(vm: &VM, fct_id: FunctionId, type_params: &BytecodeTypeArray, code: &Code) {
+pub fn decode(buf: &[u8], start_addr: u64, syntax: DisasmSyntax) -> Vec<DisasmInsn> {
+    let engine = get_engine(syntax).expect("cannot create capstone engine");
+
+    let instrs = engine
+        .disasm_all(buf, start_addr)
+        .expect("could not disassemble code");
+
+    instrs
+        .iter()
+        .map(|instr| DisasmInsn {
+            address: instr.address(),
+            size: instr.bytes().len(),
+            mnemonic: instr.mnemonic().expect("no mnmemonic found").to_string(),
+            operands: instr.op_str().expect("no op_str found").to_string(),
+        })
+        .collect()
+}
+
+pub fn disassemble(
+    vm: &VM,
+    fct_id: FunctionId,
+    type_params: &BytecodeTypeArray,
+    code: &Code,
+    syntax: DisasmSyntax,
+) {
     let instruction_length = code.instruction_end().offset_from(code.instruction_start());
     let buf: &[u8] =
         unsafe { slice::from_raw_parts(code.instruction_start().to_ptr(), instruction_length) };
 
-    let engine = get_engine().expect("cannot create capstone engine");
-
     let mut w: Box<dyn Write> = if vm.args.flag_emit_asm_file {
         let pid = unsafe { libc::getpid() };
         let name = format!("code-{}.asm", pid);
@@ -37,9 +60,7 @@ pub fn disassemble(vm: &VM, fct_id: FunctionId, type_params: &BytecodeTypeArray,
     let start_addr = code.instruction_start().to_usize() as u64;
     let end_addr = code.instruction_end().to_usize() as u64;
 
-    let instrs = engine
-        .disasm_all(buf, start_addr)
-        .expect("could not disassemble code");
+    let instrs = decode(buf, start_addr, syntax);
 
     let name = display_fct(vm, fct_id);
 
@@ -62,8 +83,8 @@ pub fn disassemble(vm: &VM, fct_id: FunctionId, type_params: &BytecodeTypeArray,
     )
     .unwrap();
 
-    for instr in instrs.iter() {
-        let addr = (instr.address() - start_addr) as u32;
+    for instr in &instrs {
+        let addr = (instr.address - start_addr) as u32;
 
         if let Some(gc_point) = code.gcpoint_for_offset(addr) {
             write!(&mut w, "\t\t  ; gc point = (").unwrap();
@@ -92,9 +113,7 @@ pub fn disassemble(vm: &VM, fct_id: FunctionId, type_params: &BytecodeTypeArray,
         writeln!(
             &mut w,
             "  {:#06x}: {}\t\t{}",
-            instr.address(),
-            instr.mnemonic().expect("no mnmemonic found"),
-            instr.op_str().expect("no op_str found"),
+            instr.address, instr.mnemonic, instr.operands,
         )
         .unwrap();
     }
@@ -103,18 +122,76 @@ pub fn disassemble(vm: &VM, fct_id: FunctionId, type_params: &BytecodeTypeArray,
 }
 
 #[cfg(target_arch = "x86_64")]
-fn get_engine() -> CsResult<Capstone> {
+fn get_engine(syntax: DisasmSyntax) -> CsResult<Capstone> {
+    let syntax = match syntax {
+        DisasmSyntax::Intel => arch::x86::ArchSyntax::Intel,
+        DisasmSyntax::ATT => arch::x86::ArchSyntax::Att,
+    };
+
     Capstone::new()
         .x86()
         .mode(arch::x86::ArchMode::Mode64)
-        .syntax(arch::x86::ArchSyntax::Intel)
+        .syntax(syntax)
         .build()
 }
 
+// AArch64 only has a single assembly syntax.
 #[cfg(target_arch = "aarch64")]
-fn get_engine() -> CsResult<Capstone> {
+fn get_engine(_syntax: DisasmSyntax) -> CsResult<Capstone> {
     Capstone::new()
         .arm64()
         .mode(arch::arm64::ArchMode::Arm)
         .build()
 }
+
+#[cfg(all(test, target_arch = "x86_64"))]
+mod tests {
+    use super::*;
+    use crate::masm::MacroAssembler;
+
+    fn render(code: &[u8], syntax: DisasmSyntax) -> Vec<String> {
+        decode(code, 0, syntax)
+            .iter()
+            .map(|instr| format!("{} {}", instr.mnemonic, instr.operands))
+            .collect()
+    }
+
+    #[test]
+    fn test_decode_prolog() {
+        let mut masm = MacroAssembler::new();
+        masm.prolog(16);
+        let code = masm.data();
+
+        let instrs = decode(&code, 0x1000, DisasmSyntax::Intel);
+        assert_eq!(
+            instrs[0],
+            DisasmInsn {
+                address: 0x1000,
+                size: 1,
+                mnemonic: "push".into(),
+                operands: "rbp".into(),
+            }
+        );
+        assert_eq!(instrs[1].address, 0x1001);
+        assert_eq!(
+            instrs.iter().map(|instr| instr.size).sum::<usize>(),
+            code.len()
+        );
+    }
+
+    #[test]
+    fn test_prolog_syntax() {
+        let mut masm = MacroAssembler::new();
+        masm.prolog(16);
+        let code = masm.data();
+
+        assert_eq!(
+            render(&code, DisasmSyntax::Intel),
+            vec!["push rbp", "mov rbp, rsp", "sub rsp, 0x10"]
+        );
+        assert_eq!(
+            render(&code, DisasmSyntax::ATT),
+            vec!["pushq %rbp", "movq %rsp, %rbp", "subq $0x10, %rsp"]
+        );
+    }
+}
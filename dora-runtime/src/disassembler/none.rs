@@ -1,10 +1,21 @@
-use crate::vm::{Code, VM};
+use crate::disassembler::DisasmInsn;
+use crate::vm::{Code, DisasmSyntax, VM};
 use dora_bytecode::{BytecodeTypeArray, FunctionId};
 
 pub fn supported() -> bool {
     false
 }
 
-pub fn disassemble(_vm: &VM, _fct_id: FunctionId, _type_params: &BytecodeTypeArray, _code: &Code) {
+pub fn disassemble(
+    _vm: &VM,
+    _fct_id: FunctionId,
+    _type_params: &BytecodeTypeArray,
+    _code: &Code,
+    _syntax: DisasmSyntax,
+) {
+    unreachable!();
+}
+
+pub fn decode(_buf: &[u8], _start_addr: u64, _syntax: DisasmSyntax) -> Vec<DisasmInsn> {
     unreachable!();
 }
@@ -8,17 +8,19 @@ use std::sync::Arc;
 use crate::gc::code::CodeSpace;
 use crate::gc::compact::MarkCompactCollector;
 use crate::gc::copy::CopyCollector;
+use crate::gc::finalizer::FinalizationQueue;
 use crate::gc::region::RegionCollector;
 use crate::gc::space::{Space, SpaceConfig};
 use crate::gc::sweep::SweepCollector;
-use crate::gc::swiper::{Swiper, CARD_SIZE};
+use crate::gc::swiper::{Swiper, CARD_SIZE, CARD_SIZE_BITS};
 use crate::gc::tlab::TLAB_OBJECT_SIZE;
 use crate::gc::zero::ZeroCollector;
 use crate::mem;
-use crate::object::{Header, Obj};
+use crate::object::{offset_of_array_data, Header, Obj};
 use crate::os;
+use crate::size::InstanceSize;
 use crate::threads::DoraThread;
-use crate::vm::VM;
+use crate::vm::{get_vm, VM};
 use crate::vm::{Args, CollectorName};
 use crate::vtable::VTable;
 
@@ -28,6 +30,7 @@ pub mod bump;
 pub mod code;
 pub mod compact;
 pub mod copy;
+pub mod finalizer;
 pub mod freelist;
 pub mod marking;
 pub mod pmarking;
@@ -59,6 +62,7 @@ pub struct Gc {
     epoch: AtomicUsize,
 
     finalizers: Mutex<Vec<(Address, Arc<DoraThread>)>>,
+    finalization_queue: FinalizationQueue,
 }
 
 impl Gc {
@@ -94,6 +98,7 @@ impl Gc {
             epoch: AtomicUsize::new(0),
 
             finalizers: Mutex::new(Vec::new()),
+            finalization_queue: FinalizationQueue::new(),
         }
     }
 
@@ -102,6 +107,14 @@ impl Gc {
         finalizers.push((object, thread));
     }
 
+    pub fn finalization_queue(&self) -> &FinalizationQueue {
+        &self.finalization_queue
+    }
+
+    pub fn supports_finalizers(&self) -> bool {
+        self.collector.supports_finalizers()
+    }
+
     pub fn needs_write_barrier(&self) -> bool {
         self.collector.needs_write_barrier()
     }
@@ -110,6 +123,14 @@ impl Gc {
         self.collector.card_table_offset()
     }
 
+    pub fn concurrent_marking_flag(&self) -> Option<Address> {
+        self.collector.concurrent_marking_flag()
+    }
+
+    pub fn satb_enqueue(&self, object: Address) {
+        self.collector.satb_enqueue(object);
+    }
+
     pub fn alloc_code(&self, size: usize) -> Address {
         self.code_space.alloc(size)
     }
@@ -176,6 +197,16 @@ impl Gc {
         self.collector.dump_summary(runtime);
     }
 
+    pub fn stats(&self) -> GcStats {
+        self.collector.stats()
+    }
+
+    pub fn set_young_size(&self, vm: &VM, min_young_size: usize, max_young_size: usize) {
+        debug_assert!(vm.state().in_safepoint());
+        self.collector
+            .set_young_size(min_young_size, max_young_size);
+    }
+
     pub fn verify_ref(&self, vm: &VM, reference: Address) {
         if reference.is_null() {
             return;
@@ -184,11 +215,128 @@ impl Gc {
         self.collector.verify_ref(vm, reference);
     }
 
+    pub fn shutdown(&self) {
+        self.collector.shutdown();
+    }
+
     pub fn drop_all_native_code_objects(&mut self) {
         self.code_space.drop_all_native_code_objects();
     }
 }
 
+// called by pre-write barrier with the old value of the updated field
+pub extern "C" fn satb_enqueue(object: Address) {
+    get_vm().gc.collector.satb_enqueue(object);
+}
+
+// stores the element at value into the first length elements of the array,
+// used for element types that might contain references. Takes care of both
+// the pre-write barrier and card marking.
+pub extern "C" fn fill_array(array: Address, length: usize, value: Address) {
+    let vm = get_vm();
+    let vtable = array.to_obj().header().vtbl();
+    let element_size = vtable.element_size;
+    let cls = vtable.class_instance();
+
+    let ref_offsets: &[i32] = match cls.size {
+        InstanceSize::ObjArray => &[0],
+        InstanceSize::StructArray(_) => &cls.ref_fields,
+        _ => &[],
+    };
+
+    let start = array.offset(offset_of_array_data() as usize);
+    let end = start.offset(length * element_size);
+    let pre_write_barrier = vm.gc.concurrent_marking_flag().is_some();
+
+    let mut element = start;
+
+    while element < end {
+        if pre_write_barrier {
+            for &offset in ref_offsets {
+                let slot = Slot::at(element.offset(offset as usize));
+                vm.gc.collector.satb_enqueue(slot.get());
+            }
+        }
+
+        unsafe {
+            std::ptr::copy_nonoverlapping(
+                value.to_ptr::<u8>(),
+                element.to_mut_ptr::<u8>(),
+                element_size,
+            );
+        }
+
+        element = element.offset(element_size);
+    }
+
+    if vm.gc.needs_write_barrier() && !ref_offsets.is_empty() && start < end {
+        let card_table_offset = vm.gc.card_table_offset();
+        let first_card = start.to_usize() >> CARD_SIZE_BITS;
+        let last_card = (end.to_usize() - 1) >> CARD_SIZE_BITS;
+
+        for card in first_card..=last_card {
+            unsafe {
+                *((card_table_offset + card) as *mut u8) = 0;
+            }
+        }
+    }
+}
+
+// copies length elements from src to dest inside array, both addresses
+// point to elements of arrays with the same element type and the ranges
+// are allowed to overlap. Takes care of both the pre-write barrier and
+// card marking for the overwritten elements.
+pub extern "C" fn copy_array(array: Address, dest: Address, src: Address, length: usize) {
+    let vm = get_vm();
+    let vtable = array.to_obj().header().vtbl();
+    let element_size = vtable.element_size;
+    let cls = vtable.class_instance();
+
+    let ref_offsets: &[i32] = match cls.size {
+        InstanceSize::ObjArray => &[0],
+        InstanceSize::StructArray(_) => &cls.ref_fields,
+        _ => &[],
+    };
+
+    let size = length * element_size;
+    let end = dest.offset(size);
+
+    if !ref_offsets.is_empty() && vm.gc.concurrent_marking_flag().is_some() {
+        let mut element = dest;
+
+        while element < end {
+            for &offset in ref_offsets {
+                let slot = Slot::at(element.offset(offset as usize));
+                vm.gc.collector.satb_enqueue(slot.get());
+            }
+
+            element = element.offset(element_size);
+        }
+    }
+
+    // memmove semantics: copies backwards if dest overlaps the end of src
+    unsafe {
+        std::ptr::copy(src.to_ptr::<u8>(), dest.to_mut_ptr::<u8>(), size);
+    }
+
+    if vm.gc.needs_write_barrier() && !ref_offsets.is_empty() && dest < end {
+        let card_table_offset = vm.gc.card_table_offset();
+        let first_card = dest.to_usize() >> CARD_SIZE_BITS;
+        let last_card = (end.to_usize() - 1) >> CARD_SIZE_BITS;
+
+        for card in first_card..=last_card {
+            unsafe {
+                *((card_table_offset + card) as *mut u8) = 0;
+            }
+        }
+    }
+}
+
+// called right after allocating an object whose class implements Finalizable
+pub extern "C" fn register_finalizable(object: Address) {
+    get_vm().gc.finalization_queue.register(object);
+}
+
 trait Collector {
     // allocate object of given size
     fn alloc_tlab_area(&self, vm: &VM, size: usize) -> Option<Region>;
@@ -215,9 +363,42 @@ trait Collector {
         0
     }
 
+    // address of byte that is non-zero while marking runs concurrently,
+    // pre-write barriers are only needed if this returns an address
+    fn concurrent_marking_flag(&self) -> Option<Address> {
+        None
+    }
+
+    // stops all GC work running in the background
+    fn shutdown(&self) {
+        // do nothing
+    }
+
+    // remembers reference overwritten during concurrent marking
+    fn satb_enqueue(&self, _object: Address) {
+        // do nothing
+    }
+
     // prints GC summary: minor/full collections, etc.
     fn dump_summary(&self, _runtime: f32);
 
+    // gives true when collector keeps unreachable registered objects
+    // alive and queues them for finalization.
+    fn supports_finalizers(&self) -> bool {
+        false
+    }
+
+    // returns statistics accumulated since the VM was started
+    fn stats(&self) -> GcStats {
+        GcStats::default()
+    }
+
+    // sets bounds for the size of the young generation, only supported
+    // by generational collectors. Needs to be called at a safepoint.
+    fn set_young_size(&self, _min_young_size: usize, _max_young_size: usize) {
+        // do nothing
+    }
+
     // verify reference
     fn verify_ref(&self, _vm: &VM, _addr: Address) {
         // do nothing
@@ -607,6 +788,18 @@ pub fn fill_region_with_free(vm: &VM, start: Address, end: Address, next: Addres
     }
 }
 
+// GC statistics that can be queried while the program is running,
+// pause times are in milliseconds.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct GcStats {
+    pub minor_collections: usize,
+    pub major_collections: usize,
+    pub total_pause: f32,
+    pub max_pause: f32,
+    pub bytes_allocated: usize,
+    pub bytes_live_after_major: usize,
+}
+
 struct CollectionStats {
     collections: usize,
     total_pause: f32,
@@ -0,0 +1,244 @@
+use parking_lot::{Condvar, Mutex};
+use std::mem;
+use std::thread;
+
+use crate::compiler;
+use crate::gc::root::Slot;
+use crate::gc::{marking, Address, Region};
+use crate::handle::{create_handle, handle_scope, Handle};
+use crate::object::{Obj, Ref};
+use crate::stack::DoraToNativeInfo;
+use crate::threads::{
+    deinit_current_thread, init_current_thread, parked_scope, DoraThread, ManagedThread,
+    ThreadState, STACK_SIZE,
+};
+use crate::vm::{
+    find_trait_impl_with_type_params, get_vm, stack_pointer, ty_implements_trait, BytecodeTypeExt,
+    ShapeKind, VM,
+};
+use dora_bytecode::{BytecodeType, BytecodeTypeArray, ClassId, TypeParamData};
+
+// Objects of classes implementing the Finalizable trait are registered right
+// after allocation. A full collection keeps registered objects that became
+// unreachable alive and moves them into the pending list. The finalizer
+// thread removes objects from the pending list and runs their finalizer.
+pub struct FinalizationQueue {
+    state: Mutex<FinalizationState>,
+    cv_pending: Condvar,
+}
+
+struct FinalizationState {
+    registered: Vec<Address>,
+    pending: Vec<Address>,
+    shutdown: bool,
+}
+
+impl FinalizationQueue {
+    pub fn new() -> FinalizationQueue {
+        FinalizationQueue {
+            state: Mutex::new(FinalizationState {
+                registered: Vec::new(),
+                pending: Vec::new(),
+                shutdown: false,
+            }),
+            cv_pending: Condvar::new(),
+        }
+    }
+
+    pub fn register(&self, object: Address) {
+        self.state.lock().registered.push(object);
+    }
+
+    // slots of all registered and pending objects, a minor collection
+    // treats them as strong roots. Slots are only valid until the next
+    // modification of the queue.
+    pub fn root_slots(&self) -> Vec<Slot> {
+        let state = self.state.lock();
+
+        state
+            .registered
+            .iter()
+            .chain(state.pending.iter())
+            .map(|object| Slot::at(Address::from_ptr(object)))
+            .collect()
+    }
+
+    // runs after a full collection marked all reachable objects:
+    // marks everything reachable from pending objects and moves
+    // unreachable registered objects into the pending list.
+    pub fn mark_unreachable(&self, heap: Region, perm: Region) {
+        let mut state = self.state.lock();
+
+        marking::start(&slots(&state.pending), heap, perm);
+
+        let (registered, unreachable): (Vec<Address>, Vec<Address>) = state
+            .registered
+            .iter()
+            .partition(|object| object.to_obj().header().is_marked_non_atomic());
+
+        if unreachable.is_empty() {
+            return;
+        }
+
+        // unreachable objects are resurrected until their finalizer ran
+        marking::start(&slots(&unreachable), heap, perm);
+
+        state.registered = registered;
+        state.pending.extend(unreachable);
+        self.cv_pending.notify_one();
+    }
+
+    pub fn update_references<F>(&self, object_updater: F)
+    where
+        F: Fn(Address) -> Address,
+    {
+        let mut state = self.state.lock();
+        let state = &mut *state;
+
+        for object in state.registered.iter_mut().chain(state.pending.iter_mut()) {
+            *object = object_updater(*object);
+        }
+    }
+
+    pub fn shutdown(&self) {
+        let mut state = self.state.lock();
+        state.shutdown = true;
+        self.cv_pending.notify_all();
+    }
+
+    // waits until there are objects to finalize, returns false
+    // when the finalizer thread should terminate.
+    fn wait_for_pending(&self) -> bool {
+        let mut state = self.state.lock();
+
+        while state.pending.is_empty() && !state.shutdown {
+            self.cv_pending.wait(&mut state);
+        }
+
+        !state.shutdown
+    }
+
+    fn pop(&self) -> Option<Address> {
+        self.state.lock().pending.pop()
+    }
+}
+
+fn slots(objects: &[Address]) -> Vec<Slot> {
+    objects
+        .iter()
+        .map(|object| Slot::at(Address::from_ptr(object)))
+        .collect()
+}
+
+pub fn is_finalizable(vm: &VM, cls_id: ClassId, type_params: &BytecodeTypeArray) -> bool {
+    let trait_id = match vm.known.finalizable_trait_id {
+        Some(trait_id) => trait_id,
+        None => return false,
+    };
+
+    let type_param_data = TypeParamData {
+        names: Vec::new(),
+        bounds: Vec::new(),
+    };
+
+    ty_implements_trait(
+        vm,
+        BytecodeType::Class(cls_id, type_params.clone()),
+        &type_param_data,
+        BytecodeType::Trait(trait_id, BytecodeTypeArray::empty()),
+    )
+}
+
+pub fn needs_finalizer_thread(vm: &VM) -> bool {
+    if !vm.gc.supports_finalizers() {
+        return false;
+    }
+
+    let trait_id = match vm.known.finalizable_trait_id {
+        Some(trait_id) => trait_id,
+        None => return false,
+    };
+
+    vm.program
+        .impls
+        .iter()
+        .any(|impl_| impl_.trait_ty.trait_id() == Some(trait_id))
+}
+
+// needs to be called from a running thread
+pub fn start_finalizer_thread(vm: &VM) {
+    let managed_thread = ManagedThread::alloc(vm);
+    let mut managed_thread: Handle<ManagedThread> = create_handle(managed_thread);
+
+    let thread = DoraThread::new_daemon(vm, ThreadState::Parked);
+    managed_thread.install_native_thread(&thread);
+
+    vm.threads.add_thread(thread.clone());
+
+    // see spawn_thread: the new thread is still parked and the current
+    // thread is running, so no GC can happen while creating the handle.
+    let thread_location = thread
+        .handles
+        .create_handle(managed_thread.direct())
+        .location();
+
+    thread::spawn(move || {
+        let thread = init_current_thread(thread);
+        finalizer_main(thread, thread_location);
+        deinit_current_thread();
+    });
+}
+
+fn finalizer_main(thread: &DoraThread, thread_location: Address) {
+    let vm = get_vm();
+    let queue = vm.gc.finalization_queue();
+
+    thread.tld.set_managed_thread_handle(thread_location);
+
+    let stack_top = stack_pointer();
+    let stack_limit = stack_top.sub(STACK_SIZE);
+    thread.tld.set_stack_limit(stack_limit);
+
+    thread.unpark(vm);
+
+    // The object needs to be removed from the queue while the thread
+    // is running, otherwise the GC could move it in the meantime.
+    while parked_scope(|| queue.wait_for_pending()) {
+        handle_scope(|| {
+            if let Some(object) = queue.pop() {
+                let object: Handle<Obj> = create_handle(object.into());
+                run_finalizer(vm, thread, object);
+            }
+        });
+    }
+
+    vm.threads.remove_current_thread();
+    thread.stop();
+}
+
+fn run_finalizer(vm: &VM, thread: &DoraThread, object: Handle<Obj>) {
+    let class_instance = object.header().vtbl().class_instance();
+
+    let object_ty = match &class_instance.kind {
+        ShapeKind::Class(cls_id, type_params) => BytecodeType::Class(*cls_id, type_params.clone()),
+        _ => unreachable!(),
+    };
+
+    let trait_ty = BytecodeType::Trait(vm.known.finalizable_trait_id(), BytecodeTypeArray::empty());
+    let (fct_id, type_params) =
+        find_trait_impl_with_type_params(vm, vm.known.finalize_fct_id(), trait_ty, object_ty);
+
+    let fct_ptr = {
+        let mut dtn = DoraToNativeInfo::new();
+
+        thread.use_dtn(&mut dtn, || {
+            compiler::generate_fct(vm, fct_id, &type_params)
+        })
+    };
+
+    let tld = thread.tld_address();
+    let dora_stub_address = vm.stubs.dora_entry();
+    let fct: extern "C" fn(Address, Address, Ref<Obj>) =
+        unsafe { mem::transmute(dora_stub_address) };
+    fct(tld, fct_ptr, object.direct());
+}
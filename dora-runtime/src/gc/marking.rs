@@ -3,7 +3,19 @@ use crate::gc::{Address, Region};
 
 pub fn start(rootset: &[Slot], heap: Region, perm: Region) {
     let mut marking_stack: Vec<Address> = Vec::new();
+    mark_roots(&mut marking_stack, rootset, heap, perm);
+    trace(marking_stack, heap, perm);
+}
+
+// finishes marking that was started before (e.g. concurrently): all objects
+// on the marking stack are already marked but their fields still need to be
+// traced. Roots are scanned again since they could have changed in the meantime.
+pub fn finish(mut marking_stack: Vec<Address>, rootset: &[Slot], heap: Region, perm: Region) {
+    mark_roots(&mut marking_stack, rootset, heap, perm);
+    trace(marking_stack, heap, perm);
+}
 
+pub fn mark_roots(marking_stack: &mut Vec<Address>, rootset: &[Slot], heap: Region, perm: Region) {
     for root in rootset {
         let root_ptr = root.get();
 
@@ -18,24 +30,44 @@ pub fn start(rootset: &[Slot], heap: Region, perm: Region) {
             debug_assert!(root_ptr.is_null() || perm.contains(root_ptr));
         }
     }
+}
+
+fn trace(mut marking_stack: Vec<Address>, heap: Region, perm: Region) {
+    let mut weak_slots = Vec::new();
 
     while marking_stack.len() > 0 {
         let object_addr = marking_stack.pop().expect("stack already empty");
         let object = object_addr.to_mut_obj();
 
-        object.visit_reference_fields(|field| {
-            let field_addr = field.get();
+        object.visit_reference_fields_with_weak(
+            |field| {
+                let field_addr = field.get();
 
-            if heap.contains(field_addr) {
-                let field_obj = field_addr.to_mut_obj();
+                if heap.contains(field_addr) {
+                    let field_obj = field_addr.to_mut_obj();
 
-                if !field_obj.header().is_marked_non_atomic() {
-                    marking_stack.push(field_addr);
-                    field_obj.header_mut().mark_non_atomic();
+                    if !field_obj.header().is_marked_non_atomic() {
+                        marking_stack.push(field_addr);
+                        field_obj.header_mut().mark_non_atomic();
+                    }
+                } else {
+                    debug_assert!(field_addr.is_null() || perm.contains(field_addr));
                 }
-            } else {
-                debug_assert!(field_addr.is_null() || perm.contains(field_addr));
-            }
-        });
+            },
+            |weak_slot| weak_slots.push(weak_slot),
+        );
+    }
+
+    clear_weak_refs(&weak_slots, heap);
+}
+
+// clears all weak references whose referent wasn't marked
+pub fn clear_weak_refs(weak_slots: &[Slot], heap: Region) {
+    for &slot in weak_slots {
+        let referent = slot.get();
+
+        if heap.contains(referent) && !referent.to_obj().header().is_marked_non_atomic() {
+            slot.set(Address::null());
+        }
     }
 }
@@ -1,3 +1,4 @@
+use parking_lot::Mutex;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::thread;
 use std::time::Duration;
@@ -7,6 +8,7 @@ use rand::distributions::{Distribution, Uniform};
 use rand::thread_rng;
 use scoped_threadpool::Pool;
 
+use crate::gc::marking::clear_weak_refs;
 use crate::gc::root::Slot;
 use crate::gc::{Address, Region};
 
@@ -39,6 +41,7 @@ pub fn start(rootset: &[Slot], heap: Region, perm: Region, threadpool: &mut Pool
     }
 
     let terminator = Terminator::new(number_workers);
+    let weak_slots = Mutex::new(Vec::new());
 
     threadpool.scoped(|scoped| {
         for (task_id, worker) in workers.into_iter().enumerate() {
@@ -48,6 +51,7 @@ pub fn start(rootset: &[Slot], heap: Region, perm: Region, threadpool: &mut Pool
             let injector = &injector;
             let stealers = &stealers;
             let terminator = &terminator;
+            let weak_slots = &weak_slots;
 
             scoped.execute(move || {
                 let mut task = MarkingTask {
@@ -60,12 +64,15 @@ pub fn start(rootset: &[Slot], heap: Region, perm: Region, threadpool: &mut Pool
                     heap_region,
                     perm_region,
                     marked: 0,
+                    weak_slots,
                 };
 
                 task.run();
             });
         }
     });
+
+    clear_weak_refs(&weak_slots.into_inner(), heap);
 }
 
 pub struct Terminator {
@@ -139,6 +146,7 @@ struct MarkingTask<'a> {
     heap_region: Region,
     perm_region: Region,
     marked: usize,
+    weak_slots: &'a Mutex<Vec<Slot>>,
 }
 
 impl<'a> MarkingTask<'a> {
@@ -206,6 +214,8 @@ impl<'a> MarkingTask<'a> {
     }
 
     fn run(&mut self) {
+        let mut weak_slots = Vec::new();
+
         loop {
             let object_addr = if let Some(object_addr) = self.pop() {
                 object_addr
@@ -217,10 +227,15 @@ impl<'a> MarkingTask<'a> {
 
             let object = object_addr.to_mut_obj();
 
-            object.visit_reference_fields(|field| {
-                self.trace(field);
-            });
+            object.visit_reference_fields_with_weak(
+                |field| {
+                    self.trace(field);
+                },
+                |weak_slot| weak_slots.push(weak_slot),
+            );
         }
+
+        self.weak_slots.lock().extend(weak_slots);
     }
 
     fn trace(&mut self, slot: Slot) {
@@ -136,7 +136,10 @@ fn iterate_roots_from_stack_frame<F: FnMut(Slot)>(
             CodeKind::SafepointStub => true,
             CodeKind::CompileStub => true,
 
-            CodeKind::VerifyStub | CodeKind::TrapStub => unreachable!(),
+            CodeKind::VerifyStub
+            | CodeKind::TrapStub
+            | CodeKind::ThrowStub
+            | CodeKind::ResumeStub => unreachable!(),
         }
     } else {
         println!("no code found at pc = {:x}", pc);
@@ -7,6 +7,7 @@ use std::sync::Arc;
 use crate::gc::root::{determine_strong_roots, Slot};
 use crate::gc::swiper::card::CardTable;
 use crate::gc::swiper::compact::FullCollector;
+use crate::gc::swiper::concurrent::ConcurrentMarking;
 use crate::gc::swiper::controller::{HeapConfig, SharedHeapConfig};
 use crate::gc::swiper::crossing::CrossingMap;
 use crate::gc::swiper::large::LargeSpace;
@@ -14,12 +15,12 @@ use crate::gc::swiper::minor::MinorCollector;
 use crate::gc::swiper::old::OldGen;
 use crate::gc::swiper::pcompact::ParallelFullCollector;
 use crate::gc::swiper::pminor::ParallelMinorCollector;
-use crate::gc::swiper::verify::{Verifier, VerifierPhase};
+use crate::gc::swiper::verify::{verify_card_table, Verifier, VerifierPhase};
 use crate::gc::swiper::young::YoungGen;
 use crate::gc::tlab;
 use crate::gc::Collector;
 use crate::gc::{align_gen, fill_region, formatted_size, Address, Region, K};
-use crate::gc::{GcReason, GEN_SIZE};
+use crate::gc::{GcReason, GcStats, GEN_SIZE};
 use crate::mem;
 use crate::object::Obj;
 use crate::os::{self, MemoryPermission, Reservation};
@@ -29,6 +30,7 @@ use crate::vm::{Args, VM};
 
 pub mod card;
 mod compact;
+mod concurrent;
 mod controller;
 mod crossing;
 mod large;
@@ -50,6 +52,8 @@ pub const CARD_SIZE: usize = 512;
 pub const CARD_SIZE_BITS: usize = 9;
 pub const CARD_REFS: usize = CARD_SIZE / size_of::<usize>();
 
+// objects of at least this size are allocated in the large object space,
+// can be changed with --gc-large-object-size
 pub const LARGE_OBJECT_SIZE: usize = 16 * K;
 
 pub struct Swiper {
@@ -69,6 +73,9 @@ pub struct Swiper {
     card_table_offset: usize,
     emit_write_barrier: bool,
 
+    // objects of at least this size are allocated in large space
+    large_object_size: usize,
+
     // minimum & maximum heap size
     min_heap_size: usize,
     max_heap_size: usize,
@@ -76,6 +83,9 @@ pub struct Swiper {
     threadpool: Option<Mutex<Pool>>,
     config: SharedHeapConfig,
 
+    // only set when old generation is marked concurrently
+    concurrent_marking: Option<ConcurrentMarking>,
+
     reservation: Reservation,
 }
 
@@ -182,6 +192,12 @@ impl Swiper {
             None
         };
 
+        let concurrent_marking = if args.flag_gc_concurrent_marking {
+            Some(ConcurrentMarking::new())
+        } else {
+            None
+        };
+
         Swiper {
             heap: Region::new(heap_start, heap_end),
             reserved_area,
@@ -197,11 +213,13 @@ impl Swiper {
 
             card_table_offset,
             emit_write_barrier,
+            large_object_size: args.large_object_size(),
 
             min_heap_size,
             max_heap_size,
 
             threadpool,
+            concurrent_marking,
         }
     }
 
@@ -222,25 +240,41 @@ impl Swiper {
             tlab::make_iterable_all(vm, threads);
             let rootset = determine_strong_roots(vm, threads);
 
+            // an active concurrent marking cycle is always finished by the next pause
+            let remark_stack = self.finish_concurrent_marking(vm);
+
             let kind = match kind {
-                CollectionKind::Minor => {
-                    let promotion_failed = self.minor_collect(vm, reason, &rootset, threads);
+                CollectionKind::Minor if remark_stack.is_none() => {
+                    // objects registered for finalization are only
+                    // considered unreachable by full collections
+                    let mut minor_rootset = rootset.clone();
+                    minor_rootset.extend(vm.gc.finalization_queue().root_slots());
+
+                    let promotion_failed = self.minor_collect(vm, reason, &minor_rootset, threads);
 
                     if promotion_failed {
                         reason = GcReason::PromotionFailure;
-                        self.full_collect(vm, reason, threads, &rootset);
+                        self.full_collect(vm, reason, threads, &rootset, None);
                         CollectionKind::Full
                     } else {
                         CollectionKind::Minor
                     }
                 }
 
-                CollectionKind::Full => {
-                    self.full_collect(vm, reason, threads, &rootset);
+                CollectionKind::Minor | CollectionKind::Full => {
+                    self.full_collect(vm, reason, threads, &rootset, remark_stack);
                     CollectionKind::Full
                 }
             };
 
+            if vm.args.flag_gc_verify_heap {
+                if vm.args.flag_gc_dev_verbose {
+                    println!("GC: Verify card table");
+                }
+
+                verify_card_table(&self.young, &self.old, &self.large, &self.card_table);
+            }
+
             controller::stop(
                 &self.config,
                 kind,
@@ -251,10 +285,43 @@ impl Swiper {
                 reason,
             );
 
+            if kind.is_minor() {
+                self.start_concurrent_marking(vm, &rootset);
+            }
+
             kind
         })
     }
 
+    fn start_concurrent_marking(&self, vm: &VM, rootset: &[Slot]) {
+        if let Some(ref concurrent_marking) = self.concurrent_marking {
+            if controller::should_start_concurrent_marking(&self.config) {
+                if vm.args.flag_gc_dev_verbose {
+                    println!("GC: Start concurrent marking");
+                }
+
+                concurrent_marking.start(
+                    rootset,
+                    self.heap,
+                    vm.gc.readonly_space.total(),
+                    &self.young,
+                    &self.large,
+                );
+            }
+        }
+    }
+
+    fn finish_concurrent_marking(&self, vm: &VM) -> Option<Vec<Address>> {
+        let concurrent_marking = self.concurrent_marking.as_ref()?;
+        let remark_stack = concurrent_marking.finish(vm, self.heap, &self.young, &self.large)?;
+
+        if vm.args.flag_gc_dev_verbose {
+            println!("GC: Finish concurrent marking");
+        }
+
+        Some(remark_stack)
+    }
+
     fn minor_collect(
         &self,
         vm: &VM,
@@ -352,6 +419,7 @@ impl Swiper {
         reason: GcReason,
         threads: &[Arc<DoraThread>],
         rootset: &[Slot],
+        remark_stack: Option<Vec<Address>>,
     ) {
         self.verify(
             vm,
@@ -363,7 +431,8 @@ impl Swiper {
             Vec::new(),
         );
 
-        if vm.args.parallel_full() {
+        // marking that was started concurrently is only finished by the serial collector
+        if vm.args.parallel_full() && remark_stack.is_none() {
             let pool = self.threadpool.as_ref().unwrap();
             let mut pool = pool.lock();
             let mut collector = ParallelFullCollector::new(
@@ -404,7 +473,7 @@ impl Swiper {
                 self.min_heap_size,
                 self.max_heap_size,
             );
-            collector.collect();
+            collector.collect(remark_stack);
 
             if vm.args.flag_gc_stats {
                 let mut config = self.config.lock();
@@ -517,7 +586,9 @@ impl Collector for Swiper {
     }
 
     fn alloc(&self, vm: &VM, size: usize, array_ref: bool) -> Address {
-        if size < LARGE_OBJECT_SIZE {
+        // objects that don't fit into eden go into large space as well,
+        // large_object_size might exceed the young generation.
+        if size < self.large_object_size && size <= self.young.eden_committed().size() {
             self.alloc_normal(vm, size, array_ref)
         } else {
             self.alloc_large(vm, size, array_ref)
@@ -540,6 +611,38 @@ impl Collector for Swiper {
         self.card_table_offset
     }
 
+    fn concurrent_marking_flag(&self) -> Option<Address> {
+        self.concurrent_marking
+            .as_ref()
+            .map(|concurrent_marking| concurrent_marking.flag_address())
+    }
+
+    fn shutdown(&self) {
+        if let Some(ref concurrent_marking) = self.concurrent_marking {
+            concurrent_marking.shutdown();
+        }
+    }
+
+    fn satb_enqueue(&self, object: Address) {
+        if let Some(ref concurrent_marking) = self.concurrent_marking {
+            if concurrent_marking.is_active() {
+                concurrent_marking.enqueue(object);
+            }
+        }
+    }
+
+    fn set_young_size(&self, min_young_size: usize, max_young_size: usize) {
+        controller::set_young_size(&self.config, min_young_size, max_young_size);
+    }
+
+    fn supports_finalizers(&self) -> bool {
+        true
+    }
+
+    fn stats(&self) -> GcStats {
+        controller::stats(&self.config, &self.young, &self.old, &self.large)
+    }
+
     fn dump_summary(&self, runtime: f32) {
         let config = self.config.lock();
         let total_gc = config.total_minor_pause + config.total_full_pause;
@@ -94,7 +94,8 @@ impl<'a> FullCollector<'a> {
         self.phases.clone()
     }
 
-    pub fn collect(&mut self) {
+    // remark_stack contains already marked objects left by concurrent marking
+    pub fn collect(&mut self, remark_stack: Option<Vec<Address>>) {
         let dev_verbose = self.vm.args.flag_gc_dev_verbose;
         let stats = self.vm.args.flag_gc_stats;
         self.init_old_top = self.old_protected.regions.iter().map(|r| r.top()).collect();
@@ -105,7 +106,7 @@ impl<'a> FullCollector<'a> {
             println!("Full GC: Start");
         }
 
-        self.mark_live();
+        self.mark_live(remark_stack);
 
         if stats {
             let duration = timer.stop();
@@ -183,8 +184,22 @@ impl<'a> FullCollector<'a> {
         self.old_protected.update_single_region(self.old_top);
     }
 
-    fn mark_live(&mut self) {
-        marking::start(self.rootset, self.heap, self.readonly_space.total());
+    fn mark_live(&mut self, remark_stack: Option<Vec<Address>>) {
+        if let Some(remark_stack) = remark_stack {
+            marking::finish(
+                remark_stack,
+                self.rootset,
+                self.heap,
+                self.readonly_space.total(),
+            );
+        } else {
+            marking::start(self.rootset, self.heap, self.readonly_space.total());
+        }
+
+        self.vm
+            .gc
+            .finalization_queue()
+            .mark_unreachable(self.heap, self.readonly_space.total());
     }
 
     fn compute_forward(&mut self) {
@@ -237,6 +252,19 @@ impl<'a> FullCollector<'a> {
             )
         });
 
+        self.vm
+            .gc
+            .finalization_queue()
+            .update_references(|current_address| {
+                forward_full(
+                    current_address,
+                    self.heap,
+                    self.readonly_space.total(),
+                    self.large_space.total(),
+                )
+                .expect("finalizable object not marked")
+            });
+
         self.large_space.remove_objects(|object_start| {
             let object = object_start.to_mut_obj();
 
@@ -0,0 +1,265 @@
+use parking_lot::Mutex;
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+use crate::gc::root::Slot;
+use crate::gc::swiper::large::LargeSpace;
+use crate::gc::swiper::walk_region;
+use crate::gc::swiper::young::YoungGen;
+use crate::gc::{marking, Address, Region};
+use crate::vm::VM;
+use crate::vtable::VTable;
+
+// number of objects traced by the marker thread before it checks
+// whether it was asked to stop.
+const STOP_CHECK_INTERVAL: usize = 256;
+
+// Concurrent marking of the heap using a snapshot-at-the-beginning (SATB)
+// barrier. A cycle is started at the end of a minor collection: roots are
+// marked during the pause and a background thread traces the object graph
+// while the mutator runs. All objects allocated during the cycle (in eden,
+// to-space after eden is exhausted or large space) are considered live.
+// Mutators record every reference they overwrite in the SATB queue while
+// the cycle is active. The next pause finishes marking (remark) and then
+// compacts the heap.
+pub struct ConcurrentMarking {
+    // non-zero while a cycle is active, checked by the pre-write barrier
+    active: Box<AtomicBool>,
+    satb_queue: Arc<Mutex<Vec<Address>>>,
+    cycle: Mutex<Option<MarkingCycle>>,
+}
+
+struct MarkingCycle {
+    stop: Arc<AtomicBool>,
+    marker: JoinHandle<Vec<Address>>,
+
+    // top of to-space when the cycle was started
+    semi_top: Address,
+
+    // large objects that existed when the cycle was started
+    large_objects: Arc<HashSet<Address>>,
+}
+
+impl ConcurrentMarking {
+    pub fn new() -> ConcurrentMarking {
+        ConcurrentMarking {
+            active: Box::new(AtomicBool::new(false)),
+            satb_queue: Arc::new(Mutex::new(Vec::new())),
+            cycle: Mutex::new(None),
+        }
+    }
+
+    pub fn flag_address(&self) -> Address {
+        Address::from_ptr(&*self.active)
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active.load(Ordering::Relaxed)
+    }
+
+    pub fn enqueue(&self, object: Address) {
+        if object.is_non_null() {
+            self.satb_queue.lock().push(object);
+        }
+    }
+
+    // marks roots and starts tracing in the background, needs to run
+    // in a pause right after a minor collection (eden is empty).
+    pub fn start(
+        &self,
+        rootset: &[Slot],
+        heap: Region,
+        perm: Region,
+        young: &YoungGen,
+        large: &LargeSpace,
+    ) {
+        let mut cycle = self.cycle.lock();
+        assert!(cycle.is_none());
+        assert!(young.eden_active().empty());
+
+        let mut marking_stack = Vec::new();
+        marking::mark_roots(&mut marking_stack, rootset, heap, perm);
+
+        let mut large_objects = HashSet::new();
+        large.visit_objects(|object| {
+            large_objects.insert(object);
+        });
+        let large_objects = Arc::new(large_objects);
+
+        let semi_top = young.to_active().end;
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let marker = {
+            let stop = stop.clone();
+            let satb_queue = self.satb_queue.clone();
+            let large_objects = large_objects.clone();
+            let snapshot = Snapshot {
+                heap,
+                eden: young.eden_total(),
+                new_semi: Region::new(semi_top, young.to_total().end),
+                large: large.total(),
+                large_objects,
+            };
+
+            thread::spawn(move || mark_concurrently(marking_stack, snapshot, stop, satb_queue))
+        };
+
+        self.active.store(true, Ordering::SeqCst);
+
+        *cycle = Some(MarkingCycle {
+            stop,
+            marker,
+            semi_top,
+            large_objects,
+        });
+    }
+
+    // stops the marker thread and returns all objects that still need to
+    // be traced by the remark. Those objects are already marked. Returns None
+    // if no cycle is active. Needs to run in a pause after TLABs were made iterable.
+    pub fn finish(
+        &self,
+        vm: &VM,
+        heap: Region,
+        young: &YoungGen,
+        large: &LargeSpace,
+    ) -> Option<Vec<Address>> {
+        let cycle = self.stop_marker()?;
+        let mut marking_stack = cycle.marker.join().expect("marker thread panicked");
+
+        for object in self.satb_queue.lock().drain(..) {
+            mark(&mut marking_stack, object, heap);
+        }
+
+        // objects allocated during the cycle are live
+        let fillers = filler_vtables(vm);
+
+        let new_semi = Region::new(cycle.semi_top, young.to_active().end);
+
+        for region in [young.eden_active(), new_semi] {
+            walk_region(region, |object, address, _size| {
+                if !fillers.contains(&object.header().vtblptr()) {
+                    mark(&mut marking_stack, address, heap);
+                }
+            });
+        }
+
+        large.visit_objects(|object| {
+            if !cycle.large_objects.contains(&object) {
+                mark(&mut marking_stack, object, heap);
+            }
+        });
+
+        Some(marking_stack)
+    }
+
+    // aborts the active cycle, the marker thread must not
+    // access the heap anymore once the VM is destroyed.
+    pub fn shutdown(&self) {
+        if let Some(cycle) = self.stop_marker() {
+            let _ = cycle.marker.join();
+        }
+    }
+
+    fn stop_marker(&self) -> Option<MarkingCycle> {
+        let cycle = self.cycle.lock().take()?;
+        self.active.store(false, Ordering::SeqCst);
+        cycle.stop.store(true, Ordering::SeqCst);
+        Some(cycle)
+    }
+}
+
+impl Drop for ConcurrentMarking {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+struct Snapshot {
+    heap: Region,
+    eden: Region,
+    new_semi: Region,
+    large: Region,
+    large_objects: Arc<HashSet<Address>>,
+}
+
+impl Snapshot {
+    fn contains(&self, object: Address) -> bool {
+        if !self.heap.contains(object)
+            || self.eden.contains(object)
+            || self.new_semi.contains(object)
+        {
+            false
+        } else if self.large.contains(object) {
+            self.large_objects.contains(&object)
+        } else {
+            true
+        }
+    }
+}
+
+fn mark_concurrently(
+    mut marking_stack: Vec<Address>,
+    snapshot: Snapshot,
+    stop: Arc<AtomicBool>,
+    satb_queue: Arc<Mutex<Vec<Address>>>,
+) -> Vec<Address> {
+    let mut traced = 0;
+
+    loop {
+        while let Some(object_addr) = marking_stack.pop() {
+            traced += 1;
+
+            if traced % STOP_CHECK_INTERVAL == 0 && stop.load(Ordering::Relaxed) {
+                marking_stack.push(object_addr);
+                return marking_stack;
+            }
+
+            let object = object_addr.to_mut_obj();
+
+            object.visit_reference_fields(|field| {
+                let field_addr = field.get();
+
+                if snapshot.contains(field_addr)
+                    && field_addr.to_obj().header().try_mark_non_atomic()
+                {
+                    marking_stack.push(field_addr);
+                }
+            });
+        }
+
+        let overwritten = std::mem::take(&mut *satb_queue.lock());
+
+        if overwritten.is_empty() {
+            return marking_stack;
+        }
+
+        for object in overwritten {
+            if snapshot.contains(object) && object.to_obj().header().try_mark_non_atomic() {
+                marking_stack.push(object);
+            }
+        }
+    }
+}
+
+fn mark(marking_stack: &mut Vec<Address>, object: Address, heap: Region) {
+    if heap.contains(object) && object.to_obj().header().try_mark_non_atomic() {
+        marking_stack.push(object);
+    }
+}
+
+fn filler_vtables(vm: &VM) -> [Address; 2] {
+    let vtable_address = |cls_id| {
+        let cls = vm.class_instances.idx(cls_id);
+        let vtable = cls.vtable.read();
+        let vtable: &VTable = vtable.as_ref().unwrap();
+        Address::from_ptr(vtable)
+    };
+
+    [
+        vtable_address(vm.known.free_object_class_instance()),
+        vtable_address(vm.known.free_array_class_instance()),
+    ]
+}
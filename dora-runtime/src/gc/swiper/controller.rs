@@ -3,24 +3,36 @@ use std::cmp::{max, min};
 use std::f32;
 use std::fmt;
 use std::sync::Arc;
-use std::time::Instant;
 
 use crate::gc::swiper::large::LargeSpace;
 use crate::gc::swiper::young::YoungGen;
 use crate::gc::swiper::{CollectionKind, CommonOldGen};
-use crate::gc::{align_gen, align_gen_down, formatted_size, AllNumbers, GcReason, GEN_SIZE, M};
+use crate::gc::{
+    align_gen, align_gen_down, formatted_size, AllNumbers, GcReason, GcStats, GEN_SIZE, M,
+};
 use crate::mem;
 use crate::stdlib;
+use crate::timer::Timer;
 use crate::vm::{Args, Trap};
 
 const INIT_HEAP_SIZE_RATIO: usize = 2;
 const INIT_SEMI_RATIO: usize = 3;
 
+// concurrent marking is started once old generation is filled up to
+// 1/CONCURRENT_MARKING_RATIO of its limit
+const CONCURRENT_MARKING_RATIO: usize = 2;
+
 pub fn init(config: &mut HeapConfig, args: &Args) {
     assert!(config.min_heap_size <= config.max_heap_size);
 
+    set_young_size_bounds(
+        config,
+        args.min_young_size().unwrap_or(GEN_SIZE),
+        args.max_young_size().unwrap_or(usize::MAX),
+    );
+
     let young_size = if let Some(young_size) = args.young_size() {
-        min(align_gen(young_size), config.max_heap_size - GEN_SIZE)
+        align_gen(young_size)
     } else if args.young_appel() {
         let max_heap_size = (config.max_heap_size as f64 * 0.9) as usize;
         align_gen(max_heap_size / 2)
@@ -28,6 +40,9 @@ pub fn init(config: &mut HeapConfig, args: &Args) {
         unreachable!();
     };
 
+    let young_size = min(young_size, config.max_young_size);
+    let young_size = max(young_size, config.min_young_size);
+    let young_size = min(young_size, config.max_heap_size - GEN_SIZE);
     let young_size = max(young_size, GEN_SIZE);
     let (eden_size, semi_size) = calculate_young_size(args, young_size, 0);
 
@@ -49,6 +64,20 @@ pub fn init(config: &mut HeapConfig, args: &Args) {
     config.old_limit = old_limit;
 }
 
+// needs to be called at a safepoint, the young generation
+// is resized within the bounds at the end of the next collection.
+pub fn set_young_size(config: &SharedHeapConfig, min_young_size: usize, max_young_size: usize) {
+    let mut config = config.lock();
+    set_young_size_bounds(&mut config, min_young_size, max_young_size);
+}
+
+fn set_young_size_bounds(config: &mut HeapConfig, min_young_size: usize, max_young_size: usize) {
+    let min_young_size = max(align_gen(min_young_size), GEN_SIZE);
+
+    config.min_young_size = min_young_size;
+    config.max_young_size = max(align_gen_down(max_young_size), min_young_size);
+}
+
 fn calculate_young_size(args: &Args, young_size: usize, min_semi_size: usize) -> (usize, usize) {
     let semi_ratio = args.flag_gc_semi_ratio.unwrap_or(INIT_SEMI_RATIO);
     let semi_size = if semi_ratio == 0 {
@@ -79,6 +108,11 @@ pub fn choose_collection_kind(
     };
 }
 
+pub fn should_start_concurrent_marking(config: &SharedHeapConfig) -> bool {
+    let config = config.lock();
+    config.old_size >= config.old_limit / CONCURRENT_MARKING_RATIO
+}
+
 pub fn start(
     config: &SharedHeapConfig,
     young: &YoungGen,
@@ -87,9 +121,14 @@ pub fn start(
 ) {
     let mut config = config.lock();
 
-    config.gc_start = Some(Instant::now());
+    config.gc_timer = Some(Timer::new(true));
     config.start_object_size = object_size(young, old, large);
     config.start_memory_size = memory_size(young, old, large);
+
+    // everything on top of the heap size after the last collection was allocated since then
+    config.bytes_allocated += config
+        .start_object_size
+        .saturating_sub(config.end_object_size);
 }
 
 pub fn stop(
@@ -103,8 +142,8 @@ pub fn stop(
 ) {
     let mut config = config.lock();
 
-    let duration = config.gc_start.expect("not started").elapsed();
-    config.gc_duration = duration.as_secs_f32() / 1000f32;
+    config.gc_duration = config.gc_timer.take().expect("not started").stop();
+    config.max_pause = config.max_pause.max(config.gc_duration);
 
     assert!(young.eden_active().empty());
     assert!(young.from_active().empty());
@@ -112,15 +151,13 @@ pub fn stop(
     let old_size = old.committed_size() + large.committed_size();
     config.old_size = old_size;
 
-    let max_young_size = if let Some(young_size) = args.young_size() {
-        align_gen(young_size)
-    } else {
-        std::usize::MAX
-    };
-
     let rest = config.max_heap_size - config.old_size;
     let target_young_size = align_gen_down(rest / 2);
-    let target_young_size = min(target_young_size, max_young_size);
+    let target_young_size = min(target_young_size, config.max_young_size);
+
+    // the minimum size is only guaranteed as long as the heap has enough space left
+    let min_young_size = min(config.min_young_size, align_gen_down(rest / 2));
+    let target_young_size = max(target_young_size, min_young_size);
     let target_young_size = max(target_young_size, GEN_SIZE);
 
     let to_size = young.to_active().size();
@@ -155,6 +192,7 @@ pub fn stop(
         CollectionKind::Full => {
             config.total_full_collections += 1;
             config.total_full_pause += config.gc_duration;
+            config.live_after_full = config.end_object_size;
 
             if args.flag_gc_stats {
                 config.full_phases.last_mut().unwrap().total = config.gc_duration;
@@ -167,6 +205,25 @@ pub fn stop(
     }
 }
 
+pub fn stats(
+    config: &SharedHeapConfig,
+    young: &YoungGen,
+    old: &dyn CommonOldGen,
+    large: &LargeSpace,
+) -> GcStats {
+    let config = config.lock();
+    let allocated_since_gc = object_size(young, old, large).saturating_sub(config.end_object_size);
+
+    GcStats {
+        minor_collections: config.total_minor_collections,
+        major_collections: config.total_full_collections,
+        total_pause: config.total_minor_pause + config.total_full_pause,
+        max_pause: config.max_pause,
+        bytes_allocated: config.bytes_allocated + allocated_since_gc,
+        bytes_live_after_major: config.live_after_full,
+    }
+}
+
 fn print(config: &HeapConfig, kind: CollectionKind, reason: GcReason) {
     match kind {
         CollectionKind::Minor => {
@@ -220,8 +277,12 @@ pub struct HeapConfig {
     pub old_size: usize,
     pub old_limit: usize,
 
-    gc_start: Option<Instant>,
+    min_young_size: usize,
+    max_young_size: usize,
+
+    gc_timer: Option<Timer>,
     gc_duration: f32,
+    max_pause: f32,
 
     start_object_size: usize,
     start_memory_size: usize,
@@ -237,6 +298,9 @@ pub struct HeapConfig {
     pub total_full_collections: usize,
     pub total_full_pause: f32,
 
+    bytes_allocated: usize,
+    live_after_full: usize,
+
     full_phases: Vec<FullCollectorPhases>,
     minor_phases: Vec<MinorCollectorPhases>,
 }
@@ -254,8 +318,12 @@ impl HeapConfig {
             old_size: 0,
             old_limit: 0,
 
-            gc_start: None,
+            min_young_size: GEN_SIZE,
+            max_young_size: usize::MAX,
+
+            gc_timer: None,
             gc_duration: 0f32,
+            max_pause: 0f32,
 
             start_object_size: 0,
             start_memory_size: 0,
@@ -271,6 +339,9 @@ impl HeapConfig {
             total_full_collections: 0,
             total_full_pause: 0f32,
 
+            bytes_allocated: 0,
+            live_after_full: 0,
+
             full_phases: Vec::new(),
             minor_phases: Vec::new(),
         }
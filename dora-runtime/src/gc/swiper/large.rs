@@ -2,7 +2,7 @@ use parking_lot::Mutex;
 use std::mem::size_of;
 
 use crate::gc::swiper::controller::SharedHeapConfig;
-use crate::gc::swiper::LARGE_OBJECT_SIZE;
+use crate::gc::tlab::TLAB_OBJECT_SIZE;
 use crate::gc::{Address, Region};
 use crate::mem;
 use crate::os::{self, MemoryPermission};
@@ -23,7 +23,7 @@ impl LargeSpace {
     }
 
     pub fn alloc(&self, size: usize) -> Address {
-        debug_assert!(size >= LARGE_OBJECT_SIZE);
+        debug_assert!(size >= TLAB_OBJECT_SIZE);
         let size = mem::page_align(size_of::<LargeAlloc>() + size);
 
         let mut space = self.space.lock();
@@ -10,7 +10,8 @@ use crate::gc::swiper::old::{OldGen, OldGenProtected};
 use crate::gc::swiper::on_different_cards;
 use crate::gc::swiper::young::YoungGen;
 use crate::gc::swiper::{forward_minor, CardIdx, CARD_SIZE};
-use crate::gc::{iterate_strong_roots, iterate_weak_roots, Address, GcReason, Region, Slot};
+use crate::gc::{iterate_weak_roots, Address, GcReason, Region, Slot};
+use crate::mem;
 use crate::object::{offset_of_array_data, Obj};
 use crate::threads::DoraThread;
 use crate::timer::Timer;
@@ -26,7 +27,7 @@ pub struct MinorCollector<'a> {
     card_table: &'a CardTable,
     crossing_map: &'a CrossingMap,
 
-    _rootset: &'a [Slot],
+    rootset: &'a [Slot],
     threads: &'a [Arc<DoraThread>],
     _reason: GcReason,
 
@@ -68,7 +69,7 @@ impl<'a> MinorCollector<'a> {
             old,
             old_protected: old.protected(),
             large,
-            _rootset: rootset,
+            rootset,
             threads,
             card_table,
             crossing_map,
@@ -170,13 +171,13 @@ impl<'a> MinorCollector<'a> {
 
     fn visit_roots(&mut self) {
         // detect all references from roots into young generation
-        iterate_strong_roots(self.vm, self.threads, |root| {
+        for &root in self.rootset {
             let root_ptr = root.get();
 
             if self.young.contains(root_ptr) {
                 root.set(self.copy(root_ptr));
             }
-        });
+        }
     }
 
     fn visit_dirty_cards(&mut self) {
@@ -210,6 +211,11 @@ impl<'a> MinorCollector<'a> {
             let card_start = self.card_table.to_address(card_idx);
             let card_end = card_start.offset(CARD_SIZE);
 
+            // start loading the next card while we are busy with this one
+            if card_end < object_end {
+                mem::prefetch(card_end);
+            }
+
             let ref_start = object_start.offset(offset_of_array_data() as usize);
             let ref_start = cmp::max(ref_start, card_start);
             let ref_end = cmp::min(card_end, object_end);
@@ -237,6 +237,11 @@ impl<'a> ParallelFullCollector<'a> {
             self.readonly_space.total(),
             pool,
         );
+
+        self.vm
+            .gc
+            .finalization_queue()
+            .mark_unreachable(self.heap, self.readonly_space.total());
     }
 
     fn compute_forward(&mut self, pool: &mut Pool) {
@@ -729,6 +734,19 @@ impl<'a> ParallelFullCollector<'a> {
                 self.large_space.total(),
             )
         });
+
+        self.vm
+            .gc
+            .finalization_queue()
+            .update_references(|current_address| {
+                forward_full(
+                    current_address,
+                    self.heap,
+                    self.readonly_space.total(),
+                    self.large_space.total(),
+                )
+                .expect("finalizable object not marked")
+            });
     }
 
     fn update_references_unit(&self, unit: &Unit) {
@@ -12,7 +12,7 @@ use crate::gc::swiper::large::{LargeAlloc, LargeSpace};
 use crate::gc::swiper::old::OldGen;
 use crate::gc::swiper::on_different_cards;
 use crate::gc::swiper::young::YoungGen;
-use crate::gc::swiper::{forward_minor, CardIdx, CARD_SIZE, LARGE_OBJECT_SIZE};
+use crate::gc::swiper::{forward_minor, CardIdx, CARD_SIZE};
 use crate::gc::tlab::{TLAB_OBJECT_SIZE, TLAB_SIZE};
 use crate::gc::{fill_region, iterate_weak_roots, Address, GcReason, Region};
 use crate::object::{offset_of_array_data, Obj};
@@ -880,7 +880,7 @@ impl<'a> CopyTask<'a> {
     }
 
     fn alloc_young_medium(&mut self, size: usize) -> Address {
-        debug_assert!(CLAB_OBJECT_SIZE <= size && size < LARGE_OBJECT_SIZE);
+        debug_assert!(CLAB_OBJECT_SIZE <= size);
 
         if self.copy_failed {
             return Address::null();
@@ -960,7 +960,7 @@ impl<'a> CopyTask<'a> {
     }
 
     fn alloc_old_medium(&mut self, size: usize, array_ref: bool) -> Address {
-        debug_assert!(CLAB_OBJECT_SIZE <= size && size < LARGE_OBJECT_SIZE);
+        debug_assert!(CLAB_OBJECT_SIZE <= size);
 
         if self.promotion_failed {
             return Address::null();
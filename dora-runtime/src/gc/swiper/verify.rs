@@ -7,8 +7,8 @@ use crate::gc::swiper::card::{CardEntry, CardTable};
 use crate::gc::swiper::crossing::{CrossingEntry, CrossingMap};
 use crate::gc::swiper::large::LargeSpace;
 use crate::gc::swiper::old::{OldGen, OldGenProtected};
-use crate::gc::swiper::on_different_cards;
 use crate::gc::swiper::young::YoungGen;
+use crate::gc::swiper::{on_different_cards, walk_region};
 use crate::gc::swiper::{CARD_REFS, CARD_SIZE};
 use crate::gc::{Address, Region};
 
@@ -578,3 +578,56 @@ impl<'a> Verifier<'a> {
         );
     }
 }
+
+// Checks that every reference from the old generation or large space into
+// the young generation is covered by a dirty card. Cheaper than the full
+// Verifier and only run after collections, when cards need to be exact.
+pub fn verify_card_table(
+    young: &YoungGen,
+    old: &OldGen,
+    large: &LargeSpace,
+    card_table: &CardTable,
+) {
+    let young_total = young.total();
+
+    let verify_object = |object: &mut Obj, object_address: Address| {
+        let array_ref = object.is_array_ref();
+
+        object.visit_reference_fields(|slot| {
+            if !young_total.contains(slot.get()) {
+                return;
+            }
+
+            // the write barrier marks the card of the element for object
+            // arrays, and the card of the object header otherwise
+            let card_address = if array_ref {
+                slot.address()
+            } else {
+                object_address
+            };
+
+            let card = card_table.card_idx(card_address);
+
+            if card_table.get(card).is_clean() {
+                panic!(
+                    "object {} references young object {} (at {}) but card {} ({}) is clean.",
+                    object_address,
+                    slot.get(),
+                    slot.address(),
+                    card.to_usize(),
+                    card_table.to_address(card),
+                );
+            }
+        });
+    };
+
+    for old_region in &old.protected().regions {
+        walk_region(old_region.active_region(), |object, address, _size| {
+            verify_object(object, address);
+        });
+    }
+
+    large.visit_objects(|address| {
+        verify_object(address.to_mut_obj(), address);
+    });
+}
@@ -44,7 +44,6 @@ impl YoungGen {
         self.eden.active()
     }
 
-    #[allow(dead_code)]
     pub fn eden_committed(&self) -> Region {
         self.eden.committed()
     }
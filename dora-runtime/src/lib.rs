@@ -15,7 +15,7 @@ mod cannon;
 mod compiler;
 mod constpool;
 mod cpu;
-mod disassembler;
+pub mod disassembler;
 mod gc;
 mod handle;
 mod masm;
@@ -35,5 +35,6 @@ mod vtable;
 
 pub use vm::VM;
 pub use vm::{
-    clear_vm, display_fct, execute_on_main, set_vm, Args, CollectorName, CompilerName, MemSize,
+    clear_vm, display_fct, execute_on_main, set_vm, Args, CollectorName, CompilerName,
+    DisasmSyntax, MemSize, TestExit, Trap,
 };
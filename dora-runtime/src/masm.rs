@@ -4,14 +4,14 @@ use std::rc::Rc;
 
 use crate::compiler::codegen::AnyReg;
 use crate::constpool::ConstPool;
-use crate::cpu::{Reg, SCRATCH};
-use crate::gc::Address;
+use crate::cpu::{FReg, Reg, FREG_TMP2, REG_PARAMS, SCRATCH};
+use crate::gc::{self, Address};
 use crate::mem;
 use crate::mode::MachineMode;
 use crate::object::Header;
 use crate::vm::{
-    CommentTable, GcPoint, GcPointTable, LazyCompilationData, LazyCompilationSite, LocationTable,
-    RelocationTable, Trap, CODE_ALIGNMENT,
+    CommentTable, ExceptionHandler, ExceptionHandlerTable, GcPoint, GcPointTable,
+    LazyCompilationData, LazyCompilationSite, LocationTable, RelocationTable, Trap, CODE_ALIGNMENT,
 };
 pub use dora_asm::Label;
 use dora_bytecode::Location;
@@ -36,6 +36,7 @@ pub struct CodeDescriptor {
     pub comments: CommentTable,
     pub positions: LocationTable,
     pub relocations: RelocationTable,
+    pub exception_handlers: ExceptionHandlerTable,
 }
 
 impl CodeDescriptor {
@@ -48,6 +49,7 @@ impl CodeDescriptor {
             comments: CommentTable::new(),
             positions: LocationTable::new(),
             relocations: RelocationTable::new(),
+            exception_handlers: ExceptionHandlerTable::new(),
         }
     }
 }
@@ -75,7 +77,9 @@ pub struct MacroAssembler {
     comments: CommentTable,
     positions: LocationTable,
     relocations: RelocationTable,
+    exception_handlers: ExceptionHandlerTable,
     scratch_registers: ScratchRegisters,
+    jump_tables: Vec<(i32, Vec<Label>)>,
 }
 
 impl MacroAssembler {
@@ -89,12 +93,15 @@ impl MacroAssembler {
             comments: CommentTable::new(),
             positions: LocationTable::new(),
             relocations: RelocationTable::new(),
+            exception_handlers: ExceptionHandlerTable::new(),
             scratch_registers: ScratchRegisters::new(),
+            jump_tables: Vec::new(),
         }
     }
 
     pub fn code(mut self) -> CodeDescriptor {
         self.finish();
+        self.resolve_jump_tables();
 
         // align data such that code starts at address that is
         // aligned to 16
@@ -110,6 +117,7 @@ impl MacroAssembler {
             comments: self.comments,
             positions: self.positions,
             relocations: self.relocations,
+            exception_handlers: self.exception_handlers,
         }
     }
 
@@ -136,6 +144,20 @@ impl MacroAssembler {
         }
     }
 
+    // Jump table entries are stored relative to the start of the table,
+    // which makes them independent of the final code address.
+    fn resolve_jump_tables(&mut self) {
+        let jump_tables = std::mem::take(&mut self.jump_tables);
+
+        for (disp, targets) in jump_tables {
+            for (idx, &target) in targets.iter().enumerate() {
+                let offset = self.asm.offset(target).expect("unbound label") as i32;
+                self.constpool
+                    .patch_i32(disp - 4 * idx as i32, offset + disp);
+            }
+        }
+    }
+
     pub fn add_addr(&mut self, ptr: Address) -> i32 {
         self.constpool.add_addr(ptr)
     }
@@ -167,6 +189,38 @@ impl MacroAssembler {
         lbl
     }
 
+    // traps if the Int64 in src doesn't fit into an Int32
+    pub fn check_int64_to_int32(&mut self, location: Location, src: Reg) {
+        let scratch = self.get_scratch();
+        self.extend_int_long(*scratch, src);
+        self.cmp_reg(MachineMode::Int64, *scratch, src);
+
+        let lbl = self.asm.create_label();
+        self.jump_if(CondCode::NotEqual, lbl);
+        self.emit_bailout(lbl, Trap::OVERFLOW, location);
+    }
+
+    // traps if the Int32 in src doesn't fit into an UInt8
+    pub fn check_int32_to_uint8(&mut self, location: Location, src: Reg) {
+        self.cmp_reg_imm(MachineMode::Int32, src, 255);
+
+        let lbl = self.asm.create_label();
+        self.jump_if(CondCode::UnsignedGreater, lbl);
+        self.emit_bailout(lbl, Trap::OVERFLOW, location);
+    }
+
+    // converts the Float64 in src into an Int32, traps for NaN
+    // and values outside of the Int32 range.
+    pub fn checked_float64_to_int32(&mut self, location: Location, dest: Reg, src: FReg) {
+        let lbl_nan = self.asm.create_label();
+        self.float_cmp_nan(MachineMode::Float64, dest, src);
+        self.test_and_jump_if(CondCode::NonZero, dest, lbl_nan);
+        self.emit_bailout(lbl_nan, Trap::OVERFLOW, location);
+
+        self.float_to_int(MachineMode::Int64, dest, MachineMode::Float64, src);
+        self.check_int64_to_int32(location, dest);
+    }
+
     pub fn emit_position(&mut self, location: Location) {
         let offset = self.pos() as u32;
         self.positions.insert(offset, location);
@@ -181,6 +235,10 @@ impl MacroAssembler {
         self.gcpoints.insert(0, gcpoint);
     }
 
+    pub fn add_exception_handler(&mut self, handler: ExceptionHandler) {
+        self.exception_handlers.insert(handler);
+    }
+
     pub fn emit_lazy_compilation_site(&mut self, info: LazyCompilationSite) {
         let pos = self.pos() as u32;
         self.lazy_compilation.insert(pos, info);
@@ -239,6 +297,24 @@ impl MacroAssembler {
         }
     }
 
+    // Computes the truncated remainder `lhs - trunc(lhs / rhs) * rhs`, the
+    // result has the sign of `lhs`. Like the other float operations on x64
+    // this may overwrite `lhs`.
+    //
+    // Unlike fmod this is not exact: the quotient is rounded before it is
+    // truncated, so the result is off once `lhs / rhs` exceeds the mantissa
+    // precision, and an infinite `rhs` yields NaN instead of `lhs`.
+    pub fn float_rem(&mut self, mode: MachineMode, dest: FReg, lhs: FReg, rhs: FReg) {
+        let tmp = FREG_TMP2;
+        assert!(lhs != tmp && rhs != tmp);
+
+        self.copy_freg(mode, tmp, lhs);
+        self.float_div(mode, tmp, tmp, rhs);
+        self.float_round_tozero(mode, tmp, tmp);
+        self.float_mul(mode, tmp, tmp, rhs);
+        self.float_sub(mode, dest, lhs, tmp);
+    }
+
     pub fn fill_zero(&mut self, obj: Reg, array: bool, size: usize) {
         let header_size =
             (Header::size() as usize) + if array { mem::ptr_width_usize() } else { 0 };
@@ -288,6 +364,50 @@ impl MacroAssembler {
         self.jump(start);
         self.bind_label(done);
     }
+
+    // jumps to lbl_inactive if GC is not marking concurrently right now.
+    pub fn test_concurrent_marking(&mut self, marking_flag: Address, lbl_inactive: Label) {
+        let flag = self.get_scratch();
+        self.load_int_const(MachineMode::Ptr, *flag, marking_flag.to_usize() as i64);
+        self.load_mem(MachineMode::Int8, (*flag).into(), Mem::Base(*flag, 0));
+        self.test_and_jump_if(CondCode::Zero, *flag, lbl_inactive);
+    }
+
+    // snapshot-at-the-beginning barrier: remembers the reference in slot
+    // before it gets overwritten. Clobbers all caller-saved registers.
+    pub fn emit_pre_write_barrier(&mut self, slot: Mem) {
+        let lbl_null = self.create_label();
+        self.load_mem(MachineMode::Ptr, REG_PARAMS[0].into(), slot);
+        self.cmp_zero(MachineMode::Ptr, REG_PARAMS[0]);
+        self.jump_if(CondCode::Equal, lbl_null);
+        self.raw_call(Address::from_ptr(gc::satb_enqueue as *const u8));
+        self.bind_label(lbl_null);
+    }
+
+    // fills the first length elements of the array with the value stored
+    // at value, elements might contain references. Clobbers all
+    // caller-saved registers.
+    pub fn fill_array_with_references(&mut self, array: Reg, length: Reg, value: Mem) {
+        debug_assert!(array != REG_PARAMS[1] && length != REG_PARAMS[0]);
+        self.copy_reg(MachineMode::Ptr, REG_PARAMS[0], array);
+        self.copy_reg(MachineMode::Int64, REG_PARAMS[1], length);
+        self.lea(REG_PARAMS[2], value);
+        self.raw_call(Address::from_ptr(gc::fill_array as *const u8));
+    }
+
+    // copies elements between two arrays, expects the array, the destination
+    // and source addresses and the number of elements in the first four
+    // parameter registers. Clobbers all caller-saved registers.
+    pub fn copy_array(&mut self) {
+        self.raw_call(Address::from_ptr(gc::copy_array as *const u8));
+    }
+
+    // registers the object in obj for finalization. Clobbers all
+    // caller-saved registers.
+    pub fn emit_register_finalizable(&mut self, obj: Reg) {
+        self.copy_reg(MachineMode::Ptr, REG_PARAMS[0], obj);
+        self.raw_call(Address::from_ptr(gc::register_finalizable as *const u8));
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -339,6 +459,18 @@ impl ScratchRegisters {
     }
 }
 
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum PrefetchHint {
+    // fetch into all cache levels
+    T0,
+    // fetch into L2 and higher
+    T1,
+    // fetch into L3 and higher
+    T2,
+    // fetch while minimizing cache pollution
+    NonTemporal,
+}
+
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
 pub enum CondCode {
     Zero,
@@ -2,12 +2,12 @@ use crate::compiler::codegen::AnyReg;
 use crate::cpu::*;
 use crate::gc::swiper::CARD_SIZE_BITS;
 use crate::gc::Address;
-use crate::masm::{CondCode, Label, MacroAssembler, Mem};
+use crate::masm::{CondCode, Label, MacroAssembler, Mem, PrefetchHint};
 use crate::mem::ptr_width;
 use crate::mode::MachineMode;
 use crate::object::{offset_of_array_data, offset_of_array_length, Header};
 use crate::threads::ThreadLocalData;
-use crate::vm::{get_vm, LazyCompilationSite, Trap};
+use crate::vm::{get_vm, GcPoint, LazyCompilationSite, Trap};
 use crate::vtable::VTable;
 pub use dora_asm::arm64::AssemblerArm64 as Assembler;
 use dora_asm::arm64::{self as asm, Cond, Extend, NeonRegister, Shift};
@@ -136,6 +136,20 @@ impl MacroAssembler {
         self.emit_lazy_compilation_site(lazy_compilation_site);
     }
 
+    // No inline cache on arm64 yet, always dispatch through the vtable.
+    pub fn virtual_call_cached(
+        &mut self,
+        location: Location,
+        gcpoint: GcPoint,
+        vtable_index: u32,
+        self_index: u32,
+        lazy_compilation_site: LazyCompilationSite,
+    ) {
+        self.virtual_call(location, vtable_index, self_index, lazy_compilation_site);
+        self.emit_position(location);
+        self.emit_gcpoint(gcpoint);
+    }
+
     pub fn load_array_elem(&mut self, mode: MachineMode, dest: AnyReg, array: Reg, index: Reg) {
         self.load_mem(
             mode,
@@ -167,7 +181,9 @@ impl MacroAssembler {
 
     pub fn cmp_reg(&mut self, mode: MachineMode, lhs: Reg, rhs: Reg) {
         match mode {
-            MachineMode::Int8 | MachineMode::Int32 => self.asm.cmp_w(lhs.into(), rhs.into()),
+            MachineMode::Int8 | MachineMode::Int16 | MachineMode::Int32 => {
+                self.asm.cmp_w(lhs.into(), rhs.into())
+            }
             MachineMode::IntPtr | MachineMode::Ptr | MachineMode::Int64 => {
                 self.asm.cmp(lhs.into(), rhs.into())
             }
@@ -183,7 +199,9 @@ impl MacroAssembler {
 
     pub fn cmp_zero(&mut self, mode: MachineMode, lhs: Reg) {
         match mode {
-            MachineMode::Int8 | MachineMode::Int32 => self.asm.cmp_imm_w(lhs.into(), 0, 0),
+            MachineMode::Int8 | MachineMode::Int16 | MachineMode::Int32 => {
+                self.asm.cmp_imm_w(lhs.into(), 0, 0)
+            }
             MachineMode::Int64 | MachineMode::Ptr => self.asm.cmp_imm(lhs.into(), 0, 0),
             _ => unreachable!(),
         }
@@ -208,6 +226,33 @@ impl MacroAssembler {
         self.asm.b_r(reg.into());
     }
 
+    // Jumps to `targets[index]`, index needs to be in bounds and zero-extended.
+    pub fn jump_table(&mut self, index: Reg, targets: Vec<Label>) {
+        let disp = self.constpool.add_jump_table(targets.len());
+        self.jump_tables.push((disp, targets));
+
+        let table = self.get_scratch();
+        let pos = self.pos() as i32;
+
+        self.asm.adr_imm((*table).into(), -(disp + pos));
+        self.load_mem(
+            MachineMode::Int32,
+            index.into(),
+            Mem::Index(*table, index, 4, 0),
+        );
+        self.asm.add(index.into(), index.into(), (*table).into());
+        self.asm.b_r(index.into());
+    }
+
+    pub fn increment_counter(&mut self, counter: Address) {
+        let scratch = self.get_scratch();
+        let value = self.get_scratch();
+        self.load_int_const(MachineMode::Ptr, *scratch, counter.to_usize() as i64);
+        self.load_mem(MachineMode::Int64, (*value).into(), Mem::Base(*scratch, 0));
+        self.int_add_imm(MachineMode::Int64, *value, *value, 1);
+        self.store_mem(MachineMode::Int64, Mem::Base(*scratch, 0), (*value).into());
+    }
+
     pub fn int_div(
         &mut self,
         mode: MachineMode,
@@ -309,6 +354,54 @@ impl MacroAssembler {
         }
     }
 
+    pub fn int_mul_imm(&mut self, mode: MachineMode, dest: Reg, lhs: Reg, value: i64) {
+        let x64 = match mode {
+            MachineMode::Int32 => false,
+            MachineMode::Int64 => true,
+            _ => panic!("unimplemented mode {:?}", mode),
+        };
+        let value = if x64 { value } else { value as i32 as i64 };
+
+        match value {
+            0 => self.load_int_const(mode, dest, 0),
+
+            1 => {
+                if dest != lhs {
+                    self.copy_reg(mode, dest, lhs);
+                }
+            }
+
+            3 | 5 | 9 => {
+                // lhs + (lhs << log2(value - 1))
+                let shift = (value - 1).trailing_zeros();
+
+                if x64 {
+                    self.asm
+                        .add_sh(dest.into(), lhs.into(), lhs.into(), Shift::LSL, shift);
+                } else {
+                    self.asm
+                        .add_sh_w(dest.into(), lhs.into(), lhs.into(), Shift::LSL, shift);
+                }
+            }
+
+            _ if value > 0 && (value as u64).is_power_of_two() => {
+                let shift = value.trailing_zeros();
+
+                if x64 {
+                    self.asm.lsl_imm(dest.into(), lhs.into(), shift);
+                } else {
+                    self.asm.lsl_imm_w(dest.into(), lhs.into(), shift);
+                }
+            }
+
+            _ => {
+                let scratch = self.get_scratch();
+                self.load_int_const(mode, *scratch, value);
+                self.int_mul(mode, dest, lhs, *scratch);
+            }
+        }
+    }
+
     pub fn int_mul_checked(
         &mut self,
         mode: MachineMode,
@@ -390,6 +483,110 @@ impl MacroAssembler {
         self.emit_bailout(lbl_overflow, Trap::OVERFLOW, location);
     }
 
+    // the int_*_overflows functions set dest to 1 if the operation
+    // overflows and to 0 otherwise.
+    pub fn int_add_overflows(&mut self, mode: MachineMode, dest: Reg, lhs: Reg, rhs: Reg) {
+        let result = self.get_scratch();
+
+        match mode {
+            MachineMode::Int32 => self.asm.adds_w((*result).into(), lhs.into(), rhs.into()),
+            MachineMode::Int64 => self.asm.adds((*result).into(), lhs.into(), rhs.into()),
+            _ => panic!("unimplemented mode {:?}", mode),
+        }
+
+        self.asm.cset_w(dest.into(), Cond::VS);
+    }
+
+    pub fn int_sub_overflows(&mut self, mode: MachineMode, dest: Reg, lhs: Reg, rhs: Reg) {
+        let result = self.get_scratch();
+
+        match mode {
+            MachineMode::Int32 => self.asm.subs_w((*result).into(), lhs.into(), rhs.into()),
+            MachineMode::Int64 => self.asm.subs((*result).into(), lhs.into(), rhs.into()),
+            _ => panic!("unimplemented mode {:?}", mode),
+        }
+
+        self.asm.cset_w(dest.into(), Cond::VS);
+    }
+
+    pub fn int_mul_overflows(&mut self, mode: MachineMode, dest: Reg, lhs: Reg, rhs: Reg) {
+        let result = self.get_scratch();
+
+        match mode {
+            MachineMode::Int32 => {
+                self.asm.smull((*result).into(), lhs.into(), rhs.into());
+                self.asm
+                    .cmp_ext((*result).into(), (*result).into(), Extend::SXTW, 0);
+            }
+            MachineMode::Int64 => {
+                let high = self.get_scratch();
+                self.asm.mul((*result).into(), lhs.into(), rhs.into());
+                self.asm.smulh((*high).into(), lhs.into(), rhs.into());
+                self.asm
+                    .cmp_sh((*high).into(), (*result).into(), Shift::ASR, 63);
+            }
+            _ => panic!("unimplemented mode {:?}", mode),
+        }
+
+        self.asm.cset_w(dest.into(), Cond::NE);
+    }
+
+    pub fn int_add_saturating(&mut self, mode: MachineMode, dest: Reg, lhs: Reg, rhs: Reg) {
+        let bound = self.get_scratch();
+        self.int_saturation_bound(mode, *bound, lhs);
+
+        match mode {
+            MachineMode::Int32 => {
+                self.asm.adds_w(dest.into(), lhs.into(), rhs.into());
+                self.asm
+                    .csel_w(dest.into(), (*bound).into(), dest.into(), Cond::VS);
+            }
+            MachineMode::Int64 => {
+                self.asm.adds(dest.into(), lhs.into(), rhs.into());
+                self.asm
+                    .csel(dest.into(), (*bound).into(), dest.into(), Cond::VS);
+            }
+            _ => panic!("unimplemented mode {:?}", mode),
+        }
+    }
+
+    pub fn int_sub_saturating(&mut self, mode: MachineMode, dest: Reg, lhs: Reg, rhs: Reg) {
+        let bound = self.get_scratch();
+        self.int_saturation_bound(mode, *bound, lhs);
+
+        match mode {
+            MachineMode::Int32 => {
+                self.asm.subs_w(dest.into(), lhs.into(), rhs.into());
+                self.asm
+                    .csel_w(dest.into(), (*bound).into(), dest.into(), Cond::VS);
+            }
+            MachineMode::Int64 => {
+                self.asm.subs(dest.into(), lhs.into(), rhs.into());
+                self.asm
+                    .csel(dest.into(), (*bound).into(), dest.into(), Cond::VS);
+            }
+            _ => panic!("unimplemented mode {:?}", mode),
+        }
+    }
+
+    // On overflow the exact result has the sign of lhs: MAX + (lhs >>> 63)
+    // is MAX for non-negative lhs and wraps around to MIN otherwise.
+    fn int_saturation_bound(&mut self, mode: MachineMode, dest: Reg, lhs: Reg) {
+        match mode {
+            MachineMode::Int32 => {
+                self.load_int_const(mode, dest, i32::MAX as i64);
+                self.asm
+                    .add_sh_w(dest.into(), dest.into(), lhs.into(), Shift::LSR, 31);
+            }
+            MachineMode::Int64 => {
+                self.load_int_const(mode, dest, i64::MAX);
+                self.asm
+                    .add_sh(dest.into(), dest.into(), lhs.into(), Shift::LSR, 63);
+            }
+            _ => panic!("unimplemented mode {:?}", mode),
+        }
+    }
+
     pub fn int_add_imm(&mut self, mode: MachineMode, dest: Reg, lhs: Reg, value: i64) {
         if (value as u32) as i64 == value && asm::fits_addsub_imm(value as u32) {
             match mode {
@@ -721,6 +918,22 @@ impl MacroAssembler {
         };
     }
 
+    pub fn float_min(&mut self, mode: MachineMode, dest: FReg, lhs: FReg, rhs: FReg) {
+        match mode {
+            MachineMode::Float32 => self.asm.fmin_s(dest.into(), lhs.into(), rhs.into()),
+            MachineMode::Float64 => self.asm.fmin_d(dest.into(), lhs.into(), rhs.into()),
+            _ => unreachable!(),
+        }
+    }
+
+    pub fn float_max(&mut self, mode: MachineMode, dest: FReg, lhs: FReg, rhs: FReg) {
+        match mode {
+            MachineMode::Float32 => self.asm.fmax_s(dest.into(), lhs.into(), rhs.into()),
+            MachineMode::Float64 => self.asm.fmax_d(dest.into(), lhs.into(), rhs.into()),
+            _ => unreachable!(),
+        }
+    }
+
     pub fn float_sub(&mut self, mode: MachineMode, dest: FReg, lhs: FReg, rhs: FReg) {
         match mode {
             MachineMode::Float32 => self.asm.fsub_s(dest.into(), lhs.into(), rhs.into()),
@@ -803,7 +1016,7 @@ impl MacroAssembler {
 
     pub fn cmp_int(&mut self, mode: MachineMode, dest: Reg, lhs: Reg, rhs: Reg) {
         match mode {
-            MachineMode::Int8 | MachineMode::Int32 => {
+            MachineMode::Int8 | MachineMode::Int16 | MachineMode::Int32 => {
                 self.asm.cmp_w(lhs.into(), rhs.into());
             }
 
@@ -957,6 +1170,45 @@ impl MacroAssembler {
         self.emit_bailout(lbl, Trap::INDEX_OUT_OF_BOUNDS, location);
     }
 
+    pub fn check_length_out_of_bounds(&mut self, location: Location, array: Reg, length: Reg) {
+        let scratch = self.get_scratch();
+        self.load_mem(
+            MachineMode::Int64,
+            (*scratch).into(),
+            Mem::Base(array, offset_of_array_length()),
+        );
+        self.cmp_reg(MachineMode::Int64, length, *scratch);
+
+        let lbl = self.create_label();
+        self.jump_if(CondCode::UnsignedGreater, lbl);
+        self.emit_bailout(lbl, Trap::INDEX_OUT_OF_BOUNDS, location);
+    }
+
+    // stores value into the first length elements of the array
+    pub fn fill_array(&mut self, mode: MachineMode, array: Reg, length: Reg, value: Reg) {
+        let element_size = mode.size();
+        let curr = self.get_scratch();
+        let end = self.get_scratch();
+        let lbl_start = self.create_label();
+        let lbl_done = self.create_label();
+
+        self.array_address(*end, array, length, element_size);
+        self.int_add_imm(
+            MachineMode::Ptr,
+            *curr,
+            array,
+            offset_of_array_data() as i64,
+        );
+
+        self.bind_label(lbl_start);
+        self.cmp_reg(MachineMode::Ptr, *curr, *end);
+        self.jump_if(CondCode::Equal, lbl_done);
+        self.store_mem(mode, Mem::Base(*curr, 0), value.into());
+        self.int_add_imm(MachineMode::Ptr, *curr, *curr, element_size as i64);
+        self.jump(lbl_start);
+        self.bind_label(lbl_done);
+    }
+
     pub fn load_nil(&mut self, dest: Reg) {
         self.asm.movz(dest.into(), 0, 0);
     }
@@ -969,6 +1221,22 @@ impl MacroAssembler {
         self.asm.ldar(dest.into(), address.into());
     }
 
+    pub fn fence_acquire(&mut self) {
+        self.asm.dmb_ishld();
+    }
+
+    pub fn fence_release(&mut self) {
+        self.asm.dmb_ish();
+    }
+
+    pub fn fence_seq_cst(&mut self) {
+        self.asm.dmb_ish();
+    }
+
+    pub fn prefetch(&mut self, _mem: Mem, _hint: PrefetchHint) {
+        // prefetching is only a hint, not emitting anything is always correct
+    }
+
     pub fn store_int32_synchronized(&mut self, dest: Reg, address: Reg) {
         self.asm.stlr_w(dest.into(), address.into());
     }
@@ -978,27 +1246,43 @@ impl MacroAssembler {
     }
 
     pub fn exchange_int32_synchronized(&mut self, old: Reg, new: Reg, address: Reg) {
-        if has_lse_atomics() {
-            self.asm.swpal_w(new.into(), old.into(), address.into());
-        } else {
-            let scratch = self.get_scratch();
-            let loop_start = self.asm.create_and_bind_label();
-            self.asm.ldaxr_w(old.into(), address.into());
-            self.asm
-                .stlxr_w((*scratch).into(), new.into(), address.into());
-            self.asm.cbnz_w((*scratch).into(), loop_start);
-        }
+        self.exchange_synchronized(MachineMode::Int32, old, new, address, has_lse_atomics());
     }
 
     pub fn exchange_int64_synchronized(&mut self, old: Reg, new: Reg, address: Reg) {
-        if has_lse_atomics() {
-            self.asm.swpal(new.into(), old.into(), address.into());
+        self.exchange_synchronized(MachineMode::Int64, old, new, address, has_lse_atomics());
+    }
+
+    fn exchange_synchronized(
+        &mut self,
+        mode: MachineMode,
+        old: Reg,
+        new: Reg,
+        address: Reg,
+        lse: bool,
+    ) {
+        if lse {
+            match mode {
+                MachineMode::Int32 => self.asm.swpal_w(new.into(), old.into(), address.into()),
+                MachineMode::Int64 => self.asm.swpal(new.into(), old.into(), address.into()),
+                _ => unreachable!(),
+            }
         } else {
             let scratch = self.get_scratch();
             let loop_start = self.asm.create_and_bind_label();
-            self.asm.ldaxr(old.into(), address.into());
-            self.asm
-                .stlxr((*scratch).into(), new.into(), address.into());
+            match mode {
+                MachineMode::Int32 => {
+                    self.asm.ldaxr_w(old.into(), address.into());
+                    self.asm
+                        .stlxr_w((*scratch).into(), new.into(), address.into());
+                }
+                MachineMode::Int64 => {
+                    self.asm.ldaxr(old.into(), address.into());
+                    self.asm
+                        .stlxr((*scratch).into(), new.into(), address.into());
+                }
+                _ => unreachable!(),
+            }
             self.asm.cbnz_w((*scratch).into(), loop_start);
         }
     }
@@ -1009,25 +1293,13 @@ impl MacroAssembler {
         new: Reg,
         address: Reg,
     ) -> Reg {
-        if has_lse_atomics() {
-            self.asm
-                .casal_w(expected.into(), new.into(), address.into());
-            expected
-        } else {
-            let current = self.get_scratch();
-            let state = self.get_scratch();
-            let loop_start = self.asm.create_and_bind_label();
-            let loop_end = self.asm.create_label();
-            self.asm.ldaxr_w((*current).into(), address.into());
-            self.asm.cmp_w((*current).into(), expected.into());
-            self.asm.bc_l(Cond::NE, loop_end);
-            self.asm
-                .stlxr_w((*state).into(), new.into(), address.into());
-            self.asm.cbnz_w((*state).into(), loop_start);
-            self.asm.bind_label(loop_end);
-
-            *current
-        }
+        self.compare_exchange_synchronized(
+            MachineMode::Int32,
+            expected,
+            new,
+            address,
+            has_lse_atomics(),
+        )
     }
 
     pub fn compare_exchange_int64_synchronized(
@@ -1036,19 +1308,56 @@ impl MacroAssembler {
         new: Reg,
         address: Reg,
     ) -> Reg {
-        if has_lse_atomics() {
-            self.asm.casal(expected.into(), new.into(), address.into());
+        self.compare_exchange_synchronized(
+            MachineMode::Int64,
+            expected,
+            new,
+            address,
+            has_lse_atomics(),
+        )
+    }
+
+    // Returns the register holding the value found at `address`, the
+    // exchange succeeded iff it is equal to `expected`.
+    fn compare_exchange_synchronized(
+        &mut self,
+        mode: MachineMode,
+        expected: Reg,
+        new: Reg,
+        address: Reg,
+        lse: bool,
+    ) -> Reg {
+        if lse {
+            match mode {
+                MachineMode::Int32 => self
+                    .asm
+                    .casal_w(expected.into(), new.into(), address.into()),
+                MachineMode::Int64 => self.asm.casal(expected.into(), new.into(), address.into()),
+                _ => unreachable!(),
+            }
             expected
         } else {
             let current = self.get_scratch();
             let state = self.get_scratch();
             let loop_start = self.asm.create_and_bind_label();
             let loop_end = self.asm.create_label();
-            self.asm.ldaxr((*current).into(), address.into());
-            self.asm.cmp((*current).into(), expected.into());
-            self.asm.bc_l(Cond::NE, loop_end);
-            self.asm.stlxr((*state).into(), new.into(), address.into());
-            self.asm.cbnz((*state).into(), loop_start);
+            match mode {
+                MachineMode::Int32 => {
+                    self.asm.ldaxr_w((*current).into(), address.into());
+                    self.asm.cmp_w((*current).into(), expected.into());
+                    self.asm.bc_l(Cond::NE, loop_end);
+                    self.asm
+                        .stlxr_w((*state).into(), new.into(), address.into());
+                }
+                MachineMode::Int64 => {
+                    self.asm.ldaxr((*current).into(), address.into());
+                    self.asm.cmp((*current).into(), expected.into());
+                    self.asm.bc_l(Cond::NE, loop_end);
+                    self.asm.stlxr((*state).into(), new.into(), address.into());
+                }
+                _ => unreachable!(),
+            }
+            self.asm.cbnz_w((*state).into(), loop_start);
             self.asm.bind_label(loop_end);
 
             *current
@@ -1056,45 +1365,71 @@ impl MacroAssembler {
     }
 
     pub fn fetch_add_int32_synchronized(&mut self, previous: Reg, value: Reg, address: Reg) -> Reg {
-        if has_lse_atomics() {
-            self.asm
-                .ldaddal_w(value.into(), previous.into(), address.into());
-            previous
-        } else {
-            let new_value = self.get_scratch();
-            let state = self.get_scratch();
-
-            let loop_start = self.asm.create_and_bind_label();
-            self.asm.ldaxr_w(previous.into(), address.into());
-            self.asm
-                .add_w((*new_value).into(), previous.into(), value.into());
-            self.asm
-                .stlxr_w((*state).into(), (*new_value).into(), address.into());
-            self.asm.cbnz_w((*state).into(), loop_start);
-
-            previous
-        }
+        self.fetch_add_synchronized(
+            MachineMode::Int32,
+            previous,
+            value,
+            address,
+            has_lse_atomics(),
+        )
     }
 
     pub fn fetch_add_int64_synchronized(&mut self, previous: Reg, value: Reg, address: Reg) -> Reg {
-        if has_lse_atomics() {
-            self.asm
-                .ldaddal(value.into(), previous.into(), address.into());
-            previous
+        self.fetch_add_synchronized(
+            MachineMode::Int64,
+            previous,
+            value,
+            address,
+            has_lse_atomics(),
+        )
+    }
+
+    fn fetch_add_synchronized(
+        &mut self,
+        mode: MachineMode,
+        previous: Reg,
+        value: Reg,
+        address: Reg,
+        lse: bool,
+    ) -> Reg {
+        if lse {
+            match mode {
+                MachineMode::Int32 => {
+                    self.asm
+                        .ldaddal_w(value.into(), previous.into(), address.into())
+                }
+                MachineMode::Int64 => {
+                    self.asm
+                        .ldaddal(value.into(), previous.into(), address.into())
+                }
+                _ => unreachable!(),
+            }
         } else {
             let new_value = self.get_scratch();
             let state = self.get_scratch();
 
             let loop_start = self.asm.create_and_bind_label();
-            self.asm.ldaxr(previous.into(), address.into());
-            self.asm
-                .add((*new_value).into(), previous.into(), value.into());
-            self.asm
-                .stlxr((*state).into(), (*new_value).into(), address.into());
+            match mode {
+                MachineMode::Int32 => {
+                    self.asm.ldaxr_w(previous.into(), address.into());
+                    self.asm
+                        .add_w((*new_value).into(), previous.into(), value.into());
+                    self.asm
+                        .stlxr_w((*state).into(), (*new_value).into(), address.into());
+                }
+                MachineMode::Int64 => {
+                    self.asm.ldaxr(previous.into(), address.into());
+                    self.asm
+                        .add((*new_value).into(), previous.into(), value.into());
+                    self.asm
+                        .stlxr((*state).into(), (*new_value).into(), address.into());
+                }
+                _ => unreachable!(),
+            }
             self.asm.cbnz_w((*state).into(), loop_start);
-
-            previous
         }
+
+        previous
     }
 
     pub fn load_mem(&mut self, mode: MachineMode, dest: AnyReg, mem: Mem) {
@@ -1123,6 +1458,13 @@ impl MacroAssembler {
                         Extend::LSL,
                         0,
                     ),
+                    MachineMode::Int16 => self.asm.ldr_ind_h(
+                        dest.reg().into(),
+                        (*scratch).into(),
+                        index.into(),
+                        Extend::LSL,
+                        1,
+                    ),
                     MachineMode::Int32 => self.asm.ldr_ind_w(
                         dest.reg().into(),
                         (*scratch).into(),
@@ -1172,6 +1514,7 @@ impl MacroAssembler {
             let disp = (disp / mode.size()) as u32;
             match mode {
                 MachineMode::Int8 => self.asm.ldr_imm_b(dest.reg().into(), base.into(), disp),
+                MachineMode::Int16 => self.asm.ldr_imm_h(dest.reg().into(), base.into(), disp),
                 MachineMode::Int32 => self.asm.ldr_imm_w(dest.reg().into(), base.into(), disp),
                 MachineMode::IntPtr | MachineMode::Int64 | MachineMode::Ptr => {
                     self.asm.ldr_imm(dest.reg().into(), base.into(), disp)
@@ -1184,6 +1527,9 @@ impl MacroAssembler {
                 MachineMode::Int8 => self
                     .asm
                     .ldr_unscaled_b(dest.reg().into(), base.into(), disp),
+                MachineMode::Int16 => self
+                    .asm
+                    .ldr_unscaled_h(dest.reg().into(), base.into(), disp),
                 MachineMode::Int32 => self
                     .asm
                     .ldr_unscaled_w(dest.reg().into(), base.into(), disp),
@@ -1210,6 +1556,13 @@ impl MacroAssembler {
                     Extend::LSL,
                     0,
                 ),
+                MachineMode::Int16 => self.asm.ldr_ind_h(
+                    dest.reg().into(),
+                    base.into(),
+                    (*scratch).into(),
+                    Extend::LSL,
+                    0,
+                ),
                 MachineMode::Int32 => self.asm.ldr_ind_w(
                     dest.reg().into(),
                     base.into(),
@@ -1339,6 +1692,13 @@ impl MacroAssembler {
                         Extend::LSL,
                         0,
                     ),
+                    MachineMode::Int16 => self.asm.str_ind_h(
+                        src.reg().into(),
+                        (*scratch).into(),
+                        index.into(),
+                        Extend::LSL,
+                        1,
+                    ),
                     MachineMode::Int32 => self.asm.str_ind_w(
                         src.reg().into(),
                         (*scratch).into(),
@@ -1394,6 +1754,7 @@ impl MacroAssembler {
             let offset = (offset / mode.size()) as u32;
             match mode {
                 MachineMode::Int8 => self.asm.str_imm_b(src.reg().into(), base.into(), offset),
+                MachineMode::Int16 => self.asm.str_imm_h(src.reg().into(), base.into(), offset),
                 MachineMode::Int32 => self.asm.str_imm_w(src.reg().into(), base.into(), offset),
                 MachineMode::IntPtr | MachineMode::Int64 | MachineMode::Ptr => {
                     self.asm.str_imm(src.reg().into(), base.into(), offset)
@@ -1406,6 +1767,10 @@ impl MacroAssembler {
                 MachineMode::Int8 => self
                     .asm
                     .str_unscaled_b(src.reg().into(), base.into(), offset),
+                MachineMode::Int16 => {
+                    self.asm
+                        .str_unscaled_h(src.reg().into(), base.into(), offset)
+                }
                 MachineMode::Int32 => {
                     self.asm
                         .str_unscaled_w(src.reg().into(), base.into(), offset)
@@ -1433,6 +1798,13 @@ impl MacroAssembler {
                     Extend::LSL,
                     0,
                 ),
+                MachineMode::Int16 => self.asm.str_ind_h(
+                    src.reg().into(),
+                    base.into(),
+                    (*scratch).into(),
+                    Extend::LSL,
+                    0,
+                ),
                 MachineMode::Int32 => self.asm.str_ind_w(
                     src.reg().into(),
                     base.into(),
@@ -1467,7 +1839,7 @@ impl MacroAssembler {
 
     pub fn copy_reg(&mut self, mode: MachineMode, dest: Reg, src: Reg) {
         match mode {
-            MachineMode::Int32 | MachineMode::Int8 => {
+            MachineMode::Int32 | MachineMode::Int16 | MachineMode::Int8 => {
                 self.asm.mov_w(dest.into(), src.into());
             }
             MachineMode::Ptr | MachineMode::Int64 => {
@@ -1529,7 +1901,7 @@ impl MacroAssembler {
     pub fn load_int_const(&mut self, mode: MachineMode, dest: Reg, imm: i64) {
         let sf = size_flag(mode);
         let register_size = match mode {
-            MachineMode::Int8 => 32,
+            MachineMode::Int8 | MachineMode::Int16 => 32,
             MachineMode::Int32 => 32,
             MachineMode::IntPtr | MachineMode::Ptr | MachineMode::Int64 => 64,
             MachineMode::Float32 | MachineMode::Float64 => unreachable!(),
@@ -1664,7 +2036,7 @@ enum JumpType {
 
 fn size_flag(mode: MachineMode) -> u32 {
     match mode {
-        MachineMode::Int8 | MachineMode::Int32 => 0,
+        MachineMode::Int8 | MachineMode::Int16 | MachineMode::Int32 => 0,
         MachineMode::IntPtr | MachineMode::Ptr | MachineMode::Int64 => 1,
         MachineMode::Float32 | MachineMode::Float64 => unimplemented!(),
     }
@@ -1679,7 +2051,9 @@ impl From<FReg> for NeonRegister {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::mem;
     use crate::mode::MachineMode::{Int32, Ptr};
+    use crate::os;
     use byteorder::{LittleEndian, WriteBytesExt};
 
     macro_rules! assert_emit {
@@ -1820,4 +2194,221 @@ mod tests {
         masm.load_int_const(Ptr, R0, !0x10001);
         assert_emit!(0x92800020, 0xF2BFFFC0; masm);
     }
+
+    #[test]
+    fn test_exchange_synchronized_encoding() {
+        let mut masm = MacroAssembler::new();
+        masm.exchange_synchronized(MachineMode::Int64, R1, R2, R0, false);
+        assert_emit!(0xC85FFC01, 0xC809FC02, 0x35FFFFC9; masm);
+
+        let mut masm = MacroAssembler::new();
+        masm.exchange_synchronized(MachineMode::Int32, R1, R2, R0, false);
+        assert_emit!(0x885FFC01, 0x8809FC02, 0x35FFFFC9; masm);
+
+        let mut masm = MacroAssembler::new();
+        masm.exchange_synchronized(MachineMode::Int64, R1, R2, R0, true);
+        assert_emit!(0xF8E28001; masm);
+
+        let mut masm = MacroAssembler::new();
+        masm.exchange_synchronized(MachineMode::Int32, R1, R2, R0, true);
+        assert_emit!(0xB8E28001; masm);
+    }
+
+    #[test]
+    fn test_compare_exchange_synchronized_encoding() {
+        let mut masm = MacroAssembler::new();
+        let result = masm.compare_exchange_synchronized(MachineMode::Int64, R1, R2, R0, false);
+        assert_eq!(result, R9);
+        assert_emit!(0xC85FFC09, 0xEB01013F, 0x54000061, 0xC80CFC02, 0x35FFFF8C; masm);
+
+        let mut masm = MacroAssembler::new();
+        masm.compare_exchange_synchronized(MachineMode::Int32, R1, R2, R0, false);
+        assert_emit!(0x885FFC09, 0x6B01013F, 0x54000061, 0x880CFC02, 0x35FFFF8C; masm);
+
+        let mut masm = MacroAssembler::new();
+        let result = masm.compare_exchange_synchronized(MachineMode::Int64, R1, R2, R0, true);
+        assert_eq!(result, R1);
+        assert_emit!(0xC8E1FC02; masm);
+
+        let mut masm = MacroAssembler::new();
+        masm.compare_exchange_synchronized(MachineMode::Int32, R1, R2, R0, true);
+        assert_emit!(0x88E1FC02; masm);
+    }
+
+    #[test]
+    fn test_fetch_add_synchronized_encoding() {
+        let mut masm = MacroAssembler::new();
+        masm.fetch_add_synchronized(MachineMode::Int64, R1, R2, R0, false);
+        assert_emit!(0xC85FFC01, 0x8B020029, 0xC80CFC09, 0x35FFFFAC; masm);
+
+        let mut masm = MacroAssembler::new();
+        masm.fetch_add_synchronized(MachineMode::Int32, R1, R2, R0, false);
+        assert_emit!(0x885FFC01, 0x0B020029, 0x880CFC09, 0x35FFFFAC; masm);
+
+        let mut masm = MacroAssembler::new();
+        masm.fetch_add_synchronized(MachineMode::Int64, R1, R2, R0, true);
+        assert_emit!(0xF8E20001; masm);
+
+        let mut masm = MacroAssembler::new();
+        masm.fetch_add_synchronized(MachineMode::Int32, R1, R2, R0, true);
+        assert_emit!(0xB8E20001; masm);
+    }
+
+    // Copies the generated code into executable memory and passes it as a
+    // function pointer of type `F` to `f`.
+    fn execute<F: Copy, R>(masm: MacroAssembler, f: impl FnOnce(F) -> R) -> R {
+        assert_eq!(std::mem::size_of::<F>(), std::mem::size_of::<*const u8>());
+        let code = masm.data();
+
+        let size = mem::page_align(code.len());
+        let ptr = os::commit(size, true);
+
+        let result = unsafe {
+            std::ptr::copy_nonoverlapping(code.as_ptr(), ptr.to_mut_ptr::<u8>(), code.len());
+            flush_icache(ptr.to_ptr::<u8>(), code.len());
+            f(std::mem::transmute_copy(&ptr.to_ptr::<u8>()))
+        };
+
+        os::free(ptr, size);
+        result
+    }
+
+    #[derive(Copy, Clone)]
+    enum Atomic {
+        Exchange,
+        CompareExchange,
+        FetchAdd,
+    }
+
+    // Emits `fn(*mut u64, u64, u64) -> u64` performing the atomic operation
+    // on the first argument and returns the result together with the memory
+    // value afterwards.
+    fn run_atomic(
+        op: Atomic,
+        mode: MachineMode,
+        lse: bool,
+        initial: u64,
+        lhs: u64,
+        rhs: u64,
+    ) -> (u64, u64) {
+        let mut masm = MacroAssembler::new();
+        let address = REG_PARAMS[0];
+
+        let result = match op {
+            Atomic::Exchange => {
+                masm.exchange_synchronized(mode, R3, REG_PARAMS[1], address, lse);
+                R3
+            }
+            Atomic::CompareExchange => {
+                masm.compare_exchange_synchronized(mode, REG_PARAMS[1], REG_PARAMS[2], address, lse)
+            }
+            Atomic::FetchAdd => masm.fetch_add_synchronized(mode, R3, REG_PARAMS[1], address, lse),
+        };
+
+        masm.copy_reg(MachineMode::Int64, REG_RESULT, result);
+        masm.asm.ret(REG_LR.into());
+
+        let mut memory = initial;
+        let result = execute(masm, |fct: extern "C" fn(*mut u64, u64, u64) -> u64| {
+            fct(&mut memory, lhs, rhs)
+        });
+
+        if mode.is64() {
+            (result, memory)
+        } else {
+            (result as u32 as u64, memory)
+        }
+    }
+
+    // The LL/SC sequences run everywhere, the LSE ones only when the CPU
+    // supports them.
+    fn lse_variants() -> Vec<bool> {
+        if has_lse_atomics() {
+            vec![false, true]
+        } else {
+            vec![false]
+        }
+    }
+
+    #[test]
+    fn test_exchange_synchronized() {
+        for lse in lse_variants() {
+            assert_eq!(
+                run_atomic(Atomic::Exchange, MachineMode::Int64, lse, 1, 2, 0),
+                (1, 2)
+            );
+            assert_eq!(
+                run_atomic(
+                    Atomic::Exchange,
+                    MachineMode::Int32,
+                    lse,
+                    0x1_0000_0001,
+                    2,
+                    0
+                ),
+                (1, 0x1_0000_0002)
+            );
+        }
+    }
+
+    #[test]
+    fn test_compare_exchange_synchronized() {
+        for lse in lse_variants() {
+            assert_eq!(
+                run_atomic(Atomic::CompareExchange, MachineMode::Int64, lse, 5, 5, 7),
+                (5, 7)
+            );
+            assert_eq!(
+                run_atomic(Atomic::CompareExchange, MachineMode::Int64, lse, 5, 6, 7),
+                (5, 5)
+            );
+            assert_eq!(
+                run_atomic(
+                    Atomic::CompareExchange,
+                    MachineMode::Int32,
+                    lse,
+                    0x1_0000_0005,
+                    5,
+                    7
+                ),
+                (5, 0x1_0000_0007)
+            );
+            assert_eq!(
+                run_atomic(
+                    Atomic::CompareExchange,
+                    MachineMode::Int32,
+                    lse,
+                    0x1_0000_0005,
+                    6,
+                    7
+                ),
+                (5, 0x1_0000_0005)
+            );
+        }
+    }
+
+    #[test]
+    fn test_fetch_add_synchronized() {
+        for lse in lse_variants() {
+            assert_eq!(
+                run_atomic(Atomic::FetchAdd, MachineMode::Int64, lse, 10, 5, 0),
+                (10, 15)
+            );
+            assert_eq!(
+                run_atomic(Atomic::FetchAdd, MachineMode::Int64, lse, u64::MAX, 2, 0),
+                (u64::MAX, 1)
+            );
+            assert_eq!(
+                run_atomic(
+                    Atomic::FetchAdd,
+                    MachineMode::Int32,
+                    lse,
+                    0x1_FFFF_FFFF,
+                    2,
+                    0
+                ),
+                (0xFFFF_FFFF, 0x1_0000_0001)
+            );
+        }
+    }
 }
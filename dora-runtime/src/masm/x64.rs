@@ -2,12 +2,12 @@ use crate::compiler::codegen::AnyReg;
 use crate::cpu::*;
 use crate::gc::swiper::CARD_SIZE_BITS;
 use crate::gc::Address;
-use crate::masm::{CondCode, Label, MacroAssembler, Mem};
+use crate::masm::{CondCode, Label, MacroAssembler, Mem, PrefetchHint};
 use crate::mem::{fits_i32, ptr_width};
 use crate::mode::MachineMode;
 use crate::object::{offset_of_array_data, offset_of_array_length, Header};
 use crate::threads::ThreadLocalData;
-use crate::vm::{get_vm, LazyCompilationSite, Trap};
+use crate::vm::{get_vm, GcPoint, LazyCompilationSite, Trap};
 use crate::vtable::VTable;
 pub use dora_asm::x64::AssemblerX64 as Assembler;
 use dora_asm::x64::Register as AsmRegister;
@@ -136,6 +136,90 @@ impl MacroAssembler {
         self.emit_lazy_compilation_site(lazy_compilation_site);
     }
 
+    // Virtual call with a monomorphic inline cache. The call site owns two
+    // constant pool slots: the vtable of the first receiver seen and the
+    // method it resolved to. A hit calls the cached method without touching
+    // the vtable. The first miss goes through the lazy compilation stub,
+    // which fills the cache; once filled, other receivers use the vtable.
+    pub fn virtual_call_cached(
+        &mut self,
+        location: Location,
+        gcpoint: GcPoint,
+        vtable_index: u32,
+        self_index: u32,
+        lazy_compilation_site: LazyCompilationSite,
+    ) {
+        let obj = REG_PARAMS[self_index as usize];
+        self.test_if_nil_bailout(location, obj, Trap::NIL);
+
+        let cache_vtable_disp = self.add_addr(Address::null());
+        let cache_target_disp = self.add_addr(Address::null());
+        let stub_disp = self.add_addr(get_vm().stubs.lazy_compilation());
+
+        let lbl_miss = self.create_label();
+        let lbl_uncached = self.create_label();
+        let lbl_done = self.create_label();
+
+        // REG_RESULT = [obj] (load vtable)
+        self.load_mem(MachineMode::Ptr, REG_RESULT.into(), Mem::Base(obj, 0));
+
+        let pos = self.pos() as i32;
+        self.load_constpool(REG_TMP1, cache_vtable_disp + pos);
+        self.cmp_reg(MachineMode::Ptr, REG_RESULT, REG_TMP1);
+        self.jump_if(CondCode::NotEqual, lbl_miss);
+
+        // cache hit: call cached method
+        let pos = self.pos() as i32;
+        self.load_constpool(REG_RESULT, cache_target_disp + pos);
+        self.call_reg(REG_RESULT);
+        self.emit_position(location);
+        self.emit_gcpoint(gcpoint.clone());
+        self.jump(lbl_done);
+
+        // cache miss: fill empty cache through the lazy compilation stub
+        self.bind_label(lbl_miss);
+        self.cmp_reg_imm(MachineMode::Ptr, REG_TMP1, 0);
+        self.jump_if(CondCode::NotEqual, lbl_uncached);
+
+        let pos = self.pos() as i32;
+        self.load_constpool(REG_RESULT, stub_disp + pos);
+        self.call_reg(REG_RESULT);
+
+        let pos = self.pos() as i32;
+        let (receiver_is_first, fct_id, type_params) = match lazy_compilation_site {
+            LazyCompilationSite::Virtual(receiver_is_first, fct_id, _, ref type_params) => {
+                (receiver_is_first, fct_id, type_params.clone())
+            }
+            _ => unreachable!(),
+        };
+        self.emit_lazy_compilation_site(LazyCompilationSite::VirtualCache(
+            receiver_is_first,
+            fct_id,
+            vtable_index,
+            type_params,
+            cache_vtable_disp + pos,
+            cache_target_disp + pos,
+        ));
+        self.emit_position(location);
+        self.emit_gcpoint(gcpoint.clone());
+        self.jump(lbl_done);
+
+        // cache already holds another receiver: regular vtable dispatch
+        self.bind_label(lbl_uncached);
+        let disp = VTable::offset_of_method_table() + (vtable_index as i32) * ptr_width();
+        self.load_mem(
+            MachineMode::Ptr,
+            REG_RESULT.into(),
+            Mem::Base(REG_RESULT, disp),
+        );
+        self.call_reg(REG_RESULT);
+        self.emit_lazy_compilation_site(lazy_compilation_site);
+        self.emit_position(location);
+        self.emit_gcpoint(gcpoint);
+
+        self.bind_label(lbl_done);
+    }
+
     pub fn load_array_elem(&mut self, mode: MachineMode, dest: AnyReg, array: Reg, index: Reg) {
         self.load_mem(
             mode,
@@ -191,15 +275,23 @@ impl MacroAssembler {
         self.asm.xorl_rr(dest.into(), dest.into());
         match mode {
             MachineMode::Int64 => self.asm.cmpq_rr(lhs.into(), rhs.into()),
-            MachineMode::Int8 | MachineMode::Int32 => self.asm.cmpl_rr(lhs.into(), rhs.into()),
+            MachineMode::Int8 | MachineMode::Int16 | MachineMode::Int32 => {
+                self.asm.cmpl_rr(lhs.into(), rhs.into())
+            }
             _ => unreachable!(),
         }
-        self.asm.setcc_r(Condition::Above, dest.into());
+
+        // UInt8 and UInt16 are unsigned, Int32 and Int64 need a signed comparison.
+        let (greater, less) = match mode {
+            MachineMode::Int8 | MachineMode::Int16 => (Condition::Above, Condition::Below),
+            _ => (Condition::Greater, Condition::Less),
+        };
+
+        self.asm.setcc_r(greater, dest.into());
 
         let scratch = self.get_scratch();
         self.asm.movl_ri((*scratch).into(), Immediate(-1));
-        self.asm
-            .cmovl(Condition::Below, dest.into(), (*scratch).into());
+        self.asm.cmovl(less, dest.into(), (*scratch).into());
     }
 
     pub fn float_cmp_int(&mut self, mode: MachineMode, dest: Reg, lhs: FReg, rhs: FReg) {
@@ -330,6 +422,33 @@ impl MacroAssembler {
         self.asm.jmp_r(reg.into());
     }
 
+    // Jumps to `targets[index]`, index needs to be in bounds and zero-extended.
+    pub fn jump_table(&mut self, index: Reg, targets: Vec<Label>) {
+        let disp = self.constpool.add_jump_table(targets.len());
+        self.jump_tables.push((disp, targets));
+
+        let table = self.get_scratch();
+        let pos = self.pos() as i32;
+
+        // next instruction has 7 bytes
+        self.asm
+            .lea((*table).into(), AsmAddress::rip(-(disp + pos + 7)));
+        self.load_mem(
+            MachineMode::Int32,
+            index.into(),
+            Mem::Index(*table, index, 4, 0),
+        );
+        self.asm.addq_rr(index.into(), (*table).into());
+        self.asm.jmp_r(index.into());
+    }
+
+    pub fn increment_counter(&mut self, counter: Address) {
+        let scratch = self.get_scratch();
+        self.load_int_const(MachineMode::Ptr, *scratch, counter.to_usize() as i64);
+        self.asm
+            .addq_ai(AsmAddress::offset((*scratch).into(), 0), Immediate(1));
+    }
+
     pub fn int_div(
         &mut self,
         mode: MachineMode,
@@ -431,6 +550,75 @@ impl MacroAssembler {
         }
     }
 
+    pub fn int_mul_imm(&mut self, mode: MachineMode, dest: Reg, lhs: Reg, value: i64) {
+        let x64 = mode.is64();
+        let value = if x64 { value } else { value as i32 as i64 };
+
+        match value {
+            0 => {
+                self.load_int_const(mode, dest, 0);
+                return;
+            }
+
+            1 => {
+                if dest != lhs {
+                    self.mov_rr(x64, dest.into(), lhs.into());
+                }
+                return;
+            }
+
+            3 | 5 | 9 => {
+                let factor = match value {
+                    3 => ScaleFactor::Two,
+                    5 => ScaleFactor::Four,
+                    9 => ScaleFactor::Eight,
+                    _ => unreachable!(),
+                };
+
+                // lhs + lhs * (value - 1)
+                let address = AsmAddress::array(lhs.into(), lhs.into(), factor, 0);
+
+                if x64 {
+                    self.asm.lea(dest.into(), address);
+                } else {
+                    self.asm.leal(dest.into(), address);
+                }
+                return;
+            }
+
+            _ if value > 0 && (value as u64).is_power_of_two() => {
+                if dest != lhs {
+                    self.mov_rr(x64, dest.into(), lhs.into());
+                }
+
+                let shift = Immediate(value.trailing_zeros() as i64);
+
+                if x64 {
+                    self.asm.shlq_ri(dest.into(), shift);
+                } else {
+                    self.asm.shll_ri(dest.into(), shift);
+                }
+                return;
+            }
+
+            _ => {}
+        }
+
+        if !fits_i32(value) {
+            assert!(x64);
+            let reg_size = self.get_scratch();
+            self.load_int_const(mode, *reg_size, value);
+            self.int_mul(mode, dest, lhs, *reg_size);
+            return;
+        }
+
+        if x64 {
+            self.asm.imulq_ri(dest.into(), lhs.into(), Immediate(value));
+        } else {
+            self.asm.imull_ri(dest.into(), lhs.into(), Immediate(value));
+        }
+    }
+
     pub fn int_mul_checked(
         &mut self,
         mode: MachineMode,
@@ -489,6 +677,98 @@ impl MacroAssembler {
         }
     }
 
+    // the int_*_overflows functions clobber lhs and set dest to 1 if the
+    // operation overflows and to 0 otherwise.
+    pub fn int_add_overflows(&mut self, mode: MachineMode, dest: Reg, lhs: Reg, rhs: Reg) {
+        if mode.is64() {
+            self.asm.addq_rr(lhs.into(), rhs.into());
+        } else {
+            self.asm.addl_rr(lhs.into(), rhs.into());
+        }
+
+        self.set_overflow(dest);
+    }
+
+    pub fn int_sub_overflows(&mut self, mode: MachineMode, dest: Reg, lhs: Reg, rhs: Reg) {
+        if mode.is64() {
+            self.asm.subq_rr(lhs.into(), rhs.into());
+        } else {
+            self.asm.subl_rr(lhs.into(), rhs.into());
+        }
+
+        self.set_overflow(dest);
+    }
+
+    pub fn int_mul_overflows(&mut self, mode: MachineMode, dest: Reg, lhs: Reg, rhs: Reg) {
+        if mode.is64() {
+            self.asm.imulq_rr(lhs.into(), rhs.into());
+        } else {
+            self.asm.imull_rr(lhs.into(), rhs.into());
+        }
+
+        self.set_overflow(dest);
+    }
+
+    fn set_overflow(&mut self, dest: Reg) {
+        self.asm.setcc_r(Condition::Overflow, dest.into());
+        self.asm.movzxb_rr(dest.into(), dest.into());
+    }
+
+    pub fn int_add_saturating(&mut self, mode: MachineMode, dest: Reg, lhs: Reg, rhs: Reg) {
+        let bound = self.get_scratch();
+        self.int_saturation_bound(mode, *bound, lhs);
+
+        if mode.is64() {
+            self.asm.addq_rr(lhs.into(), rhs.into());
+            self.asm
+                .cmovq(Condition::Overflow, lhs.into(), (*bound).into());
+        } else {
+            self.asm.addl_rr(lhs.into(), rhs.into());
+            self.asm
+                .cmovl(Condition::Overflow, lhs.into(), (*bound).into());
+        }
+
+        if dest != lhs {
+            self.mov_rr(mode.is64(), dest.into(), lhs.into());
+        }
+    }
+
+    pub fn int_sub_saturating(&mut self, mode: MachineMode, dest: Reg, lhs: Reg, rhs: Reg) {
+        let bound = self.get_scratch();
+        self.int_saturation_bound(mode, *bound, lhs);
+
+        if mode.is64() {
+            self.asm.subq_rr(lhs.into(), rhs.into());
+            self.asm
+                .cmovq(Condition::Overflow, lhs.into(), (*bound).into());
+        } else {
+            self.asm.subl_rr(lhs.into(), rhs.into());
+            self.asm
+                .cmovl(Condition::Overflow, lhs.into(), (*bound).into());
+        }
+
+        if dest != lhs {
+            self.mov_rr(mode.is64(), dest.into(), lhs.into());
+        }
+    }
+
+    // On overflow of lhs + rhs or lhs - rhs the exact result has the sign
+    // of lhs, so the result saturates to MAX for non-negative lhs and to
+    // MIN otherwise.
+    fn int_saturation_bound(&mut self, mode: MachineMode, dest: Reg, lhs: Reg) {
+        if mode.is64() {
+            let max = self.get_scratch();
+            self.load_int_const(MachineMode::Int64, *max, i64::MAX);
+            self.asm.movq_rr(dest.into(), lhs.into());
+            self.asm.shrq_ri(dest.into(), Immediate(63));
+            self.asm.addq_rr(dest.into(), (*max).into());
+        } else {
+            self.asm.movl_rr(dest.into(), lhs.into());
+            self.asm.sarl_ri(dest.into(), Immediate(31));
+            self.asm.xorl_ri(dest.into(), Immediate(i32::MAX as i64));
+        }
+    }
+
     pub fn int_add_imm(&mut self, mode: MachineMode, dest: Reg, lhs: Reg, value: i64) {
         if !fits_i32(value) {
             assert!(mode == MachineMode::Int64 || mode == MachineMode::Ptr);
@@ -669,6 +949,11 @@ impl MacroAssembler {
     }
 
     pub fn count_bits(&mut self, mode: MachineMode, dest: Reg, src: Reg, count_one_bits: bool) {
+        if !has_popcnt() {
+            self.count_bits_generic(mode, dest, src, count_one_bits);
+            return;
+        }
+
         if count_one_bits {
             if mode.is64() {
                 self.asm.popcntq_rr(dest.into(), src.into());
@@ -693,6 +978,11 @@ impl MacroAssembler {
         src: Reg,
         count_one_bits: bool,
     ) {
+        if !has_lzcnt() {
+            self.count_bits_leading_generic(mode, dest, src, count_one_bits);
+            return;
+        }
+
         if count_one_bits {
             if mode.is64() {
                 self.asm.notq(src.into());
@@ -717,6 +1007,11 @@ impl MacroAssembler {
         src: Reg,
         count_one_bits: bool,
     ) {
+        if !has_tzcnt() {
+            self.count_bits_trailing_generic(mode, dest, src, count_one_bits);
+            return;
+        }
+
         if count_one_bits {
             if mode.is64() {
                 self.asm.notq(src.into());
@@ -734,6 +1029,107 @@ impl MacroAssembler {
         }
     }
 
+    // Loads src into a scratch register and inverts it when zero bits
+    // should be counted. 32-bit values are zero-extended, which allows
+    // the generic implementations below to work on the full register.
+    fn count_bits_load(&mut self, mode: MachineMode, value: Reg, src: Reg, invert: bool) {
+        if mode.is64() {
+            self.asm.movq_rr(value.into(), src.into());
+        } else {
+            self.asm.movl_rr(value.into(), src.into());
+        }
+
+        if invert {
+            if mode.is64() {
+                self.asm.notq(value.into());
+            } else {
+                self.asm.notl(value.into());
+            }
+        }
+    }
+
+    // popcnt replacement: clears the lowest set bit until the value is zero.
+    pub fn count_bits_generic(
+        &mut self,
+        mode: MachineMode,
+        dest: Reg,
+        src: Reg,
+        count_one_bits: bool,
+    ) {
+        let value = self.get_scratch();
+        let tmp = self.get_scratch();
+        let lbl_loop = self.create_label();
+        let lbl_done = self.create_label();
+
+        self.count_bits_load(mode, *value, src, !count_one_bits);
+        self.asm.xorl_rr(dest.into(), dest.into());
+
+        self.bind_label(lbl_loop);
+        self.asm.testq_rr((*value).into(), (*value).into());
+        self.jump_if(CondCode::Zero, lbl_done);
+        self.asm.movq_rr((*tmp).into(), (*value).into());
+        self.asm.subq_ri((*tmp).into(), Immediate(1));
+        self.asm.andq_rr((*value).into(), (*tmp).into());
+        self.asm.addl_ri(dest.into(), Immediate(1));
+        self.jump(lbl_loop);
+        self.bind_label(lbl_done);
+    }
+
+    // lzcnt replacement: shifts the value right until it is zero, the number
+    // of leading zeros is the bit width minus the number of shifts.
+    pub fn count_bits_leading_generic(
+        &mut self,
+        mode: MachineMode,
+        dest: Reg,
+        src: Reg,
+        count_one_bits: bool,
+    ) {
+        let value = self.get_scratch();
+        let lbl_loop = self.create_label();
+        let lbl_done = self.create_label();
+
+        self.count_bits_load(mode, *value, src, count_one_bits);
+        self.asm
+            .movl_ri(dest.into(), Immediate(mode.size() as i64 * 8));
+
+        self.bind_label(lbl_loop);
+        self.asm.testq_rr((*value).into(), (*value).into());
+        self.jump_if(CondCode::Zero, lbl_done);
+        self.asm.shrq_ri((*value).into(), Immediate(1));
+        self.asm.subq_ri(dest.into(), Immediate(1));
+        self.jump(lbl_loop);
+        self.bind_label(lbl_done);
+    }
+
+    // tzcnt replacement: shifts the value right until the lowest bit is set,
+    // zero is handled upfront and results in the bit width.
+    pub fn count_bits_trailing_generic(
+        &mut self,
+        mode: MachineMode,
+        dest: Reg,
+        src: Reg,
+        count_one_bits: bool,
+    ) {
+        let value = self.get_scratch();
+        let lbl_loop = self.create_label();
+        let lbl_done = self.create_label();
+
+        self.count_bits_load(mode, *value, src, count_one_bits);
+        self.asm
+            .movl_ri(dest.into(), Immediate(mode.size() as i64 * 8));
+        self.asm.testq_rr((*value).into(), (*value).into());
+        self.jump_if(CondCode::Zero, lbl_done);
+        self.asm.xorl_rr(dest.into(), dest.into());
+
+        self.bind_label(lbl_loop);
+        self.asm.testl_ri((*value).into(), Immediate(1));
+        self.jump_if(CondCode::NonZero, lbl_done);
+        self.asm.shrq_ri((*value).into(), Immediate(1));
+        self.asm.addl_ri(dest.into(), Immediate(1));
+        self.jump(lbl_loop);
+        self.bind_label(lbl_done);
+    }
+
     pub fn int_to_float(
         &mut self,
         dest_mode: MachineMode,
@@ -898,6 +1294,41 @@ impl MacroAssembler {
         self.emit_bailout(lbl, Trap::INDEX_OUT_OF_BOUNDS, location);
     }
 
+    pub fn check_length_out_of_bounds(&mut self, location: Location, array: Reg, length: Reg) {
+        let scratch = self.get_scratch();
+        self.load_mem(
+            MachineMode::Int64,
+            (*scratch).into(),
+            Mem::Base(array, offset_of_array_length()),
+        );
+        self.asm.cmpq_rr(length.into(), (*scratch).into());
+
+        let lbl = self.create_label();
+        self.jump_if(CondCode::UnsignedGreater, lbl);
+        self.emit_bailout(lbl, Trap::INDEX_OUT_OF_BOUNDS, location);
+    }
+
+    // stores value into the first length elements of the array using
+    // rep stos, which expects destination in RDI, count in RCX and value in RAX.
+    pub fn fill_array(&mut self, mode: MachineMode, array: Reg, length: Reg, value: Reg) {
+        assert_eq!(value, RAX);
+        assert!(array != RCX && length != RDI);
+
+        self.asm.lea(
+            RDI.into(),
+            AsmAddress::offset(array.into(), offset_of_array_data()),
+        );
+        self.asm.movq_rr(RCX.into(), length.into());
+
+        match mode {
+            MachineMode::Int8 => self.asm.rep_stosb(),
+            MachineMode::Int16 => self.asm.rep_stosw(),
+            MachineMode::Int32 => self.asm.rep_stosl(),
+            MachineMode::Int64 => self.asm.rep_stosq(),
+            _ => unreachable!(),
+        }
+    }
+
     pub fn load_nil(&mut self, dest: Reg) {
         self.asm.xorl_rr(dest.into(), dest.into());
     }
@@ -910,6 +1341,27 @@ impl MacroAssembler {
         self.asm.movq_ra(dest.into(), AsmAddress::reg(addr.into()));
     }
 
+    // x64 does not reorder loads with older loads or stores with older
+    // stores, so only sequential consistency needs a barrier instruction.
+    pub fn fence_acquire(&mut self) {}
+
+    pub fn fence_release(&mut self) {}
+
+    pub fn fence_seq_cst(&mut self) {
+        self.asm.mfence();
+    }
+
+    pub fn prefetch(&mut self, mem: Mem, hint: PrefetchHint) {
+        let address = address_from_mem(mem);
+
+        match hint {
+            PrefetchHint::T0 => self.asm.prefetcht0_a(address),
+            PrefetchHint::T1 => self.asm.prefetcht1_a(address),
+            PrefetchHint::T2 => self.asm.prefetcht2_a(address),
+            PrefetchHint::NonTemporal => self.asm.prefetchnta_a(address),
+        }
+    }
+
     pub fn store_int32_synchronized(&mut self, dest: Reg, addr: Reg) {
         self.asm.xchgl_ar(AsmAddress::reg(addr.into()), dest.into());
     }
@@ -979,6 +1431,7 @@ impl MacroAssembler {
     pub fn load_mem(&mut self, mode: MachineMode, dest: AnyReg, mem: Mem) {
         match mode {
             MachineMode::Int8 => self.asm.movzxb_ra(dest.reg().into(), address_from_mem(mem)),
+            MachineMode::Int16 => self.asm.movzxw_ra(dest.reg().into(), address_from_mem(mem)),
             MachineMode::Int32 => self.asm.movl_ra(dest.reg().into(), address_from_mem(mem)),
             MachineMode::Int64 | MachineMode::Ptr | MachineMode::IntPtr => {
                 self.asm.movq_ra(dest.reg().into(), address_from_mem(mem))
@@ -1016,6 +1469,7 @@ impl MacroAssembler {
     pub fn store_mem(&mut self, mode: MachineMode, mem: Mem, src: AnyReg) {
         match mode {
             MachineMode::Int8 => self.asm.movb_ar(address_from_mem(mem), src.reg().into()),
+            MachineMode::Int16 => self.asm.movw_ar(address_from_mem(mem), src.reg().into()),
             MachineMode::Int32 => self.asm.movl_ar(address_from_mem(mem), src.reg().into()),
             MachineMode::Int64 | MachineMode::Ptr | MachineMode::IntPtr => {
                 self.asm.movq_ar(address_from_mem(mem), src.reg().into())
@@ -1028,6 +1482,7 @@ impl MacroAssembler {
     pub fn store_zero(&mut self, mode: MachineMode, mem: Mem) {
         match mode {
             MachineMode::Int8 => self.asm.movb_ai(address_from_mem(mem), Immediate(0)),
+            MachineMode::Int16 => self.asm.movw_ai(address_from_mem(mem), Immediate(0)),
             MachineMode::Float32 | MachineMode::Int32 => {
                 self.asm.movl_ai(address_from_mem(mem), Immediate(0))
             }
@@ -1096,7 +1551,7 @@ impl MacroAssembler {
         }
 
         match mode {
-            MachineMode::Int8 | MachineMode::Int32 => {
+            MachineMode::Int8 | MachineMode::Int16 | MachineMode::Int32 => {
                 self.asm.movl_ri(dest.into(), Immediate(imm));
             }
             MachineMode::Int64 | MachineMode::Ptr | MachineMode::IntPtr => {
@@ -1208,6 +1663,55 @@ impl MacroAssembler {
         }
     }
 
+    // Computes `dest = a * b + c`, fused into a single rounding step when the
+    // CPU supports FMA3 and with separate multiply and add otherwise. Both
+    // variants overwrite `a`, so `c` needs to be a different register.
+    pub fn float_mul_add(&mut self, mode: MachineMode, dest: FReg, a: FReg, b: FReg, c: FReg) {
+        if !has_fma() {
+            self.float_mul_add_generic(mode, dest, a, b, c);
+            return;
+        }
+
+        assert!(a != c);
+
+        match mode {
+            MachineMode::Float32 => self.asm.vfmadd213ss_rrr(a.into(), b.into(), c.into()),
+            MachineMode::Float64 => self.asm.vfmadd213sd_rrr(a.into(), b.into(), c.into()),
+            _ => unimplemented!(),
+        }
+
+        if dest != a {
+            self.copy_freg(mode, dest, a);
+        }
+    }
+
+    pub fn float_mul_add_generic(
+        &mut self,
+        mode: MachineMode,
+        dest: FReg,
+        a: FReg,
+        b: FReg,
+        c: FReg,
+    ) {
+        assert!(a != c);
+
+        match mode {
+            MachineMode::Float32 => {
+                self.asm.mulss_rr(a.into(), b.into());
+                self.asm.addss_rr(a.into(), c.into());
+            }
+            MachineMode::Float64 => {
+                self.asm.mulsd_rr(a.into(), b.into());
+                self.asm.addsd_rr(a.into(), c.into());
+            }
+            _ => unimplemented!(),
+        }
+
+        if dest != a {
+            self.copy_freg(mode, dest, a);
+        }
+    }
+
     pub fn float_div(&mut self, mode: MachineMode, dest: FReg, lhs: FReg, rhs: FReg) {
         match mode {
             MachineMode::Float32 => self.asm.divss_rr(lhs.into(), rhs.into()),
@@ -1220,6 +1724,66 @@ impl MacroAssembler {
         }
     }
 
+    pub fn float_min(&mut self, mode: MachineMode, dest: FReg, lhs: FReg, rhs: FReg) {
+        self.float_min_max(mode, dest, lhs, rhs, true);
+    }
+
+    pub fn float_max(&mut self, mode: MachineMode, dest: FReg, lhs: FReg, rhs: FReg) {
+        self.float_min_max(mode, dest, lhs, rhs, false);
+    }
+
+    // minss/minsd and maxss/maxsd return the second operand if either
+    // operand is NaN or both are zero. Compare upfront to propagate NaN and
+    // order -0.0 below 0.0 by combining the sign bits.
+    fn float_min_max(&mut self, mode: MachineMode, dest: FReg, lhs: FReg, rhs: FReg, min: bool) {
+        let lbl_nan = self.create_label();
+        let lbl_not_equal = self.create_label();
+        let lbl_done = self.create_label();
+
+        match mode {
+            MachineMode::Float32 => self.asm.ucomiss_rr(lhs.into(), rhs.into()),
+            MachineMode::Float64 => self.asm.ucomisd_rr(lhs.into(), rhs.into()),
+            _ => unimplemented!(),
+        }
+
+        self.asm.jcc(Condition::Parity, lbl_nan);
+        self.asm.jcc(Condition::NotEqual, lbl_not_equal);
+
+        if min {
+            self.asm.orps_rr(lhs.into(), rhs.into());
+        } else {
+            self.asm.andps_rr(lhs.into(), rhs.into());
+        }
+
+        self.asm.jmp(lbl_done);
+
+        self.bind_label(lbl_nan);
+
+        match mode {
+            MachineMode::Float32 => self.asm.addss_rr(lhs.into(), rhs.into()),
+            MachineMode::Float64 => self.asm.addsd_rr(lhs.into(), rhs.into()),
+            _ => unreachable!(),
+        }
+
+        self.asm.jmp(lbl_done);
+
+        self.bind_label(lbl_not_equal);
+
+        match (mode, min) {
+            (MachineMode::Float32, true) => self.asm.minss_rr(lhs.into(), rhs.into()),
+            (MachineMode::Float32, false) => self.asm.maxss_rr(lhs.into(), rhs.into()),
+            (MachineMode::Float64, true) => self.asm.minsd_rr(lhs.into(), rhs.into()),
+            (MachineMode::Float64, false) => self.asm.maxsd_rr(lhs.into(), rhs.into()),
+            _ => unreachable!(),
+        }
+
+        self.bind_label(lbl_done);
+
+        if dest != lhs {
+            self.copy_freg(mode, dest, lhs);
+        }
+    }
+
     pub fn float_abs(&mut self, mode: MachineMode, dest: FReg, src: FReg) {
         let (fst, snd) = if mode == MachineMode::Float32 {
             (0x7fffffff, 0)
@@ -1386,7 +1950,7 @@ impl From<FReg> for XmmRegister {
 impl MachineMode {
     pub fn is64(self) -> bool {
         match self {
-            MachineMode::Int8 | MachineMode::Int32 => false,
+            MachineMode::Int8 | MachineMode::Int16 | MachineMode::Int32 => false,
             MachineMode::Int64 | MachineMode::Ptr => true,
             _ => unreachable!(),
         }
@@ -1419,3 +1983,426 @@ fn address_from_mem(mem: Mem) -> AsmAddress {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mem;
+    use crate::os;
+
+    #[derive(Copy, Clone)]
+    enum BitCount {
+        Bits,
+        Leading,
+        Trailing,
+    }
+
+    const INPUTS: [u64; 12] = [
+        0,
+        1,
+        2,
+        0x8000_0000,
+        0xFFFF_FFFF,
+        0x1_0000_0000,
+        0x8000_0000_0000_0000,
+        0xFFFF_FFFF_FFFF_FFFF,
+        0x0000_00F0_0F00_0000,
+        0x5555_5555_5555_5555,
+        0xAAAA_AAAA_AAAA_AAAA,
+        0x1234_5678_9ABC_DEF0,
+    ];
+
+    // Copies the generated code into executable memory and passes it as a
+    // function pointer of type `F` to `f`.
+    fn execute<F: Copy, R>(masm: MacroAssembler, f: impl FnOnce(F) -> R) -> R {
+        assert_eq!(std::mem::size_of::<F>(), std::mem::size_of::<*const u8>());
+        let code = masm.data();
+
+        let size = mem::page_align(code.len());
+        let ptr = os::commit(size, true);
+
+        let result = unsafe {
+            std::ptr::copy_nonoverlapping(code.as_ptr(), ptr.to_mut_ptr::<u8>(), code.len());
+            f(std::mem::transmute_copy(&ptr.to_ptr::<u8>()))
+        };
+
+        os::free(ptr, size);
+        result
+    }
+
+    // Emits `fn(u64) -> u64` computing the given bit count on the first
+    // argument and runs it for all inputs.
+    fn run(op: BitCount, mode: MachineMode, count_one_bits: bool, generic: bool) -> Vec<u64> {
+        let mut masm = MacroAssembler::new();
+        masm.asm.movq_rr(RAX.into(), REG_PARAMS[0].into());
+
+        match (op, generic) {
+            (BitCount::Bits, false) => masm.count_bits(mode, RAX, RAX, count_one_bits),
+            (BitCount::Bits, true) => masm.count_bits_generic(mode, RAX, RAX, count_one_bits),
+            (BitCount::Leading, false) => masm.count_bits_leading(mode, RAX, RAX, count_one_bits),
+            (BitCount::Leading, true) => {
+                masm.count_bits_leading_generic(mode, RAX, RAX, count_one_bits)
+            }
+            (BitCount::Trailing, false) => masm.count_bits_trailing(mode, RAX, RAX, count_one_bits),
+            (BitCount::Trailing, true) => {
+                masm.count_bits_trailing_generic(mode, RAX, RAX, count_one_bits)
+            }
+        }
+
+        masm.asm.retq();
+
+        execute(masm, |fct: extern "C" fn(u64) -> u64| {
+            INPUTS.iter().map(|&value| fct(value)).collect()
+        })
+    }
+
+    fn expected(op: BitCount, mode: MachineMode, count_one_bits: bool) -> Vec<u64> {
+        INPUTS
+            .iter()
+            .map(|&value| {
+                let result = if mode.is64() {
+                    let value = if count_one_bits { value } else { !value };
+                    match op {
+                        BitCount::Bits => value.count_ones(),
+                        BitCount::Leading => (!value).leading_zeros(),
+                        BitCount::Trailing => (!value).trailing_zeros(),
+                    }
+                } else {
+                    let value = value as u32;
+                    let value = if count_one_bits { value } else { !value };
+                    match op {
+                        BitCount::Bits => value.count_ones(),
+                        BitCount::Leading => (!value).leading_zeros(),
+                        BitCount::Trailing => (!value).trailing_zeros(),
+                    }
+                };
+                result as u64
+            })
+            .collect()
+    }
+
+    fn check(op: BitCount, has_instruction: bool) {
+        for mode in [MachineMode::Int32, MachineMode::Int64] {
+            for count_one_bits in [false, true] {
+                let expected = expected(op, mode, count_one_bits);
+                assert_eq!(expected, run(op, mode, count_one_bits, true));
+
+                if has_instruction {
+                    assert_eq!(expected, run(op, mode, count_one_bits, false));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_count_bits() {
+        check(BitCount::Bits, has_popcnt());
+    }
+
+    #[test]
+    fn test_count_bits_leading() {
+        check(BitCount::Leading, has_lzcnt());
+    }
+
+    #[test]
+    fn test_count_bits_trailing() {
+        check(BitCount::Trailing, has_tzcnt());
+    }
+
+    // Emits `fn(u64, u64) -> u64` computing the saturating sum or
+    // difference of both arguments.
+    fn run_saturating(mode: MachineMode, add: bool, lhs: i64, rhs: i64) -> i64 {
+        let mut masm = MacroAssembler::new();
+        masm.asm.movq_rr(RAX.into(), REG_PARAMS[0].into());
+        masm.asm.movq_rr(R10.into(), REG_PARAMS[1].into());
+
+        if add {
+            masm.int_add_saturating(mode, RAX, RAX, R10);
+        } else {
+            masm.int_sub_saturating(mode, RAX, RAX, R10);
+        }
+
+        masm.asm.retq();
+
+        let result = execute(masm, |fct: extern "C" fn(u64, u64) -> u64| {
+            fct(lhs as u64, rhs as u64)
+        });
+
+        if mode.is64() {
+            result as i64
+        } else {
+            result as i32 as i64
+        }
+    }
+
+    #[test]
+    fn test_int_saturating_int32() {
+        let values = [
+            i32::MIN,
+            i32::MIN + 1,
+            -7,
+            -1,
+            0,
+            1,
+            7,
+            i32::MAX - 1,
+            i32::MAX,
+        ];
+
+        for &lhs in &values {
+            for &rhs in &values {
+                let (lhs64, rhs64) = (lhs as i64, rhs as i64);
+                assert_eq!(
+                    lhs.saturating_add(rhs) as i64,
+                    run_saturating(MachineMode::Int32, true, lhs64, rhs64)
+                );
+                assert_eq!(
+                    lhs.saturating_sub(rhs) as i64,
+                    run_saturating(MachineMode::Int32, false, lhs64, rhs64)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_int_saturating_int64() {
+        let values = [
+            i64::MIN,
+            i64::MIN + 1,
+            i32::MIN as i64,
+            -1,
+            0,
+            1,
+            i32::MAX as i64,
+            i64::MAX - 1,
+            i64::MAX,
+        ];
+
+        for &lhs in &values {
+            for &rhs in &values {
+                assert_eq!(
+                    lhs.saturating_add(rhs),
+                    run_saturating(MachineMode::Int64, true, lhs, rhs)
+                );
+                assert_eq!(
+                    lhs.saturating_sub(rhs),
+                    run_saturating(MachineMode::Int64, false, lhs, rhs)
+                );
+            }
+        }
+    }
+
+    // Emits `fn(u64) -> u64` multiplying the first argument by `value`.
+    fn run_mul_imm(mode: MachineMode, dest: Reg, value: i64) -> Vec<u64> {
+        let mut masm = MacroAssembler::new();
+        masm.asm.movq_rr(RAX.into(), REG_PARAMS[0].into());
+        masm.int_mul_imm(mode, dest, RAX, value);
+        masm.asm.movq_rr(RAX.into(), dest.into());
+        masm.asm.retq();
+
+        execute(masm, |fct: extern "C" fn(u64) -> u64| {
+            INPUTS
+                .iter()
+                .map(|&value| {
+                    let result = fct(value);
+                    if mode.is64() {
+                        result
+                    } else {
+                        result as u32 as u64
+                    }
+                })
+                .collect()
+        })
+    }
+
+    #[test]
+    fn test_int_mul_imm() {
+        for value in [0, 1, 2, 3, 5, 8, 9, 31, 1024, -1, -3, 0x1_0000_0007] {
+            for dest in [RAX, RCX] {
+                let expected: Vec<u64> = INPUTS
+                    .iter()
+                    .map(|&x| x.wrapping_mul(value as u64))
+                    .collect();
+                assert_eq!(expected, run_mul_imm(MachineMode::Int64, dest, value));
+
+                if fits_i32(value) {
+                    let expected: Vec<u64> = INPUTS
+                        .iter()
+                        .map(|&x| (x as u32).wrapping_mul(value as u32) as u64)
+                        .collect();
+                    assert_eq!(expected, run_mul_imm(MachineMode::Int32, dest, value));
+                }
+            }
+        }
+    }
+
+    // Emits `fn(f64, f64, f64) -> f64` computing `a * b + c` through either the
+    // fused or the generic variant.
+    fn run_mul_add64(fused: bool, a: f64, b: f64, c: f64) -> f64 {
+        let mut masm = MacroAssembler::new();
+
+        if fused {
+            masm.float_mul_add(MachineMode::Float64, XMM0, XMM0, XMM1, XMM2);
+        } else {
+            masm.float_mul_add_generic(MachineMode::Float64, XMM0, XMM0, XMM1, XMM2);
+        }
+
+        masm.asm.retq();
+
+        execute(masm, |fct: extern "C" fn(f64, f64, f64) -> f64| {
+            fct(a, b, c)
+        })
+    }
+
+    // Emits `fn(f32, f32, f32) -> f32` computing `a * b + c` through either the
+    // fused or the generic variant. The result ends up in a different register.
+    fn run_mul_add32(fused: bool, a: f32, b: f32, c: f32) -> f32 {
+        let mut masm = MacroAssembler::new();
+
+        if fused {
+            masm.float_mul_add(MachineMode::Float32, XMM3, XMM0, XMM1, XMM2);
+        } else {
+            masm.float_mul_add_generic(MachineMode::Float32, XMM3, XMM0, XMM1, XMM2);
+        }
+
+        masm.copy_freg(MachineMode::Float32, XMM0, XMM3);
+        masm.asm.retq();
+
+        execute(masm, |fct: extern "C" fn(f32, f32, f32) -> f32| {
+            fct(a, b, c)
+        })
+    }
+
+    #[test]
+    fn test_float_mul_add() {
+        // a * b rounds to 1.0, so only the fused variant keeps the low bits.
+        let a = 1.0 + 2f64.powi(-30);
+        let b = 1.0 - 2f64.powi(-30);
+
+        assert_eq!(7.0, run_mul_add64(false, 2.0, 3.0, 1.0));
+        assert_eq!(0.0, run_mul_add64(false, a, b, -1.0));
+        assert_eq!(-6.5f32, run_mul_add32(false, 2.5, -3.0, 1.0));
+
+        if has_fma() {
+            assert_eq!(7.0, run_mul_add64(true, 2.0, 3.0, 1.0));
+            assert_eq!(a.mul_add(b, -1.0), run_mul_add64(true, a, b, -1.0));
+            assert_ne!(0.0, run_mul_add64(true, a, b, -1.0));
+            assert_eq!(-6.5f32, run_mul_add32(true, 2.5, -3.0, 1.0));
+        }
+    }
+
+    #[derive(Copy, Clone)]
+    enum Atomic {
+        Exchange,
+        CompareExchange,
+        FetchAdd,
+    }
+
+    // Emits `fn(*mut u64, u64, u64) -> u64` performing the atomic operation
+    // on the first argument and returns the result together with the memory
+    // value afterwards.
+    fn run_atomic(op: Atomic, mode: MachineMode, initial: u64, lhs: u64, rhs: u64) -> (u64, u64) {
+        let mut masm = MacroAssembler::new();
+        let address = REG_PARAMS[0];
+
+        let result = match (op, mode) {
+            (Atomic::Exchange, MachineMode::Int32) => {
+                masm.exchange_int32_synchronized(RAX, REG_PARAMS[1], address);
+                RAX
+            }
+            (Atomic::Exchange, MachineMode::Int64) => {
+                masm.exchange_int64_synchronized(RAX, REG_PARAMS[1], address);
+                RAX
+            }
+            (Atomic::CompareExchange, MachineMode::Int32) => {
+                masm.copy_reg(MachineMode::Int64, RAX, REG_PARAMS[1]);
+                masm.compare_exchange_int32_synchronized(RAX, REG_PARAMS[2], address)
+            }
+            (Atomic::CompareExchange, MachineMode::Int64) => {
+                masm.copy_reg(MachineMode::Int64, RAX, REG_PARAMS[1]);
+                masm.compare_exchange_int64_synchronized(RAX, REG_PARAMS[2], address)
+            }
+            (Atomic::FetchAdd, MachineMode::Int32) => {
+                masm.fetch_add_int32_synchronized(RAX, REG_PARAMS[1], address)
+            }
+            (Atomic::FetchAdd, MachineMode::Int64) => {
+                masm.fetch_add_int64_synchronized(RAX, REG_PARAMS[1], address)
+            }
+            _ => unreachable!(),
+        };
+
+        masm.copy_reg(MachineMode::Int64, RAX, result);
+        masm.asm.retq();
+
+        let mut memory = initial;
+        let result = execute(masm, |fct: extern "C" fn(*mut u64, u64, u64) -> u64| {
+            fct(&mut memory, lhs, rhs)
+        });
+
+        if mode.is64() {
+            (result, memory)
+        } else {
+            (result as u32 as u64, memory)
+        }
+    }
+
+    #[test]
+    fn test_exchange_synchronized() {
+        assert_eq!(
+            run_atomic(Atomic::Exchange, MachineMode::Int64, 1, 2, 0),
+            (1, 2)
+        );
+        assert_eq!(
+            run_atomic(Atomic::Exchange, MachineMode::Int32, 0x1_0000_0001, 2, 0),
+            (1, 0x1_0000_0002)
+        );
+    }
+
+    #[test]
+    fn test_compare_exchange_synchronized() {
+        assert_eq!(
+            run_atomic(Atomic::CompareExchange, MachineMode::Int64, 5, 5, 7),
+            (5, 7)
+        );
+        assert_eq!(
+            run_atomic(Atomic::CompareExchange, MachineMode::Int64, 5, 6, 7),
+            (5, 5)
+        );
+        assert_eq!(
+            run_atomic(
+                Atomic::CompareExchange,
+                MachineMode::Int32,
+                0x1_0000_0005,
+                5,
+                7
+            ),
+            (5, 0x1_0000_0007)
+        );
+        assert_eq!(
+            run_atomic(
+                Atomic::CompareExchange,
+                MachineMode::Int32,
+                0x1_0000_0005,
+                6,
+                7
+            ),
+            (5, 0x1_0000_0005)
+        );
+    }
+
+    #[test]
+    fn test_fetch_add_synchronized() {
+        assert_eq!(
+            run_atomic(Atomic::FetchAdd, MachineMode::Int64, 10, 5, 0),
+            (10, 15)
+        );
+        assert_eq!(
+            run_atomic(Atomic::FetchAdd, MachineMode::Int64, u64::MAX, 2, 0),
+            (u64::MAX, 1)
+        );
+        assert_eq!(
+            run_atomic(Atomic::FetchAdd, MachineMode::Int32, 0x1_FFFF_FFFF, 2, 0),
+            (0xFFFF_FFFF, 0x1_0000_0001)
+        );
+    }
+}
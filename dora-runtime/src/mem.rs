@@ -1,8 +1,23 @@
 use std::i32;
 use std::mem::size_of;
 
+use crate::gc::Address;
 use crate::os;
 
+/// hint the CPU to load the cache line containing `addr`,
+/// does nothing on architectures without a prefetch instruction
+#[inline(always)]
+pub fn prefetch(addr: Address) {
+    #[cfg(target_arch = "x86_64")]
+    unsafe {
+        use std::arch::x86_64::{_mm_prefetch, _MM_HINT_T0};
+        _mm_prefetch::<_MM_HINT_T0>(addr.to_ptr::<i8>());
+    }
+
+    #[cfg(not(target_arch = "x86_64"))]
+    let _ = addr;
+}
+
 /// return pointer width: either 4 or 8
 /// (although only 64bit architectures are supported right now)
 #[inline(always)]
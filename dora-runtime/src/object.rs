@@ -292,6 +292,30 @@ impl Obj {
         visit_refs(self.address(), cls, None, f);
     }
 
+    // like visit_reference_fields but the referent of a WeakRef is passed to `weak`
+    pub fn visit_reference_fields_with_weak<F, W>(&mut self, mut strong: F, mut weak: W)
+    where
+        F: FnMut(Slot),
+        W: FnMut(Slot),
+    {
+        let classptr = self.header().vtbl().class_instance_ptr;
+        let cls = unsafe { &*classptr };
+
+        if let Some(weak_field) = cls.weak_field {
+            for &offset in &cls.ref_fields {
+                let slot = Slot::at(self.address().offset(offset as usize));
+
+                if offset == weak_field {
+                    weak(slot);
+                } else {
+                    strong(slot);
+                }
+            }
+        } else {
+            visit_refs(self.address(), cls, None, strong);
+        }
+    }
+
     pub fn visit_reference_fields_within<F>(&mut self, range: Region, f: F)
     where
         F: FnMut(Slot),
@@ -0,0 +1,354 @@
+#[cfg(target_os = "linux")]
+pub use self::linux::*;
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use std::ptr;
+    use std::sync::atomic::{compiler_fence, Ordering};
+    use std::sync::Mutex;
+
+    use crate::vm::Code;
+
+    // Layout of the JIT interface as specified in gdb's documentation,
+    // gdb finds both symbols by name and sets a breakpoint in
+    // `__jit_debug_register_code` to get notified of new entries.
+    #[repr(C)]
+    pub struct JitCodeEntry {
+        pub next_entry: *mut JitCodeEntry,
+        pub prev_entry: *mut JitCodeEntry,
+        pub symfile_addr: *const u8,
+        pub symfile_size: u64,
+    }
+
+    #[repr(C)]
+    pub struct JitDescriptor {
+        pub version: u32,
+        pub action_flag: u32,
+        pub relevant_entry: *mut JitCodeEntry,
+        pub first_entry: *mut JitCodeEntry,
+    }
+
+    const JIT_REGISTER_FN: u32 = 1;
+
+    #[no_mangle]
+    #[inline(never)]
+    pub extern "C" fn __jit_debug_register_code() {
+        // Only needs to exist, the fence keeps the call from being optimized away.
+        compiler_fence(Ordering::SeqCst);
+    }
+
+    #[no_mangle]
+    pub static mut __jit_debug_descriptor: JitDescriptor = JitDescriptor {
+        version: 1,
+        action_flag: 0,
+        relevant_entry: ptr::null_mut(),
+        first_entry: ptr::null_mut(),
+    };
+
+    // Serializes all modifications of the descriptor.
+    static LOCK: Mutex<()> = Mutex::new(());
+
+    pub fn register_with_gdb(code: &Code, name: &str) {
+        let code_start = code.instruction_start().to_usize();
+        let code_end = code.instruction_end().to_usize();
+        register_code(code_start, code_end, name);
+    }
+
+    // Code is never freed, so the symbol file and the entry are leaked
+    // and stay registered until the process exits.
+    pub fn register_code(code_start: usize, code_end: usize, name: &str) {
+        let symfile: &'static [u8] =
+            Box::leak(build_elf(code_start, code_end, name).into_boxed_slice());
+
+        let _lock = LOCK.lock().unwrap();
+
+        unsafe {
+            let descriptor = ptr::addr_of_mut!(__jit_debug_descriptor);
+            let first_entry = (*descriptor).first_entry;
+
+            let entry = Box::into_raw(Box::new(JitCodeEntry {
+                next_entry: first_entry,
+                prev_entry: ptr::null_mut(),
+                symfile_addr: symfile.as_ptr(),
+                symfile_size: symfile.len() as u64,
+            }));
+
+            if !first_entry.is_null() {
+                (*first_entry).prev_entry = entry;
+            }
+
+            (*descriptor).first_entry = entry;
+            (*descriptor).relevant_entry = entry;
+            (*descriptor).action_flag = JIT_REGISTER_FN;
+            __jit_debug_register_code();
+        }
+    }
+
+    const SHT_SYMTAB: u32 = 2;
+    const SHT_STRTAB: u32 = 3;
+    const SHT_NOBITS: u32 = 8;
+
+    const SHF_ALLOC: u64 = 2;
+    const SHF_EXECINSTR: u64 = 4;
+
+    const ELF_HEADER_SIZE: usize = 64;
+    const SECTION_HEADER_SIZE: usize = 64;
+    const SYMBOL_SIZE: usize = 24;
+
+    const SECTION_TEXT: u16 = 1;
+    const SECTION_STRTAB: u32 = 3;
+
+    #[cfg(target_arch = "x86_64")]
+    const ELF_MACHINE: u16 = 62;
+
+    #[cfg(target_arch = "aarch64")]
+    const ELF_MACHINE: u16 = 183;
+
+    // Builds a relocatable ELF object with a single `.text` section placed at
+    // the code range and one function symbol covering it. The section is
+    // NOBITS since gdb reads the instructions from process memory.
+    fn build_elf(code_start: usize, code_end: usize, name: &str) -> Vec<u8> {
+        let code_size = (code_end - code_start) as u64;
+
+        let shstrtab = b"\0.text\0.symtab\0.strtab\0.shstrtab\0";
+        let name_text = 1;
+        let name_symtab = 7;
+        let name_strtab = 15;
+        let name_shstrtab = 23;
+
+        let mut strtab = vec![0u8];
+        strtab.extend_from_slice(b"dora::");
+        strtab.extend_from_slice(name.as_bytes());
+        strtab.push(0);
+
+        let strtab_offset = ELF_HEADER_SIZE;
+        let shstrtab_offset = strtab_offset + strtab.len();
+        let symtab_offset = align8(shstrtab_offset + shstrtab.len());
+        let symtab_size = 2 * SYMBOL_SIZE;
+        let sections_offset = symtab_offset + symtab_size;
+        let sections = 5;
+
+        let mut buf = ElfBuffer::new();
+
+        // ELF header
+        buf.bytes(&[0x7f, b'E', b'L', b'F', 2, 1, 1, 0]);
+        buf.bytes(&[0; 8]);
+        buf.u16(1); // e_type: ET_REL
+        buf.u16(ELF_MACHINE);
+        buf.u32(1); // e_version
+        buf.u64(0); // e_entry
+        buf.u64(0); // e_phoff
+        buf.u64(sections_offset as u64);
+        buf.u32(0); // e_flags
+        buf.u16(ELF_HEADER_SIZE as u16);
+        buf.u16(0); // e_phentsize
+        buf.u16(0); // e_phnum
+        buf.u16(SECTION_HEADER_SIZE as u16);
+        buf.u16(sections);
+        buf.u16(4); // e_shstrndx
+
+        buf.bytes(&strtab);
+        buf.bytes(shstrtab);
+        buf.align8();
+
+        // Symbol table: null symbol followed by the function.
+        buf.bytes(&[0; SYMBOL_SIZE]);
+        buf.u32(1); // st_name
+        buf.u8(0x12); // st_info: STB_GLOBAL, STT_FUNC
+        buf.u8(0); // st_other
+        buf.u16(SECTION_TEXT);
+        buf.u64(0); // st_value: relative to .text
+        buf.u64(code_size);
+
+        // Section headers
+        buf.bytes(&[0; SECTION_HEADER_SIZE]);
+        buf.section_header(
+            name_text,
+            SHT_NOBITS,
+            SHF_ALLOC | SHF_EXECINSTR,
+            code_start as u64,
+            0,
+            code_size,
+            0,
+            0,
+            16,
+            0,
+        );
+        buf.section_header(
+            name_symtab,
+            SHT_SYMTAB,
+            0,
+            0,
+            symtab_offset as u64,
+            symtab_size as u64,
+            SECTION_STRTAB,
+            1,
+            8,
+            SYMBOL_SIZE as u64,
+        );
+        buf.section_header(
+            name_strtab,
+            SHT_STRTAB,
+            0,
+            0,
+            strtab_offset as u64,
+            strtab.len() as u64,
+            0,
+            0,
+            1,
+            0,
+        );
+        buf.section_header(
+            name_shstrtab,
+            SHT_STRTAB,
+            0,
+            0,
+            shstrtab_offset as u64,
+            shstrtab.len() as u64,
+            0,
+            0,
+            1,
+            0,
+        );
+
+        debug_assert_eq!(
+            buf.data.len(),
+            sections_offset + sections as usize * SECTION_HEADER_SIZE
+        );
+        buf.data
+    }
+
+    fn align8(value: usize) -> usize {
+        (value + 7) & !7
+    }
+
+    struct ElfBuffer {
+        data: Vec<u8>,
+    }
+
+    impl ElfBuffer {
+        fn new() -> ElfBuffer {
+            ElfBuffer { data: Vec::new() }
+        }
+
+        fn bytes(&mut self, bytes: &[u8]) {
+            self.data.extend_from_slice(bytes);
+        }
+
+        fn u8(&mut self, value: u8) {
+            self.data.push(value);
+        }
+
+        fn u16(&mut self, value: u16) {
+            self.bytes(&value.to_le_bytes());
+        }
+
+        fn u32(&mut self, value: u32) {
+            self.bytes(&value.to_le_bytes());
+        }
+
+        fn u64(&mut self, value: u64) {
+            self.bytes(&value.to_le_bytes());
+        }
+
+        fn align8(&mut self) {
+            let len = align8(self.data.len());
+            self.data.resize(len, 0);
+        }
+
+        #[allow(clippy::too_many_arguments)]
+        fn section_header(
+            &mut self,
+            name: u32,
+            kind: u32,
+            flags: u64,
+            addr: u64,
+            offset: u64,
+            size: u64,
+            link: u32,
+            info: u32,
+            align: u64,
+            entsize: u64,
+        ) {
+            self.u32(name);
+            self.u32(kind);
+            self.u64(flags);
+            self.u64(addr);
+            self.u64(offset);
+            self.u64(size);
+            self.u32(link);
+            self.u32(info);
+            self.u64(align);
+            self.u64(entsize);
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::masm::MacroAssembler;
+        use crate::mem;
+        use crate::os;
+
+        fn find_entry(start: usize) -> Option<&'static [u8]> {
+            let _lock = LOCK.lock().unwrap();
+
+            unsafe {
+                let descriptor = ptr::addr_of!(__jit_debug_descriptor);
+                let mut entry = (*descriptor).first_entry;
+
+                while !entry.is_null() {
+                    let symfile = std::slice::from_raw_parts(
+                        (*entry).symfile_addr,
+                        (*entry).symfile_size as usize,
+                    );
+
+                    // sh_addr of .text, the first section after the null section.
+                    let sections = u64::from_le_bytes(symfile[40..48].try_into().unwrap());
+                    let text = sections as usize + SECTION_HEADER_SIZE;
+                    let addr =
+                        u64::from_le_bytes(symfile[text + 16..text + 24].try_into().unwrap());
+
+                    if addr as usize == start {
+                        return Some(symfile);
+                    }
+
+                    entry = (*entry).next_entry;
+                }
+            }
+
+            None
+        }
+
+        #[test]
+        fn test_register_code() {
+            let mut masm = MacroAssembler::new();
+            masm.nop();
+            masm.epilog();
+            let code = masm.data();
+
+            let size = mem::page_align(code.len());
+            let ptr = os::commit(size, true);
+            let start = ptr.to_usize();
+
+            unsafe {
+                std::ptr::copy_nonoverlapping(code.as_ptr(), ptr.to_mut_ptr::<u8>(), code.len());
+            }
+
+            register_code(start, start + code.len(), "test::foo");
+
+            let symfile = find_entry(start).expect("entry missing");
+            assert_eq!(&symfile[0..4], b"\x7fELF");
+            assert!(symfile
+                .windows(b"dora::test::foo\0".len())
+                .any(|window| window == b"dora::test::foo\0"));
+
+            os::free(ptr, size);
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn register_with_gdb(_: &crate::vm::Code, _: &str) {
+    // nothing to do
+}
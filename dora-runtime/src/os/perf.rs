@@ -1,29 +1,79 @@
-use crate::vm::Code;
+#[cfg(target_os = "linux")]
+pub use self::linux::*;
 
 #[cfg(target_os = "linux")]
-pub fn register_with_perf(code: &Code, name: &str) {
-    use std::fs::OpenOptions;
+mod linux {
+    use std::fs::{File, OpenOptions};
     use std::io::prelude::*;
+    use std::sync::Mutex;
+
+    use crate::vm::Code;
+
+    // Functions are compiled concurrently, the lock keeps lines from
+    // different threads from interleaving.
+    static PERF_MAP: Mutex<Option<File>> = Mutex::new(None);
+
+    pub fn perf_map_path() -> String {
+        let pid = unsafe { libc::getpid() };
+        format!("/tmp/perf-{}.map", pid)
+    }
+
+    pub fn register_with_perf(code: &Code, name: &str) {
+        let code_start = code.instruction_start().to_usize();
+        let code_end = code.instruction_end().to_usize();
+        register_code(code_start, code_end, name);
+    }
+
+    pub fn register_code(code_start: usize, code_end: usize, name: &str) {
+        let line = format!(
+            "{:x} {:x} dora::{}\n",
+            code_start,
+            code_end - code_start,
+            name
+        );
+
+        let mut perf_map = PERF_MAP.lock().unwrap();
+
+        let file = perf_map.get_or_insert_with(|| {
+            OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(perf_map_path())
+                .unwrap()
+        });
+
+        file.write_all(line.as_bytes()).unwrap();
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
 
-    let pid = unsafe { libc::getpid() };
-    let fname = format!("/tmp/perf-{}.map", pid);
+        #[test]
+        fn test_register_code() {
+            register_code(0x1000, 0x1040, "foo");
+            register_code(0x2000, 0x2008, "Bar#baz");
 
-    let mut options = OpenOptions::new();
-    let mut file = options.create(true).append(true).open(&fname).unwrap();
+            let content = std::fs::read_to_string(perf_map_path()).unwrap();
+            let lines: Vec<&str> = content.lines().collect();
+            assert!(lines.contains(&"1000 40 dora::foo"));
+            assert!(lines.contains(&"2000 8 dora::Bar#baz"));
 
-    let code_start = code.instruction_start().to_usize();
-    let code_end = code.instruction_end().to_usize();
+            for line in lines {
+                let mut parts = line.splitn(3, ' ');
+                let start = parts.next().unwrap();
+                let size = parts.next().unwrap();
+                assert!(usize::from_str_radix(start, 16).is_ok());
+                assert!(usize::from_str_radix(size, 16).is_ok());
+                assert!(parts.next().unwrap().starts_with("dora::"));
+            }
 
-    let line = format!(
-        "{:x} {:x} dora::{}\n",
-        code_start,
-        code_end - code_start,
-        name
-    );
-    file.write_all(line.as_bytes()).unwrap();
+            std::fs::remove_file(perf_map_path()).unwrap();
+        }
+    }
 }
 
 #[cfg(not(target_os = "linux"))]
-pub fn register_with_perf(_: &Code, _: &str) {
+pub fn register_with_perf(_: &crate::vm::Code, _: &str) {
     // nothing to do
 }
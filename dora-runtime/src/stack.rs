@@ -1,9 +1,11 @@
+use std::mem;
 use std::ptr;
 
 use crate::handle::{create_handle, Handle};
-use crate::object::{alloc, Array, Int32Array, Ref, Stacktrace, StacktraceElement, Str};
+use crate::object::{alloc, Array, Int32Array, Obj, Ref, Stacktrace, StacktraceElement, Str};
+use crate::stdlib;
 use crate::threads::current_thread;
-use crate::vm::{display_fct, get_vm, CodeId, CodeKind, VM};
+use crate::vm::{display_fct, get_vm, CodeId, CodeKind, Trap, VM};
 use dora_bytecode::Location;
 
 pub struct NativeStacktrace {
@@ -81,6 +83,93 @@ impl DoraToNativeInfo {
     }
 }
 
+// Passed to the resume stub, which continues execution at `pc`.
+#[repr(C)]
+pub struct ExceptionResumeInfo {
+    pub pc: usize,
+    pub fp: usize,
+    pub sp: usize,
+    pub exception: usize,
+    pub tld: usize,
+}
+
+impl ExceptionResumeInfo {
+    pub fn pc_offset() -> i32 {
+        offset_of!(ExceptionResumeInfo, pc) as i32
+    }
+
+    pub fn fp_offset() -> i32 {
+        offset_of!(ExceptionResumeInfo, fp) as i32
+    }
+
+    pub fn sp_offset() -> i32 {
+        offset_of!(ExceptionResumeInfo, sp) as i32
+    }
+
+    pub fn exception_offset() -> i32 {
+        offset_of!(ExceptionResumeInfo, exception) as i32
+    }
+
+    pub fn tld_offset() -> i32 {
+        offset_of!(ExceptionResumeInfo, tld) as i32
+    }
+}
+
+// Walks the frames of the current Dora activation (up to the Dora entry stub)
+// and continues at the first handler for the class of the thrown object.
+// The throw is a trap if no handler was found. Must not allocate: the stack
+// isn't walkable by the GC while unwinding.
+pub extern "C" fn throw_exception(exception: Handle<Obj>) {
+    let vm = get_vm();
+    let thread = current_thread();
+    let exception = exception.direct();
+    let class_instance_id = exception.header().vtbl().class_instance().id();
+
+    let dtn = unsafe { &*thread.dtn() };
+    let mut fp = dtn.fp;
+
+    while fp != 0 {
+        let ra = unsafe { *((fp + 8) as *const usize) };
+        let caller_fp = unsafe { *(fp as *const usize) };
+
+        let code_id = vm.code_map.get(ra.into()).expect("invalid stack frame");
+        let code = vm.code_objects.get(code_id);
+
+        match code.descriptor() {
+            CodeKind::DoraFct(_) => {
+                // the return address already points to the instruction after the call
+                let offset = ra - code.instruction_start().to_usize() - 1;
+
+                if let Some(handler) =
+                    code.exception_handler_for_offset(offset as u32, class_instance_id)
+                {
+                    let info = ExceptionResumeInfo {
+                        pc: code.instruction_start().to_usize() + handler.handler as usize,
+                        fp: caller_fp,
+                        sp: caller_fp - handler.framesize as usize,
+                        exception: exception.address().to_usize(),
+                        tld: thread.tld_address().to_usize(),
+                    };
+
+                    thread.set_dtn(dtn.last);
+
+                    let resume: extern "C" fn(*const ExceptionResumeInfo) -> ! =
+                        unsafe { mem::transmute(vm.stubs.resume()) };
+                    resume(&info);
+                }
+            }
+
+            CodeKind::DoraStub => break,
+
+            _ => {}
+        }
+
+        fp = caller_fp;
+    }
+
+    stdlib::trap(Trap::UNCAUGHT_EXCEPTION.int());
+}
+
 pub fn stacktrace_from_last_dtn(vm: &VM) -> NativeStacktrace {
     let mut stacktrace = NativeStacktrace::new();
     frames_from_dtns(&mut stacktrace, vm);
@@ -143,12 +232,15 @@ fn determine_stack_entry(stacktrace: &mut NativeStacktrace, vm: &VM, pc: usize)
             }
 
             CodeKind::TrapStub => true,
+            CodeKind::ThrowStub => true,
             CodeKind::GuardCheckStub => true,
             CodeKind::CompileStub => true,
             CodeKind::AllocStub => true,
             CodeKind::DoraStub => false,
 
-            CodeKind::VerifyStub | CodeKind::SafepointStub => unreachable!(),
+            CodeKind::VerifyStub | CodeKind::SafepointStub | CodeKind::ResumeStub => {
+                unreachable!()
+            }
         }
     } else {
         println!("no code found at pc = {:x}", pc);
@@ -208,6 +300,15 @@ fn set_backtrace(vm: &VM, mut obj: Handle<Stacktrace>, via_retrieve: bool) {
                 }
             }
         }
+
+        // Throwable::new captures the stack trace of its caller
+        if let Some(elem) = stacktrace.elems.get(skip) {
+            let code = vm.code_objects.get(elem.fct_id);
+
+            if code.fct_id() == vm.known.throwable_new_fct_id() {
+                skip += 1;
+            }
+        }
     }
 
     let len = stacktrace.len() - skip;
@@ -222,5 +323,9 @@ fn set_backtrace(vm: &VM, mut obj: Handle<Stacktrace>, via_retrieve: bool) {
         array.set_at(i + 1, elem.fct_id.idx() as i32);
         i += 2;
     }
+
+    // SATB pre-write barrier for the overwritten backtrace
+    vm.gc.satb_enqueue(obj.backtrace.address());
+
     obj.backtrace = array.direct();
 }
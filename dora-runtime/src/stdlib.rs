@@ -1,8 +1,11 @@
 use libc;
 
 use std::char;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
 use std::io::Write;
 use std::mem;
+use std::ptr;
 use std::str;
 use std::thread;
 use std::time::Duration;
@@ -10,11 +13,13 @@ use std::time::Duration;
 use crate::gc::{Address, GcReason};
 use crate::handle::{create_handle, handle_scope, Handle};
 use crate::object::{Obj, Ref, Str, UInt8Array};
+use crate::safepoint;
 use crate::stack::stacktrace_from_last_dtn;
 use crate::threads::{
     current_thread, deinit_current_thread, init_current_thread, DoraThread, ManagedThread,
     ThreadState, STACK_SIZE,
 };
+use crate::timer;
 use crate::vm::{get_vm, stack_pointer, ManagedCondition, ManagedMutex, ShapeKind, Trap};
 
 pub mod io;
@@ -73,6 +78,46 @@ pub extern "C" fn float64_to_string(val: f64) -> Ref<Str> {
     })
 }
 
+pub extern "C" fn float64_to_string_fixed(val: f64, digits: i32) -> Ref<Str> {
+    handle_scope(|| {
+        // the formatter panics for a precision above u16::MAX
+        let digits = digits.clamp(0, u16::MAX as i32) as usize;
+        let buffer = format!("{:.*}", digits, val);
+        let vm = get_vm();
+
+        Str::from_buffer(vm, buffer.as_bytes())
+    })
+}
+
+pub extern "C" fn math_sin(val: f64) -> f64 {
+    val.sin()
+}
+
+pub extern "C" fn math_cos(val: f64) -> f64 {
+    val.cos()
+}
+
+pub extern "C" fn math_tan(val: f64) -> f64 {
+    val.tan()
+}
+
+pub extern "C" fn math_exp(val: f64) -> f64 {
+    val.exp()
+}
+
+pub extern "C" fn math_ln(val: f64) -> f64 {
+    val.ln()
+}
+
+pub extern "C" fn math_pow(base: f64, exponent: f64) -> f64 {
+    base.powf(exponent)
+}
+
+pub extern "C" fn random_os_seed() -> i64 {
+    // RandomState gets its keys from the operating system's random source.
+    RandomState::new().build_hasher().finish() as i64
+}
+
 pub extern "C" fn print(val: Handle<Str>) {
     std::io::stdout().write(val.content()).unwrap();
 }
@@ -119,6 +164,20 @@ pub extern "C" fn timestamp() -> u64 {
     timestamp.duration_since(UNIX_EPOCH).unwrap().as_millis() as u64
 }
 
+pub extern "C" fn monotonic_nanos() -> i64 {
+    timer::monotonic_nanos()
+}
+
+pub extern "C" fn hash_seed() -> i64 {
+    let vm = get_vm();
+    vm.args.flag_hash_seed.unwrap_or(0) as i64
+}
+
+pub extern "C" fn has_hash_seed() -> bool {
+    let vm = get_vm();
+    vm.args.flag_hash_seed.is_some()
+}
+
 pub extern "C" fn println(val: Handle<Str>) {
     let stdout = std::io::stdout();
     let mut handle = stdout.lock();
@@ -164,6 +223,29 @@ pub extern "C" fn str_from_bytes(val: Handle<UInt8Array>, offset: usize, len: us
     })
 }
 
+pub extern "C" fn str_copy_bytes(
+    val: Handle<Str>,
+    src_pos: i64,
+    dest: Handle<UInt8Array>,
+    dest_pos: i64,
+    len: i64,
+) {
+    let in_bounds = |pos: i64, size: usize| pos >= 0 && pos as u64 + len as u64 <= size as u64;
+
+    if len < 0 || !in_bounds(src_pos, val.len()) || !in_bounds(dest_pos, dest.len()) {
+        trap(Trap::INDEX_OUT_OF_BOUNDS.int());
+        return;
+    }
+
+    unsafe {
+        ptr::copy_nonoverlapping(
+            val.data().offset(src_pos as isize),
+            (dest.data() as *mut u8).offset(dest_pos as isize),
+            len as usize,
+        );
+    }
+}
+
 pub extern "C" fn gc_alloc(size: usize, array_ref: bool) -> *mut Obj {
     let vm = get_vm();
     vm.gc.alloc(vm, size, array_ref).to_mut_ptr()
@@ -179,6 +261,54 @@ pub extern "C" fn gc_minor_collect() {
     vm.gc.minor_collect(vm, GcReason::ForceMinorCollect);
 }
 
+pub extern "C" fn gc_stat(idx: i32) -> i64 {
+    let stats = get_vm().gc_stats();
+
+    match idx {
+        0 => stats.minor_collections as i64,
+        1 => stats.major_collections as i64,
+        2 => (stats.total_pause * 1000f32) as i64,
+        3 => (stats.max_pause * 1000f32) as i64,
+        4 => stats.bytes_allocated as i64,
+        5 => stats.bytes_live_after_major as i64,
+        _ => panic!("unknown gc statistic {}", idx),
+    }
+}
+
+pub extern "C" fn gc_set_young_size(min_young_size: i64, max_young_size: i64) {
+    let vm = get_vm();
+
+    safepoint::stop_the_world(vm, |_threads| {
+        vm.gc.set_young_size(
+            vm,
+            min_young_size.max(0) as usize,
+            max_young_size.max(0) as usize,
+        );
+    });
+}
+
+pub extern "C" fn register_finalizer(object: Handle<Obj>) {
+    let vm = get_vm();
+
+    if !vm.gc.supports_finalizers() {
+        return;
+    }
+
+    // the argument is a trait object, register the boxed object
+    let class_instance = object.header().vtbl().class_instance();
+
+    match &class_instance.kind {
+        ShapeKind::TraitObject { object_ty, .. } if object_ty.is_class() => {
+            let field = &class_instance.fields[0];
+            let boxed = object.direct_ptr().offset(field.offset as usize);
+            let boxed: Address = unsafe { *boxed.to_ptr::<Address>() };
+            vm.gc.finalization_queue().register(boxed);
+        }
+
+        _ => {}
+    }
+}
+
 pub extern "C" fn argc() -> i32 {
     let vm = get_vm();
 
@@ -267,6 +397,7 @@ pub extern "C" fn trap(trap_id: u32) {
         Trap::STACK_OVERFLOW => "stack overflow",
         Trap::ILLEGAL => "illegal state",
         Trap::OVERFLOW => "overflow",
+        Trap::UNCAUGHT_EXCEPTION => "uncaught exception",
     };
 
     eprintln!("{}", msg);
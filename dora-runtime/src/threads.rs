@@ -103,6 +103,13 @@ impl Threads {
     pub fn join_all(&self) {
         let mut threads = self.threads.lock();
 
+        while threads.iter().any(|thread| !thread.is_daemon()) {
+            self.cv_join.wait(&mut threads);
+        }
+
+        // only daemon threads are left, ask them to terminate
+        get_vm().gc.finalization_queue().shutdown();
+
         while threads.len() > 0 {
             self.cv_join.wait(&mut threads);
         }
@@ -137,6 +144,8 @@ pub struct DoraThread {
     pub state: AtomicUsize,
     join_data: JoinData,
     blocking_data: BlockingData,
+    // daemon threads don't keep the program alive
+    daemon: bool,
 }
 
 unsafe impl Sync for DoraThread {}
@@ -144,10 +153,14 @@ unsafe impl Send for DoraThread {}
 
 impl DoraThread {
     pub fn new(vm: &VM, initial_state: ThreadState) -> Arc<DoraThread> {
-        DoraThread::with_id(vm.threads.next_thread_id(), initial_state)
+        DoraThread::with_id(vm.threads.next_thread_id(), initial_state, false)
     }
 
-    fn with_id(id: usize, initial_state: ThreadState) -> Arc<DoraThread> {
+    pub fn new_daemon(vm: &VM, initial_state: ThreadState) -> Arc<DoraThread> {
+        DoraThread::with_id(vm.threads.next_thread_id(), initial_state, true)
+    }
+
+    fn with_id(id: usize, initial_state: ThreadState, daemon: bool) -> Arc<DoraThread> {
         Arc::new(DoraThread {
             id: AtomicUsize::new(id),
             handles: HandleMemory::new(),
@@ -155,6 +168,7 @@ impl DoraThread {
             state: AtomicUsize::new(initial_state as usize),
             join_data: JoinData::new(),
             blocking_data: BlockingData::new(),
+            daemon,
         })
     }
 
@@ -162,6 +176,10 @@ impl DoraThread {
         self.id.load(Ordering::Relaxed)
     }
 
+    pub fn is_daemon(&self) -> bool {
+        self.daemon
+    }
+
     pub fn dtn(&self) -> *const DoraToNativeInfo {
         self.tld.dtn.load(Ordering::Relaxed) as *const _
     }
@@ -1,5 +1,14 @@
+use std::sync::OnceLock;
 use std::time::Instant;
 
+static PROCESS_START: OnceLock<Instant> = OnceLock::new();
+
+// nanoseconds since the first call, never decreases.
+pub fn monotonic_nanos() -> i64 {
+    let start = PROCESS_START.get_or_init(Instant::now);
+    start.elapsed().as_nanos() as i64
+}
+
 pub struct Timer {
     start: Option<Instant>,
 }
@@ -14,7 +23,7 @@ impl Timer {
     pub fn stop(&mut self) -> f32 {
         let duration = self.start.expect("not started").elapsed();
 
-        duration.as_secs_f32() / 1000f32
+        duration.as_secs_f32() * 1000f32
     }
 
     pub fn stop_with<F>(&self, f: F)
@@ -24,7 +33,7 @@ impl Timer {
         if let Some(start) = self.start {
             let duration = start.elapsed();
 
-            f(duration.as_secs_f32() / 1000f32);
+            f(duration.as_secs_f32() * 1000f32);
         }
     }
 }
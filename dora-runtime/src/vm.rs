@@ -1,6 +1,7 @@
 use num_enum::{IntoPrimitive, TryFromPrimitive};
 use parking_lot::{Mutex, RwLock};
 use std::collections::HashMap;
+use std::fs::File;
 use std::mem;
 use std::ptr;
 use std::sync::atomic::{AtomicU8, Ordering};
@@ -8,7 +9,8 @@ use std::sync::Arc;
 
 use crate::compiler;
 use crate::compiler::dora_exit_stubs::NativeStubs;
-use crate::gc::{Address, Gc};
+use crate::gc::finalizer;
+use crate::gc::{Address, Gc, GcStats};
 use crate::stack::DoraToNativeInfo;
 use crate::threads::ManagedThread;
 use crate::threads::{
@@ -21,28 +23,32 @@ use dora_bytecode::{
     TraitId,
 };
 
-pub use self::args::{Args, CollectorName, CompilerName, MemSize};
+pub use self::args::{Args, CollectorName, CompilerName, DisasmSyntax, MemSize};
 pub use self::classes::{
     create_class_instance_with_vtable, ClassInstance, ClassInstanceId, FieldInstance, ShapeKind,
 };
 pub use self::code::{
-    install_code, install_code_stub, Code, CodeId, CodeKind, CodeObjects, CommentTable, GcPoint,
-    GcPointTable, LazyCompilationData, LazyCompilationSite, LocationTable, ManagedCodeHeader,
-    RelocationTable, CODE_ALIGNMENT,
+    install_code, install_code_stub, Code, CodeId, CodeKind, CodeObjects, CommentTable,
+    ExceptionHandler, ExceptionHandlerTable, GcPoint, GcPointTable, LazyCompilationData,
+    LazyCompilationSite, LocationTable, ManagedCodeHeader, RelocationTable, CODE_ALIGNMENT,
 };
 pub use self::code_map::CodeMap;
 pub use self::compilation::CompilationDatabase;
+pub use self::coverage::Coverage;
 pub use self::enums::{enum_definition_name, EnumInstance, EnumInstanceId, EnumLayout};
 pub use self::extensions::block_matches_ty;
 pub use self::functions::display_fct;
 use self::globals::GlobalVariableMemory;
-pub use self::impls::{bounds_for_tp, find_trait_impl, tp_implements_trait, ty_implements_trait};
+pub use self::impls::{
+    bounds_for_tp, find_trait_impl, find_trait_impl_with_type_params, tp_implements_trait,
+    ty_implements_trait,
+};
 use self::known::KnownElements;
 pub use self::modules::{module_path, module_path_name};
 pub use self::specialize::{
     add_ref_fields, create_class_instance, create_enum_instance, create_struct_instance,
     ensure_class_instance_for_enum_variant, ensure_class_instance_for_lambda,
-    ensure_class_instance_for_trait_object, specialize_bty, specialize_bty_array,
+    ensure_class_instance_for_trait_object, specialize_bty, specialize_bty_array, weak_ref_field,
 };
 pub use self::structs::{StructInstance, StructInstanceField, StructInstanceId};
 pub use self::stubs::{setup_stubs, Stubs};
@@ -55,6 +61,7 @@ mod classes;
 mod code;
 mod code_map;
 mod compilation;
+mod coverage;
 mod enums;
 mod extensions;
 mod functions;
@@ -134,6 +141,7 @@ pub struct VM {
     pub class_instances: GrowableVecNonIter<ClassInstance>, // stores all class definitions
     pub code_objects: CodeObjects,
     pub compilation_database: CompilationDatabase,
+    pub coverage: Coverage,
     pub enum_specializations: RwLock<HashMap<(EnumId, BytecodeTypeArray), EnumInstanceId>>,
     pub enum_instances: GrowableVecNonIter<EnumInstance>, // stores all enum definitions
     pub trait_vtables: RwLock<HashMap<(TraitId, BytecodeTypeArray), ClassInstanceId>>,
@@ -167,6 +175,7 @@ impl VM {
             known: KnownElements::new(),
             gc,
             compilation_database: CompilationDatabase::new(),
+            coverage: Coverage::new(),
             code_objects: CodeObjects::new(),
             code_map: CodeMap::new(),
             native_stubs: Mutex::new(NativeStubs::new()),
@@ -223,6 +232,55 @@ impl VM {
         fct(tld, ptr);
     }
 
+    // A trap terminates the whole process, so the test is run in a forked
+    // child and the trap is recovered from the child's exit status.
+    #[cfg(target_family = "unix")]
+    pub fn run_test_catching_trap(&self, fct_id: FunctionId) -> TestExit {
+        use std::io::Write;
+
+        std::io::stdout().flush().expect("flushing stdout failed");
+
+        let pid = unsafe { libc::fork() };
+        assert!(pid >= 0, "fork failed");
+
+        if pid == 0 {
+            unsafe {
+                let dev_null = libc::open(c"/dev/null".as_ptr(), libc::O_WRONLY);
+                libc::dup2(dev_null, libc::STDERR_FILENO);
+            }
+
+            self.run_test(fct_id);
+            std::io::stdout().flush().expect("flushing stdout failed");
+
+            unsafe {
+                libc::_exit(0);
+            }
+        }
+
+        let mut status = 0;
+        let result = unsafe { libc::waitpid(pid, &mut status, 0) };
+        assert_eq!(result, pid, "waitpid failed");
+
+        if !libc::WIFEXITED(status) {
+            return TestExit::Abnormal(status);
+        }
+
+        match libc::WEXITSTATUS(status) {
+            0 => TestExit::Finished,
+            // see stdlib::trap for the exit code of a trap
+            code => match Trap::from((code - 100) as u32) {
+                Some(trap) => TestExit::Trap(trap),
+                None => TestExit::Abnormal(code),
+            },
+        }
+    }
+
+    // Without fork a trap can't be recovered from, the test isn't run.
+    #[cfg(target_family = "windows")]
+    pub fn run_test_catching_trap(&self, _fct_id: FunctionId) -> TestExit {
+        TestExit::Unsupported
+    }
+
     pub fn ensure_compiled(&self, fct_id: FunctionId) -> Address {
         let mut dtn = DoraToNativeInfo::new();
         let type_params = BytecodeTypeArray::empty();
@@ -232,10 +290,51 @@ impl VM {
         })
     }
 
+    /// Compiles the given instantiation of a function ahead of its first call
+    /// and returns the start address and size of its machine code. The code is
+    /// registered in the compilation database, so later calls reuse it.
+    pub fn compile_function(
+        &self,
+        fct_id: FunctionId,
+        type_params: &BytecodeTypeArray,
+    ) -> (Address, usize) {
+        let mut dtn = DoraToNativeInfo::new();
+
+        let instruction_start = current_thread().use_dtn(&mut dtn, || {
+            compiler::generate_fct(self, fct_id, type_params)
+        });
+
+        let code_id = self
+            .code_map
+            .get(instruction_start)
+            .expect("compiled code missing");
+        let code = self.code_objects.get(code_id);
+        let size = code.instruction_end().offset_from(instruction_start);
+
+        (instruction_start, size)
+    }
+
+    pub fn dump_coverage(&self) {
+        let result = if let Some(ref path) = self.args.flag_coverage_file {
+            let mut file = File::create(path).expect("couldn't create coverage file");
+            self.coverage.write_lcov(self, &mut file)
+        } else {
+            let stdout = std::io::stdout();
+            let mut stdout = stdout.lock();
+            self.coverage.write_lcov(self, &mut stdout)
+        };
+
+        result.expect("writing coverage report failed");
+    }
+
     pub fn dump_gc_summary(&self, runtime: f32) {
         self.gc.dump_summary(runtime);
     }
 
+    pub fn gc_stats(&self) -> GcStats {
+        self.gc.stats()
+    }
+
     pub fn add_code(&self, code: Arc<Code>) -> CodeId {
         let code_start = code.object_start();
         let code_end = code.object_end();
@@ -322,6 +421,7 @@ impl VM {
 
 impl Drop for VM {
     fn drop(&mut self) {
+        self.gc.shutdown();
         self.gc.drop_all_native_code_objects();
     }
 }
@@ -339,6 +439,7 @@ pub enum Trap {
     STACK_OVERFLOW,
     ILLEGAL,
     OVERFLOW,
+    UNCAUGHT_EXCEPTION,
 }
 
 impl Trap {
@@ -353,6 +454,7 @@ impl Trap {
             Trap::STACK_OVERFLOW => 7,
             Trap::ILLEGAL => 8,
             Trap::OVERFLOW => 9,
+            Trap::UNCAUGHT_EXCEPTION => 10,
         }
     }
 
@@ -367,9 +469,39 @@ impl Trap {
             7 => Some(Trap::STACK_OVERFLOW),
             8 => Some(Trap::ILLEGAL),
             9 => Some(Trap::OVERFLOW),
+            10 => Some(Trap::UNCAUGHT_EXCEPTION),
             _ => None,
         }
     }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Trap::DIV0 => "DIV0",
+            Trap::ASSERT => "ASSERT",
+            Trap::INDEX_OUT_OF_BOUNDS => "INDEX_OUT_OF_BOUNDS",
+            Trap::NIL => "NIL",
+            Trap::CAST => "CAST",
+            Trap::OOM => "OOM",
+            Trap::STACK_OVERFLOW => "STACK_OVERFLOW",
+            Trap::ILLEGAL => "ILLEGAL",
+            Trap::OVERFLOW => "OVERFLOW",
+            Trap::UNCAUGHT_EXCEPTION => "UNCAUGHT_EXCEPTION",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Trap> {
+        (1..=10)
+            .filter_map(Trap::from)
+            .find(|trap| trap.name() == name)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TestExit {
+    Finished,
+    Trap(Trap),
+    Abnormal(i32),
+    Unsupported,
 }
 
 pub fn execute_on_main<F, R>(callback: F) -> R
@@ -398,6 +530,10 @@ where
     let thread = current_thread();
     thread.tld.set_stack_limit(stack_limit);
 
+    if finalizer::needs_finalizer_thread(vm) {
+        finalizer::start_finalizer_thread(vm);
+    }
+
     let result = callback();
 
     vm.threads.remove_current_thread();
@@ -1,3 +1,5 @@
+use crate::gc::swiper::LARGE_OBJECT_SIZE;
+use crate::gc::tlab::TLAB_OBJECT_SIZE;
 use crate::gc::M;
 use crate::gc::{DEFAULT_CODE_SPACE_LIMIT, DEFAULT_READONLY_SPACE_LIMIT};
 use num_cpus;
@@ -9,9 +11,12 @@ use std::ops::Deref;
 pub struct Args {
     pub flag_emit_asm: Option<String>,
     pub flag_emit_asm_file: bool,
+    pub flag_emit_asm_lines: bool,
+    pub flag_asm_syntax: Option<DisasmSyntax>,
     pub flag_emit_compiler: bool,
     pub flag_emit_stubs: bool,
     pub flag_enable_perf: bool,
+    pub flag_enable_gdb: bool,
     pub flag_omit_bounds_check: bool,
     pub flag_emit_debug: Option<String>,
     pub flag_emit_debug_native: bool,
@@ -27,9 +32,14 @@ pub struct Args {
     pub flag_gc_verbose: bool,
     pub flag_gc_dev_verbose: bool,
     pub flag_gc_verify: bool,
+    pub flag_gc_verify_heap: bool,
+    pub flag_gc_concurrent_marking: bool,
     pub flag_gc_worker: usize,
     pub flag_gc_young_size: Option<MemSize>,
+    pub flag_gc_min_young_size: Option<MemSize>,
+    pub flag_gc_max_young_size: Option<MemSize>,
     pub flag_gc_semi_ratio: Option<usize>,
+    pub flag_gc_large_object_size: Option<MemSize>,
     pub flag_gc: Option<CollectorName>,
     pub flag_compiler: Option<CompilerName>,
     pub flag_min_heap_size: Option<MemSize>,
@@ -38,6 +48,10 @@ pub struct Args {
     pub flag_readonly_size: Option<MemSize>,
     pub flag_disable_tlab: bool,
     pub flag_disable_barrier: bool,
+    pub flag_disable_bce: bool,
+    pub flag_hash_seed: Option<u64>,
+    pub flag_coverage: bool,
+    pub flag_coverage_file: Option<String>,
 }
 
 impl Args {
@@ -78,6 +92,29 @@ impl Args {
         self.flag_gc_young_size.map(|young_size| *young_size)
     }
 
+    // a fixed young generation size is also its minimum and maximum size
+    pub fn min_young_size(&self) -> Option<usize> {
+        self.flag_gc_young_size
+            .or(self.flag_gc_min_young_size)
+            .map(|young_size| *young_size)
+    }
+
+    pub fn max_young_size(&self) -> Option<usize> {
+        self.flag_gc_young_size
+            .or(self.flag_gc_max_young_size)
+            .map(|young_size| *young_size)
+    }
+
+    pub fn large_object_size(&self) -> usize {
+        let size = self
+            .flag_gc_large_object_size
+            .map(|s| *s)
+            .unwrap_or(LARGE_OBJECT_SIZE);
+
+        // smaller objects are always allocated in the TLAB
+        max(size, TLAB_OBJECT_SIZE)
+    }
+
     pub fn young_appel(&self) -> bool {
         self.flag_gc_young_size.is_none()
     }
@@ -93,6 +130,14 @@ impl Args {
     pub fn compiler(&self) -> CompilerName {
         self.flag_compiler.unwrap_or(CompilerName::Cannon)
     }
+
+    pub fn asm_syntax(&self) -> DisasmSyntax {
+        self.flag_asm_syntax.unwrap_or(DisasmSyntax::Intel)
+    }
+
+    pub fn coverage(&self) -> bool {
+        self.flag_coverage || self.flag_coverage_file.is_some()
+    }
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -122,6 +167,12 @@ pub enum CompilerName {
     Boots,
 }
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DisasmSyntax {
+    Intel,
+    ATT,
+}
+
 impl fmt::Display for CompilerName {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let text = match self {
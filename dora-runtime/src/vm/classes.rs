@@ -2,7 +2,7 @@ use parking_lot::RwLock;
 
 use crate::size::InstanceSize;
 use crate::utils::Id;
-use crate::vm::{add_ref_fields, VM};
+use crate::vm::{add_ref_fields, weak_ref_field, VM};
 use crate::vtable::VTableBox;
 use dora_bytecode::{BytecodeType, BytecodeTypeArray, ClassId, EnumId, FunctionId, TraitId};
 
@@ -51,6 +51,8 @@ pub struct ClassInstance {
     pub fields: Vec<FieldInstance>,
     pub size: InstanceSize,
     pub ref_fields: Vec<i32>,
+    // offset of the referent in WeakRef objects, also part of ref_fields
+    pub weak_field: Option<i32>,
     pub vtable: RwLock<Option<VTableBox>>,
 }
 
@@ -88,6 +90,7 @@ pub fn create_class_instance_with_vtable(
     vtable_entries: usize,
 ) -> ClassInstanceId {
     let ref_fields = build_ref_fields(vm, &kind, size, &fields);
+    let weak_field = weak_ref_field(vm, &kind, &fields);
 
     let size = match size {
         InstanceSize::StructArray(element_size) if ref_fields.is_empty() => {
@@ -102,6 +105,7 @@ pub fn create_class_instance_with_vtable(
         fields,
         size,
         ref_fields,
+        weak_field,
         vtable: RwLock::new(None),
     });
     let class_instance = vm.class_instances.idx(class_instance_id);
@@ -11,7 +11,7 @@ use crate::masm::CodeDescriptor;
 use crate::mem;
 use crate::object::Header;
 use crate::os;
-use crate::vm::VM;
+use crate::vm::{ClassInstanceId, VM};
 use crate::vtable::VTable;
 use dora_bytecode::{BytecodeTypeArray, FunctionId, Location};
 
@@ -28,6 +28,8 @@ pub enum CodeKind {
     DoraStub,
     GuardCheckStub,
     SafepointStub,
+    ThrowStub,
+    ResumeStub,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -129,6 +131,7 @@ pub fn install_code(vm: &VM, code_descriptor: CodeDescriptor, kind: CodeKind) ->
         gcpoints: code_descriptor.gcpoints,
         comments: code_descriptor.comments,
         locations: code_descriptor.positions,
+        exception_handlers: code_descriptor.exception_handlers,
     });
 
     let code_header = object_start.to_mut_ptr::<ManagedCodeHeader>();
@@ -155,6 +158,7 @@ pub struct Code {
     gcpoints: GcPointTable,
     comments: CommentTable,
     locations: LocationTable,
+    exception_handlers: ExceptionHandlerTable,
 }
 
 impl Code {
@@ -162,6 +166,14 @@ impl Code {
         self.locations.get(offset)
     }
 
+    pub fn exception_handler_for_offset(
+        &self,
+        offset: u32,
+        class_instance_id: ClassInstanceId,
+    ) -> Option<&ExceptionHandler> {
+        self.exception_handlers.find(offset, class_instance_id)
+    }
+
     pub fn gcpoint_for_offset(&self, offset: u32) -> Option<&GcPoint> {
         self.gcpoints.get(offset)
     }
@@ -247,7 +259,7 @@ impl GcPointTable {
     }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct GcPoint {
     pub offsets: Vec<i32>,
 }
@@ -278,6 +290,54 @@ impl GcPoint {
     }
 }
 
+// Try regions of a function in machine code offsets. An object of the given
+// class thrown in [try_start, try_end) continues at `handler`, the stack
+// pointer needs to be reset to `fp - framesize` before.
+#[derive(Debug)]
+pub struct ExceptionHandler {
+    pub try_start: u32,
+    pub try_end: u32,
+    pub handler: u32,
+    pub class_instance_id: ClassInstanceId,
+    pub framesize: i32,
+}
+
+#[derive(Debug)]
+pub struct ExceptionHandlerTable {
+    entries: Vec<ExceptionHandler>,
+}
+
+impl Default for ExceptionHandlerTable {
+    fn default() -> ExceptionHandlerTable {
+        ExceptionHandlerTable::new()
+    }
+}
+
+impl ExceptionHandlerTable {
+    pub fn new() -> ExceptionHandlerTable {
+        ExceptionHandlerTable {
+            entries: Vec::new(),
+        }
+    }
+
+    // handlers of inner try regions are inserted first
+    pub fn insert(&mut self, handler: ExceptionHandler) {
+        self.entries.push(handler);
+    }
+
+    pub fn find(
+        &self,
+        offset: u32,
+        class_instance_id: ClassInstanceId,
+    ) -> Option<&ExceptionHandler> {
+        self.entries.iter().find(|handler| {
+            handler.try_start <= offset
+                && offset < handler.try_end
+                && handler.class_instance_id == class_instance_id
+        })
+    }
+}
+
 pub struct CommentTable {
     entries: Vec<(u32, String)>,
 }
@@ -290,21 +350,14 @@ impl CommentTable {
     }
 
     pub fn get(&self, offset: u32) -> Vec<&String> {
-        let result = self
-            .entries
-            .binary_search_by_key(&offset, |&(offset, _)| offset);
+        // Multiple comments can share an offset, start at the first of them.
+        let start = self.entries.partition_point(|&(o, _)| o < offset);
 
-        match result {
-            Ok(mut idx) => {
-                let mut comments = Vec::new();
-                while idx < self.entries.len() && self.entries[idx].0 == offset {
-                    comments.push(&self.entries[idx].1);
-                    idx += 1;
-                }
-                comments
-            }
-            Err(_) => Vec::new(),
-        }
+        self.entries[start..]
+            .iter()
+            .take_while(|&&(o, _)| o == offset)
+            .map(|(_, comment)| comment)
+            .collect()
     }
 
     pub fn insert(&mut self, offset: u32, comment: String) {
@@ -384,6 +437,9 @@ impl LazyCompilationData {
 pub enum LazyCompilationSite {
     Direct(FunctionId, i32, BytecodeTypeArray),
     Virtual(bool, FunctionId, u32, BytecodeTypeArray),
+    // Like `Virtual`, additionally fills the inline cache whose vtable and
+    // target slots are at the given displacements from the return address.
+    VirtualCache(bool, FunctionId, u32, BytecodeTypeArray, i32, i32),
     Lambda(bool),
 }
 
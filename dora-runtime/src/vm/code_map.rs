@@ -40,6 +40,8 @@ impl CodeMap {
                 CodeKind::DoraStub => println!("dora_stub"),
                 CodeKind::GuardCheckStub => println!("guard_check_stub"),
                 CodeKind::SafepointStub => println!("safepoint_stub"),
+                CodeKind::ThrowStub => println!("throw_stub"),
+                CodeKind::ResumeStub => println!("resume_stub"),
             }
         }
 
@@ -0,0 +1,84 @@
+use parking_lot::Mutex;
+
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::gc::Address;
+use crate::vm::VM;
+use dora_bytecode::SourceFileId;
+
+struct CoverageCounter {
+    file: SourceFileId,
+    line: u32,
+    hits: Box<AtomicU64>,
+}
+
+// Hit counters for the basic blocks of all functions compiled with
+// --coverage. Compiled code increments the counters directly, so each
+// counter is boxed to keep its address stable.
+pub struct Coverage {
+    counters: Mutex<Vec<CoverageCounter>>,
+}
+
+impl Default for Coverage {
+    fn default() -> Coverage {
+        Coverage::new()
+    }
+}
+
+impl Coverage {
+    pub fn new() -> Coverage {
+        Coverage {
+            counters: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn add_counter(&self, file: SourceFileId, line: u32) -> Address {
+        let hits = Box::new(AtomicU64::new(0));
+        let address = Address::from_ptr(&*hits as *const AtomicU64);
+
+        self.counters
+            .lock()
+            .push(CoverageCounter { file, line, hits });
+
+        address
+    }
+
+    // Blocks starting on the same line add up, this also merges the blocks
+    // of all specializations of a generic function.
+    fn hits_per_line(&self) -> BTreeMap<u32, BTreeMap<u32, u64>> {
+        let counters = self.counters.lock();
+        let mut result: BTreeMap<u32, BTreeMap<u32, u64>> = BTreeMap::new();
+
+        for counter in counters.iter() {
+            let hits = counter.hits.load(Ordering::Relaxed);
+            *result
+                .entry(counter.file.0)
+                .or_default()
+                .entry(counter.line)
+                .or_default() += hits;
+        }
+
+        result
+    }
+
+    pub fn write_lcov(&self, vm: &VM, w: &mut dyn Write) -> io::Result<()> {
+        for (file, lines) in self.hits_per_line() {
+            let path = &vm.program.source_files[file as usize].path;
+            writeln!(w, "TN:")?;
+            writeln!(w, "SF:{}", path)?;
+
+            for (line, hits) in &lines {
+                writeln!(w, "DA:{},{}", line, hits)?;
+            }
+
+            let lines_hit = lines.values().filter(|&&hits| hits > 0).count();
+            writeln!(w, "LF:{}", lines.len())?;
+            writeln!(w, "LH:{}", lines_hit)?;
+            writeln!(w, "end_of_record")?;
+        }
+
+        Ok(())
+    }
+}
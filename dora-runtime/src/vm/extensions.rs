@@ -138,6 +138,9 @@ fn compare_concrete_types(
         | BytecodeType::Bool
         | BytecodeType::Char
         | BytecodeType::UInt8
+        | BytecodeType::UInt16
+        | BytecodeType::UInt32
+        | BytecodeType::UInt64
         | BytecodeType::Int32
         | BytecodeType::Int64
         | BytecodeType::Float32
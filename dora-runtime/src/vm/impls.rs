@@ -9,6 +9,16 @@ pub fn find_trait_impl(
     trait_ty: BytecodeType,
     object_type: BytecodeType,
 ) -> FunctionId {
+    find_trait_impl_with_type_params(vm, fct_id, trait_ty, object_type).0
+}
+
+// also returns the type params of the impl block for the given object type
+pub fn find_trait_impl_with_type_params(
+    vm: &VM,
+    fct_id: FunctionId,
+    trait_ty: BytecodeType,
+    object_type: BytecodeType,
+) -> (FunctionId, BytecodeTypeArray) {
     debug_assert!(object_type.is_concrete_type());
 
     let type_param_data = TypeParamData {
@@ -16,7 +26,7 @@ pub fn find_trait_impl(
         bounds: Vec::new(),
     };
 
-    let impl_id = find_impl(vm, object_type, &type_param_data, trait_ty.clone())
+    let impl_id = find_impl(vm, object_type.clone(), &type_param_data, trait_ty.clone())
         .expect("no impl found for generic trait method call");
 
     let impl_ = &vm.program.impls[impl_id.0 as usize];
@@ -33,7 +43,10 @@ pub fn find_trait_impl(
         .position(|mid| *mid == fct_id)
         .expect("trait method id not found");
 
-    impl_.methods[trait_entry_id]
+    let type_params = impl_block_matches_ty(vm, object_type, &type_param_data, impl_id)
+        .expect("impl doesn't match type");
+
+    (impl_.methods[trait_entry_id], type_params)
 }
 
 fn find_impl(
@@ -103,6 +116,9 @@ pub fn ty_implements_trait(
 
         BytecodeType::Bool
         | BytecodeType::UInt8
+        | BytecodeType::UInt16
+        | BytecodeType::UInt32
+        | BytecodeType::UInt64
         | BytecodeType::Char
         | BytecodeType::Int32
         | BytecodeType::Int64
@@ -17,12 +17,16 @@ pub struct KnownElements {
     pub code_class_instance: Option<ClassInstanceId>,
 
     pub zero_trait_id: Option<TraitId>,
+    pub finalizable_trait_id: Option<TraitId>,
     pub array_class_id: Option<ClassId>,
     pub string_class_id: Option<ClassId>,
     pub thread_class_id: Option<ClassId>,
     pub stacktrace_element_class_id: Option<ClassId>,
+    pub weak_ref_class_id: Option<ClassId>,
     pub stacktrace_retrieve_fct_id: Option<FunctionId>,
+    pub throwable_new_fct_id: Option<FunctionId>,
     pub boots_compile_fct_id: Option<FunctionId>,
+    pub finalize_fct_id: Option<FunctionId>,
 }
 
 impl KnownElements {
@@ -40,12 +44,16 @@ impl KnownElements {
             code_class_instance: None,
 
             zero_trait_id: None,
+            finalizable_trait_id: None,
             array_class_id: None,
             string_class_id: None,
             thread_class_id: None,
             stacktrace_element_class_id: None,
+            weak_ref_class_id: None,
             stacktrace_retrieve_fct_id: None,
+            throwable_new_fct_id: None,
             boots_compile_fct_id: None,
+            finalize_fct_id: None,
         }
     }
 
@@ -65,6 +73,10 @@ impl KnownElements {
         self.zero_trait_id.expect("uninitialized")
     }
 
+    pub fn finalizable_trait_id(&self) -> TraitId {
+        self.finalizable_trait_id.expect("uninitialized")
+    }
+
     pub fn array_class_id(&self) -> ClassId {
         self.array_class_id.expect("uninitialized")
     }
@@ -85,7 +97,15 @@ impl KnownElements {
         self.stacktrace_retrieve_fct_id.expect("uninitialized")
     }
 
+    pub fn throwable_new_fct_id(&self) -> FunctionId {
+        self.throwable_new_fct_id.expect("uninitialized")
+    }
+
     pub fn boots_compile_fct_id(&self) -> FunctionId {
         self.boots_compile_fct_id.expect("uninitialized")
     }
+
+    pub fn finalize_fct_id(&self) -> FunctionId {
+        self.finalize_fct_id.expect("uninitialized")
+    }
 }
@@ -260,6 +260,9 @@ pub fn add_ref_fields(vm: &VM, ref_fields: &mut Vec<i32>, offset: i32, ty: Bytec
         BytecodeType::Bool
         | BytecodeType::Char
         | BytecodeType::UInt8
+        | BytecodeType::UInt16
+        | BytecodeType::UInt32
+        | BytecodeType::UInt64
         | BytecodeType::Int32
         | BytecodeType::Int64
         | BytecodeType::Float32
@@ -277,6 +280,34 @@ pub fn add_ref_fields(vm: &VM, ref_fields: &mut Vec<i32>, offset: i32, ty: Bytec
     }
 }
 
+// The referent of WeakRef[T] is only weak when Option[T] is a nullable
+// pointer. For all other types the option is boxed and nothing but the
+// WeakRef would keep the box alive, so the field stays strong.
+pub fn weak_ref_field(vm: &VM, kind: &ShapeKind, fields: &[FieldInstance]) -> Option<i32> {
+    match kind {
+        ShapeKind::Class(cls_id, _) if vm.known.weak_ref_class_id == Some(*cls_id) => {
+            assert_eq!(fields.len(), 1);
+            let field = &fields[0];
+
+            match &field.ty {
+                BytecodeType::Enum(enum_id, type_params) => {
+                    let edef_id = create_enum_instance(vm, *enum_id, type_params.clone());
+                    let edef = vm.enum_instances.idx(edef_id);
+
+                    match edef.layout {
+                        EnumLayout::Ptr => Some(field.offset),
+                        EnumLayout::Int | EnumLayout::Tagged => None,
+                    }
+                }
+
+                _ => unreachable!(),
+            }
+        }
+
+        _ => None,
+    }
+}
+
 pub fn create_class_instance(
     vm: &VM,
     cls_id: ClassId,
@@ -413,6 +444,9 @@ fn create_specialized_class_array(
 
             BytecodeType::Bool
             | BytecodeType::UInt8
+            | BytecodeType::UInt16
+            | BytecodeType::UInt32
+            | BytecodeType::UInt64
             | BytecodeType::Char
             | BytecodeType::Int32
             | BytecodeType::Int64
@@ -594,6 +628,9 @@ pub fn specialize_bty(ty: BytecodeType, type_params: &BytecodeTypeArray) -> Byte
 
         BytecodeType::Unit
         | BytecodeType::UInt8
+        | BytecodeType::UInt16
+        | BytecodeType::UInt32
+        | BytecodeType::UInt64
         | BytecodeType::Bool
         | BytecodeType::Char
         | BytecodeType::Int32
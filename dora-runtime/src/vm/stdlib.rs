@@ -6,7 +6,7 @@ use crate::stdlib;
 use crate::vm::VM;
 use dora_bytecode::program::InternalClass;
 use dora_bytecode::program::InternalFunction;
-use dora_bytecode::{ClassId, FunctionId, NativeFunction};
+use dora_bytecode::{ClassId, FunctionId, FunctionKind, NativeFunction};
 
 pub fn resolve_native_functions(vm: &mut VM) {
     let mut mappings: HashMap<NativeFunction, *const u8> = HashMap::from([
@@ -25,7 +25,25 @@ pub fn resolve_native_functions(vm: &mut VM) {
             NativeFunction::ForceMinorCollect,
             stdlib::gc_minor_collect as *const u8,
         ),
+        (NativeFunction::GcStat, stdlib::gc_stat as *const u8),
+        (
+            NativeFunction::GcSetYoungSize,
+            stdlib::gc_set_young_size as *const u8,
+        ),
+        (
+            NativeFunction::RegisterFinalizer,
+            stdlib::register_finalizer as *const u8,
+        ),
         (NativeFunction::Timestamp, stdlib::timestamp as *const u8),
+        (
+            NativeFunction::MonotonicNanos,
+            stdlib::monotonic_nanos as *const u8,
+        ),
+        (NativeFunction::HashSeed, stdlib::hash_seed as *const u8),
+        (
+            NativeFunction::HasHashSeed,
+            stdlib::has_hash_seed as *const u8,
+        ),
         (NativeFunction::Sleep, stdlib::sleep as *const u8),
         (
             NativeFunction::UInt8ToString,
@@ -85,6 +103,20 @@ pub fn resolve_native_functions(vm: &mut VM) {
             NativeFunction::Float64ToString,
             stdlib::float64_to_string as *const u8,
         ),
+        (
+            NativeFunction::Float64ToStringFixed,
+            stdlib::float64_to_string_fixed as *const u8,
+        ),
+        (NativeFunction::MathSin, stdlib::math_sin as *const u8),
+        (NativeFunction::MathCos, stdlib::math_cos as *const u8),
+        (NativeFunction::MathTan, stdlib::math_tan as *const u8),
+        (NativeFunction::MathExp, stdlib::math_exp as *const u8),
+        (NativeFunction::MathLn, stdlib::math_ln as *const u8),
+        (NativeFunction::MathPow, stdlib::math_pow as *const u8),
+        (
+            NativeFunction::RandomOsSeed,
+            stdlib::random_os_seed as *const u8,
+        ),
         (
             NativeFunction::StringFromBytesPart,
             stdlib::str_from_bytes as *const u8,
@@ -168,6 +200,10 @@ pub fn resolve_native_functions(vm: &mut VM) {
             stdlib::io::socket_accept as *const u8,
         ),
         (NativeFunction::StringClone, stdlib::str_clone as *const u8),
+        (
+            NativeFunction::StringCopyBytes,
+            stdlib::str_copy_bytes as *const u8,
+        ),
     ]);
 
     for (fct_id, fct) in vm.program.functions.iter().enumerate() {
@@ -196,6 +232,7 @@ pub fn resolve_internal_classes(vm: &mut VM) {
                 InternalClass::StacktraceElement => {
                     vm.known.stacktrace_element_class_id = Some(cls_id)
                 }
+                InternalClass::WeakRef => vm.known.weak_ref_class_id = Some(cls_id),
             }
         }
     }
@@ -213,6 +250,18 @@ pub fn resolve_internal_functions(vm: &mut VM) {
                 InternalFunction::StacktraceRetrieve => {
                     vm.known.stacktrace_retrieve_fct_id = Some(fct_id);
                 }
+                InternalFunction::ThrowableNew => {
+                    vm.known.throwable_new_fct_id = Some(fct_id);
+                }
+                InternalFunction::Finalize => {
+                    let trait_id = match fct.kind {
+                        FunctionKind::Trait(trait_id) => trait_id,
+                        _ => unreachable!(),
+                    };
+
+                    vm.known.finalize_fct_id = Some(fct_id);
+                    vm.known.finalizable_trait_id = Some(trait_id);
+                }
             }
         }
     }
@@ -1,8 +1,9 @@
 use crate::compiler::dora_entry_stub;
 use crate::compiler::dora_exit_stubs::{self, NativeFct, NativeFctKind};
-use crate::compiler::lazy_compilation_stub;
+use crate::compiler::{lazy_compilation_stub, resume_stub};
 use crate::gc::Address;
 use crate::safepoint;
+use crate::stack;
 use crate::stdlib;
 use crate::vm::VM;
 use dora_bytecode::{BytecodeType, BytecodeTypeArray};
@@ -11,6 +12,8 @@ pub struct Stubs {
     compile: Option<Address>,
     dora_entry: Option<Address>,
     trap: Option<Address>,
+    throw: Option<Address>,
+    resume: Option<Address>,
     stack_overflow: Option<Address>,
     safepoint: Option<Address>,
 }
@@ -21,6 +24,8 @@ impl Stubs {
             compile: None,
             dora_entry: None,
             trap: None,
+            throw: None,
+            resume: None,
             stack_overflow: None,
             safepoint: None,
         }
@@ -38,6 +43,14 @@ impl Stubs {
         self.trap.expect("uninitialized field")
     }
 
+    pub fn throw(&self) -> Address {
+        self.throw.expect("uninitialized field")
+    }
+
+    pub fn resume(&self) -> Address {
+        self.resume.expect("uninitialized field")
+    }
+
     pub fn stack_overflow(&self) -> Address {
         self.stack_overflow.expect("uninitialized field")
     }
@@ -59,6 +72,17 @@ pub fn setup_stubs(vm: &mut VM) {
     let code = dora_exit_stubs::generate(vm, ifct, false);
     vm.stubs.trap = Some(code.instruction_start());
 
+    let ifct = NativeFct {
+        fctptr: Address::from_ptr(stack::throw_exception as *const u8),
+        args: BytecodeTypeArray::one(BytecodeType::Ptr),
+        return_type: BytecodeType::Unit,
+        desc: NativeFctKind::ThrowStub,
+    };
+    let code = dora_exit_stubs::generate(vm, ifct, false);
+    vm.stubs.throw = Some(code.instruction_start());
+
+    vm.stubs.resume = Some(resume_stub::generate(vm).instruction_start());
+
     vm.stubs.compile = Some(lazy_compilation_stub::generate(vm).instruction_start());
 
     let ifct = NativeFct {
@@ -37,6 +37,9 @@ impl BytecodeTypeExt for BytecodeType {
         match self {
             &BytecodeType::Bool
             | &BytecodeType::UInt8
+            | &BytecodeType::UInt16
+            | &BytecodeType::UInt32
+            | &BytecodeType::UInt64
             | &BytecodeType::Char
             | &BytecodeType::Int32
             | &BytecodeType::Int64
@@ -63,6 +66,9 @@ impl BytecodeTypeExt for BytecodeType {
             | BytecodeType::Trait(_, params) => params.clone(),
             &BytecodeType::Bool
             | &BytecodeType::UInt8
+            | &BytecodeType::UInt16
+            | &BytecodeType::UInt32
+            | &BytecodeType::UInt64
             | &BytecodeType::Char
             | &BytecodeType::Int32
             | &BytecodeType::Int64
@@ -93,6 +99,9 @@ impl<'a> BytecodeTypePrinter<'a> {
         match ty {
             BytecodeType::Unit => write!(fmt, "()"),
             BytecodeType::UInt8 => write!(fmt, "UInt8"),
+            BytecodeType::UInt16 => write!(fmt, "UInt16"),
+            BytecodeType::UInt32 => write!(fmt, "UInt32"),
+            BytecodeType::UInt64 => write!(fmt, "UInt64"),
             BytecodeType::Char => write!(fmt, "Char"),
             BytecodeType::Int32 => write!(fmt, "Int32"),
             BytecodeType::Int64 => write!(fmt, "Int64"),
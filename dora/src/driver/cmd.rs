@@ -2,7 +2,7 @@ use std::default::Default;
 use std::path::PathBuf;
 
 use dora_runtime::Args as VmArgs;
-use dora_runtime::{CollectorName, CompilerName, MemSize};
+use dora_runtime::{CollectorName, CompilerName, DisasmSyntax, MemSize};
 
 // Write the Docopt usage string.
 static USAGE: &'static str = "
@@ -16,7 +16,11 @@ Options:
     --emit-ast=<fct>        Emits AST to stdout.
     --emit-asm=<fct>        Emits assembly code to stdout.
     --emit-asm-file         Emits assembly code into file `dora-<pid>.asm`.
+    --emit-asm-lines        Annotates emitted assembly code with source lines.
     --emit-bytecode=<fct>   Emits bytecode to stdout.
+    --emit-bytecode-json=<fct>
+                            Emits bytecode as JSON to stdout.
+    --explain-desugar=<fct> Emits each desugaring step of function to stdout.
     --emit-stubs            Emits generated stubs.
     --emit-debug=<fct>      Emits debug instruction at beginning of functions.
     --emit-debug-native     Emits debug instruction at beginning of native stub.
@@ -24,9 +28,12 @@ Options:
     --emit-debug-entry      Emits debug instruction at beginning of entry stub.
     --omit-bounds-check     Omit array index out of bounds checks.
     --check                 Only type check given program.
-    --asm-syntax TYPE       Emits assembly with Intel or AT&T syntax.
-                            Allowed values: intel, att.
+    --bytecode-cache=<file> Load bytecode from file if sources are unchanged,
+                            otherwise compile and store bytecode in file.
+    --asm-syntax=<syntax>   Emits assembly with Intel or AT&T syntax.
+                            Allowed values: intel (default), att.
     --enable-perf           Enable dump for perf.
+    --enable-gdb            Register compiled code with gdb's JIT interface.
     --gc-events             Dump GC events.
     --gc-stress             Collect garbage at every allocation.
     --gc-stress-minor       Minor collection at every allocation.
@@ -37,17 +44,29 @@ Options:
     --gc-verbose            Verbose GC.
     --gc-dev-verbose        Verbose GC for developers.
     --gc-verify             Verify heap before and after collections.
+    --gc-verify-heap        Verify card table after collections (swiper only).
+    --gc-concurrent-marking Mark old generation concurrently (swiper and cannon only).
     --gc-worker=<num>       Number of GC worker threads.
     --gc=<name>             Switch GC. Possible values: zero, copy, swiper (default).
     --gc-young-size=<SIZE>  Use fixed size for young generation.
+    --gc-min-young-size=<SIZE>
+                            Set minimum size of young generation.
+    --gc-max-young-size=<SIZE>
+                            Set maximum size of young generation.
     --gc-semi-ratio=<num>   Use fixed ratio of semi space in young generation.
+    --gc-large-object-size=<SIZE>
+                            Allocate objects of at least this size in large space.
 
     --compiler=<name>       Switch default compiler. Possible values: cannon [default: cannon].
     --test-filter=<name>    Filter tests.
     --clear-regs            Clear register when freeing.
+    --hash-seed=<num>       Seed used by HashMap and HashSet when none is given.
+    --coverage              Count executed basic blocks and print an lcov report at exit.
+    --coverage-file=<file>  Like --coverage but writes the report into the given file.
 
     --disable-tlab          Disable tlab allocation.
     --disable-barrier       Disable barriers.
+    --disable-bce           Disable bounds check elimination in loops.
 
     --min-heap-size=<SIZE>  Set minimum heap size.
     --max-heap-size=<SIZE>  Set maximum heap size.
@@ -62,12 +81,18 @@ pub struct Args {
 
     pub flag_emit_ast: Option<String>,
     pub flag_output: Option<String>,
+    pub flag_bytecode_cache: Option<String>,
     pub flag_emit_asm: Option<String>,
     pub flag_emit_asm_file: bool,
+    pub flag_emit_asm_lines: bool,
+    pub flag_asm_syntax: Option<DisasmSyntax>,
     pub flag_emit_bytecode: Option<String>,
+    pub flag_emit_bytecode_json: Option<String>,
+    pub flag_explain_desugar: Option<String>,
     pub flag_emit_compiler: bool,
     pub flag_emit_stubs: bool,
     pub flag_enable_perf: bool,
+    pub flag_enable_gdb: bool,
     pub flag_omit_bounds_check: bool,
     pub flag_version: bool,
     pub flag_help: bool,
@@ -85,9 +110,14 @@ pub struct Args {
     pub flag_gc_verbose: bool,
     pub flag_gc_dev_verbose: bool,
     pub flag_gc_verify: bool,
+    pub flag_gc_verify_heap: bool,
+    pub flag_gc_concurrent_marking: bool,
     pub flag_gc_worker: usize,
     flag_gc_young_size: Option<MemSize>,
+    flag_gc_min_young_size: Option<MemSize>,
+    flag_gc_max_young_size: Option<MemSize>,
     pub flag_gc_semi_ratio: Option<usize>,
+    pub flag_gc_large_object_size: Option<MemSize>,
     pub flag_gc: Option<CollectorName>,
     pub flag_compiler: Option<CompilerName>,
     pub flag_min_heap_size: Option<MemSize>,
@@ -97,7 +127,11 @@ pub struct Args {
     pub flag_check: bool,
     pub flag_disable_tlab: bool,
     pub flag_disable_barrier: bool,
+    pub flag_disable_bce: bool,
     pub flag_test_filter: Option<String>,
+    pub flag_hash_seed: Option<u64>,
+    pub flag_coverage: bool,
+    pub flag_coverage_file: Option<String>,
     pub packages: Vec<(String, PathBuf)>,
 
     pub command: Command,
@@ -110,10 +144,15 @@ impl Default for Args {
             arg_file: None,
 
             flag_output: None,
+            flag_bytecode_cache: None,
             flag_emit_ast: None,
             flag_emit_asm: None,
             flag_emit_asm_file: false,
+            flag_emit_asm_lines: false,
+            flag_asm_syntax: None,
             flag_emit_bytecode: None,
+            flag_emit_bytecode_json: None,
+            flag_explain_desugar: None,
             flag_emit_compiler: false,
             flag_emit_stubs: false,
             flag_emit_debug: None,
@@ -121,6 +160,7 @@ impl Default for Args {
             flag_emit_debug_native: false,
             flag_emit_debug_entry: false,
             flag_enable_perf: false,
+            flag_enable_gdb: false,
             flag_omit_bounds_check: false,
             flag_version: false,
             flag_help: false,
@@ -134,9 +174,14 @@ impl Default for Args {
             flag_gc_verbose: false,
             flag_gc_dev_verbose: false,
             flag_gc_verify: false,
+            flag_gc_verify_heap: false,
+            flag_gc_concurrent_marking: false,
             flag_gc_worker: 0,
             flag_gc_young_size: None,
+            flag_gc_min_young_size: None,
+            flag_gc_max_young_size: None,
             flag_gc_semi_ratio: None,
+            flag_gc_large_object_size: None,
             flag_gc: None,
             flag_compiler: None,
             flag_min_heap_size: None,
@@ -146,7 +191,11 @@ impl Default for Args {
             flag_check: false,
             flag_disable_tlab: false,
             flag_disable_barrier: false,
+            flag_disable_bce: false,
             flag_test_filter: None,
+            flag_hash_seed: None,
+            flag_coverage: false,
+            flag_coverage_file: None,
             packages: Vec::new(),
 
             command: Command::Run,
@@ -203,14 +252,30 @@ pub fn parse_arguments() -> Result<Args, String> {
             args.flag_check = true;
         } else if arg == "-h" || arg == "--help" {
             args.flag_help = true;
+        } else if arg.starts_with("--bytecode-cache=") {
+            args.flag_bytecode_cache = Some(argument_value(arg).into());
         } else if arg.starts_with("--emit-ast=") {
             args.flag_emit_ast = Some(argument_value(arg).into());
         } else if arg.starts_with("--emit-asm=") {
             args.flag_emit_asm = Some(argument_value(arg).into());
         } else if arg == "--emit-asm-file" {
             args.flag_emit_asm_file = true;
+        } else if arg == "--emit-asm-lines" {
+            args.flag_emit_asm_lines = true;
+        } else if arg.starts_with("--asm-syntax=") {
+            let value = argument_value(arg);
+            let value = match value {
+                "intel" => DisasmSyntax::Intel,
+                "att" => DisasmSyntax::ATT,
+                _ => return Err(format!("--asm-syntax: unknown syntax '{}'", value)),
+            };
+            args.flag_asm_syntax = Some(value);
         } else if arg.starts_with("--emit-bytecode=") {
             args.flag_emit_bytecode = Some(argument_value(arg).into());
+        } else if arg.starts_with("--emit-bytecode-json=") {
+            args.flag_emit_bytecode_json = Some(argument_value(arg).into());
+        } else if arg.starts_with("--explain-desugar=") {
+            args.flag_explain_desugar = Some(argument_value(arg).into());
         } else if arg == "--emit-stubs" {
             args.flag_emit_stubs = true;
         } else if arg.starts_with("--emit-debug=") {
@@ -227,6 +292,8 @@ pub fn parse_arguments() -> Result<Args, String> {
             args.flag_omit_bounds_check = true;
         } else if arg == "--enable-perf" {
             args.flag_enable_perf = true;
+        } else if arg == "--enable-gdb" {
+            args.flag_enable_gdb = true;
         } else if arg == "--gc-events" {
             args.flag_gc_events = true;
         } else if arg == "--gc-stress" {
@@ -247,6 +314,10 @@ pub fn parse_arguments() -> Result<Args, String> {
             args.flag_gc_dev_verbose = true;
         } else if arg == "--gc-verify" {
             args.flag_gc_verify = true;
+        } else if arg == "--gc-verify-heap" {
+            args.flag_gc_verify_heap = true;
+        } else if arg == "--gc-concurrent-marking" {
+            args.flag_gc_concurrent_marking = true;
         } else if arg.starts_with("--gc-worker=") {
             args.flag_gc_worker = argument_usize(arg)?;
         } else if arg.starts_with("--gc=") {
@@ -263,8 +334,14 @@ pub fn parse_arguments() -> Result<Args, String> {
             args.flag_gc = Some(value);
         } else if arg.starts_with("--gc-young-size=") {
             args.flag_gc_young_size = Some(argument_mem_size(arg)?);
+        } else if arg.starts_with("--gc-min-young-size=") {
+            args.flag_gc_min_young_size = Some(argument_mem_size(arg)?);
+        } else if arg.starts_with("--gc-max-young-size=") {
+            args.flag_gc_max_young_size = Some(argument_mem_size(arg)?);
         } else if arg.starts_with("--gc-semi-ratio=") {
             args.flag_gc_semi_ratio = Some(argument_usize(arg)?);
+        } else if arg.starts_with("--gc-large-object-size=") {
+            args.flag_gc_large_object_size = Some(argument_mem_size(arg)?);
         } else if arg.starts_with("--compiler=") {
             let value = argument_value(arg);
             let value = match value {
@@ -275,6 +352,12 @@ pub fn parse_arguments() -> Result<Args, String> {
             args.flag_compiler = Some(value);
         } else if arg.starts_with("--test-filter=") {
             args.flag_test_filter = Some(argument_value(arg).into());
+        } else if arg.starts_with("--hash-seed=") {
+            args.flag_hash_seed = Some(argument_u64(arg)?);
+        } else if arg == "--coverage" {
+            args.flag_coverage = true;
+        } else if arg.starts_with("--coverage-file=") {
+            args.flag_coverage_file = Some(argument_value(arg).into());
         } else if arg == "--disable-tlab" {
             args.flag_disable_tlab = true;
         } else if arg == "-o" {
@@ -285,6 +368,8 @@ pub fn parse_arguments() -> Result<Args, String> {
             idx += 1;
         } else if arg == "--disable-barrier" {
             args.flag_disable_barrier = true;
+        } else if arg == "--disable-bce" {
+            args.flag_disable_bce = true;
         } else if arg.starts_with("--min-heap-size=") {
             args.flag_min_heap_size = Some(argument_mem_size(arg)?);
         } else if arg.starts_with("--max-heap-size=") {
@@ -327,6 +412,10 @@ pub fn parse_arguments() -> Result<Args, String> {
         idx = idx + 1;
     }
 
+    if args.flag_gc_concurrent_marking && matches!(args.flag_compiler, Some(CompilerName::Boots)) {
+        return Err("--gc-concurrent-marking: not supported by compiler 'boots'".into());
+    }
+
     Ok(args)
 }
 
@@ -356,6 +445,16 @@ fn argument_usize(arg: &str) -> Result<usize, String> {
     }
 }
 
+fn argument_u64(arg: &str) -> Result<u64, String> {
+    let idx = arg.find("=").expect("missing =");
+    let (name, value) = arg.split_at(idx);
+    let value = &value[1..];
+    match value.parse::<u64>() {
+        Ok(value) => Ok(value),
+        Err(_) => Err(format!("{}: invalid value '{}'", name, value)),
+    }
+}
+
 fn parse_mem_size(value: &str) -> Result<MemSize, String> {
     let suffix = if let Some(ch) = value.chars().last() {
         match ch {
@@ -389,9 +488,12 @@ pub fn create_vm_args(args: &Args) -> VmArgs {
     VmArgs {
         flag_emit_asm: args.flag_emit_asm.clone(),
         flag_emit_asm_file: args.flag_emit_asm_file,
+        flag_emit_asm_lines: args.flag_emit_asm_lines,
+        flag_asm_syntax: args.flag_asm_syntax,
         flag_emit_compiler: args.flag_emit_compiler,
         flag_emit_stubs: args.flag_emit_stubs,
         flag_enable_perf: args.flag_enable_perf,
+        flag_enable_gdb: args.flag_enable_gdb,
         flag_omit_bounds_check: args.flag_omit_bounds_check,
         flag_emit_debug: args.flag_emit_debug.clone(),
         flag_emit_debug_native: args.flag_emit_debug_native,
@@ -407,9 +509,14 @@ pub fn create_vm_args(args: &Args) -> VmArgs {
         flag_gc_verbose: args.flag_gc_verbose,
         flag_gc_dev_verbose: args.flag_gc_dev_verbose,
         flag_gc_verify: args.flag_gc_verify,
+        flag_gc_verify_heap: args.flag_gc_verify_heap,
+        flag_gc_concurrent_marking: args.flag_gc_concurrent_marking,
         flag_gc_worker: args.flag_gc_worker,
         flag_gc_young_size: args.flag_gc_young_size,
+        flag_gc_min_young_size: args.flag_gc_min_young_size,
+        flag_gc_max_young_size: args.flag_gc_max_young_size,
         flag_gc_semi_ratio: args.flag_gc_semi_ratio,
+        flag_gc_large_object_size: args.flag_gc_large_object_size,
         flag_gc: args.flag_gc,
         flag_compiler: args.flag_compiler,
         flag_min_heap_size: args.flag_min_heap_size,
@@ -418,5 +525,9 @@ pub fn create_vm_args(args: &Args) -> VmArgs {
         flag_readonly_size: args.flag_readonly_size,
         flag_disable_tlab: args.flag_disable_tlab,
         flag_disable_barrier: args.flag_disable_barrier,
+        flag_disable_bce: args.flag_disable_bce,
+        flag_hash_seed: args.flag_hash_seed,
+        flag_coverage: args.flag_coverage,
+        flag_coverage_file: args.flag_coverage_file.clone(),
     }
 }
@@ -1,13 +1,18 @@
-use std::fs::File;
+use std::collections::hash_map::DefaultHasher;
+use std::fs::{self, File};
+use std::hash::{Hash, Hasher};
 use std::io::{self, Read, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::time::Instant;
 
 use crate::driver::cmd::{self, Args};
-use dora_bytecode::{FunctionData, FunctionId, PackageId, Program};
+use dora_bytecode::{
+    decode_module, encode_module, verify, FunctionData, FunctionId, PackageId, Program,
+    MODULE_VERSION,
+};
 use dora_frontend::language;
 use dora_frontend::language::sem_analysis::{SemAnalysis, SemAnalysisArgs};
-use dora_runtime::{clear_vm, display_fct, execute_on_main, set_vm, VM};
+use dora_runtime::{clear_vm, display_fct, execute_on_main, set_vm, TestExit, Trap, VM};
 
 pub fn start() -> i32 {
     let args = cmd::parse_arguments();
@@ -45,6 +50,13 @@ pub fn start() -> i32 {
                 return 1;
             }
         }
+    } else if let Some(ref cache_file) = args.flag_bytecode_cache {
+        match compile_with_bytecode_cache(&args, file, cache_file) {
+            Ok(result) => result,
+            Err(_) => {
+                return 1;
+            }
+        }
     } else {
         match compile_into_program(&args, file) {
             Ok(result) => result,
@@ -96,9 +108,13 @@ pub fn start() -> i32 {
 
     vm.threads.join_all();
 
+    if vm.args.coverage() {
+        vm.dump_coverage();
+    }
+
     if vm.args.flag_gc_stats {
         let duration = timer.expect("missing timer").elapsed();
-        vm.dump_gc_summary(duration.as_secs_f32() / 1000f32);
+        vm.dump_gc_summary(duration.as_secs_f32() * 1000f32);
     }
 
     clear_vm();
@@ -118,6 +134,8 @@ fn compile_into_program(args: &Args, file: String) -> Result<Program, ()> {
     let success = language::check(&mut sa);
     assert_eq!(success, !sa.diag.lock().has_errors());
 
+    sa.diag.lock().dump_warnings(&sa);
+
     if report_errors(&sa) {
         return Err(());
     }
@@ -132,10 +150,18 @@ fn compile_into_program(args: &Args, file: String) -> Result<Program, ()> {
 
     language::generate_bytecode(&sa);
 
+    if let Some(ref filter) = args.flag_explain_desugar {
+        language::explain_desugar(&sa, filter);
+    }
+
     if let Some(ref filter) = args.flag_emit_bytecode {
         language::emit_bytecode(&sa, filter);
     }
 
+    if let Some(ref filter) = args.flag_emit_bytecode_json {
+        language::emit_bytecode_json(&sa, filter);
+    }
+
     // Create a serializable data structure from bytecode and metadata.
     // Here we drop the generated AST.
     let prog = language::emit_program(sa);
@@ -143,6 +169,110 @@ fn compile_into_program(args: &Args, file: String) -> Result<Program, ()> {
     Ok(prog)
 }
 
+fn compile_with_bytecode_cache(args: &Args, file: String, cache_file: &str) -> Result<Program, ()> {
+    let source_hash = match hash_sources(args, &file) {
+        Ok(hash) => hash,
+        Err(_) => {
+            eprintln!("couldn't read input file.");
+            return Err(());
+        }
+    };
+
+    // Flags that emit frontend output need the frontend to run.
+    let needs_frontend = args.flag_emit_ast.is_some()
+        || args.flag_emit_bytecode.is_some()
+        || args.flag_emit_bytecode_json.is_some()
+        || args.flag_explain_desugar.is_some();
+
+    if !needs_frontend {
+        if let Ok(encoded) = read_input_file(cache_file) {
+            if let Ok(module) = decode_module(&encoded) {
+                if module.source_hash == source_hash && verify_program(&module.program) {
+                    return Ok(module.program);
+                }
+            }
+        }
+    }
+
+    let mut prog = compile_into_program(args, file)?;
+    let encoded = encode_module(&mut prog, source_hash);
+
+    // A cache that can't be written only costs recompilation next time.
+    if write_program_into_file(&encoded, cache_file).is_err() {
+        eprintln!("couldn't write bytecode cache {}.", cache_file);
+    }
+
+    Ok(prog)
+}
+
+// A cached program is only used if the bytecode of all its functions
+// passes the verifier, a corrupted cache is recompiled instead.
+fn verify_program(prog: &Program) -> bool {
+    prog.functions
+        .iter()
+        .filter_map(|fct| fct.bytecode.as_ref())
+        .all(|bc| verify(bc).is_ok())
+}
+
+// Hashes everything the compiled program depends on: the input file or
+// directory, all packages, the bundled standard library, the format version
+// and the dora build itself, since the bytecode refers to intrinsics and
+// native functions of the build that created it.
+fn hash_sources(args: &Args, file: &str) -> Result<u64, io::Error> {
+    let mut hasher = DefaultHasher::new();
+    MODULE_VERSION.hash(&mut hasher);
+    hash_build(&mut hasher);
+
+    for (name, content) in dora_frontend::STDLIB {
+        name.hash(&mut hasher);
+        content.hash(&mut hasher);
+    }
+
+    hash_path(Path::new(file), &mut hasher)?;
+
+    for (name, path) in &args.packages {
+        name.hash(&mut hasher);
+        hash_path(path, &mut hasher)?;
+    }
+
+    Ok(hasher.finish())
+}
+
+// Identifies the running dora binary by its version, size and
+// modification time. Hashing the whole executable would be too slow.
+fn hash_build(hasher: &mut DefaultHasher) {
+    env!("CARGO_PKG_VERSION").hash(hasher);
+
+    let metadata = std::env::current_exe().and_then(fs::metadata);
+
+    if let Ok(metadata) = metadata {
+        metadata.len().hash(hasher);
+
+        if let Ok(modified) = metadata.modified() {
+            modified.hash(hasher);
+        }
+    }
+}
+
+fn hash_path(path: &Path, hasher: &mut DefaultHasher) -> Result<(), io::Error> {
+    path.hash(hasher);
+
+    if path.is_dir() {
+        let mut entries = fs::read_dir(path)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<Vec<_>, io::Error>>()?;
+        entries.sort();
+
+        for entry in entries {
+            hash_path(&entry, hasher)?;
+        }
+    } else {
+        fs::read(path)?.hash(hasher);
+    }
+
+    Ok(())
+}
+
 fn command_build(args: &Args, prog: Program) -> i32 {
     if args.flag_output.is_none() {
         eprintln!("missing output file");
@@ -244,9 +374,20 @@ fn run_tests(vm: &VM, args: &Args, package_id: PackageId) -> i32 {
 
             print!("test {} ... ", fct.name);
 
-            run_test(vm, fct_id);
-            passed += 1;
-            println!("ok");
+            if let Some(ref expected_trap) = fct.expected_trap {
+                match run_test_expecting_trap(vm, fct_id, expected_trap) {
+                    Ok(()) => {
+                        passed += 1;
+                        println!("ok");
+                    }
+
+                    Err(msg) => println!("failed ({})", msg),
+                }
+            } else {
+                run_test(vm, fct_id);
+                passed += 1;
+                println!("ok");
+            }
         }
     });
 
@@ -269,6 +410,25 @@ fn run_test(vm: &VM, fct: FunctionId) {
     vm.run_test(fct);
 }
 
+fn run_test_expecting_trap(vm: &VM, fct: FunctionId, expected: &str) -> Result<(), String> {
+    let expected = match Trap::from_name(expected) {
+        Some(trap) => trap,
+        None => return Err(format!("unknown trap {}", expected)),
+    };
+
+    match vm.run_test_catching_trap(fct) {
+        TestExit::Trap(trap) if trap == expected => Ok(()),
+        TestExit::Trap(trap) => Err(format!(
+            "expected trap {} but got {}",
+            expected.name(),
+            trap.name()
+        )),
+        TestExit::Finished => Err(format!("expected trap {}", expected.name())),
+        TestExit::Abnormal(status) => Err(format!("exited with status {}", status)),
+        TestExit::Unsupported => Err("expecting a trap is not supported on this platform".into()),
+    }
+}
+
 fn is_test_fct(fct: &FunctionData) -> bool {
     // the function needs to be marked with the @Test annotation
     fct.is_test
@@ -299,3 +459,54 @@ fn run_main(vm: &VM, main: FunctionId) -> i32 {
         res
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dora_bytecode::{BytecodeType, BytecodeTypeArray};
+
+    #[test]
+    fn test_compile_function_ahead_of_time() {
+        let code = "
+            fn twice[T: std::Add](x: T): T { x + x }
+            fn main(): Int32 {
+                if twice[Int32](21i32) == 42i32 && twice[Float64](1.25) == 2.5 { 0i32 } else { 1i32 }
+            }
+        ";
+
+        let mut sa = SemAnalysis::new(SemAnalysisArgs::for_test(code));
+        assert!(language::check(&mut sa));
+        language::generate_bytecode(&sa);
+        let prog = language::emit_program(sa);
+
+        let twice_id = prog
+            .functions
+            .iter()
+            .position(|fct| fct.package_id == prog.program_package_id && fct.name == "twice")
+            .map(|idx| FunctionId(idx as u32))
+            .expect("twice missing");
+        let main_id = prog.main_fct_id.expect("main missing");
+
+        let vm = VM::new(prog, cmd::create_vm_args(&Args::default()), Vec::new());
+        set_vm(&vm);
+
+        let result = execute_on_main(|| {
+            let int32 = BytecodeTypeArray::one(BytecodeType::Int32);
+            let float64 = BytecodeTypeArray::one(BytecodeType::Float64);
+
+            let (int32_start, int32_size) = vm.compile_function(twice_id, &int32);
+            let (float64_start, float64_size) = vm.compile_function(twice_id, &float64);
+
+            assert_ne!(int32_start, float64_start);
+            assert!(int32_size > 0 && float64_size > 0);
+            assert_eq!(vm.compile_function(twice_id, &int32).0, int32_start);
+
+            vm.run(main_id)
+        });
+
+        vm.threads.join_all();
+        clear_vm();
+
+        assert_eq!(result, 0);
+    }
+}
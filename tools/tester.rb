@@ -638,6 +638,7 @@ def parse_test_file(file)
         when "oom" then test_case.expectation.code = 106
         when "stack-overflow" then test_case.expectation.code = 107
         when "overflow" then test_case.expectation.code = 109
+        when "exception" then test_case.expectation.code = 110
         when "fail"
           # do nothing
         else